@@ -1,7 +1,7 @@
 use parquet::errors::ParquetError;
 use quick_error::quick_error;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::result;
 
 quick_error! {
@@ -21,23 +21,109 @@ quick_error! {
         IO(err: String) {
             display("IO error: {}", err)
             description("IO error")
-            from(e: io::Error) -> (format!("{}", e))
+        }
+        /// Output closed before all rows were written, eg: `xpq read | head`.
+        BrokenPipe {
+            display("Broken pipe")
+            description("Broken pipe")
         }
         CSV(err: String) {
             display("CSV error: {}", err)
             description("CSV error")
-            from(e: csv::Error) -> (format!("{}", e))
+        }
+        Arrow(err: String) {
+            display("Arrow error: {}", err)
+            description("Arrow error")
+            from(e: arrow::error::ArrowError) -> (format!("{}", e))
+        }
+        JSON(err: String) {
+            display("JSON error: {}", err)
+            description("JSON error")
         }
         Filter(err: String) {
             display("Filter error: {}", err)
             description("Filter error")
             from(e: regex::Error) -> (format!("{}", e))
         }
+        Script(err: String) {
+            display("Script error: {}", err)
+            description("Script error")
+            from(e: Box<rhai::EvalAltResult>) -> (format!("{}", e))
+            from(e: rhai::ParseError) -> (format!("{}", e))
+        }
         /// Invalid argument error.
         InvalidArgument(name: String) {
             display("Invalid argument: {}", name)
             description("Invalid argument")
         }
+        /// Invalid config file.
+        Config(err: String) {
+            display("Config error: {}", err)
+            description("Config error")
+        }
+        /// Files checked by `verify` are corrupt.
+        CorruptFiles(num: usize) {
+            display("Corrupt files: {}", num)
+            description("Corrupt files")
+        }
+        /// Schemas compared by `schema-diff` are different.
+        SchemaMismatch(changes: usize) {
+            display("Schemas differ: {} changed fields", changes)
+            description("Schemas differ")
+        }
+        /// Rows checked by `check` have violations.
+        CheckViolations(num: usize) {
+            display("Check violations: {}", num)
+            description("Check violations")
+        }
+    }
+}
+
+impl Error {
+    /// Errors reporting what a command found once its output is written in full,
+    /// eg: the changes found by `schema-diff`.
+    pub fn is_finding(&self) -> bool {
+        matches!(
+            self,
+            Error::CorruptFiles(_) | Error::SchemaMismatch(_) | Error::CheckViolations(_)
+        )
+    }
+
+    /// Path and error of a file that could not be read, eg: a corrupt footer.
+    pub fn unreadable_file(&self) -> Option<(&Path, String)> {
+        match self {
+            Error::Parquet(path, e) => Some((path.as_path(), e.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::BrokenPipe => Error::BrokenPipe,
+            _ => Error::IO(format!("{}", e)),
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        match e.kind() {
+            csv::ErrorKind::Io(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                Error::BrokenPipe
+            }
+            _ => Error::CSV(format!("{}", e)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        match e.io_error_kind() {
+            Some(io::ErrorKind::BrokenPipe) => Error::BrokenPipe,
+            _ => Error::JSON(format!("{}", e)),
+        }
     }
 }
 
@@ -50,6 +136,8 @@ pub(crate) mod tests {
     extern crate tempfile;
 
     use self::tempfile::{Builder, NamedTempFile, TempDir};
+    use super::Error;
+    use std::io;
     use std::iter;
     use std::sync::Arc;
     use std::{fs, path::Path};
@@ -57,10 +145,25 @@ pub(crate) mod tests {
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
     use parquet_derive::ParquetRecordWriter;
 
+    use parquet::arrow::ArrowWriter;
     use parquet::file::properties::WriterProperties;
     use parquet::file::writer::SerializedFileWriter;
     use parquet::record::RecordWriter;
 
+    #[test]
+    fn test_broken_pipe_error() {
+        let broken = || io::Error::new(io::ErrorKind::BrokenPipe, "closed");
+        let other = io::Error::other("other");
+
+        assert_eq!(Error::from(broken()), Error::BrokenPipe);
+        assert_eq!(Error::from(csv::Error::from(broken())), Error::BrokenPipe);
+        assert_eq!(
+            Error::from(serde_json::Error::io(broken())),
+            Error::BrokenPipe
+        );
+        assert_eq!(Error::from(other), Error::IO(String::from("other")));
+    }
+
     #[derive(Debug, ParquetRecordWriter)]
     pub struct SimpleMessage {
         pub field_int32: i32,
@@ -118,15 +221,40 @@ pub(crate) mod tests {
     }
 
     pub fn write_simple_messages_parquet(path: &Path, vec: &[SimpleMessage]) {
-        let schema = vec.schema().unwrap();
+        write_simple_messages_row_groups(path, &[vec]);
+    }
+
+    pub fn write_simple_messages_row_groups(path: &Path, groups: &[&[SimpleMessage]]) {
+        let schema = groups[0].schema().unwrap();
         let props = Arc::new(WriterProperties::builder().build());
         let file = fs::File::create(path).unwrap();
         let mut writer = SerializedFileWriter::new(file, schema, props).unwrap();
-        let mut row_group = writer.next_row_group().unwrap();
 
-        vec.write_to_row_group(&mut row_group).unwrap();
+        for vec in groups {
+            let mut row_group = writer.next_row_group().unwrap();
+
+            vec.write_to_row_group(&mut row_group).unwrap();
+
+            row_group.close().unwrap();
+        }
+
+        writer.close().unwrap();
+    }
+
+    /// Write json lines to parquet, using the inferred arrow schema.
+    pub fn write_json_parquet(path: &Path, lines: &[&str]) {
+        let json = lines.join("\n");
+        let reader = arrow::json::ReaderBuilder::new()
+            .infer_schema(None)
+            .build(std::io::Cursor::new(json))
+            .unwrap();
+        let file = fs::File::create(path).unwrap();
+        let mut writer = ArrowWriter::try_new(file, reader.schema(), None).unwrap();
+
+        for batch in reader {
+            writer.write(&batch.unwrap()).unwrap();
+        }
 
-        row_group.close().unwrap();
         writer.close().unwrap();
     }
 }
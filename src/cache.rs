@@ -0,0 +1,402 @@
+use crate::api::Result;
+use crate::hash;
+use crate::reader::{Discovery, ParquetFile};
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tempfile::NamedTempFile;
+
+/// Schema hash and number of rows of a parquet file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub schema_hash: String,
+    pub num_rows: usize,
+}
+
+/// Size and modification time of a file, in nanoseconds since the epoch.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stamp {
+    pub size: u64,
+    pub modified: u128,
+}
+
+impl Stamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+        Some(Self {
+            size: metadata.len(),
+            modified: modified.as_nanos(),
+        })
+    }
+}
+
+/// Fingerprints of the files of a directory, stored as one tab separated line per file
+/// keyed by the path relative to the directory.
+///
+/// Entries are only used while the size and modification time of the file match,
+/// files not looked up are dropped when the cache is saved.
+#[derive(Debug, Default)]
+pub struct FileCache {
+    path: PathBuf,
+    root: PathBuf,
+    entries: HashMap<PathBuf, (Stamp, Fingerprint)>,
+    seen: HashMap<PathBuf, (Stamp, Fingerprint)>,
+    dirty: bool,
+}
+
+impl FileCache {
+    /// Load the cache file, a missing or invalid cache is empty.
+    pub fn load(path: PathBuf) -> Self {
+        let source = fs::read_to_string(&path).unwrap_or_default();
+        let entries = source.lines().filter_map(parse_line).collect();
+
+        Self {
+            path,
+            entries,
+            ..Self::default()
+        }
+    }
+
+    /// Cache of a directory in the user cache directory,
+    /// `None` when the cache is disabled with `XPQ_NO_CACHE`.
+    pub fn open(dir: &Path) -> Option<Self> {
+        if env::var_os("XPQ_NO_CACHE")
+            .filter(|v| !v.is_empty())
+            .is_some()
+        {
+            return None;
+        }
+
+        let canonical = dir.canonicalize().ok()?;
+        let key = hash::sha256(canonical.to_string_lossy().as_bytes());
+        let name = format!("{}.tsv", &hash::to_hex(&key)[..16]);
+        let cache = Self::load(cache_dir()?.join("files").join(name));
+
+        Some(Self {
+            root: dir.to_path_buf(),
+            ..cache
+        })
+    }
+
+    /// Fingerprint of a file, computed and stored when missing or outdated.
+    pub fn get<F>(&mut self, path: &Path, compute: F) -> Result<Fingerprint>
+    where
+        F: FnOnce() -> Result<Fingerprint>,
+    {
+        let stamp = match Stamp::of(path) {
+            Some(stamp) => stamp,
+            None => return compute(),
+        };
+
+        let key = path.strip_prefix(&self.root).unwrap_or(path).to_path_buf();
+        let fingerprint = match self.entries.remove(&key) {
+            Some((cached, fingerprint)) if cached == stamp => fingerprint,
+            _ => {
+                self.dirty = true;
+                compute()?
+            }
+        };
+
+        self.seen.insert(key, (stamp, fingerprint.clone()));
+
+        Ok(fingerprint)
+    }
+
+    /// Write the looked up entries, replacing the cache file atomically.
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty && self.entries.is_empty() {
+            return Ok(());
+        }
+
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+
+        fs::create_dir_all(dir)?;
+
+        let mut file = NamedTempFile::new_in(dir)?;
+        let mut lines = self.seen.iter().collect::<Vec<_>>();
+
+        lines.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (path, (stamp, fingerprint)) in lines {
+            writeln!(
+                file,
+                "{}\t{}\t{}\t{}\t{}",
+                stamp.size,
+                stamp.modified,
+                fingerprint.num_rows,
+                fingerprint.schema_hash,
+                path.display()
+            )?;
+        }
+
+        file.persist(&self.path).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
+
+/// Size of the largest output kept by a `ResultCache`.
+pub const MAX_RESULT_SIZE: usize = 1024 * 1024;
+
+/// Output of commands stored in a directory, one file per command line.
+///
+/// Outputs are keyed by the arguments, the rendering settings and the size and
+/// modification time of every input file, adding, removing or changing a file is
+/// a cache miss.
+#[derive(Debug)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Key of a command line reading the files of every path found by `discovery`,
+    /// `None` when a file can not be stamped, eg: remote files.
+    ///
+    /// `settings` holds what the output depends on besides the arguments,
+    /// eg: colors, table layout and config presets.
+    pub fn key<P: AsRef<Path>>(
+        args: &[OsString],
+        settings: &str,
+        paths: &[P],
+        discovery: &Discovery,
+    ) -> Option<String> {
+        let mut bytes = Vec::new();
+
+        for arg in args {
+            bytes.extend_from_slice(arg.to_string_lossy().as_bytes());
+            bytes.push(0);
+        }
+
+        bytes.extend_from_slice(settings.as_bytes());
+        bytes.push(0);
+
+        let files = paths.iter().flat_map(|p| {
+            ParquetFile::from(p.as_ref())
+                .with_discovery(discovery.clone())
+                .files()
+        });
+
+        for path in files {
+            let stamp = Stamp::of(&path)?;
+
+            bytes.extend_from_slice(
+                format!("{}\t{}\t{}\n", stamp.size, stamp.modified, path.display())
+                    .as_bytes(),
+            );
+        }
+
+        Some(hash::to_hex(&hash::sha256(&bytes)))
+    }
+
+    #[inline]
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.out", key))
+    }
+
+    /// Stored output of a key, if any.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        fs::read(self.path(key)).ok()
+    }
+
+    /// Store the output of a key, replacing the file atomically.
+    ///
+    /// Outputs larger than `MAX_RESULT_SIZE` are not stored.
+    pub fn put(&self, key: &str, output: &[u8]) -> Result<()> {
+        if output.len() > MAX_RESULT_SIZE {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+
+        let mut file = NamedTempFile::new_in(&self.dir)?;
+
+        file.write_all(output)?;
+        file.persist(self.path(key)).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn parse_line(line: &str) -> Option<(PathBuf, (Stamp, Fingerprint))> {
+    let mut parts = line.splitn(5, '\t');
+    let size = parts.next()?.parse().ok()?;
+    let modified = parts.next()?.parse().ok()?;
+    let num_rows = parts.next()?.parse().ok()?;
+    let schema_hash = parts.next()?.to_string();
+    let path = PathBuf::from(parts.next()?);
+
+    Some((
+        path,
+        (
+            Stamp { size, modified },
+            Fingerprint {
+                schema_hash,
+                num_rows,
+            },
+        ),
+    ))
+}
+
+/// Cache directory, `$XPQ_CACHE_DIR` or `xpq` in the user cache directory.
+fn cache_dir() -> Option<PathBuf> {
+    let var = |name| {
+        env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    var("XPQ_CACHE_DIR").or_else(|| {
+        var("XDG_CACHE_HOME")
+            .or_else(|| var("HOME").map(|h| h.join(".cache")))
+            .map(|d| d.join("xpq"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::cell::Cell;
+
+    fn fingerprint(num_rows: usize) -> Fingerprint {
+        Fingerprint {
+            schema_hash: String::from("abc"),
+            num_rows,
+        }
+    }
+
+    #[test]
+    fn test_file_cache() {
+        let dir = api::tests::temp_dir();
+        let cache_path = dir.path().join("cache").join("files.tsv");
+        let file = dir.path().join("1.parquet");
+        let calls = Cell::new(0);
+        let compute = |n| {
+            calls.set(calls.get() + 1);
+            Ok(fingerprint(n))
+        };
+
+        fs::write(&file, "data").unwrap();
+
+        let mut cache = FileCache::load(cache_path.clone());
+
+        assert_eq!(cache.get(&file, || compute(1)), Ok(fingerprint(1)));
+        assert!(cache.save().is_ok());
+
+        let mut cache = FileCache::load(cache_path.clone());
+
+        assert_eq!(cache.get(&file, || compute(2)), Ok(fingerprint(1)));
+        assert_eq!(calls.get(), 1);
+
+        fs::write(&file, "changed").unwrap();
+
+        let mut cache = FileCache::load(cache_path);
+
+        assert_eq!(cache.get(&file, || compute(3)), Ok(fingerprint(3)));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_file_cache_parse_line() {
+        assert_eq!(
+            parse_line("4\t10\t2\tabc\t/data/a b.parquet"),
+            Some((
+                PathBuf::from("/data/a b.parquet"),
+                (
+                    Stamp {
+                        size: 4,
+                        modified: 10
+                    },
+                    fingerprint(2)
+                )
+            ))
+        );
+        assert_eq!(parse_line("4\tx\t2\tabc\t/a.parquet"), None);
+        assert_eq!(parse_line("4\t10\t2"), None);
+    }
+
+    #[test]
+    fn test_result_cache() {
+        let dir = api::tests::temp_dir();
+        let file = dir.path().join("1.parquet");
+        let cache = ResultCache::new(dir.path().join("results"));
+        let args = vec![OsString::from("count"), OsString::from("1.parquet")];
+        let key = |args: &[OsString]| {
+            ResultCache::key(args, "", &[&file], &Discovery::default())
+        };
+
+        fs::write(&file, b"1").unwrap();
+
+        let first = key(&args).unwrap();
+
+        assert_eq!(cache.get(&first), None);
+
+        cache.put(&first, b"count\n1\n").unwrap();
+
+        assert_eq!(key(&args), Some(first.clone()));
+        assert_eq!(cache.get(&first), Some(b"count\n1\n".to_vec()));
+        assert_ne!(key(&args[..1]), Some(first.clone()));
+        assert_ne!(
+            ResultCache::key(&args, "Color::Always", &[&file], &Discovery::default()),
+            Some(first.clone())
+        );
+
+        // a changed input is a different key
+        fs::write(&file, b"12").unwrap();
+
+        assert_ne!(key(&args), Some(first));
+        assert_eq!(
+            ResultCache::key(
+                &args,
+                "",
+                &["https://example.com/1.parquet"],
+                &Discovery::default()
+            ),
+            None
+        );
+
+        let large = vec![0; MAX_RESULT_SIZE + 1];
+
+        cache.put("large", &large).unwrap();
+
+        assert_eq!(cache.get("large"), None);
+    }
+
+    #[test]
+    fn test_result_cache_key_paths() {
+        let dir = api::tests::temp_dir();
+        let first = dir.path().join("1.parquet");
+        let second = dir.path().join("2");
+        let args = vec![OsString::from("stats")];
+
+        fs::write(&first, b"1").unwrap();
+        fs::create_dir(&second).unwrap();
+        fs::write(second.join("2.parquet"), b"2").unwrap();
+
+        let key = ResultCache::key(&args, "", &[&first, &second], &Discovery::default())
+            .unwrap();
+
+        assert_ne!(
+            ResultCache::key(&args, "", &[&first], &Discovery::default()),
+            Some(key.clone())
+        );
+
+        fs::write(second.join("2.parquet"), b"22").unwrap();
+
+        assert_ne!(
+            ResultCache::key(&args, "", &[&first, &second], &Discovery::default()),
+            Some(key)
+        );
+    }
+}
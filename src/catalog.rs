@@ -0,0 +1,288 @@
+use crate::api::{Error, Result};
+use arrow::datatypes::{DataType, Field, Schema};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Footer key holding the spark schema, where column comments are kept.
+pub const SPARK_SCHEMA_KEY: &str = "org.apache.spark.sql.parquet.row.metadata";
+
+/// Metadata keys of a column description, in order of preference.
+const DESCRIPTION_KEYS: &[&str] = &["description", "comment", "doc"];
+
+const OWNER_KEY: &str = "owner";
+
+/// Documentation of a column.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnDoc {
+    pub description: Option<String>,
+    pub owner: Option<String>,
+}
+
+impl ColumnDoc {
+    #[inline]
+    fn from_map<F>(get: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        Self {
+            description: DESCRIPTION_KEYS.iter().find_map(|k| get(k)),
+            owner: get(OWNER_KEY),
+        }
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.description.is_none() && self.owner.is_none()
+    }
+}
+
+/// Documentation of the columns of a schema, keyed by dotted column path, eg: `address.city`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Catalog {
+    columns: HashMap<String, ColumnDoc>,
+}
+
+impl Catalog {
+    #[inline]
+    fn insert(&mut self, column: String, doc: ColumnDoc) {
+        if !doc.is_empty() {
+            self.columns.insert(column, doc);
+        }
+    }
+
+    /// Descriptions and owners of the field metadata of an arrow schema,
+    /// as written by pyarrow, eg: `pa.field("id", pa.int64(), metadata={"description": ".."})`.
+    pub fn from_arrow(schema: &Schema) -> Self {
+        fn visit(catalog: &mut Catalog, prefix: &str, fields: &[Field]) {
+            for field in fields {
+                let path = format!("{}{}", prefix, field.name());
+                let metadata = field.metadata().cloned().unwrap_or_else(BTreeMap::new);
+
+                catalog.insert(
+                    path.clone(),
+                    ColumnDoc::from_map(|k| metadata.get(k).cloned()),
+                );
+
+                if let DataType::Struct(children) = field.data_type() {
+                    visit(catalog, &format!("{}.", path), children);
+                }
+            }
+        }
+
+        let mut catalog = Self::default();
+
+        visit(&mut catalog, "", schema.fields());
+
+        catalog
+    }
+
+    /// Column comments of a spark schema, see `SPARK_SCHEMA_KEY`.
+    pub fn from_spark(json: &str) -> Result<Self> {
+        fn visit(catalog: &mut Catalog, prefix: &str, kind: &Value) {
+            let fields = kind.get("fields").and_then(Value::as_array);
+
+            for field in fields.into_iter().flatten() {
+                let name = match field.get("name").and_then(Value::as_str) {
+                    Some(name) => format!("{}{}", prefix, name),
+                    None => continue,
+                };
+                let metadata = field.get("metadata");
+                let doc = ColumnDoc::from_map(|k| {
+                    metadata
+                        .and_then(|m| m.get(k))
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                });
+
+                catalog.insert(name.clone(), doc);
+
+                if let Some(kind) = field.get("type").filter(|t| t.is_object()) {
+                    visit(catalog, &format!("{}.", name), kind);
+                }
+            }
+        }
+
+        let mut catalog = Self::default();
+
+        visit(&mut catalog, "", &serde_json::from_str(json)?);
+
+        Ok(catalog)
+    }
+
+    /// Parse a yaml file mapping each column to its `description` and `owner`, eg:
+    ///
+    /// ```yaml
+    /// id:
+    ///   description: Unique user id
+    ///   owner: identity-team
+    /// "address.city":
+    ///   description: City of the billing address
+    /// ```
+    ///
+    /// Only this subset of yaml is supported, other attributes are ignored.
+    pub fn parse_yaml(source: &str) -> Result<Self> {
+        let mut catalog = Self::default();
+        let mut column: Option<(String, ColumnDoc)> = None;
+
+        for (i, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+                continue;
+            }
+
+            let invalid =
+                || Error::InvalidArgument(format!("Invalid line {}: {}", i + 1, line));
+            let (key, value) = trimmed.split_once(':').ok_or_else(invalid)?;
+            let (key, value) = (unquote(key.trim()), unquote(value.trim()));
+
+            if !line.starts_with(char::is_whitespace) {
+                if !value.is_empty() {
+                    return Err(invalid());
+                }
+
+                if let Some((name, doc)) = column.replace((key, ColumnDoc::default())) {
+                    catalog.insert(name, doc);
+                }
+
+                continue;
+            }
+
+            let doc = &mut column.as_mut().ok_or_else(invalid)?.1;
+
+            match key.as_str() {
+                "description" => doc.description = Some(value),
+                "owner" => doc.owner = Some(value),
+                _ => {}
+            }
+        }
+
+        if let Some((name, doc)) = column {
+            catalog.insert(name, doc);
+        }
+
+        Ok(catalog)
+    }
+
+    /// Add the documentation of another catalog, replacing the attributes it defines.
+    pub fn merge(&mut self, other: Catalog) {
+        for (column, doc) in other.columns {
+            let entry = self.columns.entry(column).or_default();
+
+            if doc.description.is_some() {
+                entry.description = doc.description;
+            }
+
+            if doc.owner.is_some() {
+                entry.owner = doc.owner;
+            }
+        }
+    }
+
+    /// Documentation of a column, names are compared without case when there is no exact match.
+    pub fn get(&self, column: &str) -> Option<&ColumnDoc> {
+        self.columns.get(column).or_else(|| {
+            self.columns
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(column))
+                .map(|(_, v)| v)
+        })
+    }
+}
+
+/// Value without its surrounding quotes, if any.
+#[inline]
+fn unquote(value: &str) -> String {
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+
+    if quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(description: Option<&str>, owner: Option<&str>) -> ColumnDoc {
+        ColumnDoc {
+            description: description.map(String::from),
+            owner: owner.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_catalog_parse_yaml() {
+        let catalog = Catalog::parse_yaml(
+            "# users\nid:\n  description: Unique user id\n  owner: 'identity'\n\n\
+             \"address.city\":\n  tags: pii\n  description: \"City: billing\"\nempty:\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            catalog.get("id"),
+            Some(&doc(Some("Unique user id"), Some("identity")))
+        );
+        assert_eq!(
+            catalog.get("ADDRESS.city"),
+            Some(&doc(Some("City: billing"), None))
+        );
+        assert_eq!(catalog.get("empty"), None);
+
+        assert!(Catalog::parse_yaml("id: 1").is_err());
+        assert!(Catalog::parse_yaml("  description: x").is_err());
+        assert!(Catalog::parse_yaml("id:\n  description").is_err());
+    }
+
+    #[test]
+    fn test_catalog_from_spark() {
+        let json = r#"{"type": "struct", "fields": [
+            {"name": "id", "type": "long", "metadata": {"comment": "User id"}},
+            {"name": "tags", "type": {"type": "array"}, "metadata": {}},
+            {"name": "address", "type": {"type": "struct", "fields": [
+                {"name": "city", "type": "string", "metadata": {"owner": "geo"}}
+            ]}, "metadata": {}}
+        ]}"#;
+        let catalog = Catalog::from_spark(json).unwrap();
+
+        assert_eq!(catalog.get("id"), Some(&doc(Some("User id"), None)));
+        assert_eq!(catalog.get("address.city"), Some(&doc(None, Some("geo"))));
+        assert_eq!(catalog.get("tags"), None);
+        assert!(Catalog::from_spark("{").is_err());
+    }
+
+    #[test]
+    fn test_catalog_from_arrow_and_merge() {
+        let metadata = |pairs: &[(&str, &str)]| {
+            Some(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            )
+        };
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false)
+                .with_metadata(metadata(&[("description", "User id"), ("owner", "ids")])),
+            Field::new(
+                "address",
+                DataType::Struct(vec![Field::new("city", DataType::Utf8, true)
+                    .with_metadata(metadata(&[("comment", "City")]))]),
+                true,
+            ),
+        ]);
+        let mut catalog = Catalog::from_arrow(&schema);
+
+        assert_eq!(catalog.get("id"), Some(&doc(Some("User id"), Some("ids"))));
+        assert_eq!(catalog.get("address.city"), Some(&doc(Some("City"), None)));
+        assert_eq!(catalog.get("address"), None);
+
+        catalog.merge(Catalog::parse_yaml("id:\n  owner: core\n").unwrap());
+
+        assert_eq!(catalog.get("id"), Some(&doc(Some("User id"), Some("core"))));
+    }
+}
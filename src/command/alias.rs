@@ -0,0 +1,123 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::config;
+use crate::output::OutputFormat;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::io::Write;
+
+#[inline]
+fn validate_name(value: &str) -> std::result::Result<(), String> {
+    let valid = !value.is_empty()
+        && !value.starts_with('-')
+        && value
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid alias name '{}'", value))
+    }
+}
+
+fn name_arg() -> Arg<'static> {
+    Arg::with_name("name")
+        .validator(validate_name)
+        .help("Alias name")
+        .required(true)
+        .index(1)
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("alias")
+        .about("Save, list and remove command aliases, eg: `xpq <alias> <path>`")
+        .subcommand(
+            SubCommand::with_name("save")
+                .about("Save an alias, built-in commands take precedence over aliases")
+                .arg(name_arg())
+                .arg(
+                    Arg::with_name("command")
+                        .help("Command line without the path, eg: 'read -c id -f csv'")
+                        .required(true)
+                        .index(2),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("remove")
+                .about("Remove an alias")
+                .arg(name_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("list").about("List aliases").arg(
+                Arg::with_name("format")
+                    .help("Output format")
+                    .possible_values(OutputFormat::values())
+                    .default_value("table")
+                    .long("format")
+                    .short('f'),
+            ),
+        )
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    match matches.subcommand() {
+        Some(("save", args)) => {
+            let name = args.value_of("name").unwrap_or_default();
+            let command = args.value_of("command").unwrap_or_default();
+
+            if config::split_args(command).is_empty() {
+                return Err(Error::InvalidArgument(String::from("command")));
+            }
+
+            config::update(&config::alias_key(name), Some(command))
+        }
+        Some(("remove", args)) => {
+            let name = args.value_of("name").unwrap_or_default();
+
+            config::update(&config::alias_key(name), None)
+        }
+        Some(("list", args)) => {
+            let format = args::output_format_value(args, "format")?;
+            let headers = vec![String::from("NAME"), String::from("COMMAND")];
+            let rows = config::get()?
+                .aliases()
+                .into_iter()
+                .map(|(name, command)| Ok(vec![name.to_string(), command.to_string()]))
+                .collect::<Vec<_>>();
+
+            let mut writer =
+                args::writer(matches, headers, rows.into_iter()).format(format);
+
+            writer.write(out)
+        }
+        _ => Err(Error::InvalidArgument(String::from("alias"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_validate_name() {
+        assert!(validate_name("failed-orders").is_ok());
+        assert!(validate_name("a_1").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("-a").is_err());
+        assert!(validate_name("a.b").is_err());
+        assert!(validate_name("a b").is_err());
+    }
+
+    #[test]
+    fn test_alias_save_requires_command() {
+        let mut output = Vec::new();
+        let args = def()
+            .get_matches_from_safe(vec!["alias", "save", "empty", " "])
+            .unwrap();
+
+        assert_eq!(
+            run(&args, &mut output),
+            Err(Error::InvalidArgument(String::from("command")))
+        );
+    }
+}
@@ -1,12 +1,39 @@
 use crate::api::{Error, Result};
-use crate::output::OutputFormat;
-use clap::ArgMatches;
+use crate::command::frequency::Transform;
+use crate::config::{self, Config};
+use crate::filter::{self, Expr, Operand, Operator, Predicate};
+use crate::group::ColumnGroup;
+use crate::http;
+use crate::output::{
+    self, BinaryFormat, Cell, Color, Layout, Measure, NestedFormat, OutputFormat,
+    OutputWriter, Overflow,
+};
+use crate::query::{self, OrderBy};
+use crate::reader::{self, Discovery};
+use crate::time::{TimeFormat, TimeZone};
+use crate::value::ValueFormat;
+use clap::{Arg, ArgMatches};
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::path::Path;
 use std::str;
 
+/// Whether the path exists locally or is a http(s) url.
+#[inline]
+fn path_exists(path: &Path) -> bool {
+    path == Path::new(reader::STDIN_PATH) || path.exists() || http::is_url(path)
+}
+
+/// Path of an argument value, `-` is the copy of stdin.
+#[inline]
+fn input_path(value: &str) -> Result<&Path> {
+    match value {
+        reader::STDIN_PATH => reader::stdin_path(),
+        _ => Ok(Path::new(value)),
+    }
+}
+
 /// Gets the value of a specific argument
 /// Converting the ArgMatches value to a Path reference.
 ///
@@ -15,17 +42,34 @@ use std::str;
 pub fn path_value<'a>(matches: &'a ArgMatches, name: &str) -> Result<&'a Path> {
     matches
         .value_of(name)
-        .map(Path::new)
-        .filter(|p| p.exists())
+        .filter(|p| path_exists(Path::new(p)))
         .ok_or_else(|| Error::InvalidArgument(name.to_string()))
+        .and_then(input_path)
+}
+
+/// Gets all values of a specific argument as path references.
+///
+/// If the option wasn't present or any path is invalid returns
+/// `crate::api::Error::InvalidArgument`.
+pub fn path_values<'a>(matches: &'a ArgMatches, name: &str) -> Result<Vec<&'a Path>> {
+    matches
+        .values_of(name)
+        .map(|v| v.collect::<Vec<_>>())
+        .filter(|v| v.iter().all(|p| path_exists(Path::new(p))))
+        .ok_or_else(|| Error::InvalidArgument(name.to_string()))?
+        .into_iter()
+        .map(input_path)
+        .collect()
 }
 
 /// Gets all values of a specific argument.
 ///
+/// Values starting with `@` are replaced by the columns of the config preset with that name.
+///
 /// If the option wasn't present `None` or `Some(crate::api::Error::InvalidArgument)` when
 /// invalid.
 pub fn string_values(matches: &ArgMatches, name: &str) -> Result<Option<Vec<String>>> {
-    matches
+    let values = matches
         .values_of(name)
         .map(|v| {
             v.flat_map(|s| s.split(','))
@@ -33,35 +77,496 @@ pub fn string_values(matches: &ArgMatches, name: &str) -> Result<Option<Vec<Stri
                 .collect::<Vec<_>>()
         })
         .or_else(|| Some(vec![]))
-        .map(|vec| Some(vec).filter(|v| !v.is_empty()))
-        .ok_or_else(|| Error::InvalidArgument(name.to_string()))
+        .ok_or_else(|| Error::InvalidArgument(name.to_string()))?;
+
+    let values = if values.iter().any(|v| v.starts_with('@')) {
+        let groups = if matches.try_contains_id("group").unwrap_or(false) {
+            group_values(matches, "group")?
+        } else {
+            vec![]
+        };
+
+        expand_presets(values, config::get()?, &groups)?
+    } else {
+        values
+    };
+
+    Ok(Some(values).filter(|v| !v.is_empty()))
 }
 
-/// Gets all values of a specific argument.
+/// Replace `@<name>` values by the columns of the preset,
+/// or the patterns of the `--group` or config group with that name.
+#[inline]
+fn expand_presets(
+    values: Vec<String>,
+    config: &Config,
+    groups: &[ColumnGroup],
+) -> Result<Vec<String>> {
+    let mut result = Vec::with_capacity(values.len());
+
+    for value in values {
+        let name = match value.strip_prefix('@') {
+            Some(name) => name,
+            None => {
+                result.push(value);
+                continue;
+            }
+        };
+
+        if let Some(group) = groups.iter().find(|g| g.name == name) {
+            result.extend(group.patterns.iter().cloned());
+        } else if let Some(columns) = config.preset(name) {
+            result.extend(columns.iter().cloned());
+        } else if let Some(group) = config.group(name) {
+            result.extend(group.patterns);
+        } else {
+            return Err(Error::InvalidArgument(format!(
+                "Unknown column preset '{}'",
+                name
+            )));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Gets all values of a specific argument as column groups, either `name=pattern,pattern`
+/// definitions or the name of a group of the config file.
+///
+/// If any value is invalid or an unknown group returns `crate::api::Error::InvalidArgument`.
+pub fn group_values(matches: &ArgMatches, name: &str) -> Result<Vec<ColumnGroup>> {
+    let mut result = Vec::new();
+
+    for value in matches.values_of(name).into_iter().flatten() {
+        let group = if value.contains('=') {
+            ColumnGroup::parse(value).ok_or_else(|| {
+                Error::InvalidArgument(format!("Invalid column group '{}'", value))
+            })?
+        } else {
+            config::get()?.group(value).ok_or_else(|| {
+                Error::InvalidArgument(format!("Unknown column group '{}'", value))
+            })?
+        };
+
+        result.push(group);
+    }
+
+    Ok(result)
+}
+
+/// Gets all values of a specific argument as column filters, eg: `name:^Ben` or `age>=30`.
+///
+/// If any value is invalid returns `crate::api::Error::InvalidArgument`.
+pub fn filter_values(matches: &ArgMatches, name: &str) -> Result<Vec<Expr>> {
+    let mut result = Vec::new();
+
+    for value in matches.values_of(name).into_iter().flatten() {
+        let (column, predicate) = filter::parse(value)?;
+
+        result.push(Expr::Column(column, predicate));
+    }
+
+    Ok(result)
+}
+
+/// Filter skipping the rows a `--search-not` matches, rows with a null value
+/// do not match the filter so they are kept.
+#[inline]
+fn search_not(expr: Expr) -> Expr {
+    match &expr {
+        Expr::Column(_, Predicate::Null | Predicate::NotNull) => {
+            Expr::Not(Box::new(expr))
+        }
+        Expr::Column(column, _) => {
+            let null = Expr::Column(column.clone(), Predicate::Null);
+
+            Expr::Or(vec![Expr::Not(Box::new(expr)), null])
+        }
+        _ => Expr::Not(Box::new(expr)),
+    }
+}
+
+/// Filter expression of the `search_args`, every `--search` and `--partition` has to match,
+/// no `--search-not` can match, at least one `--search-any` has to match
+/// and so does the `--where` clause.
+///
+/// If no filter was given `None`.
+pub fn search_value(matches: &ArgMatches) -> Result<Option<Expr>> {
+    let mut all = filter_values(matches, "search")?;
+    let not = filter_values(matches, "search-not")?;
+    let any = filter_values(matches, "search-any")?;
+
+    all.extend(filter_values(matches, "partition")?);
+    all.extend(not.into_iter().map(search_not));
+
+    if !any.is_empty() {
+        all.push(Expr::Or(any));
+    }
+
+    if let Some(clause) = matches.value_of("where") {
+        all.push(query::parse(clause)?);
+    }
+
+    all.extend(as_of_value(matches)?);
+
+    if all.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(Expr::And(all)))
+}
+
+/// Filter of the rows valid at the `--as-of` time, eg: SCD2 datasets,
+/// `--valid-from` is inclusive, `--valid-to` is exclusive and a null end is still valid.
+fn as_of_value(matches: &ArgMatches) -> Result<Option<Expr>> {
+    let as_of = match matches.value_of("as-of") {
+        Some(as_of) => as_of,
+        None => return Ok(None),
+    };
+    let compare = |column: &str, op| {
+        Expr::Column(
+            column.to_string(),
+            Predicate::Compare(op, Operand::new(as_of)),
+        )
+    };
+    let mut all = Vec::new();
+
+    if let Some(column) = matches.value_of("valid-from") {
+        all.push(compare(column, Operator::Le));
+    }
+
+    if let Some(column) = matches.value_of("valid-to") {
+        all.push(Expr::Or(vec![
+            Expr::Column(column.to_string(), Predicate::Null),
+            compare(column, Operator::Gt),
+        ]));
+    }
+
+    if all.is_empty() {
+        return Err(Error::InvalidArgument(String::from(
+            "--as-of requires --valid-from or --valid-to",
+        )));
+    }
+
+    Ok(Some(Expr::And(all)))
+}
+
+/// Row filter arguments, read using `search_value`.
+pub fn search_args() -> [Arg<'static>; 8] {
+    [
+        Arg::with_name("search")
+            .validator(validate_filter)
+            .help("Search columns, eg: name:^Ben or age>=30")
+            .takes_value(true)
+            .long("search")
+            .multiple(true)
+            .short('s'),
+        Arg::with_name("search-not")
+            .validator(validate_filter)
+            .help("Skip rows matching the filter, eg: status:^failed$")
+            .takes_value(true)
+            .long("search-not")
+            .multiple(true),
+        Arg::with_name("search-any")
+            .validator(validate_filter)
+            .help("Keep rows matching at least one of the filters, eg: a=1")
+            .takes_value(true)
+            .long("search-any")
+            .multiple(true),
+        Arg::with_name("partition")
+            .validator(validate_filter)
+            .help("Only read the hive partition directories matching the filter, eg: date=2024-05-01")
+            .takes_value(true)
+            .long("partition")
+            .multiple(true),
+        Arg::with_name("where")
+            .validator(validate_where)
+            .help(
+                "Filter rows with a SQL-like clause, eg: \"age > 30 AND country = 'BR'\"",
+            )
+            .takes_value(true)
+            .long("where")
+            .short('w'),
+        Arg::with_name("as-of")
+            .validator(validate_as_of)
+            .help("Keep the rows valid at a date or time, eg: --as-of 2024-06-01")
+            .takes_value(true)
+            .long("as-of"),
+        Arg::with_name("valid-from")
+            .help("Column with the start of the validity of a row, inclusive")
+            .requires("as-of")
+            .takes_value(true)
+            .long("valid-from"),
+        Arg::with_name("valid-to")
+            .help("Column with the end of the validity of a row, exclusive, null is still valid")
+            .requires("as-of")
+            .takes_value(true)
+            .long("valid-to"),
+    ]
+}
+
+/// Table rendering arguments of commands writing tables.
+pub fn table_args() -> [Arg<'static>; 7] {
+    [
+        Arg::with_name("wide")
+            .help("Do not elide columns to fit the terminal width")
+            .long("wide"),
+        Arg::with_name("max-columns")
+            .help("Max number of table columns, middle columns are elided")
+            .validator(validate_number)
+            .takes_value(true)
+            .long("max-columns"),
+        Arg::with_name("max-col-width")
+            .help("Max width of table cells, longer cells are truncated")
+            .validator(validate_col_width)
+            .takes_value(true)
+            .long("max-col-width"),
+        Arg::with_name("no-truncate")
+            .help("Write long table cells in full instead of truncating them")
+            .conflicts_with_all(&["max-col-width", "wrap"])
+            .long("no-truncate"),
+        Arg::with_name("wrap")
+            .help("Wrap long table cells onto the next lines instead of truncating them")
+            .long("wrap"),
+        Arg::with_name("layout")
+            .help("Measure table columns on the first rows or on every row")
+            .possible_values(Measure::values())
+            .takes_value(true)
+            .long("layout"),
+        Arg::with_name("color")
+            .help("Color tables: bold headers, dim nulls, numbers and search matches")
+            .possible_values(Color::values())
+            .takes_value(true)
+            .long("color"),
+    ]
+}
+
+/// Whether the output goes to the `--output` file instead of stdout.
+fn output_to_file(matches: &ArgMatches) -> bool {
+    matches.try_contains_id("output").unwrap_or(false)
+}
+
+/// Whether a flag is given, `false` when the command does not take it.
+#[inline]
+fn flag_value(matches: &ArgMatches, name: &str) -> bool {
+    matches.try_contains_id(name).unwrap_or(false)
+}
+
+/// Table layout from the `table_args`, limited to the terminal width unless
+/// `--wide` is given or the output is written to a file.
+///
+/// The defaults for commands not taking them.
+pub fn layout_value(matches: &ArgMatches) -> Layout {
+    let width = if flag_value(matches, "wide") || output_to_file(matches) {
+        None
+    } else {
+        output::terminal_width()
+    };
+    let overflow = if flag_value(matches, "wrap") {
+        Overflow::Wrap
+    } else if flag_value(matches, "no-truncate") {
+        Overflow::Full
+    } else {
+        Overflow::Truncate
+    };
+
+    Layout {
+        max_columns: optional_value(matches, "max-columns").and_then(|v| v.parse().ok()),
+        width,
+        max_col_width: optional_value(matches, "max-col-width")
+            .and_then(|v| v.parse().ok()),
+        overflow,
+        measure: optional_value(matches, "layout")
+            .and_then(|v| Measure::try_from(v).ok())
+            .unwrap_or_default(),
+    }
+}
+
+/// Colors of the tables from `--color`, following the environment when not given.
+///
+/// Outputs written to a file are only colored by `--color always`.
+pub fn color_value(matches: &ArgMatches) -> Color {
+    match optional_value(matches, "color").map(Color::try_from) {
+        Some(Ok(color)) => color,
+        _ if output_to_file(matches) => Color::Never,
+        _ => Color::from_env(),
+    }
+}
+
+/// First value of an arg, `None` when the command does not take it.
+fn optional_value<'a>(matches: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    matches
+        .try_get_raw(name)
+        .ok()
+        .flatten()
+        .and_then(|mut v| v.next())
+        .and_then(|v| v.to_str())
+}
+
+/// Rendering of the values from the `value_args`,
+/// the defaults for commands not taking them.
+pub fn value_format_value(matches: &ArgMatches) -> ValueFormat {
+    ValueFormat {
+        null_display: optional_value(matches, "null-display").map(String::from),
+        binary: optional_value(matches, "binary")
+            .and_then(|v| BinaryFormat::try_from(v).ok())
+            .unwrap_or_default(),
+        time: TimeFormat {
+            timezone: optional_value(matches, "timezone")
+                .and_then(|v| TimeZone::parse(v).ok())
+                .unwrap_or_default(),
+            format: optional_value(matches, "time-format").map(String::from),
+            unix: flag_value(matches, "time-unix"),
+        },
+    }
+}
+
+/// Writer of the rows of a command, rendered by its `table_args` and `value_args`
+/// or their defaults.
+pub fn writer<T, C>(
+    matches: &ArgMatches,
+    headers: Vec<String>,
+    values: T,
+) -> OutputWriter<T>
+where
+    T: Iterator<Item = Result<Vec<C>>>,
+    C: Cell,
+{
+    OutputWriter::new(headers, values)
+        .layout(layout_value(matches))
+        .color(color_value(matches))
+        .values(value_format_value(matches))
+}
+
+/// Value rendering arguments of commands writing the values of rows.
+pub fn value_args() -> [Arg<'static>; 5] {
+    [
+        Arg::with_name("null-display")
+            .help("Text written for null values, eg: --null-display '<null>'")
+            .takes_value(true)
+            .long("null-display"),
+        Arg::with_name("binary")
+            .help("Rendering of binary values")
+            .possible_values(BinaryFormat::values())
+            .takes_value(true)
+            .long("binary"),
+        Arg::with_name("timezone")
+            .help("Timezone of timestamps, eg: --timezone America/Sao_Paulo or -03:00")
+            .validator(validate_timezone)
+            .takes_value(true)
+            .long("timezone"),
+        Arg::with_name("time-format")
+            .help("strftime format of timestamps, eg: --time-format '%Y-%m-%dT%H:%M:%S'")
+            .validator(validate_time_format)
+            .takes_value(true)
+            .long("time-format"),
+        Arg::with_name("time-unix")
+            .help("Write timestamps as seconds since the unix epoch")
+            .conflicts_with_all(&["timezone", "time-format"])
+            .long("time-unix"),
+    ]
+}
+
+/// File discovery arguments of commands reading parquet directories.
+pub fn discovery_args() -> [Arg<'static>; 7] {
+    [
+        Arg::with_name("ext")
+            .help("File extensions read from directories, eg: --ext .pq,.parquet")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .long("ext"),
+        Arg::with_name("max-depth")
+            .help("Levels of directories descended, 1 reads the files of the directory only")
+            .validator(validate_number)
+            .takes_value(true)
+            .long("max-depth"),
+        Arg::with_name("follow-symlinks")
+            .help("Descend into symlinked directories")
+            .long("follow-symlinks"),
+        Arg::with_name("hidden")
+            .help("Read files and directories starting with . or _, eg: _temporary")
+            .long("hidden"),
+        Arg::with_name("include-files")
+            .help("Read the files matching a glob only, eg: --include-files 'part-*.parquet'")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .long("include-files"),
+        Arg::with_name("exclude-files")
+            .help("Skip the files and directories matching a glob, eg: --exclude-files 'scratch'")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .long("exclude-files"),
+        Arg::with_name("strict")
+            .help("Fail on the first unreadable file instead of skipping it with a warning")
+            .long("strict"),
+    ]
+}
+
+/// Files read from directories, from the `discovery_args`.
+pub fn discovery_value(matches: &ArgMatches) -> Discovery {
+    let values = |name| {
+        matches
+            .values_of(name)
+            .into_iter()
+            .flatten()
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+    let extensions = values("ext")
+        .into_iter()
+        .map(|e| {
+            if e.starts_with('.') {
+                e
+            } else {
+                format!(".{}", e)
+            }
+        })
+        .collect::<Vec<_>>();
+    let default = Discovery::default();
+
+    Discovery {
+        extensions: Some(extensions)
+            .filter(|e| !e.is_empty())
+            .unwrap_or(default.extensions),
+        max_depth: matches.value_of_t("max-depth").ok(),
+        follow_symlinks: matches.is_present("follow-symlinks"),
+        hidden: matches.is_present("hidden"),
+        include: values("include-files"),
+        exclude: values("exclude-files"),
+    }
+}
+
+/// Output cache argument of the commands with small outputs.
+pub fn cache_arg() -> Arg<'static> {
+    Arg::with_name("cache-dir")
+        .help("Reuse the output of the command while its files do not change")
+        .takes_value(true)
+        .long("cache-dir")
+}
+
+/// Gets all values of a specific argument
+/// Converting the ArgMatches values to `crate::command::frequency::Transform`.
 ///
 /// If the option wasn't present `None` or `Some(crate::api::Error::InvalidArgument)` when
 /// invalid.
-pub fn filter_values(
+pub fn transform_values(
     matches: &ArgMatches,
     name: &str,
-) -> Result<Option<HashMap<String, Regex>>> {
+) -> Result<Option<HashMap<String, Transform>>> {
     match matches.values_of(name) {
         Some(values) => {
             let mut result = HashMap::new();
-            let filters = values.map(String::from).collect::<Vec<_>>();
-
-            for entry in filters {
-                let filter = entry.as_str();
-                let parts = filter.splitn(2, ':').collect::<Vec<_>>();
 
-                if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
-                    return Err(Error::InvalidArgument(name.to_string()));
+            for entry in values {
+                match entry.split_once(':') {
+                    Some((field, transform)) if !field.is_empty() => {
+                        result.insert(field.to_string(), Transform::try_from(transform)?);
+                    }
+                    _ => return Err(Error::InvalidArgument(name.to_string())),
                 }
-
-                let field = String::from(parts[0]);
-                let regex = Regex::new(parts[1])?;
-
-                result.insert(field, regex);
             }
 
             Ok(Some(result))
@@ -70,6 +575,73 @@ pub fn filter_values(
     }
 }
 
+/// Parse an order by expression, `<column>[:asc|desc]`.
+#[inline]
+fn parse_order_by(value: &str) -> Option<OrderBy> {
+    let (column, descending) = match value.rsplit_once(':') {
+        Some((column, order)) if order.eq_ignore_ascii_case("desc") => (column, true),
+        Some((column, order)) if order.eq_ignore_ascii_case("asc") => (column, false),
+        _ => (value, false),
+    };
+
+    Some(OrderBy {
+        column: column.to_string(),
+        descending,
+    })
+    .filter(|o| !o.column.is_empty())
+}
+
+/// Gets the values of a specific argument
+/// Converting the ArgMatches values to `OrderBy` columns, comma separated values are split.
+///
+/// If the option wasn't present an empty vec or `crate::api::Error::InvalidArgument` when
+/// invalid.
+pub fn order_by_values(matches: &ArgMatches, name: &str) -> Result<Vec<OrderBy>> {
+    matches
+        .values_of(name)
+        .into_iter()
+        .flatten()
+        .flat_map(|v| v.split(','))
+        .map(|v| {
+            parse_order_by(v).ok_or_else(|| Error::InvalidArgument(name.to_string()))
+        })
+        .collect()
+}
+
+#[inline]
+fn parse_table(value: &str) -> Option<(String, String)> {
+    value
+        .split_once('=')
+        .filter(|(name, path)| !name.is_empty() && !path.is_empty())
+        .map(|(name, path)| (name.to_lowercase(), path.to_string()))
+}
+
+/// Gets the values of a specific argument
+/// Converting the ArgMatches `<name>=<path>` values to table paths by lowercase name.
+///
+/// If the option wasn't present an empty map or `crate::api::Error::InvalidArgument` when
+/// invalid.
+pub fn table_values(matches: &ArgMatches, name: &str) -> Result<HashMap<String, String>> {
+    matches
+        .values_of(name)
+        .into_iter()
+        .flatten()
+        .map(|v| parse_table(v).ok_or_else(|| Error::InvalidArgument(name.to_string())))
+        .collect()
+}
+
+/// Gets the value of a specific argument
+/// Converting the ArgMatches value to a `regex::Regex`.
+///
+/// If the option wasn't present `None` or `Some(crate::api::Error::Filter)` when
+/// invalid.
+pub fn regex_value(matches: &ArgMatches, name: &str) -> Result<Option<Regex>> {
+    match matches.value_of(name) {
+        Some(value) => Ok(Some(Regex::new(value)?)),
+        None => Ok(None),
+    }
+}
+
 /// Gets the value of a specific argument
 /// Converting the ArgMatches value to a usize.
 ///
@@ -84,6 +656,56 @@ pub fn usize_value(matches: &ArgMatches, name: &str) -> Result<usize> {
         .ok_or_else(|| Error::InvalidArgument(name.to_string()))
 }
 
+/// Parse a byte size with an optional unit, eg: `500`, `100MB` or `1GiB`.
+///
+/// Decimal units (`KB`, `MB`, `GB`) are powers of 1000, binary units (`KiB`, `MiB`, `GiB`)
+/// are powers of 1024.
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_ref() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000 * 1000,
+        "g" | "gb" => 1000 * 1000 * 1000,
+        "kib" => 1024,
+        "mib" => 1024 * 1024,
+        "gib" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// Gets the value of a specific argument
+/// Converting the ArgMatches value to a byte size.
+///
+/// If the option wasn't present or is invalid returns
+/// `crate::api::Error::InvalidArgument`.
+pub fn size_value(matches: &ArgMatches, name: &str) -> Result<u64> {
+    matches
+        .value_of(name)
+        .and_then(parse_size)
+        .ok_or_else(|| Error::InvalidArgument(name.to_string()))
+}
+
+/// Gets the value of a specific argument
+/// Converting the ArgMatches value to a f64.
+///
+/// If the option wasn't present or is invalid returns
+/// `crate::api::Error::InvalidArgument`.
+pub fn f64_value(matches: &ArgMatches, name: &str) -> Result<f64> {
+    matches
+        .value_of(name)
+        .map(str::parse)
+        .filter(std::result::Result::is_ok)
+        .map(std::result::Result::unwrap)
+        .ok_or_else(|| Error::InvalidArgument(name.to_string()))
+}
+
 /// Gets the value of a specific argument
 /// Converting the ArgMatches value to a `crate::output::OutputFormat`.
 ///
@@ -99,6 +721,20 @@ pub fn output_format_value(matches: &ArgMatches, name: &str) -> Result<OutputFor
         .ok_or_else(|| Error::InvalidArgument(name.to_string()))
 }
 
+/// Gets the value of a specific argument
+/// Converting the ArgMatches value to a `crate::output::NestedFormat`.
+///
+/// If the option wasn't present or is invalid returns
+/// `crate::api::Error::InvalidArgument`.
+pub fn nested_format_value(matches: &ArgMatches, name: &str) -> Result<NestedFormat> {
+    matches
+        .value_of(name)
+        .map(NestedFormat::try_from)
+        .filter(std::result::Result::is_ok)
+        .map(std::result::Result::unwrap)
+        .ok_or_else(|| Error::InvalidArgument(name.to_string()))
+}
+
 pub fn validate_number(value: &str) -> std::result::Result<(), String> {
     value
         .parse::<usize>()
@@ -106,28 +742,145 @@ pub fn validate_number(value: &str) -> std::result::Result<(), String> {
         .map_err(|err| err.to_string())
 }
 
+/// Column widths below the `...` marker and a character can not be truncated.
+pub fn validate_col_width(value: &str) -> std::result::Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(width) if width >= 4 => Ok(()),
+        _ => Err(format!("Expected a width of at least 4 got '{}'", value)),
+    }
+}
+
+pub fn validate_size(value: &str) -> std::result::Result<(), String> {
+    parse_size(value)
+        .filter(|size| *size > 0)
+        .map(|_| ())
+        .ok_or_else(|| format!("Invalid size '{}', eg: 500KB, 100MB or 1GiB", value))
+}
+
+pub fn validate_float(value: &str) -> std::result::Result<(), String> {
+    value
+        .parse::<f64>()
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+pub fn validate_fraction(value: &str) -> std::result::Result<(), String> {
+    value
+        .parse::<f64>()
+        .map_err(|err| err.to_string())
+        .and_then(|v| match v > 0.0 && v <= 1.0 {
+            true => Ok(()),
+            false => Err(format!("Fraction must be in (0, 1]. Got '{}'", value)),
+        })
+}
+
 pub fn validate_path(value: &str) -> std::result::Result<(), String> {
     Some(Path::new(&value))
-        .filter(|p| p.exists())
+        .filter(|p| path_exists(p))
         .map(|_| ())
         .ok_or_else(|| format!("Path '{}' does not exist", value))
 }
 
 pub fn validate_filter(value: &str) -> std::result::Result<(), String> {
-    Some(value)
-        .map(|s| {
-            s.splitn(2, ':')
-                .map(ToString::to_string)
-                .collect::<Vec<_>>()
+    filter::parse(value).map(|_| ()).map_err(|_| {
+        format!(
+            "Invalid filter expression. Expected '<column>:<regex>' or '<column><op><value>' got '{}'",
+            value
+        )
+    })
+}
+
+pub fn validate_as_of(value: &str) -> std::result::Result<(), String> {
+    if Operand::new(value).is_timestamp() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Invalid date. Expected '<yyyy-mm-dd>' or '<yyyy-mm-dd hh:mm:ss>' got '{}'",
+        value
+    ))
+}
+
+pub fn validate_where(value: &str) -> std::result::Result<(), String> {
+    query::parse(value).map(|_| ()).map_err(|e| match e {
+        Error::InvalidArgument(message) => format!("Invalid where clause. {}", message),
+        e => e.to_string(),
+    })
+}
+
+/// Subqueries are only parsed, they run with the query.
+pub fn validate_query(value: &str) -> std::result::Result<(), String> {
+    let mut subquery = |_: &query::Select| Ok(HashSet::new());
+
+    query::parse_select_with(value, &mut subquery)
+        .map(|_| ())
+        .map_err(|e| match e {
+            Error::InvalidArgument(message) => format!("Invalid query. {}", message),
+            e => e.to_string(),
         })
-        .filter(|s| s.len() == 2)
-        .filter(|s| !s[0].is_empty() && !s[1].is_empty())
-        .map(|s| Regex::new(&s[1]))
+}
+
+pub fn validate_timezone(value: &str) -> std::result::Result<(), String> {
+    TimeZone::parse(value)
+        .map(|_| ())
+        .map_err(|_| format!("Unknown timezone '{}'", value))
+}
+
+pub fn validate_time_format(value: &str) -> std::result::Result<(), String> {
+    TimeFormat::default()
+        .with_format(Some(value.to_string()))
+        .map(|_| ())
+        .map_err(|_| format!("Invalid time format '{}'", value))
+}
+
+pub fn validate_regex(value: &str) -> std::result::Result<(), String> {
+    Regex::new(value)
+        .map(|_| ())
+        .map_err(|_| format!("Invalid regex expression. Got '{}'", value))
+}
+
+pub fn validate_transform(value: &str) -> std::result::Result<(), String> {
+    value
+        .split_once(':')
+        .filter(|s| !s.0.is_empty())
+        .map(|s| Transform::try_from(s.1))
         .filter(std::result::Result::is_ok)
         .map(|_| ())
         .ok_or_else(|| {
             format!(
-                "Invalid filter expression. Expected '<column>:<regex>' got '{}'",
+                "Invalid transform expression. Expected '<column>:<transform>' got '{}'",
+                value
+            )
+        })
+}
+
+pub fn validate_table(value: &str) -> std::result::Result<(), String> {
+    parse_table(value)
+        .map(|_| ())
+        .ok_or_else(|| format!("Invalid table. Expected '<name>=<path>' got '{}'", value))
+}
+
+pub fn validate_order_by(value: &str) -> std::result::Result<(), String> {
+    if value.split(',').all(|v| parse_order_by(v).is_some()) {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Invalid order by expression. Expected '<column>[:desc]' got '{}'",
+        value
+    ))
+}
+
+pub fn validate_date_part(value: &str) -> std::result::Result<(), String> {
+    value
+        .split_once(':')
+        .filter(|s| !s.0.is_empty())
+        .map(|s| Transform::try_from(s.1))
+        .filter(|t| t.as_ref().map(Transform::is_date_part).unwrap_or(false))
+        .map(|_| ())
+        .ok_or_else(|| {
+            format!(
+                "Invalid date part expression. Expected '<column>:dow|hour|month' got '{}'",
                 value
             )
         })
@@ -164,14 +917,255 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_args_validate_regex() {
+        assert_eq!(Ok(()), validate_regex("[a-z]+"));
+        assert_eq!(
+            Err("Invalid regex expression. Got '[a-z'".to_string()),
+            validate_regex("[a-z")
+        );
+    }
+
+    #[test]
+    fn test_args_validate_float() {
+        assert_eq!(Ok(()), validate_float("1.5"));
+        assert_eq!(
+            Err("invalid float literal".to_string()),
+            validate_float("NOT VALID")
+        );
+    }
+
+    #[test]
+    fn test_args_parse_size() {
+        assert_eq!(parse_size("500"), Some(500));
+        assert_eq!(parse_size("100MB"), Some(100_000_000));
+        assert_eq!(parse_size("2 kib"), Some(2048));
+        assert_eq!(parse_size("1GiB"), Some(1024 * 1024 * 1024));
+        assert_eq!(parse_size("1TB"), None);
+        assert_eq!(parse_size("MB"), None);
+
+        assert_eq!(Ok(()), validate_size("10KB"));
+        assert!(validate_size("0").is_err());
+        assert!(validate_size("ten").is_err());
+    }
+
+    #[test]
+    fn test_args_validate_fraction() {
+        assert_eq!(Ok(()), validate_fraction("0.01"));
+        assert_eq!(Ok(()), validate_fraction("1"));
+        assert_eq!(
+            Err("Fraction must be in (0, 1]. Got '0'".to_string()),
+            validate_fraction("0")
+        );
+        assert_eq!(
+            Err("Fraction must be in (0, 1]. Got '1.5'".to_string()),
+            validate_fraction("1.5")
+        );
+        assert_eq!(
+            Err("invalid float literal".to_string()),
+            validate_fraction("NOT VALID")
+        );
+    }
+
+    #[test]
+    fn test_args_validate_where() {
+        assert_eq!(Ok(()), validate_where("age > 30 AND country = 'BR'"));
+        assert_eq!(
+            Err(
+                "Invalid where clause. Expected a value at position 6 of 'age > '"
+                    .to_string()
+            ),
+            validate_where("age > ")
+        );
+    }
+
+    #[test]
+    fn test_args_validate_query() {
+        assert_eq!(Ok(()), validate_query("SELECT a FROM 'b' WHERE a > 1"));
+        assert_eq!(
+            Err("Invalid query. Expected FROM at position 9 of 'SELECT a '".to_string()),
+            validate_query("SELECT a ")
+        );
+    }
+
+    #[test]
+    fn test_args_discovery_value() {
+        let app = App::new("ls").args(discovery_args());
+        let matches = app
+            .get_matches_from_safe(vec![
+                "ls",
+                "--ext",
+                "pq, parquet",
+                "--max-depth=1",
+                "--hidden",
+            ])
+            .unwrap();
+        let discovery = discovery_value(&matches);
+
+        assert_eq!(discovery.extensions, vec![".pq", ".parquet"]);
+        assert_eq!(discovery.max_depth, Some(1));
+        assert!(discovery.hidden);
+        assert!(!discovery.follow_symlinks);
+        assert_eq!(
+            discovery_value(
+                &App::new("ls")
+                    .args(discovery_args())
+                    .get_matches_from(vec!["ls"])
+            )
+            .extensions,
+            Discovery::default().extensions
+        );
+    }
+
+    #[test]
+    fn test_args_layout_value() {
+        let matches = App::new("read")
+            .args(table_args())
+            .get_matches_from_safe(vec![
+                "read",
+                "--max-columns=3",
+                "--wrap",
+                "--layout=exact",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            layout_value(&matches),
+            Layout {
+                max_columns: Some(3),
+                width: None,
+                max_col_width: None,
+                overflow: Overflow::Wrap,
+                measure: Measure::Exact,
+            }
+        );
+        assert_eq!(
+            layout_value(
+                &App::new("read")
+                    .args(table_args())
+                    .get_matches_from(vec!["read"])
+            ),
+            Layout::default()
+        );
+    }
+
+    #[test]
+    fn test_args_color_value() {
+        let color = |args: Vec<&str>| {
+            let app = App::new("read")
+                .args(table_args())
+                .arg(Arg::with_name("output").takes_value(true).long("output"));
+
+            color_value(&app.get_matches_from(args))
+        };
+
+        assert_eq!(color(vec!["read", "--color=always"]), Color::Always);
+        assert_eq!(color(vec!["read", "--output=out.txt"]), Color::Never);
+        assert_eq!(
+            color(vec!["read", "--output=out.txt", "--color=always"]),
+            Color::Always
+        );
+    }
+
+    #[test]
+    fn test_args_stdin_path() {
+        let file = api::tests::temp_file("msg", ".parquet");
+        let path = file.path().to_str().unwrap();
+        let matches = crate::command::read::def()
+            .get_matches_from_safe(["read", path, "--null-display", "-"])
+            .unwrap();
+
+        // only path arguments read stdin, other values are kept as given
+        assert_eq!(path_value(&matches, "path"), Ok(file.path()));
+        assert_eq!(value_format_value(&matches).null_display(), "-");
+        assert!(validate_path(reader::STDIN_PATH).is_ok());
+    }
+
+    #[test]
+    fn test_args_table_values() {
+        let matches = App::new("query")
+            .arg(
+                Arg::with_name("table")
+                    .takes_value(true)
+                    .multiple(true)
+                    .long("table"),
+            )
+            .get_matches_from_safe([
+                "query",
+                "--table",
+                "Events=data/events/",
+                "--table",
+                "users=a=b.parquet",
+            ])
+            .unwrap();
+        let tables = table_values(&matches, "table").unwrap();
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables["events"], "data/events/");
+        assert_eq!(tables["users"], "a=b.parquet");
+        assert_eq!(Ok(()), validate_table("a=b"));
+        assert_eq!(
+            Err("Invalid table. Expected '<name>=<path>' got 'a='".to_string()),
+            validate_table("a=")
+        );
+        assert!(validate_table("path").is_err());
+    }
+
+    #[test]
+    fn test_args_validate_query_subquery() {
+        assert_eq!(
+            Ok(()),
+            validate_query("SELECT a FROM b WHERE a IN (SELECT id FROM c)")
+        );
+    }
+
+    #[test]
+    fn test_args_order_by_values() {
+        let order = |column: &str, descending| OrderBy {
+            column: String::from(column),
+            descending,
+        };
+        let matches = App::new("read")
+            .arg(
+                Arg::with_name("order-by")
+                    .takes_value(true)
+                    .multiple(true)
+                    .long("order-by"),
+            )
+            .get_matches_from_safe([
+                "read",
+                "--order-by",
+                "a:desc,b",
+                "--order-by",
+                "c:ASC",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            order_by_values(&matches, "order-by"),
+            Ok(vec![order("a", true), order("b", false), order("c", false)])
+        );
+        assert_eq!(parse_order_by("ts:hour"), Some(order("ts:hour", false)));
+        assert_eq!(Ok(()), validate_order_by("a:desc,b"));
+        assert_eq!(
+            Err(
+                "Invalid order by expression. Expected '<column>[:desc]' got ':desc'"
+                    .to_string()
+            ),
+            validate_order_by(":desc")
+        );
+    }
+
     #[test]
     fn test_args_validate_filter() {
         assert_eq!(Ok(()), validate_filter("foo:bar"));
         assert_eq!(Ok(()), validate_filter("foo:^ns::[a-zA-Z]*$"));
+        assert_eq!(Ok(()), validate_filter("age>=30"));
+        assert_eq!(Ok(()), validate_filter("name!=foo"));
 
         assert_eq!(
             Err(
-                "Invalid filter expression. Expected '<column>:<regex>' got 'NOT VALID'"
+                "Invalid filter expression. Expected '<column>:<regex>' or '<column><op><value>' got 'NOT VALID'"
                     .to_string()
             ),
             validate_filter("NOT VALID")
@@ -179,7 +1173,7 @@ mod tests {
 
         assert_eq!(
             Err(
-                "Invalid filter expression. Expected '<column>:<regex>' got 'foo'"
+                "Invalid filter expression. Expected '<column>:<regex>' or '<column><op><value>' got 'foo'"
                     .to_string()
             ),
             validate_filter("foo")
@@ -187,7 +1181,7 @@ mod tests {
 
         assert_eq!(
             Err(
-                "Invalid filter expression. Expected '<column>:<regex>' got 'bar:'"
+                "Invalid filter expression. Expected '<column>:<regex>' or '<column><op><value>' got 'bar:'"
                     .to_string()
             ),
             validate_filter("bar:")
@@ -195,13 +1189,91 @@ mod tests {
 
         assert_eq!(
             Err(
-                "Invalid filter expression. Expected '<column>:<regex>' got ':bar'"
+                "Invalid filter expression. Expected '<column>:<regex>' or '<column><op><value>' got ':bar'"
                     .to_string()
             ),
             validate_filter(":bar")
         );
     }
 
+    #[test]
+    fn test_args_validate_transform() {
+        assert_eq!(Ok(()), validate_transform("url:host"));
+        assert_eq!(Ok(()), validate_transform("email:domain"));
+        assert_eq!(Ok(()), validate_transform("name:prefix=2"));
+        assert_eq!(Ok(()), validate_transform("path:regex=^/([^/]+)"));
+
+        assert_eq!(
+            Err(
+                "Invalid transform expression. Expected '<column>:<transform>' got 'url'"
+                    .to_string()
+            ),
+            validate_transform("url")
+        );
+
+        assert_eq!(
+            Err(
+                "Invalid transform expression. Expected '<column>:<transform>' got ':host'"
+                    .to_string()
+            ),
+            validate_transform(":host")
+        );
+
+        assert_eq!(
+            Err(
+                "Invalid transform expression. Expected '<column>:<transform>' got 'url:foo'"
+                    .to_string()
+            ),
+            validate_transform("url:foo")
+        );
+    }
+
+    #[test]
+    fn test_args_validate_date_part() {
+        assert_eq!(Ok(()), validate_date_part("ts:dow"));
+        assert_eq!(Ok(()), validate_date_part("ts:hour"));
+        assert_eq!(Ok(()), validate_date_part("ts:month"));
+
+        assert_eq!(
+            Err(
+                "Invalid date part expression. Expected '<column>:dow|hour|month' got 'ts:host'"
+                    .to_string()
+            ),
+            validate_date_part("ts:host")
+        );
+
+        assert_eq!(
+            Err(
+                "Invalid date part expression. Expected '<column>:dow|hour|month' got ':dow'"
+                    .to_string()
+            ),
+            validate_date_part(":dow")
+        );
+    }
+
+    #[test]
+    fn test_args_transform_values() {
+        let name = "transforms";
+
+        let missing_matches = create_mult_matches(name, &[name]);
+        let missing_result = transform_values(&missing_matches, name);
+
+        let mult_matches = create_mult_matches(name, &[name, "a:host", "b:prefix=1"]);
+        let mult_result = transform_values(&mult_matches, name).unwrap().unwrap();
+
+        let invalid_matches = create_mult_matches(name, &[name, "a"]);
+        let invalid_result = transform_values(&invalid_matches, name);
+
+        assert_eq!(Ok(None), missing_result.map(|r| r.map(|m| m.len())));
+        assert_eq!(2, mult_result.len());
+        assert!(matches!(mult_result.get("a"), Some(Transform::Host)));
+        assert!(matches!(mult_result.get("b"), Some(Transform::Prefix(1))));
+        assert_eq!(
+            Err(Error::InvalidArgument(name.to_string())),
+            invalid_result.map(|r| r.map(|m| m.len()))
+        );
+    }
+
     #[test]
     fn test_args_usize_value() {
         let name = "limit";
@@ -215,6 +1287,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_args_f64_value() {
+        let name = "threshold";
+        let valid = create_matches(name, "2.5");
+        let invalid = create_matches(name, "NOT VALID");
+
+        assert_eq!(Ok(2.5), f64_value(&valid, name));
+        assert_eq!(
+            Err(Error::InvalidArgument("threshold".to_string())),
+            f64_value(&invalid, name)
+        );
+    }
+
+    #[test]
+    fn test_args_regex_value() {
+        let name = "pattern";
+        let valid = create_matches(name, "[a-z]+");
+        let invalid = create_matches(name, "[a-z");
+
+        assert_eq!(
+            Ok(Some(String::from("[a-z]+"))),
+            regex_value(&valid, name).map(|r| r.map(|r| r.to_string()))
+        );
+        assert!(regex_value(&invalid, name).is_err());
+    }
+
     #[test]
     fn test_args_output_format_value() {
         let name = "format";
@@ -228,6 +1326,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_args_nested_format_value() {
+        let name = "nested";
+        let valid = create_matches(name, "json");
+        let invalid = create_matches(name, "NOT VALID");
+
+        assert_eq!(Ok(NestedFormat::Json), nested_format_value(&valid, name));
+        assert_eq!(
+            Err(Error::InvalidArgument("nested".to_string())),
+            nested_format_value(&invalid, name)
+        );
+    }
+
     #[test]
     fn test_args_string_values() {
         let name = "values";
@@ -275,59 +1386,122 @@ mod tests {
     }
 
     #[test]
-    fn test_args_filter_values() {
-        let name = "filters";
-
-        let missing_matches = create_mult_matches(name, &[name]);
-        let missing_result = filter_values(&missing_matches, name);
+    fn test_args_expand_presets() {
+        let config =
+            Config::parse("preset.events = id,ts\ngroup.address = addr_*").unwrap();
+        let values = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let groups = [ColumnGroup::parse("events=event_*").unwrap()];
 
-        let simple_matches = create_mult_matches(name, &[name, "field:[a-z]"]);
-        let simple_result = filter_values(&simple_matches, name);
-
-        let url_matches = create_mult_matches(name, &[name, "url:^http://"]);
-        let url_result = filter_values(&url_matches, name);
-
-        let mult_matches = create_mult_matches(name, &[name, "a:A", "b:B"]);
-        let mult_result = filter_values(&mult_matches, name);
-
-        let regex_matches = create_mult_matches(name, &[name, "foo:^ns::[a-zA-Z]*$"]);
-        let regex_result = filter_values(&regex_matches, name);
-
-        assert!(missing_result.is_ok());
-        assert!(missing_result.as_ref().unwrap().is_none());
-
-        assert!(simple_result.is_ok());
-        assert!(simple_result.as_ref().unwrap().is_some());
-
-        assert!(url_result.is_ok());
-        assert!(url_result.as_ref().unwrap().is_some());
-
-        assert!(mult_result.is_ok());
-        assert!(mult_result.as_ref().unwrap().is_some());
-
-        assert!(regex_result.is_ok());
-        assert!(regex_result.as_ref().unwrap().is_some());
+        assert_eq!(
+            expand_presets(values(&["type", "@events"]), &config, &[]),
+            Ok(values(&["type", "id", "ts"]))
+        );
+        assert_eq!(
+            expand_presets(values(&["@events", "@address"]), &config, &groups),
+            Ok(values(&["event_*", "addr_*"]))
+        );
+        assert_eq!(
+            expand_presets(values(&["@unknown"]), &config, &[]),
+            Err(Error::InvalidArgument(String::from(
+                "Unknown column preset 'unknown'"
+            )))
+        );
+    }
 
-        let simple_result_map = simple_result.unwrap().unwrap();
-        let regex_result_map = regex_result.unwrap().unwrap();
-        let mult_result_map = mult_result.unwrap().unwrap();
-        let url_result_map = url_result.unwrap().unwrap();
+    #[test]
+    fn test_args_filter_values() {
+        let name = "filters";
+        let values = |args: &[&str]| {
+            filter_values(&create_mult_matches(name, args), name)
+                .map(|v| v.iter().map(Expr::to_string).collect::<Vec<_>>())
+        };
 
-        assert_eq!(1, simple_result_map.len());
-        assert_eq!("[a-z]", simple_result_map.get("field").unwrap().as_str());
+        assert_eq!(values(&[name]), Ok(vec![]));
+        assert_eq!(
+            values(&[name, "field:[a-z]"]),
+            Ok(vec![String::from("field:[a-z]")])
+        );
+        assert_eq!(
+            values(&[name, "url:^http://"]),
+            Ok(vec![String::from("url:^http://")])
+        );
+        assert_eq!(
+            values(&[name, "a:A", "b:B"]),
+            Ok(vec![String::from("a:A"), String::from("b:B")])
+        );
+        assert_eq!(
+            values(&[name, "foo:^ns::[a-zA-Z]*$"]),
+            Ok(vec![String::from("foo:^ns::[a-zA-Z]*$")])
+        );
+        assert_eq!(
+            values(&[name, "a>1", "a<5"]),
+            Ok(vec![String::from("a>1"), String::from("a<5")])
+        );
+        assert!(values(&[name, "foo"]).is_err());
+    }
 
-        assert_eq!(1, url_result_map.len());
-        assert_eq!("^http://", url_result_map.get("url").unwrap().as_str());
+    #[test]
+    fn test_args_search_value() {
+        let matches = |args: &[&str]| {
+            App::new("search")
+                .args(search_args())
+                .get_matches_from_safe(args)
+                .unwrap()
+        };
+        let value =
+            |args: &[&str]| search_value(&matches(args)).unwrap().map(|e| e.to_string());
 
-        assert_eq!(1, regex_result_map.len());
+        assert_eq!(value(&["search"]), None);
         assert_eq!(
-            "^ns::[a-zA-Z]*$",
-            regex_result_map.get("foo").unwrap().as_str()
+            value(&["search", "-s", "a>1", "-s", "a<5"]),
+            Some(String::from("a>1 AND a<5"))
         );
-
-        assert_eq!(2, mult_result_map.len());
-        assert_eq!("A", mult_result_map.get("a").unwrap().as_str());
-        assert_eq!("B", mult_result_map.get("b").unwrap().as_str());
+        assert_eq!(
+            value(&[
+                "search",
+                "-s=a>1",
+                "--search-not=b:^x",
+                "--search-any",
+                "c=1",
+                "d=1"
+            ]),
+            Some(String::from(
+                "a>1 AND (NOT b:^x OR b IS NULL) AND (c=1 OR d=1)"
+            ))
+        );
+        assert_eq!(
+            value(&["search", "-s", "a>1", "--partition", "date=2024-05-01"]),
+            Some(String::from("a>1 AND date=2024-05-01"))
+        );
+        assert_eq!(
+            value(&["search", "-s", "a>1", "--where", "b = 'x' OR c IS NULL"]),
+            Some(String::from("a>1 AND (b=x OR c IS NULL)"))
+        );
+        assert!(App::new("search")
+            .args(search_args())
+            .get_matches_from_safe(["search", "--where", "b ="])
+            .is_err());
+        assert_eq!(
+            value(&[
+                "search",
+                "--as-of=2024-06-01",
+                "--valid-from=start",
+                "--valid-to=end"
+            ]),
+            Some(String::from(
+                "(start<=2024-06-01 AND (end IS NULL OR end>2024-06-01))"
+            ))
+        );
+        assert_eq!(
+            search_value(&matches(&["search", "--as-of=2024-06-01"]))
+                .unwrap_err()
+                .to_string(),
+            "Invalid argument: --as-of requires --valid-from or --valid-to"
+        );
+        assert!(App::new("search")
+            .args(search_args())
+            .get_matches_from_safe(["search", "--as-of=June", "--valid-to=end"])
+            .is_err());
     }
 
     #[test]
@@ -345,6 +1519,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_args_path_values() {
+        let name = "path";
+        let tmp1 = api::tests::temp_file("tmp1", ".file");
+        let tmp2 = api::tests::temp_file("tmp2", ".file");
+        let path1 = tmp1.path().to_str().unwrap();
+        let path2 = tmp2.path().to_str().unwrap();
+        let valid = create_mult_matches(name, &[name, path1, path2]);
+        let invalid = create_mult_matches(name, &[name, path1, "NOT VALID"]);
+        let missing = create_mult_matches(name, &[name]);
+
+        assert_eq!(
+            Ok(vec![Path::new(path1), Path::new(path2)]),
+            path_values(&valid, name)
+        );
+        assert_eq!(
+            Err(Error::InvalidArgument("path".to_string())),
+            path_values(&invalid, name)
+        );
+        assert_eq!(
+            Err(Error::InvalidArgument("path".to_string())),
+            path_values(&missing, name)
+        );
+    }
+
     fn create_matches<'a>(name: &'a str, value: &'a str) -> ArgMatches {
         App::new(name)
             .arg(Arg::with_name(name).index(1).required(true))
@@ -352,7 +1551,7 @@ mod tests {
             .unwrap()
     }
 
-    fn create_mult_matches<'a>(name: &'a str, values: &[&str]) -> ArgMatches {
+    fn create_mult_matches(name: &str, values: &[&str]) -> ArgMatches {
         App::new(name)
             .arg(Arg::with_name(name).index(1).required(false).multiple(true))
             .get_matches_from_safe(values)
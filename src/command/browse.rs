@@ -0,0 +1,811 @@
+#[cfg(not(unix))]
+use crate::api::Error;
+use crate::api::Result;
+#[cfg(unix)]
+use crate::command::tty;
+use crate::command::{args, schema};
+use crate::reader::{self, ParquetFile};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::io::Write;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Max width of a column, longer cells are truncated.
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// Max width of the schema sidebar.
+const SIDEBAR_WIDTH: usize = 32;
+
+/// Columns between two cells.
+const GAP: &str = "  ";
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const REVERSE: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+const HELP: &str = "q quit  / search  n/N next/prev  x hide  a show all  s schema";
+
+/// Key presses understood by the browser.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+    Interrupt,
+    Char(char),
+}
+
+/// Keys of the bytes read from the terminal, unknown escape sequences are ignored.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+
+        if rest[0] == 0x1b && rest.len() > 2 && (rest[1] == b'[' || rest[1] == b'O') {
+            let end = rest[2..]
+                .iter()
+                .position(|b| b.is_ascii_alphabetic() || *b == b'~')
+                .map_or(rest.len(), |p| p + 3);
+            let key = match &rest[2..end] {
+                b"A" => Some(Key::Up),
+                b"B" => Some(Key::Down),
+                b"C" => Some(Key::Right),
+                b"D" => Some(Key::Left),
+                b"H" | b"1~" | b"7~" => Some(Key::Home),
+                b"F" | b"4~" | b"8~" => Some(Key::End),
+                b"5~" => Some(Key::PageUp),
+                b"6~" => Some(Key::PageDown),
+                _ => None,
+            };
+
+            keys.extend(key);
+            i += end;
+
+            continue;
+        }
+
+        let len = match rest[0] {
+            b if b >> 5 == 0b110 => 2,
+            b if b >> 4 == 0b1110 => 3,
+            b if b >> 3 == 0b11110 => 4,
+            _ => 1,
+        };
+        let key = match rest[0] {
+            0x1b => Some(Key::Esc),
+            0x03 => Some(Key::Interrupt),
+            b'\r' | b'\n' => Some(Key::Enter),
+            b'\t' => Some(Key::Tab),
+            0x7f | 0x08 => Some(Key::Backspace),
+            _ => rest
+                .get(..len)
+                .and_then(|b| std::str::from_utf8(b).ok())
+                .and_then(|s| s.chars().next())
+                .filter(|c| !c.is_control())
+                .map(Key::Char),
+        };
+
+        keys.extend(key);
+        i += len;
+    }
+
+    keys
+}
+
+/// Text cut to at most `width` columns.
+fn clip(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut used = 0;
+
+    for c in text.chars() {
+        let size = UnicodeWidthChar::width(c).unwrap_or(0);
+
+        if used + size > width {
+            break;
+        }
+
+        result.push(c);
+        used += size;
+    }
+
+    result
+}
+
+/// Text padded to `width` columns, longer text is truncated ending with `...`.
+fn fit(text: &str, width: usize) -> String {
+    let length = UnicodeWidthStr::width(text);
+
+    if length <= width {
+        return format!("{}{}", text, " ".repeat(width - length));
+    }
+
+    if width <= 3 {
+        return clip("...", width);
+    }
+
+    let text = clip(text, width - 3);
+    let length = UnicodeWidthStr::width(text.as_str());
+
+    format!("{}...{}", text, " ".repeat(width - 3 - length))
+}
+
+/// Screen line limited to the terminal width.
+struct Line {
+    text: String,
+    width: usize,
+    limit: usize,
+}
+
+impl Line {
+    fn new(limit: usize) -> Self {
+        Self {
+            text: String::new(),
+            width: 0,
+            limit,
+        }
+    }
+
+    /// Append the text clipped to the columns left, wrapped in the style escape codes.
+    fn push(&mut self, text: &str, style: &str) {
+        let text = clip(text, self.limit - self.width);
+
+        if text.is_empty() {
+            return;
+        }
+
+        self.width += UnicodeWidthStr::width(text.as_str());
+        self.text.push_str(style);
+        self.text.push_str(&text);
+        self.text.push_str(RESET);
+    }
+
+    /// Fill the rest of the line with spaces in the style.
+    fn fill(mut self, style: &str) -> String {
+        let spaces = " ".repeat(self.limit - self.width);
+
+        self.push(&spaces, style);
+        self.text
+    }
+}
+
+/// Rows loaded in memory and the state of the screen.
+struct Browser {
+    headers: Vec<String>,
+    kinds: Vec<String>,
+    rows: Vec<Vec<String>>,
+    widths: Vec<usize>,
+    hidden: Vec<bool>,
+
+    // Selected row and first row on the screen
+    cursor: usize,
+    top: usize,
+
+    // Selected column and first column on the screen
+    column: usize,
+    offset: usize,
+
+    sidebar: bool,
+
+    // Query being typed and the row selected when the search started
+    typing: Option<(String, usize)>,
+    query: String,
+    message: Option<String>,
+}
+
+impl Browser {
+    fn new(headers: Vec<String>, kinds: Vec<String>, rows: Vec<Vec<String>>) -> Self {
+        let widths = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                rows.iter()
+                    .filter_map(|r| r.get(i))
+                    .chain(std::iter::once(header))
+                    .map(|v| UnicodeWidthStr::width(v.as_str()))
+                    .max()
+                    .unwrap_or(0)
+                    .min(MAX_COLUMN_WIDTH)
+            })
+            .collect();
+
+        Self {
+            hidden: vec![false; headers.len()],
+            headers,
+            kinds,
+            rows,
+            widths,
+            cursor: 0,
+            top: 0,
+            column: 0,
+            offset: 0,
+            sidebar: false,
+            typing: None,
+            query: String::new(),
+            message: None,
+        }
+    }
+
+    fn visible(&self) -> Vec<usize> {
+        (0..self.headers.len())
+            .filter(|i| !self.hidden[*i])
+            .collect()
+    }
+
+    fn sidebar_width(&self, width: usize) -> usize {
+        if self.sidebar && width > 2 {
+            SIDEBAR_WIDTH.min(width / 2)
+        } else {
+            0
+        }
+    }
+
+    /// Whether a visible cell of the row contains the query, ignoring case.
+    fn is_match(&self, row: usize, query: &str) -> bool {
+        let query = query.to_lowercase();
+
+        self.visible()
+            .into_iter()
+            .filter_map(|i| self.rows[row].get(i))
+            .any(|cell| cell.to_lowercase().contains(&query))
+    }
+
+    /// Select the first row matching the query from `from`, wrapping around the rows.
+    fn find(&mut self, query: &str, from: usize, forward: bool) {
+        let len = self.rows.len();
+        let position = (0..len)
+            .map(|k| {
+                if forward {
+                    (from + k) % len
+                } else {
+                    (from + len - k % len) % len
+                }
+            })
+            .find(|row| self.is_match(*row, query));
+
+        match position {
+            Some(row) => self.cursor = row,
+            None => self.message = Some(format!("Pattern not found: {}", query)),
+        }
+    }
+
+    /// Move the selected column to the next visible one before or after it.
+    fn move_column(&mut self, forward: bool) {
+        let visible = self.visible();
+        let next = if forward {
+            visible.iter().find(|i| **i > self.column)
+        } else {
+            visible.iter().rev().find(|i| **i < self.column)
+        };
+
+        if let Some(column) = next {
+            self.column = *column;
+        }
+    }
+
+    /// Update the state, returns `false` when the browser should exit.
+    fn handle(&mut self, key: Key, height: usize) -> bool {
+        let page = height.saturating_sub(2).max(1);
+        let last = self.rows.len().saturating_sub(1);
+
+        self.message = None;
+
+        if let Some((mut query, anchor)) = self.typing.take() {
+            match key {
+                Key::Interrupt => return false,
+                Key::Esc => return true,
+                Key::Enter => {
+                    self.query = query;
+                    return true;
+                }
+                Key::Backspace => {
+                    query.pop();
+                }
+                Key::Char(c) => query.push(c),
+                _ => {}
+            }
+
+            if !query.is_empty() && !self.rows.is_empty() {
+                self.find(&query, anchor, true);
+            }
+
+            self.typing = Some((query, anchor));
+
+            return true;
+        }
+
+        match key {
+            Key::Char('q') | Key::Esc | Key::Interrupt => return false,
+            Key::Up | Key::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down | Key::Char('j') => self.cursor = (self.cursor + 1).min(last),
+            Key::PageUp => self.cursor = self.cursor.saturating_sub(page),
+            Key::PageDown | Key::Char(' ') => {
+                self.cursor = (self.cursor + page).min(last)
+            }
+            Key::Home | Key::Char('g') => self.cursor = 0,
+            Key::End | Key::Char('G') => self.cursor = last,
+            Key::Left | Key::Char('h') => self.move_column(false),
+            Key::Right | Key::Char('l') => self.move_column(true),
+            Key::Char('x') | Key::Char('-') if self.visible().len() > 1 => {
+                let column = self.column;
+
+                self.move_column(true);
+
+                if self.column == column {
+                    self.move_column(false);
+                }
+
+                self.hidden[column] = true;
+            }
+            Key::Char('a') => self.hidden = vec![false; self.headers.len()],
+            Key::Char('s') | Key::Tab => self.sidebar = !self.sidebar,
+            Key::Char('/') => self.typing = Some((String::new(), self.cursor)),
+            Key::Char('n') | Key::Char('N') if !self.query.is_empty() && last > 0 => {
+                let query = self.query.clone();
+
+                match key {
+                    Key::Char('n') => self.find(&query, self.cursor + 1, true),
+                    _ => self.find(&query, self.cursor + last, false),
+                }
+            }
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Scroll the screen to keep the selected row and column visible.
+    fn scroll(&mut self, width: usize, height: usize) {
+        let body = height.saturating_sub(2).max(1);
+        let main = width.saturating_sub(self.sidebar_width(width));
+        let visible = self.visible();
+
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        }
+
+        if self.cursor >= self.top + body {
+            self.top = self.cursor + 1 - body;
+        }
+
+        let selected = visible.iter().position(|i| *i == self.column).unwrap_or(0);
+        let mut first = visible
+            .iter()
+            .position(|i| *i >= self.offset)
+            .unwrap_or(0)
+            .min(selected);
+        let span = |from: usize| {
+            visible[from..=selected]
+                .iter()
+                .map(|i| self.widths[*i] + GAP.len())
+                .sum::<usize>()
+        };
+
+        while first < selected && span(first) > main {
+            first += 1;
+        }
+
+        self.offset = visible.get(first).copied().unwrap_or(0);
+    }
+
+    /// Lines of the schema sidebar, hidden columns are dimmed.
+    fn sidebar_line(&self, line: usize, width: usize) -> String {
+        let mut text = Line::new(width);
+
+        if line == 0 {
+            text.push(&fit("Schema", width - 1), BOLD);
+        } else if let Some(header) = self.headers.get(line - 1) {
+            let kind = &self.kinds[line - 1];
+            let name_width =
+                (width - 1).saturating_sub(UnicodeWidthStr::width(kind.as_str()) + 1);
+            let style = if self.hidden[line - 1] { DIM } else { "" };
+
+            text.push(&fit(header, name_width), style);
+            text.push(" ", style);
+            text.push(kind, DIM);
+        }
+
+        let used = text.width;
+        let mut text = text.text;
+
+        text.push_str(&" ".repeat((width - 1).saturating_sub(used)));
+        text.push('│');
+        text
+    }
+
+    /// Lines of the screen, the header, the rows and the status line.
+    fn render(&self, width: usize, height: usize) -> Vec<String> {
+        let sidebar = self.sidebar_width(width);
+        let main = width - sidebar;
+        let columns = self
+            .visible()
+            .into_iter()
+            .filter(|i| *i >= self.offset)
+            .collect::<Vec<_>>();
+        let mut lines = Vec::with_capacity(height);
+
+        for line in 0..height.saturating_sub(1) {
+            let mut text = Line::new(main);
+            let row = self.top + line.saturating_sub(1);
+            let style = if line > 0 && row == self.cursor {
+                REVERSE
+            } else {
+                ""
+            };
+
+            for (n, column) in columns.iter().enumerate() {
+                if n > 0 {
+                    text.push(GAP, style);
+                }
+
+                let width = self.widths[*column];
+
+                if line == 0 {
+                    let style = if *column == self.column {
+                        REVERSE
+                    } else {
+                        BOLD
+                    };
+
+                    text.push(&fit(&self.headers[*column], width), style);
+                } else if let Some(cell) = self.rows.get(row).and_then(|r| r.get(*column))
+                {
+                    text.push(&fit(cell, width), style);
+                }
+            }
+
+            let prefix = match sidebar {
+                0 => String::new(),
+                _ => self.sidebar_line(line, sidebar),
+            };
+
+            lines.push(format!("{}{}", prefix, text.fill(style)));
+        }
+
+        let status = match (&self.typing, &self.message) {
+            (Some((query, _)), _) => format!("/{}", query),
+            (None, Some(message)) => message.to_string(),
+            (None, None) => format!(
+                "row {}/{}  column {}  {} hidden  {}",
+                (self.cursor + 1).min(self.rows.len()),
+                self.rows.len(),
+                self.headers.get(self.column).map_or("", String::as_str),
+                self.hidden.iter().filter(|h| **h).count(),
+                HELP
+            ),
+        };
+        let mut line = Line::new(width);
+
+        line.push(&status, REVERSE);
+        lines.push(line.fill(REVERSE));
+
+        lines
+    }
+}
+
+/// Terminal in raw mode on the alternate screen, restored when dropped.
+///
+/// Keys are read from and the screen written to `/dev/tty`, so the browser works
+/// when stdin or stdout are redirected and outputs are never paged.
+#[cfg(unix)]
+struct Terminal {
+    raw: tty::RawMode,
+}
+
+#[cfg(unix)]
+impl Terminal {
+    fn open() -> Result<Self> {
+        let mut raw = tty::RawMode::open("browse")?;
+
+        // alternate screen, hidden cursor
+        raw.tty.write_all(b"\x1b[?1049h\x1b[?25l\x1b[2J")?;
+
+        Ok(Self { raw })
+    }
+
+    /// Columns and lines of the terminal.
+    fn size(&self) -> (usize, usize) {
+        use std::os::unix::io::AsRawFd;
+
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let result =
+            unsafe { libc::ioctl(self.raw.tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) };
+
+        match result {
+            0 if size.ws_col > 0 && size.ws_row > 0 => {
+                (usize::from(size.ws_col), usize::from(size.ws_row))
+            }
+            _ => (80, 24),
+        }
+    }
+
+    fn draw(&mut self, lines: &[String]) -> Result<()> {
+        let screen = lines.join("\x1b[K\r\n");
+
+        write!(self.raw.tty, "\x1b[H{}\x1b[K", screen)?;
+        self.raw.tty.flush()?;
+
+        Ok(())
+    }
+
+    /// Keys of the next read, empty when the terminal is closed.
+    fn read_keys(&mut self) -> Result<Vec<Key>> {
+        use std::io::Read;
+
+        let mut buffer = [0; 64];
+
+        loop {
+            let read = self.raw.tty.read(&mut buffer)?;
+            let keys = parse_keys(&buffer[..read]);
+
+            if read == 0 || !keys.is_empty() {
+                return Ok(keys);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        // the raw mode is restored once the screen is
+        let _ = self.raw.tty.write_all(b"\x1b[?25h\x1b[?1049l");
+        let _ = self.raw.tty.flush();
+    }
+}
+
+#[cfg(unix)]
+fn browse(browser: &mut Browser) -> Result<()> {
+    let mut terminal = Terminal::open()?;
+
+    loop {
+        let (width, height) = terminal.size();
+
+        browser.scroll(width, height);
+        terminal.draw(&browser.render(width, height))?;
+
+        let keys = terminal.read_keys()?;
+
+        if keys.is_empty() || !keys.into_iter().all(|k| browser.handle(k, height)) {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn browse(_: &mut Browser) -> Result<()> {
+    Err(Error::IO(String::from("browse needs a unix terminal")))
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("browse")
+        .about("Browse rows in an interactive viewer")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("limit")
+                .validator(args::validate_number)
+                .help("Max number of rows loaded")
+                .default_value("10000")
+                .long("limit")
+                .short('l'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::value_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, _: &mut W) -> Result<()> {
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let limit = args::usize_value(matches, "limit")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_value_format(args::value_format_value(matches))
+        .with_fields(columns)
+        .with_filter(search);
+    let headers = parquet.field_names()?;
+    let schema = parquet.schema()?;
+    let kinds = headers
+        .iter()
+        .map(|h| {
+            reader::field_type(&schema, h)
+                .map(|f| schema::field_kind(&f))
+                .unwrap_or_default()
+        })
+        .collect();
+    let rows = parquet.iter().take(limit).collect::<Result<Vec<_>>>()?;
+
+    browse(&mut Browser::new(headers, kinds, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser() -> Browser {
+        let headers = vec![String::from("id"), String::from("name")];
+        let kinds = vec![String::from("INT_64"), String::from("UTF8")];
+        let rows = (1..=20)
+            .map(|i| vec![i.to_string(), format!("\"user {}\"", i)])
+            .collect();
+
+        Browser::new(headers, kinds, rows)
+    }
+
+    /// Lines without the escape codes.
+    fn plain(lines: &[String]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| {
+                [BOLD, DIM, REVERSE, RESET]
+                    .iter()
+                    .fold(l.to_string(), |text, code| text.replace(code, ""))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_browse_parse_keys() {
+        assert_eq!(
+            parse_keys(b"\x1b[A\x1b[Bj\x1b[5~\x1bOCq"),
+            vec![
+                Key::Up,
+                Key::Down,
+                Key::Char('j'),
+                Key::PageUp,
+                Key::Right,
+                Key::Char('q')
+            ]
+        );
+        assert_eq!(parse_keys(b"\x1b"), vec![Key::Esc]);
+        assert_eq!(
+            parse_keys("é\x7f\r\x03".as_bytes()),
+            vec![Key::Char('é'), Key::Backspace, Key::Enter, Key::Interrupt]
+        );
+        assert!(parse_keys(b"\x1b[99~").is_empty());
+    }
+
+    #[test]
+    fn test_browse_fit() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcdefgh", 6), "abc...");
+        assert_eq!(fit("日本語です", 7), "日本...");
+        assert_eq!(fit("abcd", 2), "..");
+    }
+
+    #[test]
+    fn test_browse_render() {
+        let mut browser = browser();
+
+        browser.scroll(30, 5);
+
+        assert_eq!(
+            plain(&browser.render(30, 5)),
+            vec![
+                "id  name                      ",
+                "1   \"user 1\"                  ",
+                "2   \"user 2\"                  ",
+                "3   \"user 3\"                  ",
+                "row 1/20  column id  0 hidden ",
+            ]
+        );
+
+        browser.sidebar = true;
+        browser.hidden[0] = true;
+        browser.column = 1;
+        browser.scroll(30, 3);
+
+        assert_eq!(
+            plain(&browser.render(30, 3)),
+            vec![
+                "Schema        │name           ",
+                "id      INT_64│\"user 1\"       ",
+                "row 1/20  column name  1 hidde",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_browse_scroll_rows() {
+        let mut browser = browser();
+
+        for _ in 0..5 {
+            browser.handle(Key::Down, 5);
+        }
+
+        browser.scroll(30, 5);
+
+        assert_eq!((browser.cursor, browser.top), (5, 3));
+
+        browser.handle(Key::End, 5);
+        browser.handle(Key::PageUp, 5);
+        browser.scroll(30, 5);
+
+        assert_eq!((browser.cursor, browser.top), (16, 14));
+        assert!(browser.handle(Key::Home, 5));
+        assert_eq!(browser.cursor, 0);
+        assert!(!browser.handle(Key::Char('q'), 5));
+    }
+
+    #[test]
+    fn test_browse_hide_columns() {
+        let mut browser = browser();
+
+        browser.handle(Key::Char('x'), 5);
+
+        assert_eq!(browser.visible(), vec![1]);
+        assert_eq!(browser.column, 1);
+
+        // the last visible column can not be hidden
+        browser.handle(Key::Char('x'), 5);
+
+        assert_eq!(browser.visible(), vec![1]);
+
+        browser.handle(Key::Char('a'), 5);
+
+        assert_eq!(browser.visible(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_browse_incremental_search() {
+        let mut browser = browser();
+
+        for key in parse_keys(b"/user 1") {
+            browser.handle(key, 5);
+        }
+
+        assert_eq!(browser.cursor, 0);
+
+        browser.handle(Key::Char('5'), 5);
+
+        assert_eq!(browser.cursor, 14);
+        assert_eq!(plain(&browser.render(20, 3))[2], "/user 15            ");
+
+        browser.handle(Key::Backspace, 5);
+        browser.handle(Key::Enter, 5);
+        browser.handle(Key::Char('n'), 5);
+
+        assert_eq!(browser.cursor, 9);
+
+        browser.handle(Key::Char('N'), 5);
+
+        assert_eq!(browser.cursor, 0);
+
+        for key in parse_keys(b"/missing\r") {
+            browser.handle(key, 5);
+        }
+
+        browser.handle(Key::Char('n'), 5);
+
+        assert_eq!(browser.cursor, 0);
+        assert_eq!(
+            browser.message,
+            Some(String::from("Pattern not found: missing"))
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::api::Result;
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use stats::Frequencies;
+use std::io::Write;
+use std::str;
+
+/// Character set composition of a text column.
+#[derive(Clone, Debug, Default)]
+pub struct Charset {
+    values: u64,
+    ascii: u64,
+    multibyte: u64,
+    control: u64,
+    replacement: u64,
+    invalid: u64,
+    languages: Frequencies<&'static str>,
+}
+
+impl Charset {
+    pub fn add(&mut self, field: &Field) {
+        match field {
+            Field::Str(value) => self.add_text(value),
+            Field::Bytes(bytes) => match str::from_utf8(bytes.data()) {
+                Ok(value) => self.add_text(value),
+                Err(_) => {
+                    self.values += 1;
+                    self.invalid += 1;
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn add_text(&mut self, value: &str) {
+        self.values += 1;
+
+        if value.is_ascii() {
+            self.ascii += 1;
+        } else {
+            self.multibyte += 1;
+        }
+
+        if value
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+        {
+            self.control += 1;
+        }
+
+        if value.contains('\u{FFFD}') {
+            self.replacement += 1;
+        }
+
+        if let Some(info) = whatlang::detect(value).filter(|i| i.is_reliable()) {
+            self.languages.add(info.lang().code());
+        }
+    }
+
+    /// Most frequent reliably detected language and its share of the values.
+    pub fn language(&self) -> Option<(&'static str, f64)> {
+        self.languages
+            .most_frequent()
+            .first()
+            .map(|c| (*c.0, c.1 as f64 / self.values as f64))
+    }
+}
+
+fn compute<I>(num_fields: usize, iter: I) -> Result<Vec<Charset>>
+where
+    I: Iterator<Item = Result<Vec<Field>>>,
+{
+    let mut vec = vec![Charset::default(); num_fields];
+
+    for row in iter {
+        for (i, field) in row?.iter().enumerate() {
+            vec[i].add(field);
+        }
+    }
+
+    Ok(vec)
+}
+
+fn format_row(field: &str, charset: &Charset) -> Vec<String> {
+    let language = match charset.language() {
+        Some((code, share)) => format!("{} ({:.2})", code, share),
+        None => String::from("-"),
+    };
+
+    vec![
+        field.to_string(),
+        charset.values.to_string(),
+        charset.ascii.to_string(),
+        charset.multibyte.to_string(),
+        charset.control.to_string(),
+        charset.replacement.to_string(),
+        charset.invalid.to_string(),
+        language,
+    ]
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("charset")
+        .about("Show character set composition and language of text columns")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("sample")
+                .validator(args::validate_number)
+                .help("Number of rows to scan")
+                .default_value("1000")
+                .long("sample")
+                .short('n'),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let sample = args::usize_value(matches, "sample")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from((path, columns))
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+
+    let fields = parquet.field_names()?;
+    let vec = compute(fields.len(), parquet.records().take(sample))?;
+    let headers = vec![
+        String::from("FIELD"),
+        String::from("VALUES"),
+        String::from("ASCII"),
+        String::from("MULTIBYTE"),
+        String::from("CONTROL"),
+        String::from("REPLACEMENT"),
+        String::from("INVALID"),
+        String::from("LANGUAGE"),
+    ];
+
+    // only text columns are reported
+    let rows = fields
+        .iter()
+        .zip(vec.iter())
+        .filter(|t| t.1.values > 0)
+        .map(|t| Ok(format_row(t.0, t.1)))
+        .collect::<Vec<_>>();
+
+    let mut writer = args::writer(matches, headers, rows.into_iter()).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use parquet::data_type::ByteArray;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_charset_add() {
+        let mut charset = Charset::default();
+
+        charset.add(&Field::Str(String::from("plain ascii")));
+        charset.add(&Field::Str(String::from("ação")));
+        charset.add(&Field::Str(String::from("bell \u{7}")));
+        charset.add(&Field::Str(String::from("broken \u{FFFD}")));
+        charset.add(&Field::Bytes(ByteArray::from(vec![0xff, 0xfe])));
+        charset.add(&Field::Int(1));
+
+        assert_eq!(charset.values, 5);
+        assert_eq!(charset.ascii, 2);
+        assert_eq!(charset.multibyte, 2);
+        assert_eq!(charset.control, 1);
+        assert_eq!(charset.replacement, 1);
+        assert_eq!(charset.invalid, 1);
+    }
+
+    #[test]
+    fn test_charset_language() {
+        let mut charset = Charset::default();
+
+        assert_eq!(charset.language(), None);
+
+        charset.add(&Field::Str(String::from(
+            "This is a simple english sentence used to detect the language",
+        )));
+        charset.add(&Field::Str(String::from("ok")));
+
+        assert_eq!(charset.language(), Some(("eng", 0.5)));
+    }
+
+    #[test]
+    fn test_charset_json_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("charset", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "FIELD,VALUES,ASCII,MULTIBYTE,CONTROL,REPLACEMENT,INVALID,LANGUAGE",
+            "name,2,1,1,0,0,0,por (0.50)",
+            "",
+        ]
+        .join("\n");
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[
+                r#"{"id": 1, "name": "foo"}"#,
+                r#"{"id": 2, "name": "ação"}"#,
+            ],
+        );
+
+        let subcomand = def();
+        let arg_vec = vec!["charset", path_str, "-f=csv"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
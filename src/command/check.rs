@@ -0,0 +1,439 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use crate::value::{Value, ValueFormat};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use std::cell::Cell;
+use std::fs;
+use std::io::Write;
+
+const CHECK_FN: &str = "check";
+
+/// Violations and derived values returned by the check function for a row.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcome {
+    pub violations: Vec<String>,
+    pub values: Vec<(String, Value)>,
+}
+
+/// User defined row check written in rhai.
+///
+/// The script must define a `check(row)` function, where `row` is a map
+/// of the selected columns. It can return `()` or `true` when the row is valid,
+/// `false`, a string or an array of strings to report violations,
+/// or a map of derived values.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    pub fn compile(source: &str) -> Result<Self> {
+        let engine = Engine::new();
+        let ast = engine.compile(source)?;
+
+        Ok(Self { engine, ast })
+    }
+
+    /// Run the check function against a row, returning its violations or derived values.
+    pub fn check(&self, headers: &[String], row: &[Field]) -> Result<Outcome> {
+        let mut map = Map::new();
+        let mut scope = Scope::new();
+
+        for (name, field) in headers.iter().zip(row.iter()) {
+            map.insert(name.into(), rhai::serde::to_dynamic(field.to_json_value())?);
+        }
+
+        let result: Dynamic = self.engine.call_fn(
+            &mut scope,
+            &self.ast,
+            CHECK_FN,
+            (Dynamic::from_map(map),),
+        )?;
+
+        if result.is_map() {
+            let values = result
+                .cast::<Map>()
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), derived_value(v)))
+                .collect();
+
+            return Ok(Outcome {
+                violations: vec![],
+                values,
+            });
+        }
+
+        Ok(Outcome {
+            violations: violations(result),
+            values: vec![],
+        })
+    }
+}
+
+#[inline]
+fn violations(result: Dynamic) -> Vec<String> {
+    if result.is_unit() {
+        return vec![];
+    }
+
+    if let Ok(valid) = result.as_bool() {
+        return if valid {
+            vec![]
+        } else {
+            vec![String::from("check failed")]
+        };
+    }
+
+    if result.is_array() {
+        let array = result.into_array().unwrap_or_default();
+
+        return array.into_iter().flat_map(violations).collect();
+    }
+
+    match result.to_string() {
+        value if value.is_empty() => vec![],
+        value => vec![value],
+    }
+}
+
+#[inline]
+fn derived_value(value: Dynamic) -> Value {
+    if value.is_unit() {
+        return Value::Null;
+    }
+
+    if let Ok(v) = value.as_bool() {
+        return Value::Bool(v);
+    }
+
+    if let Ok(v) = value.as_int() {
+        return Value::Int(v);
+    }
+
+    if let Ok(v) = value.as_float() {
+        return Value::Double(v);
+    }
+
+    if value.is_string() {
+        return Value::Str(value.to_string());
+    }
+
+    match serde_json::to_string(&value) {
+        Ok(json) => Value::Nested(json),
+        Err(_) => Value::Nested(value.to_string()),
+    }
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("check")
+        .about("Run a user defined rhai script against each row")
+        .arg(
+            Arg::with_name("script")
+                .validator(args::validate_path)
+                .help("Rhai script defining a check(row) function")
+                .takes_value(true)
+                .required(true)
+                .long("script")
+                .short('x'),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("limit")
+                .validator(args::validate_number)
+                .help("Max number of violations and rows of derived values")
+                .default_value("500")
+                .long("limit")
+                .short('l'),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::discovery_args())
+}
+
+/// Writes the violations and derived values of each row,
+/// failing with `Error::CheckViolations` when any row is reported.
+///
+/// Rows are numbered from 1 in the order of the files, as `read --row-numbers` does,
+/// filtered out rows keep their number. Derived values are written as extra columns
+/// named by the keys of the map returned for the first row.
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let limit = args::usize_value(matches, "limit")?;
+    let script = args::path_value(matches, "script")?;
+    let path = args::path_value(matches, "path")?;
+    let script = Script::compile(&fs::read_to_string(script)?)?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(columns)
+        .with_filter(search)
+        .with_row_numbers(true);
+
+    // the first field holds the row number
+    let fields = parquet.field_names()?.split_off(1);
+    let mut outcomes = parquet.records().map(|r| {
+        let row = r?;
+        let number = match row.first() {
+            Some(Field::ULong(n)) => *n,
+            _ => 0,
+        };
+
+        Ok((number, script.check(&fields, &row[1..])?))
+    });
+    let first = outcomes.next();
+    let derived = match &first {
+        Some(Ok((_, outcome))) => outcome.values.iter().map(|v| v.0.clone()).collect(),
+        _ => vec![],
+    };
+    let headers = vec![String::from("ROW"), String::from("VIOLATION")]
+        .into_iter()
+        .chain(derived.iter().cloned())
+        .collect();
+    let values = args::value_format_value(matches);
+    let reported = Cell::new(0);
+    let iter = first
+        .into_iter()
+        .chain(outcomes)
+        .flat_map(|r| match r {
+            Ok((number, outcome)) => rows(number, outcome, &derived, &values),
+            Err(e) => vec![Err(e)],
+        })
+        .take(limit)
+        .inspect(|r| {
+            if r.as_ref().is_ok_and(|row| !row[1].is_empty()) {
+                reported.set(reported.get() + 1);
+            }
+        });
+
+    let mut writer = args::writer(matches, headers, iter).format(format);
+
+    writer.write(out)?;
+
+    match reported.get() {
+        0 => Ok(()),
+        num => Err(Error::CheckViolations(num)),
+    }
+}
+
+/// Output rows of a check, one for each violation or the derived values of the row.
+#[inline]
+fn rows(
+    number: u64,
+    outcome: Outcome,
+    derived: &[String],
+    format: &ValueFormat,
+) -> Vec<Result<Vec<String>>> {
+    let row = |violation: String, values: &[(String, Value)]| {
+        let values = derived.iter().map(|name| {
+            let value = values.iter().find(|v| v.0 == *name);
+
+            format.format(value.map_or(Value::Null, |v| v.1.clone()))
+        });
+
+        Ok(vec![number.to_string(), violation]
+            .into_iter()
+            .chain(values)
+            .collect())
+    };
+
+    if outcome.violations.is_empty() && outcome.values.is_empty() {
+        return vec![];
+    }
+
+    if outcome.violations.is_empty() {
+        return vec![row(String::new(), &outcome.values)];
+    }
+
+    outcome
+        .violations
+        .into_iter()
+        .map(|v| row(v, &[]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_check_script_violations() {
+        let script = Script::compile(
+            r#"
+            fn check(row) {
+                let errors = [];
+
+                if row.field_int32 > 1 { errors.push("too big"); }
+                if row.field_boolean { errors.push(`even ${row.field_int32}`); }
+
+                errors
+            }
+            "#,
+        )
+        .unwrap();
+        let headers = vec![String::from("field_int32"), String::from("field_boolean")];
+
+        assert_eq!(
+            script.check(&headers, &[Field::Int(1), Field::Bool(false)]),
+            Ok(Outcome::default())
+        );
+        assert_eq!(
+            script
+                .check(&headers, &[Field::Int(2), Field::Bool(true)])
+                .map(|o| o.violations),
+            Ok(vec![String::from("too big"), String::from("even 2")])
+        );
+    }
+
+    #[test]
+    fn test_check_script_derived_values() {
+        let script = Script::compile(
+            "fn check(row) { #{ double: row.field_int32 * 2, note: () } }",
+        )
+        .unwrap();
+        let headers = vec![String::from("field_int32")];
+        let format = ValueFormat::default();
+
+        assert_eq!(
+            script.check(&headers, &[Field::Int(2)]),
+            Ok(Outcome {
+                violations: vec![],
+                values: vec![
+                    (String::from("double"), Value::Int(4)),
+                    (String::from("note"), Value::Null)
+                ],
+            })
+        );
+        assert_eq!(
+            rows(3, Outcome::default(), &[String::from("double")], &format),
+            vec![]
+        );
+        assert_eq!(
+            rows(
+                3,
+                Outcome {
+                    violations: vec![String::from("bad")],
+                    values: vec![],
+                },
+                &[String::from("double")],
+                &format
+            ),
+            vec![Ok(vec![
+                String::from("3"),
+                String::from("bad"),
+                String::from("null")
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_check_violations() {
+        assert!(violations(Dynamic::UNIT).is_empty());
+        assert!(violations(Dynamic::TRUE).is_empty());
+        assert_eq!(violations(Dynamic::FALSE), vec!["check failed"]);
+        assert_eq!(violations(Dynamic::from("bad")), vec!["bad"]);
+    }
+
+    #[test]
+    fn test_check_script_errors() {
+        let missing = Script::compile("fn other(row) { true }").unwrap();
+
+        assert!(Script::compile("fn check(row) {").is_err());
+        assert!(missing.check(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_check_simple_messages() {
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("msg.parquet");
+        let script = dir.path().join("check.rhai");
+        let check = |source: &str, extra: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+
+            fs::write(&script, source).unwrap();
+
+            let mut arg_vec = vec![
+                "check",
+                parquet.to_str().unwrap(),
+                "-x",
+                script.to_str().unwrap(),
+                "-f=csv",
+            ];
+
+            arg_vec.extend(extra);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+            let result = run(&args, &mut output);
+            let vec = output.into_inner();
+
+            (result, str::from_utf8(&vec).unwrap().to_string())
+        };
+
+        api::tests::write_simple_messages_parquet(
+            &parquet,
+            &api::tests::create_simple_messages(4),
+        );
+
+        assert_eq!(
+            check(
+                "fn check(row) { if row.field_boolean { row.field_string } }",
+                &[]
+            ),
+            (
+                Err(Error::CheckViolations(2)),
+                ["ROW,VIOLATION", "2,even 22222", "4,even 44444", ""].join("\n")
+            )
+        );
+
+        // rows keep their number when filtered
+        assert_eq!(
+            check(
+                "fn check(row) { row.field_int32 > 3 }",
+                &["-s", "field_int32:[34]"]
+            ),
+            (
+                Err(Error::CheckViolations(1)),
+                ["ROW,VIOLATION", "3,check failed", ""].join("\n")
+            )
+        );
+        assert_eq!(
+            check(
+                "fn check(row) { #{ half: row.field_int32 / 2 } }",
+                &["-c", "field_int32", "-l", "2"]
+            ),
+            (
+                Ok(()),
+                ["ROW,VIOLATION,half", "1,,0", "2,,1", ""].join("\n")
+            )
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::NestedFormat;
+use crate::reader::ParquetFile;
+use crate::value::{Value, ValueFormat};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::convert::TryFrom;
+use std::io::Write;
+
+static COLUMN_FORMAT_VALUES: &[&str] = &["lines", "json"];
+
+#[derive(Debug, PartialEq)]
+pub enum ColumnFormat {
+    // Raw value per line
+    Lines,
+
+    // JSON encoded value per line
+    Json,
+}
+
+impl ColumnFormat {
+    pub fn values() -> &'static [&'static str] {
+        COLUMN_FORMAT_VALUES
+    }
+}
+
+impl TryFrom<&str> for ColumnFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "lines" => Ok(ColumnFormat::Lines),
+            "json" => Ok(ColumnFormat::Json),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Write a single value, strings are written without quotes.
+#[inline]
+fn write_value<W: Write>(
+    out: &mut W,
+    value: &Value,
+    format: &ColumnFormat,
+    values: &ValueFormat,
+) -> Result<()> {
+    match (format, value) {
+        (ColumnFormat::Json, _) => writeln!(out, "{}", value.to_json_value(values))?,
+        (ColumnFormat::Lines, Value::Str(v)) => writeln!(out, "{}", v)?,
+        (ColumnFormat::Lines, _) => writeln!(out, "{}", value)?,
+    }
+
+    Ok(())
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("column")
+        .about("Print the values of a single column, one per line")
+        .arg(
+            Arg::with_name("column")
+                .help("Column to print")
+                .takes_value(true)
+                .required(true)
+                .long("column")
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .validator(args::validate_number)
+                .help("Max number of values")
+                .takes_value(true)
+                .long("limit")
+                .short('l'),
+        )
+        .arg(
+            Arg::with_name("nested")
+                .help("Nested values format")
+                .possible_values(NestedFormat::values())
+                .default_value("default")
+                .long("nested"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(ColumnFormat::values())
+                .default_value("lines")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::value_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = ColumnFormat::try_from(matches.value_of("format").unwrap_or("lines"))?;
+    let nested = args::nested_format_value(matches, "nested")?;
+    let column = args::string_values(matches, "column")?;
+    let path = args::path_value(matches, "path")?;
+    let limit = match matches.value_of("limit") {
+        Some(_) => args::usize_value(matches, "limit")?,
+        None => usize::MAX,
+    };
+    let parquet = ParquetFile::from((path, column))
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_nested(nested);
+    let names = parquet.field_names()?;
+
+    if names.len() != 1 {
+        return Err(Error::InvalidArgument(format!(
+            "Expected a single column, found {}",
+            names.len()
+        )));
+    }
+
+    let values = args::value_format_value(matches);
+
+    for row in parquet.values().take(limit) {
+        for value in row? {
+            write_value(out, &value, &format, &values)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_column_format_try_from() {
+        assert_eq!(
+            ColumnFormat::try_from("lines").unwrap(),
+            ColumnFormat::Lines
+        );
+        assert_eq!(ColumnFormat::try_from("JSON").unwrap(), ColumnFormat::Json);
+        assert!(ColumnFormat::try_from("table").is_err());
+    }
+
+    #[test]
+    fn test_column_simple_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = ["1", "2", "3", ""].join("\n");
+
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let arg_vec = vec!["column", path_str, "-c=field_int32", "-l=3"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_column_strings_unquoted() {
+        let mut lines = Cursor::new(Vec::new());
+        let mut json = Cursor::new(Vec::new());
+        let value = Value::Str(String::from("a b"));
+        let values = ValueFormat::default();
+
+        write_value(&mut lines, &value, &ColumnFormat::Lines, &values).unwrap();
+        write_value(&mut json, &value, &ColumnFormat::Json, &values).unwrap();
+
+        assert_eq!(str::from_utf8(&lines.into_inner()).unwrap(), "a b\n");
+        assert_eq!(str::from_utf8(&json.into_inner()).unwrap(), "\"a b\"\n");
+    }
+
+    #[test]
+    fn test_column_requires_single_column() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+
+        let msgs = api::tests::create_simple_messages(2);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let arg_vec = vec!["column", path_str, "-c=field_*"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_err());
+    }
+}
@@ -0,0 +1,1268 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::command::estimate::{self, ByteCounter, Estimate};
+use crate::command::schema;
+use crate::filter::Expr;
+use crate::hash::RowHash;
+use crate::reader::{ParquetFile, ROW_NUMBER_FIELD};
+use crate::value;
+use arrow::array::{BooleanArray, StringArray};
+use arrow::compute::filter_record_batch;
+use arrow::datatypes::{DataType, Field as ArrowField, Schema, SchemaRef};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use chrono::Utc;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use parquet::record::Field;
+use serde_json::json;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Export format.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    // CSV format
+    Csv,
+
+    // Newline delimited JSON format
+    Jsonl,
+
+    // Arrow IPC file format
+    Arrow,
+}
+
+const EXPORT_FORMAT_VALUES: &[&str] = &["csv", "jsonl", "arrow"];
+
+const ARROW_BATCH_SIZE: usize = 1024;
+
+const ROW_HASH_COLUMN: &str = "row_hash";
+
+const CSV_ROW_CAPACITY: usize = 256;
+
+const STDOUT_PATH: &str = "-";
+
+/// Options applied to every export format.
+#[derive(Copy, Clone, Debug, Default)]
+struct ExportOptions {
+    // Compress the output using gzip
+    gzip: bool,
+
+    // Append a hash of the row values
+    row_hash: Option<RowHash>,
+}
+
+impl ExportFormat {
+    pub fn values() -> &'static [&'static str] {
+        EXPORT_FORMAT_VALUES
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Arrow => "arrow",
+        }
+    }
+}
+
+impl TryFrom<&str> for ExportFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "arrow" => Ok(ExportFormat::Arrow),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Write the bytes of a field as a csv field.
+///
+/// String values are written as is, other values are formatted into a reused buffer
+/// to avoid allocating a string per field.
+#[inline]
+fn write_csv_field<W: Write>(
+    writer: &mut csv::Writer<W>,
+    field: &Field,
+    buffer: &mut Vec<u8>,
+) -> Result<()> {
+    match field {
+        Field::Null => writer.write_field(b"")?,
+        Field::Str(value) => writer.write_field(value.as_bytes())?,
+        _ => {
+            buffer.clear();
+
+            write!(buffer, "{}", field)?;
+
+            writer.write_field(&buffer)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_csv<W: Write>(
+    headers: &[String],
+    records: &mut dyn Iterator<Item = Result<Vec<Field>>>,
+    out: &mut W,
+) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    let mut buffer = Vec::new();
+
+    writer.write_record(headers)?;
+
+    for record in records {
+        for field in record?.iter() {
+            write_csv_field(&mut writer, field, &mut buffer)?;
+        }
+
+        writer.write_record(None::<&[u8]>)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Write a row as a json object, keys are already json encoded.
+#[inline]
+fn write_json_row<W: Write>(keys: &[String], row: &[Field], out: &mut W) -> Result<()> {
+    out.write_all(b"{")?;
+
+    for (i, field) in row.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+
+        out.write_all(keys[i].as_bytes())?;
+        out.write_all(b":")?;
+
+        match field {
+            Field::Decimal(v) => {
+                serde_json::to_writer(&mut *out, &value::format_decimal(v))?
+            }
+            _ => serde_json::to_writer(&mut *out, &field.to_json_value())?,
+        }
+    }
+
+    out.write_all(b"}\n")?;
+
+    Ok(())
+}
+
+#[inline]
+fn json_keys(headers: &[String]) -> Result<Vec<String>> {
+    let keys = headers
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(keys)
+}
+
+fn write_jsonl<W: Write>(
+    headers: &[String],
+    records: &mut dyn Iterator<Item = Result<Vec<Field>>>,
+    out: &mut W,
+) -> Result<()> {
+    // keys are encoded once and values are serialized straight into the output
+    let keys = json_keys(headers)?;
+
+    for record in records {
+        write_json_row(&keys, &record?, out)?;
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Write the batches as an arrow file, the file is still valid when there are no batches.
+fn write_arrow<W: Write>(
+    schema: &Schema,
+    batches: &mut dyn Iterator<Item = Result<RecordBatch>>,
+    out: &mut W,
+) -> Result<()> {
+    let mut writer = FileWriter::try_new(out, schema)?;
+
+    for batch in batches {
+        writer.write(&batch?)?;
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Batches of the rows matching the filter, with the row hash column appended.
+///
+/// The rows are matched by reading the records of `numbered`, the same file
+/// with row numbers, and selected from the unfiltered batches by their number.
+fn arrow_batches<'a>(
+    parquet: &'a ParquetFile,
+    numbered: &'a ParquetFile,
+    schema: SchemaRef,
+    batch_size: usize,
+    row_hash: Option<RowHash>,
+) -> Box<dyn Iterator<Item = Result<RecordBatch>> + 'a> {
+    if parquet.filter().is_none() && row_hash.is_none() {
+        return Box::new(parquet.batches(batch_size));
+    }
+
+    let mut matching = numbered.records().peekable();
+    let mut start = 0;
+    let number = |row: &[Field]| match row.first() {
+        Some(Field::ULong(n)) => Ok(*n),
+        _ => Err(Error::InvalidArgument(String::from(ROW_NUMBER_FIELD))),
+    };
+
+    Box::new(parquet.batches(batch_size).map(move |batch| {
+        let batch = batch?;
+        let end = start + batch.num_rows() as u64;
+        let mut mask = vec![false; batch.num_rows()];
+        let mut hashes = Vec::new();
+
+        while let Some(record) = matching.next_if(|r| {
+            r.as_ref()
+                .map_or(true, |row| number(row).map_or(true, |n| n <= end))
+        }) {
+            let row = record?;
+
+            mask[(number(&row)? - start - 1) as usize] = true;
+
+            if let Some(hash) = row_hash {
+                hashes.push(hash.hex(&row[1..])?);
+            }
+        }
+
+        start = end;
+
+        let batch = filter_record_batch(&batch, &BooleanArray::from(mask))?;
+
+        if row_hash.is_none() {
+            return Ok(batch);
+        }
+
+        let mut columns = batch.columns().to_vec();
+
+        columns.push(Arc::new(StringArray::from(hashes)));
+
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }))
+}
+
+/// Append the row hash column to each record.
+#[inline]
+fn with_row_hash<'a>(
+    records: impl Iterator<Item = Result<Vec<Field>>> + 'a,
+    row_hash: Option<RowHash>,
+) -> impl Iterator<Item = Result<Vec<Field>>> + 'a {
+    records.map(move |record| match (record, row_hash) {
+        (Ok(mut row), Some(hash)) => {
+            row.push(Field::Str(hash.hex(&row)?));
+
+            Ok(row)
+        }
+        (record, _) => record,
+    })
+}
+
+/// Names of the exported columns.
+#[inline]
+fn export_headers(
+    parquet: &ParquetFile,
+    row_hash: Option<RowHash>,
+) -> Result<Vec<String>> {
+    let mut headers = parquet.field_names()?;
+
+    if row_hash.is_some() {
+        headers.push(String::from(ROW_HASH_COLUMN));
+    }
+
+    Ok(headers)
+}
+
+/// Export at most `limit` rows, returning the number of rows written.
+fn export<W: Write>(
+    parquet: &ParquetFile,
+    format: ExportFormat,
+    row_hash: Option<RowHash>,
+    limit: usize,
+    out: &mut W,
+) -> Result<usize> {
+    let mut rows = 0;
+    let headers = export_headers(parquet, row_hash)?;
+
+    match format {
+        ExportFormat::Csv => {
+            let records = parquet.records().take(limit).inspect(|_| rows += 1);
+
+            write_csv(&headers, &mut with_row_hash(records, row_hash), out)?;
+        }
+        ExportFormat::Jsonl => {
+            let records = parquet.records().take(limit).inspect(|_| rows += 1);
+
+            write_jsonl(&headers, &mut with_row_hash(records, row_hash), out)?;
+        }
+        ExportFormat::Arrow => {
+            let mut schema = parquet.arrow_schema()?;
+            let batch_size = ARROW_BATCH_SIZE.min(limit).max(1);
+            let numbered = parquet.clone().with_row_numbers(true);
+
+            if row_hash.is_some() {
+                let mut fields = schema.fields().clone();
+
+                fields.push(ArrowField::new(ROW_HASH_COLUMN, DataType::Utf8, false));
+                schema = Arc::new(Schema::new(fields));
+            }
+
+            let mut batches =
+                arrow_batches(parquet, &numbered, schema.clone(), batch_size, row_hash);
+            // filtered batches come with fewer rows, the last one is cut at the limit
+            let mut batches = std::iter::from_fn(|| {
+                if rows >= limit {
+                    return None;
+                }
+
+                let batch = batches
+                    .next()?
+                    .map(|b| b.slice(0, b.num_rows().min(limit - rows)));
+
+                rows += batch.as_ref().map(|b| b.num_rows()).unwrap_or(0);
+
+                Some(batch)
+            });
+
+            write_arrow(&schema, &mut batches, out)?;
+        }
+    }
+
+    Ok(rows)
+}
+
+fn write<W: Write>(
+    parquet: &ParquetFile,
+    format: ExportFormat,
+    options: &ExportOptions,
+    limit: usize,
+    out: &mut W,
+) -> Result<usize> {
+    if !options.gzip {
+        return export(parquet, format, options.row_hash, limit, out);
+    }
+
+    let mut encoder = GzEncoder::new(out, Compression::default());
+    let rows = export(parquet, format, options.row_hash, limit, &mut encoder)?;
+
+    encoder.finish()?.flush()?;
+
+    Ok(rows)
+}
+
+/// Limits of each file of a split export.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct Split {
+    // Max number of rows per file
+    rows: Option<usize>,
+
+    // Max number of uncompressed bytes per file, including headers
+    bytes: Option<u64>,
+}
+
+impl Split {
+    fn is_enabled(&self) -> bool {
+        self.rows.is_some() || self.bytes.is_some()
+    }
+
+    fn exceeded(&self, rows: usize, bytes: u64) -> bool {
+        self.rows.map(|max| rows > max).unwrap_or(false)
+            || self.bytes.map(|max| bytes > max).unwrap_or(false)
+    }
+}
+
+/// Path of a numbered split file, eg: `out.csv.gz` becomes `out-0001.csv.gz`.
+#[inline]
+fn split_path(path: &Path, num: usize) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (stem, extension) = match name.find('.').filter(|i| *i > 0) {
+        Some(i) => name.split_at(i),
+        None => (name.as_str(), ""),
+    };
+
+    path.with_file_name(format!("{}-{:04}{}", stem, num, extension))
+}
+
+/// Encodes rows one at a time, the same way `write_csv` and `write_jsonl` do.
+struct RowEncoder {
+    format: ExportFormat,
+    keys: Vec<String>,
+    buffer: Vec<u8>,
+}
+
+impl RowEncoder {
+    fn new(format: ExportFormat, headers: &[String]) -> Result<Self> {
+        Ok(Self {
+            format,
+            keys: json_keys(headers)?,
+            buffer: Vec::new(),
+        })
+    }
+
+    #[inline]
+    fn csv_writer(out: &mut Vec<u8>) -> csv::Writer<&mut Vec<u8>> {
+        csv::WriterBuilder::new()
+            .buffer_capacity(CSV_ROW_CAPACITY)
+            .from_writer(out)
+    }
+
+    fn header(&mut self, headers: &[String]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+
+        if self.format == ExportFormat::Csv {
+            let mut writer = Self::csv_writer(&mut out);
+
+            writer.write_record(headers)?;
+            writer.flush()?;
+        }
+
+        Ok(out)
+    }
+
+    fn encode(&mut self, row: &[Field]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+
+        match self.format {
+            ExportFormat::Csv => {
+                let mut writer = Self::csv_writer(&mut out);
+
+                for field in row {
+                    write_csv_field(&mut writer, field, &mut self.buffer)?;
+                }
+
+                writer.write_record(None::<&[u8]>)?;
+                writer.flush()?;
+            }
+            _ => write_json_row(&self.keys, row, &mut out)?,
+        }
+
+        Ok(out)
+    }
+}
+
+/// File of a split export.
+enum SplitFile {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl SplitFile {
+    fn create(path: &Path, gzip: bool) -> Result<Self> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        if gzip {
+            return Ok(SplitFile::Gzip(GzEncoder::new(
+                writer,
+                Compression::default(),
+            )));
+        }
+
+        Ok(SplitFile::Plain(writer))
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        match self {
+            SplitFile::Plain(w) => w.write_all(bytes)?,
+            SplitFile::Gzip(w) => w.write_all(bytes)?,
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            SplitFile::Plain(mut w) => w.flush()?,
+            SplitFile::Gzip(w) => w.finish()?.flush()?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Export into numbered files, rolling over to the next file when a limit is reached.
+///
+/// Returns the path and number of rows of each written file.
+fn write_split(
+    parquet: &ParquetFile,
+    format: ExportFormat,
+    options: &ExportOptions,
+    split: Split,
+    path: &Path,
+) -> Result<Vec<(PathBuf, usize)>> {
+    let headers = export_headers(parquet, options.row_hash)?;
+    let mut encoder = RowEncoder::new(format, &headers)?;
+    let header = encoder.header(&headers)?;
+    let mut files = vec![(split_path(path, 1), 0)];
+    let mut file = SplitFile::create(&files[0].0, options.gzip)?;
+    let mut rows = 0;
+    let mut bytes = header.len() as u64;
+
+    file.write_all(&header)?;
+
+    for record in with_row_hash(parquet.records(), options.row_hash) {
+        let row = encoder.encode(&record?)?;
+
+        // a single row larger than the limit is still written to its own file
+        if rows > 0 && split.exceeded(rows + 1, bytes + row.len() as u64) {
+            let next = split_path(path, files.len() + 1);
+
+            file.finish()?;
+            file = SplitFile::create(&next, options.gzip)?;
+            file.write_all(&header)?;
+            files.push((next, 0));
+            rows = 0;
+            bytes = header.len() as u64;
+        }
+
+        file.write_all(&row)?;
+        rows += 1;
+        bytes += row.len() as u64;
+
+        if let Some(last) = files.last_mut() {
+            last.1 = rows;
+        }
+    }
+
+    file.finish()?;
+
+    Ok(files)
+}
+
+/// Provenance of an export, written as a json sidecar file.
+fn export_metadata(
+    parquet: &ParquetFile,
+    format: ExportFormat,
+    options: &ExportOptions,
+    filters: Option<String>,
+    rows: usize,
+    files: &[(PathBuf, usize)],
+) -> Result<serde_json::Value> {
+    let sources = parquet
+        .files()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>();
+    let outputs = files
+        .iter()
+        .map(|(path, rows)| json!({"path": path.display().to_string(), "rows": rows}))
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "xpq_version": env!("CARGO_PKG_VERSION"),
+        "created_at": Utc::now().to_rfc3339(),
+        "format": format.name(),
+        "gzip": options.gzip,
+        "row_hash": options.row_hash.map(|h| h.name()),
+        "rows": rows,
+        "columns": export_headers(parquet, options.row_hash)?,
+        "filters": filters,
+        "sources": sources,
+        "outputs": outputs,
+        "schema": schema::json_field(&parquet.schema()?),
+    }))
+}
+
+/// Predict the size of the export by writing a sample of the rows,
+/// the number of rows comes from the file metadata.
+fn estimate(
+    parquet: &ParquetFile,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> Result<Estimate> {
+    let mut header = ByteCounter::default();
+    let mut sample = ByteCounter::default();
+
+    write(parquet, format, options, 0, &mut header)?;
+
+    let sample_rows =
+        write(parquet, format, options, estimate::SAMPLE_ROWS, &mut sample)?;
+
+    Ok(Estimate {
+        rows: parquet.num_rows()?,
+        sample_rows,
+        header_bytes: header.bytes,
+        sample_bytes: sample.bytes,
+    })
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("convert")
+        .about("Convert parquet to csv/jsonl/arrow")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("to")
+                .help("Export format")
+                .possible_values(ExportFormat::values())
+                .default_value("csv")
+                .long("to")
+                .short('t'),
+        )
+        .arg(
+            Arg::with_name("output")
+                .help("Output file, defaults to stdout")
+                .takes_value(true)
+                .long("output")
+                .short('o'),
+        )
+        .arg(
+            Arg::with_name("gzip")
+                .help("Compress the output using gzip")
+                .long("gzip")
+                .short('z'),
+        )
+        .arg(
+            Arg::with_name("with-row-hash")
+                .help("Append a row_hash column hashing the row values")
+                .possible_values(RowHash::values())
+                .takes_value(true)
+                .long("with-row-hash"),
+        )
+        .arg(
+            Arg::with_name("split-rows")
+                .validator(args::validate_number)
+                .help("Split the output into numbered files of at most n rows")
+                .takes_value(true)
+                .requires("output")
+                .long("split-rows"),
+        )
+        .arg(
+            Arg::with_name("split-size")
+                .validator(args::validate_size)
+                .help("Split the output into numbered files of at most the given size, eg: 100MB")
+                .takes_value(true)
+                .requires("output")
+                .long("split-size"),
+        )
+        .arg(
+            Arg::with_name("emit-metadata")
+                .help("Write the schema, row count, filters and source files to a json file")
+                .takes_value(true)
+                .long("emit-metadata"),
+        )
+        .arg(
+            Arg::with_name("estimate")
+                .help("Estimate the output size from a sample instead of exporting")
+                .long("estimate"),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let path = args::path_value(matches, "path")?;
+    let row_hash = matches
+        .value_of("with-row-hash")
+        .map(RowHash::try_from)
+        .transpose()?;
+    let options = ExportOptions {
+        gzip: matches.is_present("gzip"),
+        row_hash,
+    };
+    let format = matches
+        .value_of("to")
+        .map(ExportFormat::try_from)
+        .unwrap_or_else(|| Err(Error::InvalidArgument(String::from("to"))))?;
+
+    let split = Split {
+        rows: matches
+            .value_of("split-rows")
+            .map(|_| args::usize_value(matches, "split-rows"))
+            .transpose()?,
+        bytes: matches
+            .value_of("split-size")
+            .map(|_| args::size_value(matches, "split-size"))
+            .transpose()?,
+    };
+
+    if format == ExportFormat::Arrow && split.is_enabled() {
+        return Err(Error::InvalidArgument(String::from("split")));
+    }
+
+    let filters = search.as_ref().map(Expr::to_string);
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(columns)
+        .with_filter(search);
+
+    if matches.is_present("estimate") {
+        return estimate::write(matches, &estimate(&parquet, format, &options)?, out);
+    }
+
+    let files = match matches.value_of("output") {
+        Some(file) if split.is_enabled() => {
+            write_split(&parquet, format, &options, split, Path::new(file))?
+        }
+        Some(file) => {
+            let mut writer = BufWriter::new(File::create(file)?);
+            let rows = write(&parquet, format, &options, usize::MAX, &mut writer)?;
+
+            writer.flush()?;
+
+            vec![(PathBuf::from(file), rows)]
+        }
+        None => {
+            let rows = write(&parquet, format, &options, usize::MAX, out)?;
+
+            vec![(PathBuf::from(STDOUT_PATH), rows)]
+        }
+    };
+
+    if let Some(file) = matches.value_of("emit-metadata") {
+        let rows = files.iter().map(|f| f.1).sum();
+
+        let metadata =
+            export_metadata(&parquet, format, &options, filters, rows, &files)?;
+        let mut writer = BufWriter::new(File::create(file)?);
+
+        serde_json::to_writer_pretty(&mut writer, &metadata)?;
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use arrow::array::Int32Array;
+    use arrow::ipc::reader::FileReader;
+    use flate2::read::GzDecoder;
+    use std::io::{Cursor, Read};
+    use std::str;
+
+    #[test]
+    fn test_convert_estimate_matches_small_exports() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let msgs = api::tests::create_simple_messages(20);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let file = ParquetFile::from(parquet.path());
+
+        for format in [ExportFormat::Csv, ExportFormat::Jsonl, ExportFormat::Arrow] {
+            let mut output = Cursor::new(Vec::new());
+            let estimate = estimate(&file, format, &ExportOptions::default()).unwrap();
+
+            write(
+                &file,
+                format,
+                &ExportOptions::default(),
+                usize::MAX,
+                &mut output,
+            )
+            .unwrap();
+
+            assert_eq!(estimate.rows, 20);
+            assert_eq!(estimate.sample_rows, 20);
+            assert_eq!(estimate.total_bytes(), output.into_inner().len() as u64);
+        }
+    }
+
+    #[test]
+    fn test_convert_estimate_output() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let arg_vec = vec!["convert", path_str, "-c=field_int32", "--estimate"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+        let lines = actual.lines().collect::<Vec<_>>();
+
+        // header + 5 values of 2 bytes
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("ROWS"));
+        assert!(lines[1].starts_with("5 "));
+        assert!(lines[1].contains(" 22 "));
+    }
+
+    #[test]
+    fn test_export_format_try_from() {
+        assert_eq!(ExportFormat::try_from("CSV"), Ok(ExportFormat::Csv));
+        assert_eq!(ExportFormat::try_from("jsonl"), Ok(ExportFormat::Jsonl));
+        assert_eq!(ExportFormat::try_from("arrow"), Ok(ExportFormat::Arrow));
+        assert_eq!(
+            ExportFormat::try_from("foo"),
+            Err(Error::InvalidArgument(String::from("foo")))
+        );
+    }
+
+    #[test]
+    fn test_convert_write_csv_quoting() {
+        let mut buff = Cursor::new(Vec::new());
+        let headers = vec![String::from("c1"), String::from("c2")];
+        let mut records = vec![
+            Ok(vec![Field::Int(1), Field::Str(String::from("a, \"b\""))]),
+            Ok(vec![Field::Null, Field::Str(String::from("c"))]),
+        ]
+        .into_iter();
+
+        write_csv(&headers, &mut records, &mut buff).unwrap();
+
+        let vec = buff.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, "c1,c2\n1,\"a, \"\"b\"\"\"\n,c\n");
+    }
+
+    #[test]
+    fn test_convert_write_jsonl_escaping() {
+        let mut buff = Cursor::new(Vec::new());
+        let headers = vec![String::from("c\"1"), String::from("c2")];
+        let mut records = vec![
+            Ok(vec![Field::Int(1), Field::Str(String::from("a\nb"))]),
+            Ok(vec![Field::Null, Field::Bool(true)]),
+        ]
+        .into_iter();
+
+        write_jsonl(&headers, &mut records, &mut buff).unwrap();
+
+        let vec = buff.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(
+            actual,
+            "{\"c\\\"1\":1,\"c2\":\"a\\nb\"}\n{\"c\\\"1\":null,\"c2\":true}\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_simple_messages_csv() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "field_int32,field_string,field_timestamp",
+            "1,odd 11111,2011-01-01 00:00:00 +00:00",
+            "2,even 22222,2012-01-01 00:00:00 +00:00",
+            "",
+        ]
+        .join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(2);
+        let arg_vec = vec![
+            "convert",
+            path_str,
+            "-c=field_int32,field_string,field_timestamp",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_convert_simple_messages_row_hash() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let hash = |v| RowHash::Sha256.hex(&[Field::Int(v)]).unwrap();
+        let expected = [
+            String::from("field_int32,row_hash"),
+            format!("1,{}", hash(1)),
+            format!("2,{}", hash(2)),
+            String::new(),
+        ]
+        .join("\n");
+
+        let msgs = api::tests::create_simple_messages(2);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let arg_vec = vec![
+            "convert",
+            path_str,
+            "-c=field_int32",
+            "--with-row-hash=sha256",
+        ];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+
+        let mut output = Cursor::new(Vec::new());
+        let arg_vec = vec![
+            "convert",
+            path_str,
+            "-t=arrow",
+            "-c=field_int32",
+            "--with-row-hash=crc32",
+        ];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        output.set_position(0);
+
+        let reader = FileReader::try_new(output, None).unwrap();
+        let schema = reader.schema();
+        let batches = reader.map(|b| b.unwrap()).collect::<Vec<_>>();
+        let hashes = batches
+            .iter()
+            .flat_map(|b| {
+                let column = b.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+
+                column
+                    .iter()
+                    .map(|v| v.unwrap().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let crc32 = |v| RowHash::Crc32.hex(&[Field::Int(v)]).unwrap();
+
+        assert_eq!(schema.field(1).name(), ROW_HASH_COLUMN);
+        assert_eq!(hashes, vec![crc32(1), crc32(2)]);
+    }
+
+    #[test]
+    fn test_convert_split_path() {
+        assert_eq!(
+            split_path(Path::new("/tmp/out.csv.gz"), 2),
+            PathBuf::from("/tmp/out-0002.csv.gz")
+        );
+        assert_eq!(split_path(Path::new("out"), 12), PathBuf::from("out-0012"));
+        assert_eq!(split_path(Path::new(".out"), 1), PathBuf::from(".out-0001"));
+    }
+
+    #[test]
+    fn test_convert_split_exceeded() {
+        let rows = Split {
+            rows: Some(2),
+            bytes: None,
+        };
+        let bytes = Split {
+            rows: None,
+            bytes: Some(10),
+        };
+
+        assert!(!Split::default().is_enabled());
+        assert!(!rows.exceeded(2, 100));
+        assert!(rows.exceeded(3, 0));
+        assert!(!bytes.exceeded(100, 10));
+        assert!(bytes.exceeded(1, 11));
+    }
+
+    #[test]
+    fn test_convert_simple_messages_split_rows() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("msg.parquet");
+        let out = dir.path().join("out.csv");
+        let path_str = parquet.to_str().unwrap();
+        let out_str = out.to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(&parquet, &msgs);
+
+        let arg_vec = vec![
+            "convert",
+            path_str,
+            "-c=field_int32",
+            "-o",
+            out_str,
+            "--split-rows=2",
+        ];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+        assert!(output.into_inner().is_empty());
+
+        let read = |n| std::fs::read_to_string(dir.path().join(n)).unwrap();
+
+        assert_eq!(read("out-0001.csv"), "field_int32\n1\n2\n");
+        assert_eq!(read("out-0002.csv"), "field_int32\n3\n4\n");
+        assert_eq!(read("out-0003.csv"), "field_int32\n5\n");
+        assert!(!dir.path().join("out-0004.csv").exists());
+    }
+
+    #[test]
+    fn test_convert_simple_messages_split_size() {
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("msg.parquet");
+        let out = dir.path().join("out.jsonl");
+        let msgs = api::tests::create_simple_messages(3);
+        let split = Split {
+            rows: None,
+            bytes: Some(40),
+        };
+
+        api::tests::write_simple_messages_parquet(&parquet, &msgs);
+
+        let columns = Some(vec![String::from("field_int32")]);
+        let file = ParquetFile::from(parquet.as_path()).with_fields(columns);
+        let options = ExportOptions::default();
+        let files =
+            write_split(&file, ExportFormat::Jsonl, &options, split, &out).unwrap();
+        let contents = files
+            .iter()
+            .map(|(p, _)| std::fs::read_to_string(p).unwrap())
+            .collect::<Vec<_>>();
+
+        // each row is 18 bytes
+        assert_eq!(files.iter().map(|f| f.1).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(
+            contents,
+            vec![
+                "{\"field_int32\":1}\n{\"field_int32\":2}\n",
+                "{\"field_int32\":3}\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_simple_messages_emit_metadata() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("msg.parquet");
+        let meta = dir.path().join("out.meta.json");
+        let path_str = parquet.to_str().unwrap();
+        let meta_str = meta.to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(&parquet, &msgs);
+
+        let arg_vec = vec![
+            "convert",
+            path_str,
+            "-c=field_int32",
+            "-s=field_int32:^[12]$",
+            "--emit-metadata",
+            meta_str,
+        ];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let contents = std::fs::read_to_string(&meta).unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&contents).unwrap();
+
+        assert_eq!(metadata["xpq_version"], json!(env!("CARGO_PKG_VERSION")));
+        assert_eq!(metadata["format"], json!("csv"));
+        assert_eq!(metadata["rows"], json!(2));
+        assert_eq!(metadata["columns"], json!(["field_int32"]));
+        assert_eq!(metadata["filters"], json!("field_int32:^[12]$"));
+        assert_eq!(metadata["sources"], json!([path_str]));
+        assert_eq!(metadata["outputs"], json!([{"path": "-", "rows": 2}]));
+        assert_eq!(metadata["schema"]["type"], json!("GROUP"));
+    }
+
+    #[test]
+    fn test_convert_simple_messages_jsonl() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "{\"field_string\":\"odd 11111\",\"field_int32\":1,\"field_boolean\":false}",
+            "{\"field_string\":\"odd 33333\",\"field_int32\":3,\"field_boolean\":false}",
+            "",
+        ]
+        .join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(3);
+        let arg_vec = vec![
+            "convert",
+            path_str,
+            "-t=jsonl",
+            "-s=field_string:odd",
+            "-c=field_string,field_int32,field_boolean",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_convert_simple_messages_arrow_output() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("msg.parquet");
+        let arrow = dir.path().join("msg.arrow");
+
+        let msgs = api::tests::create_simple_messages(3);
+
+        api::tests::write_simple_messages_parquet(&parquet, &msgs);
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "convert",
+            parquet.to_str().unwrap(),
+            "-t=arrow",
+            "-c=field_int32,field_string",
+            "-o",
+            arrow.to_str().unwrap(),
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+        assert!(output.into_inner().is_empty());
+
+        let reader = FileReader::try_new(File::open(arrow).unwrap(), None).unwrap();
+        let schema = reader.schema();
+        let batches = reader.map(|b| b.unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(schema.fields().len(), 2);
+        assert_eq!(schema.field(0).name(), "field_int32");
+        assert_eq!(schema.field(1).name(), "field_string");
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_convert_empty_file_arrow_output() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("msg.parquet");
+        let arrow = dir.path().join("msg.arrow");
+
+        api::tests::write_simple_messages_parquet(&parquet, &[]);
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "convert",
+            parquet.to_str().unwrap(),
+            "-t=arrow",
+            "-c=field_int32",
+            "-o",
+            arrow.to_str().unwrap(),
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let reader = FileReader::try_new(File::open(arrow).unwrap(), None).unwrap();
+
+        assert_eq!(reader.schema().fields().len(), 1);
+        assert_eq!(reader.schema().field(0).name(), "field_int32");
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[test]
+    fn test_convert_simple_messages_arrow_search() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(6);
+        let arg_vec = vec![
+            "convert",
+            path_str,
+            "-t=arrow",
+            "-c=field_int32",
+            "-s=field_string:odd",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_row_groups(
+            parquet.path(),
+            &[&msgs[..4], &msgs[4..]],
+        );
+
+        assert!(run(&args, &mut output).is_ok());
+
+        output.set_position(0);
+
+        let reader = FileReader::try_new(output, None).unwrap();
+        let values = reader
+            .flat_map(|b| {
+                let batch = b.unwrap();
+                let column = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int32Array>()
+                    .unwrap();
+
+                column.iter().map(|v| v.unwrap()).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(values, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn test_convert_simple_messages_gzip() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(2);
+        let arg_vec = vec!["convert", path_str, "-z", "-c=field_int32"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let mut actual = String::new();
+
+        GzDecoder::new(vec.as_slice())
+            .read_to_string(&mut actual)
+            .unwrap();
+
+        assert_eq!(actual, "field_int32\n1\n2\n");
+    }
+}
@@ -1,13 +1,36 @@
 use crate::api::Result;
 use crate::command::args;
-use crate::output::{OutputFormat, OutputWriter};
+use crate::output::OutputFormat;
 use crate::reader::ParquetFile;
+use crate::value::{Value, ValueFormat};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use std::collections::HashMap;
 use std::io::Write;
 
 pub fn def() -> App<'static> {
     SubCommand::with_name("count")
         .about("Show num of rows")
+        .arg(
+            Arg::with_name("per-file")
+                .help("Show the num of rows of each file")
+                .conflicts_with("per-row-group")
+                .long("per-file"),
+        )
+        .arg(
+            Arg::with_name("per-row-group")
+                .help("Show the num of rows of each row group")
+                .conflicts_with("by")
+                .long("per-row-group"),
+        )
+        .arg(
+            Arg::with_name("by")
+                .help("Count rows grouped by the given columns")
+                .conflicts_with("per-file")
+                .takes_value(true)
+                .multiple(true)
+                .long("by")
+                .short('b'),
+        )
         .arg(
             Arg::with_name("format")
                 .help("Output format")
@@ -23,19 +46,135 @@ pub fn def() -> App<'static> {
                 .required(true)
                 .index(1),
         )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
+        .arg(args::cache_arg())
+}
+
+const TOTAL_VALUE: &str = "TOTAL";
+
+fn count_per_file(parquet: &ParquetFile) -> Result<Vec<Vec<String>>> {
+    let mut total = 0;
+    let mut rows = Vec::new();
+
+    for result in parquet.file_num_rows() {
+        let (path, count) = result?;
+
+        total += count;
+        rows.push(vec![path.display().to_string(), count.to_string()]);
+    }
+
+    rows.push(vec![TOTAL_VALUE.to_string(), total.to_string()]);
+
+    Ok(rows)
+}
+
+fn count_per_row_group(parquet: &ParquetFile) -> Result<Vec<Vec<String>>> {
+    let mut total = 0;
+    let mut rows = Vec::new();
+
+    for result in parquet.row_group_num_rows() {
+        let (path, index, count) = result?;
+
+        total += count;
+        rows.push(vec![
+            path.display().to_string(),
+            index.to_string(),
+            count.to_string(),
+        ]);
+    }
+
+    rows.push(vec![
+        TOTAL_VALUE.to_string(),
+        String::new(),
+        total.to_string(),
+    ]);
+
+    Ok(rows)
+}
+
+/// Count rows by their formatted group values, most frequent groups first.
+fn count_by<I>(iter: I, format: &ValueFormat) -> Result<Vec<Vec<String>>>
+where
+    I: Iterator<Item = Result<Vec<Value>>>,
+{
+    let mut groups: HashMap<Vec<String>, u64> = HashMap::new();
+
+    for row in iter {
+        let key = row?.into_iter().map(|v| format.format(v)).collect();
+
+        *groups.entry(key).or_insert(0) += 1;
+    }
+
+    let mut vec = groups.into_iter().collect::<Vec<_>>();
+
+    vec.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let rows = vec
+        .into_iter()
+        .map(|(mut key, count)| {
+            key.push(count.to_string());
+            key
+        })
+        .collect();
+
+    Ok(rows)
 }
 
 pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
     let format = args::output_format_value(matches, "format")?;
     let path = args::path_value(matches, "path")?;
-    let parquet = ParquetFile::from(path);
-    let count = parquet.num_rows();
+    let by = args::string_values(matches, "by")?;
+
+    if by.is_some() {
+        let parquet = ParquetFile::from((path, by))
+            .with_discovery(args::discovery_value(matches))
+            .with_strict(matches.is_present("strict"));
+        let mut headers = parquet.field_names()?;
+        let rows = count_by(parquet.values(), &args::value_format_value(matches))?;
+
+        headers.push(String::from("COUNT"));
+
+        let mut writer =
+            args::writer(matches, headers, rows.into_iter().map(Ok)).format(format);
+
+        return writer.write(out);
+    }
+
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+
+    if matches.is_present("per-file") {
+        let headers = vec![String::from("FILE"), String::from("COUNT")];
+        let rows = count_per_file(&parquet)?;
+        let mut writer =
+            args::writer(matches, headers, rows.into_iter().map(Ok)).format(format);
+
+        return writer.write(out);
+    }
+
+    if matches.is_present("per-row-group") {
+        let headers = vec![
+            String::from("FILE"),
+            String::from("ROW_GROUP"),
+            String::from("COUNT"),
+        ];
+        let rows = count_per_row_group(&parquet)?;
+        let mut writer =
+            args::writer(matches, headers, rows.into_iter().map(Ok)).format(format);
+
+        return writer.write(out);
+    }
+
+    let count = parquet.num_rows()?;
 
     let headers = vec![String::from("COUNT")];
     let values = vec![Ok(vec![format!("{}", count)])];
 
     let iter = values.into_iter();
-    let mut writer = OutputWriter::new(headers, iter).format(format);
+    let mut writer = args::writer(matches, headers, iter).format(format);
 
     writer.write(out)
 }
@@ -68,6 +207,160 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_count_simple_messages_per_file() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.parquet");
+        let path2 = dir.path().join("2.parquet");
+        let msgs = api::tests::create_simple_messages(3);
+
+        api::tests::write_simple_messages_parquet(&path1, &msgs[0..1]);
+        api::tests::write_simple_messages_parquet(&path2, &msgs[1..3]);
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "count",
+            dir.path().to_str().unwrap(),
+            "--per-file",
+            "-f=csv",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(4, actual.lines().count());
+        assert!(actual.starts_with("FILE,COUNT\n"));
+        assert!(actual.contains(&format!("{},1\n", path1.display())));
+        assert!(actual.contains(&format!("{},2\n", path2.display())));
+        assert!(actual.ends_with("TOTAL,3\n"));
+    }
+
+    #[test]
+    fn test_count_discovery_args() {
+        let dir = api::tests::temp_dir();
+        let msgs = api::tests::create_simple_messages(3);
+        let count = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec!["count", dir.path().to_str().unwrap(), "-f=csv"];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output).unwrap();
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+
+        api::tests::write_simple_messages_parquet(
+            &dir.path().join("1.parquet"),
+            &msgs[..1],
+        );
+        api::tests::write_simple_messages_parquet(&dir.path().join("2.pq"), &msgs[1..]);
+
+        assert_eq!(count(&[]), "COUNT\n1\n");
+        assert_eq!(count(&["--ext", "pq"]), "COUNT\n2\n");
+        assert_eq!(count(&["--ext", "pq,parquet"]), "COUNT\n3\n");
+    }
+
+    #[test]
+    fn test_count_strict() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(2);
+        let count = |args: Vec<&str>| {
+            let mut output = Cursor::new(Vec::new());
+            let args = def().get_matches_from_safe(args).unwrap();
+
+            run(&args, &mut output)
+                .map(|_| String::from_utf8(output.into_inner()).unwrap())
+        };
+
+        api::tests::write_simple_messages_parquet(&dir.path().join("1.parquet"), &msgs);
+        std::fs::write(dir.path().join("2.parquet"), b"corrupt").unwrap();
+
+        assert_eq!(
+            count(vec!["count", path, "-f=csv"]),
+            Ok(String::from("COUNT\n2\n"))
+        );
+        assert!(count(vec!["count", path, "--strict"]).is_err());
+    }
+
+    #[test]
+    fn test_count_simple_messages_per_row_group() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+        let expected = [
+            "FILE,ROW_GROUP,COUNT",
+            &format!("{},0,2", path_str),
+            &format!("{},1,3", path_str),
+            "TOTAL,,5",
+            "",
+        ]
+        .join("\n");
+
+        api::tests::write_simple_messages_row_groups(
+            parquet.path(),
+            &[&msgs[0..2], &msgs[2..5]],
+        );
+
+        let subcomand = def();
+        let arg_vec = vec!["count", path_str, "--per-row-group", "-f=csv"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_count_simple_messages_by() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+        let expected = ["field_boolean,COUNT", "false,3", "true,2", ""].join("\n");
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let subcomand = def();
+        let arg_vec = vec!["count", path_str, "--by=field_boolean", "-f=csv"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_count_by_multiple_columns() {
+        let rows = vec![
+            Ok(vec![Value::Bool(true), Value::Int(1)]),
+            Ok(vec![Value::Bool(false), Value::Int(1)]),
+            Ok(vec![Value::Bool(true), Value::Int(1)]),
+        ];
+
+        assert_eq!(
+            count_by(rows.into_iter(), &ValueFormat::default()),
+            Ok(vec![
+                vec![String::from("true"), String::from("1"), String::from("2")],
+                vec![String::from("false"), String::from("1"), String::from("1")],
+            ])
+        );
+    }
+
     #[test]
     fn test_count_simple_messages_vertical_format() {
         let mut output = Cursor::new(Vec::new());
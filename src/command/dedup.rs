@@ -0,0 +1,300 @@
+use crate::api::{Error, Result};
+use crate::command::{args, estimate};
+use crate::hash;
+use crate::output::OutputFormat;
+use crate::reader::{ParquetFile, ParquetSource};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Column chunks with the same compressed bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Duplicate {
+    columns: BTreeSet<String>,
+    files: BTreeSet<PathBuf>,
+    hash: String,
+    size: u64,
+    copies: u64,
+}
+
+impl Duplicate {
+    /// Bytes a storage layout keeping a single copy of the chunk would save.
+    fn saved(&self) -> u64 {
+        self.size * (self.copies - 1)
+    }
+}
+
+/// Identical chunks of every file and the total number and size of the chunks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Report {
+    chunks: u64,
+    bytes: u64,
+    duplicates: Vec<Duplicate>,
+}
+
+impl Report {
+    fn saved(&self) -> u64 {
+        self.duplicates.iter().map(Duplicate::saved).sum()
+    }
+}
+
+/// Whether the column path is one of the columns or one of their children.
+#[inline]
+fn is_selected(columns: &Option<Vec<String>>, path: &str) -> bool {
+    match columns {
+        None => true,
+        Some(columns) => columns.iter().any(|c| {
+            let path = path.to_lowercase();
+            let column = c.to_lowercase();
+
+            path == column || path.starts_with(&format!("{}.", column))
+        }),
+    }
+}
+
+/// Hash the compressed bytes of each column chunk of the files,
+/// chunks are read one at a time and never decoded.
+fn find_duplicates<I>(files: I, columns: &Option<Vec<String>>) -> Result<Report>
+where
+    I: Iterator<Item = PathBuf>,
+{
+    let mut report = Report::default();
+    let mut chunks: HashMap<(String, u64), Duplicate> = HashMap::new();
+
+    for path in files {
+        let error = |e| Error::Parquet(path.to_path_buf(), e);
+        let source = ParquetSource::open(&path).map_err(error)?;
+        let reader =
+            SerializedFileReader::new(ParquetSource::open(&path).map_err(error)?)
+                .map_err(error)?;
+
+        for row_group in reader.metadata().row_groups() {
+            for column in row_group.columns() {
+                let name = column.column_path().string();
+
+                if !is_selected(columns, &name) {
+                    continue;
+                }
+
+                let (start, length) = column.byte_range();
+                let bytes = source.get_bytes(start, length as usize).map_err(error)?;
+                let hash = hash::to_hex(&hash::sha256(&bytes));
+                let duplicate =
+                    chunks
+                        .entry((hash.clone(), length))
+                        .or_insert_with(|| Duplicate {
+                            columns: BTreeSet::new(),
+                            files: BTreeSet::new(),
+                            hash,
+                            size: length,
+                            copies: 0,
+                        });
+
+                duplicate.columns.insert(name);
+                duplicate.files.insert(path.to_path_buf());
+                duplicate.copies += 1;
+
+                report.chunks += 1;
+                report.bytes += length;
+            }
+        }
+    }
+
+    report.duplicates = chunks.into_values().filter(|d| d.copies > 1).collect();
+    report.duplicates.sort_by(|a, b| {
+        b.saved()
+            .cmp(&a.saved())
+            .then_with(|| a.columns.cmp(&b.columns))
+            .then_with(|| a.hash.cmp(&b.hash))
+    });
+
+    Ok(report)
+}
+
+#[inline]
+fn percent(part: u64, total: u64) -> String {
+    match total {
+        0 => format!("{:.2}", 0.0),
+        total => format!("{:.2}", part as f64 * 100.0 / total as f64),
+    }
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("dedup")
+        .about("Report identical column chunks and the space a dedup-aware storage would save")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("summary")
+                .help("Write a single row with the total and duplicated bytes")
+                .long("summary"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+/// Chunks are compared by their compressed bytes, including the page headers,
+/// so only chunks written with the same encoding and compression match.
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let path = args::path_value(matches, "path")?;
+    let report = find_duplicates(
+        ParquetFile::from(path)
+            .with_discovery(args::discovery_value(matches))
+            .with_strict(matches.is_present("strict"))
+            .files(),
+        &columns,
+    )?;
+
+    if matches.is_present("summary") {
+        let saved = report.saved();
+        let headers = vec![
+            String::from("CHUNKS"),
+            String::from("BYTES"),
+            String::from("DUPLICATES"),
+            String::from("SAVED_BYTES"),
+            String::from("SAVED_SIZE"),
+            String::from("PERCENT"),
+        ];
+        let row = vec![
+            report.chunks.to_string(),
+            report.bytes.to_string(),
+            report.duplicates.len().to_string(),
+            saved.to_string(),
+            estimate::format_size(saved),
+            percent(saved, report.bytes),
+        ];
+
+        let mut writer =
+            args::writer(matches, headers, std::iter::once(Ok(row))).format(format);
+
+        return writer.write(out);
+    }
+
+    let headers = vec![
+        String::from("COLUMNS"),
+        String::from("HASH"),
+        String::from("BYTES"),
+        String::from("COPIES"),
+        String::from("FILES"),
+        String::from("SAVED_BYTES"),
+    ];
+    let rows = report.duplicates.into_iter().map(|d| {
+        Ok(vec![
+            d.columns.iter().cloned().collect::<Vec<_>>().join(","),
+            d.hash[..16].to_string(),
+            d.size.to_string(),
+            d.copies.to_string(),
+            d.files.len().to_string(),
+            d.saved().to_string(),
+        ])
+    });
+
+    let mut writer = args::writer(matches, headers, rows).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::Path;
+    use std::str;
+
+    fn dedup(path: &Path, args: &[&str]) -> String {
+        let mut output = Cursor::new(Vec::new());
+        let mut arg_vec = vec!["dedup", path.to_str().unwrap(), "-f=csv"];
+
+        arg_vec.extend_from_slice(args);
+
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        run(&args, &mut output).unwrap();
+
+        String::from_utf8(output.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_dedup_copied_files() {
+        let dir = api::tests::temp_dir();
+        let original = dir.path().join("1.parquet");
+        let other = dir.path().join("3.parquet");
+
+        api::tests::write_simple_messages_parquet(
+            &original,
+            &api::tests::create_simple_messages(3),
+        );
+        api::tests::write_simple_messages_parquet(
+            &other,
+            &api::tests::create_simple_messages(5),
+        );
+        fs::copy(&original, dir.path().join("2.parquet")).unwrap();
+
+        let report =
+            find_duplicates(ParquetFile::from(dir.path()).files(), &None).unwrap();
+        let copied = find_duplicates(std::iter::once(original.clone()), &None).unwrap();
+
+        // every chunk of the copied file is duplicated once
+        assert_eq!(report.chunks, copied.chunks * 3);
+        assert_eq!(report.saved(), copied.bytes);
+        assert!(report.duplicates.iter().all(|d| d.copies == 2));
+        assert!(copied.duplicates.is_empty());
+
+        let lines = dedup(dir.path(), &["-c=field_int32"])
+            .lines()
+            .map(|l| l.split(',').map(String::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            ["COLUMNS", "HASH", "BYTES", "COPIES", "FILES", "SAVED_BYTES"]
+        );
+        assert_eq!(lines[1][0], "field_int32");
+        assert_eq!(lines[1][3..5], ["2", "2"]);
+
+        let summary = dedup(dir.path(), &["--summary"]);
+        let summary = summary
+            .lines()
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .collect::<Vec<_>>();
+
+        assert_eq!(summary[0], report.chunks.to_string());
+        assert_eq!(summary[3], copied.bytes.to_string());
+    }
+
+    #[test]
+    fn test_dedup_percent() {
+        assert_eq!(percent(1, 4), "25.00");
+        assert_eq!(percent(0, 0), "0.00");
+    }
+}
@@ -0,0 +1,225 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::NestedFormat;
+use crate::reader::ParquetFile;
+use crate::value::{Value, ValueFormat};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Distinct values of a column, nulls are recorded apart from the values.
+#[derive(Clone, Debug, Default)]
+struct Domain {
+    values: HashMap<String, Value>,
+    nullable: bool,
+}
+
+impl Domain {
+    /// Json of the domain with its values sorted, eg: `{"nullable": false, "values": [..]}`.
+    fn to_json_value(&self, format: &ValueFormat) -> serde_json::Value {
+        let mut values = self.values.values().collect::<Vec<_>>();
+
+        values.sort_by(|a, b| a.total_cmp(b));
+
+        json!({
+            "nullable": self.nullable,
+            "values": values.iter().map(|v| v.to_json_value(format)).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Distinct values of every column, failing once a column exceeds `max_distinct` values.
+fn domains(
+    parquet: &ParquetFile,
+    headers: &[String],
+    max_distinct: usize,
+) -> Result<Vec<Domain>> {
+    let mut domains = vec![Domain::default(); headers.len()];
+
+    for row in parquet.values() {
+        for (i, value) in row?.into_iter().enumerate() {
+            let domain = &mut domains[i];
+
+            if value.is_null() {
+                domain.nullable = true;
+                continue;
+            }
+
+            domain
+                .values
+                .entry(value.to_json_value(&ValueFormat::default()).to_string())
+                .or_insert(value);
+
+            if domain.values.len() > max_distinct {
+                return Err(Error::InvalidArgument(format!(
+                    "Column '{}' has more than {} distinct values, see --max-distinct",
+                    headers[i], max_distinct
+                )));
+            }
+        }
+    }
+
+    Ok(domains)
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("domain")
+        .about("Export the distinct values of low cardinality columns as json")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("max-distinct")
+                .validator(args::validate_number)
+                .help("Max number of distinct values of a column")
+                .default_value("1000")
+                .long("max-distinct"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .help("Path of the json file, written to stdout when not given")
+                .takes_value(true)
+                .long("output")
+                .short('o'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::value_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let max_distinct = args::usize_value(matches, "max-distinct")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(columns)
+        .with_nested(NestedFormat::Json)
+        .with_filter(search);
+    let headers = parquet.field_names()?;
+    let domains = domains(&parquet, &headers, max_distinct)?;
+    let values = args::value_format_value(matches);
+    let json = headers
+        .iter()
+        .zip(&domains)
+        .map(|(name, domain)| (name.to_string(), domain.to_json_value(&values)))
+        .collect::<serde_json::Map<_, _>>();
+
+    match matches.value_of("output") {
+        Some(file) => {
+            let mut writer = BufWriter::new(File::create(file)?);
+
+            serde_json::to_writer_pretty(&mut writer, &json)?;
+            writeln!(writer)?;
+            writer.flush()?;
+
+            writeln!(out, "{} columns written to {}", headers.len(), file)?;
+        }
+        None => {
+            serde_json::to_writer_pretty(&mut *out, &json)?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::fs;
+    use std::str;
+
+    #[test]
+    fn test_domain_columns() {
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("users.parquet");
+        let output = dir.path().join("domains.json");
+        let mut out = Vec::new();
+
+        api::tests::write_json_parquet(
+            &parquet,
+            &[
+                r#"{"id": 1, "status": "active", "level": 10}"#,
+                r#"{"id": 2, "status": null, "level": 2}"#,
+                r#"{"id": 3, "status": "deleted", "level": 10}"#,
+                r#"{"id": 4, "status": "active", "level": 2}"#,
+            ],
+        );
+
+        let args = def()
+            .get_matches_from_safe([
+                "domain",
+                parquet.to_str().unwrap(),
+                "-c=status,level",
+                "-o",
+                output.to_str().unwrap(),
+            ])
+            .unwrap();
+
+        run(&args, &mut out).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&fs::read(&output).unwrap()).unwrap();
+
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            format!("2 columns written to {}\n", output.display())
+        );
+        assert_eq!(
+            json,
+            json!({
+                "status": {"nullable": true, "values": ["active", "deleted"]},
+                "level": {"nullable": false, "values": [2, 10]},
+            })
+        );
+    }
+
+    #[test]
+    fn test_domain_max_distinct() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let domain = |args: &[&str]| {
+            let mut out = Vec::new();
+            let mut arg_vec = vec!["domain", path_str, "-c=field_int32,field_boolean"];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut out).map(|_| out)
+        };
+        let out = domain(&[]).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(json["field_int32"]["values"], json!([1, 2, 3, 4, 5]));
+        assert_eq!(json["field_boolean"]["values"], json!([false, true]));
+        assert_eq!(
+            domain(&["--max-distinct=4"]),
+            Err(Error::InvalidArgument(String::from(
+                "Column 'field_int32' has more than 4 distinct values, see --max-distinct"
+            )))
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::api::Result;
+use crate::command::args;
+use clap::ArgMatches;
+use std::io::{self, Write};
+
+/// Number of rows written to estimate the size of an output.
+pub const SAMPLE_ROWS: usize = 1000;
+
+/// Writer discarding its input, only counting the written bytes.
+#[derive(Debug, Default)]
+pub struct ByteCounter {
+    pub bytes: u64,
+}
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len() as u64;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Output size predicted from a sample of rows.
+#[derive(Debug, PartialEq)]
+pub struct Estimate {
+    // Number of rows of the full output
+    pub rows: usize,
+
+    // Number of rows written by the sample
+    pub sample_rows: usize,
+
+    // Bytes written with no rows, eg: csv headers
+    pub header_bytes: u64,
+
+    // Bytes written by the sample, including the headers
+    pub sample_bytes: u64,
+}
+
+impl Estimate {
+    /// Average size of a row in the sample.
+    pub fn row_bytes(&self) -> f64 {
+        match self.sample_rows {
+            0 => 0.0,
+            n => self.sample_bytes.saturating_sub(self.header_bytes) as f64 / n as f64,
+        }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.header_bytes + (self.row_bytes() * self.rows as f64).round() as u64
+    }
+}
+
+/// Human readable size, eg: `1.5 MiB`.
+#[inline]
+pub fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} {}", bytes, units[unit]),
+        _ => format!("{:.1} {}", size, units[unit]),
+    }
+}
+
+/// Write the estimate as a single table row.
+pub fn write<W: Write>(
+    matches: &ArgMatches,
+    estimate: &Estimate,
+    out: &mut W,
+) -> Result<()> {
+    let headers = vec![
+        String::from("ROWS"),
+        String::from("SAMPLE_ROWS"),
+        String::from("ROW_BYTES"),
+        String::from("ESTIMATED_BYTES"),
+        String::from("ESTIMATED_SIZE"),
+    ];
+    let row = vec![
+        estimate.rows.to_string(),
+        estimate.sample_rows.to_string(),
+        format!("{:.1}", estimate.row_bytes()),
+        estimate.total_bytes().to_string(),
+        format_size(estimate.total_bytes()),
+    ];
+
+    args::writer(matches, headers, std::iter::once(Ok(row))).write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_total_bytes() {
+        let estimate = Estimate {
+            rows: 100,
+            sample_rows: 10,
+            header_bytes: 5,
+            sample_bytes: 45,
+        };
+
+        assert_eq!(estimate.row_bytes(), 4.0);
+        assert_eq!(estimate.total_bytes(), 405);
+
+        let empty = Estimate {
+            rows: 0,
+            sample_rows: 0,
+            header_bytes: 5,
+            sample_bytes: 5,
+        };
+
+        assert_eq!(empty.total_bytes(), 5);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(10), "10 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_byte_counter() {
+        let mut counter = ByteCounter::default();
+
+        write!(counter, "abc").unwrap();
+        counter.write_all(b"de").unwrap();
+
+        assert_eq!(counter.bytes, 5);
+    }
+}
@@ -0,0 +1,444 @@
+use crate::api::{Error, Result};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::OnceLock;
+
+/// Example invocation of a command.
+pub struct Example {
+    // Name of the command
+    pub command: &'static str,
+
+    // What the example does
+    pub description: &'static str,
+
+    // Arguments after the command name, quoted like a shell command line
+    pub args: &'static str,
+}
+
+impl Example {
+    /// Full command line, eg: `xpq read users.parquet -l 10`.
+    pub fn line(&self) -> String {
+        format!("xpq {} {}", self.command, self.args)
+    }
+}
+
+/// Examples of every command, they run against `users.parquet`, `users-v2.parquet`,
+/// the `users/` directory, `users.csv`, `columns.yaml` and `check.rhai`.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        command: "read",
+        description: "Read the first 10 rows as csv",
+        args: "users.parquet -l 10 -f csv",
+    },
+    Example {
+        command: "read",
+        description: "Select columns of the rows matching every filter",
+        args: "users.parquet -c id,name -s 'age>=30' -s country=BR",
+    },
+    Example {
+        command: "read",
+        description: "Read the 10 oldest users",
+        args: "users.parquet --order-by age:desc -l 10",
+    },
+    Example {
+        command: "read",
+        description: "Regexes match the value without quotes",
+        args: "users.parquet -s 'name:^(Ana|Ben)$'",
+    },
+    Example {
+        command: "read",
+        description: "Skip rows matching a filter, keep rows matching any other",
+        args:
+            "users.parquet --search-not status:^deleted$ --search-any country=BR 'age<18'",
+    },
+    Example {
+        command: "read",
+        description: "Filter rows with a SQL-like clause",
+        args: "users.parquet --where \"age > 30 AND country IN ('BR', 'PT')\"",
+    },
+    Example {
+        command: "read",
+        description: "Estimate the size of a csv output",
+        args: "users.parquet -f csv --estimate",
+    },
+    Example {
+        command: "read",
+        description: "Number the rows matching a search to reference them",
+        args: "users/ --row-numbers -s country=BR",
+    },
+    Example {
+        command: "browse",
+        description: "Browse the active users, press / to search and s for the schema",
+        args: "users.parquet -s status=active",
+    },
+    Example {
+        command: "query",
+        description: "Select, filter and sort rows with SQL",
+        args: "\"SELECT name, age FROM 'users.parquet' WHERE age > 18 ORDER BY age DESC LIMIT 10\"",
+    },
+    Example {
+        command: "query",
+        description: "Count rows of each country of a directory",
+        args: "\"SELECT country, COUNT(*) FROM 'users/' GROUP BY country ORDER BY COUNT(*) DESC\"",
+    },
+    Example {
+        command: "count",
+        description: "Count rows grouped by a column",
+        args: "users.parquet --by country",
+    },
+    Example {
+        command: "count",
+        description: "Count the rows of each file of a directory",
+        args: "users/ --per-file",
+    },
+    Example {
+        command: "ls",
+        description: "List the files of a directory with their size and rows",
+        args: "users/",
+    },
+    Example {
+        command: "partitions",
+        description: "Show the files and rows of each partition of a dataset",
+        args: "users/ --depth 1",
+    },
+    Example {
+        command: "schema",
+        description: "Show the schema as json",
+        args: "users.parquet -f json",
+    },
+    Example {
+        command: "schema",
+        description: "Show the description and owner of each column",
+        args: "users.parquet --describe --docs columns.yaml",
+    },
+    Example {
+        command: "schema-diff",
+        description: "Show the fields changed between two files",
+        args: "users.parquet users-v2.parquet",
+    },
+    Example {
+        command: "validate-schema",
+        description: "Compare the schema of every file of a directory",
+        args: "users/",
+    },
+    Example {
+        command: "verify",
+        description: "Verify the footer and pages of every file",
+        args: "users/",
+    },
+    Example {
+        command: "sample",
+        description: "Sample 5 rows, the same ones on every run",
+        args: "users.parquet -s 5 --seed 42",
+    },
+    Example {
+        command: "sample-diff",
+        description: "Sample users removed, added or changed between two versions",
+        args: "users.parquet users-v2.parquet --key id -s 5",
+    },
+    Example {
+        command: "frequency",
+        description: "Show the 3 most frequent values of a column",
+        args: "users.parquet -c country --top 3",
+    },
+    Example {
+        command: "frequency",
+        description: "Count the email domains of active users",
+        args: "users.parquet -c email -t email:domain -s status=active",
+    },
+    Example {
+        command: "frequency",
+        description: "Approximate the most frequent countries of every row",
+        args: "users/ -c country --approx --top 3",
+    },
+    Example {
+        command: "domain",
+        description: "Export the distinct values of columns to generate enums",
+        args: "users.parquet -c country --max-distinct 50",
+    },
+    Example {
+        command: "stats",
+        description: "Show statistics of some columns",
+        args: "users.parquet -c age,country",
+    },
+    Example {
+        command: "find-row",
+        description: "Find the row of a sha256 row hash, eg: from a log line",
+        args: "users.parquet --hash 6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b",
+    },
+    Example {
+        command: "dedup",
+        description: "Show the identical column chunks of a dataset",
+        args: "users/",
+    },
+    Example {
+        command: "dedup",
+        description: "Estimate the space a dedup-aware storage would save",
+        args: "users/ --summary",
+    },
+    Example {
+        command: "nulls",
+        description: "Show the null count of each column",
+        args: "users.parquet",
+    },
+    Example {
+        command: "nulls",
+        description: "Count the nulls of the rows missing an email",
+        args: "users.parquet -c name -s email:NULL",
+    },
+    Example {
+        command: "outliers",
+        description: "Show rows with an unusual age",
+        args: "users.parquet -c age -m zscore -t 2",
+    },
+    Example {
+        command: "scan-pii",
+        description: "Report columns holding emails or phone numbers",
+        args: "users.parquet -d email phone",
+    },
+    Example {
+        command: "charset",
+        description: "Show the character sets of a column",
+        args: "users.parquet -c name",
+    },
+    Example {
+        command: "suggest-types",
+        description: "List the casts of text columns that always parse as another type",
+        args: "users.parquet --casts",
+    },
+    Example {
+        command: "check",
+        description: "Report rows failing the checks of a script",
+        args: "users.parquet -x check.rhai",
+    },
+    Example {
+        command: "head",
+        description: "Show the first 3 rows vertically",
+        args: "users.parquet -l 3 -f vertical",
+    },
+    Example {
+        command: "tail",
+        description: "Show the last 3 rows",
+        args: "users.parquet -l 3",
+    },
+    Example {
+        command: "column",
+        description: "Print the emails as a json array",
+        args: "users.parquet -c email -f json",
+    },
+    Example {
+        command: "tokens",
+        description: "Count the words of a column",
+        args: "users.parquet -c name -t 10",
+    },
+    Example {
+        command: "convert",
+        description: "Export active users to gzipped jsonl",
+        args: "users.parquet -t jsonl -z -s status=active -o active.jsonl.gz",
+    },
+    Example {
+        command: "convert",
+        description: "Export to csv files of at most 1000 rows",
+        args: "users.parquet --split-rows 1000 -o users.csv",
+    },
+    Example {
+        command: "export",
+        description: "Export active users to a SQLite table",
+        args: "users.parquet -s status=active -o users.sqlite -t users",
+    },
+    Example {
+        command: "write",
+        description: "Write a csv file to parquet",
+        args: "users.csv -o users-copy.parquet",
+    },
+    Example {
+        command: "undo",
+        description: "Restore the parquet files replaced by the last write of a directory",
+        args: "users/",
+    },
+    Example {
+        command: "alias",
+        description: "Save an alias, run it with `xpq failed users.parquet`",
+        args: "save failed 'read -s status:^failed$ -c id,name'",
+    },
+    Example {
+        command: "examples",
+        description: "Show the examples of a command",
+        args: "read",
+    },
+];
+
+/// Examples of a command.
+pub fn command_examples(command: &str) -> impl Iterator<Item = &'static Example> + '_ {
+    EXAMPLES.iter().filter(move |e| e.command == command)
+}
+
+/// Examples written as text, one `# <description>` line before each command line.
+fn format_examples<'a>(
+    examples: impl Iterator<Item = &'a Example>,
+    indent: &str,
+) -> String {
+    examples
+        .map(|e| format!("{}# {}\n{}{}\n", indent, e.description, indent, e.line()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Examples section appended to the `--help` of a command.
+pub fn help(command: &str) -> Option<&'static str> {
+    static HELP: OnceLock<HashMap<&'static str, String>> = OnceLock::new();
+
+    let help = HELP.get_or_init(|| {
+        let mut help = HashMap::new();
+
+        for example in EXAMPLES {
+            help.entry(example.command).or_insert_with(|| {
+                let text = format_examples(command_examples(example.command), "    ");
+
+                format!("EXAMPLES:\n{}", text.trim_end())
+            });
+        }
+
+        help
+    });
+
+    help.get(command).map(String::as_str)
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("examples")
+        .about("Show example invocations of every command")
+        .arg(
+            Arg::with_name("command")
+                .help("Only show the examples of this command")
+                .index(1),
+        )
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let text = match matches.value_of("command") {
+        Some(command) => {
+            let examples = command_examples(command).collect::<Vec<_>>();
+
+            if examples.is_empty() {
+                return Err(Error::InvalidArgument(command.to_string()));
+            }
+
+            format_examples(examples.into_iter(), "")
+        }
+        None => format_examples(EXAMPLES.iter(), ""),
+    };
+
+    write!(out, "{}", text)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::command;
+    use crate::config;
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::Path;
+    use std::str;
+
+    const USERS: &[&str] = &[
+        r#"{"id": 1, "name": "Ana", "email": "ana@example.com", "age": 34, "country": "BR", "status": "active"}"#,
+        r#"{"id": 2, "name": "Ben", "email": "ben@example.org", "age": 17, "country": "PT", "status": "failed"}"#,
+        r#"{"id": 3, "name": "Caio", "email": "caio@example.com", "age": 29, "country": "BR", "status": "deleted"}"#,
+        r#"{"id": 4, "name": "Dora", "email": "dora@example.net", "age": 81, "country": "US", "status": "active"}"#,
+    ];
+
+    /// Files used by the examples.
+    fn write_example_files(dir: &Path) {
+        let users = dir.join("users");
+
+        fs::create_dir(&users).unwrap();
+
+        api::tests::write_json_parquet(&dir.join("users.parquet"), USERS);
+        api::tests::write_json_parquet(&dir.join("users-v2.parquet"), &USERS[..1]);
+        api::tests::write_json_parquet(&users.join("part-0.parquet"), &USERS[..2]);
+        api::tests::write_json_parquet(&users.join("part-1.parquet"), &USERS[2..]);
+
+        fs::write(dir.join("users.csv"), "id,name\n1,Ana\n2,Ben\n").unwrap();
+        fs::write(dir.join("columns.yaml"), "id:\n  description: User id\n").unwrap();
+        fs::write(dir.join("check.rhai"), "fn check(row) { row.age < 80 }").unwrap();
+    }
+
+    #[test]
+    fn test_examples_command() {
+        let mut output = Cursor::new(Vec::new());
+        let args = def().get_matches_from_safe(vec!["examples", "tail"]);
+
+        assert!(run(&args.unwrap(), &mut output).is_ok());
+        assert_eq!(
+            str::from_utf8(&output.into_inner()).unwrap(),
+            "# Show the last 3 rows\nxpq tail users.parquet -l 3\n"
+        );
+
+        let args = def().get_matches_from_safe(vec!["examples", "unknown"]);
+
+        assert_eq!(
+            run(&args.unwrap(), &mut Cursor::new(Vec::new())),
+            Err(Error::InvalidArgument(String::from("unknown")))
+        );
+    }
+
+    #[test]
+    fn test_examples_help() {
+        let text = help("verify").unwrap();
+
+        assert_eq!(
+            text,
+            "EXAMPLES:\n    # Verify the footer and pages of every file\n    xpq verify users/"
+        );
+        assert!(help("unknown").is_none());
+        assert!(command::defs()
+            .iter()
+            .all(|c| command_examples(c.get_name()).next().is_some()));
+    }
+
+    #[test]
+    fn test_examples_run() {
+        let dir = api::tests::temp_dir();
+
+        write_example_files(dir.path());
+
+        // aliases update the user config and browse needs a terminal
+        let skip = ["alias", "browse"];
+
+        for example in EXAMPLES.iter().filter(|e| !skip.contains(&e.command)) {
+            let in_dir = |arg: String| {
+                if arg.starts_with("SELECT ") {
+                    return arg
+                        .replace("FROM '", &format!("FROM '{}/", dir.path().display()));
+                }
+
+                match arg.ends_with('/') || arg.contains('.') {
+                    true => dir.path().join(arg).display().to_string(),
+                    false => arg,
+                }
+            };
+            let mut args = vec![String::from("xpq"), example.command.to_string()];
+
+            args.extend(config::split_args(example.args).into_iter().map(in_dir));
+
+            let app = App::new("xpq").subcommands(command::defs());
+            let matches = app.get_matches_from_safe(args);
+            let matches = matches.unwrap_or_else(|e| panic!("{}: {}", example.line(), e));
+            let result = command::run(&matches, &mut Cursor::new(Vec::new()));
+
+            // examples may report what they find, eg: the rows failing a check
+            assert!(
+                result.as_ref().err().is_none_or(api::Error::is_finding),
+                "{}: {:?}",
+                example.line(),
+                result
+            );
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::{NestedFormat, OutputFile, WriteLock};
+use crate::reader::ParquetFile;
+use crate::snapshot::Snapshot;
+use crate::sqlite::{self, TableWriter};
+use crate::time::{self, TimeFormat};
+use crate::value::Value;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::convert::TryFrom;
+use std::io::{Seek, Write};
+use std::path::Path;
+
+/// Timestamps are written in UTC, in the format understood by the SQLite date functions.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Declared type of a column holding the value, `None` for nulls.
+#[inline]
+fn column_type(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) | Value::Int(_) | Value::UInt(_) => Some("INTEGER"),
+        Value::Float(_) | Value::Double(_) => Some("REAL"),
+        Value::Decimal(_) => Some("NUMERIC"),
+        Value::Bytes(_) => Some("BLOB"),
+        _ => Some("TEXT"),
+    }
+}
+
+/// Value stored in the database, dates and times are written as text.
+///
+/// Decimals are stored as numbers when they convert without loss,
+/// the same as SQLite does for `NUMERIC` columns.
+fn sqlite_value(value: Value, timestamps: &TimeFormat) -> sqlite::Value {
+    match value {
+        Value::Null => sqlite::Value::Null,
+        Value::Bool(v) => sqlite::Value::Integer(i64::from(v)),
+        Value::Int(v) => sqlite::Value::Integer(v),
+        Value::UInt(v) => match i64::try_from(v) {
+            Ok(v) => sqlite::Value::Integer(v),
+            Err(_) => sqlite::Value::Real(v as f64),
+        },
+        Value::Float(v) => sqlite::Value::Real(f64::from(v)),
+        Value::Double(v) => sqlite::Value::Real(v),
+        Value::Bytes(v) => sqlite::Value::Blob(v),
+        Value::Date(v) => sqlite::Value::Text(time::format_date(v)),
+        Value::Time(v) => sqlite::Value::Text(time::format_time(v)),
+        Value::Timestamp(v) => sqlite::Value::Text(timestamps.format(v)),
+        Value::Decimal(v) => match (v.parse::<i64>(), v.parse::<f64>()) {
+            (Ok(number), _) => sqlite::Value::Integer(number),
+            (_, Ok(number)) if number.to_string() == v => sqlite::Value::Real(number),
+            _ => sqlite::Value::Text(v),
+        },
+        Value::Str(v) | Value::Uuid(v) | Value::Nested(v) => sqlite::Value::Text(v),
+    }
+}
+
+/// `CREATE TABLE` statement of the columns, columns without a type only held nulls.
+fn create_table(table: &str, headers: &[String], types: &[Option<&str>]) -> String {
+    let columns = headers
+        .iter()
+        .zip(types)
+        .map(|(name, column_type)| match column_type {
+            Some(column_type) => format!("{} {}", sqlite::quote(name), column_type),
+            None => sqlite::quote(name),
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "CREATE TABLE {} ({})",
+        sqlite::quote(table),
+        columns.join(", ")
+    )
+}
+
+/// Write the rows to a new database, returning the number of rows written.
+///
+/// Column types are the type of the first value which is not null,
+/// nested values are written as json.
+fn export<W: Write + Seek>(parquet: &ParquetFile, table: &str, out: W) -> Result<i64> {
+    let headers = parquet.field_names()?;
+    let timestamps = TimeFormat::default().with_format(Some(TIMESTAMP_FORMAT.into()))?;
+    let mut types = vec![None; headers.len()];
+    let mut writer = TableWriter::new(out);
+
+    for row in parquet.values() {
+        let row = row?;
+
+        for (column_type, value) in types.iter_mut().zip(&row) {
+            *column_type = column_type.or_else(|| self::column_type(value));
+        }
+
+        let values = row
+            .into_iter()
+            .map(|v| sqlite_value(v, &timestamps))
+            .collect::<Vec<_>>();
+
+        writer.insert(&values)?;
+    }
+
+    let rows = writer.rows();
+
+    writer.finish(table, &create_table(table, &headers, &types))?;
+
+    Ok(rows)
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("export")
+        .about("Export rows to a SQLite database")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("output")
+                .help("Path of the database, an existing file is replaced")
+                .takes_value(true)
+                .required(true)
+                .long("output")
+                .short('o'),
+        )
+        .arg(
+            Arg::with_name("table")
+                .help("Name of the table, defaults to the name of the parquet file")
+                .takes_value(true)
+                .long("table")
+                .short('t'),
+        )
+        .arg(
+            Arg::with_name("no-snapshot")
+                .help("Do not keep a copy of the replaced database for undo")
+                .long("no-snapshot"),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let path = args::path_value(matches, "path")?;
+    let output = matches
+        .value_of("output")
+        .map(Path::new)
+        .ok_or_else(|| Error::InvalidArgument(String::from("output")))?;
+    let table = match matches.value_of("table") {
+        Some(table) => table.to_string(),
+        None => path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| String::from("data")),
+    };
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(columns)
+        .with_nested(NestedFormat::Json)
+        .with_filter(search);
+
+    // the database is written next to the output and only replaces it once complete
+    let _lock = WriteLock::acquire(output)?;
+    let mut file = OutputFile::create(output)?;
+
+    // a replaced database is kept until the rename, `undo <dir>` moves it back
+    let snapshot = if output.is_file() && !matches.is_present("no-snapshot") {
+        let root = output.parent().unwrap_or_else(|| Path::new(""));
+        let mut snapshot = Snapshot::create(root, "export")?;
+
+        snapshot.keep(output)?;
+
+        Some(snapshot)
+    } else {
+        None
+    };
+    let rows = export(&parquet, &table, &mut file)?;
+
+    file.finish()?;
+
+    if let Some(snapshot) = snapshot {
+        snapshot.commit()?;
+    }
+
+    writeln!(out, "{} rows written to table {}", rows, table)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::fs;
+    use std::str;
+
+    #[test]
+    fn test_export_sqlite_table() {
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("events.parquet");
+        let output = dir.path().join("events.sqlite");
+        let mut out = Vec::new();
+
+        api::tests::write_json_parquet(
+            &parquet,
+            &[
+                r#"{"id": 1, "name": null, "score": 1.5, "tags": ["a"]}"#,
+                r#"{"id": 2, "name": "b", "score": 2.5, "tags": []}"#,
+                r#"{"id": 3, "name": "c", "score": null, "tags": ["c"]}"#,
+            ],
+        );
+
+        let args = def()
+            .get_matches_from_safe([
+                "export",
+                parquet.to_str().unwrap(),
+                "-o",
+                output.to_str().unwrap(),
+                "-s=id>1",
+            ])
+            .unwrap();
+
+        run(&args, &mut out).unwrap();
+
+        let bytes = fs::read(&output).unwrap();
+        let sql = r#"CREATE TABLE "events" ("id" INTEGER, "score" REAL, "tags" TEXT, "name" TEXT)"#;
+
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            "2 rows written to table events\n"
+        );
+        assert_eq!(&bytes[..16], b"SQLite format 3\0");
+        assert!(bytes.windows(sql.len()).any(|w| w == sql.as_bytes()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_export_replaced_database() {
+        use crate::snapshot;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("events.parquet");
+        let output = dir.path().join("events.sqlite");
+        let export = || {
+            let args = def()
+                .get_matches_from_safe([
+                    "export",
+                    parquet.to_str().unwrap(),
+                    "-o",
+                    output.to_str().unwrap(),
+                ])
+                .unwrap();
+
+            run(&args, &mut Vec::new())
+        };
+
+        api::tests::write_json_parquet(&parquet, &[r#"{"id": 1}"#]);
+        fs::write(&output, "previous").unwrap();
+        fs::set_permissions(&output, fs::Permissions::from_mode(0o640)).unwrap();
+
+        let lock = WriteLock::acquire(&output).unwrap();
+
+        assert!(export().is_err());
+        assert_eq!(fs::read(&output).unwrap(), b"previous");
+
+        drop(lock);
+        export().unwrap();
+
+        let mode = fs::metadata(&output).unwrap().permissions().mode();
+
+        assert_eq!(mode & 0o777, 0o640);
+        assert_eq!(&fs::read(&output).unwrap()[..16], b"SQLite format 3\0");
+        assert_eq!(snapshot::snapshots(dir.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_export_sqlite_value() {
+        let timestamps = TimeFormat::default()
+            .with_format(Some(TIMESTAMP_FORMAT.into()))
+            .unwrap();
+        let value = |v| sqlite_value(v, &timestamps);
+
+        assert_eq!(value(Value::Bool(true)), sqlite::Value::Integer(1));
+        assert_eq!(
+            value(Value::UInt(u64::MAX)),
+            sqlite::Value::Real(u64::MAX as f64)
+        );
+        assert_eq!(
+            value(Value::Decimal("12".into())),
+            sqlite::Value::Integer(12)
+        );
+        assert_eq!(
+            value(Value::Decimal("1.25".into())),
+            sqlite::Value::Real(1.25)
+        );
+        assert_eq!(
+            value(Value::Decimal("0.10000000000000000001".into())),
+            sqlite::Value::Text("0.10000000000000000001".into())
+        );
+        assert_eq!(
+            value(Value::Timestamp(1_000_000_500_000)),
+            sqlite::Value::Text("1970-01-12 13:46:40.500".into())
+        );
+        assert_eq!(
+            value(Value::Date(1)),
+            sqlite::Value::Text("1970-01-02".into())
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::hash::RowHash;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::collections::HashSet;
+use std::io::Write;
+
+/// Algorithm of a hex encoded row hash, by its length.
+#[inline]
+fn hash_algorithm(hash: &str) -> Option<RowHash> {
+    if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match hash.len() {
+        64 => Some(RowHash::Sha256),
+        8 => Some(RowHash::Crc32),
+        _ => None,
+    }
+}
+
+fn validate_hash(value: &str) -> std::result::Result<(), String> {
+    match hash_algorithm(value) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "Expected a hex sha256 or crc32 row hash. Got '{}'",
+            value
+        )),
+    }
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("find-row")
+        .about("Find the rows whose content hash matches, eg: a row_hash of convert")
+        .arg(
+            Arg::with_name("hash")
+                .validator(validate_hash)
+                .help("Hex sha256 or crc32 hash of the row values")
+                .takes_value(true)
+                .required(true)
+                .multiple(true)
+                .long("hash"),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .help("Columns hashed, in the order they were hashed")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let path = args::path_value(matches, "path")?;
+    let mut hashes: Vec<(RowHash, HashSet<String>)> = Vec::new();
+
+    for hash in matches.values_of("hash").into_iter().flatten() {
+        let algorithm = hash_algorithm(hash)
+            .ok_or_else(|| Error::InvalidArgument(hash.to_string()))?;
+
+        match hashes.iter_mut().find(|h| h.0 == algorithm) {
+            Some((_, set)) => {
+                set.insert(hash.to_lowercase());
+            }
+            None => hashes.push((algorithm, HashSet::from([hash.to_lowercase()]))),
+        }
+    }
+
+    let parquet = ParquetFile::from((path, columns))
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_row_numbers(true)
+        .with_filename(true);
+    let headers = parquet.field_names()?;
+
+    // rows are hashed without their # and #file fields, the same way convert hashes them
+    let records = parquet.records().filter(|r| {
+        let row = match r {
+            Ok(row) if row.len() >= 2 => &row[1..row.len() - 1],
+            _ => return true,
+        };
+
+        hashes
+            .iter()
+            .any(|(algorithm, set)| algorithm.hex(row).is_ok_and(|h| set.contains(&h)))
+    });
+
+    let mut writer =
+        args::writer(matches, headers, parquet.to_values(records)).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_find_row_hash() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(3);
+        let columns = vec![String::from("field_int32"), String::from("field_boolean")];
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let second = ParquetFile::from((parquet.path(), Some(columns)))
+            .records()
+            .nth(1)
+            .unwrap()
+            .unwrap();
+        let sha256 = RowHash::Sha256.hex(&second).unwrap();
+        let crc32 = RowHash::Crc32.hex(&second).unwrap();
+        let find = |hashes: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec!["find-row", path_str, "-c=field_int32,field_boolean"];
+
+            arg_vec.extend(hashes.iter().flat_map(|h| ["--hash", h]));
+            arg_vec.push("-f=csv");
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output).unwrap();
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+        let expected = format!(
+            "#,field_int32,field_boolean,#file\n2,2,true,\"{}\"\n",
+            parquet.path().display()
+        );
+
+        assert_eq!(find(&[&sha256]), expected);
+        assert_eq!(find(&[&crc32.to_uppercase(), "00000000"]), expected);
+        assert_eq!(find(&["00000000"]), "#,field_int32,field_boolean,#file\n");
+        assert!(def()
+            .get_matches_from_safe(["find-row", path_str, "--hash", "xyz"])
+            .is_err());
+    }
+}
@@ -1,51 +1,709 @@
-use crate::api::Result;
-use crate::command::args;
-use crate::output::{OutputFormat, OutputWriter};
+use crate::api::{Error, Result};
+use crate::command::{self, args};
+use crate::output::OutputFormat;
 use crate::reader::ParquetFile;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use clap::{App, Arg, ArgMatches, SubCommand};
-use stats::Frequencies;
-use std::io::Write;
+use regex::Regex;
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::panic;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-fn compute<I>(num_fields: usize, iter: I) -> Result<Vec<Frequencies<String>>>
+/// Transformation applied to a value before counting it.
+#[derive(Clone, Debug)]
+pub enum Transform {
+    // Host of a URL value
+    Host,
+
+    // Domain of an email value
+    Domain,
+
+    // First N characters of the value
+    Prefix(usize),
+
+    // First capture group (or whole match) of a regex
+    Capture(Regex),
+
+    // Day of week of a date/timestamp value
+    DayOfWeek,
+
+    // Hour of a timestamp value
+    Hour,
+
+    // Month of a date/timestamp value
+    Month,
+}
+
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S %:z",
+    "%Y-%m-%dT%H:%M:%S%.f%:z",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+];
+
+#[inline]
+fn unquote(value: &str) -> &str {
+    if value.len() > 1 && value.starts_with('"') && value.ends_with('"') {
+        return &value[1..value.len() - 1];
+    }
+
+    value
+}
+
+#[inline]
+fn url_host(value: &str) -> Option<String> {
+    let (_, rest) = value.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = match host.rfind(':') {
+        Some(i) if !host.ends_with(']') => &host[..i],
+        _ => host,
+    };
+
+    Some(host.to_lowercase()).filter(|h| !h.is_empty())
+}
+
+#[inline]
+fn email_domain(value: &str) -> Option<String> {
+    value
+        .rsplit_once('@')
+        .map(|t| t.1.to_lowercase())
+        .filter(|d| !d.is_empty())
+}
+
+#[inline]
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let datetime = DATETIME_FORMATS.iter().find_map(|f| {
+        DateTime::parse_from_str(value, f)
+            .map(|d| d.naive_local())
+            .or_else(|_| NaiveDateTime::parse_from_str(value, f))
+            .ok()
+    });
+
+    datetime.or_else(|| {
+        // dates are formatted as `%Y-%m-%d %:z`, the offset is ignored
+        let date = value.split_whitespace().next()?;
+
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|d| d.and_hms(0, 0, 0))
+            .ok()
+    })
+}
+
+impl Transform {
+    /// Whether this transformation extracts a part of a date/timestamp.
+    pub fn is_date_part(&self) -> bool {
+        matches!(
+            self,
+            Transform::DayOfWeek | Transform::Hour | Transform::Month
+        )
+    }
+
+    /// Apply the transformation to a formatted value.
+    ///
+    /// Returns `None` when the value does not produce a token.
+    pub fn apply(&self, value: &str) -> Option<String> {
+        let value = unquote(value);
+
+        match self {
+            Transform::Host => url_host(value),
+            Transform::Domain => email_domain(value),
+            Transform::Prefix(len) => Some(value.chars().take(*len).collect()),
+            Transform::Capture(regex) => regex
+                .captures(value)
+                .and_then(|c| c.get(1).or_else(|| c.get(0)))
+                .map(|m| m.as_str().to_string()),
+            Transform::DayOfWeek => {
+                parse_datetime(value).map(|d| d.format("%a").to_string())
+            }
+            Transform::Hour => parse_datetime(value).map(|d| d.format("%H").to_string()),
+            Transform::Month => parse_datetime(value).map(|d| d.format("%b").to_string()),
+        }
+    }
+}
+
+impl TryFrom<&str> for Transform {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        let (name, param) = match value.split_once('=') {
+            Some((name, param)) => (name, Some(param)),
+            None => (value, None),
+        };
+
+        match (name.to_lowercase().as_ref(), param) {
+            ("host", None) => Ok(Transform::Host),
+            ("domain", None) => Ok(Transform::Domain),
+            ("dow", None) => Ok(Transform::DayOfWeek),
+            ("hour", None) => Ok(Transform::Hour),
+            ("month", None) => Ok(Transform::Month),
+            ("prefix", Some(len)) => len
+                .parse()
+                .map(Transform::Prefix)
+                .map_err(|_| Error::InvalidArgument(value.to_string())),
+            ("regex", Some(pattern)) if !pattern.is_empty() => {
+                Ok(Transform::Capture(Regex::new(pattern)?))
+            }
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+#[inline]
+fn field_transforms(
+    fields: &[String],
+    transforms: HashMap<String, Transform>,
+) -> HashMap<usize, Transform> {
+    let mut result = HashMap::new();
+
+    for (name, transform) in transforms {
+        if let Some(index) = fields.iter().position(|f| f.eq_ignore_ascii_case(&name)) {
+            result.insert(index, transform);
+        }
+    }
+
+    result
+}
+
+/// Ordering of the counted values.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FrequencySort {
+    // Most frequent values first
+    Count,
+
+    // Values in lexicographic order
+    Value,
+}
+
+const FREQUENCY_SORT_VALUES: &[&str] = &["count", "value"];
+
+impl FrequencySort {
+    pub fn values() -> &'static [&'static str] {
+        FREQUENCY_SORT_VALUES
+    }
+}
+
+impl TryFrom<&str> for FrequencySort {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "count" => Ok(FrequencySort::Count),
+            "value" => Ok(FrequencySort::Value),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Selection and ordering of the counted values of a column.
+#[derive(Clone, Debug)]
+pub struct Ranking {
+    sort: FrequencySort,
+    top: Option<usize>,
+    bottom: Option<usize>,
+    min_count: u64,
+}
+
+impl Default for Ranking {
+    fn default() -> Self {
+        Self {
+            sort: FrequencySort::Count,
+            top: None,
+            bottom: None,
+            min_count: 0,
+        }
+    }
+}
+
+impl Ranking {
+    /// Keep the values seen at least `min_count` times, the `top` most frequent
+    /// or the `bottom` least frequent values, ordered by `sort`.
+    ///
+    /// When sorting by count the least frequent values come first with `bottom`.
+    pub fn apply(&self, counts: Vec<(String, u64)>) -> Vec<(String, u64)> {
+        let mut vec = counts
+            .into_iter()
+            .filter(|c| c.1 >= self.min_count)
+            .collect::<Vec<_>>();
+
+        // ties are broken by value to get a stable output
+        vec.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        if let Some(top) = self.top {
+            vec.truncate(top);
+        }
+
+        if let Some(bottom) = self.bottom {
+            vec.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+            vec.truncate(bottom);
+        }
+
+        if self.sort == FrequencySort::Value {
+            vec.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        vec
+    }
+}
+
+/// Counter of the values of a column.
+trait Counter {
+    fn add(&mut self, value: &str);
+}
+
+/// Value counts of a column.
+///
+/// Values are looked up by reference, only new values are allocated.
+#[derive(Clone, Debug, Default)]
+pub struct Counts {
+    data: HashMap<String, u64>,
+    total: u64,
+}
+
+impl Counts {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: HashMap::with_capacity(capacity),
+            total: 0,
+        }
+    }
+
+    pub fn add(&mut self, value: &str) {
+        self.total += 1;
+
+        match self.data.get_mut(value) {
+            Some(count) => *count += 1,
+            None => {
+                self.data.insert(value.to_string(), 1);
+            }
+        }
+    }
+
+    /// Add the counts of another partial result.
+    pub fn merge(&mut self, other: Counts) {
+        self.total += other.total;
+
+        for (value, count) in other.data {
+            *self.data.entry(value).or_insert(0) += count;
+        }
+    }
+
+    /// Number of counted values, including the values left out of an approximation.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn into_vec(self) -> Vec<(String, u64)> {
+        self.data.into_iter().collect()
+    }
+}
+
+impl Counter for Counts {
+    fn add(&mut self, value: &str) {
+        Counts::add(self, value)
+    }
+}
+
+/// Approximate value counts of a column keeping at most `size` values (space-saving).
+///
+/// Once full the least counted value is replaced by the new one, the count of a value
+/// is over-estimated by at most its error.
+#[derive(Clone, Debug)]
+pub struct Sketch {
+    size: usize,
+    total: u64,
+
+    // Value, count and error of each counter
+    counters: Vec<(String, u64, u64)>,
+    index: HashMap<String, usize>,
+
+    // Count and position of each counter, the least counted first
+    order: BTreeSet<(u64, usize)>,
+}
+
+impl Sketch {
+    pub fn new(size: usize) -> Self {
+        Self::from_counters(size, 0, Vec::new())
+    }
+
+    fn from_counters(size: usize, total: u64, counters: Vec<(String, u64, u64)>) -> Self {
+        let index = counters
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.0.to_string(), i))
+            .collect();
+        let order = counters.iter().enumerate().map(|(i, c)| (c.1, i)).collect();
+
+        Self {
+            size,
+            total,
+            counters,
+            index,
+            order,
+        }
+    }
+
+    pub fn add(&mut self, value: &str) {
+        self.total += 1;
+
+        if let Some(&i) = self.index.get(value) {
+            let count = self.counters[i].1;
+
+            self.order.remove(&(count, i));
+            self.order.insert((count + 1, i));
+            self.counters[i].1 += 1;
+
+            return;
+        }
+
+        if self.counters.len() < self.size {
+            self.index.insert(value.to_string(), self.counters.len());
+            self.order.insert((1, self.counters.len()));
+            self.counters.push((value.to_string(), 1, 0));
+
+            return;
+        }
+
+        if let Some((min, i)) = self.order.pop_first() {
+            self.index.remove(&self.counters[i].0);
+            self.index.insert(value.to_string(), i);
+            self.order.insert((min + 1, i));
+            self.counters[i] = (value.to_string(), min + 1, min);
+        }
+    }
+
+    /// Highest count a value missing from the sketch may have.
+    fn min(&self) -> u64 {
+        if self.counters.len() < self.size {
+            return 0;
+        }
+
+        self.order.first().map_or(0, |c| c.0)
+    }
+
+    /// Add the counters of another sketch, keeping the `size` most counted values.
+    ///
+    /// Values missing from one of the sketches may have been counted up to its least count.
+    pub fn merge(&mut self, other: Sketch) {
+        let (min, other_min) = (self.min(), other.min());
+        let mut others = other
+            .counters
+            .into_iter()
+            .map(|(value, count, error)| (value, (count, error)))
+            .collect::<HashMap<_, _>>();
+        let mut counters = self
+            .counters
+            .drain(..)
+            .map(|(value, count, error)| {
+                let (c, e) = others.remove(&value).unwrap_or((other_min, other_min));
+
+                (value, count + c, error + e)
+            })
+            .collect::<Vec<_>>();
+
+        counters.extend(
+            others
+                .into_iter()
+                .map(|(value, (count, error))| (value, count + min, error + min)),
+        );
+        counters.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counters.truncate(self.size);
+
+        *self = Self::from_counters(self.size, self.total + other.total, counters);
+    }
+
+    /// Approximate counts of the sketch, percentages stay relative to every counted value.
+    pub fn into_counts(self) -> Counts {
+        Counts {
+            data: self.counters.into_iter().map(|c| (c.0, c.1)).collect(),
+            total: self.total,
+        }
+    }
+
+    /// Json of the sketch, eg: `{"size": 1000, "total": 3, "counters": [["BR", 2, 0], ..]}`.
+    fn to_json_value(&self) -> serde_json::Value {
+        let mut counters = self.counters.iter().collect::<Vec<_>>();
+
+        counters.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        json!({
+            "size": self.size,
+            "total": self.total,
+            "counters": counters.iter().map(|c| json!([c.0, c.1, c.2])).collect::<Vec<_>>(),
+        })
+    }
+
+    fn from_json_value(value: &serde_json::Value) -> Option<Self> {
+        let size = value.get("size")?.as_u64()? as usize;
+        let total = value.get("total")?.as_u64()?;
+        let counters = value
+            .get("counters")?
+            .as_array()?
+            .iter()
+            .map(|c| {
+                let value = c.get(0)?.as_str()?.to_string();
+
+                Some((value, c.get(1)?.as_u64()?, c.get(2)?.as_u64()?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self::from_counters(size, total, counters))
+    }
+}
+
+impl Counter for Sketch {
+    fn add(&mut self, value: &str) {
+        Sketch::add(self, value)
+    }
+}
+
+/// Write the sketch of each field as json, see `--sketch-out`.
+fn write_sketches(path: &Path, fields: &[String], sketches: &[Sketch]) -> Result<()> {
+    let columns = fields
+        .iter()
+        .zip(sketches)
+        .map(|(name, sketch)| (name.to_string(), sketch.to_json_value()))
+        .collect::<serde_json::Map<_, _>>();
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    serde_json::to_writer(&mut writer, &json!({ "columns": columns }))?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Sketches of each column written by `--sketch-out`.
+fn read_sketches(path: &Path) -> Result<HashMap<String, Sketch>> {
+    let invalid = || Error::InvalidArgument(format!("Invalid sketch {}", path.display()));
+    let json: serde_json::Value =
+        serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|_| invalid())?;
+
+    json.get("columns")
+        .and_then(|c| c.as_object())
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|(name, value)| {
+            Sketch::from_json_value(value)
+                .map(|s| (name.to_string(), s))
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+/// Initial capacity of columns without distinct count statistics.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Capacity of each field, bounded by the number of rows to read.
+#[inline]
+fn field_capacities(
+    fields: &[String],
+    distinct: &HashMap<String, u64>,
+    limit: usize,
+) -> Vec<usize> {
+    fields
+        .iter()
+        .map(|f| match distinct.get(f) {
+            Some(count) => (*count as usize).min(limit),
+            None => DEFAULT_CAPACITY.min(limit),
+        })
+        .collect()
+}
+
+fn compute<C, I>(
+    mut vec: Vec<C>,
+    iter: I,
+    transforms: &HashMap<usize, Transform>,
+) -> Result<Vec<C>>
 where
+    C: Counter,
     I: Iterator<Item = Result<Vec<String>>>,
 {
-    let mut vec: Vec<_> = (0..num_fields).map(|_| Frequencies::new()).collect();
-
     for row in iter {
         for (i, val) in row?.iter().enumerate() {
-            vec[i].add(val.to_string());
+            match transforms.get(&i) {
+                Some(transform) => {
+                    if let Some(token) = transform.apply(val) {
+                        vec[i].add(&token);
+                    }
+                }
+                None => vec[i].add(val),
+            }
         }
     }
 
     Ok(vec)
 }
 
-fn format_row(field: &str, name: &str, count: u64) -> Vec<String> {
-    vec![field.to_string(), name.to_string(), count.to_string()]
+/// Field names and value counts of a single input path.
+fn compute_path(
+    parquet: &ParquetFile,
+    limit: usize,
+    transforms: &HashMap<String, Transform>,
+) -> Result<(Vec<String>, Vec<Counts>)> {
+    let fields = parquet.field_names()?;
+    let distinct = parquet.distinct_counts()?;
+    let capacities = field_capacities(&fields, &distinct, limit);
+    let transforms = field_transforms(&fields, transforms.clone());
+    let counts = capacities
+        .iter()
+        .map(|c| Counts::with_capacity(*c))
+        .collect();
+    let vec = compute(counts, parquet.iter().take(limit), &transforms)?;
+
+    Ok((fields, vec))
+}
+
+/// Compute the partial counts of each input path in parallel.
+fn compute_paths(
+    parquets: &[ParquetFile],
+    limit: usize,
+    transforms: &HashMap<String, Transform>,
+) -> Result<Vec<(Vec<String>, Vec<Counts>)>> {
+    command::parallel_map(parquets, |p| compute_path(p, limit, transforms))
+        .into_iter()
+        .collect()
+}
+
+/// Field names and sketches of every row of the paths, each row group is sketched
+/// on its own by a pool of threads then the sketches are merged.
+fn sketch_paths(
+    parquets: &[ParquetFile],
+    size: usize,
+    transforms: &HashMap<String, Transform>,
+) -> Result<(Vec<String>, Vec<Sketch>)> {
+    let mut fields: Option<Vec<String>> = None;
+    let mut parts = Vec::new();
+
+    for parquet in parquets {
+        let names = parquet.field_names()?;
+
+        if let Some(fields) = fields.as_ref().filter(|f| **f != names) {
+            return Err(Error::InvalidArgument(format!(
+                "Paths have different columns: {:?} and {:?}",
+                fields, names
+            )));
+        }
+
+        fields = Some(names);
+        parts.extend(parquet.row_group_parts()?);
+    }
+
+    let fields = fields.ok_or_else(|| Error::InvalidArgument(String::from("path")))?;
+    let transforms = field_transforms(&fields, transforms.clone());
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let sketch_parts = || -> Result<Vec<Sketch>> {
+        let mut sketches = vec![Sketch::new(size); fields.len()];
+
+        while let Some(part) = parts.get(next.fetch_add(1, Ordering::Relaxed)) {
+            let vec = compute(
+                vec![Sketch::new(size); fields.len()],
+                part.iter(),
+                &transforms,
+            )?;
+
+            for (sketch, other) in sketches.iter_mut().zip(vec) {
+                sketch.merge(other);
+            }
+        }
+
+        Ok(sketches)
+    };
+    let partials = thread::scope(|scope| {
+        let handles = (0..workers.min(parts.len()))
+            .map(|_| scope.spawn(sketch_parts))
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    let mut result = vec![Sketch::new(size); fields.len()];
+
+    for vec in partials {
+        for (sketch, other) in result.iter_mut().zip(vec) {
+            sketch.merge(other);
+        }
+    }
+
+    Ok((fields, result))
+}
+
+/// Sum the partial counts of each path, all paths must have the same fields.
+fn merge_partials(partials: Vec<(Vec<String>, Vec<Counts>)>) -> Result<Vec<Counts>> {
+    let mut iter = partials.into_iter();
+    let (fields, mut result) = iter
+        .next()
+        .ok_or_else(|| Error::InvalidArgument(String::from("path")))?;
+
+    for (names, vec) in iter {
+        if names != fields {
+            return Err(Error::InvalidArgument(format!(
+                "Paths have different columns: {:?} and {:?}",
+                fields, names
+            )));
+        }
+
+        for (counts, other) in result.iter_mut().zip(vec) {
+            counts.merge(other);
+        }
+    }
+
+    Ok(result)
+}
+
+const TOTAL_VALUE: &str = "TOTAL";
+
+#[inline]
+fn percent(count: u64, total: u64) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => count as f64 * 100.0 / total as f64,
+    }
+}
+
+fn format_row(field: &str, name: &str, count: u64, total: u64) -> Vec<String> {
+    vec![
+        field.to_string(),
+        name.to_string(),
+        count.to_string(),
+        format!("{:.2}", percent(count, total)),
+    ]
 }
 
 fn format_rows(
     fields: Vec<String>,
-    vec: Vec<Frequencies<String>>,
+    vec: Vec<Counts>,
+    ranking: Ranking,
 ) -> impl Iterator<Item = Result<Vec<String>>> {
     vec.into_iter()
         .enumerate()
         .map(move |t| {
             let header = fields[t.0].to_string();
-            let counts =
-                t.1.least_frequent()
-                    .into_iter()
-                    .map(|c| (c.0.to_string(), c.1))
-                    .collect::<Vec<_>>();
 
-            (header, counts)
+            // percentages are relative to all counted values of the column
+            let total = t.1.total();
+            let counts = t.1.into_vec();
+
+            (header, ranking.apply(counts), total)
         })
         .flat_map(|t| {
             let header = t.0.to_string();
-            let counts = t.1.into_iter();
+            let total = t.2;
+            let counts =
+                t.1.into_iter()
+                    .chain(std::iter::once((TOTAL_VALUE.to_string(), total)));
 
-            counts.map(move |c| Ok(format_row(&header, &c.0, c.1)))
+            counts.map(move |c| Ok(format_row(&header, &c.0, c.1, total)))
         })
 }
 
@@ -61,18 +719,97 @@ pub fn def() -> App<'static> {
                 .short('c'),
         )
         .arg(
-            Arg::with_name("search")
-                .validator(args::validate_filter)
-                .help("Search columns")
+            Arg::with_name("exclude")
+                .help("Exclude columns from parquet")
                 .takes_value(true)
-                .long("search")
+                .long("exclude")
+                .multiple(true),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("transform")
+                .validator(args::validate_transform)
+                .help("Count derived tokens (<column>:host|domain|prefix=<n>|regex=<regex>)")
+                .takes_value(true)
+                .long("transform")
+                .multiple(true)
+                .short('t'),
+        )
+        .arg(
+            Arg::with_name("by-date-part")
+                .validator(args::validate_date_part)
+                .help("Count date parts of a date/timestamp (<column>:dow|hour|month)")
+                .takes_value(true)
+                .long("by-date-part")
                 .multiple(true)
-                .short('s'),
+                .short('d'),
+        )
+        .arg(
+            Arg::with_name("top")
+                .validator(args::validate_number)
+                .help("Show only the N most frequent values of each column")
+                .conflicts_with("bottom")
+                .takes_value(true)
+                .long("top"),
+        )
+        .arg(
+            Arg::with_name("bottom")
+                .validator(args::validate_number)
+                .help("Show only the N least frequent values of each column")
+                .takes_value(true)
+                .long("bottom"),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .help("Sort values by count or value")
+                .possible_values(FrequencySort::values())
+                .default_value("count")
+                .long("sort"),
+        )
+        .arg(
+            Arg::with_name("min-count")
+                .validator(args::validate_number)
+                .help("Drop values seen less than N times")
+                .takes_value(true)
+                .long("min-count"),
+        )
+        .arg(
+            Arg::with_name("per-path")
+                .help("Show counts of each path along with the combined counts")
+                .long("per-path"),
+        )
+        .arg(
+            Arg::with_name("approx")
+                .help("Approximate the counts of every row, reading row groups in parallel")
+                .conflicts_with_all(&["limit", "per-path"])
+                .long("approx"),
+        )
+        .arg(
+            Arg::with_name("sketch-size")
+                .validator(args::validate_number)
+                .help("Max number of values kept for each column by --approx")
+                .default_value("1000")
+                .long("sketch-size"),
+        )
+        .arg(
+            Arg::with_name("sketch-out")
+                .help("Write the --approx sketch of each column to a file, see --sketch-merge")
+                .requires("approx")
+                .takes_value(true)
+                .long("sketch-out"),
+        )
+        .arg(
+            Arg::with_name("sketch-merge")
+                .help("Add the sketches of a previous --sketch-out to the --approx counts")
+                .requires("approx")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .long("sketch-merge"),
         )
         .arg(
             Arg::with_name("limit")
                 .validator(args::validate_number)
-                .help("Max number of rows")
+                .help("Max number of rows of each path")
                 .default_value("500")
                 .long("limit")
                 .short('l'),
@@ -88,33 +825,141 @@ pub fn def() -> App<'static> {
         .arg(
             Arg::with_name("path")
                 .validator(args::validate_path)
-                .help("Path to parquet")
+                .help("Paths to parquet")
+                .multiple(true)
                 .required(true)
                 .index(1),
         )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
+        .arg(args::cache_arg())
+}
+
+fn ranking_value(matches: &ArgMatches) -> Result<Ranking> {
+    let optional = |name| match matches.value_of(name) {
+        Some(_) => args::usize_value(matches, name).map(Some),
+        None => Ok(None),
+    };
+
+    let sort = matches
+        .value_of("sort")
+        .map(FrequencySort::try_from)
+        .unwrap_or(Ok(FrequencySort::Count))?;
+
+    Ok(Ranking {
+        sort,
+        top: optional("top")?,
+        bottom: optional("bottom")?,
+        min_count: optional("min-count")?.unwrap_or(0) as u64,
+    })
+}
+
+/// Approximate counts of every row, adding the sketches of `--sketch-merge`
+/// before writing them to `--sketch-out`.
+fn approx_counts(
+    matches: &ArgMatches,
+    parquets: &[ParquetFile],
+    transforms: &HashMap<String, Transform>,
+) -> Result<(Vec<String>, Vec<Counts>)> {
+    let size = args::usize_value(matches, "sketch-size")?;
+    let (fields, mut sketches) = sketch_paths(parquets, size, transforms)?;
+
+    // columns that are not selected are left out
+    for path in matches.values_of("sketch-merge").into_iter().flatten() {
+        let mut previous = read_sketches(Path::new(path))?;
+
+        for (name, sketch) in fields.iter().zip(sketches.iter_mut()) {
+            if let Some(other) = previous.remove(name) {
+                sketch.merge(other);
+            }
+        }
+    }
+
+    if let Some(path) = matches.value_of("sketch-out") {
+        write_sketches(Path::new(path), &fields, &sketches)?;
+    }
+
+    Ok((
+        fields,
+        sketches.into_iter().map(Sketch::into_counts).collect(),
+    ))
 }
 
 pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
     let format = args::output_format_value(matches, "format")?;
     let columns = args::string_values(matches, "columns")?;
-    let search = args::filter_values(matches, "search")?;
+    let exclude = args::string_values(matches, "exclude")?;
+    let search = args::search_value(matches)?;
+    let transform = args::transform_values(matches, "transform")?;
+    let date_part = args::transform_values(matches, "by-date-part")?;
     let limit = args::usize_value(matches, "limit")?;
-    let path = args::path_value(matches, "path")?;
-    let parquet = ParquetFile::from(path)
-        .with_fields(columns)
-        .with_filters(search);
+    let paths = args::path_values(matches, "path")?;
+    let per_path = matches.is_present("per-path");
+    let ranking = ranking_value(matches)?;
+    let parquets = paths
+        .iter()
+        .map(|p| {
+            ParquetFile::from(*p)
+                .with_discovery(args::discovery_value(matches))
+                .with_strict(matches.is_present("strict"))
+                .with_value_format(args::value_format_value(matches))
+                .with_value_format(args::value_format_value(matches))
+                .with_fields(columns.clone())
+                .with_exclude(exclude.clone())
+                .with_filter(search.clone())
+        })
+        .collect::<Vec<_>>();
 
-    let fields = parquet.field_names()?;
-    let rows = parquet.iter().take(limit);
-    let vec = compute(fields.len(), rows)?;
-    let headers = vec![
+    let mut transforms = transform.unwrap_or_default();
+
+    transforms.extend(date_part.unwrap_or_default());
+
+    let mut headers = vec![
         String::from("FIELD"),
         String::from("VALUE"),
         String::from("COUNT"),
+        String::from("PERCENT"),
     ];
 
-    let iter = format_rows(fields, vec);
-    let mut writer = OutputWriter::new(headers, iter).format(format);
+    if matches.is_present("approx") {
+        let (fields, counts) = approx_counts(matches, &parquets, &transforms)?;
+        let iter = format_rows(fields, counts, ranking);
+        let mut writer = args::writer(matches, headers, iter).format(format);
+
+        return writer.write(out);
+    }
+
+    let partials = compute_paths(&parquets, limit, &transforms)?;
+    let fields = partials.first().map(|p| p.0.clone()).unwrap_or_default();
+    let sections = if per_path {
+        let merged = merge_partials(partials.clone())?;
+        let labels = paths.iter().map(|p| Some(p.display().to_string()));
+        let vecs = partials.into_iter().map(|p| p.1);
+
+        headers.insert(0, String::from("PATH"));
+
+        labels
+            .zip(vecs)
+            .chain(std::iter::once((Some(TOTAL_VALUE.to_string()), merged)))
+            .collect::<Vec<_>>()
+    } else {
+        vec![(None, merge_partials(partials)?)]
+    };
+
+    let iter = sections.into_iter().flat_map(move |(label, vec)| {
+        format_rows(fields.clone(), vec, ranking.clone()).map(move |r| {
+            r.map(|mut row| {
+                if let Some(label) = &label {
+                    row.insert(0, label.to_string());
+                }
+
+                row
+            })
+        })
+    });
+
+    let mut writer = args::writer(matches, headers, iter).format(format);
 
     writer.write(out)
 }
@@ -152,7 +997,7 @@ mod tests {
         let vec = output.into_inner();
         let actual = str::from_utf8(&vec).unwrap();
 
-        assert_eq!(6, actual.lines().count());
+        assert_eq!(8, actual.lines().count());
         assert!(actual.starts_with("FIELD          VALUE  COUNT"));
         assert!(actual.contains("field_int32    1      1"));
         assert!(actual.contains("field_int32    2      1"));
@@ -187,11 +1032,12 @@ mod tests {
         let vec = output.into_inner();
         let actual = str::from_utf8(&vec).unwrap();
 
-        assert_eq!(4, actual.lines().count());
+        assert_eq!(6, actual.lines().count());
         assert!(actual.starts_with("FIELD          VALUE  COUNT"));
         assert!(actual.contains("field_int32    1      1"));
         assert!(actual.contains("field_int32    3      1"));
-        assert!(actual.contains("field_boolean  false  2"));
+        assert!(actual.contains("field_boolean  false  2      100.00"));
+        assert!(actual.contains("field_int32    TOTAL  2      100.00"));
         assert!(actual.ends_with(""));
     }
 
@@ -214,23 +1060,36 @@ mod tests {
         let vec = output.into_inner();
         let actual = str::from_utf8(&vec).unwrap();
 
-        assert_eq!(8, actual.lines().count());
+        assert_eq!(15, actual.lines().count());
         assert!(actual.contains(
-            &vec![
+            &[
                 "",
-                "FIELD:  field_boolean",
-                "VALUE:  true", // 4 true
-                "COUNT:  4",
+                "FIELD:    field_boolean",
+                "VALUE:    true", // 4 true
+                "COUNT:    4",
+                "PERCENT:  44.44",
                 ""
             ]
             .join("\n")
         ));
         assert!(actual.contains(
-            &vec![
+            &[
                 "",
-                "FIELD:  field_boolean",
-                "VALUE:  false",
-                "COUNT:  5",
+                "FIELD:    field_boolean",
+                "VALUE:    false",
+                "COUNT:    5",
+                "PERCENT:  55.56",
+                ""
+            ]
+            .join("\n")
+        ));
+        assert!(actual.contains(
+            &[
+                "",
+                "FIELD:    field_boolean",
+                "VALUE:    TOTAL",
+                "COUNT:    9",
+                "PERCENT:  100.00",
                 ""
             ]
             .join("\n")
@@ -238,6 +1097,438 @@ mod tests {
         assert!(actual.ends_with(""));
     }
 
+    #[test]
+    fn test_ranking_apply() {
+        let counts = || {
+            vec![
+                (String::from("a"), 1),
+                (String::from("b"), 3),
+                (String::from("c"), 2),
+                (String::from("d"), 3),
+            ]
+        };
+        let ranking = |sort, top, bottom, min_count| Ranking {
+            sort,
+            top,
+            bottom,
+            min_count,
+        };
+        let values = |vec: Vec<(String, u64)>| {
+            vec.into_iter().map(|c| c.0).collect::<Vec<_>>().join(",")
+        };
+
+        assert_eq!(values(Ranking::default().apply(counts())), "b,d,c,a");
+        assert_eq!(
+            values(ranking(FrequencySort::Count, Some(2), None, 0).apply(counts())),
+            "b,d"
+        );
+        assert_eq!(
+            values(ranking(FrequencySort::Count, None, Some(2), 0).apply(counts())),
+            "a,c"
+        );
+        assert_eq!(
+            values(ranking(FrequencySort::Value, Some(3), None, 0).apply(counts())),
+            "b,c,d"
+        );
+        assert_eq!(
+            values(ranking(FrequencySort::Value, None, None, 2).apply(counts())),
+            "b,c,d"
+        );
+    }
+
+    #[test]
+    fn test_counts_add() {
+        let mut counts = Counts::with_capacity(2);
+
+        counts.add("a");
+        counts.add("b");
+        counts.add("a");
+
+        let mut actual = counts.into_vec();
+
+        actual.sort();
+
+        assert_eq!(actual, vec![(String::from("a"), 2), (String::from("b"), 1)]);
+    }
+
+    #[test]
+    fn test_sketch_add_merge() {
+        let mut sketch = Sketch::new(2);
+        let mut other = Sketch::new(2);
+
+        ["a", "a", "b", "c"].iter().for_each(|v| sketch.add(v));
+        ["b", "b"].iter().for_each(|v| other.add(v));
+
+        assert_eq!(sketch.min(), 2);
+        assert_eq!(other.min(), 0);
+        assert_eq!(
+            sketch.to_json_value(),
+            json!({"size": 2, "total": 4, "counters": [["a", 2, 0], ["c", 2, 1]]})
+        );
+
+        sketch.merge(other);
+
+        let json = sketch.to_json_value();
+
+        assert_eq!(
+            json,
+            json!({"size": 2, "total": 6, "counters": [["b", 4, 2], ["a", 2, 0]]})
+        );
+        assert_eq!(
+            Sketch::from_json_value(&json).unwrap().to_json_value(),
+            json
+        );
+
+        let counts = sketch.into_counts();
+        let mut actual = counts.clone().into_vec();
+
+        actual.sort();
+
+        assert_eq!(counts.total(), 6);
+        assert_eq!(actual, vec![(String::from("a"), 2), (String::from("b"), 4)]);
+    }
+
+    #[test]
+    fn test_field_capacities() {
+        let fields = vec![String::from("a"), String::from("b"), String::from("c")];
+        let distinct = vec![(String::from("a"), 10), (String::from("b"), 5000)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            field_capacities(&fields, &distinct, 2000),
+            vec![10, 2000, DEFAULT_CAPACITY]
+        );
+        assert_eq!(field_capacities(&fields, &distinct, 1), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_simple_messages_frequency_per_path() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.parquet");
+        let path2 = dir.path().join("2.parquet");
+        let path1_str = path1.to_str().unwrap();
+        let path2_str = path2.to_str().unwrap();
+        let expected = [
+            String::from("PATH,FIELD,VALUE,COUNT,PERCENT"),
+            format!("{},field_boolean,false,2,66.67", path1_str),
+            format!("{},field_boolean,true,1,33.33", path1_str),
+            format!("{},field_boolean,TOTAL,3,100.00", path1_str),
+            format!("{},field_boolean,false,1,50.00", path2_str),
+            format!("{},field_boolean,true,1,50.00", path2_str),
+            format!("{},field_boolean,TOTAL,2,100.00", path2_str),
+            String::from("TOTAL,field_boolean,false,3,60.00"),
+            String::from("TOTAL,field_boolean,true,2,40.00"),
+            String::from("TOTAL,field_boolean,TOTAL,5,100.00"),
+            String::new(),
+        ]
+        .join("\n");
+
+        api::tests::write_simple_messages_parquet(
+            &path1,
+            &api::tests::create_simple_messages(3),
+        );
+        api::tests::write_simple_messages_parquet(
+            &path2,
+            &api::tests::create_simple_messages(2),
+        );
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "frequency",
+            path1_str,
+            path2_str,
+            "-f=csv",
+            "-c=field_boolean",
+            "--per-path",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_simple_messages_frequency_approx() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("1.parquet");
+        let sketch = dir.path().join("sketch.json");
+        let path_str = path.to_str().unwrap();
+        let sketch_str = sketch.to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_row_groups(&path, &[&msgs[..2], &msgs[2..]]);
+
+        let frequency = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec!["frequency", path_str, "-f=csv", "-c=field_boolean"];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output).unwrap();
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            frequency(&["--approx", "--sketch-out", sketch_str]),
+            [
+                "FIELD,VALUE,COUNT,PERCENT",
+                "field_boolean,false,3,60.00",
+                "field_boolean,true,2,40.00",
+                "field_boolean,TOTAL,5,100.00",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            frequency(&["--approx", "--sketch-merge", sketch_str, "--top=1"]),
+            [
+                "FIELD,VALUE,COUNT,PERCENT",
+                "field_boolean,false,6,60.00",
+                "field_boolean,TOTAL,10,100.00",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_merge_partials() {
+        let fields = vec![String::from("a")];
+        let counts = |values: &[&str]| {
+            let mut counts = Counts::default();
+
+            values.iter().for_each(|v| counts.add(v));
+
+            vec![counts]
+        };
+
+        let partials = vec![
+            (fields.clone(), counts(&["x", "y"])),
+            (fields.clone(), counts(&["x"])),
+        ];
+        let mut actual = merge_partials(partials).unwrap().remove(0).into_vec();
+
+        actual.sort();
+
+        assert_eq!(actual, vec![(String::from("x"), 2), (String::from("y"), 1)]);
+        assert!(merge_partials(vec![
+            (fields, counts(&["x"])),
+            (vec![String::from("b")], counts(&["x"])),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_simple_messages_frequency_top() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let path = parquet.path();
+        let expected = [
+            "FIELD,VALUE,COUNT,PERCENT",
+            "field_boolean,false,3,60.00",
+            "field_boolean,true,2,40.00",
+            "field_boolean,TOTAL,5,100.00",
+            "field_int32,TOTAL,5,100.00",
+            "",
+        ]
+        .join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(5);
+        let arg_vec = vec![
+            "frequency",
+            path_str,
+            "-f=csv",
+            "-c=field_boolean,field_int32",
+            "--top=2",
+            "--min-count=2",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(path, &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_transform_try_from() {
+        assert!(matches!(Transform::try_from("host"), Ok(Transform::Host)));
+        assert!(matches!(
+            Transform::try_from("DOMAIN"),
+            Ok(Transform::Domain)
+        ));
+        assert!(matches!(
+            Transform::try_from("prefix=3"),
+            Ok(Transform::Prefix(3))
+        ));
+        assert!(matches!(
+            Transform::try_from("regex=^([a-z]+)"),
+            Ok(Transform::Capture(_))
+        ));
+
+        assert_eq!(
+            Transform::try_from("prefix=x").err().unwrap(),
+            Error::InvalidArgument(String::from("prefix=x"))
+        );
+        assert_eq!(
+            Transform::try_from("host=1").err().unwrap(),
+            Error::InvalidArgument(String::from("host=1"))
+        );
+        assert_eq!(
+            Transform::try_from("foo").err().unwrap(),
+            Error::InvalidArgument(String::from("foo"))
+        );
+    }
+
+    #[test]
+    fn test_transform_apply() {
+        let host = Transform::Host;
+        let domain = Transform::Domain;
+        let prefix = Transform::Prefix(3);
+        let capture = Transform::try_from("regex=^(\\w+)-").unwrap();
+        let matched = Transform::try_from("regex=\\d+").unwrap();
+
+        assert_eq!(
+            host.apply("\"https://User@Example.com:8080/a?b=c\""),
+            Some(String::from("example.com"))
+        );
+        assert_eq!(
+            host.apply("http://example.com#top"),
+            Some(String::from("example.com"))
+        );
+        assert_eq!(host.apply("http://[::1]/"), Some(String::from("[::1]")));
+        assert_eq!(host.apply("\"not a url\""), None);
+
+        assert_eq!(
+            domain.apply("\"john@Example.org\""),
+            Some(String::from("example.org"))
+        );
+        assert_eq!(domain.apply("\"john\""), None);
+
+        assert_eq!(prefix.apply("\"abcdef\""), Some(String::from("abc")));
+        assert_eq!(prefix.apply("\"ab\""), Some(String::from("ab")));
+
+        assert_eq!(capture.apply("\"foo-bar\""), Some(String::from("foo")));
+        assert_eq!(capture.apply("\"foobar\""), None);
+        assert_eq!(matched.apply("\"abc 123\""), Some(String::from("123")));
+    }
+
+    #[test]
+    fn test_transform_apply_date_part() {
+        let dow = Transform::try_from("dow").unwrap();
+        let hour = Transform::try_from("hour").unwrap();
+        let month = Transform::try_from("month").unwrap();
+
+        assert!(dow.is_date_part());
+        assert!(!Transform::Host.is_date_part());
+
+        assert_eq!(
+            dow.apply("2001-09-09 01:46:40 +00:00"),
+            Some(String::from("Sun"))
+        );
+        assert_eq!(
+            hour.apply("2001-09-09 01:46:40 +00:00"),
+            Some(String::from("01"))
+        );
+        assert_eq!(
+            month.apply("2001-09-09 01:46:40 +00:00"),
+            Some(String::from("Sep"))
+        );
+        assert_eq!(
+            hour.apply("\"2001-09-09T13:46:40.123+02:00\""),
+            Some(String::from("13"))
+        );
+        assert_eq!(
+            hour.apply("\"2001-09-09 22:00:00\""),
+            Some(String::from("22"))
+        );
+        assert_eq!(dow.apply("2024-05-01 +00:00"), Some(String::from("Wed")));
+        assert_eq!(month.apply("\"2024-05-01\""), Some(String::from("May")));
+        assert_eq!(month.apply("\"not a date\""), None);
+        assert_eq!(month.apply("null"), None);
+    }
+
+    #[test]
+    fn test_simple_messages_frequency_by_date_part() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let path = parquet.path();
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(4);
+        let arg_vec = vec![
+            "frequency",
+            path_str,
+            "-f=csv",
+            "-c=field_timestamp",
+            "--by-date-part=field_timestamp:dow",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(path, &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        // 2011-01-01 Sat, 2012-01-01 Sun, 2013-01-01 Tue, 2014-01-01 Wed
+        assert_eq!(6, actual.lines().count());
+        assert!(actual.starts_with("FIELD,VALUE,COUNT"));
+        assert!(actual.contains("field_timestamp,Sat,1"));
+        assert!(actual.contains("field_timestamp,Sun,1"));
+        assert!(actual.contains("field_timestamp,Tue,1"));
+        assert!(actual.contains("field_timestamp,Wed,1"));
+    }
+
+    #[test]
+    fn test_simple_messages_frequency_transform() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let path = parquet.path();
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(5);
+        let arg_vec = vec![
+            "frequency",
+            path_str,
+            "-f=csv",
+            "-c=field_string",
+            "-t=field_string:regex=^(\\w+) ",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(path, &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(4, actual.lines().count());
+        assert!(actual.starts_with("FIELD,VALUE,COUNT"));
+        assert!(actual.contains("field_string,odd,3"));
+        assert!(actual.contains("field_string,even,2,40.00"));
+        assert!(actual.contains("field_string,TOTAL,5,100.00"));
+    }
+
     #[test]
     fn test_simple_messages_frequency_vertical_csv() {
         let mut output = Cursor::new(Vec::new());
@@ -285,9 +1576,9 @@ mod tests {
         let vec = output.into_inner();
         let actual = str::from_utf8(&vec).unwrap();
 
-        assert_eq!(3, actual.lines().count());
+        assert_eq!(4, actual.lines().count());
         assert!(actual.starts_with("FIELD,VALUE,COUNT"));
-        assert!(actual.contains("field_string,\"odd\",2"));
+        assert!(actual.contains("field_string,\"odd\",2,66.67"));
         assert!(actual.contains("field_string,\"even\",1"));
         assert!(actual.ends_with(""));
     }
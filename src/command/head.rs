@@ -0,0 +1,131 @@
+use crate::api::Result;
+use crate::command::args;
+use crate::output::{NestedFormat, OutputFormat};
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::io::Write;
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("head")
+        .about("Show the first rows from parquet")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .validator(args::validate_number)
+                .help("Number of rows")
+                .default_value("10")
+                .long("limit")
+                .short('l'),
+        )
+        .arg(
+            Arg::with_name("nested")
+                .help("Nested values format")
+                .possible_values(NestedFormat::values())
+                .default_value("default")
+                .long("nested"),
+        )
+        .arg(
+            Arg::with_name("flatten")
+                .help("Expand struct fields into parent.child columns")
+                .long("flatten"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let nested = args::nested_format_value(matches, "nested")?;
+    let flatten = matches.is_present("flatten");
+    let columns = args::string_values(matches, "columns")?;
+    let limit = args::usize_value(matches, "limit")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from((path, columns))
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_flatten(flatten)
+        .with_nested(nested);
+
+    let headers = parquet.field_names()?;
+    let iter = parquet.values().take(limit);
+    let mut writer = args::writer(matches, headers, iter).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_head_simple_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = ["field_int32", "1", "2", ""].join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(15);
+        let arg_vec = vec!["head", path_str, "-l=2", "-c=field_int32"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_head_simple_messages_default_limit() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(15);
+        let arg_vec = vec!["head", path_str, "-f=csv", "-c=field_int32"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        // header + 10 rows
+        assert_eq!(11, actual.lines().count());
+        assert!(actual.starts_with("field_int32\n1\n2\n"));
+        assert!(actual.ends_with("\n9\n10\n"));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::api::{Error, Result};
+use crate::command::{args, estimate};
+use crate::output::OutputFormat;
+use crate::reader::{ParquetFile, ParquetSource};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::file::reader::{FileReader, Length, SerializedFileReader};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const TOTAL_VALUE: &str = "TOTAL";
+
+/// Footer summary of a parquet file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Listing {
+    path: PathBuf,
+    size: u64,
+    rows: u64,
+    row_groups: usize,
+    codecs: BTreeSet<String>,
+    created_by: Option<String>,
+}
+
+/// Row of a file whose footer could not be read, with the error.
+fn error_row(path: &Path, error: String, bytes: bool) -> Vec<String> {
+    let size = fs::metadata(path).map_or(0, |m| m.len());
+    let none = || String::from("-");
+
+    vec![
+        path.display().to_string(),
+        format_size(size, bytes),
+        none(),
+        none(),
+        none(),
+        none(),
+        error,
+    ]
+}
+
+/// Read the footer of a file, the rows are never read.
+fn listing(path: &Path) -> Result<Listing> {
+    let error = |e| Error::Parquet(path.to_path_buf(), e);
+    let source = ParquetSource::open(path).map_err(error)?;
+    let size = source.len();
+    let reader = SerializedFileReader::new(source).map_err(error)?;
+    let metadata = reader.metadata();
+    let codecs = metadata
+        .row_groups()
+        .iter()
+        .flat_map(|g| g.columns())
+        .map(|c| c.compression().to_string().to_lowercase())
+        .collect();
+
+    Ok(Listing {
+        path: path.to_path_buf(),
+        size,
+        rows: metadata.file_metadata().num_rows() as u64,
+        row_groups: metadata.num_row_groups(),
+        codecs,
+        created_by: metadata.file_metadata().created_by().map(String::from),
+    })
+}
+
+fn format_size(size: u64, bytes: bool) -> String {
+    if bytes {
+        size.to_string()
+    } else {
+        estimate::format_size(size)
+    }
+}
+
+fn format_row(listing: &Listing, bytes: bool) -> Vec<String> {
+    let size = format_size(listing.size, bytes);
+    let codecs = listing.codecs.iter().cloned().collect::<Vec<_>>();
+
+    vec![
+        listing.path.display().to_string(),
+        size,
+        listing.rows.to_string(),
+        listing.row_groups.to_string(),
+        Some(codecs.join(","))
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| String::from("-")),
+        listing
+            .created_by
+            .clone()
+            .unwrap_or_else(|| String::from("-")),
+    ]
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("ls")
+        .about("List parquet files with their size, rows, row groups, codecs and writer")
+        .arg(
+            Arg::with_name("bytes")
+                .help("Show sizes in bytes")
+                .long("bytes"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let bytes = matches.is_present("bytes");
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+    let mut total = Listing {
+        path: PathBuf::from(TOTAL_VALUE),
+        size: 0,
+        rows: 0,
+        row_groups: 0,
+        codecs: BTreeSet::new(),
+        created_by: None,
+    };
+    let mut rows = Vec::new();
+    let mut unreadable = false;
+
+    // unreadable files are listed with their error, they do not count in the total
+    for path in parquet.files() {
+        let listing = match listing(&path) {
+            Ok(listing) => listing,
+            Err(e) if matches.is_present("strict") => return Err(e),
+            Err(e) => {
+                let error = e.unreadable_file().map_or_else(|| e.to_string(), |f| f.1);
+
+                unreadable = true;
+                rows.push(error_row(&path, error, bytes));
+                continue;
+            }
+        };
+
+        total.size += listing.size;
+        total.rows += listing.rows;
+        total.row_groups += listing.row_groups;
+        total.codecs.extend(listing.codecs.iter().cloned());
+
+        rows.push(format_row(&listing, bytes));
+    }
+
+    rows.push(format_row(&total, bytes));
+
+    let mut headers = vec![
+        String::from("PATH"),
+        String::from("SIZE"),
+        String::from("ROWS"),
+        String::from("ROW GROUPS"),
+        String::from("CODEC"),
+        String::from("CREATED BY"),
+    ];
+
+    if unreadable {
+        headers.push(String::from("ERROR"));
+        rows.iter_mut()
+            .for_each(|r| r.resize(headers.len(), String::new()));
+    }
+
+    let rows = rows.into_iter().map(Ok);
+    let mut writer = args::writer(matches, headers, rows).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::fs;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ls_files() {
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.parquet");
+        let path2 = dir.path().join("2.parquet");
+        let msgs = api::tests::create_simple_messages(3);
+        let mut output = Cursor::new(Vec::new());
+
+        api::tests::write_simple_messages_parquet(&path1, &msgs[..1]);
+        api::tests::write_simple_messages_row_groups(&path2, &[&msgs[1..2], &msgs[2..]]);
+
+        let size = |p: &Path| fs::metadata(p).unwrap().len();
+        let listing2 = listing(&path2).unwrap();
+        let arg_vec = vec!["ls", dir.path().to_str().unwrap(), "--bytes", "-f=csv"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        run(&args, &mut output).unwrap();
+
+        let actual = String::from_utf8(output.into_inner()).unwrap();
+        let lines = actual.lines().collect::<Vec<_>>();
+        let codec = listing2.codecs.iter().next().unwrap().to_string();
+        let created_by = listing2.created_by.clone().unwrap();
+
+        assert_eq!(listing2.path, path2);
+        assert_eq!(listing2.size, size(&path2));
+        assert_eq!((listing2.rows, listing2.row_groups), (2, 2));
+        assert_eq!(lines[0], "PATH,SIZE,ROWS,ROW GROUPS,CODEC,CREATED BY");
+        assert!(lines.contains(
+            &format!(
+                "{},{},2,2,{},{}",
+                path2.display(),
+                size(&path2),
+                codec,
+                created_by
+            )
+            .as_str()
+        ));
+        assert_eq!(
+            lines.last(),
+            Some(
+                &format!("TOTAL,{},3,3,{},-", size(&path1) + size(&path2), codec)
+                    .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn test_ls_unreadable() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().to_str().unwrap();
+        let corrupt = dir.path().join("2.parquet");
+        let msgs = api::tests::create_simple_messages(2);
+        let ls = |args: Vec<&str>| {
+            let mut output = Cursor::new(Vec::new());
+            let args = def().get_matches_from_safe(args).unwrap();
+
+            run(&args, &mut output)
+                .map(|_| String::from_utf8(output.into_inner()).unwrap())
+        };
+
+        api::tests::write_simple_messages_parquet(&dir.path().join("1.parquet"), &msgs);
+        fs::write(&corrupt, b"corrupt").unwrap();
+
+        let listing = listing(&dir.path().join("1.parquet")).unwrap();
+        let actual = ls(vec!["ls", path, "--bytes", "-f=csv"]).unwrap();
+        let lines = actual.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "PATH,SIZE,ROWS,ROW GROUPS,CODEC,CREATED BY,ERROR");
+        assert_eq!(
+            lines[2],
+            format!(
+                "{},7,-,-,-,-,Parquet error: Invalid Parquet file. Size is smaller than footer",
+                corrupt.display()
+            )
+        );
+        assert_eq!(
+            lines[3],
+            format!("TOTAL,{},-,", format_row(&listing, true)[1..5].join(","))
+        );
+        assert!(ls(vec!["ls", path, "--strict"]).is_err());
+    }
+}
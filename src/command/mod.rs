@@ -1,7 +1,196 @@
 pub(in crate::command) mod args;
+pub(crate) mod estimate;
+#[cfg(unix)]
+pub(in crate::command) mod tty;
 
+pub mod alias;
+pub mod browse;
+pub mod charset;
+pub mod check;
+pub mod column;
+pub mod convert;
 pub mod count;
+pub mod dedup;
+pub mod domain;
+pub mod examples;
+pub mod export;
+pub mod find_row;
 pub mod frequency;
+pub mod head;
+pub mod ls;
+pub mod nulls;
+pub mod outliers;
+pub mod partitions;
+pub mod query;
 pub mod read;
 pub mod sample;
+pub mod sample_diff;
+pub mod scan_pii;
 pub mod schema;
+pub mod schema_diff;
+pub mod stats;
+pub mod suggest_types;
+pub mod tail;
+pub mod tokens;
+pub mod undo;
+pub mod validate_schema;
+pub mod verify;
+pub mod write;
+
+use crate::api::Result;
+use crate::reader::Discovery;
+use clap::{App, ArgMatches};
+use std::io::Write;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Definitions of every command, with the examples of each command after its help.
+pub fn defs() -> Vec<App<'static>> {
+    vec![
+        read::def(),
+        browse::def(),
+        query::def(),
+        count::def(),
+        ls::def(),
+        partitions::def(),
+        schema::def(),
+        schema_diff::def(),
+        validate_schema::def(),
+        verify::def(),
+        find_row::def(),
+        dedup::def(),
+        sample::def(),
+        sample_diff::def(),
+        frequency::def(),
+        domain::def(),
+        stats::def(),
+        nulls::def(),
+        outliers::def(),
+        scan_pii::def(),
+        charset::def(),
+        suggest_types::def(),
+        check::def(),
+        head::def(),
+        tail::def(),
+        column::def(),
+        tokens::def(),
+        convert::def(),
+        export::def(),
+        write::def(),
+        undo::def(),
+        alias::def(),
+        examples::def(),
+    ]
+    .into_iter()
+    .map(|app| match examples::help(app.get_name()) {
+        Some(help) => app.after_help(help),
+        None => app,
+    })
+    .collect()
+}
+
+/// Files of directories read by the matched command, the default ones
+/// when it does not take the discovery args.
+pub fn discovery(matches: &ArgMatches) -> Discovery {
+    match matches.subcommand() {
+        Some((_, args)) if args.try_contains_id("hidden").is_ok() => {
+            args::discovery_value(args)
+        }
+        _ => Discovery::default(),
+    }
+}
+
+/// Map each item on a pool of at most one thread per core, keeping the order of the items.
+pub fn parallel_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let map_items = || {
+        let mut results = Vec::new();
+
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+
+            match items.get(index) {
+                Some(item) => results.push((index, f(item))),
+                None => break,
+            }
+        }
+
+        results
+    };
+    let mut results = thread::scope(|scope| {
+        let handles = (0..workers.min(items.len()))
+            .map(|_| scope.spawn(map_items))
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|r| r.0);
+    results.into_iter().map(|r| r.1).collect()
+}
+
+/// Run the matched command.
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    match matches.subcommand() {
+        Some(("read", args)) => read::run(args, out),
+        Some(("browse", args)) => browse::run(args, out),
+        Some(("query", args)) => query::run(args, out),
+        Some(("schema", args)) => schema::run(args, out),
+        Some(("schema-diff", args)) => schema_diff::run(args, out),
+        Some(("validate-schema", args)) => validate_schema::run(args, out),
+        Some(("verify", args)) => verify::run(args, out),
+        Some(("find-row", args)) => find_row::run(args, out),
+        Some(("dedup", args)) => dedup::run(args, out),
+        Some(("sample", args)) => sample::run(args, out),
+        Some(("sample-diff", args)) => sample_diff::run(args, out),
+        Some(("count", args)) => count::run(args, out),
+        Some(("ls", args)) => ls::run(args, out),
+        Some(("partitions", args)) => partitions::run(args, out),
+        Some(("frequency", args)) => frequency::run(args, out),
+        Some(("domain", args)) => domain::run(args, out),
+        Some(("stats", args)) => stats::run(args, out),
+        Some(("nulls", args)) => nulls::run(args, out),
+        Some(("outliers", args)) => outliers::run(args, out),
+        Some(("scan-pii", args)) => scan_pii::run(args, out),
+        Some(("charset", args)) => charset::run(args, out),
+        Some(("suggest-types", args)) => suggest_types::run(args, out),
+        Some(("check", args)) => check::run(args, out),
+        Some(("alias", args)) => alias::run(args, out),
+        Some(("head", args)) => head::run(args, out),
+        Some(("tail", args)) => tail::run(args, out),
+        Some(("column", args)) => column::run(args, out),
+        Some(("tokens", args)) => tokens::run(args, out),
+        Some(("convert", args)) => convert::run(args, out),
+        Some(("export", args)) => export::run(args, out),
+        Some(("write", args)) => write::run(args, out),
+        Some(("undo", args)) => undo::run(args, out),
+        Some(("examples", args)) => examples::run(args, out),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_map() {
+        let items = (0..100).collect::<Vec<usize>>();
+
+        assert_eq!(
+            parallel_map(&items, |i| i * 2),
+            (0..100).map(|i| i * 2).collect::<Vec<_>>()
+        );
+        assert!(parallel_map(&[] as &[usize], |i| *i).is_empty());
+    }
+}
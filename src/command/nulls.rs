@@ -0,0 +1,271 @@
+use crate::api::Result;
+use crate::command::args;
+use crate::filter::Expr;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::schema::types::SchemaDescriptor;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Null count of a column, from the footer statistics or by reading the rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ColumnNulls {
+    column: String,
+    nulls: Option<u64>,
+    source: &'static str,
+}
+
+const SOURCE_STATS: &str = "stats";
+const SOURCE_SCAN: &str = "scan";
+const SOURCE_NONE: &str = "none";
+
+/// Leaf column paths and whether they are repeated, eg: list elements,
+/// only the given columns and their children when selected.
+fn leaf_columns(
+    parquet: &ParquetFile,
+    columns: &Option<Vec<String>>,
+) -> Result<Vec<(String, bool)>> {
+    let descriptor = SchemaDescriptor::new(Arc::new(parquet.schema()?));
+    let is_selected = |path: &str| match columns {
+        None => true,
+        Some(columns) => columns.iter().any(|c| {
+            let path = path.to_lowercase();
+            let column = c.to_lowercase();
+
+            path == column || path.starts_with(&format!("{}.", column))
+        }),
+    };
+
+    Ok(descriptor
+        .columns()
+        .iter()
+        .map(|c| (c.path().string(), c.max_rep_level() > 0))
+        .filter(|c| is_selected(&c.0))
+        .collect())
+}
+
+/// Count the nulls of the columns reading the rows, returning the number of rows read.
+fn scan_nulls(
+    parquet: &ParquetFile,
+    columns: &[String],
+    filter: Option<Expr>,
+) -> Result<(u64, HashMap<String, u64>)> {
+    let parquet = parquet
+        .clone()
+        .with_fields(Some(columns.to_vec()))
+        .with_filter(filter);
+    let names = parquet.field_names()?;
+    let mut counts = vec![0; names.len()];
+    let mut rows = 0;
+
+    for row in parquet.values() {
+        rows += 1;
+
+        for (count, value) in counts.iter_mut().zip(row?) {
+            if value.is_null() {
+                *count += 1;
+            }
+        }
+    }
+
+    Ok((rows, names.into_iter().zip(counts).collect()))
+}
+
+/// Null counts of each leaf column and the number of rows, statistics are used unless
+/// a filter is given or `scan` is set, columns missing statistics are read.
+///
+/// Repeated columns can only be counted from the statistics,
+/// their nulls are relative to the elements rather than the rows.
+fn column_nulls(
+    parquet: &ParquetFile,
+    columns: &Option<Vec<String>>,
+    filter: Option<Expr>,
+    scan: bool,
+) -> Result<(u64, Vec<ColumnNulls>)> {
+    let leaves = leaf_columns(parquet, columns)?;
+    let scan = scan || filter.is_some();
+    let stats = if scan {
+        HashMap::new()
+    } else {
+        parquet.null_counts()?
+    };
+    let missing = leaves
+        .iter()
+        .filter(|(c, repeated)| !repeated && !stats.contains_key(c))
+        .map(|c| c.0.clone())
+        .collect::<Vec<_>>();
+
+    let (rows, scanned) = if missing.is_empty() {
+        (parquet.num_rows()? as u64, HashMap::new())
+    } else {
+        scan_nulls(parquet, &missing, filter)?
+    };
+    let result = leaves
+        .into_iter()
+        .map(|(column, repeated)| match (stats.get(&column), repeated) {
+            (Some(nulls), _) => ColumnNulls {
+                nulls: Some(*nulls),
+                source: SOURCE_STATS,
+                column,
+            },
+            (None, false) => ColumnNulls {
+                nulls: scanned.get(&column).copied(),
+                source: SOURCE_SCAN,
+                column,
+            },
+            (None, true) => ColumnNulls {
+                nulls: None,
+                source: SOURCE_NONE,
+                column,
+            },
+        })
+        .collect();
+
+    Ok((rows, result))
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("nulls")
+        .about("Show the number of nulls of each column")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("scan")
+                .help(
+                    "Count the nulls reading every row instead of the footer statistics",
+                )
+                .long("scan"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+        .arg(args::cache_arg())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+    let (rows, nulls) =
+        column_nulls(&parquet, &columns, search, matches.is_present("scan"))?;
+    let headers = vec![
+        String::from("COLUMN"),
+        String::from("ROWS"),
+        String::from("NULLS"),
+        String::from("PERCENT"),
+        String::from("SOURCE"),
+    ];
+    let values = nulls.into_iter().map(|n| {
+        let percent = match (n.nulls, rows) {
+            (Some(_), 0) => format!("{:.2}", 0.0),
+            (Some(nulls), rows) => format!("{:.2}", nulls as f64 * 100.0 / rows as f64),
+            (None, _) => String::new(),
+        };
+
+        Ok(vec![
+            n.column,
+            rows.to_string(),
+            n.nulls.map(|c| c.to_string()).unwrap_or_default(),
+            percent,
+            n.source.to_string(),
+        ])
+    });
+
+    let mut writer = args::writer(matches, headers, values).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    fn nulls(path: &str, args: &[&str]) -> String {
+        let mut output = Cursor::new(Vec::new());
+        let mut arg_vec = vec!["nulls", path, "-f=csv"];
+
+        arg_vec.extend_from_slice(args);
+
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        run(&args, &mut output).unwrap();
+
+        String::from_utf8(output.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_nulls_stats_and_scan() {
+        let parquet = api::tests::temp_file("nulls", ".parquet");
+        let path = parquet.path().to_str().unwrap();
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[
+                r#"{"a": 1, "b": "x", "c": {"d": 1}}"#,
+                r#"{"a": 2}"#,
+                r#"{"a": 3, "b": null, "c": {"d": null}}"#,
+                r#"{"a": 4, "b": "y", "c": {"d": 4}}"#,
+            ],
+        );
+
+        assert_eq!(
+            nulls(path, &[]),
+            [
+                "COLUMN,ROWS,NULLS,PERCENT,SOURCE",
+                "a,4,0,0.00,stats",
+                "b,4,2,50.00,stats",
+                "c.d,4,2,50.00,stats",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            nulls(path, &["--scan", "-c=a,b"]),
+            [
+                "COLUMN,ROWS,NULLS,PERCENT,SOURCE",
+                "a,4,0,0.00,scan",
+                "b,4,2,50.00,scan",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            nulls(path, &["-c=b", "-s=b:NOT_NULL"]),
+            ["COLUMN,ROWS,NULLS,PERCENT,SOURCE", "b,2,0,0.00,scan", ""].join("\n")
+        );
+        assert_eq!(
+            nulls(path, &["-c=b", "-s=b:NULL"]),
+            ["COLUMN,ROWS,NULLS,PERCENT,SOURCE", "b,2,2,100.00,scan", ""].join("\n")
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use crate::value;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use stats::OnlineStats;
+use std::convert::TryFrom;
+use std::io::Write;
+
+/// Outlier detection method.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OutlierMethod {
+    // Values outside [q1 - k * iqr, q3 + k * iqr]
+    Iqr,
+
+    // Values more than k standard deviations away from the mean
+    ZScore,
+}
+
+const OUTLIER_METHOD_VALUES: &[&str] = &["iqr", "zscore"];
+
+impl OutlierMethod {
+    pub fn values() -> &'static [&'static str] {
+        OUTLIER_METHOD_VALUES
+    }
+
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            OutlierMethod::Iqr => 1.5,
+            OutlierMethod::ZScore => 3.0,
+        }
+    }
+}
+
+impl TryFrom<&str> for OutlierMethod {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "iqr" => Ok(OutlierMethod::Iqr),
+            "zscore" => Ok(OutlierMethod::ZScore),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+#[inline]
+fn field_number(field: &Field) -> Option<f64> {
+    match *field {
+        Field::Byte(v) => Some(f64::from(v)),
+        Field::Short(v) => Some(f64::from(v)),
+        Field::Int(v) => Some(f64::from(v)),
+        Field::Long(v) => Some(v as f64),
+        Field::UByte(v) => Some(f64::from(v)),
+        Field::UShort(v) => Some(f64::from(v)),
+        Field::UInt(v) => Some(f64::from(v)),
+        Field::ULong(v) => Some(v as f64),
+        Field::Float(v) => Some(f64::from(v)),
+        Field::Double(v) => Some(v),
+        Field::Decimal(ref v) => value::format_decimal(v).parse().ok(),
+        _ => None,
+    }
+}
+
+/// Linear interpolated quantile of sorted values.
+#[inline]
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = (sorted.len() - 1) as f64 * q;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+/// Compute the range of non outlier values.
+fn bounds(values: Vec<f64>, method: OutlierMethod, threshold: f64) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+
+    match method {
+        OutlierMethod::Iqr => {
+            let mut sorted = values;
+
+            sorted.sort_by(f64::total_cmp);
+
+            let q1 = quantile(&sorted, 0.25);
+            let q3 = quantile(&sorted, 0.75);
+            let iqr = q3 - q1;
+
+            Some((q1 - threshold * iqr, q3 + threshold * iqr))
+        }
+        OutlierMethod::ZScore => {
+            let stats = OnlineStats::from_slice(&values);
+            let distance = threshold * stats.stddev();
+
+            Some((stats.mean() - distance, stats.mean() + distance))
+        }
+    }
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("outliers")
+        .about("Show rows with extreme values of a numeric column")
+        .arg(
+            Arg::with_name("column")
+                .help("Numeric column")
+                .takes_value(true)
+                .required(true)
+                .long("column")
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("method")
+                .help("Outlier detection method")
+                .possible_values(OutlierMethod::values())
+                .default_value("iqr")
+                .long("method")
+                .short('m'),
+        )
+        .arg(
+            Arg::with_name("threshold")
+                .validator(args::validate_float)
+                .help("IQR multiplier or number of standard deviations (1.5 for iqr, 3 for zscore)")
+                .takes_value(true)
+                .long("threshold")
+                .short('t'),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .validator(args::validate_number)
+                .help("Max number of rows")
+                .default_value("500")
+                .long("limit")
+                .short('l'),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let search = args::search_value(matches)?;
+    let limit = args::usize_value(matches, "limit")?;
+    let path = args::path_value(matches, "path")?;
+    let column = matches
+        .value_of("column")
+        .ok_or_else(|| Error::InvalidArgument(String::from("column")))?;
+    let method = matches
+        .value_of("method")
+        .map(OutlierMethod::try_from)
+        .unwrap_or(Ok(OutlierMethod::Iqr))?;
+    let threshold = match matches.value_of("threshold") {
+        Some(_) => args::f64_value(matches, "threshold")?,
+        None => method.default_threshold(),
+    };
+
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_filter(search);
+    let headers = parquet.field_names()?;
+    let index = headers
+        .iter()
+        .position(|h| h.eq_ignore_ascii_case(column))
+        .ok_or_else(|| Error::InvalidArgument(String::from("column")))?;
+
+    let mut values = Vec::new();
+
+    for row in parquet.records() {
+        if let Some(value) = field_number(&row?[index]) {
+            values.push(value);
+        }
+    }
+
+    let (min, max) =
+        bounds(values, method, threshold).unwrap_or((f64::NEG_INFINITY, f64::INFINITY));
+
+    let iter = parquet
+        .records()
+        .filter(move |r| match r {
+            Ok(row) => field_number(&row[index])
+                .map(|v| v < min || v > max)
+                .unwrap_or(false),
+            Err(_) => true,
+        })
+        .map(|r| r.map(|row| row.iter().map(value::format_field).collect()))
+        .take(limit);
+
+    let mut writer = args::writer(matches, headers, iter).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_outlier_method_try_from() {
+        assert_eq!(OutlierMethod::try_from("IQR"), Ok(OutlierMethod::Iqr));
+        assert_eq!(OutlierMethod::try_from("zscore"), Ok(OutlierMethod::ZScore));
+        assert_eq!(
+            OutlierMethod::try_from("foo"),
+            Err(Error::InvalidArgument(String::from("foo")))
+        );
+    }
+
+    #[test]
+    fn test_outliers_bounds() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 100.0];
+
+        assert_eq!(quantile(&[1.0, 2.0, 3.0, 4.0], 0.5), 2.5);
+        assert_eq!(bounds(values, OutlierMethod::Iqr, 1.5), Some((-1.5, 8.5)));
+        assert_eq!(
+            bounds(
+                vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0],
+                OutlierMethod::ZScore,
+                2.0
+            ),
+            Some((1.0, 9.0))
+        );
+        assert_eq!(bounds(vec![], OutlierMethod::Iqr, 1.5), None);
+    }
+
+    #[test]
+    fn test_outliers_simple_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "field_int32,field_int64,field_float,field_double,field_string,field_boolean,field_timestamp",
+            "100,66,666.3,6666.4,\"even 66666\",true,2016-01-01 00:00:00 +00:00",
+            "",
+        ]
+        .join("\n");
+
+        let mut msgs = api::tests::create_simple_messages(6);
+
+        msgs[5].field_int32 = 100;
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        for method in &["-m=iqr", "-m=zscore"] {
+            let mut output = Cursor::new(Vec::new());
+            let subcomand = def();
+            let arg_vec = vec![
+                "outliers",
+                path_str,
+                "-c=field_int32",
+                method,
+                "-t=2",
+                "-f=csv",
+            ];
+            let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+            assert!(run(&args, &mut output).is_ok());
+
+            let vec = output.into_inner();
+            let actual = str::from_utf8(&vec).unwrap();
+
+            assert_eq!(actual, expected);
+        }
+
+        let subcomand = def();
+        let arg_vec = vec!["outliers", path_str, "-c=field_unknown"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert_eq!(
+            run(&args, &mut output),
+            Err(Error::InvalidArgument(String::from("column")))
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use crate::api::Result;
+use crate::command::{args, estimate};
+use crate::output::OutputFormat;
+use crate::reader::{self, ParquetFile};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const TOTAL_VALUE: &str = "TOTAL";
+
+/// Files of a partition, rows come from the footers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Partition {
+    files: usize,
+    rows: usize,
+    size: u64,
+}
+
+/// Name of the partition of a file below `root` using its first `depth` keys,
+/// eg: `date=2024-05-01/country=BR`, files outside of partitions are `-`.
+fn partition_name(root: &Path, path: &Path, depth: Option<usize>) -> String {
+    let partitions = reader::path_partitions(root, path);
+    let name = partitions
+        .iter()
+        .take(depth.unwrap_or(usize::MAX))
+        .map(|(key, value)| match value {
+            Field::Str(value) => format!("{}={}", key, value),
+            _ => format!("{}=null", key),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some(name)
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| String::from("-"))
+}
+
+fn format_size(size: u64, bytes: bool) -> String {
+    if bytes {
+        size.to_string()
+    } else {
+        estimate::format_size(size)
+    }
+}
+
+fn format_row(
+    name: &str,
+    partition: &Partition,
+    total_rows: usize,
+    bytes: bool,
+) -> Vec<String> {
+    let size = format_size(partition.size, bytes);
+    let share = match total_rows {
+        0 => 0.0,
+        total => partition.rows as f64 * 100.0 / total as f64,
+    };
+
+    vec![
+        name.to_string(),
+        partition.files.to_string(),
+        partition.rows.to_string(),
+        size,
+        format!("{:.2}", share),
+    ]
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("partitions")
+        .about("Show the files, rows and size of each hive partition of a directory")
+        .arg(
+            Arg::with_name("depth")
+                .validator(args::validate_number)
+                .help("Group the partitions by their first keys only, eg: --depth 1 for dates of date=/country= layouts")
+                .takes_value(true)
+                .long("depth"),
+        )
+        .arg(
+            Arg::with_name("bytes")
+                .help("Show sizes in bytes")
+                .long("bytes"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let bytes = matches.is_present("bytes");
+    let depth = match matches.value_of("depth") {
+        Some(_) => Some(args::usize_value(matches, "depth")?),
+        None => None,
+    };
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+    let mut partitions: BTreeMap<String, Partition> = BTreeMap::new();
+    let mut total = Partition::default();
+    let mut unreadable = Vec::new();
+
+    // unreadable files are listed with their error, they do not count in the total
+    for result in parquet.fingerprints() {
+        let (file, rows) = match result {
+            Ok((file, fingerprint)) => (file, fingerprint.num_rows),
+            Err(e) if matches.is_present("strict") => return Err(e),
+            Err(e) => match e.unreadable_file() {
+                Some((file, error)) => {
+                    let size = fs::metadata(file).map_or(0, |m| m.len());
+
+                    unreadable.push(vec![
+                        file.display().to_string(),
+                        String::from("1"),
+                        String::from("-"),
+                        format_size(size, bytes),
+                        String::from("-"),
+                        error,
+                    ]);
+                    continue;
+                }
+                None => return Err(e),
+            },
+        };
+        let size = fs::metadata(&file).map_or(0, |m| m.len());
+        let partition = partitions
+            .entry(partition_name(path, &file, depth))
+            .or_default();
+
+        for p in [partition, &mut total] {
+            p.files += 1;
+            p.rows += rows;
+            p.size += size;
+        }
+    }
+
+    let mut headers = vec![
+        String::from("PARTITION"),
+        String::from("FILES"),
+        String::from("ROWS"),
+        String::from("SIZE"),
+        String::from("SHARE"),
+    ];
+    let mut rows = partitions
+        .iter()
+        .map(|(name, p)| format_row(name, p, total.rows, bytes))
+        .chain(unreadable.iter().cloned())
+        .collect::<Vec<_>>();
+
+    rows.push(format_row(TOTAL_VALUE, &total, total.rows, bytes));
+
+    if !unreadable.is_empty() {
+        headers.push(String::from("ERROR"));
+        rows.iter_mut()
+            .for_each(|r| r.resize(headers.len(), String::new()));
+    }
+
+    let rows = rows.into_iter().map(Ok);
+    let mut writer = args::writer(matches, headers, rows).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_partitions_rows() {
+        let dir = api::tests::temp_dir();
+        let msgs = api::tests::create_simple_messages(4);
+        let path = |partition: &str, file: &str| dir.path().join(partition).join(file);
+        let files = [
+            (path("date=2024-05-01/country=BR", "0.parquet"), &msgs[..1]),
+            (path("date=2024-05-01/country=BR", "1.parquet"), &msgs[1..2]),
+            (path("date=2024-05-01/country=PT", "0.parquet"), &msgs[2..]),
+            (path("date=2024-05-02/country=BR", "0.parquet"), &msgs[..0]),
+        ];
+
+        for (file, msgs) in &files {
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            api::tests::write_simple_messages_parquet(file, msgs);
+        }
+
+        let sizes = files
+            .iter()
+            .map(|(f, _)| fs::metadata(f).unwrap().len())
+            .collect::<Vec<_>>();
+        let partitions = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec![
+                "partitions",
+                dir.path().to_str().unwrap(),
+                "--bytes",
+                "-f=csv",
+            ];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output).unwrap();
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            partitions(&[]),
+            [
+                String::from("PARTITION,FILES,ROWS,SIZE,SHARE"),
+                format!(
+                    "date=2024-05-01/country=BR,2,2,{},50.00",
+                    sizes[0] + sizes[1]
+                ),
+                format!("date=2024-05-01/country=PT,1,2,{},50.00", sizes[2]),
+                format!("date=2024-05-02/country=BR,1,0,{},0.00", sizes[3]),
+                format!("TOTAL,4,4,{},100.00", sizes.iter().sum::<u64>()),
+                String::new(),
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            partitions(&["--depth=1"]).lines().nth(1),
+            Some(
+                format!(
+                    "date=2024-05-01,3,4,{},100.00",
+                    sizes[..3].iter().sum::<u64>()
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn test_partitions_unreadable() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(2);
+        let good = dir.path().join("date=2024-05-01").join("0.parquet");
+        let corrupt = dir.path().join("date=2024-05-02").join("0.parquet");
+        let partitions = |args: Vec<&str>| {
+            let mut output = Cursor::new(Vec::new());
+            let args = def().get_matches_from_safe(args).unwrap();
+
+            run(&args, &mut output)
+                .map(|_| String::from_utf8(output.into_inner()).unwrap())
+        };
+
+        fs::create_dir_all(good.parent().unwrap()).unwrap();
+        fs::create_dir_all(corrupt.parent().unwrap()).unwrap();
+        api::tests::write_simple_messages_parquet(&good, &msgs);
+        fs::write(&corrupt, b"corrupt").unwrap();
+
+        let size = fs::metadata(&good).unwrap().len();
+
+        assert_eq!(
+            partitions(vec!["partitions", path, "--bytes", "-f=csv"]),
+            Ok([
+                String::from("PARTITION,FILES,ROWS,SIZE,SHARE,ERROR"),
+                format!("date=2024-05-01,1,2,{},100.00,", size),
+                format!(
+                    "{},1,-,7,-,Parquet error: Invalid Parquet file. Size is smaller than footer",
+                    corrupt.display()
+                ),
+                format!("TOTAL,1,2,{},100.00,", size),
+                String::new(),
+            ]
+            .join("\n"))
+        );
+        assert!(partitions(vec!["partitions", path, "--strict"]).is_err());
+    }
+}
@@ -0,0 +1,799 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::filter;
+use crate::output::{NestedFormat, OutputFormat};
+use crate::query::{self, Join, OrderBy, Select, SelectItem, COUNT_COLUMN};
+use crate::reader::ParquetFile;
+use crate::sort::{self, SortKey};
+use crate::value::Value;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+
+/// Rows of a statement given to the function consuming them.
+type Rows<'a> = &'a mut dyn Iterator<Item = Result<Vec<Value>>>;
+
+/// Index of a column in the headers, names are compared without case.
+#[inline]
+fn column_index(headers: &[String], column: &str) -> Result<usize> {
+    headers
+        .iter()
+        .position(|h| h.eq_ignore_ascii_case(column))
+        .ok_or_else(|| Error::InvalidArgument(format!("Unknown column '{}'", column)))
+}
+
+/// Index of a column in the `<table>.<column>` headers of a join,
+/// the table may be left out when a single table has the column.
+fn joined_index(headers: &[String], column: &str) -> Result<usize> {
+    if let Ok(index) = column_index(headers, column) {
+        return Ok(index);
+    }
+
+    let mut matches = headers.iter().enumerate().filter(|(_, h)| {
+        h.split_once('.')
+            .map(|(_, name)| name.eq_ignore_ascii_case(column))
+            .unwrap_or(false)
+    });
+
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => Ok(index),
+        (Some(_), Some(_)) => Err(Error::InvalidArgument(format!(
+            "Ambiguous column '{}'",
+            column
+        ))),
+        (None, _) => Err(Error::InvalidArgument(format!(
+            "Unknown column '{}'",
+            column
+        ))),
+    }
+}
+
+/// Path of a table registered with `--table`, other names are paths.
+#[inline]
+fn table_path(tables: &HashMap<String, String>, name: &str) -> String {
+    tables
+        .get(&name.to_lowercase())
+        .cloned()
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Sort the rows by the `ORDER BY` columns, nulls come last in ascending order.
+fn sort_rows(
+    rows: &mut [Vec<Value>],
+    headers: &[String],
+    order_by: &[OrderBy],
+) -> Result<()> {
+    let keys = order_by
+        .iter()
+        .map(|o| {
+            Ok(SortKey {
+                index: column_index(headers, &o.column)?,
+                descending: o.descending,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    rows.sort_by(|a, b| sort::compare(a, b, &keys));
+
+    Ok(())
+}
+
+/// Headers and rows of a `GROUP BY` or `COUNT(*)` query, one row for each distinct
+/// value of the group columns in the order they are first seen.
+///
+/// `count` is the number of rows when it is known without reading them.
+fn group_values<I>(
+    select: &Select,
+    names: &[String],
+    rows: I,
+    count: Option<u64>,
+) -> Result<(Vec<String>, Vec<Vec<Value>>)>
+where
+    I: Iterator<Item = Result<Vec<Value>>>,
+{
+    let keys = select
+        .group_by
+        .iter()
+        .map(|c| column_index(names, c))
+        .collect::<Result<Vec<_>>>()?;
+    let mut headers = Vec::with_capacity(select.items.len());
+    let mut items = Vec::with_capacity(select.items.len());
+
+    for item in &select.items {
+        match item {
+            SelectItem::All => {
+                return Err(Error::InvalidArgument(String::from(
+                    "'*' can not be selected with GROUP BY or COUNT(*)",
+                )))
+            }
+            SelectItem::Column(column) => {
+                let key = select
+                    .group_by
+                    .iter()
+                    .position(|g| g.eq_ignore_ascii_case(column))
+                    .ok_or_else(|| {
+                        Error::InvalidArgument(format!(
+                            "Column '{}' must be in GROUP BY",
+                            column
+                        ))
+                    })?;
+
+                headers.push(names[keys[key]].clone());
+                items.push(Some(key));
+            }
+            SelectItem::Count => {
+                headers.push(String::from(COUNT_COLUMN));
+                items.push(None);
+            }
+        }
+    }
+
+    let mut index: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut groups: Vec<(Vec<Value>, u64)> = Vec::new();
+
+    match count {
+        Some(count) if keys.is_empty() => groups.push((vec![], count)),
+        _ => {
+            for row in rows {
+                let row = row?;
+                let key = keys.iter().map(|k| row[*k].clone()).collect::<Vec<_>>();
+                let text = key.iter().map(Value::to_string).collect::<Vec<_>>();
+
+                match index.get(&text) {
+                    Some(i) => groups[*i].1 += 1,
+                    None => {
+                        index.insert(text, groups.len());
+                        groups.push((key, 1));
+                    }
+                }
+            }
+        }
+    }
+
+    // counting without groups always returns a single row
+    if keys.is_empty() && groups.is_empty() {
+        groups.push((vec![], 0));
+    }
+
+    let rows = groups
+        .into_iter()
+        .map(|(key, count)| {
+            items
+                .iter()
+                .map(|item| match item {
+                    Some(k) => key[*k].clone(),
+                    None => Value::UInt(count),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok((headers, rows))
+}
+
+/// Opens the tables of a query, with the read args of the command.
+type Open<'a> = &'a dyn Fn(&str) -> ParquetFile;
+
+/// Headers and rows of a `GROUP BY` or `COUNT(*)` query over a single table.
+fn group_rows(select: &Select, open: Open) -> Result<(Vec<String>, Vec<Vec<Value>>)> {
+    let parquet = open(&select.from)
+        .with_fields(Some(select.group_by.clone()))
+        .with_filter(select.filter.clone());
+    let names = parquet.field_names()?;
+    let count = match (select.group_by.is_empty(), &select.filter) {
+        (true, None) => Some(parquet.num_rows()? as u64),
+        _ => None,
+    };
+
+    group_values(select, &names, parquet.values(), count)
+}
+
+/// Table of a join, rows are kept in memory by the text of the `ON` column.
+struct JoinTable {
+    width: usize,
+    key: usize,
+    left: bool,
+    rows: HashMap<String, Vec<Vec<Field>>>,
+}
+
+impl JoinTable {
+    /// Headers qualified by the alias and the table, `key` is the `ON` column of `headers`.
+    fn read(join: &Join, headers: &[String], open: Open) -> Result<(Vec<String>, Self)> {
+        let parquet = open(&join.table);
+        let qualifier = join.alias.as_deref().unwrap_or(&join.table);
+        let names = qualify(qualifier, parquet.field_names()?);
+        let (left, right) = &join.on;
+
+        // the columns of the `ON` condition can be written in any order
+        let (key, on) = match (joined_index(headers, left), joined_index(&names, right)) {
+            (Ok(key), Ok(on)) => (key, on),
+            _ => (joined_index(headers, right)?, joined_index(&names, left)?),
+        };
+
+        let mut rows: HashMap<String, Vec<Vec<Field>>> = HashMap::new();
+
+        for row in parquet.records() {
+            let row = row?;
+
+            // nulls never match
+            if !matches!(row[on], Field::Null) {
+                let text = filter::field_text(&row[on]).into_owned();
+
+                rows.entry(text).or_default().push(row);
+            }
+        }
+
+        let table = Self {
+            width: names.len(),
+            key,
+            left: join.left,
+            rows,
+        };
+
+        Ok((names, table))
+    }
+
+    /// Rows of the join of a row with the matching rows of the table.
+    fn join(&self, row: Vec<Field>) -> Vec<Vec<Field>> {
+        let matches = match row[self.key] {
+            Field::Null => None,
+            ref key => self.rows.get(filter::field_text(key).as_ref()),
+        };
+
+        match (matches, self.left) {
+            (Some(matches), _) => matches
+                .iter()
+                .map(|m| row.iter().chain(m.iter()).cloned().collect())
+                .collect(),
+            (None, true) => {
+                let mut row = row;
+
+                row.resize(row.len() + self.width, Field::Null);
+
+                vec![row]
+            }
+            (None, false) => vec![],
+        }
+    }
+}
+
+#[inline]
+fn qualify(qualifier: &str, names: Vec<String>) -> Vec<String> {
+    names
+        .into_iter()
+        .map(|n| format!("{}.{}", qualifier, n))
+        .collect()
+}
+
+/// Rows of a statement with joins, writing qualified `<table>.<column>` headers.
+///
+/// Joined tables are read into memory while the rows of the `FROM` table are streamed,
+/// the `WHERE` clause is applied to the joined rows.
+fn join_rows<F>(select: &Select, open: Open, sink: F) -> Result<()>
+where
+    F: FnOnce(Vec<String>, Rows) -> Result<()>,
+{
+    let parquet = open(&select.from);
+    let qualifier = select.alias.as_deref().unwrap_or(&select.from);
+    let mut names = qualify(qualifier, parquet.field_names()?);
+    let mut tables = Vec::with_capacity(select.joins.len());
+
+    for join in &select.joins {
+        let (headers, table) = JoinTable::read(join, &names, open)?;
+
+        names.extend(headers);
+        tables.push(table);
+    }
+
+    let filter = match &select.filter {
+        Some(expr) => {
+            let mut error = None;
+            let bound = expr.map_columns(&mut |c: &String| {
+                joined_index(&names, c).unwrap_or_else(|e| {
+                    error = Some(e);
+                    0
+                })
+            });
+
+            match error {
+                Some(e) => return Err(e),
+                None => Some(bound),
+            }
+        }
+        None => None,
+    };
+
+    // columns are written as named by the headers of the join
+    let resolve = |c: &String| joined_index(&names, c).map(|i| names[i].clone());
+    let group_by = select
+        .group_by
+        .iter()
+        .map(resolve)
+        .collect::<Result<Vec<_>>>()?;
+    let order_by = select
+        .order_by
+        .iter()
+        .map(|o| match o.column.as_str() {
+            COUNT_COLUMN if select.items.contains(&SelectItem::Count) => Ok(o.clone()),
+            _ => resolve(&o.column).map(|column| OrderBy {
+                column,
+                descending: o.descending,
+            }),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let items = select
+        .items
+        .iter()
+        .map(|item| match item {
+            SelectItem::Column(c) => resolve(c).map(SelectItem::Column),
+            item => Ok(item.clone()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let select = Select {
+        items,
+        group_by,
+        order_by,
+        ..select.clone()
+    };
+
+    let rows = parquet
+        .records()
+        .flat_map(|row| -> Vec<Result<Vec<Field>>> {
+            let row = match row {
+                Ok(row) => row,
+                Err(e) => return vec![Err(e)],
+            };
+
+            tables
+                .iter()
+                .fold(vec![row], |rows, table| {
+                    rows.into_iter().flat_map(|r| table.join(r)).collect()
+                })
+                .into_iter()
+                .map(Ok)
+                .collect()
+        })
+        .filter(|row| match (row, &filter) {
+            (Ok(row), Some(filter)) => filter.is_match(&|i: &usize| &row[*i]),
+            _ => true,
+        })
+        .map(|row| {
+            row.map(|r| {
+                r.iter()
+                    .map(|f| Value::from_field(f, NestedFormat::Default))
+                    .collect::<Vec<_>>()
+            })
+        });
+
+    write_rows(&select, names, rows, sink)
+}
+
+/// Project, group, sort and limit rows, selecting the `items` of the statement.
+fn write_rows<I, F>(select: &Select, names: Vec<String>, rows: I, sink: F) -> Result<()>
+where
+    I: Iterator<Item = Result<Vec<Value>>>,
+    F: FnOnce(Vec<String>, Rows) -> Result<()>,
+{
+    let limit = select.limit.unwrap_or(usize::MAX);
+    let grouped =
+        !select.group_by.is_empty() || select.items.contains(&SelectItem::Count);
+
+    if grouped {
+        let (headers, mut rows) = group_values(select, &names, rows, None)?;
+
+        sort_rows(&mut rows, &headers, &select.order_by)?;
+        rows.truncate(limit);
+
+        return sink(headers, &mut rows.into_iter().map(Ok));
+    }
+
+    let mut columns = Vec::new();
+
+    for item in &select.items {
+        match item {
+            SelectItem::Column(column) => columns.push(column_index(&names, column)?),
+            _ => columns.extend(0..names.len()),
+        }
+    }
+
+    let headers = columns.iter().map(|c| names[*c].clone()).collect();
+    let project = |row: Vec<Value>| columns.iter().map(|c| row[*c].clone()).collect();
+
+    if select.order_by.is_empty() {
+        return sink(headers, &mut rows.map(|r| r.map(project)).take(limit));
+    }
+
+    let mut rows = rows.collect::<Result<Vec<_>>>()?;
+
+    sort_rows(&mut rows, &names, &select.order_by)?;
+    rows.truncate(limit);
+
+    sink(headers, &mut rows.into_iter().map(project).map(Ok))
+}
+
+/// Run a `SELECT` statement, giving its headers and rows to `sink`.
+///
+/// `ORDER BY`, `GROUP BY` and joins keep the matching rows in memory.
+fn execute<F>(select: &Select, open: Open, sink: F) -> Result<()>
+where
+    F: FnOnce(Vec<String>, Rows) -> Result<()>,
+{
+    let limit = select.limit.unwrap_or(usize::MAX);
+    let grouped =
+        !select.group_by.is_empty() || select.items.contains(&SelectItem::Count);
+
+    if !select.joins.is_empty() {
+        return join_rows(select, open, sink);
+    }
+
+    if grouped {
+        let (headers, mut rows) = group_rows(select, open)?;
+
+        sort_rows(&mut rows, &headers, &select.order_by)?;
+        rows.truncate(limit);
+
+        return sink(headers, &mut rows.into_iter().map(Ok));
+    }
+
+    // `*` selects every column
+    let columns = select
+        .items
+        .iter()
+        .map(|item| match item {
+            SelectItem::Column(column) => Some(column.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    let parquet = open(&select.from).with_fields(columns.clone());
+    let headers = parquet.field_names()?;
+
+    for column in columns.iter().flatten() {
+        column_index(&headers, column)?;
+    }
+
+    if select.order_by.is_empty() {
+        let parquet = parquet.with_filter(select.filter.clone());
+
+        return sink(headers, &mut parquet.values().take(limit));
+    }
+
+    // order by columns that are not selected are read and dropped after sorting
+    let mut fields = columns;
+
+    for order in &select.order_by {
+        if let (Some(fields), Err(_)) =
+            (fields.as_mut(), column_index(&headers, &order.column))
+        {
+            fields.push(order.column.clone());
+        }
+    }
+
+    let parquet = parquet
+        .with_fields(fields)
+        .with_filter(select.filter.clone());
+    let names = parquet.field_names()?;
+    let mut rows = parquet.values().collect::<Result<Vec<_>>>()?;
+
+    sort_rows(&mut rows, &names, &select.order_by)?;
+    rows.truncate(limit);
+    rows.iter_mut().for_each(|r| r.truncate(headers.len()));
+
+    sink(headers, &mut rows.into_iter().map(Ok))
+}
+
+/// Replace the registered table names of a statement by their paths,
+/// the names qualify the columns of joins unless an alias is given.
+fn resolve_tables(select: Select, tables: &HashMap<String, String>) -> Select {
+    let alias = |name: &str, alias: Option<String>| {
+        alias.or_else(|| {
+            Some(name.to_string()).filter(|_| tables.contains_key(&name.to_lowercase()))
+        })
+    };
+    let joins = select
+        .joins
+        .into_iter()
+        .map(|join| Join {
+            table: table_path(tables, &join.table),
+            alias: alias(&join.table, join.alias),
+            ..join
+        })
+        .collect();
+
+    Select {
+        from: table_path(tables, &select.from),
+        alias: alias(&select.from, select.alias),
+        joins,
+        ..select
+    }
+}
+
+/// Values of the single column of a `IN (SELECT ..)` subquery, nulls are left out.
+fn subquery_values(
+    select: &Select,
+    tables: &HashMap<String, String>,
+    open: Open,
+) -> Result<HashSet<String>> {
+    let select = resolve_tables(select.clone(), tables);
+    let mut values = HashSet::new();
+
+    execute(&select, open, |headers, rows| {
+        if headers.len() != 1 {
+            return Err(Error::InvalidArgument(String::from(
+                "Subqueries must select a single column",
+            )));
+        }
+
+        for row in rows {
+            match row?.remove(0) {
+                Value::Null => {}
+                Value::Str(text) => {
+                    values.insert(text);
+                }
+                value => {
+                    values.insert(value.to_string());
+                }
+            }
+        }
+
+        Ok(())
+    })?;
+
+    Ok(values)
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("query")
+        .about("Run a SQL-like query")
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("table")
+                .help("Register a path as a table of the query, eg: --table users=data/users/")
+                .validator(args::validate_table)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .long("table")
+                .short('t'),
+        )
+        .arg(
+            Arg::with_name("query")
+                .validator(args::validate_query)
+                .help("Query, eg: \"SELECT a, b FROM 'data/' WHERE c > 5 LIMIT 100\"")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
+}
+
+/// Rows of a `SELECT` statement, subqueries run while the statement is parsed.
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let tables = args::table_values(matches, "table")?;
+    let open = |path: &str| {
+        ParquetFile::from(Path::new(path))
+            .with_discovery(args::discovery_value(matches))
+            .with_strict(matches.is_present("strict"))
+    };
+    let mut subquery = |select: &Select| subquery_values(select, &tables, &open);
+    let select = query::parse_select_with(
+        matches.value_of("query").unwrap_or_default(),
+        &mut subquery,
+    )?;
+    let select = resolve_tables(select, &tables);
+
+    execute(&select, &open, |headers, rows| {
+        let mut writer = args::writer(matches, headers, rows).format(format);
+
+        writer.write(out)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    fn query(sql: &str) -> Result<String> {
+        query_tables(sql, &[])
+    }
+
+    fn query_tables(sql: &str, tables: &[&str]) -> Result<String> {
+        let mut output = Cursor::new(Vec::new());
+        let mut arg_vec = vec!["query", "-f=csv"];
+
+        for table in tables {
+            arg_vec.extend_from_slice(&["--table", table]);
+        }
+
+        arg_vec.push(sql);
+
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        run(&args, &mut output)?;
+
+        Ok(String::from_utf8(output.into_inner()).unwrap())
+    }
+
+    #[test]
+    fn test_query_select_where_order_limit() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(6);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert_eq!(
+            query(&format!(
+                "SELECT field_int32 FROM '{}' WHERE field_int32 > 2 ORDER BY field_int64 DESC LIMIT 2",
+                path
+            ))
+            .unwrap(),
+            "field_int32\n6\n5\n"
+        );
+        assert_eq!(
+            query(&format!(
+                "SELECT field_int32, field_boolean FROM '{}' WHERE field_string LIKE 'odd%' LIMIT 2",
+                path
+            ))
+            .unwrap(),
+            "field_int32,field_boolean\n1,false\n3,false\n"
+        );
+        assert_eq!(
+            query(&format!(
+                "SELECT * FROM '{}' ORDER BY field_int32 DESC LIMIT 1",
+                path
+            ))
+            .unwrap()
+            .lines()
+            .nth(1)
+            .map(|l| l.starts_with("6,66,")),
+            Some(true)
+        );
+        assert_eq!(
+            query(&format!("SELECT unknown FROM '{}'", path)),
+            Err(Error::InvalidArgument(String::from(
+                "Unknown column 'unknown'"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_query_group_by_count() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert_eq!(
+            query(&format!(
+                "SELECT field_boolean, COUNT(*) FROM '{}' GROUP BY field_boolean ORDER BY COUNT(*) DESC",
+                path
+            ))
+            .unwrap(),
+            "field_boolean,count\nfalse,3\ntrue,2\n"
+        );
+        assert_eq!(
+            query(&format!("SELECT COUNT(*) FROM '{}'", path)).unwrap(),
+            "count\n5\n"
+        );
+        assert_eq!(
+            query(&format!(
+                "SELECT COUNT(*) FROM '{}' WHERE field_int32 > 9",
+                path
+            ))
+            .unwrap(),
+            "count\n0\n"
+        );
+        assert_eq!(
+            query(&format!(
+                "SELECT field_int32, COUNT(*) FROM '{}' GROUP BY field_boolean",
+                path
+            )),
+            Err(Error::InvalidArgument(String::from(
+                "Column 'field_int32' must be in GROUP BY"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_query_join_and_subquery() {
+        let users = api::tests::temp_file("users", ".parquet");
+        let orders = api::tests::temp_file("orders", ".parquet");
+        let tables = [
+            format!("users={}", users.path().to_str().unwrap()),
+            format!("orders={}", orders.path().to_str().unwrap()),
+        ];
+        let tables = tables.iter().map(String::as_str).collect::<Vec<_>>();
+
+        api::tests::write_json_parquet(
+            users.path(),
+            &[
+                r#"{"id": 1, "name": "ana", "score": 3.0}"#,
+                r#"{"id": 2, "name": "bob", "score": 2.5}"#,
+                r#"{"id": 3, "name": "eve", "score": 1.0}"#,
+            ],
+        );
+        api::tests::write_json_parquet(
+            orders.path(),
+            &[
+                r#"{"user_id": 1, "total": 10}"#,
+                r#"{"user_id": 1, "total": 20}"#,
+                r#"{"user_id": 3, "total": 5}"#,
+                r#"{"total": 7}"#,
+            ],
+        );
+
+        assert_eq!(
+            query_tables(
+                "SELECT name, o.total FROM users JOIN orders o ON id = o.user_id WHERE total > 5",
+                &tables
+            )
+            .unwrap(),
+            "users.name,o.total\n\"ana\",10\n\"ana\",20\n"
+        );
+        assert_eq!(
+            query_tables(
+                "SELECT u.name, COUNT(*) FROM users u LEFT JOIN orders ON orders.user_id = u.id GROUP BY u.name ORDER BY u.name",
+                &tables
+            )
+            .unwrap(),
+            "u.name,count\n\"ana\",2\n\"bob\",1\n\"eve\",1\n"
+        );
+        assert_eq!(
+            query_tables(
+                "SELECT u.name, total FROM users u LEFT JOIN orders ON user_id = id WHERE u.id = 2",
+                &tables
+            )
+            .unwrap(),
+            "u.name,orders.total\n\"bob\",null\n"
+        );
+        assert_eq!(
+            query_tables(
+                "SELECT name FROM users WHERE id IN (SELECT user_id FROM orders) ORDER BY name DESC",
+                &tables
+            )
+            .unwrap(),
+            "name\n\"eve\"\n\"ana\"\n"
+        );
+        assert_eq!(
+            query_tables(
+                "SELECT name FROM users WHERE score IN (SELECT user_id FROM orders) ORDER BY name",
+                &tables
+            )
+            .unwrap(),
+            "name\n\"ana\"\n\"eve\"\n"
+        );
+        assert_eq!(
+            query_tables(
+                "SELECT name FROM users WHERE id IN (SELECT user_id, total FROM orders)",
+                &tables
+            ),
+            Err(Error::InvalidArgument(String::from(
+                "Subqueries must select a single column"
+            )))
+        );
+        assert_eq!(
+            query_tables(
+                "SELECT total FROM users JOIN orders ON id = user_id JOIN orders o ON id = o.user_id",
+                &tables
+            ),
+            Err(Error::InvalidArgument(String::from(
+                "Ambiguous column 'total'"
+            )))
+        );
+    }
+}
@@ -1,9 +1,139 @@
-use crate::api::Result;
+use crate::api::{Error, Result};
 use crate::command::args;
-use crate::output::{OutputFormat, OutputWriter};
+use crate::command::estimate::{self, ByteCounter, Estimate};
+#[cfg(unix)]
+use crate::command::tty;
+use crate::filter::Expr;
+use crate::group::Nesting;
+use crate::output::{self, Layout, NestedFormat, OutputFormat, OutputWriter};
+use crate::query::OrderBy;
 use crate::reader::ParquetFile;
+use crate::sort::{self, SortKey};
+use crate::value::{Value, ValueFormat};
+use arrow::ipc::writer::StreamWriter;
 use clap::{App, Arg, ArgMatches, SubCommand};
-use std::io::Write;
+use regex::Regex;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+const ARROW_BATCH_SIZE: usize = 1024;
+
+/// Predict the size of the output by writing a sample of the rows,
+/// the number of rows comes from the file metadata.
+fn estimate(
+    parquet: &ParquetFile,
+    headers: Vec<String>,
+    format: OutputFormat,
+    layout: Layout,
+    limit: usize,
+) -> Result<Estimate> {
+    let mut header = ByteCounter::default();
+    let mut sample = ByteCounter::default();
+    let mut sample_rows = 0;
+
+    OutputWriter::new(headers.clone(), std::iter::empty::<Result<Vec<Value>>>())
+        .format(format)
+        .layout(layout)
+        .write(&mut header)?;
+
+    let values = parquet
+        .values()
+        .take(limit.min(estimate::SAMPLE_ROWS))
+        .inspect(|_| sample_rows += 1);
+
+    OutputWriter::new(headers, values)
+        .format(format)
+        .layout(layout)
+        .write(&mut sample)?;
+
+    Ok(Estimate {
+        rows: parquet.num_rows()?.min(limit),
+        sample_rows,
+        header_bytes: header.bytes,
+        sample_bytes: sample.bytes,
+    })
+}
+
+/// Rows sorted by the order by columns, limited reads keep the first rows in a heap
+/// and larger reads are sorted by an external merge sort.
+///
+/// Order by columns which are not selected are read and dropped after sorting.
+fn sorted_values<'a>(
+    parquet: ParquetFile,
+    columns: Option<Vec<String>>,
+    order_by: &[OrderBy],
+    limit: usize,
+) -> Result<Box<dyn Iterator<Item = Result<Vec<Value>>> + 'a>> {
+    let headers = parquet.field_names()?;
+    let position = |names: &[String], column: &str| {
+        names.iter().position(|n| n.eq_ignore_ascii_case(column))
+    };
+    let mut fields = columns;
+
+    for order in order_by {
+        if let (Some(fields), None) = (fields.as_mut(), position(&headers, &order.column))
+        {
+            fields.push(order.column.clone());
+        }
+    }
+
+    let parquet = parquet.with_fields(fields);
+    let names = parquet.field_names()?;
+    let keys = order_by
+        .iter()
+        .map(|o| {
+            let index = position(&names, &o.column).ok_or_else(|| {
+                Error::InvalidArgument(format!("Unknown column '{}'", o.column))
+            })?;
+
+            Ok(SortKey {
+                index,
+                descending: o.descending,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let width = headers.len();
+    let truncate = move |row: Result<Vec<Value>>| {
+        row.map(|mut r| {
+            r.truncate(width);
+            r
+        })
+    };
+
+    if limit <= sort::MAX_HEAP_ROWS {
+        let rows = sort::top_k(parquet.values(), &keys, limit)?;
+
+        return Ok(Box::new(rows.into_iter().map(Ok).map(truncate)));
+    }
+
+    let rows = sort::external_sort(parquet.values(), &keys, sort::SPILL_ROWS)?;
+
+    Ok(Box::new(rows.take(limit).map(truncate)))
+}
+
+/// Stream at most `limit` rows as arrow record batches keeping the column types,
+/// the stream is still valid when there are no rows.
+fn write_arrow<W: Write>(parquet: &ParquetFile, limit: usize, out: &mut W) -> Result<()> {
+    let schema = parquet.arrow_schema()?;
+    let mut writer = StreamWriter::try_new(out, &schema)?;
+    let mut remaining = limit;
+
+    for batch in parquet.batches(ARROW_BATCH_SIZE.min(limit).max(1)) {
+        if remaining == 0 {
+            break;
+        }
+
+        let batch = batch?;
+        let rows = batch.num_rows().min(remaining);
+
+        writer.write(&batch.slice(0, rows))?;
+        remaining -= rows;
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
 
 pub fn def() -> App<'static> {
     SubCommand::with_name("read")
@@ -17,14 +147,13 @@ pub fn def() -> App<'static> {
                 .short('c'),
         )
         .arg(
-            Arg::with_name("search")
-                .validator(args::validate_filter)
-                .help("Search columns")
+            Arg::with_name("exclude")
+                .help("Exclude columns from parquet")
                 .takes_value(true)
-                .long("search")
-                .multiple(true)
-                .short('s'),
+                .long("exclude")
+                .multiple(true),
         )
+        .args(args::search_args())
         .arg(
             Arg::with_name("limit")
                 .validator(args::validate_number)
@@ -33,6 +162,49 @@ pub fn def() -> App<'static> {
                 .long("limit")
                 .short('l'),
         )
+        .arg(
+            Arg::with_name("order-by")
+                .validator(args::validate_order_by)
+                .help("Sort rows by the given columns, eg: --order-by age:desc,name")
+                .takes_value(true)
+                .multiple(true)
+                .long("order-by"),
+        )
+        .arg(
+            Arg::with_name("nested")
+                .help("Nested values format")
+                .possible_values(NestedFormat::values())
+                .default_value("default")
+                .long("nested"),
+        )
+        .arg(
+            Arg::with_name("row-numbers")
+                .help("Prepend a # column with the number of each row in the files, from 1")
+                .long("row-numbers"),
+        )
+        .arg(
+            Arg::with_name("with-filename")
+                .help("Append a #file column with the path of the file of each row")
+                .long("with-filename"),
+        )
+        .arg(
+            Arg::with_name("summarize-columns")
+                .help("Show only the non null and non default values of each row as ROW, COLUMN, VALUE rows")
+                .conflicts_with_all(&["peek", "estimate"])
+                .long("summarize-columns"),
+        )
+        .arg(
+            Arg::with_name("flatten")
+                .help("Expand struct fields into parent.child columns")
+                .long("flatten"),
+        )
+        .arg(
+            Arg::with_name("group")
+                .help("Render the columns of a group as a json object, eg: --group 'address=addr_*'")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .long("group"),
+        )
         .arg(
             Arg::with_name("format")
                 .help("Output format")
@@ -41,6 +213,26 @@ pub fn def() -> App<'static> {
                 .long("format")
                 .short('f'),
         )
+        .arg(
+            Arg::with_name("output")
+                .help("Write the rows to a file or to the clipboard, eg: --output clipboard")
+                .takes_value(true)
+                .long("output")
+                .short('o'),
+        )
+        .arg(
+            Arg::with_name("peek")
+                .validator(args::validate_number)
+                .help("Write the rows N at a time, reading the next ones on a key press")
+                .conflicts_with_all(&["output", "estimate"])
+                .takes_value(true)
+                .long("peek"),
+        )
+        .arg(
+            Arg::with_name("estimate")
+                .help("Estimate the output size from a sample instead of reading")
+                .long("estimate"),
+        )
         .arg(
             Arg::with_name("path")
                 .validator(args::validate_path)
@@ -48,25 +240,266 @@ pub fn def() -> App<'static> {
                 .required(true)
                 .index(1),
         )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
 }
 
 pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    match matches.value_of("output") {
+        Some(output::CLIPBOARD) => {
+            let mut buffer = Vec::new();
+
+            write(matches, &mut buffer)?;
+
+            output::copy_to_clipboard(&buffer)
+        }
+        Some(file) => {
+            let mut writer = BufWriter::new(File::create(file)?);
+
+            write(matches, &mut writer)?;
+
+            writer.flush()?;
+
+            Ok(())
+        }
+        None => write(matches, out),
+    }
+}
+
+/// Search regexes by the index of their column, highlighted in colored tables.
+fn highlights(search: Option<&Expr>, headers: &[String]) -> Vec<(usize, Regex)> {
+    let regexes = search.map(Expr::regexes).unwrap_or_default();
+
+    regexes
+        .into_iter()
+        .filter_map(|(column, regex)| {
+            let index = headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(column))?;
+
+            Some((index, regex.clone()))
+        })
+        .collect()
+}
+
+fn write<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
     let format = args::output_format_value(matches, "format")?;
+    let nested = args::nested_format_value(matches, "nested")?;
+    let flatten = matches.is_present("flatten");
+    let row_numbers = matches.is_present("row-numbers");
+    let filename = matches.is_present("with-filename");
+    let groups = args::group_values(matches, "group")?;
     let columns = args::string_values(matches, "columns")?;
-    let search = args::filter_values(matches, "search")?;
+    let exclude = args::string_values(matches, "exclude")?;
+    let search = args::search_value(matches)?;
+    let order_by = args::order_by_values(matches, "order-by")?;
     let limit = args::usize_value(matches, "limit")?;
     let path = args::path_value(matches, "path")?;
+
+    if format == OutputFormat::Arrow && search.is_some() {
+        return Err(Error::InvalidArgument(String::from("search")));
+    }
+
+    if format == OutputFormat::Arrow && !order_by.is_empty() {
+        return Err(Error::InvalidArgument(String::from("order-by")));
+    }
+
+    if format == OutputFormat::Arrow && row_numbers {
+        return Err(Error::InvalidArgument(String::from("row-numbers")));
+    }
+
+    if format == OutputFormat::Arrow && filename {
+        return Err(Error::InvalidArgument(String::from("with-filename")));
+    }
+
+    if format == OutputFormat::Arrow && !groups.is_empty() {
+        return Err(Error::InvalidArgument(String::from("group")));
+    }
+
+    if format == OutputFormat::Arrow && matches.is_present("summarize-columns") {
+        return Err(Error::InvalidArgument(String::from("summarize-columns")));
+    }
+
+    // pages of csv-like formats would repeat the header
+    if matches.is_present("peek")
+        && !matches!(format, OutputFormat::Tabular | OutputFormat::Vertical)
+    {
+        return Err(Error::InvalidArgument(String::from("peek")));
+    }
+
+    let filter = search.clone();
     let parquet = ParquetFile::from(path)
-        .with_fields(columns)
-        .with_filters(search);
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(columns.clone())
+        .with_exclude(exclude)
+        .with_flatten(flatten)
+        .with_nested(nested)
+        .with_filter(search)
+        .with_row_numbers(row_numbers)
+        .with_filename(filename);
 
     let headers = parquet.field_names()?;
-    let iter = parquet.iter().take(limit);
-    let mut writer = OutputWriter::new(headers, iter).format(format);
+
+    if matches.is_present("estimate") {
+        let layout = args::layout_value(matches);
+
+        return estimate::write(
+            matches,
+            &estimate(&parquet, headers, format, layout, limit)?,
+            out,
+        );
+    }
+
+    if format == OutputFormat::Arrow {
+        return write_arrow(&parquet, limit, out);
+    }
+
+    let iter: Box<dyn Iterator<Item = Result<Vec<Value>>>> = if order_by.is_empty() {
+        Box::new(parquet.values().take(limit))
+    } else {
+        sorted_values(parquet, columns, &order_by, limit)?
+    };
+    let (headers, iter) = if groups.is_empty() {
+        (headers, iter)
+    } else {
+        let nesting = Nesting::new(&headers, &groups);
+        let headers = nesting.headers().to_vec();
+        let values = args::value_format_value(matches);
+        let iter: Box<dyn Iterator<Item = Result<Vec<Value>>>> =
+            Box::new(iter.map(move |row| row.map(|r| nesting.apply(r, &values))));
+
+        (headers, iter)
+    };
+    let highlights = highlights(filter.as_ref(), &headers);
+
+    if matches.is_present("peek") {
+        let rows = args::usize_value(matches, "peek")?;
+        let write_page = |page: Vec<Result<Vec<Value>>>| {
+            args::writer(matches, headers.clone(), page.into_iter())
+                .format(format)
+                .highlight(highlights.clone())
+                .write(out)?;
+
+            Ok(out.flush()?)
+        };
+
+        return peek(iter, rows.max(1), write_page, more_rows);
+    }
+
+    if matches.is_present("summarize-columns") {
+        let values = args::value_format_value(matches);
+        let iter = summarize_columns(headers, iter, row_numbers, values);
+        let headers = vec![
+            String::from("ROW"),
+            String::from("COLUMN"),
+            String::from("VALUE"),
+        ];
+
+        return args::writer(matches, headers, iter)
+            .format(format)
+            .write(out);
+    }
+
+    let mut writer = args::writer(matches, headers, iter)
+        .format(format)
+        .highlight(highlights);
 
     writer.write(out)
 }
 
+/// Rows as `ROW`, `COLUMN`, `VALUE` triples of their non null and non default values,
+/// eg: to inspect sparse rows of wide tables.
+///
+/// Rows are numbered in the order they are read, or by their `#` field with `numbered`.
+fn summarize_columns<I>(
+    headers: Vec<String>,
+    iter: I,
+    numbered: bool,
+    format: ValueFormat,
+) -> impl Iterator<Item = Result<Vec<String>>>
+where
+    I: Iterator<Item = Result<Vec<Value>>>,
+{
+    iter.enumerate().flat_map(move |(i, row)| {
+        let mut row = match row {
+            Ok(row) => row,
+            Err(e) => return vec![Err(e)],
+        };
+        let number = if numbered && !row.is_empty() {
+            format.format(row.remove(0))
+        } else {
+            (i + 1).to_string()
+        };
+        let names = headers.iter().skip(numbered as usize);
+
+        names
+            .zip(row)
+            .filter(|(_, value)| !value.is_default())
+            .map(|(name, value)| {
+                Ok(vec![
+                    number.to_string(),
+                    name.to_string(),
+                    format.format(value),
+                ])
+            })
+            .collect()
+    })
+}
+
+/// Write the rows a page at a time, the next page is only read once `more` returns true.
+fn peek<I, P, M>(iter: I, rows: usize, mut write_page: P, mut more: M) -> Result<()>
+where
+    I: Iterator<Item = Result<Vec<Value>>>,
+    P: FnMut(Vec<Result<Vec<Value>>>) -> Result<()>,
+    M: FnMut() -> Result<bool>,
+{
+    let mut iter = iter.peekable();
+
+    while iter.peek().is_some() {
+        let page = iter.by_ref().take(rows).collect::<Vec<_>>();
+        let full = page.len() == rows;
+
+        write_page(page)?;
+
+        if !full || !more()? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the key pressed at the peek prompt quits, eg: `q`, `Esc` or `Ctrl-C`.
+fn is_quit(keys: &[u8]) -> bool {
+    matches!(keys.first(), None | Some(b'q' | b'Q' | 0x1b | 0x03 | 0x04))
+}
+
+/// Prompt on the terminal for more rows, waiting for a key press.
+#[cfg(unix)]
+fn more_rows() -> Result<bool> {
+    use std::io::Read;
+
+    let mut raw = tty::RawMode::open("peek")?;
+    let mut buffer = [0; 16];
+
+    raw.tty
+        .write_all(b"-- any key for more rows, q to quit --")?;
+    raw.tty.flush()?;
+
+    let read = raw.tty.read(&mut buffer)?;
+
+    raw.tty.write_all(b"\r\x1b[K")?;
+
+    Ok(!is_quit(&buffer[..read]))
+}
+
+#[cfg(not(unix))]
+fn more_rows() -> Result<bool> {
+    Err(Error::IO(String::from("peek needs a unix terminal")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,13 +507,38 @@ mod tests {
     use std::io::Cursor;
     use std::str;
 
+    #[test]
+    fn test_read_estimate_matches_small_output() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let msgs = api::tests::create_simple_messages(30);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let file = ParquetFile::from(parquet.path());
+        let headers = file.field_names().unwrap();
+
+        for format in [OutputFormat::Csv, OutputFormat::Vertical] {
+            let mut output = Cursor::new(Vec::new());
+            let estimate =
+                estimate(&file, headers.clone(), format, Layout::default(), 10).unwrap();
+            let mut writer =
+                OutputWriter::new(headers.clone(), file.values().take(10)).format(format);
+
+            writer.write(&mut output).unwrap();
+
+            assert_eq!(estimate.rows, 10);
+            assert_eq!(estimate.sample_rows, 10);
+            assert_eq!(estimate.total_bytes(), output.into_inner().len() as u64);
+        }
+    }
+
     #[test]
     fn test_read_simple_messages() {
         let mut output = Cursor::new(Vec::new());
         let parquet = api::tests::temp_file("msg", ".parquet");
         let path_str = parquet.path().to_str().unwrap();
         let path = parquet.path();
-        let expected = vec![
+        let expected = [
             "field_int32  field_int64",
             "1            11",
             "2            22",
@@ -103,13 +561,287 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_read_order_by() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(6);
+        let read = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec!["read", path_str, "-f=csv"];
+
+            arg_vec.extend_from_slice(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output)
+                .map(|_| String::from_utf8(output.into_inner()).unwrap())
+        };
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert_eq!(
+            read(&[
+                "-c=field_int32",
+                "--order-by=field_boolean:desc,field_int64:desc",
+                "-l=4"
+            ]),
+            Ok(String::from("field_int32\n6\n4\n2\n5\n"))
+        );
+        assert_eq!(
+            read(&["-c=field_int32", "--order-by=field_int32:desc", "-l=600000"]),
+            Ok(String::from("field_int32\n6\n5\n4\n3\n2\n1\n"))
+        );
+        assert_eq!(
+            read(&["-c=field_int32", "--order-by=unknown"]),
+            Err(Error::InvalidArgument(String::from(
+                "Unknown column 'unknown'"
+            )))
+        );
+        assert!(def()
+            .get_matches_from_safe(["read", path_str, "--order-by=:desc"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_row_numbers() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(4);
+        let read = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec!["read", path_str, "-c=field_int32", "--row-numbers"];
+
+            arg_vec.extend_from_slice(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output)
+                .map(|_| String::from_utf8(output.into_inner()).unwrap())
+        };
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert_eq!(
+            read(&["-f=csv", "-s=field_boolean=false"]),
+            Ok(String::from("#,field_int32\n1,1\n3,3\n"))
+        );
+        assert_eq!(
+            read(&["-f=csv", "--order-by=field_int32:desc", "-l=2"]),
+            Ok(String::from("#,field_int32\n4,4\n3,3\n"))
+        );
+        assert_eq!(
+            read(&["-f=arrow"]),
+            Err(Error::InvalidArgument(String::from("row-numbers")))
+        );
+    }
+
+    #[test]
+    fn test_read_summarize_columns() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(2);
+        let read = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec![
+                "read",
+                path_str,
+                "-c=field_int32,field_boolean",
+                "--summarize-columns",
+            ];
+
+            arg_vec.extend_from_slice(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output)
+                .map(|_| String::from_utf8(output.into_inner()).unwrap())
+        };
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert_eq!(
+            read(&["-f=csv"]),
+            Ok(String::from(
+                "ROW,COLUMN,VALUE\n1,field_int32,1\n2,field_int32,2\n2,field_boolean,true\n"
+            ))
+        );
+        assert_eq!(
+            read(&["-f=csv", "--row-numbers", "-s=field_boolean=true"]),
+            Ok(String::from(
+                "ROW,COLUMN,VALUE\n2,field_int32,2\n2,field_boolean,true\n"
+            ))
+        );
+        assert_eq!(
+            read(&["-f=arrow"]),
+            Err(Error::InvalidArgument(String::from("summarize-columns")))
+        );
+    }
+
+    #[test]
+    fn test_read_with_filename() {
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.parquet");
+        let path2 = dir.path().join("2.parquet");
+        let msgs = api::tests::create_simple_messages(3);
+        let mut output = Cursor::new(Vec::new());
+
+        api::tests::write_simple_messages_parquet(&path1, &msgs[..1]);
+        api::tests::write_simple_messages_parquet(&path2, &msgs[1..]);
+
+        let arg_vec = vec![
+            "read",
+            dir.path().to_str().unwrap(),
+            "-c=field_int32",
+            "--with-filename",
+            "--order-by=field_int32",
+            "-f=csv",
+        ];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        run(&args, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            format!(
+                "field_int32,#file\n1,\"{0}\"\n2,\"{1}\"\n3,\"{1}\"\n",
+                path1.display(),
+                path2.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_read_column_groups() {
+        let parquet = api::tests::temp_file("users", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[
+                r#"{"id": 1, "addr_city": "Lisbon", "addr_zip": "1000", "name": "Ana"}"#,
+                r#"{"id": 2, "addr_city": null, "addr_zip": "2000", "name": "Ben"}"#,
+            ],
+        );
+
+        let read = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec!["read", path_str, "-f=csv"];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output)
+                .map(|_| String::from_utf8(output.into_inner()).unwrap())
+        };
+
+        assert_eq!(
+            read(&["--group=address=addr_*"]),
+            Ok(String::from(
+                "id,address,name\n\
+                1,{\"city\":\"Lisbon\",\"zip\":\"1000\"},\"Ana\"\n\
+                2,{\"city\":null,\"zip\":\"2000\"},\"Ben\"\n"
+            ))
+        );
+        assert_eq!(
+            read(&["--group=address=addr_*", "-c=id,@address"]),
+            read(&["--group=address=addr_*", "--exclude=name"])
+        );
+        assert_eq!(
+            read(&["--group=address=addr_*", "--exclude=@address"]),
+            Ok(String::from("id,name\n1,\"Ana\"\n2,\"Ben\"\n"))
+        );
+        assert_eq!(
+            read(&["--group=address"]),
+            Err(Error::InvalidArgument(String::from(
+                "Unknown column group 'address'"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_read_simple_messages_with_exclude() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let path = parquet.path();
+        let expected = [
+            "field_int32  field_boolean",
+            "1            false",
+            "2            true",
+            "",
+        ]
+        .join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(2);
+        let args = subcomand
+            .get_matches_from_safe(vec![
+                "read",
+                path_str,
+                "--exclude=field_int64,field_float,field_double",
+                "--exclude=field_string,field_timestamp",
+            ])
+            .unwrap();
+
+        api::tests::write_simple_messages_parquet(path, &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_read_nested_json_flatten() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("nested", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "id,user.name,tags",
+            "1,\"foo\",[\"a\",\"b\"]",
+            "2,null,[]",
+            "",
+        ]
+        .join("\n");
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[
+                r#"{"id": 1, "user": {"name": "foo"}, "tags": ["a", "b"]}"#,
+                r#"{"id": 2, "tags": []}"#,
+            ],
+        );
+
+        let subcomand = def();
+        let args = subcomand
+            .get_matches_from_safe(vec![
+                "read",
+                path_str,
+                "--flatten",
+                "--nested=json",
+                "-f=csv",
+            ])
+            .unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_read_simple_messages_with_filters() {
         let mut output = Cursor::new(Vec::new());
         let parquet = api::tests::temp_file("msg", ".parquet");
         let path_str = parquet.path().to_str().unwrap();
         let path = parquet.path();
-        let expected = vec![
+        let expected = [
             "field_int32  field_string",
             "1            \"odd 11111\"",
             "3            \"odd 33333\"",
@@ -144,7 +876,7 @@ mod tests {
         let parquet = api::tests::temp_file("msg", ".parquet");
         let path_str = parquet.path().to_str().unwrap();
         let path = parquet.path();
-        let expected = vec![
+        let expected = [
             "",
             "field_int32:  1",
             "field_int64:  11",
@@ -170,6 +902,50 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_read_format_arrow() {
+        use arrow::datatypes::DataType;
+        use arrow::ipc::reader::StreamReader;
+
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("msg.parquet");
+        let output = dir.path().join("msg.arrows");
+        let path_str = parquet.to_str().unwrap();
+        let output_str = output.to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(3);
+
+        api::tests::write_simple_messages_parquet(&parquet, &msgs);
+
+        let read = |args: &[&str]| {
+            let mut arg_vec = vec!["read", path_str, "-f=arrow"];
+
+            arg_vec.extend_from_slice(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut Cursor::new(Vec::new()))
+        };
+
+        assert!(read(&["-c=field_int32,field_string", "-l=2", "-o", output_str]).is_ok());
+
+        let reader =
+            StreamReader::try_new(std::fs::File::open(&output).unwrap(), None).unwrap();
+        let schema = reader.schema();
+        let rows = reader.map(|b| b.unwrap().num_rows()).sum::<usize>();
+
+        assert_eq!(rows, 2);
+        assert_eq!(schema.fields().len(), 2);
+        assert_eq!(schema.field(0).data_type(), &DataType::Int32);
+        assert_eq!(
+            read(&["-s=field_int32=1"]),
+            Err(Error::InvalidArgument(String::from("search")))
+        );
+        assert_eq!(
+            read(&["--order-by=field_int32"]),
+            Err(Error::InvalidArgument(String::from("order-by")))
+        );
+    }
+
     #[test]
     fn test_read_simple_messages_with_format_csv() {
         let mut output = Cursor::new(Vec::new());
@@ -192,4 +968,34 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_read_peek_pages() {
+        let rows = (1..=5).map(|i| Ok(vec![Value::Int(i)]));
+        let peek_pages = |answers: &[bool]| {
+            let mut answers = answers.iter();
+            let mut pages = Vec::new();
+            let mut prompts = 0;
+            let write_page = |page: Vec<_>| {
+                pages.push(page.len());
+                Ok(())
+            };
+            let more = || {
+                prompts += 1;
+                Ok(*answers.next().unwrap())
+            };
+
+            peek(rows.clone(), 2, write_page, more).unwrap();
+
+            (pages, prompts)
+        };
+
+        assert_eq!(peek_pages(&[true, true]), (vec![2, 2, 1], 2));
+        assert_eq!(peek_pages(&[true, false]), (vec![2, 2], 2));
+        assert_eq!(peek_pages(&[false]), (vec![2], 1));
+        assert!(is_quit(b"q"));
+        assert!(is_quit(b"\x1b"));
+        assert!(is_quit(b""));
+        assert!(!is_quit(b" "));
+    }
 }
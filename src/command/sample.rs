@@ -1,11 +1,10 @@
 use crate::api::Result;
 use crate::command::args;
-use crate::output::{OutputFormat, OutputWriter};
+use crate::output::{NestedFormat, OutputFormat};
 use crate::reader::ParquetFile;
 use clap::{App, Arg, ArgMatches, SubCommand};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::io::Write;
 
 pub fn def() -> App<'static> {
@@ -19,6 +18,13 @@ pub fn def() -> App<'static> {
                 .multiple(true)
                 .short('c'),
         )
+        .arg(
+            Arg::with_name("exclude")
+                .help("Exclude columns from parquet")
+                .takes_value(true)
+                .long("exclude")
+                .multiple(true),
+        )
         .arg(
             Arg::with_name("sample")
                 .validator(args::validate_number)
@@ -27,6 +33,44 @@ pub fn def() -> App<'static> {
                 .long("sample")
                 .short('s'),
         )
+        .arg(
+            Arg::with_name("fraction")
+                .validator(args::validate_fraction)
+                .help("Sample a fraction of the rows instead of a fixed size, eg: 0.01")
+                .takes_value(true)
+                .long("fraction"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .validator(args::validate_number)
+                .help("Seed the random generator to get reproducible samples")
+                .takes_value(true)
+                .long("seed"),
+        )
+        .arg(
+            Arg::with_name("nested")
+                .help("Nested values format")
+                .possible_values(NestedFormat::values())
+                .default_value("default")
+                .long("nested"),
+        )
+        .arg(
+            Arg::with_name("row-numbers")
+                .help(
+                    "Prepend a # column with the number of each row in the files, from 1",
+                )
+                .long("row-numbers"),
+        )
+        .arg(
+            Arg::with_name("with-filename")
+                .help("Append a #file column with the path of the file of each row")
+                .long("with-filename"),
+        )
+        .arg(
+            Arg::with_name("flatten")
+                .help("Expand struct fields into parent.child columns")
+                .long("flatten"),
+        )
         .arg(
             Arg::with_name("format")
                 .help("Output format")
@@ -42,34 +86,83 @@ pub fn def() -> App<'static> {
                 .required(true)
                 .index(1),
         )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
 }
 
-fn sample_indexes(sample: usize, size: usize) -> HashSet<usize> {
-    let mut vec = (0..size).collect::<Vec<_>>();
-    let mut rng = thread_rng();
+/// Single pass reservoir sampling (algorithm R).
+///
+/// Keeps at most `sample` items in memory and returns them in their original order.
+pub(in crate::command) fn reservoir<T, I, R>(
+    iter: I,
+    sample: usize,
+    rng: &mut R,
+) -> Result<Vec<T>>
+where
+    I: Iterator<Item = Result<T>>,
+    R: Rng,
+{
+    let mut vec: Vec<(usize, T)> = Vec::with_capacity(sample);
+
+    for (i, item) in iter.enumerate() {
+        let item = item?;
+
+        if vec.len() < sample {
+            vec.push((i, item));
+            continue;
+        }
+
+        let j = rng.gen_range(0..=i);
+
+        if j < sample {
+            vec[j] = (i, item);
+        }
+    }
 
-    vec.shuffle(&mut rng);
+    vec.sort_by_key(|t| t.0);
 
-    vec.iter().take(sample).cloned().collect()
+    Ok(vec.into_iter().map(|t| t.1).collect())
 }
 
 pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
     let format = args::output_format_value(matches, "format")?;
+    let nested = args::nested_format_value(matches, "nested")?;
+    let flatten = matches.is_present("flatten");
     let columns = args::string_values(matches, "columns")?;
+    let exclude = args::string_values(matches, "exclude")?;
     let sample = args::usize_value(matches, "sample")?;
     let path = args::path_value(matches, "path")?;
-    let parquet = ParquetFile::from((path, columns));
+    let mut rng = match matches.value_of("seed") {
+        Some(_) => StdRng::seed_from_u64(args::usize_value(matches, "seed")? as u64),
+        None => StdRng::from_entropy(),
+    };
+    let parquet = ParquetFile::from((path, columns))
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_exclude(exclude)
+        .with_flatten(flatten)
+        .with_nested(nested)
+        .with_row_numbers(matches.is_present("row-numbers"))
+        .with_filename(matches.is_present("with-filename"));
     let headers = parquet.field_names()?;
-    let size = parquet.num_rows();
 
-    let rows = parquet.iter();
-    let indexes = sample_indexes(sample, size);
-    let iter = rows
-        .enumerate()
-        .filter(|t| indexes.contains(&t.0))
-        .map(|r| r.1);
+    // each row is kept with the given probability, so the sample is streamed
+    if matches.value_of("fraction").is_some() {
+        let fraction = args::f64_value(matches, "fraction")?;
+        let iter = parquet
+            .values()
+            .filter(|r| r.is_err() || rng.gen_bool(fraction));
+
+        let mut writer = args::writer(matches, headers, iter).format(format);
+
+        return writer.write(out);
+    }
+
+    let rows = reservoir(parquet.values(), sample, &mut rng)?;
+    let iter = rows.into_iter().map(Ok);
 
-    let mut writer = OutputWriter::new(headers, iter).format(format);
+    let mut writer = args::writer(matches, headers, iter).format(format);
 
     writer.write(out)
 }
@@ -78,14 +171,98 @@ pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
 mod tests {
     use super::*;
     use crate::api;
+    use crate::api::Error;
     use std::io::Cursor;
     use std::str;
 
+    #[test]
+    fn test_sample_reservoir() {
+        let mut rng = StdRng::from_entropy();
+        let items = || (0..100).map(Ok);
+
+        let sampled = reservoir(items(), 10, &mut rng).unwrap();
+        let mut sorted = sampled.clone();
+
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(sampled, sorted);
+        assert!(sampled.iter().all(|i| *i < 100));
+
+        assert_eq!(reservoir(items(), 200, &mut rng).unwrap().len(), 100);
+        assert!(reservoir(items(), 0, &mut rng).unwrap().is_empty());
+        assert!(reservoir(
+            vec![Ok(1), Err(Error::InvalidArgument(String::from("x")))].into_iter(),
+            1,
+            &mut rng
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sample_simple_messages_with_seed() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(50);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let sample = |seed: &str| {
+            let mut output = Cursor::new(Vec::new());
+            let arg_vec = vec!["sample", path_str, "-s=5", "-c=field_int32", seed];
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            assert!(run(&args, &mut output).is_ok());
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+
+        assert_eq!(6, sample("--seed=42").lines().count());
+        assert_eq!(sample("--seed=42"), sample("--seed=42"));
+        assert_ne!(sample("--seed=42"), sample("--seed=7"));
+    }
+
+    #[test]
+    fn test_sample_simple_messages_with_fraction() {
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(200);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let sample = |fraction: &str| {
+            let mut output = Cursor::new(Vec::new());
+            let arg_vec = vec![
+                "sample",
+                path_str,
+                "-c=field_int32",
+                "-f=csv",
+                "--seed=42",
+                fraction,
+            ];
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            assert!(run(&args, &mut output).is_ok());
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+
+        let lines = sample("--fraction=0.5").lines().count() - 1;
+
+        assert!(lines > 50 && lines < 150);
+        assert_eq!(sample("--fraction=0.5"), sample("--fraction=0.5"));
+        assert_eq!(201, sample("--fraction=1").lines().count());
+        assert!(def()
+            .get_matches_from_safe(vec!["sample", path_str, "--fraction=2"])
+            .is_err());
+    }
+
     #[test]
     fn test_sample_simple_messages() {
         let mut output = Cursor::new(Vec::new());
         let parquet = api::tests::temp_file("msg", ".parquet");
-        let expected = vec![
+        let expected = [
             "field_int32  field_int64  field_float  field_double  field_string  field_boolean  field_timestamp",
             "1            11           111.3        1111.4        \"odd 11111\"   false          2011-01-01 00:00:00 +00:00",
             "2            22           222.3        2222.4        \"even 22222\"  true           2012-01-01 00:00:00 +00:00",
@@ -114,7 +291,7 @@ mod tests {
         let parquet = api::tests::temp_file("msg", ".parquet");
         let path_str = parquet.path().to_str().unwrap();
         let path = parquet.path();
-        let expected = vec![
+        let expected = [
             "field_boolean  field_int32",
             "false          1",
             "true           2",
@@ -142,7 +319,7 @@ mod tests {
         let mut output = Cursor::new(Vec::new());
         let parquet = api::tests::temp_file("msg", ".parquet");
         let path_str = parquet.path().to_str().unwrap();
-        let expected = vec![
+        let expected = [
             "",
             "field_boolean:  false",
             "field_int32:    1",
@@ -180,7 +357,7 @@ mod tests {
         let mut output = Cursor::new(Vec::new());
         let parquet = api::tests::temp_file("msg", ".parquet");
         let path_str = parquet.path().to_str().unwrap();
-        let expected = vec![
+        let expected = [
             "field_int32,field_timestamp",
             "1,2011-01-01 00:00:00 +00:00",
             "2,2012-01-01 00:00:00 +00:00",
@@ -0,0 +1,359 @@
+use crate::api::{Error, Result};
+use crate::command::{args, sample};
+use crate::hash;
+use crate::output::{NestedFormat, OutputFormat};
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Rows of the old version by the digest of their key,
+/// with the digest of the first row and the number of rows holding the key.
+type Digests = HashMap<u128, (u128, usize)>;
+
+/// Digest of the key columns and digest of the whole row,
+/// rows are their own key when no key columns are given.
+#[inline]
+fn row_digests(row: &[String], keys: &[usize]) -> Result<(u128, u128)> {
+    let digest = hash::digest(row)?;
+
+    if keys.is_empty() {
+        return Ok((digest, digest));
+    }
+
+    let key = keys.iter().map(|i| row[*i].as_str()).collect::<Vec<_>>();
+
+    Ok((hash::digest(&key)?, digest))
+}
+
+/// Take a row of the key out of the digests, returning the digest of the old row.
+#[inline]
+fn take(digests: &mut Digests, key: u128) -> Option<u128> {
+    let (digest, count) = digests.get_mut(&key)?;
+    let digest = *digest;
+
+    *count -= 1;
+
+    if *count == 0 {
+        digests.remove(&key);
+    }
+
+    Some(digest)
+}
+
+/// Sample rows of the new version missing from the old one, `changed` rows share a key
+/// with an old row but hold other values, then sample the old rows left.
+///
+/// Each version is sampled separately, at most `sample` rows of each.
+fn sample_diff<I, R>(
+    old: &ParquetFile,
+    new: I,
+    keys: &[usize],
+    sample: usize,
+    rng: &mut R,
+) -> Result<Vec<Vec<String>>>
+where
+    I: Iterator<Item = Result<Vec<String>>>,
+    R: Rng,
+{
+    let mut digests = Digests::new();
+
+    for row in old.iter() {
+        let (key, digest) = row_digests(&row?, keys)?;
+
+        digests.entry(key).or_insert((digest, 0)).1 += 1;
+    }
+
+    let added = new.filter_map(|row| {
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => return Some(Err(e)),
+        };
+        let (key, digest) = match row_digests(&row, keys) {
+            Ok(digests) => digests,
+            Err(e) => return Some(Err(e)),
+        };
+
+        match take(&mut digests, key) {
+            None => Some(Ok(("added", row))),
+            Some(old) if old != digest => Some(Ok(("changed", row))),
+            Some(_) => None,
+        }
+    });
+    let added = sample::reservoir(added, sample, rng)?;
+
+    let removed = old.iter().filter_map(|row| {
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => return Some(Err(e)),
+        };
+
+        match row_digests(&row, keys).map(|(key, _)| take(&mut digests, key)) {
+            Ok(Some(_)) => Some(Ok(("removed", row))),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    });
+    let removed = sample::reservoir(removed, sample, rng)?;
+
+    let rows = removed
+        .into_iter()
+        .chain(added)
+        .map(|(change, row)| {
+            let mut vec = Vec::with_capacity(row.len() + 1);
+
+            vec.push(change.to_string());
+            vec.extend(row);
+            vec
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("sample-diff")
+        .about("Sample rows present in one version of a dataset but not the other")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("key")
+                .help("Columns identifying a row, rows with the same key and other values are changed")
+                .takes_value(true)
+                .long("key")
+                .multiple(true)
+                .short('k'),
+        )
+        .arg(
+            Arg::with_name("sample")
+                .validator(args::validate_number)
+                .help("Sample size limit of each version")
+                .default_value("100")
+                .long("sample")
+                .short('s'),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .validator(args::validate_number)
+                .help("Seed the random generator to get reproducible samples")
+                .takes_value(true)
+                .long("seed"),
+        )
+        .arg(
+            Arg::with_name("nested")
+                .help("Nested values format")
+                .possible_values(NestedFormat::values())
+                .default_value("default")
+                .long("nested"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("old")
+                .validator(args::validate_path)
+                .help("Path to the old version")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("new")
+                .validator(args::validate_path)
+                .help("Path to the new version")
+                .required(true)
+                .index(2),
+        )
+        .args(args::table_args())
+        .args(args::value_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let nested = args::nested_format_value(matches, "nested")?;
+    let columns = args::string_values(matches, "columns")?;
+    let key = args::string_values(matches, "key")?.unwrap_or_default();
+    let sample = args::usize_value(matches, "sample")?;
+    let old = args::path_value(matches, "old")?;
+    let new = args::path_value(matches, "new")?;
+    let mut rng = match matches.value_of("seed") {
+        Some(_) => StdRng::seed_from_u64(args::usize_value(matches, "seed")? as u64),
+        None => StdRng::from_entropy(),
+    };
+    let values = args::value_format_value(matches);
+    let old = ParquetFile::from((old, columns))
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_value_format(values.clone())
+        .with_nested(nested);
+    let headers = old.field_names()?;
+
+    // both versions are read with the columns of the old one
+    let new = ParquetFile::from(new)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(Some(headers.clone()))
+        .with_value_format(values.clone())
+        .with_nested(nested);
+    let names = new.field_names()?;
+    let positions = headers
+        .iter()
+        .map(|h| names.iter().position(|n| n.eq_ignore_ascii_case(h)))
+        .collect::<Vec<_>>();
+    let keys = key
+        .iter()
+        .map(|k| {
+            headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(k))
+                .ok_or_else(|| Error::InvalidArgument(format!("Unknown column '{}'", k)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // columns missing from the new version are null
+    let rows = new.iter().map(|row| {
+        row.map(|row| {
+            positions
+                .iter()
+                .map(|p| match p {
+                    Some(i) => row[*i].clone(),
+                    None => values.null_display().to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let rows = sample_diff(&old, rows, &keys, sample, &mut rng)?;
+    let headers = std::iter::once(String::from("change"))
+        .chain(headers)
+        .collect();
+    let mut writer =
+        args::writer(matches, headers, rows.into_iter().map(Ok)).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_sample_diff_by_row() {
+        let dir = api::tests::temp_dir();
+        let old = dir.path().join("old.parquet");
+        let new = dir.path().join("new.parquet");
+        let mut output = Cursor::new(Vec::new());
+
+        api::tests::write_json_parquet(
+            &old,
+            &[
+                r#"{"id": 1, "name": "a"}"#,
+                r#"{"id": 2, "name": "b"}"#,
+                r#"{"id": 2, "name": "b"}"#,
+            ],
+        );
+        api::tests::write_json_parquet(
+            &new,
+            &[
+                r#"{"id": 2, "name": "b"}"#,
+                r#"{"id": 3, "name": "c"}"#,
+                r#"{"id": 1, "name": "x"}"#,
+            ],
+        );
+
+        let arg_vec = vec![
+            "sample-diff",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "-f=csv",
+            "--seed=1",
+        ];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+        let expected = [
+            "change,id,name",
+            "removed,1,\"a\"",
+            "removed,2,\"b\"",
+            "added,3,\"c\"",
+            "added,1,\"x\"",
+            "",
+        ]
+        .join("\n");
+
+        run(&args, &mut output).unwrap();
+
+        assert_eq!(str::from_utf8(&output.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_sample_diff_by_key() {
+        let dir = api::tests::temp_dir();
+        let old = dir.path().join("old.parquet");
+        let new = dir.path().join("new.parquet");
+
+        api::tests::write_json_parquet(
+            &old,
+            &[r#"{"id": 1, "name": "a"}"#, r#"{"id": 2, "name": "b"}"#],
+        );
+        api::tests::write_json_parquet(
+            &new,
+            &[
+                r#"{"id": 2, "name": "b"}"#,
+                r#"{"id": 3, "name": "c"}"#,
+                r#"{"id": 1, "name": "x"}"#,
+            ],
+        );
+
+        let diff = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec![
+                "sample-diff",
+                old.to_str().unwrap(),
+                new.to_str().unwrap(),
+                "-f=csv",
+                "--seed=1",
+            ];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output).map(|_| output.into_inner())
+        };
+        let expected =
+            ["change,id,name", "added,3,\"c\"", "changed,1,\"x\"", ""].join("\n");
+
+        assert_eq!(
+            str::from_utf8(&diff(&["-k=ID"]).unwrap()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            diff(&["-k=id", "-s=1"])
+                .unwrap()
+                .split(|b| *b == b'\n')
+                .count(),
+            3
+        );
+        assert_eq!(
+            diff(&["-k=missing"]),
+            Err(Error::InvalidArgument(String::from(
+                "Unknown column 'missing'"
+            )))
+        );
+    }
+}
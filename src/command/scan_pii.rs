@@ -0,0 +1,342 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use regex::Regex;
+use std::convert::TryFrom;
+use std::io::Write;
+
+/// Sensitive data detector.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Detector {
+    // Email addresses
+    Email,
+
+    // Phone numbers
+    Phone,
+
+    // Credit card numbers, validated using the Luhn checksum
+    CreditCard,
+
+    // IPv4 addresses
+    Ip,
+
+    // US social security numbers
+    Ssn,
+}
+
+const DETECTOR_VALUES: &[&str] = &["email", "phone", "credit-card", "ip", "ssn"];
+
+impl Detector {
+    pub fn values() -> &'static [&'static str] {
+        DETECTOR_VALUES
+    }
+
+    pub fn all() -> Vec<Detector> {
+        vec![
+            Detector::Email,
+            Detector::Phone,
+            Detector::CreditCard,
+            Detector::Ip,
+            Detector::Ssn,
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Detector::Email => "email",
+            Detector::Phone => "phone",
+            Detector::CreditCard => "credit-card",
+            Detector::Ip => "ip",
+            Detector::Ssn => "ssn",
+        }
+    }
+
+    fn pattern(self) -> &'static str {
+        match self {
+            Detector::Email => r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b",
+            Detector::Phone => {
+                r"(?:\+\d{1,3}[\s.-]?)?\(?\b\d{3}\)?[\s.-]?\d{3}[\s.-]\d{4}\b"
+            }
+            Detector::CreditCard => r"\b(?:\d[ -]?){12,18}\d\b",
+            Detector::Ip => r"\b(?:\d{1,3}\.){3}\d{1,3}\b",
+            Detector::Ssn => r"\b\d{3}-\d{2}-\d{4}\b",
+        }
+    }
+
+    /// Extra validation of a regex match.
+    fn is_valid(self, value: &str) -> bool {
+        match self {
+            Detector::CreditCard => luhn(value),
+            Detector::Ip => value.split('.').all(|o| o.parse::<u8>().is_ok()),
+            _ => true,
+        }
+    }
+}
+
+impl TryFrom<&str> for Detector {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "email" => Ok(Detector::Email),
+            "phone" => Ok(Detector::Phone),
+            "credit-card" => Ok(Detector::CreditCard),
+            "ip" => Ok(Detector::Ip),
+            "ssn" => Ok(Detector::Ssn),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Luhn checksum of the digits in a value.
+#[inline]
+fn luhn(value: &str) -> bool {
+    let digits = value
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect::<Vec<_>>();
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| match (i % 2, d * 2) {
+            (1, d) if d > 9 => d - 9,
+            (1, d) => d,
+            _ => *d,
+        })
+        .sum();
+
+    !digits.is_empty() && sum.is_multiple_of(10)
+}
+
+struct Matcher {
+    detector: Detector,
+    regex: Regex,
+}
+
+impl Matcher {
+    fn new(detector: Detector) -> Result<Self> {
+        let regex = Regex::new(detector.pattern())?;
+
+        Ok(Self { detector, regex })
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .find_iter(value)
+            .any(|m| self.detector.is_valid(m.as_str()))
+    }
+}
+
+/// Count the sampled string values and detector hits of each column.
+fn scan<I>(
+    num_fields: usize,
+    iter: I,
+    matchers: &[Matcher],
+) -> Result<(Vec<u64>, Vec<Vec<u64>>)>
+where
+    I: Iterator<Item = Result<Vec<Field>>>,
+{
+    let mut sampled = vec![0; num_fields];
+    let mut hits = vec![vec![0; matchers.len()]; num_fields];
+
+    for row in iter {
+        for (i, field) in row?.iter().enumerate() {
+            if let Field::Str(value) = field {
+                sampled[i] += 1;
+
+                for (m, matcher) in matchers.iter().enumerate() {
+                    if matcher.is_match(value) {
+                        hits[i][m] += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((sampled, hits))
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("scan-pii")
+        .about("Report columns likely to contain sensitive data")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("exclude")
+                .help("Exclude columns from parquet")
+                .takes_value(true)
+                .long("exclude")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("detectors")
+                .help("Detectors to run, defaults to all")
+                .possible_values(Detector::values())
+                .takes_value(true)
+                .use_value_delimiter(true)
+                .long("detectors")
+                .multiple(true)
+                .short('d'),
+        )
+        .arg(
+            Arg::with_name("sample")
+                .validator(args::validate_number)
+                .help("Number of rows to scan")
+                .default_value("1000")
+                .long("sample")
+                .short('n'),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let exclude = args::string_values(matches, "exclude")?;
+    let sample = args::usize_value(matches, "sample")?;
+    let path = args::path_value(matches, "path")?;
+    let detectors = match matches.values_of("detectors") {
+        Some(values) => values.map(Detector::try_from).collect::<Result<Vec<_>>>()?,
+        None => Detector::all(),
+    };
+
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(columns)
+        .with_exclude(exclude);
+
+    let matchers = detectors
+        .into_iter()
+        .map(Matcher::new)
+        .collect::<Result<Vec<_>>>()?;
+    let fields = parquet.field_names()?;
+    let rows = parquet.records().take(sample);
+    let (sampled, hits) = scan(fields.len(), rows, &matchers)?;
+    let headers = vec![
+        String::from("FIELD"),
+        String::from("DETECTOR"),
+        String::from("MATCHES"),
+        String::from("SAMPLED"),
+        String::from("RATIO"),
+    ];
+
+    let mut result = Vec::new();
+
+    for (i, field) in fields.iter().enumerate() {
+        for (m, matcher) in matchers.iter().enumerate() {
+            let count = hits[i][m];
+
+            if count == 0 {
+                continue;
+            }
+
+            result.push(Ok(vec![
+                field.to_string(),
+                matcher.detector.name().to_string(),
+                count.to_string(),
+                sampled[i].to_string(),
+                format!("{:.4}", count as f64 / sampled[i] as f64),
+            ]));
+        }
+    }
+
+    let mut writer = args::writer(matches, headers, result.into_iter()).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_scan_pii_luhn() {
+        assert!(luhn("4111 1111 1111 1111"));
+        assert!(luhn("5500-0000-0000-0004"));
+        assert!(!luhn("4111 1111 1111 1112"));
+        assert!(!luhn(""));
+    }
+
+    #[test]
+    fn test_scan_pii_matchers() {
+        let matcher = |d| Matcher::new(d).unwrap();
+
+        assert!(matcher(Detector::Email).is_match("contact: foo.bar@example.com"));
+        assert!(!matcher(Detector::Email).is_match("foo at example.com"));
+        assert!(matcher(Detector::Phone).is_match("call +1 555-123-4567"));
+        assert!(matcher(Detector::Phone).is_match("(555) 123-4567"));
+        assert!(!matcher(Detector::Phone).is_match("5551234567890"));
+        assert!(matcher(Detector::CreditCard).is_match("4111-1111-1111-1111"));
+        assert!(!matcher(Detector::CreditCard).is_match("4111-1111-1111-1112"));
+        assert!(matcher(Detector::Ip).is_match("from 192.168.0.1"));
+        assert!(!matcher(Detector::Ip).is_match("version 1.300.0.1"));
+        assert!(matcher(Detector::Ssn).is_match("ssn 078-05-1120"));
+    }
+
+    #[test]
+    fn test_scan_pii_json_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("pii", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "FIELD,DETECTOR,MATCHES,SAMPLED,RATIO",
+            "contact,email,2,3,0.6667",
+            "note,credit-card,1,2,0.5000",
+            "note,ip,1,2,0.5000",
+            "",
+        ]
+        .join("\n");
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[
+                r#"{"id": 1, "contact": "foo@example.com", "note": "paid 4111 1111 1111 1111"}"#,
+                r#"{"id": 2, "contact": "n/a", "note": "login from 10.0.0.1"}"#,
+                r#"{"id": 3, "contact": "bar@example.org"}"#,
+                r#"{"id": 4, "contact": "baz@example.org", "note": "late"}"#,
+            ],
+        );
+
+        let subcomand = def();
+        let arg_vec = vec!["scan-pii", path_str, "-n=3", "-f=csv"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,9 +1,576 @@
-use crate::api::Result;
+use crate::api::{Error, Result};
+use crate::catalog::{Catalog, SPARK_SCHEMA_KEY};
 use crate::command::args;
+use crate::output::OutputFormat;
 use crate::reader::ParquetFile;
 use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
 use parquet::schema::printer::print_schema;
+use parquet::schema::types::Type;
+use serde_json::{json, Map, Value};
+use std::convert::TryFrom;
+use std::fs;
 use std::io::Write;
+use std::path::Path;
+
+/// Schema format.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SchemaFormat {
+    // Hive style message type
+    Hive,
+
+    // Structured JSON fields
+    Json,
+
+    // Avro record schema
+    Avro,
+
+    // Protobuf (proto3) message definitions
+    Proto,
+
+    // Thrift struct definitions
+    Thrift,
+}
+
+const SCHEMA_FORMAT_VALUES: &[&str] = &["hive", "json", "avro", "proto", "thrift"];
+
+impl SchemaFormat {
+    pub fn values() -> &'static [&'static str] {
+        SCHEMA_FORMAT_VALUES
+    }
+}
+
+impl TryFrom<&str> for SchemaFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "hive" => Ok(SchemaFormat::Hive),
+            "json" => Ok(SchemaFormat::Json),
+            "avro" => Ok(SchemaFormat::Avro),
+            "proto" => Ok(SchemaFormat::Proto),
+            "thrift" => Ok(SchemaFormat::Thrift),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+#[inline]
+fn converted_type(field: &Type) -> Option<String> {
+    match field.get_basic_info().converted_type() {
+        ConvertedType::NONE => None,
+        converted => Some(converted.to_string()),
+    }
+}
+
+/// Logical type of a field, its physical type or `GROUP` when it has none.
+pub fn field_kind(field: &Type) -> String {
+    match field {
+        Type::PrimitiveType { physical_type, .. } => {
+            converted_type(field).unwrap_or_else(|| physical_type.to_string())
+        }
+        Type::GroupType { .. } => {
+            converted_type(field).unwrap_or_else(|| String::from("GROUP"))
+        }
+    }
+}
+
+/// Name, type, repetition and logical type of a field and its children.
+pub fn json_field(field: &Type) -> Value {
+    let info = field.get_basic_info();
+    let mut map = Map::new();
+
+    map.insert(String::from("name"), json!(field.name()));
+
+    if info.has_repetition() {
+        map.insert(
+            String::from("repetition"),
+            json!(info.repetition().to_string()),
+        );
+    }
+
+    map.insert(String::from("logical_type"), json!(converted_type(field)));
+
+    match field {
+        Type::PrimitiveType {
+            physical_type,
+            type_length,
+            scale,
+            precision,
+            ..
+        } => {
+            map.insert(String::from("type"), json!(physical_type.to_string()));
+
+            if *physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+                map.insert(String::from("length"), json!(type_length));
+            }
+
+            if info.converted_type() == ConvertedType::DECIMAL {
+                map.insert(String::from("precision"), json!(precision));
+                map.insert(String::from("scale"), json!(scale));
+            }
+        }
+        Type::GroupType { fields, .. } => {
+            let children = fields.iter().map(|f| json_field(f)).collect::<Vec<_>>();
+
+            map.insert(String::from("type"), json!("GROUP"));
+            map.insert(String::from("fields"), Value::Array(children));
+        }
+    }
+
+    Value::Object(map)
+}
+
+/// Avro type of a primitive field.
+fn avro_primitive(field: &Type) -> Value {
+    let converted = field.get_basic_info().converted_type();
+
+    if converted == ConvertedType::DECIMAL {
+        return json!({
+            "type": "bytes",
+            "logicalType": "decimal",
+            "precision": field.get_precision(),
+            "scale": field.get_scale(),
+        });
+    }
+
+    match (field.get_physical_type(), converted) {
+        (PhysicalType::BOOLEAN, _) => json!("boolean"),
+        (PhysicalType::INT32, ConvertedType::DATE) => {
+            json!({"type": "int", "logicalType": "date"})
+        }
+        (PhysicalType::INT32, ConvertedType::UINT_32) => json!("long"),
+        (PhysicalType::INT32, _) => json!("int"),
+        (PhysicalType::INT64, ConvertedType::TIMESTAMP_MILLIS) => {
+            json!({"type": "long", "logicalType": "timestamp-millis"})
+        }
+        (PhysicalType::INT64, ConvertedType::TIMESTAMP_MICROS) => {
+            json!({"type": "long", "logicalType": "timestamp-micros"})
+        }
+        (PhysicalType::INT64, _) => json!("long"),
+        (PhysicalType::FLOAT, _) => json!("float"),
+        (PhysicalType::DOUBLE, _) => json!("double"),
+        (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8)
+        | (PhysicalType::BYTE_ARRAY, ConvertedType::ENUM)
+        | (PhysicalType::BYTE_ARRAY, ConvertedType::JSON) => json!("string"),
+        (PhysicalType::FIXED_LEN_BYTE_ARRAY, _) => match field {
+            Type::PrimitiveType { type_length, .. } => {
+                json!({"type": "fixed", "name": field.name(), "size": type_length})
+            }
+            _ => json!("bytes"),
+        },
+        _ => json!("bytes"),
+    }
+}
+
+/// Element of a LIST or value of a MAP group, skipping the repeated wrapper.
+///
+/// Repeated groups not shaped as a list element or a key/value pair
+/// are themselves the element, eg: legacy two level lists.
+#[inline]
+fn avro_repeated_child(field: &Type, index: usize) -> Value {
+    match field.get_fields().first().map(|f| f.as_ref()) {
+        Some(Type::GroupType { fields, .. }) if fields.len() == index + 1 => {
+            avro_type(&fields[index])
+        }
+        Some(repeated) => avro_base_type(repeated),
+        None => json!("null"),
+    }
+}
+
+/// Avro type of a field without its repetition.
+fn avro_base_type(field: &Type) -> Value {
+    if field.is_primitive() {
+        return avro_primitive(field);
+    }
+
+    match field.get_basic_info().converted_type() {
+        ConvertedType::LIST => json!({
+            "type": "array",
+            "items": avro_repeated_child(field, 0),
+        }),
+        ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE => json!({
+            "type": "map",
+            "values": avro_repeated_child(field, 1),
+        }),
+        _ => avro_record(field),
+    }
+}
+
+/// Avro type of a field, optional fields are unions with null.
+fn avro_type(field: &Type) -> Value {
+    let info = field.get_basic_info();
+    let base = avro_base_type(field);
+
+    if !info.has_repetition() {
+        return base;
+    }
+
+    match info.repetition() {
+        Repetition::REQUIRED => base,
+        Repetition::OPTIONAL => json!(["null", base]),
+        Repetition::REPEATED => json!({"type": "array", "items": base}),
+    }
+}
+
+fn avro_record(field: &Type) -> Value {
+    let fields = field
+        .get_fields()
+        .iter()
+        .map(|f| {
+            let mut map = Map::new();
+
+            map.insert(String::from("name"), json!(f.name()));
+            map.insert(String::from("type"), avro_type(f));
+
+            if f.get_basic_info().repetition() == Repetition::OPTIONAL {
+                map.insert(String::from("default"), Value::Null);
+            }
+
+            Value::Object(map)
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "type": "record",
+        "name": field.name(),
+        "fields": fields,
+    })
+}
+
+/// Scalar types shared by the protobuf and thrift definitions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Scalar {
+    Bool,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float,
+    Double,
+    String,
+    Bytes,
+}
+
+/// Field type of an interface definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum IdlType {
+    // Scalar value
+    Scalar(Scalar),
+
+    // List of values
+    List(Box<IdlType>),
+
+    // Map of keys to values
+    Map(Box<IdlType>, Box<IdlType>),
+
+    // Nested message/struct by name
+    Message(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IdlField {
+    name: String,
+    repetition: Repetition,
+    kind: IdlType,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IdlMessage {
+    name: String,
+    fields: Vec<IdlField>,
+}
+
+/// Replace characters not valid in an identifier.
+#[inline]
+fn idl_identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Message name of a group field, eg: `home_address` becomes `HomeAddress`.
+#[inline]
+fn idl_message_name(name: &str, messages: &[IdlMessage]) -> String {
+    let camel = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|p| !p.is_empty())
+        .map(|p| p[..1].to_uppercase() + &p[1..])
+        .collect::<String>();
+
+    let exists = |n: &str| messages.iter().any(|m| m.name == n);
+    let base = if camel.is_empty() {
+        String::from("Message")
+    } else {
+        camel
+    };
+
+    (1..)
+        .map(|i| match i {
+            1 => base.to_string(),
+            _ => format!("{}{}", base, i),
+        })
+        .find(|n| !exists(n))
+        .unwrap_or(base)
+}
+
+#[inline]
+fn idl_scalar(field: &Type) -> Scalar {
+    match (
+        field.get_physical_type(),
+        field.get_basic_info().converted_type(),
+    ) {
+        (PhysicalType::BOOLEAN, _) => Scalar::Bool,
+        (PhysicalType::INT32, ConvertedType::UINT_8)
+        | (PhysicalType::INT32, ConvertedType::UINT_16)
+        | (PhysicalType::INT32, ConvertedType::UINT_32) => Scalar::UInt32,
+        (PhysicalType::INT32, _) => Scalar::Int32,
+        (PhysicalType::INT64, ConvertedType::UINT_64) => Scalar::UInt64,
+        (PhysicalType::INT64, _) => Scalar::Int64,
+        (PhysicalType::FLOAT, _) => Scalar::Float,
+        (PhysicalType::DOUBLE, _) => Scalar::Double,
+        (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8)
+        | (PhysicalType::BYTE_ARRAY, ConvertedType::ENUM)
+        | (PhysicalType::BYTE_ARRAY, ConvertedType::JSON) => Scalar::String,
+        _ => Scalar::Bytes,
+    }
+}
+
+/// Children of a LIST or MAP group, skipping the repeated wrapper.
+///
+/// Repeated fields not shaped as a list element or a key/value pair
+/// are themselves the element, eg: legacy two level lists.
+#[inline]
+fn idl_repeated_children(field: &Type, num: usize) -> Vec<&Type> {
+    match field.get_fields().first().map(|f| f.as_ref()) {
+        Some(Type::GroupType { fields, .. }) if fields.len() == num => {
+            fields.iter().map(|f| f.as_ref()).collect()
+        }
+        Some(repeated) => vec![repeated],
+        None => vec![],
+    }
+}
+
+/// Type of a field without its repetition, nested groups are added to `messages`.
+fn idl_type(field: &Type, messages: &mut Vec<IdlMessage>) -> IdlType {
+    if field.is_primitive() {
+        return IdlType::Scalar(idl_scalar(field));
+    }
+
+    let converted = field.get_basic_info().converted_type();
+    let children = match converted {
+        ConvertedType::LIST => idl_repeated_children(field, 1),
+        ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE => {
+            idl_repeated_children(field, 2)
+        }
+        _ => vec![],
+    };
+
+    match (converted, children.as_slice()) {
+        (ConvertedType::LIST, [element]) => {
+            IdlType::List(Box::new(idl_type(element, messages)))
+        }
+        (ConvertedType::MAP, [key, value])
+        | (ConvertedType::MAP_KEY_VALUE, [key, value]) => IdlType::Map(
+            Box::new(idl_type(key, messages)),
+            Box::new(idl_type(value, messages)),
+        ),
+        _ => IdlType::Message(idl_message(field, messages)),
+    }
+}
+
+/// Add the message of a group and its nested groups, returning its name.
+fn idl_message(field: &Type, messages: &mut Vec<IdlMessage>) -> String {
+    let fields = field
+        .get_fields()
+        .iter()
+        .map(|f| IdlField {
+            name: idl_identifier(f.name()),
+            repetition: f.get_basic_info().repetition(),
+            kind: idl_type(f, messages),
+        })
+        .collect();
+
+    let name = idl_message_name(field.name(), messages);
+
+    messages.push(IdlMessage {
+        name: name.to_string(),
+        fields,
+    });
+
+    name
+}
+
+/// Messages of a schema, nested messages come before the ones using them.
+fn idl_messages(schema: &Type) -> Vec<IdlMessage> {
+    let mut messages = Vec::new();
+    let fields = schema
+        .get_fields()
+        .iter()
+        .map(|f| IdlField {
+            name: idl_identifier(f.name()),
+            repetition: f.get_basic_info().repetition(),
+            kind: idl_type(f, &mut messages),
+        })
+        .collect();
+
+    messages.push(IdlMessage {
+        name: idl_identifier(schema.name()),
+        fields,
+    });
+
+    messages
+}
+
+fn proto_type(kind: &IdlType) -> String {
+    match kind {
+        IdlType::Scalar(scalar) => String::from(match scalar {
+            Scalar::Bool => "bool",
+            Scalar::Int32 => "int32",
+            Scalar::UInt32 => "uint32",
+            Scalar::Int64 => "int64",
+            Scalar::UInt64 => "uint64",
+            Scalar::Float => "float",
+            Scalar::Double => "double",
+            Scalar::String => "string",
+            Scalar::Bytes => "bytes",
+        }),
+        IdlType::Message(name) => name.to_string(),
+        // proto does not support nested collections
+        IdlType::List(_) | IdlType::Map(_, _) => String::from("bytes"),
+    }
+}
+
+fn proto_field(field: &IdlField, number: usize) -> String {
+    let (label, kind) = match (&field.kind, field.repetition) {
+        (IdlType::List(element), _) => ("repeated ", proto_type(element)),
+        (IdlType::Map(key, value), _) => (
+            "",
+            format!("map<{}, {}>", proto_type(key), proto_type(value)),
+        ),
+        (kind, Repetition::REPEATED) => ("repeated ", proto_type(kind)),
+        (kind, Repetition::OPTIONAL) => ("optional ", proto_type(kind)),
+        (kind, Repetition::REQUIRED) => ("", proto_type(kind)),
+    };
+
+    format!("  {}{} {} = {};", label, kind, field.name, number)
+}
+
+/// Proto3 message definitions of a schema.
+fn proto_schema(schema: &Type) -> String {
+    let messages = idl_messages(schema)
+        .iter()
+        .map(|m| {
+            let fields = m
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| proto_field(f, i + 1) + "\n")
+                .collect::<String>();
+
+            format!("message {} {{\n{}}}\n", m.name, fields)
+        })
+        .collect::<Vec<_>>();
+
+    format!("syntax = \"proto3\";\n\n{}", messages.join("\n"))
+}
+
+fn thrift_type(kind: &IdlType) -> String {
+    match kind {
+        IdlType::Scalar(scalar) => String::from(match scalar {
+            Scalar::Bool => "bool",
+            Scalar::Int32 => "i32",
+            Scalar::UInt32 | Scalar::Int64 | Scalar::UInt64 => "i64",
+            Scalar::Float | Scalar::Double => "double",
+            Scalar::String => "string",
+            Scalar::Bytes => "binary",
+        }),
+        IdlType::List(element) => format!("list<{}>", thrift_type(element)),
+        IdlType::Map(key, value) => {
+            format!("map<{}, {}>", thrift_type(key), thrift_type(value))
+        }
+        IdlType::Message(name) => name.to_string(),
+    }
+}
+
+fn thrift_field(field: &IdlField, number: usize) -> String {
+    let kind = thrift_type(&field.kind);
+    let (requiredness, kind) = match field.repetition {
+        Repetition::REQUIRED => ("required", kind),
+        Repetition::OPTIONAL => ("optional", kind),
+        Repetition::REPEATED => ("optional", format!("list<{}>", kind)),
+    };
+
+    format!("  {}: {} {} {};", number, requiredness, kind, field.name)
+}
+
+/// Thrift struct definitions of a schema.
+fn thrift_schema(schema: &Type) -> String {
+    idl_messages(schema)
+        .iter()
+        .map(|m| {
+            let fields = m
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| thrift_field(f, i + 1) + "\n")
+                .collect::<String>();
+
+            format!("struct {} {{\n{}}}\n", m.name, fields)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Documentation of the columns, from the footer metadata and the given yaml file.
+///
+/// Field metadata of an embedded arrow schema and spark column comments are read from
+/// the footer, the yaml file takes precedence.
+fn catalog(parquet: &ParquetFile, docs: Option<&Path>) -> Result<Catalog> {
+    let metadata = parquet.footer_metadata()?;
+    let mut catalog = parquet
+        .arrow_schema()
+        .map(|s| Catalog::from_arrow(&s))
+        .unwrap_or_default();
+
+    if let Some(spark) = metadata.get(SPARK_SCHEMA_KEY) {
+        catalog.merge(Catalog::from_spark(spark).unwrap_or_default());
+    }
+
+    if let Some(path) = docs {
+        catalog.merge(Catalog::parse_yaml(&fs::read_to_string(path)?)?);
+    }
+
+    Ok(catalog)
+}
+
+/// Path, type, description and owner of every field.
+fn describe_rows(schema: &Type, catalog: &Catalog) -> Vec<Vec<String>> {
+    fn visit(rows: &mut Vec<Vec<String>>, prefix: &str, field: &Type, catalog: &Catalog) {
+        for child in field.get_fields() {
+            let path = format!("{}{}", prefix, child.name());
+            let kind = field_kind(child);
+            let doc = catalog.get(&path).cloned().unwrap_or_default();
+
+            rows.push(vec![
+                path.clone(),
+                kind,
+                doc.description.unwrap_or_default(),
+                doc.owner.unwrap_or_default(),
+            ]);
+
+            if child.is_group() {
+                visit(rows, &format!("{}.", path), child, catalog);
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+
+    visit(&mut rows, "", schema, catalog);
+
+    rows
+}
 
 pub fn def() -> App<'static> {
     SubCommand::with_name("schema")
@@ -11,11 +578,24 @@ pub fn def() -> App<'static> {
         .arg(
             Arg::with_name("format")
                 .help("Output format")
-                .possible_values(&["hive"])
+                .possible_values(SchemaFormat::values())
                 .default_value("hive")
                 .long("format")
                 .short('f'),
         )
+        .arg(
+            Arg::with_name("describe")
+                .help("Show the description and owner of each column")
+                .long("describe"),
+        )
+        .arg(
+            Arg::with_name("docs")
+                .validator(args::validate_path)
+                .help("Yaml file of column descriptions and owners")
+                .requires("describe")
+                .takes_value(true)
+                .long("docs"),
+        )
         .arg(
             Arg::with_name("path")
                 .validator(args::validate_path)
@@ -23,14 +603,49 @@ pub fn def() -> App<'static> {
                 .required(true)
                 .index(1),
         )
+        .args(args::table_args())
+        .args(args::discovery_args())
 }
 
 pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
     let path = args::path_value(matches, "path")?;
-    let parquet = ParquetFile::from(path);
+    let format = matches
+        .value_of("format")
+        .map(SchemaFormat::try_from)
+        .unwrap_or(Ok(SchemaFormat::Hive))?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
     let schema = parquet.schema()?;
 
-    print_schema(out, &schema);
+    if matches.is_present("describe") {
+        let docs = matches.value_of("docs").map(Path::new);
+        let rows = describe_rows(&schema, &catalog(&parquet, docs)?);
+        let headers = vec![
+            String::from("COLUMN"),
+            String::from("TYPE"),
+            String::from("DESCRIPTION"),
+            String::from("OWNER"),
+        ];
+        let mut writer = args::writer(matches, headers, rows.into_iter().map(Ok))
+            .format(OutputFormat::Tabular);
+
+        return writer.write(out);
+    }
+
+    match format {
+        SchemaFormat::Hive => print_schema(out, &schema),
+        SchemaFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &json_field(&schema))?;
+            writeln!(out)?;
+        }
+        SchemaFormat::Avro => {
+            serde_json::to_writer_pretty(&mut *out, &avro_record(&schema))?;
+            writeln!(out)?;
+        }
+        SchemaFormat::Proto => write!(out, "{}", proto_schema(&schema))?,
+        SchemaFormat::Thrift => write!(out, "{}", thrift_schema(&schema))?,
+    }
 
     Ok(())
 }
@@ -46,7 +661,7 @@ mod tests {
     fn test_schema_simple_message() {
         let mut output = Cursor::new(Vec::new());
         let parquet = api::tests::temp_file("msg", "parquet");
-        let expected = vec![
+        let expected = [
             "message rust_schema {",
             "  REQUIRED INT32 field_int32;",
             "  REQUIRED INT64 field_int64;",
@@ -74,4 +689,268 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_schema_format_try_from() {
+        assert_eq!(SchemaFormat::try_from("HIVE"), Ok(SchemaFormat::Hive));
+        assert_eq!(SchemaFormat::try_from("json"), Ok(SchemaFormat::Json));
+        assert_eq!(SchemaFormat::try_from("avro"), Ok(SchemaFormat::Avro));
+        assert_eq!(SchemaFormat::try_from("proto"), Ok(SchemaFormat::Proto));
+        assert_eq!(SchemaFormat::try_from("thrift"), Ok(SchemaFormat::Thrift));
+        assert_eq!(
+            SchemaFormat::try_from("foo"),
+            Err(Error::InvalidArgument(String::from("foo")))
+        );
+    }
+
+    fn schema_json(path: &str, format: &str) -> Value {
+        let mut output = Cursor::new(Vec::new());
+        let arg_vec = vec!["schema", path, format];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        serde_json::from_slice(&output.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_schema_simple_message_json() {
+        let parquet = api::tests::temp_file("msg", "parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(1);
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        let actual = schema_json(path_str, "-f=json");
+
+        assert_eq!(actual["name"], json!("rust_schema"));
+        assert_eq!(actual["type"], json!("GROUP"));
+        assert_eq!(actual["fields"].as_array().map(Vec::len), Some(7));
+        assert_eq!(
+            actual["fields"][4],
+            json!({
+                "name": "field_string",
+                "repetition": "REQUIRED",
+                "logical_type": "UTF8",
+                "type": "BYTE_ARRAY",
+            })
+        );
+        assert_eq!(actual["fields"][0]["logical_type"], Value::Null);
+    }
+
+    #[test]
+    fn test_schema_nested_avro() {
+        let parquet = api::tests::temp_file("nested", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[
+                r#"{"id": 1, "name": "foo", "address": {"city": "Lisbon"}, "tags": ["a"]}"#,
+            ],
+        );
+
+        let actual = schema_json(path_str, "-f=avro");
+
+        assert_eq!(actual["type"], json!("record"));
+        assert_eq!(
+            actual["fields"],
+            json!([
+                {"name": "id", "type": ["null", "long"], "default": null},
+                {"name": "name", "type": ["null", "string"], "default": null},
+                {
+                    "name": "address",
+                    "type": ["null", {
+                        "type": "record",
+                        "name": "address",
+                        "fields": [
+                            {"name": "city", "type": ["null", "string"], "default": null}
+                        ]
+                    }],
+                    "default": null
+                },
+                {
+                    "name": "tags",
+                    "type": ["null", {"type": "array", "items": ["null", "string"]}],
+                    "default": null
+                }
+            ])
+        );
+    }
+
+    fn schema_text(path: &str, format: &str) -> String {
+        let mut output = Cursor::new(Vec::new());
+        let arg_vec = vec!["schema", path, format];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        String::from_utf8(output.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_schema_simple_message_proto() {
+        let parquet = api::tests::temp_file("msg", "parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let msgs = api::tests::create_simple_messages(1);
+        let expected = [
+            "syntax = \"proto3\";",
+            "",
+            "message rust_schema {",
+            "  int32 field_int32 = 1;",
+            "  int64 field_int64 = 2;",
+            "  float field_float = 3;",
+            "  double field_double = 4;",
+            "  string field_string = 5;",
+            "  bool field_boolean = 6;",
+            "  int64 field_timestamp = 7;",
+            "}",
+            "",
+        ]
+        .join("\n");
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert_eq!(schema_text(path_str, "-f=proto"), expected);
+    }
+
+    #[test]
+    fn test_schema_nested_proto_and_thrift() {
+        let parquet = api::tests::temp_file("nested", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let proto = [
+            "syntax = \"proto3\";",
+            "",
+            "message Address {",
+            "  optional string city = 1;",
+            "}",
+            "",
+            "message arrow_schema {",
+            "  optional int64 id = 1;",
+            "  optional Address address = 2;",
+            "  repeated string tags = 3;",
+            "}",
+            "",
+        ]
+        .join("\n");
+        let thrift = [
+            "struct Address {",
+            "  1: optional string city;",
+            "}",
+            "",
+            "struct arrow_schema {",
+            "  1: optional i64 id;",
+            "  2: optional Address address;",
+            "  3: optional list<string> tags;",
+            "}",
+            "",
+        ]
+        .join("\n");
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[r#"{"id": 1, "address": {"city": "Lisbon"}, "tags": ["a"]}"#],
+        );
+
+        assert_eq!(schema_text(path_str, "-f=proto"), proto);
+        assert_eq!(schema_text(path_str, "-f=thrift"), thrift);
+    }
+
+    #[test]
+    fn test_schema_describe() {
+        use arrow::array::{Int64Array, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::ArrowWriter;
+        use std::sync::Arc;
+
+        let parquet = api::tests::temp_file("docs", ".parquet");
+        let docs = api::tests::temp_file("docs", ".yaml");
+        let metadata = [("description", "User id"), ("owner", "core")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false).with_metadata(Some(metadata)),
+            Field::new("email", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["ana@example.com"])),
+            ],
+        )
+        .unwrap();
+        let mut writer =
+            ArrowWriter::try_new(fs::File::create(parquet.path()).unwrap(), schema, None)
+                .unwrap();
+
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        fs::write(
+            docs.path(),
+            "email:\n  description: Primary email\n  owner: crm\n",
+        )
+        .unwrap();
+
+        let describe = |extra: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec =
+                vec!["schema", "--describe", parquet.path().to_str().unwrap()];
+
+            arg_vec.extend(extra);
+
+            assert!(
+                run(&def().get_matches_from_safe(arg_vec).unwrap(), &mut output).is_ok()
+            );
+
+            str::from_utf8(&output.into_inner())
+                .unwrap()
+                .lines()
+                .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            describe(&[]),
+            vec![
+                "COLUMN TYPE DESCRIPTION OWNER",
+                "id INT64 User id core",
+                "email UTF8",
+            ]
+        );
+        assert_eq!(
+            describe(&["--docs", docs.path().to_str().unwrap()]),
+            vec![
+                "COLUMN TYPE DESCRIPTION OWNER",
+                "id INT64 User id core",
+                "email UTF8 Primary email crm",
+            ]
+        );
+        assert!(def()
+            .get_matches_from_safe(vec![
+                "schema",
+                "--docs",
+                docs.path().to_str().unwrap(),
+                parquet.path().to_str().unwrap()
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn test_schema_idl_message_name() {
+        let message = |name: &str| IdlMessage {
+            name: name.to_string(),
+            fields: vec![],
+        };
+
+        assert_eq!(idl_message_name("home_address", &[]), "HomeAddress");
+        assert_eq!(
+            idl_message_name("address", &[message("Address")]),
+            "Address2"
+        );
+        assert_eq!(idl_message_name("_", &[]), "Message");
+        assert_eq!(idl_identifier("example.avro.User"), "example_avro_User");
+    }
 }
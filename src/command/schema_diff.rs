@@ -0,0 +1,249 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::basic::ConvertedType;
+use parquet::schema::types::{ColumnDescriptor, SchemaDescriptor, Type};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Repetition, physical and logical type of a leaf column, eg: `OPTIONAL INT64 (TIMESTAMP_MILLIS)`.
+#[inline]
+fn column_type(column: &ColumnDescriptor) -> String {
+    let info = column.self_type().get_basic_info();
+    let physical = column.physical_type();
+    let repetition = if info.has_repetition() {
+        format!("{} ", info.repetition())
+    } else {
+        String::new()
+    };
+
+    match column.converted_type() {
+        ConvertedType::NONE => format!("{}{}", repetition, physical),
+        ConvertedType::DECIMAL => format!(
+            "{}{} (DECIMAL({},{}))",
+            repetition,
+            physical,
+            column.type_precision(),
+            column.type_scale()
+        ),
+        converted => format!("{}{} ({})", repetition, physical, converted),
+    }
+}
+
+/// Path and type of each leaf column of a schema.
+#[inline]
+pub fn leaf_columns(schema: Type) -> Vec<(String, String)> {
+    SchemaDescriptor::new(Arc::new(schema))
+        .columns()
+        .iter()
+        .map(|c| (c.path().string(), column_type(c)))
+        .collect()
+}
+
+/// Removed, changed and added columns between two schemas.
+pub fn diff(left: &[(String, String)], right: &[(String, String)]) -> Vec<Vec<String>> {
+    let find = |columns: &[(String, String)], name: &str| {
+        columns
+            .iter()
+            .find(|c| c.0 == name)
+            .map(|c| c.1.to_string())
+    };
+
+    let mut changes = Vec::new();
+
+    for (name, left_type) in left {
+        match find(right, name) {
+            None => changes.push(vec![
+                String::from("removed"),
+                name.to_string(),
+                left_type.to_string(),
+                String::new(),
+            ]),
+            Some(right_type) if &right_type != left_type => changes.push(vec![
+                String::from("changed"),
+                name.to_string(),
+                left_type.to_string(),
+                right_type,
+            ]),
+            Some(_) => {}
+        }
+    }
+
+    for (name, right_type) in right {
+        if find(left, name).is_none() {
+            changes.push(vec![
+                String::from("added"),
+                name.to_string(),
+                String::new(),
+                right_type.to_string(),
+            ]);
+        }
+    }
+
+    changes
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("schema-diff")
+        .about("Show added, removed and changed fields between two parquet schemas")
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("left")
+                .validator(args::validate_path)
+                .help("Path to the original parquet")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("right")
+                .validator(args::validate_path)
+                .help("Path to the new parquet")
+                .required(true)
+                .index(2),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+/// Writes the changed fields, failing with `Error::SchemaMismatch` when the schemas differ.
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let left = args::path_value(matches, "left")?;
+    let right = args::path_value(matches, "right")?;
+    let left_columns = leaf_columns(
+        ParquetFile::from(left)
+            .with_discovery(args::discovery_value(matches))
+            .with_strict(matches.is_present("strict"))
+            .schema()?,
+    );
+    let right_columns = leaf_columns(
+        ParquetFile::from(right)
+            .with_discovery(args::discovery_value(matches))
+            .with_strict(matches.is_present("strict"))
+            .schema()?,
+    );
+    let changes = diff(&left_columns, &right_columns);
+    let num_changes = changes.len();
+    let headers = vec![
+        String::from("CHANGE"),
+        String::from("FIELD"),
+        String::from("LEFT"),
+        String::from("RIGHT"),
+    ];
+
+    let iter = changes.into_iter().map(Ok);
+    let mut writer = args::writer(matches, headers, iter).format(format);
+
+    writer.write(out)?;
+
+    match num_changes {
+        0 => Ok(()),
+        num => Err(Error::SchemaMismatch(num)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_schema_diff_columns() {
+        let column = |name: &str, kind: &str| (name.to_string(), kind.to_string());
+        let left = vec![
+            column("a", "INT32"),
+            column("b", "INT32"),
+            column("c", "INT32"),
+        ];
+        let right = vec![
+            column("a", "INT32"),
+            column("c", "INT64"),
+            column("d", "INT32"),
+        ];
+
+        assert_eq!(
+            diff(&left, &right),
+            vec![
+                vec!["removed", "b", "INT32", ""],
+                vec!["changed", "c", "INT32", "INT64"],
+                vec!["added", "d", "", "INT32"],
+            ]
+        );
+        assert!(diff(&left, &left).is_empty());
+    }
+
+    #[test]
+    fn test_schema_diff_simple_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("msg.parquet");
+        let expected = ["CHANGE,FIELD,LEFT,RIGHT", ""].join("\n");
+
+        api::tests::write_simple_messages_parquet(
+            &path,
+            &api::tests::create_simple_messages(1),
+        );
+
+        let path_str = path.to_str().unwrap();
+        let arg_vec = vec!["schema-diff", path_str, path_str, "-f=csv"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_schema_diff_changed_fields() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let left = dir.path().join("left.parquet");
+        let right = dir.path().join("right.parquet");
+        let expected = [
+            "CHANGE,FIELD,LEFT,RIGHT",
+            "removed,name,OPTIONAL BYTE_ARRAY (UTF8),",
+            "changed,score,OPTIONAL INT64,OPTIONAL DOUBLE",
+            "added,address.city,,OPTIONAL BYTE_ARRAY (UTF8)",
+            "",
+        ]
+        .join("\n");
+
+        api::tests::write_json_parquet(
+            &left,
+            &[r#"{"id": 1, "name": "foo", "score": 1}"#],
+        );
+        api::tests::write_json_parquet(
+            &right,
+            &[r#"{"id": 1, "score": 1.5, "address": {"city": "Lisbon"}}"#],
+        );
+
+        let arg_vec = vec![
+            "schema-diff",
+            left.to_str().unwrap(),
+            right.to_str().unwrap(),
+            "-f=csv",
+        ];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert_eq!(run(&args, &mut output), Err(Error::SchemaMismatch(3)));
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
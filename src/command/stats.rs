@@ -0,0 +1,671 @@
+use crate::api::{Error, Result};
+use crate::cache::Stamp;
+use crate::command::{self, args};
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use crate::value;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+const TOTAL_VALUE: &str = "TOTAL";
+
+/// Value distribution of a single column.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnStats {
+    rows: u64,
+    nulls: u64,
+    values: HashMap<String, u64>,
+}
+
+impl ColumnStats {
+    pub fn add(&mut self, field: &Field) {
+        self.rows += 1;
+
+        match field {
+            Field::Null => self.nulls += 1,
+            _ => *self.values.entry(value::format_field(field)).or_insert(0) += 1,
+        }
+    }
+
+    /// Add the stats of another partial result of the same column.
+    pub fn merge(&mut self, other: ColumnStats) {
+        self.rows += other.rows;
+        self.nulls += other.nulls;
+
+        for (value, count) in other.values {
+            *self.values.entry(value).or_insert(0) += count;
+        }
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    pub fn nulls(&self) -> u64 {
+        self.nulls
+    }
+
+    /// Number of distinct non null values.
+    pub fn distinct(&self) -> u64 {
+        self.values.len() as u64
+    }
+
+    /// Ratio of distinct values to rows, 1.0 for candidate key columns.
+    pub fn uniqueness(&self) -> f64 {
+        match self.rows() {
+            0 => 0.0,
+            rows => self.distinct() as f64 / rows as f64,
+        }
+    }
+
+    /// Shannon entropy (in bits) of the value distribution, nulls included.
+    pub fn entropy(&self) -> f64 {
+        let rows = self.rows() as f64;
+        let counts = self.values.values().copied();
+
+        counts
+            .chain(Some(self.nulls))
+            .filter(|c| *c > 0)
+            .map(|c| c as f64 / rows)
+            .map(|p| -p * p.log2())
+            .sum::<f64>()
+            .abs()
+    }
+
+    /// Whether every row holds the same value, nulls included.
+    pub fn is_constant(&self) -> bool {
+        let nulls = if self.nulls > 0 { 1 } else { 0 };
+
+        self.distinct() + nulls <= 1
+    }
+
+    /// Json of the stats with the count of each value, see `--state`.
+    fn to_json_value(&self) -> serde_json::Value {
+        json!({"rows": self.rows, "nulls": self.nulls, "values": self.values})
+    }
+
+    fn from_json_value(value: &serde_json::Value) -> Option<Self> {
+        let values = value
+            .get("values")?
+            .as_object()?
+            .iter()
+            .map(|(v, c)| Some((v.to_string(), c.as_u64()?)))
+            .collect::<Option<_>>()?;
+
+        Some(Self {
+            rows: value.get("rows")?.as_u64()?,
+            nulls: value.get("nulls")?.as_u64()?,
+            values,
+        })
+    }
+}
+
+/// Column stats of a file, with the size and modification time it had when read.
+///
+/// Files without a stamp, eg: urls, are left out of the state.
+#[derive(Clone, Debug)]
+struct FileStats {
+    stamp: Option<Stamp>,
+    stats: Vec<ColumnStats>,
+}
+
+/// Stats of each file kept by `--state` between runs.
+///
+/// The state only applies to the same columns and filter,
+/// files are read again once their size or modification time change.
+#[derive(Debug, Default)]
+struct State {
+    fields: Vec<String>,
+    filter: String,
+    files: HashMap<String, FileStats>,
+}
+
+impl State {
+    /// Load the state of a previous run, a missing state is empty.
+    fn load(path: &Path) -> Result<Self> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        serde_json::from_str(&source)
+            .ok()
+            .and_then(|json| Self::from_json_value(&json))
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!("Invalid state {}", path.display()))
+            })
+    }
+
+    fn from_json_value(value: &serde_json::Value) -> Option<Self> {
+        let fields = value
+            .get("fields")?
+            .as_array()?
+            .iter()
+            .map(|f| f.as_str().map(String::from))
+            .collect::<Option<_>>()?;
+        let files = value
+            .get("files")?
+            .as_object()?
+            .iter()
+            .map(|(path, file)| {
+                let stamp = Stamp {
+                    size: file.get("size")?.as_u64()?,
+                    modified: file.get("modified")?.as_u64()? as u128,
+                };
+                let stats = file
+                    .get("columns")?
+                    .as_array()?
+                    .iter()
+                    .map(ColumnStats::from_json_value)
+                    .collect::<Option<_>>()?;
+
+                let stamp = Some(stamp);
+
+                Some((path.to_string(), FileStats { stamp, stats }))
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self {
+            fields,
+            filter: value.get("filter")?.as_str()?.to_string(),
+            files,
+        })
+    }
+
+    fn to_json_value(&self) -> serde_json::Value {
+        let files = self
+            .files
+            .iter()
+            .filter_map(|(path, file)| {
+                let stamp = file.stamp?;
+                let json = json!({
+                    "size": stamp.size,
+                    "modified": stamp.modified as u64,
+                    "columns": file.stats.iter().map(ColumnStats::to_json_value).collect::<Vec<_>>(),
+                });
+
+                Some((path.to_string(), json))
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({"fields": self.fields, "filter": self.filter, "files": files})
+    }
+
+    /// Replace the state file, the previous state is kept when writing fails.
+    fn save(&self, path: &Path) -> Result<()> {
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+        let mut file = NamedTempFile::new_in(dir.unwrap_or_else(|| Path::new(".")))?;
+
+        serde_json::to_writer(&mut file, &self.to_json_value())?;
+        writeln!(file)?;
+        file.persist(path).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
+
+fn compute<I>(num_fields: usize, iter: I) -> Result<Vec<ColumnStats>>
+where
+    I: Iterator<Item = Result<Vec<Field>>>,
+{
+    let mut vec = vec![ColumnStats::default(); num_fields];
+
+    for row in iter {
+        for (i, field) in row?.iter().enumerate() {
+            vec[i].add(field);
+        }
+    }
+
+    Ok(vec)
+}
+
+/// Field names and column stats of a single input path.
+fn compute_path(parquet: &ParquetFile) -> Result<(Vec<String>, Vec<ColumnStats>)> {
+    let fields = parquet.field_names()?;
+    let vec = compute(fields.len(), parquet.records())?;
+
+    Ok((fields, vec))
+}
+
+/// State of the files of a single input path, reusing the stats of the files
+/// that did not change since the previous run.
+fn compute_files(parquet: &ParquetFile, previous: &State) -> Result<State> {
+    let fields = parquet.field_names()?;
+    let mut files = HashMap::new();
+
+    for (path, part) in parquet.file_parts() {
+        let key = path.display().to_string();
+        let stamp = Stamp::of(&path);
+
+        let stats = match previous.files.get(&key) {
+            Some(file) if stamp.is_some() && file.stamp == stamp => file.stats.clone(),
+            _ => compute(fields.len(), part.records())?,
+        };
+
+        files.insert(key, FileStats { stamp, stats });
+    }
+
+    Ok(State {
+        fields,
+        filter: previous.filter.to_string(),
+        files,
+    })
+}
+
+/// Compute the partial stats of each input path from the stats of its files,
+/// saving the stats of every file to the `state` file.
+fn compute_paths_with_state(
+    parquets: &[ParquetFile],
+    state: &Path,
+    filter: &str,
+) -> Result<Vec<(Vec<String>, Vec<ColumnStats>)>> {
+    let fields = match parquets.first() {
+        Some(parquet) => parquet.field_names()?,
+        None => Vec::new(),
+    };
+    let previous = Some(State::load(state)?)
+        .filter(|s| s.fields == fields && s.filter == filter)
+        .unwrap_or_else(|| State {
+            fields,
+            filter: filter.to_string(),
+            files: HashMap::new(),
+        });
+    let partials = command::parallel_map(parquets, |p| compute_files(p, &previous))
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    let mut next = State {
+        files: HashMap::new(),
+        ..previous
+    };
+    let mut result = Vec::new();
+
+    // files that are not part of the paths anymore are dropped
+    for partial in partials {
+        let mut vec = vec![ColumnStats::default(); partial.fields.len()];
+
+        for file in partial.files.values() {
+            for (stats, other) in vec.iter_mut().zip(&file.stats) {
+                stats.merge(other.clone());
+            }
+        }
+
+        next.files.extend(partial.files);
+        result.push((partial.fields, vec));
+    }
+
+    next.save(state)?;
+
+    Ok(result)
+}
+
+/// Compute the partial stats of each input path in parallel.
+fn compute_paths(
+    parquets: &[ParquetFile],
+) -> Result<Vec<(Vec<String>, Vec<ColumnStats>)>> {
+    command::parallel_map(parquets, compute_path)
+        .into_iter()
+        .collect()
+}
+
+/// Merge the partial stats of each path, all paths must have the same fields.
+///
+/// Distinct, uniqueness and entropy are computed from the merged value counts,
+/// so each path weights by its number of rows.
+fn merge_partials(
+    partials: Vec<(Vec<String>, Vec<ColumnStats>)>,
+) -> Result<Vec<ColumnStats>> {
+    let mut iter = partials.into_iter();
+    let (fields, mut result) = iter
+        .next()
+        .ok_or_else(|| Error::InvalidArgument(String::from("path")))?;
+
+    for (names, vec) in iter {
+        if names != fields {
+            return Err(Error::InvalidArgument(format!(
+                "Paths have different columns: {:?} and {:?}",
+                fields, names
+            )));
+        }
+
+        for (stats, other) in result.iter_mut().zip(vec) {
+            stats.merge(other);
+        }
+    }
+
+    Ok(result)
+}
+
+fn format_row(field: &str, stats: &ColumnStats) -> Vec<String> {
+    vec![
+        field.to_string(),
+        stats.rows().to_string(),
+        stats.nulls().to_string(),
+        stats.distinct().to_string(),
+        format!("{:.4}", stats.uniqueness()),
+        format!("{:.4}", stats.entropy()),
+        stats.is_constant().to_string(),
+    ]
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("stats")
+        .about("Show per column statistics")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("per-path")
+                .help("Show stats of each path along with the combined stats")
+                .long("per-path"),
+        )
+        .arg(
+            Arg::with_name("state")
+                .help("Keep the stats of each file in a json file, only new or changed files are read on later runs")
+                .takes_value(true)
+                .long("state"),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Paths to parquet")
+                .multiple(true)
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+        .arg(args::cache_arg())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let paths = args::path_values(matches, "path")?;
+    let per_path = matches.is_present("per-path");
+    let parquets = paths
+        .iter()
+        .map(|p| {
+            ParquetFile::from(*p)
+                .with_discovery(args::discovery_value(matches))
+                .with_strict(matches.is_present("strict"))
+                .with_fields(columns.clone())
+                .with_filter(search.clone())
+        })
+        .collect::<Vec<_>>();
+
+    let partials = match matches.value_of("state") {
+        Some(state) => compute_paths_with_state(
+            &parquets,
+            Path::new(state),
+            &format!("{:?}", search),
+        )?,
+        None => compute_paths(&parquets)?,
+    };
+    let fields = partials.first().map(|p| p.0.clone()).unwrap_or_default();
+    let mut headers = vec![
+        String::from("FIELD"),
+        String::from("ROWS"),
+        String::from("NULLS"),
+        String::from("DISTINCT"),
+        String::from("UNIQUENESS"),
+        String::from("ENTROPY"),
+        String::from("CONSTANT"),
+    ];
+
+    let sections = if per_path {
+        let merged = merge_partials(partials.clone())?;
+        let labels = paths.iter().map(|p| Some(p.display().to_string()));
+        let vecs = partials.into_iter().map(|p| p.1);
+
+        headers.insert(0, String::from("PATH"));
+
+        labels
+            .zip(vecs)
+            .chain(std::iter::once((Some(TOTAL_VALUE.to_string()), merged)))
+            .collect::<Vec<_>>()
+    } else {
+        vec![(None, merge_partials(partials)?)]
+    };
+
+    let mut rows = Vec::new();
+
+    for (label, vec) in sections {
+        for (field, stats) in fields.iter().zip(vec.iter()) {
+            let mut row = format_row(field, stats);
+
+            if let Some(label) = &label {
+                row.insert(0, label.to_string());
+            }
+
+            rows.push(Ok(row));
+        }
+    }
+
+    let mut writer = args::writer(matches, headers, rows.into_iter()).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_column_stats() {
+        let mut stats = ColumnStats::default();
+
+        stats.add(&Field::Int(1));
+        stats.add(&Field::Int(1));
+        stats.add(&Field::Int(2));
+        stats.add(&Field::Null);
+
+        assert_eq!(stats.rows(), 4);
+        assert_eq!(stats.nulls(), 1);
+        assert_eq!(stats.distinct(), 2);
+        assert_eq!(stats.uniqueness(), 0.5);
+        assert_eq!(stats.entropy(), 1.5);
+        assert!(!stats.is_constant());
+    }
+
+    #[test]
+    fn test_column_stats_constant() {
+        let mut stats = ColumnStats::default();
+
+        assert!(stats.is_constant());
+        assert_eq!(stats.entropy(), 0.0);
+        assert_eq!(stats.uniqueness(), 0.0);
+
+        stats.add(&Field::Bool(true));
+        stats.add(&Field::Bool(true));
+
+        assert!(stats.is_constant());
+        assert_eq!(stats.entropy(), 0.0);
+
+        stats.add(&Field::Null);
+
+        assert!(!stats.is_constant());
+    }
+
+    #[test]
+    fn test_stats_simple_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "FIELD,ROWS,NULLS,DISTINCT,UNIQUENESS,ENTROPY,CONSTANT",
+            "field_int32,4,0,4,1.0000,2.0000,false",
+            "field_boolean,4,0,2,0.5000,1.0000,false",
+            "",
+        ]
+        .join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(4);
+        let arg_vec = vec!["stats", path_str, "-c=field_int32,field_boolean", "-f=csv"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_column_stats_merge() {
+        let mut stats = ColumnStats::default();
+        let mut other = ColumnStats::default();
+
+        stats.add(&Field::Int(1));
+        stats.add(&Field::Int(2));
+        other.add(&Field::Int(1));
+        other.add(&Field::Null);
+
+        stats.merge(other);
+
+        assert_eq!(stats.rows(), 4);
+        assert_eq!(stats.nulls(), 1);
+        assert_eq!(stats.distinct(), 2);
+        assert_eq!(stats.entropy(), 1.5);
+    }
+
+    #[test]
+    fn test_stats_simple_messages_per_path() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.parquet");
+        let path2 = dir.path().join("2.parquet");
+        let path1_str = path1.to_str().unwrap();
+        let path2_str = path2.to_str().unwrap();
+        let expected = [
+            String::from("PATH,FIELD,ROWS,NULLS,DISTINCT,UNIQUENESS,ENTROPY,CONSTANT"),
+            format!("{},field_int32,2,0,2,1.0000,1.0000,false", path1_str),
+            format!("{},field_int32,2,0,2,1.0000,1.0000,false", path2_str),
+            String::from("TOTAL,field_int32,4,0,2,0.5000,1.0000,false"),
+            String::new(),
+        ]
+        .join("\n");
+
+        let msgs = api::tests::create_simple_messages(2);
+
+        api::tests::write_simple_messages_parquet(&path1, &msgs);
+        api::tests::write_simple_messages_parquet(&path2, &msgs);
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "stats",
+            path1_str,
+            path2_str,
+            "-c=field_int32",
+            "--per-path",
+            "-f=csv",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_stats_state() {
+        let dir = api::tests::temp_dir();
+        let data = dir.path().join("data");
+        let state = dir.path().join("state.json");
+        let data_str = data.to_str().unwrap();
+        let state_str = state.to_str().unwrap();
+        let path1 = data.join("1.parquet");
+
+        fs::create_dir(&data).unwrap();
+        api::tests::write_simple_messages_parquet(
+            &path1,
+            &api::tests::create_simple_messages(3),
+        );
+
+        let stats = || {
+            let mut output = Cursor::new(Vec::new());
+            let arg_vec = vec![
+                "stats",
+                data_str,
+                "-c=field_boolean",
+                "-f=csv",
+                "--state",
+                state_str,
+            ];
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output).unwrap();
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            stats(),
+            "FIELD,ROWS,NULLS,DISTINCT,UNIQUENESS,ENTROPY,CONSTANT\n\
+             field_boolean,3,0,2,0.6667,0.9183,false\n"
+        );
+
+        // the stored stats of unchanged files are not read again
+        let key = path1.display().to_string();
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&fs::read(&state).unwrap()).unwrap();
+
+        assert_eq!(json["fields"], json!(["field_boolean"]));
+        assert_eq!(
+            json["files"][&key]["columns"],
+            json!([{"rows": 3, "nulls": 0, "values": {"false": 2, "true": 1}}])
+        );
+
+        json["files"][&key]["columns"][0]["values"] = json!({"false": 3});
+        fs::write(&state, json.to_string()).unwrap();
+
+        api::tests::write_simple_messages_parquet(
+            &data.join("2.parquet"),
+            &api::tests::create_simple_messages(1),
+        );
+
+        assert_eq!(
+            stats(),
+            "FIELD,ROWS,NULLS,DISTINCT,UNIQUENESS,ENTROPY,CONSTANT\n\
+             field_boolean,4,0,1,0.2500,0.0000,true\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&fs::read(&state).unwrap()).unwrap();
+
+        assert_eq!(json["files"].as_object().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::api::Result;
+use crate::command::args;
+use crate::filter;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use chrono::{DateTime, NaiveDate};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use std::io::Write;
+
+/// Type a text column could be cast to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Suggestion {
+    Boolean,
+    Int,
+    Date,
+    Timestamp,
+    Uuid,
+    Double,
+}
+
+/// Suggestions from the most specific, integers also parse as doubles
+/// and dates as timestamps.
+const SUGGESTIONS: [Suggestion; 6] = [
+    Suggestion::Boolean,
+    Suggestion::Int,
+    Suggestion::Date,
+    Suggestion::Timestamp,
+    Suggestion::Uuid,
+    Suggestion::Double,
+];
+
+impl Suggestion {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Suggestion::Boolean => "boolean",
+            Suggestion::Int => "int64",
+            Suggestion::Date => "date",
+            Suggestion::Timestamp => "timestamp",
+            Suggestion::Uuid => "uuid",
+            Suggestion::Double => "double",
+        }
+    }
+
+    /// Whether a trimmed text parses as the type, numbers with leading zeros do not
+    /// so that codes like `007` stay strings.
+    fn parses(&self, text: &str) -> bool {
+        let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+        let leading_zero = digits.len() > 1
+            && digits.starts_with('0')
+            && digits[1..].starts_with(|c: char| c.is_ascii_digit());
+
+        match self {
+            Suggestion::Boolean => {
+                text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("false")
+            }
+            Suggestion::Int => !leading_zero && text.parse::<i64>().is_ok(),
+            Suggestion::Date => NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok(),
+            Suggestion::Timestamp => {
+                filter::parse_timestamp(text).is_some()
+                    || DateTime::parse_from_rfc3339(text).is_ok()
+            }
+            Suggestion::Uuid => is_uuid(text),
+            Suggestion::Double => {
+                !leading_zero
+                    && digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                    && text.parse::<f64>().is_ok_and(f64::is_finite)
+            }
+        }
+    }
+}
+
+/// Whether the text is a canonical uuid, eg: `f81d4fae-7dec-11d0-a765-00a0c91e6bf6`.
+#[inline]
+fn is_uuid(text: &str) -> bool {
+    text.len() == 36
+        && text.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Number of the non empty text values of a column parsing as each suggestion.
+#[derive(Clone, Debug, Default)]
+pub struct TypeCounts {
+    values: u64,
+    parsed: [u64; SUGGESTIONS.len()],
+}
+
+impl TypeCounts {
+    pub fn add(&mut self, field: &Field) {
+        let text = match field {
+            Field::Str(value) => value.trim(),
+            _ => return,
+        };
+
+        // legacy exporters write missing values as empty strings
+        if text.is_empty() {
+            return;
+        }
+
+        self.values += 1;
+
+        for (i, suggestion) in SUGGESTIONS.iter().enumerate() {
+            if suggestion.parses(text) {
+                self.parsed[i] += 1;
+            }
+        }
+    }
+
+    /// Most specific type parsing at least `min_confidence` of the values,
+    /// with the share of the values it parses.
+    pub fn suggestion(&self, min_confidence: f64) -> Option<(Suggestion, f64)> {
+        if self.values == 0 {
+            return None;
+        }
+
+        SUGGESTIONS
+            .iter()
+            .zip(self.parsed.iter())
+            .map(|(s, parsed)| (*s, *parsed as f64 / self.values as f64))
+            .find(|(_, confidence)| *confidence >= min_confidence)
+    }
+}
+
+fn compute<I>(num_fields: usize, iter: I) -> Result<Vec<TypeCounts>>
+where
+    I: Iterator<Item = Result<Vec<Field>>>,
+{
+    let mut vec = vec![TypeCounts::default(); num_fields];
+
+    for row in iter {
+        for (i, field) in row?.iter().enumerate() {
+            vec[i].add(field);
+        }
+    }
+
+    Ok(vec)
+}
+
+fn format_row(field: &str, counts: &TypeCounts, min_confidence: f64) -> Vec<String> {
+    let (name, confidence) = match counts.suggestion(min_confidence) {
+        Some((suggestion, confidence)) => {
+            (suggestion.name(), format!("{:.2}", confidence))
+        }
+        None => ("string", String::from("-")),
+    };
+
+    vec![
+        field.to_string(),
+        counts.values.to_string(),
+        name.to_string(),
+        confidence,
+    ]
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("suggest-types")
+        .about("Suggest types for text columns whose values parse as numbers, dates, booleans or uuids")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .arg(
+            Arg::with_name("sample")
+                .validator(args::validate_number)
+                .help("Number of rows to scan")
+                .default_value("1000")
+                .long("sample")
+                .short('n'),
+        )
+        .arg(
+            Arg::with_name("min-confidence")
+                .validator(args::validate_fraction)
+                .help("Share of the values a type has to parse to be suggested")
+                .default_value("1")
+                .long("min-confidence"),
+        )
+        .arg(
+            Arg::with_name("casts")
+                .help("Write the suggested casts only, one <column>:<type> per line")
+                .conflicts_with("format")
+                .long("casts"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let sample = args::usize_value(matches, "sample")?;
+    let min_confidence = args::f64_value(matches, "min-confidence")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from((path, columns))
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+
+    let fields = parquet.field_names()?;
+    let vec = compute(fields.len(), parquet.records().take(sample))?;
+
+    if matches.is_present("casts") {
+        for (field, counts) in fields.iter().zip(vec.iter()) {
+            if let Some((suggestion, _)) = counts.suggestion(min_confidence) {
+                writeln!(out, "{}:{}", field, suggestion.name())?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    let headers = vec![
+        String::from("FIELD"),
+        String::from("VALUES"),
+        String::from("TYPE"),
+        String::from("CONFIDENCE"),
+    ];
+
+    // only text columns are reported
+    let rows = fields
+        .iter()
+        .zip(vec.iter())
+        .filter(|t| t.1.values > 0)
+        .map(|t| Ok(format_row(t.0, t.1, min_confidence)))
+        .collect::<Vec<_>>();
+
+    let mut writer = args::writer(matches, headers, rows.into_iter()).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_suggest_types_parses() {
+        let parses = |s: Suggestion, text: &str| s.parses(text);
+
+        assert!(parses(Suggestion::Boolean, "TRUE"));
+        assert!(!parses(Suggestion::Boolean, "1"));
+        assert!(parses(Suggestion::Int, "-42"));
+        assert!(parses(Suggestion::Int, "0"));
+        assert!(!parses(Suggestion::Int, "007"));
+        assert!(parses(Suggestion::Double, "0.5"));
+        assert!(parses(Suggestion::Double, "-1e3"));
+        assert!(!parses(Suggestion::Double, "NaN"));
+        assert!(!parses(Suggestion::Double, "inf"));
+        assert!(parses(Suggestion::Date, "2024-05-01"));
+        assert!(!parses(Suggestion::Date, "2024-13-01"));
+        assert!(parses(Suggestion::Timestamp, "2024-05-01 10:00:00"));
+        assert!(parses(Suggestion::Timestamp, "2024-05-01T10:00:00+02:00"));
+        assert!(parses(
+            Suggestion::Uuid,
+            "f81d4fae-7dec-11d0-a765-00a0c91e6bf6"
+        ));
+        assert!(!parses(
+            Suggestion::Uuid,
+            "f81d4fae7dec-11d0-a765-00a0c91e6bf6x"
+        ));
+    }
+
+    #[test]
+    fn test_suggest_types_suggestion() {
+        let mut counts = TypeCounts::default();
+
+        assert_eq!(counts.suggestion(1.0), None);
+
+        for value in ["1", "2", "2.5", "", "3"] {
+            counts.add(&Field::Str(String::from(value)));
+        }
+
+        counts.add(&Field::Int(1));
+
+        assert_eq!(counts.values, 4);
+        assert_eq!(counts.suggestion(1.0), Some((Suggestion::Double, 1.0)));
+        assert_eq!(counts.suggestion(0.75), Some((Suggestion::Int, 0.75)));
+    }
+
+    #[test]
+    fn test_suggest_types_json_messages() {
+        let parquet = api::tests::temp_file("types", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+
+        api::tests::write_json_parquet(
+            parquet.path(),
+            &[
+                r#"{"id": 1, "age": "31", "day": "2024-05-01", "name": "Ana", "ok": "true"}"#,
+                r#"{"id": 2, "age": "x", "day": "2024-05-02", "name": "Ben", "ok": "false"}"#,
+            ],
+        );
+
+        let suggest = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec!["suggest-types", path_str];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output).unwrap();
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            suggest(&["-f=csv"]),
+            [
+                "FIELD,VALUES,TYPE,CONFIDENCE",
+                "age,2,string,-",
+                "day,2,date,1.00",
+                "name,2,string,-",
+                "ok,2,boolean,1.00",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            suggest(&["--casts", "--min-confidence=0.5"]),
+            "age:int64\nday:date\nok:boolean\n"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use crate::api::Result;
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use crate::value;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::record::Field;
+use regex::Regex;
+use stats::Frequencies;
+use std::io::Write;
+
+/// Splits a value into tokens, using a regex when given or whitespace otherwise.
+fn tokenize<'a>(value: &'a str, pattern: &Option<Regex>) -> Vec<&'a str> {
+    match pattern {
+        Some(regex) => regex.find_iter(value).map(|m| m.as_str()).collect(),
+        None => value.split_whitespace().collect(),
+    }
+}
+
+/// Joins each window of `size` consecutive tokens.
+fn ngrams(tokens: &[&str], size: usize) -> Vec<String> {
+    if size <= 1 {
+        return tokens.iter().map(|t| t.to_string()).collect();
+    }
+
+    tokens.windows(size).map(|w| w.join(" ")).collect()
+}
+
+#[inline]
+fn field_text(field: &Field) -> Option<String> {
+    match field {
+        Field::Null => None,
+        Field::Str(value) => Some(value.to_string()),
+        _ => Some(value::format_field(field)),
+    }
+}
+
+fn compute(
+    num_fields: usize,
+    iter: impl Iterator<Item = Result<Vec<Field>>>,
+    pattern: &Option<Regex>,
+    size: usize,
+) -> Result<Vec<Frequencies<String>>> {
+    let mut vec: Vec<_> = (0..num_fields).map(|_| Frequencies::new()).collect();
+
+    for row in iter {
+        for (i, field) in row?.iter().enumerate() {
+            if let Some(text) = field_text(field) {
+                for token in ngrams(&tokenize(&text, pattern), size) {
+                    vec[i].add(token);
+                }
+            }
+        }
+    }
+
+    Ok(vec)
+}
+
+/// Most frequent tokens first, ties ordered by token.
+fn top_tokens(freq: &Frequencies<String>, top: usize) -> Vec<(String, u64)> {
+    let mut counts = freq
+        .most_frequent()
+        .into_iter()
+        .map(|c| (c.0.to_string(), c.1))
+        .collect::<Vec<_>>();
+
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(top);
+
+    counts
+}
+
+fn format_rows(
+    fields: Vec<String>,
+    vec: Vec<Frequencies<String>>,
+    top: usize,
+) -> impl Iterator<Item = Result<Vec<String>>> {
+    fields.into_iter().zip(vec).flat_map(move |t| {
+        let header = t.0;
+
+        top_tokens(&t.1, top)
+            .into_iter()
+            .map(move |c| Ok(vec![header.to_string(), c.0, c.1.to_string()]))
+    })
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("tokens")
+        .about("Show the most frequent tokens of text columns")
+        .arg(
+            Arg::with_name("columns")
+                .help("Select columns from parquet")
+                .takes_value(true)
+                .required(true)
+                .long("columns")
+                .multiple(true)
+                .short('c'),
+        )
+        .args(args::search_args())
+        .arg(
+            Arg::with_name("pattern")
+                .validator(args::validate_regex)
+                .help("Token regex, splits on whitespace when missing")
+                .takes_value(true)
+                .long("pattern")
+                .short('p'),
+        )
+        .arg(
+            Arg::with_name("ngram")
+                .validator(args::validate_number)
+                .help("Number of consecutive tokens counted together")
+                .default_value("1")
+                .long("ngram")
+                .short('n'),
+        )
+        .arg(
+            Arg::with_name("top")
+                .validator(args::validate_number)
+                .help("Max number of tokens per column")
+                .default_value("50")
+                .long("top")
+                .short('t'),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+        .arg(args::cache_arg())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let columns = args::string_values(matches, "columns")?;
+    let search = args::search_value(matches)?;
+    let pattern = args::regex_value(matches, "pattern")?;
+    let ngram = args::usize_value(matches, "ngram")?;
+    let top = args::usize_value(matches, "top")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"))
+        .with_fields(columns)
+        .with_filter(search);
+
+    let fields = parquet.field_names()?;
+    let vec = compute(fields.len(), parquet.records(), &pattern, ngram)?;
+    let headers = vec![
+        String::from("FIELD"),
+        String::from("TOKEN"),
+        String::from("COUNT"),
+    ];
+
+    let iter = format_rows(fields, vec, top);
+    let mut writer = args::writer(matches, headers, iter).format(format);
+
+    writer.write(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_tokens_tokenize() {
+        let regex = Some(Regex::new("[a-z]+").unwrap());
+
+        assert_eq!(
+            tokenize(" GET  /index ok ", &None),
+            vec!["GET", "/index", "ok"]
+        );
+        assert_eq!(tokenize("GET /index ok", &regex), vec!["index", "ok"]);
+        assert_eq!(ngrams(&["a", "b", "c"], 2), vec!["a b", "b c"]);
+        assert!(ngrams(&["a"], 2).is_empty());
+    }
+
+    #[test]
+    fn test_tokens_simple_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "FIELD         TOKEN  COUNT",
+            "field_string  odd    2",
+            "field_string  11111  1",
+            "",
+        ]
+        .join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(3);
+        let arg_vec = vec!["tokens", path_str, "-c=field_string", "--top=2"];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_tokens_simple_messages_ngram_pattern() {
+        let mut output = Cursor::new(Vec::new());
+        let parquet = api::tests::temp_file("msg", ".parquet");
+        let path_str = parquet.path().to_str().unwrap();
+        let expected = [
+            "FIELD,TOKEN,COUNT",
+            "field_string,even 22222,1",
+            "field_string,odd 11111,1",
+            "field_string,odd 33333,1",
+            "",
+        ]
+        .join("\n");
+
+        let subcomand = def();
+        let msgs = api::tests::create_simple_messages(3);
+        let arg_vec = vec![
+            "tokens",
+            path_str,
+            "-c=field_string",
+            "-p=[a-z]+|[0-9]+",
+            "-n=2",
+            "-t=3",
+            "-f=csv",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        api::tests::write_simple_messages_parquet(parquet.path(), &msgs);
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::api::{Error, Result};
+use std::fs::{File, OpenOptions};
+use std::os::unix::io::AsRawFd;
+
+/// Terminal of the process in raw mode, restored when dropped.
+///
+/// Keys are read from `/dev/tty`, so they are available when stdin or stdout are redirected.
+pub(in crate::command) struct RawMode {
+    pub tty: File,
+    original: libc::termios,
+}
+
+impl RawMode {
+    /// Switch the terminal to raw mode, `command` names what needs it in errors.
+    pub fn open(command: &str) -> Result<Self> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .map_err(|e| Error::IO(format!("{} needs a terminal, {}", command, e)))?;
+        let fd = tty.as_raw_fd();
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+
+        if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+            return Err(Error::IO(format!("{} needs a terminal", command)));
+        }
+
+        let mut raw = original;
+
+        unsafe {
+            libc::cfmakeraw(&mut raw);
+            libc::tcsetattr(fd, libc::TCSANOW, &raw);
+        }
+
+        Ok(Self { tty, original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSANOW, &self.original);
+        }
+    }
+}
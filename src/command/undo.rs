@@ -0,0 +1,157 @@
+use crate::api::Result;
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::snapshot;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::io::Write;
+use std::path::Path;
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("undo")
+        .about("Restore the files replaced by the last write of a directory")
+        .arg(
+            Arg::with_name("list")
+                .help("List the snapshots instead, from the oldest")
+                .long("list"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format of --list")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Directory of the replaced files")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let path = args::path_value(matches, "path")?;
+
+    // snapshots of a file are kept in its directory
+    let root = if path.is_file() {
+        path.parent().unwrap_or_else(|| Path::new(""))
+    } else {
+        path
+    };
+
+    if matches.is_present("list") {
+        let headers = vec![
+            String::from("SNAPSHOT"),
+            String::from("COMMAND"),
+            String::from("CREATED"),
+            String::from("FILES"),
+        ];
+        let rows = snapshot::snapshots(root)?
+            .into_iter()
+            .map(|(dir, manifest)| {
+                let name = dir.file_name().unwrap_or_default().to_string_lossy();
+
+                Ok(vec![
+                    name.to_string(),
+                    manifest.command,
+                    manifest.created,
+                    manifest.files.len().to_string(),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let mut writer = args::writer(matches, headers, rows.into_iter()).format(format);
+
+        return writer.write(out);
+    }
+
+    match snapshot::restore(root)? {
+        Some(manifest) => {
+            for (file, _) in &manifest.files {
+                writeln!(out, "{} restored", root.join(file).display())?;
+            }
+        }
+        None => writeln!(out, "No snapshot to restore in {}", root.display())?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::command::write;
+    use crate::reader::{Discovery, ParquetFile};
+    use std::fs;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_undo_write() {
+        let dir = api::tests::temp_dir();
+        let parquet = dir.path().join("users.parquet");
+        let parquet_str = parquet.to_str().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let csv = |name: &str, lines: &[&str]| {
+            let path = dir.path().join(name);
+
+            fs::write(&path, lines.join("\n")).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let v1 = csv("v1.csv", &["id", "1"]);
+        let v2 = csv("v2.csv", &["id", "1", "2"]);
+        let command = |def: App<'static>, args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let matches = def.get_matches_from_safe(args).unwrap();
+            let result = if args[0] == "write" {
+                write::run(&matches, &mut output)
+            } else {
+                run(&matches, &mut output)
+            };
+
+            result.unwrap();
+
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+        let rows = || ParquetFile::from(parquet.as_path()).iter().count();
+
+        command(write::def(), &["write", &v1, "-o", parquet_str]);
+        command(write::def(), &["write", &v2, "-o", parquet_str]);
+
+        assert_eq!(rows(), 2);
+
+        // snapshots are not read as data, even with hidden files
+        let hidden = Discovery {
+            hidden: true,
+            ..Discovery::default()
+        };
+
+        assert_eq!(
+            ParquetFile::from(dir.path())
+                .with_discovery(hidden)
+                .files()
+                .collect::<Vec<_>>(),
+            vec![parquet.clone()]
+        );
+        assert_eq!(
+            command(def(), &["undo", dir_str, "--list", "-f=csv"])
+                .lines()
+                .map(|l| l.split(',').skip(1).step_by(2).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec!["COMMAND", "FILES"], vec!["write", "1"]]
+        );
+        assert_eq!(
+            command(def(), &["undo", parquet_str]),
+            format!("{} restored\n", parquet.display())
+        );
+        assert_eq!(rows(), 1);
+        assert_eq!(
+            command(def(), &["undo", dir_str]),
+            format!("No snapshot to restore in {}\n", dir.path().display())
+        );
+        assert_eq!(ParquetFile::from(dir.path()).files().count(), 1);
+    }
+}
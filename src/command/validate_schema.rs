@@ -0,0 +1,185 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::command::schema_diff::{diff, leaf_columns};
+use crate::output::OutputFormat;
+use crate::reader::ParquetFile;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::io::Write;
+
+/// Changes of each file compared to the schema of the first file, sorted by path.
+///
+/// Files without rows are still compared but never used as the reference,
+/// unless every file is empty.
+fn divergent_files(parquet: &ParquetFile) -> Result<Vec<Vec<String>>> {
+    let fingerprints = parquet
+        .fingerprints()
+        .into_iter()
+        .filter_map(|r| parquet.readable(r))
+        .collect::<Result<Vec<_>>>()?;
+    let mut rows = Vec::new();
+
+    // files sharing the same schema hash can not diverge
+    if fingerprints
+        .windows(2)
+        .all(|w| w[0].1.schema_hash == w[1].1.schema_hash)
+    {
+        return Ok(rows);
+    }
+
+    let mut schemas = parquet.file_schemas().collect::<Result<Vec<_>>>()?;
+    let empty = parquet.empty_files();
+
+    schemas.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let reference = match schemas.iter().position(|(p, _)| !empty.contains(p)) {
+        Some(index) => schemas.remove(index),
+        None if schemas.is_empty() => return Ok(rows),
+        None => schemas.remove(0),
+    };
+    let expected = leaf_columns(reference.1);
+
+    for (path, schema) in schemas {
+        for mut change in diff(&expected, &leaf_columns(schema)) {
+            change.insert(0, path.display().to_string());
+            rows.push(change);
+        }
+    }
+
+    Ok(rows)
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("validate-schema")
+        .about("Compare the schema of every file against the first file")
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet directory")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+/// Writes the divergent fields, failing with `Error::SchemaMismatch` when any file diverges.
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+    let rows = divergent_files(&parquet)?;
+    let num_changes = rows.len();
+    let headers = vec![
+        String::from("PATH"),
+        String::from("CHANGE"),
+        String::from("FIELD"),
+        String::from("EXPECTED"),
+        String::from("ACTUAL"),
+    ];
+
+    let mut writer =
+        args::writer(matches, headers, rows.into_iter().map(Ok)).format(format);
+
+    writer.write(out)?;
+
+    match num_changes {
+        0 => Ok(()),
+        num => Err(Error::SchemaMismatch(num)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::io::Cursor;
+    use std::str;
+
+    #[test]
+    fn test_validate_schema_consistent() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let msgs = api::tests::create_simple_messages(1);
+
+        api::tests::write_simple_messages_parquet(&dir.path().join("1.parquet"), &msgs);
+        api::tests::write_simple_messages_parquet(&dir.path().join("2.parquet"), &msgs);
+
+        let arg_vec = vec!["validate-schema", dir.path().to_str().unwrap(), "-f=csv"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, "PATH,CHANGE,FIELD,EXPECTED,ACTUAL\n");
+    }
+
+    #[test]
+    fn test_validate_schema_divergent() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let first = dir.path().join("1.parquet");
+        let second = dir.path().join("2.parquet");
+        let expected = [
+            String::from("PATH,CHANGE,FIELD,EXPECTED,ACTUAL"),
+            format!(
+                "{},removed,name,OPTIONAL BYTE_ARRAY (UTF8),",
+                second.display()
+            ),
+            String::new(),
+        ]
+        .join("\n");
+
+        api::tests::write_json_parquet(&first, &[r#"{"id": 1, "name": "foo"}"#]);
+        api::tests::write_json_parquet(&second, &[r#"{"id": 2}"#]);
+
+        let arg_vec = vec!["validate-schema", dir.path().to_str().unwrap(), "-f=csv"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert_eq!(run(&args, &mut output), Err(Error::SchemaMismatch(1)));
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_validate_schema_empty_file_reference() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let first = dir.path().join("1.parquet");
+        let second = dir.path().join("2.parquet");
+        let third = dir.path().join("3.parquet");
+
+        // the first file has no rows and a diverging schema
+        api::tests::write_simple_messages_parquet(&first, &[]);
+        api::tests::write_json_parquet(&second, &[r#"{"id": 1}"#]);
+        api::tests::write_json_parquet(&third, &[r#"{"id": 2}"#]);
+
+        let arg_vec = vec!["validate-schema", dir.path().to_str().unwrap(), "-f=csv"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+        let result = run(&args, &mut output);
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+        let changes = actual.lines().skip(1).collect::<Vec<_>>();
+
+        assert!(matches!(result, Err(Error::SchemaMismatch(n)) if n == changes.len()));
+        assert!(!changes.is_empty());
+        assert!(changes
+            .iter()
+            .all(|c| c.starts_with(first.to_str().unwrap())));
+    }
+}
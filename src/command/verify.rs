@@ -0,0 +1,303 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::OutputFormat;
+use crate::reader::{ParquetFile, ParquetSource};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::column::reader::{ColumnReader, ColumnReaderImpl};
+use parquet::data_type::DataType;
+use parquet::errors::ParquetError;
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use parquet_format::PageHeader;
+use std::convert::TryFrom;
+use std::io::{Cursor, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use thrift::protocol::TCompactInputProtocol;
+
+const BATCH_SIZE: usize = 1024;
+
+/// Integrity checks of a single file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Report {
+    row_groups: usize,
+    pages: usize,
+    checksums: usize,
+}
+
+#[inline]
+fn corrupt(message: String) -> ParquetError {
+    ParquetError::General(message)
+}
+
+/// Walk the pages of a column chunk, verifying the CRC of pages that have one.
+///
+/// Returns the number of pages and verified checksums.
+fn verify_pages(chunk: &[u8]) -> std::result::Result<(usize, usize), ParquetError> {
+    let mut cursor = Cursor::new(chunk);
+    let mut pages = 0;
+    let mut checksums = 0;
+
+    while (cursor.position() as usize) < chunk.len() {
+        let header = PageHeader::read_from_in_protocol(&mut TCompactInputProtocol::new(
+            &mut cursor,
+        ))?;
+        let start = cursor.position() as usize;
+        let end = start + usize::try_from(header.compressed_page_size).unwrap_or(0);
+        let data = chunk
+            .get(start..end)
+            .ok_or_else(|| corrupt(format!("Page {} exceeds the column chunk", pages)))?;
+
+        if let Some(crc) = header.crc {
+            if crc32fast::hash(data) != crc as u32 {
+                return Err(corrupt(format!("Page {} checksum mismatch", pages)));
+            }
+
+            checksums += 1;
+        }
+
+        pages += 1;
+        cursor.set_position(end as u64);
+    }
+
+    Ok((pages, checksums))
+}
+
+/// Decode every value of a column chunk.
+fn decode_column<T: DataType>(
+    mut reader: ColumnReaderImpl<T>,
+) -> std::result::Result<(), ParquetError> {
+    let mut values = vec![T::T::default(); BATCH_SIZE];
+    let mut def_levels = vec![0; BATCH_SIZE];
+    let mut rep_levels = vec![0; BATCH_SIZE];
+
+    loop {
+        let (values_read, levels_read) = reader.read_batch(
+            BATCH_SIZE,
+            Some(&mut def_levels),
+            Some(&mut rep_levels),
+            &mut values,
+        )?;
+
+        if values_read == 0 && levels_read == 0 {
+            return Ok(());
+        }
+    }
+}
+
+#[inline]
+fn decode(reader: ColumnReader) -> std::result::Result<(), ParquetError> {
+    match reader {
+        ColumnReader::BoolColumnReader(r) => decode_column(r),
+        ColumnReader::Int32ColumnReader(r) => decode_column(r),
+        ColumnReader::Int64ColumnReader(r) => decode_column(r),
+        ColumnReader::Int96ColumnReader(r) => decode_column(r),
+        ColumnReader::FloatColumnReader(r) => decode_column(r),
+        ColumnReader::DoubleColumnReader(r) => decode_column(r),
+        ColumnReader::ByteArrayColumnReader(r) => decode_column(r),
+        ColumnReader::FixedLenByteArrayColumnReader(r) => decode_column(r),
+    }
+}
+
+/// Validate the footer, checksums and decode every page of a file.
+fn verify_file(path: &Path) -> std::result::Result<Report, ParquetError> {
+    let reader = SerializedFileReader::new(ParquetSource::open(path)?)?;
+    let source = ParquetSource::open(path)?;
+    let mut report = Report::default();
+
+    for i in 0..reader.num_row_groups() {
+        let row_group = reader.get_row_group(i)?;
+
+        for (c, column) in row_group.metadata().columns().iter().enumerate() {
+            let (start, length) = column.byte_range();
+            let chunk = source.get_bytes(start, length as usize)?;
+
+            let (pages, checksums) = verify_pages(&chunk)?;
+
+            decode(row_group.get_column_reader(c)?)?;
+
+            report.pages += pages;
+            report.checksums += checksums;
+        }
+
+        report.row_groups += 1;
+    }
+
+    Ok(report)
+}
+
+/// Verify a file, reporting decoder panics as corruption.
+fn verify(path: &Path) -> Result<Report> {
+    let hook = panic::take_hook();
+
+    panic::set_hook(Box::new(|_| {}));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| verify_file(path)));
+
+    panic::set_hook(hook);
+
+    result
+        .unwrap_or_else(|_| Err(corrupt(String::from("Failed to decode pages"))))
+        .map_err(|e| Error::Parquet(path.to_path_buf(), e))
+}
+
+fn format_row(path: &Path, result: Result<Report>) -> Vec<String> {
+    let path = path.display().to_string();
+
+    match result {
+        Ok(report) => vec![
+            path,
+            String::from("OK"),
+            report.row_groups.to_string(),
+            report.pages.to_string(),
+            report.checksums.to_string(),
+            String::new(),
+        ],
+        Err(e) => vec![
+            path,
+            String::from("CORRUPT"),
+            String::new(),
+            String::new(),
+            String::new(),
+            e.to_string(),
+        ],
+    }
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("verify")
+        .about("Verify footers, page checksums and decode every page of parquet files")
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .possible_values(OutputFormat::values())
+                .default_value("table")
+                .long("format")
+                .short('f'),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to parquet")
+                .required(true)
+                .index(1),
+        )
+        .args(args::table_args())
+        .args(args::discovery_args())
+}
+
+/// Writes the status of each file, failing with `Error::CorruptFiles` when any file is corrupt.
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let format = args::output_format_value(matches, "format")?;
+    let path = args::path_value(matches, "path")?;
+    let parquet = ParquetFile::from(path)
+        .with_discovery(args::discovery_value(matches))
+        .with_strict(matches.is_present("strict"));
+    let mut corrupt = 0;
+    let mut rows = Vec::new();
+
+    for file in parquet.files() {
+        let result = verify(&file);
+
+        if result.is_err() {
+            corrupt += 1;
+        }
+
+        rows.push(Ok(format_row(&file, result)));
+    }
+
+    let headers = vec![
+        String::from("PATH"),
+        String::from("STATUS"),
+        String::from("ROW_GROUPS"),
+        String::from("PAGES"),
+        String::from("CHECKSUMS"),
+        String::from("ERROR"),
+    ];
+
+    let mut writer = args::writer(matches, headers, rows.into_iter()).format(format);
+
+    writer.write(out)?;
+
+    match corrupt {
+        0 => Ok(()),
+        num => Err(Error::CorruptFiles(num)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use parquet_format::{DataPageHeader, Encoding, PageType};
+    use std::fs;
+    use std::str;
+    use thrift::protocol::TCompactOutputProtocol;
+
+    fn page(data: &[u8], crc: Option<i32>) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let data_header =
+            DataPageHeader::new(1, Encoding::Plain, Encoding::Rle, Encoding::Rle, None);
+        let header = PageHeader::new(
+            PageType::DataPage,
+            data.len() as i32,
+            data.len() as i32,
+            crc,
+            data_header,
+            None,
+            None,
+            None,
+        );
+
+        header
+            .write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut buffer))
+            .unwrap();
+        buffer.extend_from_slice(data);
+        buffer
+    }
+
+    #[test]
+    fn test_verify_pages() {
+        let crc = crc32fast::hash(b"data") as i32;
+        let valid = [page(b"data", Some(crc)), page(b"other", None)].concat();
+        let invalid = [page(b"data", None), page(b"data", Some(crc + 1))].concat();
+        let truncated = page(b"data", None);
+
+        assert_eq!(verify_pages(&valid), Ok((2, 1)));
+        assert_eq!(
+            verify_pages(&invalid),
+            Err(corrupt(String::from("Page 1 checksum mismatch")))
+        );
+        assert_eq!(
+            verify_pages(&truncated[..truncated.len() - 1]),
+            Err(corrupt(String::from("Page 0 exceeds the column chunk")))
+        );
+    }
+
+    #[test]
+    fn test_verify_simple_messages() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let valid = dir.path().join("1.parquet");
+        let invalid = dir.path().join("2.parquet");
+        let msgs = api::tests::create_simple_messages(3);
+
+        api::tests::write_simple_messages_parquet(&valid, &msgs);
+        fs::write(&invalid, b"PAR1 not really a parquet file").unwrap();
+
+        let arg_vec = vec!["verify", dir.path().to_str().unwrap(), "-f=csv"];
+        let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+        assert_eq!(run(&args, &mut output), Err(Error::CorruptFiles(1)));
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+        let mut lines = actual.lines().collect::<Vec<_>>();
+
+        lines.sort_unstable();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], format!("{},OK,1,13,0,", valid.display()));
+        assert!(lines[1].starts_with(&format!("{},CORRUPT,,,,", invalid.display())));
+        assert_eq!(lines[2], "PATH,STATUS,ROW_GROUPS,PAGES,CHECKSUMS,ERROR");
+    }
+}
@@ -0,0 +1,616 @@
+use crate::api::{Error, Result};
+use crate::command::args;
+use crate::output::{OutputFile, WriteLock};
+use crate::snapshot::Snapshot;
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use parquet::arrow::{parquet_to_arrow_schema, ArrowWriter};
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use parquet::schema::parser::parse_message_type;
+use parquet::schema::types::SchemaDescriptor;
+use std::convert::TryFrom;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Import format.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ImportFormat {
+    // CSV format with a header line
+    Csv,
+
+    // Newline delimited JSON format
+    Jsonl,
+}
+
+const IMPORT_FORMAT_VALUES: &[&str] = &["csv", "jsonl"];
+
+const COMPRESSION_VALUES: &[&str] =
+    &["uncompressed", "snappy", "gzip", "brotli", "lz4", "zstd"];
+
+const BATCH_SIZE: usize = 1024;
+
+type RecordBatches = Box<dyn Iterator<Item = Result<RecordBatch>>>;
+
+impl ImportFormat {
+    pub fn values() -> &'static [&'static str] {
+        IMPORT_FORMAT_VALUES
+    }
+
+    /// Guess the import format using the file extension.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+
+        match extension.as_deref() {
+            Some("json") | Some("jsonl") | Some("ndjson") => ImportFormat::Jsonl,
+            _ => ImportFormat::Csv,
+        }
+    }
+}
+
+impl TryFrom<&str> for ImportFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "csv" => Ok(ImportFormat::Csv),
+            "jsonl" => Ok(ImportFormat::Jsonl),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+#[inline]
+fn compression_value(value: &str) -> Result<Compression> {
+    match value.to_lowercase().as_ref() {
+        "uncompressed" => Ok(Compression::UNCOMPRESSED),
+        "snappy" => Ok(Compression::SNAPPY),
+        "gzip" => Ok(Compression::GZIP),
+        "brotli" => Ok(Compression::BROTLI),
+        "lz4" => Ok(Compression::LZ4),
+        "zstd" => Ok(Compression::ZSTD),
+        _ => Err(Error::InvalidArgument(value.to_string())),
+    }
+}
+
+#[inline]
+fn read_schema(path: &Path) -> Result<SchemaRef> {
+    let message = fs::read_to_string(path)?;
+    let schema = parse_message_type(&message)
+        .map(|t| SchemaDescriptor::new(Arc::new(t)))
+        .and_then(|d| parquet_to_arrow_schema(&d, None))
+        .map_err(|e| Error::Parquet(path.to_path_buf(), e))?;
+
+    Ok(Arc::new(schema))
+}
+
+/// Arrow type a column is written as, `Date64` would be written as a parquet `DATE`
+/// dropping the time of the day so it is written as a timestamp instead.
+#[inline]
+fn write_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::Date64 => DataType::Timestamp(TimeUnit::Millisecond, None),
+        other => other.clone(),
+    }
+}
+
+/// Arrow type a column is parsed as, readers build timestamps without a timezone
+/// and the csv reader only parses microsecond and nanosecond ones.
+#[inline]
+fn read_type(format: ImportFormat, data_type: &DataType) -> DataType {
+    match (format, data_type) {
+        (ImportFormat::Csv, DataType::Timestamp(_, _)) => {
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        }
+        (ImportFormat::Jsonl, DataType::Timestamp(unit, _)) => {
+            DataType::Timestamp(unit.clone(), None)
+        }
+        (_, other) => other.clone(),
+    }
+}
+
+#[inline]
+fn map_schema<F: Fn(&DataType) -> DataType>(schema: &Schema, f: F) -> SchemaRef {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| Field::new(field.name(), f(field.data_type()), field.is_nullable()))
+        .collect();
+
+    Arc::new(Schema::new(fields))
+}
+
+/// Cast the columns of the batch to the types of the written schema.
+#[inline]
+fn cast_batch(batch: RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    if batch.schema().fields() == schema.fields() {
+        return Ok(batch);
+    }
+
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| match column.data_type() {
+            data_type if data_type == field.data_type() => Ok(column.clone()),
+            // dates are cast to timestamps through their milliseconds
+            DataType::Date64 => cast(&cast(column, &DataType::Int64)?, field.data_type()),
+            _ => cast(column, field.data_type()),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn read_batches(
+    path: &Path,
+    format: ImportFormat,
+    schema: Option<SchemaRef>,
+) -> Result<(SchemaRef, RecordBatches)> {
+    let file = File::open(path)?;
+
+    let (read_schema, batches): (SchemaRef, RecordBatches) = match format {
+        ImportFormat::Csv => {
+            let builder = arrow::csv::ReaderBuilder::new()
+                .has_header(true)
+                .with_batch_size(BATCH_SIZE);
+
+            let reader = match &schema {
+                Some(schema) => {
+                    builder.with_schema(map_schema(schema, |t| read_type(format, t)))
+                }
+                None => builder.infer_schema(None),
+            }
+            .build(file)?;
+
+            (reader.schema(), Box::new(reader.map(|b| Ok(b?))))
+        }
+        ImportFormat::Jsonl => {
+            let builder = arrow::json::ReaderBuilder::new().with_batch_size(BATCH_SIZE);
+
+            let reader = match &schema {
+                Some(schema) => {
+                    builder.with_schema(map_schema(schema, |t| read_type(format, t)))
+                }
+                None => builder.infer_schema(None),
+            }
+            .build(file)?;
+
+            (reader.schema(), Box::new(reader.map(|b| Ok(b?))))
+        }
+    };
+    let schema = map_schema(schema.as_ref().unwrap_or(&read_schema), write_type);
+    let target = schema.clone();
+    let batches = batches.map(move |b| cast_batch(b?, &target));
+
+    Ok((schema, Box::new(batches)))
+}
+
+pub fn def() -> App<'static> {
+    SubCommand::with_name("write")
+        .about("Write csv/jsonl rows to parquet")
+        .alias("to-parquet")
+        .arg(
+            Arg::with_name("schema")
+                .validator(args::validate_path)
+                .help("Parquet message type, inferred from the input when missing")
+                .takes_value(true)
+                .long("schema")
+                .short('s'),
+        )
+        .arg(
+            Arg::with_name("from")
+                .help("Input format, guessed from the file extension when missing")
+                .possible_values(ImportFormat::values())
+                .takes_value(true)
+                .long("from")
+                .short('i'),
+        )
+        .arg(
+            Arg::with_name("compression")
+                .help("Compression codec")
+                .possible_values(COMPRESSION_VALUES)
+                .default_value("snappy")
+                .long("compression")
+                .short('z'),
+        )
+        .arg(
+            Arg::with_name("row-group-size")
+                .validator(args::validate_number)
+                .help("Max number of rows per row group")
+                .default_value("1048576")
+                .long("row-group-size")
+                .short('r'),
+        )
+        .arg(
+            Arg::with_name("output")
+                .help("Output parquet file")
+                .takes_value(true)
+                .required(true)
+                .long("output")
+                .short('o'),
+        )
+        .arg(
+            Arg::with_name("staging-dir")
+                .validator(args::validate_path)
+                .help("Directory of the file being written, moved over the output once complete")
+                .takes_value(true)
+                .long("staging-dir"),
+        )
+        .arg(
+            Arg::with_name("no-snapshot")
+                .help("Do not keep a copy of the replaced output for undo")
+                .long("no-snapshot"),
+        )
+        .arg(
+            Arg::with_name("path")
+                .validator(args::validate_path)
+                .help("Path to csv/jsonl")
+                .required(true)
+                .index(1),
+        )
+}
+
+pub fn run<W: Write>(matches: &ArgMatches, out: &mut W) -> Result<()> {
+    let path = args::path_value(matches, "path")?;
+    let row_group_size = args::usize_value(matches, "row-group-size")?;
+    let output = matches
+        .value_of("output")
+        .map(Path::new)
+        .ok_or_else(|| Error::InvalidArgument(String::from("output")))?;
+    let compression = matches
+        .value_of("compression")
+        .map(compression_value)
+        .unwrap_or(Ok(Compression::SNAPPY))?;
+    let format = matches
+        .value_of("from")
+        .map(ImportFormat::try_from)
+        .unwrap_or_else(|| Ok(ImportFormat::from_path(path)))?;
+    let schema = match matches.value_of("schema") {
+        Some(schema) => Some(read_schema(Path::new(schema))?),
+        None => None,
+    };
+
+    let (schema, batches) = read_batches(path, format, schema)?;
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(row_group_size)
+        .set_compression(compression)
+        .build();
+
+    let parquet_err = |e| Error::Parquet(output.to_path_buf(), e);
+    let staging_dir = matches.value_of("staging-dir").map(Path::new);
+
+    // concurrent runs fail on the lock, readers see the previous file until the rename
+    let _lock = WriteLock::acquire(output)?;
+    let file = OutputFile::staged(output, staging_dir)?;
+
+    // a replaced output is kept until the rename, `undo <dir>` moves it back
+    let snapshot = if output.is_file() && !matches.is_present("no-snapshot") {
+        let root = output.parent().unwrap_or_else(|| Path::new(""));
+        let mut snapshot = Snapshot::create(root, "write")?;
+
+        snapshot.keep(output)?;
+
+        Some(snapshot)
+    } else {
+        None
+    };
+    let mut writer =
+        ArrowWriter::try_new(file, schema, Some(props)).map_err(parquet_err)?;
+    let mut rows = 0;
+
+    for batch in batches {
+        let batch = batch?;
+
+        rows += batch.num_rows();
+
+        writer.write(&batch).map_err(parquet_err)?;
+    }
+
+    writer.into_inner().map_err(parquet_err)?.finish()?;
+
+    if let Some(snapshot) = snapshot {
+        snapshot.commit()?;
+    }
+
+    writeln!(out, "{} rows written to {}", rows, output.display())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::reader::ParquetFile;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::io::Cursor;
+    use std::str;
+
+    fn write_file(path: &Path, lines: &[&str]) {
+        fs::write(path, lines.join("\n")).unwrap();
+    }
+
+    #[test]
+    fn test_import_format_from_path() {
+        assert_eq!(
+            ImportFormat::from_path(Path::new("a.csv")),
+            ImportFormat::Csv
+        );
+        assert_eq!(
+            ImportFormat::from_path(Path::new("a.NDJSON")),
+            ImportFormat::Jsonl
+        );
+        assert_eq!(
+            ImportFormat::from_path(Path::new("a.jsonl")),
+            ImportFormat::Jsonl
+        );
+        assert_eq!(ImportFormat::from_path(Path::new("a")), ImportFormat::Csv);
+    }
+
+    #[test]
+    fn test_compression_value() {
+        assert_eq!(compression_value("ZSTD"), Ok(Compression::ZSTD));
+        assert_eq!(
+            compression_value("lzo"),
+            Err(Error::InvalidArgument(String::from("lzo")))
+        );
+    }
+
+    #[test]
+    fn test_write_csv_infer_schema() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let csv = dir.path().join("input.csv");
+        let parquet = dir.path().join("output.parquet");
+
+        write_file(
+            &csv,
+            &["id,name,score", "1,foo,1.5", "2,bar,2.5", "3,baz,3"],
+        );
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "write",
+            csv.to_str().unwrap(),
+            "-o",
+            parquet.to_str().unwrap(),
+            "-r=2",
+            "-z=gzip",
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let vec = output.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+        let reader = SerializedFileReader::try_from(parquet.as_path()).unwrap();
+        let metadata = reader.metadata();
+        let rows = ParquetFile::from(parquet.as_path())
+            .iter()
+            .map(|r| r.unwrap().join(","))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            actual,
+            format!("3 rows written to {}\n", parquet.to_str().unwrap())
+        );
+        assert_eq!(metadata.num_row_groups(), 2);
+        assert_eq!(
+            metadata.row_group(0).column(0).compression(),
+            Compression::GZIP
+        );
+        assert_eq!(
+            rows,
+            vec!["1,\"foo\",1.5", "2,\"bar\",2.5", "3,\"baz\",3.0"]
+        );
+    }
+
+    #[test]
+    fn test_write_csv_with_schema() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let csv = dir.path().join("input.txt");
+        let schema = dir.path().join("schema.txt");
+        let parquet = dir.path().join("output.parquet");
+
+        write_file(&csv, &["id,name", "1,foo", "2,bar"]);
+        write_file(
+            &schema,
+            &[
+                "message schema {",
+                "  REQUIRED INT32 id;",
+                "  OPTIONAL BYTE_ARRAY name (UTF8);",
+                "}",
+            ],
+        );
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "write",
+            csv.to_str().unwrap(),
+            "-o",
+            parquet.to_str().unwrap(),
+            "-s",
+            schema.to_str().unwrap(),
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let parquet_file = ParquetFile::from(parquet.as_path());
+        let schema = parquet_file.schema().unwrap();
+        let fields = schema.get_fields();
+        let rows = parquet_file
+            .iter()
+            .map(|r| r.unwrap().join(","))
+            .collect::<Vec<_>>();
+
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].get_physical_type(), parquet::basic::Type::INT32);
+        assert_eq!(rows, vec!["1,\"foo\"", "2,\"bar\""]);
+    }
+
+    #[test]
+    fn test_write_jsonl() {
+        let mut output = Cursor::new(Vec::new());
+        let dir = api::tests::temp_dir();
+        let json = dir.path().join("input.ndjson");
+        let parquet = dir.path().join("output.parquet");
+
+        write_file(
+            &json,
+            &[
+                "{\"id\": 1, \"active\": true, \"name\": \"foo\"}",
+                "{\"id\": 2, \"active\": false}",
+            ],
+        );
+
+        let subcomand = def();
+        let arg_vec = vec![
+            "write",
+            json.to_str().unwrap(),
+            "-o",
+            parquet.to_str().unwrap(),
+        ];
+        let args = subcomand.get_matches_from_safe(arg_vec).unwrap();
+
+        assert!(run(&args, &mut output).is_ok());
+
+        let parquet_file = ParquetFile::from(parquet.as_path());
+        let headers = parquet_file.field_names().unwrap();
+        let rows = parquet_file
+            .iter()
+            .map(|r| r.unwrap().join(","))
+            .collect::<Vec<_>>();
+
+        assert_eq!(headers, vec!["id", "active", "name"]);
+        assert_eq!(rows, vec!["1,true,\"foo\"", "2,false,null"]);
+    }
+
+    #[test]
+    fn test_write_datetimes() {
+        let dir = api::tests::temp_dir();
+        let schema = dir.path().join("schema.txt");
+        let parquet = dir.path().join("output.parquet");
+        let write = |name: &str, lines: &[&str], args: &[&str]| {
+            let input = dir.path().join(name);
+
+            write_file(&input, lines);
+
+            let mut arg_vec = vec![
+                "write",
+                input.to_str().unwrap(),
+                "-o",
+                parquet.to_str().unwrap(),
+                "--no-snapshot",
+            ];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut Cursor::new(Vec::new())).unwrap();
+
+            let parquet_file = ParquetFile::from(parquet.as_path());
+            let schema = parquet_file.schema().unwrap();
+            let rows = parquet_file.iter().map(|r| r.unwrap()[1].clone());
+
+            (
+                format!("{:?}", schema.get_fields()[1]),
+                rows.collect::<Vec<_>>(),
+            )
+        };
+        let schema_arg = |unit: &str| {
+            write_file(
+                &schema,
+                &[
+                    "message schema {",
+                    "  REQUIRED INT32 id;",
+                    &format!("  OPTIONAL INT64 ts ({});", unit),
+                    "}",
+                ],
+            );
+
+            schema.to_str().unwrap().to_string()
+        };
+        let csv = ["id,ts", "1,2024-01-01T10:30:00", "2,"];
+        let jsonl = [
+            "{\"id\": 1, \"ts\": \"2024-01-01T10:30:00\"}",
+            "{\"id\": 2}",
+        ];
+        let expected = vec![
+            String::from("2024-01-01 10:30:00 +00:00"),
+            String::from("null"),
+        ];
+
+        // inferred datetimes keep their time of the day
+        let (field, rows) = write("input.csv", &csv, &[]);
+
+        assert!(field.contains("TIMESTAMP_MILLIS"), "{}", field);
+        assert_eq!(rows, expected);
+
+        for (unit, converted) in [
+            ("TIMESTAMP_MILLIS", "TIMESTAMP_MILLIS"),
+            ("TIMESTAMP_MICROS", "TIMESTAMP_MICROS"),
+            ("TIMESTAMP(MILLIS,true)", "TIMESTAMP_MILLIS"),
+        ] {
+            let schema = schema_arg(unit);
+            let (field, rows) = write("input.csv", &csv, &["-s", &schema]);
+
+            assert!(field.contains(converted), "{}", field);
+            assert_eq!(rows, expected);
+
+            let (field, rows) = write("input.jsonl", &jsonl, &["-s", &schema]);
+
+            assert!(field.contains(converted), "{}", field);
+            assert_eq!(rows, expected);
+        }
+    }
+
+    #[test]
+    fn test_write_locked_output() {
+        let dir = api::tests::temp_dir();
+        let staging = api::tests::temp_dir();
+        let csv = dir.path().join("input.csv");
+        let parquet = dir.path().join("output.parquet");
+
+        write_file(&csv, &["id,name", "1,foo", "2,bar"]);
+
+        let write = |args: &[&str]| {
+            let mut output = Cursor::new(Vec::new());
+            let mut arg_vec = vec![
+                "write",
+                csv.to_str().unwrap(),
+                "-o",
+                parquet.to_str().unwrap(),
+            ];
+
+            arg_vec.extend(args);
+
+            let args = def().get_matches_from_safe(arg_vec).unwrap();
+
+            run(&args, &mut output)
+        };
+        let lock = WriteLock::acquire(&parquet).unwrap();
+
+        assert!(WriteLock::acquire(&parquet).is_err());
+        assert!(write(&[]).is_err());
+        assert!(!parquet.exists());
+
+        drop(lock);
+
+        assert!(write(&["--staging-dir", staging.path().to_str().unwrap()]).is_ok());
+        assert_eq!(ParquetFile::from(parquet.as_path()).iter().count(), 2);
+        assert_eq!(fs::read_dir(staging.path()).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
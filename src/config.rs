@@ -0,0 +1,382 @@
+use crate::api::{Error, Result};
+use crate::group::ColumnGroup;
+use crate::http::RetryPolicy;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const PRESET_PREFIX: &str = "preset.";
+const ALIAS_PREFIX: &str = "alias.";
+const GROUP_PREFIX: &str = "group.";
+const HTTP_RETRIES_KEY: &str = "http.retries";
+const HTTP_BACKOFF_KEY: &str = "http.backoff-ms";
+const HTTP_TIMEOUT_KEY: &str = "http.timeout-secs";
+
+/// User configuration file.
+///
+/// Lines hold `key = value` pairs, blank lines and lines starting
+/// with `#` are ignored, eg: `preset.events = id,ts,type,status`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    presets: HashMap<String, Vec<String>>,
+    aliases: HashMap<String, String>,
+    groups: HashMap<String, Vec<String>>,
+    retry: RetryPolicy,
+}
+
+impl Config {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut presets = HashMap::new();
+        let mut aliases = HashMap::new();
+        let mut groups = HashMap::new();
+        let mut retry = RetryPolicy::default();
+
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || Error::Config(format!("Invalid line {}: {}", i + 1, line));
+            let (key, value) = line
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(invalid)?;
+            let number = || value.parse::<u64>().map_err(|_| invalid());
+
+            match key {
+                HTTP_RETRIES_KEY => retry.retries = number()? as u32,
+                HTTP_BACKOFF_KEY => retry.backoff = Duration::from_millis(number()?),
+                HTTP_TIMEOUT_KEY => retry.timeout = Duration::from_secs(number()?),
+                _ => {}
+            }
+
+            let columns = || {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .map(String::from)
+                    .collect()
+            };
+
+            if let Some(name) = key.strip_prefix(PRESET_PREFIX) {
+                presets.insert(name.to_string(), columns());
+            }
+
+            if let Some(name) = key.strip_prefix(GROUP_PREFIX) {
+                groups.insert(name.to_string(), columns());
+            }
+
+            if let Some(name) = key.strip_prefix(ALIAS_PREFIX) {
+                aliases.insert(name.to_string(), value.to_string());
+            }
+        }
+
+        Ok(Self {
+            presets,
+            aliases,
+            groups,
+            retry,
+        })
+    }
+
+    /// Retries of remote reads, eg: `http.retries = 5`, `http.backoff-ms = 500`
+    /// and `http.timeout-secs = 30`.
+    pub fn retry(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Columns of a named preset.
+    pub fn preset(&self, name: &str) -> Option<&[String]> {
+        self.presets.get(name).map(Vec::as_slice)
+    }
+
+    /// Column patterns of a named group, eg: `group.address = addr_*`.
+    pub fn group(&self, name: &str) -> Option<ColumnGroup> {
+        self.groups.get(name).map(|patterns| ColumnGroup {
+            name: name.to_string(),
+            patterns: patterns.clone(),
+        })
+    }
+
+    /// Command line of a saved alias.
+    pub fn alias(&self, name: &str) -> Option<&str> {
+        self.aliases.get(name).map(String::as_str)
+    }
+
+    /// Saved aliases sorted by name.
+    pub fn aliases(&self) -> Vec<(&str, &str)> {
+        let mut vec = self
+            .aliases
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect::<Vec<_>>();
+
+        vec.sort_unstable();
+        vec
+    }
+
+    /// Presets, aliases and groups as `key = value` lines sorted by key,
+    /// eg: to tell outputs rendered with different settings apart.
+    pub fn lines(&self) -> Vec<String> {
+        let entries = |prefix, map: &HashMap<String, Vec<String>>| {
+            map.iter()
+                .map(|(k, v)| format!("{}{} = {}", prefix, k, v.join(",")))
+                .collect::<Vec<_>>()
+        };
+        let mut vec = entries(PRESET_PREFIX, &self.presets);
+
+        vec.extend(entries(GROUP_PREFIX, &self.groups));
+        vec.extend(
+            self.aliases
+                .iter()
+                .map(|(k, v)| format!("{}{} = {}", ALIAS_PREFIX, k, v)),
+        );
+        vec.sort_unstable();
+        vec
+    }
+}
+
+/// Split a command line into arguments, honoring single and double quotes.
+pub fn split_args(line: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut pending = false;
+
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                pending = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if pending || !current.is_empty() {
+                    result.push(std::mem::take(&mut current));
+                }
+
+                pending = false;
+            }
+            (None, c) => current.push(c),
+        }
+    }
+
+    if pending || !current.is_empty() {
+        result.push(current);
+    }
+
+    result
+}
+
+/// Replace, add or remove (when `value` is `None`) a key of the config source.
+fn update_source(source: &str, key: &str, value: Option<&str>) -> String {
+    let is_key = |line: &str| {
+        line.split_once('=')
+            .map(|(k, _)| k.trim() == key)
+            .unwrap_or(false)
+    };
+
+    let mut lines = source
+        .lines()
+        .filter(|l| !is_key(l.trim()))
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    if let Some(value) = value {
+        lines.push(format!("{} = {}", key, value));
+    }
+
+    lines.into_iter().map(|l| l + "\n").collect()
+}
+
+/// Replace, add or remove (when `value` is `None`) a key of the config file.
+pub fn update(key: &str, value: Option<&str>) -> Result<()> {
+    let path = config_path().ok_or_else(|| {
+        Error::Config(String::from("Unknown config path, set XPQ_CONFIG"))
+    })?;
+    let source = if path.is_file() {
+        fs::read_to_string(&path)?
+    } else {
+        String::new()
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&path, update_source(&source, key, value))?;
+
+    Ok(())
+}
+
+/// Config key of an alias.
+pub fn alias_key(name: &str) -> String {
+    format!("{}{}", ALIAS_PREFIX, name)
+}
+
+/// Config file path, `$XPQ_CONFIG` or `xpq/config` in the user config directory.
+fn config_path() -> Option<PathBuf> {
+    let var = |name| {
+        env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    var("XPQ_CONFIG").or_else(|| {
+        var("XDG_CONFIG_HOME")
+            .or_else(|| var("HOME").map(|h| h.join(".config")))
+            .map(|d| d.join("xpq").join("config"))
+    })
+}
+
+fn load() -> std::result::Result<Config, String> {
+    let path = match config_path() {
+        Some(path) if path.is_file() => path,
+        _ => return Ok(Config::default()),
+    };
+
+    fs::read_to_string(&path)
+        .map_err(|e| format!("{} >>> {}", path.display(), e))
+        .and_then(|s| {
+            Config::parse(&s).map_err(|e| format!("{} >>> {}", path.display(), e))
+        })
+}
+
+/// The user configuration, loaded on first use.
+pub fn get() -> Result<&'static Config> {
+    static CONFIG: OnceLock<std::result::Result<Config, String>> = OnceLock::new();
+
+    CONFIG
+        .get_or_init(load)
+        .as_ref()
+        .map_err(|e| Error::Config(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_parse() {
+        let config = Config::parse(
+            "
+            # main table
+            preset.events = id, ts,type,status
+            preset.empty =
+            other.key = ignored
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.preset("events"),
+            Some(
+                &[
+                    String::from("id"),
+                    String::from("ts"),
+                    String::from("type"),
+                    String::from("status"),
+                ][..]
+            )
+        );
+        assert_eq!(config.preset("empty"), Some(&[][..]));
+        assert_eq!(config.preset("other"), None);
+    }
+
+    #[test]
+    fn test_config_groups() {
+        let config = Config::parse("group.address = addr_*, zip").unwrap();
+
+        assert_eq!(
+            config.group("address"),
+            Some(ColumnGroup {
+                name: String::from("address"),
+                patterns: vec![String::from("addr_*"), String::from("zip")],
+            })
+        );
+        assert_eq!(config.group("other"), None);
+    }
+
+    #[test]
+    fn test_config_aliases() {
+        let config = Config::parse(
+            "alias.failed = read --search status:failed -f csv\nalias.a = count",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.alias("failed"),
+            Some("read --search status:failed -f csv")
+        );
+        assert_eq!(
+            config.aliases(),
+            vec![
+                ("a", "count"),
+                ("failed", "read --search status:failed -f csv")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_split_args() {
+        assert_eq!(
+            split_args(r#"read  -s 'status:in progress' -c "a,b" ''"#),
+            vec!["read", "-s", "status:in progress", "-c", "a,b", ""]
+        );
+        assert!(split_args("  ").is_empty());
+    }
+
+    #[test]
+    fn test_config_update_source() {
+        let source = "# aliases\nalias.a = count\npreset.p = a\n";
+
+        assert_eq!(
+            update_source(source, "alias.a", Some("read")),
+            "# aliases\npreset.p = a\nalias.a = read\n"
+        );
+        assert_eq!(
+            update_source(source, "alias.a", None),
+            "# aliases\npreset.p = a\n"
+        );
+        assert_eq!(update_source("", "alias.b", Some("x")), "alias.b = x\n");
+    }
+
+    #[test]
+    fn test_config_retry() {
+        let config = Config::parse(
+            "http.retries = 5\nhttp.backoff-ms = 10\nhttp.timeout-secs = 2",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.retry(),
+            RetryPolicy {
+                retries: 5,
+                backoff: Duration::from_millis(10),
+                timeout: Duration::from_secs(2),
+            }
+        );
+        assert_eq!(Config::default().retry(), RetryPolicy::default());
+        assert_eq!(
+            Config::parse("http.retries = many"),
+            Err(Error::Config(String::from(
+                "Invalid line 1: http.retries = many"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_config_parse_err() {
+        assert_eq!(
+            Config::parse("preset.a = b\nfoo"),
+            Err(Error::Config(String::from("Invalid line 2: foo")))
+        );
+    }
+}
@@ -0,0 +1,814 @@
+use crate::api::{Error, Result};
+use crate::value;
+use chrono::{NaiveDate, NaiveDateTime};
+use parquet::record::Field;
+use regex::Regex;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// Comparison operator of a typed filter, eg: `age>=30`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Operators sorted so that two character operators are tried first.
+const OPERATORS: &[(&str, Operator)] = &[
+    ("!=", Operator::Ne),
+    ("<=", Operator::Le),
+    (">=", Operator::Ge),
+    ("=", Operator::Eq),
+    ("<", Operator::Lt),
+    (">", Operator::Gt),
+];
+
+impl Operator {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+        }
+    }
+
+    #[inline]
+    fn matches(&self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::Ne => ordering != Ordering::Equal,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::Le => ordering != Ordering::Greater,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Literal of a comparison, parsed once for every type it may be compared with.
+#[derive(Clone, Debug)]
+pub struct Operand {
+    text: String,
+    int: Option<i128>,
+    number: Option<f64>,
+    boolean: Option<bool>,
+
+    // Microseconds from the unix epoch, eg: `2020-01-01` or `2020-01-01 10:00:00`
+    timestamp: Option<i64>,
+}
+
+const DATETIME_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Microseconds from the unix epoch of a date or a date and time,
+/// eg: `2020-01-01` or `2020-01-01 10:00:00`.
+#[inline]
+pub fn parse_timestamp(text: &str) -> Option<i64> {
+    let datetime = DATETIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .map(|d| d.and_hms(0, 0, 0))
+        })?;
+
+    Some(datetime.timestamp() * 1_000_000 + i64::from(datetime.timestamp_subsec_micros()))
+}
+
+impl Operand {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            int: text.parse().ok(),
+            number: text.parse().ok().filter(|n: &f64| !n.is_nan()),
+            boolean: text.parse().ok(),
+            timestamp: parse_timestamp(text),
+        }
+    }
+
+    /// Whether the literal is a date or a date and time, eg: `2020-01-01`.
+    pub fn is_timestamp(&self) -> bool {
+        self.timestamp.is_some()
+    }
+
+    /// Literal as given in the expression.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[inline]
+    fn cmp_int(&self, value: i128) -> Option<Ordering> {
+        match self.int {
+            Some(int) => Some(value.cmp(&int)),
+            None => self.cmp_number(value as f64),
+        }
+    }
+
+    #[inline]
+    fn cmp_number(&self, value: f64) -> Option<Ordering> {
+        self.number.and_then(|n| value.partial_cmp(&n))
+    }
+
+    #[inline]
+    fn cmp_timestamp(&self, micros: i64) -> Option<Ordering> {
+        self.timestamp.map(|t| micros.cmp(&t))
+    }
+
+    /// Order of the value relative to the operand, `None` when they can not be compared.
+    fn cmp_field(&self, field: &Field) -> Option<Ordering> {
+        match field {
+            Field::Bool(v) => self.boolean.map(|b| v.cmp(&b)),
+            Field::Byte(v) => self.cmp_int(i128::from(*v)),
+            Field::Short(v) => self.cmp_int(i128::from(*v)),
+            Field::Int(v) => self.cmp_int(i128::from(*v)),
+            Field::Long(v) => self.cmp_int(i128::from(*v)),
+            Field::UByte(v) => self.cmp_int(i128::from(*v)),
+            Field::UShort(v) => self.cmp_int(i128::from(*v)),
+            Field::UInt(v) => self.cmp_int(i128::from(*v)),
+            Field::ULong(v) => self.cmp_int(i128::from(*v)),
+            Field::Float(v) => self.cmp_number(f64::from(*v)),
+            Field::Double(v) => self.cmp_number(*v),
+            Field::Str(v) => Some(v.as_str().cmp(self.text.as_str())),
+            Field::Date(v) => self.cmp_timestamp(i64::from(*v) * MICROS_PER_DAY),
+            Field::TimestampMillis(v) => self.cmp_timestamp(*v as i64 * 1000),
+            Field::TimestampMicros(v) => self.cmp_timestamp(*v as i64),
+            Field::Decimal(v) => value::format_decimal(v)
+                .parse::<f64>()
+                .ok()
+                .and_then(|v| self.cmp_number(v)),
+            _ => None,
+        }
+    }
+}
+
+/// Literals of an `IN` set, indexed by every type they may be compared with,
+/// so a value matches the same literals an `=` comparison would.
+#[derive(Clone, Debug, Default)]
+pub struct OperandSet {
+    texts: HashSet<String>,
+    ints: HashSet<i128>,
+    booleans: HashSet<bool>,
+
+    // Bits of the literals which are numbers but not integers, eg: `3.0` or `1.5`
+    numbers: HashSet<u64>,
+
+    // Microseconds from the unix epoch of the date and time literals
+    timestamps: HashSet<i64>,
+}
+
+/// Bits of a number as a set key, zero and negative zero being the same.
+#[inline]
+fn number_key(number: f64) -> u64 {
+    if number == 0.0 {
+        0
+    } else {
+        number.to_bits()
+    }
+}
+
+impl OperandSet {
+    pub fn new(values: HashSet<String>) -> Self {
+        let mut set = Self::default();
+
+        for text in values {
+            let operand = Operand::new(&text);
+
+            match (operand.int, operand.number) {
+                (Some(int), _) => {
+                    set.ints.insert(int);
+                }
+                (None, Some(number)) => {
+                    set.numbers.insert(number_key(number));
+                }
+                (None, None) => {}
+            }
+
+            set.booleans.extend(operand.boolean);
+            set.timestamps.extend(operand.timestamp);
+            set.texts.insert(text);
+        }
+
+        set
+    }
+
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    #[inline]
+    fn contains_int(&self, value: i128) -> bool {
+        self.ints.contains(&value) || self.numbers.contains(&number_key(value as f64))
+    }
+
+    #[inline]
+    fn contains_number(&self, value: f64) -> bool {
+        self.numbers.contains(&number_key(value))
+            || (value.fract() == 0.0 && self.ints.contains(&(value as i128)))
+    }
+
+    /// Whether a literal equals the value, dates and times also match their text,
+    /// eg: the formatted timestamps of a subquery.
+    fn contains(&self, field: &Field) -> bool {
+        let timestamp = |micros: i64| {
+            self.timestamps.contains(&micros)
+                || self.texts.contains(field_text(field).as_ref())
+        };
+
+        match field {
+            Field::Bool(v) => self.booleans.contains(v),
+            Field::Byte(v) => self.contains_int(i128::from(*v)),
+            Field::Short(v) => self.contains_int(i128::from(*v)),
+            Field::Int(v) => self.contains_int(i128::from(*v)),
+            Field::Long(v) => self.contains_int(i128::from(*v)),
+            Field::UByte(v) => self.contains_int(i128::from(*v)),
+            Field::UShort(v) => self.contains_int(i128::from(*v)),
+            Field::UInt(v) => self.contains_int(i128::from(*v)),
+            Field::ULong(v) => self.contains_int(i128::from(*v)),
+            Field::Float(v) => self.contains_number(f64::from(*v)),
+            Field::Double(v) => self.contains_number(*v),
+            Field::Str(v) => self.texts.contains(v),
+            Field::Date(v) => timestamp(i64::from(*v) * MICROS_PER_DAY),
+            Field::TimestampMillis(v) => timestamp(*v as i64 * 1000),
+            Field::TimestampMicros(v) => timestamp(*v as i64),
+            Field::Decimal(v) => value::format_decimal(v)
+                .parse::<f64>()
+                .map(|v| self.contains_number(v))
+                .unwrap_or(false),
+            _ => self.texts.contains(field_text(field).as_ref()),
+        }
+    }
+}
+
+/// Predicate evaluated against the typed value of a cell, before it is formatted.
+#[derive(Clone, Debug)]
+pub enum Predicate {
+    // Regex matching the text of the value, strings are not quoted, eg: `name:^Ben`
+    Regex(Regex),
+
+    // Comparison with a literal using the column type, eg: `age>=30`
+    Compare(Operator, Operand),
+
+    // Matches null values only, eg: `email IS NULL` or `email:NULL`
+    Null,
+
+    // Matches every value but nulls, eg: `email IS NOT NULL` or `email:NOT_NULL`
+    NotNull,
+
+    // Value equals one of the literals, eg: the values of `IN (SELECT id FROM users)`
+    In(Arc<OperandSet>),
+}
+
+impl Predicate {
+    /// Whether the value matches, nulls never match a comparison or a regex.
+    pub fn is_match(&self, field: &Field) -> bool {
+        self.eval(field).unwrap_or(false)
+    }
+
+    /// Whether the value matches, `None` when it is unknown as SQL does,
+    /// eg: comparisons and regexes of nulls.
+    pub fn eval(&self, field: &Field) -> Option<bool> {
+        match (self, field) {
+            (Predicate::Null, _) => Some(matches!(field, Field::Null)),
+            (Predicate::NotNull, _) => Some(!matches!(field, Field::Null)),
+            (_, Field::Null) => None,
+            (Predicate::Regex(regex), _) => Some(regex.is_match(&field_text(field))),
+            (Predicate::Compare(op, operand), _) => Some(
+                operand
+                    .cmp_field(field)
+                    .map(|ordering| op.matches(ordering))
+                    .unwrap_or(false),
+            ),
+            (Predicate::In(values), _) => Some(values.contains(field)),
+        }
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Predicate::Regex(regex) => write!(f, "{}", regex),
+            Predicate::Compare(op, operand) => {
+                write!(f, "{}{}", op.symbol(), operand.text)
+            }
+            Predicate::Null => write!(f, " IS NULL"),
+            Predicate::NotNull => write!(f, " IS NOT NULL"),
+            Predicate::In(values) => write!(f, " IN ({} values)", values.len()),
+        }
+    }
+}
+
+/// Filter expression over the columns of a row, columns are names until they are
+/// bound to the fields of a file.
+#[derive(Clone, Debug)]
+pub enum Expr<C = String> {
+    // Predicate on the value of a column
+    Column(C, Predicate),
+
+    // Matches when the expression does not
+    Not(Box<Expr<C>>),
+
+    // Matches when every expression matches
+    And(Vec<Expr<C>>),
+
+    // Matches when any expression matches
+    Or(Vec<Expr<C>>),
+}
+
+impl<C> Expr<C> {
+    /// Whether the row matches, `value` returns the field of a column.
+    pub fn is_match<'a, F>(&self, value: &F) -> bool
+    where
+        F: Fn(&C) -> &'a Field,
+    {
+        self.eval(value).unwrap_or(false)
+    }
+
+    /// Whether the row matches using three-valued logic, `None` when it is unknown,
+    /// so `NOT` of a comparison with a null does not match either.
+    pub fn eval<'a, F>(&self, value: &F) -> Option<bool>
+    where
+        F: Fn(&C) -> &'a Field,
+    {
+        match self {
+            Expr::Column(column, predicate) => predicate.eval(value(column)),
+            Expr::Not(expr) => expr.eval(value).map(|b| !b),
+            Expr::And(exprs) => {
+                let mut result = Some(true);
+
+                for expr in exprs {
+                    match expr.eval(value) {
+                        Some(false) => return Some(false),
+                        Some(true) => {}
+                        None => result = None,
+                    }
+                }
+
+                result
+            }
+            Expr::Or(exprs) => {
+                let mut result = Some(false);
+
+                for expr in exprs {
+                    match expr.eval(value) {
+                        Some(true) => return Some(true),
+                        Some(false) => {}
+                        None => result = None,
+                    }
+                }
+
+                result
+            }
+        }
+    }
+
+    /// Same expression with each column replaced.
+    pub fn map_columns<D, F>(&self, f: &mut F) -> Expr<D>
+    where
+        F: FnMut(&C) -> D,
+    {
+        match self {
+            Expr::Column(column, predicate) => Expr::Column(f(column), predicate.clone()),
+            Expr::Not(expr) => Expr::Not(Box::new(expr.map_columns(f))),
+            Expr::And(exprs) => {
+                Expr::And(exprs.iter().map(|e| e.map_columns(f)).collect())
+            }
+            Expr::Or(exprs) => Expr::Or(exprs.iter().map(|e| e.map_columns(f)).collect()),
+        }
+    }
+
+    /// Same expression with the predicates of the columns `value` has a field for
+    /// replaced by their result, eg: the partition columns of a file.
+    ///
+    /// Matching predicates become an empty `And`, the others an empty `Or`,
+    /// unknown ones are kept, eg: a comparison with a null partition value.
+    pub fn resolve<'a, F>(&self, value: &F) -> Expr<C>
+    where
+        C: Clone,
+        F: Fn(&C) -> Option<&'a Field>,
+    {
+        match self {
+            Expr::Column(column, predicate) => {
+                match value(column).map(|f| predicate.eval(f)) {
+                    Some(Some(true)) => Expr::And(vec![]),
+                    Some(Some(false)) => Expr::Or(vec![]),
+                    _ => Expr::Column(column.clone(), predicate.clone()),
+                }
+            }
+            Expr::Not(expr) => Expr::Not(Box::new(expr.resolve(value))),
+            Expr::And(exprs) => {
+                Expr::And(exprs.iter().map(|e| e.resolve(value)).collect())
+            }
+            Expr::Or(exprs) => Expr::Or(exprs.iter().map(|e| e.resolve(value)).collect()),
+        }
+    }
+
+    /// Result of the expression when it does not depend on the remaining column predicates,
+    /// eg: once resolved by `resolve`, `None` when a row could match or not.
+    pub fn constant(&self) -> Option<bool> {
+        match self {
+            Expr::Column(_, _) => None,
+            Expr::Not(expr) => expr.constant().map(|b| !b),
+            Expr::And(exprs) => {
+                let results = exprs.iter().map(Expr::constant).collect::<Vec<_>>();
+
+                if results.contains(&Some(false)) {
+                    Some(false)
+                } else {
+                    Some(true).filter(|_| results.iter().all(Option::is_some))
+                }
+            }
+            Expr::Or(exprs) => {
+                let results = exprs.iter().map(Expr::constant).collect::<Vec<_>>();
+
+                if results.contains(&Some(true)) {
+                    Some(true)
+                } else {
+                    Some(false).filter(|_| results.iter().all(Option::is_some))
+                }
+            }
+        }
+    }
+
+    /// Column predicates every matching row satisfies, eg: the terms of a top level `And`.
+    pub fn conjunction(&self) -> Vec<(&C, &Predicate)> {
+        match self {
+            Expr::Column(column, predicate) => vec![(column, predicate)],
+            Expr::And(exprs) => exprs.iter().flat_map(Expr::conjunction).collect(),
+            Expr::Not(_) | Expr::Or(_) => vec![],
+        }
+    }
+
+    /// Regex predicates of the columns a matching row may satisfy, negated ones excluded.
+    pub fn regexes(&self) -> Vec<(&C, &Regex)> {
+        match self {
+            Expr::Column(column, Predicate::Regex(regex)) => vec![(column, regex)],
+            Expr::Column(_, _) | Expr::Not(_) => vec![],
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                exprs.iter().flat_map(Expr::regexes).collect()
+            }
+        }
+    }
+}
+
+impl<C: fmt::Display> fmt::Display for Expr<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter, exprs: &[Expr<C>], sep| {
+            for (i, expr) in exprs.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", sep)?;
+                }
+
+                match expr {
+                    Expr::And(_) | Expr::Or(_) => write!(f, "({})", expr)?,
+                    _ => write!(f, "{}", expr)?,
+                }
+            }
+
+            Ok(())
+        };
+
+        match self {
+            Expr::Column(column, Predicate::Regex(regex)) => {
+                write!(f, "{}:{}", column, regex)
+            }
+            Expr::Column(column, predicate) => write!(f, "{}{}", column, predicate),
+            Expr::Not(expr) => match expr.as_ref() {
+                Expr::And(_) | Expr::Or(_) => write!(f, "NOT ({})", expr),
+                _ => write!(f, "NOT {}", expr),
+            },
+            Expr::And(exprs) => join(f, exprs, "AND"),
+            Expr::Or(exprs) => join(f, exprs, "OR"),
+        }
+    }
+}
+
+/// Text a regex is matched against, the formatted value without quotes for strings.
+#[inline]
+pub fn field_text(field: &Field) -> Cow<'_, str> {
+    match field {
+        Field::Str(v) => Cow::Borrowed(v.as_str()),
+        _ => Cow::Owned(value::format_field(field)),
+    }
+}
+
+/// Regex of a `<column>:<regex>` filter matching null values only.
+const NULL_KEYWORD: &str = "NULL";
+
+/// Regex of a `<column>:<regex>` filter matching every value but nulls.
+const NOT_NULL_KEYWORD: &str = "NOT_NULL";
+
+/// Parse a `<column>:<regex>` or a `<column><op><value>` filter expression,
+/// where `<op>` is one of `=`, `!=`, `<`, `<=`, `>` or `>=`.
+///
+/// `<column>:NULL` and `<column>:NOT_NULL` match nulls and every other value.
+pub fn parse(expression: &str) -> Result<(String, Predicate)> {
+    let invalid = || Error::InvalidArgument(expression.to_string());
+    let index = expression
+        .find([':', '=', '!', '<', '>'])
+        .filter(|i| *i > 0)
+        .ok_or_else(invalid)?;
+    let (column, rest) = expression.split_at(index);
+
+    if let Some(regex) = rest.strip_prefix(':') {
+        match regex {
+            "" => return Err(invalid()),
+            NULL_KEYWORD => return Ok((column.to_string(), Predicate::Null)),
+            NOT_NULL_KEYWORD => return Ok((column.to_string(), Predicate::NotNull)),
+            _ => {}
+        }
+
+        return Ok((column.to_string(), Predicate::Regex(Regex::new(regex)?)));
+    }
+
+    let (symbol, op) = OPERATORS
+        .iter()
+        .find(|(s, _)| rest.starts_with(s))
+        .ok_or_else(invalid)?;
+    let operand = Operand::new(&rest[symbol.len()..]);
+
+    Ok((column.to_string(), Predicate::Compare(*op, operand)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::data_type::ByteArray;
+
+    fn predicate(expression: &str) -> Predicate {
+        parse(expression).unwrap().1
+    }
+
+    #[test]
+    fn test_filter_parse() {
+        let (column, regex) = parse("foo:^ns::[a-z]*$").unwrap();
+
+        assert_eq!(column, "foo");
+        assert_eq!(regex.to_string(), "^ns::[a-z]*$");
+
+        let (column, compare) = parse("age>=30").unwrap();
+
+        assert_eq!(column, "age");
+        assert!(matches!(compare, Predicate::Compare(Operator::Ge, _)));
+        assert_eq!(compare.to_string(), ">=30");
+
+        assert!(matches!(
+            predicate("a!=1"),
+            Predicate::Compare(Operator::Ne, _)
+        ));
+        assert!(matches!(
+            predicate("a<1"),
+            Predicate::Compare(Operator::Lt, _)
+        ));
+        assert!(matches!(
+            predicate("a=b:c"),
+            Predicate::Compare(Operator::Eq, _)
+        ));
+        assert!(parse("foo").is_err());
+        assert!(parse(":foo").is_err());
+        assert!(parse("foo:").is_err());
+        assert!(parse("foo!1").is_err());
+        assert!(parse("foo:[").is_err());
+    }
+
+    #[test]
+    fn test_filter_expr() {
+        let column = |e: &str| {
+            let (column, predicate) = parse(e).unwrap();
+
+            Expr::Column(column, predicate)
+        };
+        let expr = Expr::And(vec![
+            column("a>1"),
+            column("a<5"),
+            Expr::Not(Box::new(column("b:^x"))),
+            Expr::Or(vec![column("c=1"), column("d=1")]),
+        ]);
+        let matches = |a, b: &str, c, d| {
+            let fields = [
+                Field::Int(a),
+                Field::Str(b.to_string()),
+                Field::Int(c),
+                Field::Int(d),
+            ];
+            let names = ["a", "b", "c", "d"];
+
+            expr.is_match(&|name: &String| {
+                &fields[names.iter().position(|n| n == name).unwrap()]
+            })
+        };
+
+        assert!(matches(2, "y", 1, 0));
+        assert!(matches(4, "y", 0, 1));
+        assert!(!matches(5, "y", 1, 1));
+        assert!(!matches(2, "xy", 1, 1));
+        assert!(!matches(2, "y", 0, 0));
+
+        assert_eq!(
+            expr.to_string(),
+            "a>1 AND a<5 AND NOT b:^x AND (c=1 OR d=1)"
+        );
+        assert_eq!(
+            expr.conjunction()
+                .iter()
+                .map(|(c, p)| format!("{}{}", c, p))
+                .collect::<Vec<_>>(),
+            vec!["a>1", "a<5"]
+        );
+        assert_eq!(
+            expr.map_columns(&mut |c: &String| c.len()).to_string(),
+            "1>1 AND 1<5 AND NOT 1:^x AND (1=1 OR 1=1)"
+        );
+    }
+
+    #[test]
+    fn test_filter_expr_nulls() {
+        let column = |e: &str| {
+            let (column, predicate) = parse(e).unwrap();
+
+            Expr::Column(column, predicate)
+        };
+        let not = |e: &str| Expr::Not(Box::new(column(e)));
+        let eval = |expr: Expr| expr.eval(&|_: &String| &Field::Null);
+
+        assert_eq!(eval(column("a>1")), None);
+        assert_eq!(eval(not("a>1")), None);
+        assert_eq!(eval(not("a:^x")), None);
+        assert_eq!(eval(not("a:NULL")), Some(false));
+        assert_eq!(eval(Expr::And(vec![not("a>1"), column("b:NULL")])), None);
+        assert_eq!(
+            eval(Expr::And(vec![not("a>1"), column("b:NOT_NULL")])),
+            Some(false)
+        );
+        assert_eq!(
+            eval(Expr::Or(vec![not("a>1"), column("b:NULL")])),
+            Some(true)
+        );
+        assert_eq!(eval(Expr::Or(vec![not("a>1"), column("b:NOT_NULL")])), None);
+        assert!(!not("a>1").is_match(&|_: &String| &Field::Null));
+        assert!(not("country=BR")
+            .resolve(&|_: &String| Some(&Field::Null))
+            .constant()
+            .is_none());
+    }
+
+    #[test]
+    fn test_filter_expr_resolve() {
+        let column = |e: &str| {
+            let (column, predicate) = parse(e).unwrap();
+
+            Expr::Column(column, predicate)
+        };
+        let expr = Expr::Or(vec![
+            Expr::And(vec![column("date:^2024-05"), column("a>1")]),
+            Expr::Not(Box::new(column("country=BR"))),
+        ]);
+        let partitions = [
+            ("date", Field::Str(String::from("2024-05-01"))),
+            ("country", Field::Str(String::from("BR"))),
+        ];
+        let resolved = expr.resolve(&|name: &String| {
+            partitions.iter().find(|p| p.0 == name).map(|p| &p.1)
+        });
+        let matches = |a| resolved.is_match(&|_: &String| &a);
+        let only_date = |date: &str| {
+            let date = Field::Str(date.to_string());
+
+            expr.resolve(&|name: &String| Some(&date).filter(|_| name == "date"))
+                .constant()
+        };
+
+        assert!(matches(Field::Int(2)));
+        assert!(!matches(Field::Int(1)));
+        assert_eq!(resolved.constant(), None);
+        assert_eq!(only_date("2024-06-01"), None);
+        assert_eq!(column("a>1").resolve(&|_: &String| None).constant(), None);
+        assert_eq!(
+            Expr::And(vec![column("date:^2024-05")])
+                .resolve(&|_: &String| partitions.first().map(|p| &p.1))
+                .constant(),
+            Some(true)
+        );
+        assert_eq!(
+            Expr::And(vec![column("date:^2024-06"), column("a>1")])
+                .resolve(&|name: &String| {
+                    partitions.iter().find(|p| p.0 == name).map(|p| &p.1)
+                })
+                .constant(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_filter_expr_regexes() {
+        let column = |e: &str| {
+            let (column, predicate) = parse(e).unwrap();
+
+            Expr::Column(column, predicate)
+        };
+        let expr = Expr::And(vec![
+            column("a:^x"),
+            column("a>1"),
+            Expr::Not(Box::new(column("b:^y"))),
+            Expr::Or(vec![column("c:z$"), column("d=1")]),
+        ]);
+
+        assert_eq!(
+            expr.regexes()
+                .iter()
+                .map(|(c, r)| format!("{}:{}", c, r))
+                .collect::<Vec<_>>(),
+            vec!["a:^x", "c:z$"]
+        );
+    }
+
+    #[test]
+    fn test_filter_regex_unquoted_strings() {
+        let odd = Field::Str(String::from("odd 1"));
+
+        assert!(predicate("a:^odd 1$").is_match(&odd));
+        assert!(!predicate("a:^\"odd 1\"$").is_match(&odd));
+        assert!(predicate("a:^12$").is_match(&Field::Int(12)));
+        assert!(!predicate("a:^null$").is_match(&Field::Null));
+        assert!(predicate("a:^false$").is_match(&Field::Bool(false)));
+    }
+
+    #[test]
+    fn test_filter_in_typed_values() {
+        let set = |values: &[&str]| {
+            Predicate::In(Arc::new(OperandSet::new(
+                values.iter().map(|v| v.to_string()).collect(),
+            )))
+        };
+
+        assert!(set(&["3"]).is_match(&Field::Double(3.0)));
+        assert!(set(&["3"]).is_match(&Field::Float(3.0)));
+        assert!(set(&["-0"]).is_match(&Field::Double(0.0)));
+        assert!(!set(&["3"]).is_match(&Field::Double(3.5)));
+        assert!(set(&["3.0", "4"]).is_match(&Field::Int(3)));
+        assert!(set(&["3.0", "4"]).is_match(&Field::Long(4)));
+        assert!(!set(&["3.5"]).is_match(&Field::Int(3)));
+        assert!(set(&["1.5"]).is_match(&Field::Double(1.5)));
+        assert!(set(&["true"]).is_match(&Field::Bool(true)));
+        assert!(set(&["2020-01-01"]).is_match(&Field::TimestampMillis(1_577_836_800_000)));
+        assert!(set(&["2020-01-01"]).is_match(&Field::Date(18262)));
+        assert!(set(&["Ben"]).is_match(&Field::Str(String::from("Ben"))));
+        assert!(!set(&["3"]).is_match(&Field::Str(String::from("3.0"))));
+        assert!(!set(&["3"]).is_match(&Field::Null));
+    }
+
+    #[test]
+    fn test_filter_compare_numbers() {
+        assert!(predicate("a>=30").is_match(&Field::Int(30)));
+        assert!(!predicate("a>30").is_match(&Field::Int(30)));
+        assert!(predicate("a<30").is_match(&Field::Long(-5)));
+        assert!(predicate("a=18446744073709551615").is_match(&Field::ULong(u64::MAX)));
+        assert!(predicate("a>1.5").is_match(&Field::Int(2)));
+        assert!(predicate("a=2").is_match(&Field::Double(2.0)));
+        assert!(predicate("a<=1.5").is_match(&Field::Float(1.5)));
+        assert!(!predicate("a!=2").is_match(&Field::Double(2.0)));
+        assert!(!predicate("a>x").is_match(&Field::Int(2)));
+        assert!(!predicate("a!=1").is_match(&Field::Null));
+        assert!(Predicate::Null.is_match(&Field::Null));
+        assert!(!Predicate::Null.is_match(&Field::Int(0)));
+        assert!(predicate("a:NULL").is_match(&Field::Null));
+        assert!(!predicate("a:NULL").is_match(&Field::Str(String::from("NULL"))));
+        assert!(predicate("a:NOT_NULL").is_match(&Field::Str(String::from("null"))));
+        assert!(!predicate("a:NOT_NULL").is_match(&Field::Null));
+        assert_eq!(predicate("a:NOT_NULL").to_string(), " IS NOT NULL");
+    }
+
+    #[test]
+    fn test_filter_compare_strings_and_dates() {
+        let ts = NaiveDate::from_ymd(2020, 1, 2)
+            .and_hms(10, 0, 0)
+            .timestamp();
+
+        assert!(predicate("a=odd").is_match(&Field::Str(String::from("odd"))));
+        assert!(predicate("a>b").is_match(&Field::Str(String::from("c"))));
+        assert!(predicate("a=true").is_match(&Field::Bool(true)));
+        assert!(predicate("a>=2020-01-02")
+            .is_match(&Field::TimestampMillis(ts as u64 * 1000)));
+        assert!(predicate("a<2020-01-02 10:00:01")
+            .is_match(&Field::TimestampMillis(ts as u64 * 1000)));
+        assert!(predicate("a=2020-01-02T10:00:00")
+            .is_match(&Field::TimestampMicros(ts as u64 * 1_000_000)));
+        assert!(predicate("a=1970-01-02").is_match(&Field::Date(1)));
+        assert!(!predicate("a=1").is_match(&Field::Bytes(ByteArray::from(vec![1]))));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::reader;
+use crate::value::{Value, ValueFormat};
+
+/// Named set of flattened columns, eg: `address=addr_*` for the `addr_city`
+/// and `addr_zip` columns written by exporters without nested types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnGroup {
+    pub name: String,
+    pub patterns: Vec<String>,
+}
+
+impl ColumnGroup {
+    /// Parse a `name=pattern,pattern` definition.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (name, patterns) = value.split_once('=')?;
+        let name = name.trim();
+        let patterns = patterns
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        if name.is_empty() || patterns.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            name: name.to_string(),
+            patterns,
+        })
+    }
+
+    /// Key of a column in the group object, globs drop the text before their first
+    /// wildcard, eg: `addr_city` is `city` in `addr_*`.
+    ///
+    /// Returns `None` when the column is not part of the group.
+    fn key(&self, column: &str) -> Option<String> {
+        let pattern = self
+            .patterns
+            .iter()
+            .find(|p| reader::matches_column(p, column))?;
+        let prefix = match pattern.find(['*', '?']) {
+            Some(i) if !pattern.starts_with('/') => &pattern[..i],
+            _ => "",
+        };
+
+        match column.get(prefix.len()..) {
+            Some(key) if !key.is_empty() && !prefix.is_empty() => Some(key.to_string()),
+            _ => Some(column.to_string()),
+        }
+    }
+}
+
+/// Output column of a nesting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Nested {
+    Column(usize),
+    Group(Vec<(String, usize)>),
+}
+
+/// Rows with the columns of each group replaced by a single json object column,
+/// placed where the first column of the group was.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Nesting {
+    headers: Vec<String>,
+    columns: Vec<Nested>,
+}
+
+impl Nesting {
+    /// Nest the columns of the groups, a column is only part of the first group matching it
+    /// and groups without columns are ignored.
+    pub fn new(headers: &[String], groups: &[ColumnGroup]) -> Self {
+        let mut result = Self {
+            headers: Vec::with_capacity(headers.len()),
+            columns: Vec::with_capacity(headers.len()),
+        };
+        let mut positions = vec![None; groups.len()];
+
+        for (index, header) in headers.iter().enumerate() {
+            let member = groups
+                .iter()
+                .enumerate()
+                .find_map(|(i, g)| g.key(header).map(|key| (i, key)));
+
+            let (group, key) = match member {
+                Some(member) => member,
+                None => {
+                    result.headers.push(header.to_string());
+                    result.columns.push(Nested::Column(index));
+                    continue;
+                }
+            };
+
+            let position = *positions[group].get_or_insert_with(|| {
+                result.headers.push(groups[group].name.to_string());
+                result.columns.push(Nested::Group(Vec::new()));
+                result.columns.len() - 1
+            });
+
+            if let Nested::Group(members) = &mut result.columns[position] {
+                members.push((key, index));
+            }
+        }
+
+        result
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Row of the nested columns, group values are json objects of the formatted values.
+    pub fn apply(&self, mut row: Vec<Value>, format: &ValueFormat) -> Vec<Value> {
+        let mut take = |index: usize| {
+            row.get_mut(index)
+                .map(|v| std::mem::replace(v, Value::Null))
+                .unwrap_or(Value::Null)
+        };
+
+        self.columns
+            .iter()
+            .map(|column| match column {
+                Nested::Column(index) => take(*index),
+                Nested::Group(members) => {
+                    let object = members
+                        .iter()
+                        .map(|(key, index)| {
+                            (key.to_string(), take(*index).to_json_value(format))
+                        })
+                        .collect::<serde_json::Map<_, _>>();
+
+                    Value::Nested(serde_json::Value::Object(object).to_string())
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_group_parse() {
+        assert_eq!(
+            ColumnGroup::parse("address = addr_*, zip"),
+            Some(ColumnGroup {
+                name: String::from("address"),
+                patterns: strings(&["addr_*", "zip"]),
+            })
+        );
+        assert_eq!(ColumnGroup::parse("address"), None);
+        assert_eq!(ColumnGroup::parse("address="), None);
+        assert_eq!(ColumnGroup::parse("=addr_*"), None);
+    }
+
+    #[test]
+    fn test_group_nesting() {
+        let headers = strings(&["id", "addr_city", "name", "addr_zip", "tag_a"]);
+        let groups = [
+            ColumnGroup::parse("address=addr_*").unwrap(),
+            ColumnGroup::parse("tags=/^tag_/").unwrap(),
+            ColumnGroup::parse("missing=other_*").unwrap(),
+        ];
+        let nesting = Nesting::new(&headers, &groups);
+        let row = vec![
+            Value::Int(1),
+            Value::Str(String::from("Lisbon")),
+            Value::Str(String::from("Ana")),
+            Value::Null,
+            Value::Bool(true),
+        ];
+
+        assert_eq!(
+            nesting.headers(),
+            strings(&["id", "address", "name", "tags"])
+        );
+        assert_eq!(
+            nesting.apply(row, &ValueFormat::default()),
+            vec![
+                Value::Int(1),
+                Value::Nested(String::from(r#"{"city":"Lisbon","zip":null}"#)),
+                Value::Str(String::from("Ana")),
+                Value::Nested(String::from(r#"{"tag_a":true}"#)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use crate::api::{Error, Result};
+use parquet::record::Field;
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+/// Row content hash algorithm.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RowHash {
+    // SHA-256 digest
+    Sha256,
+
+    // CRC-32 checksum, faster but not collision resistant
+    Crc32,
+}
+
+const ROW_HASH_VALUES: &[&str] = &["sha256", "crc32"];
+
+impl RowHash {
+    pub fn values() -> &'static [&'static str] {
+        ROW_HASH_VALUES
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RowHash::Sha256 => "sha256",
+            RowHash::Crc32 => "crc32",
+        }
+    }
+
+    /// Hex encoded hash of the row values.
+    ///
+    /// Values are hashed as a json array, so rows hash the same
+    /// regardless of the export format.
+    pub fn hex(&self, row: &[Field]) -> Result<String> {
+        let values = row.iter().map(Field::to_json_value).collect::<Vec<_>>();
+        let bytes = serde_json::to_vec(&values)?;
+
+        match self {
+            RowHash::Sha256 => Ok(to_hex(&sha256(&bytes))),
+            RowHash::Crc32 => Ok(format!("{:08x}", crc32fast::hash(&bytes))),
+        }
+    }
+}
+
+impl TryFrom<&str> for RowHash {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "sha256" => Ok(RowHash::Sha256),
+            "crc32" => Ok(RowHash::Crc32),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// First 16 bytes of the SHA-256 digest of the formatted values of a row,
+/// hashed as a json array of strings.
+///
+/// Small enough to keep the digest of every row of a dataset in memory.
+pub fn digest<T: AsRef<str>>(row: &[T]) -> Result<u128> {
+    let values = row.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    let bytes = serde_json::to_vec(&values)?;
+    let mut head = [0; 16];
+
+    head.copy_from_slice(&sha256(&bytes)[..16]);
+
+    Ok(u128::from_be_bytes(head))
+}
+
+/// Lowercase hex encoding of the bytes.
+#[inline]
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, b| {
+            let _ = write!(hex, "{:02x}", b);
+            hex
+        })
+}
+
+const SHA256_INIT: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+    0x5be0cd19,
+];
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+    0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+    0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+    0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+    0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+    0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+    0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+    0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+    0xc67178f2,
+];
+
+/// Process a single 64 bytes block.
+#[inline]
+fn sha256_block(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+
+    for i in 16..64 {
+        let s0 =
+            w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let mut h = *state;
+
+    for i in 0..64 {
+        let s1 = h[4].rotate_right(6) ^ h[4].rotate_right(11) ^ h[4].rotate_right(25);
+        let ch = (h[4] & h[5]) ^ (!h[4] & h[6]);
+        let t1 = h[7]
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA256_K[i])
+            .wrapping_add(w[i]);
+        let s0 = h[0].rotate_right(2) ^ h[0].rotate_right(13) ^ h[0].rotate_right(22);
+        let maj = (h[0] & h[1]) ^ (h[0] & h[2]) ^ (h[1] & h[2]);
+        let t2 = s0.wrapping_add(maj);
+
+        h = [
+            t1.wrapping_add(t2),
+            h[0],
+            h[1],
+            h[2],
+            h[3].wrapping_add(t1),
+            h[4],
+            h[5],
+            h[6],
+        ];
+    }
+
+    for (s, v) in state.iter_mut().zip(h.iter()) {
+        *s = s.wrapping_add(*v);
+    }
+}
+
+/// SHA-256 digest of the given bytes.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state = SHA256_INIT;
+    let mut tail = data.chunks_exact(64);
+
+    for block in &mut tail {
+        sha256_block(&mut state, block);
+    }
+
+    // pad with a single 1 bit, zeros and the message length in bits
+    let remainder = tail.remainder();
+    let mut last = [0u8; 128];
+    let len = if remainder.len() < 56 { 64 } else { 128 };
+
+    last[..remainder.len()].copy_from_slice(remainder);
+    last[remainder.len()] = 0x80;
+    last[len - 8..len].copy_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in last[..len].chunks_exact(64) {
+        sha256_block(&mut state, block);
+    }
+
+    let mut digest = [0u8; 32];
+
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(state.iter()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_vectors() {
+        let long = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+        assert_eq!(
+            to_hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            to_hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            to_hex(&sha256(long.as_bytes())),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            to_hex(&sha256(&[b'a'; 1000])),
+            "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+        );
+    }
+
+    #[test]
+    fn test_row_hash() {
+        let row = vec![Field::Int(1), Field::Str(String::from("a")), Field::Null];
+        let other = vec![Field::Int(1), Field::Str(String::from("a,")), Field::Null];
+
+        // sha256 of `[1,"a",null]`
+        assert_eq!(
+            RowHash::Sha256.hex(&row).unwrap(),
+            to_hex(&sha256(b"[1,\"a\",null]"))
+        );
+        assert_eq!(RowHash::Crc32.hex(&row).unwrap().len(), 8);
+        assert_ne!(
+            RowHash::Sha256.hex(&row).unwrap(),
+            RowHash::Sha256.hex(&other).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_row_digest() {
+        let row = ["1", "\"a\"", "null"];
+
+        // sha256 of `["1","\"a\"","null"]`
+        assert_eq!(
+            format!("{:032x}", digest(&row).unwrap()),
+            to_hex(&sha256(br#"["1","\"a\"","null"]"#)[..16])
+        );
+        assert_ne!(digest(&row).unwrap(), digest(&row[..2]).unwrap());
+    }
+
+    #[test]
+    fn test_row_hash_try_from() {
+        assert_eq!(RowHash::try_from("SHA256"), Ok(RowHash::Sha256));
+        assert_eq!(RowHash::try_from("crc32"), Ok(RowHash::Crc32));
+        assert!(RowHash::try_from("md5").is_err());
+    }
+}
@@ -0,0 +1,416 @@
+use crate::config;
+use bytes::Bytes;
+use parquet::errors::{ParquetError, Result};
+use parquet::file::reader::{ChunkReader, Length};
+use std::io::Cursor;
+use std::path::Path;
+use std::process::Command;
+use std::result;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+
+#[cfg(not(windows))]
+const NULL_DEVICE: &str = "/dev/null";
+
+/// Whether the path is a http(s) url.
+pub fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .map(|s| s.starts_with("http://") || s.starts_with("https://"))
+        .unwrap_or(false)
+}
+
+/// Longest wait between two attempts of a request.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How failed requests are retried, waiting `backoff` before the first retry
+/// and doubling the wait after each one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    // Number of retries after the first attempt
+    pub retries: u32,
+
+    // Wait before the first retry
+    pub backoff: Duration,
+
+    // Max duration of each request
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff: Duration::from_millis(250),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the given retry, starting at 1.
+    fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+
+        self.backoff.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+
+    /// Run the request until it succeeds, fails with an error that is not transient
+    /// or the retries are exhausted, the final error names the request and the number of attempts.
+    fn run<T, F>(&self, request: &str, mut f: F) -> Result<T>
+    where
+        F: FnMut() -> result::Result<T, RequestError>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match f() {
+                Ok(value) => return Ok(value),
+                Err(e) if !e.transient || attempt > self.retries => {
+                    return Err(ParquetError::General(format!(
+                        "{} failed after {} attempts: {}",
+                        request, attempt, e.message
+                    )));
+                }
+                Err(_) => thread::sleep(self.delay(attempt)),
+            }
+
+            attempt += 1;
+        }
+    }
+}
+
+/// Failed request, `transient` when a retry may succeed, eg: a timeout or a 503 response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RequestError {
+    message: String,
+    transient: bool,
+}
+
+/// Status of the error response reported by `curl --fail`,
+/// eg: `curl: (22) The requested URL returned error: 503`.
+#[inline]
+fn error_status(stderr: &str) -> Option<u16> {
+    let (_, status) = stderr.split_once("returned error: ")?;
+
+    status.split_whitespace().next()?.parse().ok()
+}
+
+/// Whether a failed `curl` run may succeed when retried: server error responses,
+/// timeouts and connection errors, see the exit codes of `man curl`.
+#[inline]
+fn is_transient(code: Option<i32>, stderr: &str) -> bool {
+    match code {
+        Some(22) => error_status(stderr).is_none_or(|status| status >= 500),
+        Some(5 | 6 | 7 | 18 | 28 | 35 | 52 | 55 | 56) => true,
+        _ => false,
+    }
+}
+
+/// Total length from the last `Content-Range` header of a response,
+/// redirects write one header block per response.
+#[inline]
+fn content_range_length(headers: &str) -> Option<u64> {
+    headers
+        .lines()
+        .filter_map(|l| l.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("content-range"))
+        .filter_map(|(_, value)| value.rsplit('/').next())
+        .filter_map(|length| length.trim().parse::<u64>().ok())
+        .next_back()
+}
+
+/// Remote parquet file, each chunk is fetched with a range request.
+///
+/// Requests are made using `curl` which handles tls, proxies and redirects.
+pub struct HttpFile {
+    url: String,
+    len: u64,
+    retry: RetryPolicy,
+}
+
+impl HttpFile {
+    /// Fetch the file length, fails when the server does not accept range requests.
+    ///
+    /// Requests are retried following the `http.*` keys of the user config.
+    pub fn open(url: &str) -> Result<Self> {
+        let retry = config::get().map(|c| c.retry()).unwrap_or_default();
+
+        Self::open_with(url, retry)
+    }
+
+    /// Fetch the file length, retrying the requests with the given policy.
+    pub fn open_with(url: &str, retry: RetryPolicy) -> Result<Self> {
+        let request = format!("HEAD {}", url);
+        let headers = retry.run(&request, || {
+            curl(
+                url,
+                &["--range", "0-0", "--dump-header", "-"],
+                NULL_DEVICE,
+                retry.timeout,
+            )
+        })?;
+        let headers = String::from_utf8_lossy(&headers);
+        let len = content_range_length(&headers).ok_or_else(|| {
+            ParquetError::General(format!("{} does not accept range requests", url))
+        })?;
+
+        Ok(Self {
+            url: url.to_string(),
+            len,
+            retry,
+        })
+    }
+
+    fn fetch(&self, start: u64, length: usize) -> Result<Bytes> {
+        if length == 0 {
+            return Ok(Bytes::new());
+        }
+
+        let end = start + length as u64 - 1;
+
+        if end >= self.len {
+            return Err(ParquetError::EOF(format!(
+                "Range {}-{} is out of bounds for {} bytes",
+                start, end, self.len
+            )));
+        }
+
+        let range = format!("{}-{}", start, end);
+        let request = format!("Range {} of {}", range, self.url);
+        let body = self.retry.run(&request, || {
+            let body = curl(&self.url, &["--range", &range], "-", self.retry.timeout)?;
+
+            // truncated responses are retried
+            if body.len() != length {
+                return Err(RequestError {
+                    message: format!("Expected {} bytes, got {}", length, body.len()),
+                    transient: true,
+                });
+            }
+
+            Ok(body)
+        })?;
+
+        Ok(Bytes::from(body))
+    }
+}
+
+/// Run `curl` returning its standard output, the request fails after `timeout`.
+#[inline]
+fn curl(
+    url: &str,
+    args: &[&str],
+    output: &str,
+    timeout: Duration,
+) -> result::Result<Vec<u8>, RequestError> {
+    let max_time = format!("{:.3}", timeout.as_secs_f64());
+    let result = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", &max_time])
+        .args(args)
+        .args(["--output", output, "--", url])
+        .output()
+        .map_err(|e| RequestError {
+            message: format!("Unable to run curl: {}", e),
+            transient: false,
+        })?;
+
+    if !result.status.success() {
+        let message = String::from_utf8_lossy(&result.stderr).trim().to_string();
+        let transient = is_transient(result.status.code(), &message);
+
+        return Err(RequestError { message, transient });
+    }
+
+    Ok(result.stdout)
+}
+
+impl Length for HttpFile {
+    fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+impl ChunkReader for HttpFile {
+    type T = Cursor<Bytes>;
+
+    fn get_read(&self, start: u64, length: usize) -> Result<Self::T> {
+        self.fetch(start, length).map(Cursor::new)
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> Result<Bytes> {
+        self.fetch(start, length)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serve the given bytes over http, answering range requests only.
+    pub fn serve_bytes(data: Vec<u8>) -> String {
+        serve_flaky(data, 0)
+    }
+
+    /// Serve the given bytes over http, failing the first `failures` requests.
+    pub fn serve_flaky(data: Vec<u8>, failures: usize) -> String {
+        serve_failing(data, failures, "503 Service Unavailable")
+    }
+
+    /// Serve the given bytes over http, answering the first `failures` requests with `status`.
+    pub fn serve_failing(data: Vec<u8>, failures: usize, status: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for (i, stream) in listener.incoming().filter_map(|s| s.ok()).enumerate() {
+                let reader = BufReader::new(&stream);
+                let mut range = None;
+
+                for line in reader.lines().map_while(|l| l.ok()) {
+                    if line.is_empty() {
+                        break;
+                    }
+
+                    if let Some(value) = line.strip_prefix("Range: bytes=") {
+                        range = value.split_once('-').map(|(s, e)| {
+                            (s.parse::<usize>().unwrap(), e.parse::<usize>().unwrap())
+                        });
+                    }
+                }
+
+                let mut stream = &stream;
+                let response = match range {
+                    _ if i < failures => format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).into_bytes(),
+                    Some((start, end)) => {
+                        let body = &data[start..=end.min(data.len() - 1)];
+                        let headers = format!(
+                            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            start,
+                            start + body.len() - 1,
+                            data.len(),
+                            body.len()
+                        );
+
+                        [headers.as_bytes(), body].concat()
+                    }
+                    None => b"HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+
+                stream.write_all(&response).unwrap();
+            }
+        });
+
+        format!("http://{}/data.parquet", address)
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url(Path::new("http://example.com/data.parquet")));
+        assert!(is_url(Path::new("https://example.com/data.parquet")));
+        assert!(!is_url(Path::new("/tmp/data.parquet")));
+        assert!(!is_url(Path::new("-")));
+    }
+
+    #[test]
+    fn test_content_range_length() {
+        let headers = [
+            "HTTP/1.1 302 Found",
+            "Location: /b",
+            "",
+            "HTTP/1.1 206 Partial Content",
+            "content-range: bytes 0-0/1234",
+            "",
+        ]
+        .join("\r\n");
+
+        assert_eq!(content_range_length(&headers), Some(1234));
+        assert_eq!(content_range_length("HTTP/1.1 200 OK\r\n"), None);
+    }
+
+    #[test]
+    fn test_http_file_range_requests() {
+        let url = serve_bytes((0..100).collect());
+        let file = HttpFile::open(&url).unwrap();
+
+        assert_eq!(file.len(), 100);
+        assert_eq!(file.get_bytes(10, 3).unwrap().to_vec(), vec![10, 11, 12]);
+        assert_eq!(file.get_bytes(98, 2).unwrap().to_vec(), vec![98, 99]);
+        assert!(file.get_bytes(99, 2).is_err());
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let retry = RetryPolicy {
+            retries: 10,
+            backoff: Duration::from_millis(100),
+            timeout: Duration::from_secs(1),
+        };
+
+        assert_eq!(retry.delay(1), Duration::from_millis(100));
+        assert_eq!(retry.delay(3), Duration::from_millis(400));
+        assert_eq!(retry.delay(20), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_http_file_retries() {
+        let retry = |retries| RetryPolicy {
+            retries,
+            backoff: Duration::from_millis(1),
+            timeout: Duration::from_secs(5),
+        };
+
+        let url = serve_flaky((0..100).collect(), 2);
+        let file = HttpFile::open_with(&url, retry(2)).unwrap();
+
+        assert_eq!(file.get_bytes(10, 3).unwrap().to_vec(), vec![10, 11, 12]);
+
+        let url = serve_flaky((0..100).collect(), 1);
+        let file = HttpFile::open_with(&url, retry(1)).unwrap();
+        let url = serve_flaky((0..100).collect(), usize::MAX);
+        let flaky = HttpFile {
+            url: url.clone(),
+            ..file
+        };
+        let message = flaky.get_bytes(10, 3).unwrap_err().to_string();
+
+        assert!(message.starts_with(&format!(
+            "Parquet error: Range 10-12 of {} failed after 2 attempts: ",
+            url
+        )));
+        assert!(HttpFile::open_with(&url, retry(0)).is_err());
+
+        // client errors are not retried
+        let url = serve_failing((0..100).collect(), usize::MAX, "404 Not Found");
+        let message = HttpFile::open_with(&url, retry(3))
+            .err()
+            .unwrap()
+            .to_string();
+
+        assert!(message.starts_with(&format!(
+            "Parquet error: HEAD {} failed after 1 attempts: ",
+            url
+        )));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let status = |s| format!("curl: (22) The requested URL returned error: {}", s);
+
+        assert_eq!(error_status(&status("503")), Some(503));
+        assert_eq!(error_status(&status("404 Not Found")), Some(404));
+        assert_eq!(error_status("curl: (28) Operation timed out"), None);
+        assert!(is_transient(Some(22), &status("503")));
+        assert!(!is_transient(Some(22), &status("404")));
+        assert!(is_transient(Some(28), "curl: (28) Operation timed out"));
+        assert!(is_transient(Some(7), "curl: (7) Failed to connect"));
+        assert!(!is_transient(Some(3), "curl: (3) URL malformed"));
+        assert!(!is_transient(None, ""));
+    }
+}
@@ -0,0 +1,90 @@
+//! Simple library for analyzing parquet files, used by the `xpq` command line tool.
+//!
+//! [`reader::ParquetFile`] reads a single file, a directory or a http(s) url of parquet
+//! files, selecting and filtering columns. Rows can be written as tables, vertical
+//! records or csv using [`output::OutputWriter`] :
+//!
+//! ```no_run
+//! use xpq::filter::{self, Expr};
+//! use xpq::output::{OutputFormat, OutputWriter};
+//! use xpq::reader::ParquetFile;
+//!
+//! let (column, predicate) = filter::parse("status=failed")?;
+//! let parquet = ParquetFile::new("orders/".into())
+//!     .with_fields(Some(vec![String::from("id"), String::from("amount")]))
+//!     .with_filter(Some(Expr::Column(column, predicate)));
+//!
+//! let headers = parquet.field_names()?;
+//! let mut writer = OutputWriter::new(headers, parquet.values().take(10))
+//!     .format(OutputFormat::Csv);
+//!
+//! writer.write(&mut std::io::stdout())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Every command of the binary is available in [`command`], each one exposing its clap
+//! definition and a `run` function writing to any [`std::io::Write`].
+
+/// Error and result types.
+pub mod api;
+
+/// Schema hashes and row counts of directory files, cached by size and modification time.
+pub mod cache;
+
+/// Column descriptions and owners, from the footer metadata or a yaml file.
+pub mod catalog;
+
+/// Command definitions and runners.
+pub mod command;
+
+/// User configuration, eg: column presets and aliases.
+pub mod config;
+
+/// Typed row filters, eg: `name:^Ben` or `age>=30`.
+pub mod filter;
+
+/// Column groups rendering flattened columns as json objects, eg: `address=addr_*`.
+pub mod group;
+
+/// Row content hashes.
+pub mod hash;
+
+/// Remote parquet files read with range requests.
+pub mod http;
+
+/// Table, vertical and csv writers.
+pub mod output;
+
+/// Pager of the outputs taller than the terminal.
+pub mod pager;
+
+/// Progress of long scans drawn on stderr.
+pub mod progress;
+
+/// SQL-like `WHERE` clauses and `SELECT` statements, eg: `age > 30 AND country = 'BR'`.
+pub mod query;
+
+/// Parquet file and directory reader.
+pub mod reader;
+
+/// Row group pruning of point lookups using statistics and column indexes,
+/// bloom filters are not read and pages are not skipped within a row group.
+pub mod row_group_pruning;
+
+/// Copies of the files replaced in a dataset directory, restored by `undo`.
+pub mod snapshot;
+
+/// Row sorting with bounded memory, using a top-k heap or an external merge sort.
+pub mod sort;
+
+/// SQLite database files of a single table, written without the sqlite library.
+pub mod sqlite;
+
+/// Run summary of the rows, files and row groups read, written by `--stats-output`.
+pub mod summary;
+
+/// Timestamp rendering with timezones, strftime formats or unix seconds.
+pub mod time;
+
+/// Typed cell values.
+pub mod value;
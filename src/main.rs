@@ -1,40 +1,355 @@
-use clap::{App, AppSettings, ArgMatches};
+use clap::{App, AppSettings, Arg, ArgMatches, ColorChoice};
+use std::convert::TryFrom;
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Instant;
+use xpq::cache::ResultCache;
+use xpq::output::{self, Color, OutputFile, OutputFormat};
+use xpq::pager::{self, Pager, PagerMode};
+use xpq::reader::ParquetFile;
+use xpq::{api, command, config, progress, reader, summary};
 
-mod api;
-mod command;
-mod output;
-mod reader;
+/// Stdout buffer size, tabular and vertical outputs are already buffered in batches.
+fn buffer_capacity(matches: &ArgMatches) -> usize {
+    let format = matches
+        .subcommand()
+        .and_then(|t| t.1.try_get_raw("format").ok().flatten())
+        .and_then(|mut v| v.next())
+        .and_then(|v| v.to_str())
+        .map(String::from)
+        .map(OutputFormat::try_from);
 
-fn run(matches: ArgMatches) -> api::Result<()> {
-    let out = &mut std::io::stdout();
+    match format {
+        Some(Ok(OutputFormat::Tabular)) | Some(Ok(OutputFormat::Vertical)) => 8 * 1024,
+        _ => 64 * 1024,
+    }
+}
+
+/// Color choice of the help, following the `NO_COLOR` and `CLICOLOR` conventions.
+fn color_choice() -> ColorChoice {
+    match Color::from_env() {
+        Color::Always => ColorChoice::Always,
+        Color::Never => ColorChoice::Never,
+        Color::Auto => ColorChoice::Auto,
+    }
+}
+
+/// First value of an arg of the command, `None` when the command does not take it.
+fn arg_value<'a>(matches: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    matches
+        .subcommand()
+        .and_then(|t| t.1.try_get_raw(name).ok().flatten())
+        .and_then(|mut v| v.next())
+        .and_then(|v| v.to_str())
+}
+
+/// Expand `xpq <alias> <args>..` into the saved command line followed by the given args.
+///
+/// Built-in commands take precedence over aliases.
+fn expand_alias(app: &App, args: Vec<OsString>) -> api::Result<Vec<OsString>> {
+    let name = match args.get(1).and_then(|a| a.to_str()) {
+        Some(name) if !name.starts_with('-') && app.find_subcommand(name).is_none() => {
+            name
+        }
+        _ => return Ok(args),
+    };
+
+    let line = match config::get()?.alias(name) {
+        Some(line) => line,
+        None => return Ok(args),
+    };
+
+    let mut result = vec![args[0].clone()];
+
+    result.extend(config::split_args(line).into_iter().map(OsString::from));
+    result.extend(args.into_iter().skip(2));
+
+    Ok(result)
+}
+
+/// Summary of the files without rows of a parquet directory, eg: header-only part files.
+///
+/// Commands treat those files as valid and skip them, the note explains missing output.
+fn empty_files_note(matches: &ArgMatches) -> Option<String> {
+    let path = matches
+        .subcommand()
+        .and_then(|t| t.1.try_get_raw("path").ok().flatten())
+        .and_then(|mut v| v.next())
+        .map(Path::new)
+        .filter(|p| p.is_dir())?;
+    let parquet = ParquetFile::from(path).with_discovery(command::discovery(matches));
+
+    match parquet.empty_files().len() {
+        0 => None,
+        num => Some(format!(
+            "note: {} of {} files have no rows",
+            num,
+            parquet.files().count()
+        )),
+    }
+}
+
+/// Commands whose output is kept by `--cache-dir`, their results are usually small.
+const CACHED_COMMANDS: &[&str] = &["count", "frequency", "stats", "nulls", "tokens"];
 
-    match matches.subcommand() {
-        Some(("read", args)) => command::read::run(args, out),
-        Some(("schema", args)) => command::schema::run(args, out),
-        Some(("sample", args)) => command::sample::run(args, out),
-        Some(("count", args)) => command::count::run(args, out),
-        Some(("frequency", args)) => command::frequency::run(args, out),
-        _ => Ok(()),
+/// Arguments of the cached commands reading or writing files on every run.
+const UNCACHED_ARGS: &[&str] = &["sketch-out", "sketch-merge", "state"];
+
+/// Settings the output of a cached command depends on besides its arguments.
+fn cache_settings() -> String {
+    let config = config::get().map(|c| c.lines()).unwrap_or_default();
+
+    format!("{:?}\n{}", Color::from_env(), config.join("\n"))
+}
+
+/// Run the command, using the `--cache-dir` output when the input files did not change.
+///
+/// Outputs written to a terminal are not cached, they follow its width and colors.
+fn run_cached<W: Write>(
+    matches: &ArgMatches,
+    args: &[OsString],
+    terminal: bool,
+    out: &mut W,
+) -> api::Result<()> {
+    let paths = matches
+        .subcommand()
+        .filter(|t| CACHED_COMMANDS.contains(&t.0))
+        .filter(|t| {
+            !UNCACHED_ARGS
+                .iter()
+                .any(|a| t.1.try_contains_id(a).unwrap_or(false))
+        })
+        .filter(|_| !terminal)
+        .and_then(|t| t.1.values_of("path"))
+        .map(|v| v.collect::<Vec<_>>())
+        .filter(|v| !v.contains(&reader::STDIN_PATH));
+    let cache =
+        arg_value(matches, "cache-dir").map(|dir| ResultCache::new(PathBuf::from(dir)));
+    let (cache, key) = match (cache, paths) {
+        (Some(cache), Some(paths)) => {
+            let discovery = command::discovery(matches);
+
+            match ResultCache::key(args, &cache_settings(), &paths, &discovery) {
+                Some(key) => (cache, key),
+                None => return command::run(matches, out),
+            }
+        }
+        _ => return command::run(matches, out),
+    };
+
+    if let Some(output) = cache.get(&key) {
+        return Ok(out.write_all(&output)?);
+    }
+
+    let mut output = Vec::new();
+    let result = command::run(matches, &mut output);
+
+    out.write_all(&output)?;
+
+    if result.is_ok() {
+        if let Err(e) = cache.put(&key, &output) {
+            let note = format!("note: output not cached, {}", e);
+
+            summary::warn(&note);
+            eprintln!("{}", note);
+        }
     }
+
+    result
+}
+
+/// Height of the terminal when outputs taller than it are paged,
+/// `read --peek` pages the rows itself.
+fn pager_height(matches: &ArgMatches) -> Option<usize> {
+    if output_file(matches).is_some() {
+        return None;
+    }
+
+    if let Some(("read", args)) = matches.subcommand() {
+        if args.is_present("peek") {
+            return None;
+        }
+    }
+
+    match matches.value_of("pager").map(PagerMode::try_from) {
+        Some(Ok(PagerMode::Never)) => None,
+        _ => output::terminal_height(),
+    }
+}
+
+/// File of the global `--output`, commands with an `--output` of their own handle it.
+fn output_file(matches: &ArgMatches) -> Option<&Path> {
+    let (name, _) = matches.subcommand()?;
+    let own = command::defs()
+        .iter()
+        .filter(|c| c.get_name() == name)
+        .any(|c| c.get_arguments().any(|a| a.get_id() == "output"));
+
+    if own {
+        return None;
+    }
+
+    matches.value_of("output").map(Path::new)
+}
+
+fn write<W: Write>(
+    matches: &ArgMatches,
+    args: &[OsString],
+    terminal: bool,
+    out: W,
+) -> api::Result<()> {
+    let out = progress::LineWriter::new(out, terminal);
+    let out = &mut BufWriter::with_capacity(buffer_capacity(matches), out);
+
+    let result = run_cached(matches, args, terminal, out);
+
+    // flush what was written before reporting errors, eg: `schema-diff` changes
+    out.flush()?;
+
+    result
+}
+
+fn run(matches: ArgMatches, args: &[OsString]) -> api::Result<()> {
+    let stdout = io::stdout();
+    let terminal = stdout.is_terminal();
+
+    let result = match (output_file(&matches), pager_height(&matches)) {
+        (Some(path), _) => {
+            let mut file = OutputFile::create(path)?;
+            let result = write(&matches, args, false, &mut file);
+
+            progress::stop();
+
+            // failed commands leave the file as it was, unless they report findings
+            match result {
+                Err(e) if !e.is_finding() => Err(e),
+                result => file.finish().and(result),
+            }
+        }
+        (None, Some(height)) => {
+            let mut pager = Pager::new(stdout.lock(), height, pager::pager_command());
+            let result = write(&matches, args, terminal, &mut pager);
+
+            progress::stop();
+            // notes and errors are reported once the pager exits
+            pager.finish()?;
+
+            result
+        }
+        (None, None) => {
+            let result = write(&matches, args, terminal, stdout.lock());
+
+            progress::stop();
+
+            result
+        }
+    };
+
+    if let (Ok(()), Some(note)) = (&result, empty_files_note(&matches)) {
+        summary::warn(&note);
+        eprintln!("{}", note);
+    }
+
+    result
 }
 
 fn main() {
     let app = App::new(env!("CARGO_PKG_NAME"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about(env!("CARGO_PKG_DESCRIPTION"))
+        .after_help("Paths can be http(s) urls, read with range requests made by curl which must be installed.")
         .version(env!("CARGO_PKG_VERSION"))
         .setting(AppSettings::ArgRequiredElseHelp)
-        .subcommands(vec![
-            command::read::def(),
-            command::count::def(),
-            command::schema::def(),
-            command::sample::def(),
-            command::frequency::def(),
-        ]);
-
-    if let Err(e) = run(app.get_matches()) {
-        eprintln!("{}", e);
-        process::exit(1);
+        .color(color_choice())
+        .arg(
+            Arg::with_name("pager")
+                .help("Page outputs taller than the terminal through $PAGER or less -S")
+                .possible_values(PagerMode::values())
+                .takes_value(true)
+                .long("pager")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("progress")
+                .help("Show a progress bar of the scanned files on stderr")
+                .conflicts_with("no-progress")
+                .long("progress")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("no-progress")
+                .help("Do not show a progress bar, shown by default when stderr is a terminal")
+                .long("no-progress")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .help("Write the output to a file, replaced once written and compressed when ending with .gz")
+                .takes_value(true)
+                .long("output")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("stats-output")
+                .help("Write a json summary of the run: rows, files, row groups, duration and warnings")
+                .takes_value(true)
+                .long("stats-output")
+                .global(true),
+        )
+        .subcommands(command::defs());
+
+    let args = match expand_alias(&app, env::args_os().collect()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let matches = match app.try_get_matches_from(args.clone()) {
+        Ok(matches) => matches,
+        Err(e) => e.exit(),
+    };
+
+    if matches.is_present("progress")
+        || (!matches.is_present("no-progress") && io::stderr().is_terminal())
+    {
+        progress::enable();
+    }
+
+    let started = Instant::now();
+    let stats_output = matches.value_of("stats-output").map(PathBuf::from);
+    let command = matches.subcommand_name().map(String::from);
+
+    if stats_output.is_some() {
+        summary::enable();
+    }
+
+    let result = run(matches, &args);
+
+    // the summary is written even when the command fails
+    let result = match stats_output {
+        Some(path) => {
+            let error = result.as_ref().err();
+            let written =
+                summary::write(&path, command.as_deref(), started.elapsed(), error);
+
+            result.and(written)
+        }
+        None => result,
+    };
+
+    reader::remove_stdin();
+
+    match result {
+        // the reader went away (eg: `xpq read | head`), nothing else to write
+        Ok(()) | Err(api::Error::BrokenPipe) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
     }
 }
@@ -1,11 +1,25 @@
 use crate::api::{Error, Result};
+use crate::summary;
+use crate::value::{Value, ValueFormat};
+use arrow::array::{ArrayRef, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::Regex;
 use std::cmp;
 use std::convert::TryFrom;
 use std::fmt::Write as _;
-use std::io::Write;
+use std::io::IsTerminal;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::str;
+use std::sync::Arc;
 use tabwriter::TabWriter;
-use unicode_width::UnicodeWidthStr;
+use tempfile::NamedTempFile;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[inline]
 fn format_cell(value: &str, width: usize) -> String {
@@ -33,19 +47,74 @@ fn format_cell(value: &str, width: usize) -> String {
     result_trim
 }
 
+/// Split a cell into lines of at most `width` columns.
+#[inline]
+fn wrap_cell(value: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![String::new()];
+    let mut length = 0;
+
+    for c in value.chars() {
+        let size = UnicodeWidthChar::width(c).unwrap_or(0);
+
+        if length + size > width && length > 0 {
+            lines.push(String::new());
+            length = 0;
+        }
+
+        if let Some(line) = lines.last_mut() {
+            line.push(c);
+        }
+
+        length += size;
+    }
+
+    lines
+}
+
+/// Lines of a cell limited to `width`, cells are padded to the width when given.
+#[inline]
+fn fit_cell(value: &str, width: Option<usize>, overflow: Overflow) -> Vec<String> {
+    let width = match width {
+        Some(width) => width,
+        None => return vec![value.to_string()],
+    };
+
+    if UnicodeWidthStr::width(value) <= width {
+        return vec![format_cell(value, width)];
+    }
+
+    match overflow {
+        Overflow::Truncate => vec![format_cell(value, width)],
+        Overflow::Full => vec![value.to_string()],
+        Overflow::Wrap => wrap_cell(value, width)
+            .iter()
+            .map(|l| format_cell(l, width))
+            .collect(),
+    }
+}
+
+/// Width of a column limited to the max column width.
+#[inline]
+fn cell_width(width: usize, layout: &Layout) -> usize {
+    layout.max_col_width.map_or(width, |max| width.min(max))
+}
+
+/// Cells of a row joined by tabs, `measure` rows update the column widths
+/// and are written as they are, other rows are fit to the widths.
 #[inline]
 fn format_row(
-    index: usize,
-    batch_size: usize,
+    measure: bool,
     cells: &[String],
     width: &mut [usize],
+    layout: &Layout,
 ) -> Vec<u8> {
-    let mut row = cells
+    let lines = cells
         .iter()
         .enumerate()
         .map(|e| {
             // collect max width for first x rows
-            if index < batch_size {
+            if measure {
                 if width.len() > e.0 {
                     let len = UnicodeWidthStr::width(e.1.as_str());
                     let max = cmp::max(len, width[e.0]);
@@ -53,34 +122,310 @@ fn format_row(
                     width[e.0] = max;
                 }
 
-                return e.1.to_owned();
+                // cells of the first rows are only cut when over the max width
+                return match layout.max_col_width {
+                    Some(max) if UnicodeWidthStr::width(e.1.as_str()) > max => {
+                        fit_cell(e.1, Some(max), layout.overflow)
+                    }
+                    _ => vec![e.1.to_owned()],
+                };
             }
 
-            format_cell(e.1, width[e.0])
+            fit_cell(e.1, Some(cell_width(width[e.0], layout)), layout.overflow)
         })
-        .collect::<Vec<_>>()
-        .join("\t");
-
-    row.push('\n');
+        .collect::<Vec<_>>();
+
+    let height = lines.iter().map(Vec::len).max().unwrap_or(1);
+    let mut row = String::new();
+
+    // wrapped cells continue on the next lines, next to blank cells
+    for i in 0..height {
+        let line = lines
+            .iter()
+            .enumerate()
+            .map(|(c, cell)| match cell.get(i) {
+                Some(line) => line.to_owned(),
+                None if measure => String::new(),
+                None => format_cell("", cell_width(width[c], layout)),
+            })
+            .collect::<Vec<_>>();
+
+        row.push_str(&line.join("\t"));
+        row.push('\n');
+    }
 
     row.into_bytes()
 }
 
+/// Marker of the elided middle columns.
+const ELIDED_MARKER: &str = "...";
+
+/// Padding added by the tab writer between columns.
+const COLUMN_PADDING: usize = 2;
+
+/// Index of the columns to show given their widths, `None` marks the elided columns.
+///
+/// Columns are kept from both ends of the table, the first half getting
+/// the extra column when the number of visible columns is odd.
+fn visible_columns(widths: &[usize], layout: &Layout) -> Vec<Option<usize>> {
+    let num = widths.len();
+    let max = layout.max_columns.unwrap_or(num).max(1).min(num);
+    let pick = |k: usize| {
+        let left = (0..k.div_ceil(2)).map(Some);
+        let right = (num - k / 2..num).map(Some);
+
+        if k < num {
+            left.chain(Some(None)).chain(right).collect()
+        } else {
+            left.chain(right).collect::<Vec<_>>()
+        }
+    };
+
+    let fits = |columns: &Vec<Option<usize>>| match layout.width {
+        None => true,
+        Some(width) => {
+            let total = columns
+                .iter()
+                .map(|c| c.map(|i| widths[i]).unwrap_or(ELIDED_MARKER.len()))
+                .map(|w| w + COLUMN_PADDING)
+                .sum::<usize>();
+
+            total <= width + COLUMN_PADDING
+        }
+    };
+
+    (1..=max)
+        .rev()
+        .map(pick)
+        .find(fits)
+        .unwrap_or_else(|| pick(1))
+}
+
+#[inline]
+fn elide(cells: Vec<String>, columns: &[Option<usize>]) -> Vec<String> {
+    if columns.len() == cells.len() && columns.iter().all(Option::is_some) {
+        return cells;
+    }
+
+    columns
+        .iter()
+        .map(|c| match c {
+            Some(i) => cells.get(*i).cloned().unwrap_or_default(),
+            None => ELIDED_MARKER.to_string(),
+        })
+        .collect()
+}
+
+/// ANSI escape codes of the colored output.
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const NUMBER: &str = "\x1b[36m";
+const MATCH: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// Style of a cell of a colored row.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Paint {
+    // Column name
+    Header,
+
+    // Value of the field at the index, right aligned when a number and `align` is set
+    Value { field: usize, align: bool },
+}
+
+/// Wrap the parts of the text matching any of the regexes, strings are matched
+/// without their quotes the same way filters match them.
+fn highlight(text: &str, regexes: &[&Regex]) -> String {
+    let quoted = text.len() > 1 && text.starts_with('"') && text.ends_with('"');
+    let (start, end) = if quoted {
+        (1, text.len() - 1)
+    } else {
+        (0, text.len())
+    };
+    let inner = &text[start..end];
+    let mut matches = regexes
+        .iter()
+        .flat_map(|r| r.find_iter(inner).map(|m| (m.start(), m.end())))
+        .filter(|m| m.0 < m.1)
+        .collect::<Vec<_>>();
+    let mut result = String::from(&text[..start]);
+    let mut last = 0;
+
+    matches.sort_unstable();
+
+    for (from, to) in matches {
+        let from = from.max(last);
+
+        if from >= to {
+            continue;
+        }
+
+        result.push_str(&inner[last..from]);
+        result.push_str(MATCH);
+        result.push_str(&inner[from..to]);
+        result.push_str(RESET);
+        last = to;
+    }
+
+    result.push_str(&inner[last..]);
+    result.push_str(&text[end..]);
+
+    result
+}
+
+#[inline]
+fn paint_cell(cell: &str, paint: Paint, config: &OutputConfig) -> String {
+    let text = cell.trim_end_matches(' ');
+    let padding = &cell[text.len()..];
+    let is_number = || text.parse::<f64>().is_ok();
+
+    if text.is_empty() {
+        return cell.to_string();
+    }
+
+    match paint {
+        Paint::Header => format!("{}{}{}{}", BOLD, text, RESET, padding),
+        Paint::Value { .. } if text == config.values.null_display() => {
+            format!("{}{}{}{}", DIM, text, RESET, padding)
+        }
+        Paint::Value { align: true, .. } if is_number() => {
+            format!("{}{}{}{}", padding, NUMBER, text, RESET)
+        }
+        Paint::Value { .. } if is_number() => {
+            format!("{}{}{}{}", NUMBER, text, RESET, padding)
+        }
+        Paint::Value { field, .. } => {
+            let regexes = config
+                .highlights
+                .iter()
+                .filter(|h| h.0 == field)
+                .map(|h| &h.1)
+                .collect::<Vec<_>>();
+
+            if regexes.is_empty() {
+                return cell.to_string();
+            }
+
+            format!("{}{}", highlight(text, &regexes), padding)
+        }
+    }
+}
+
+/// Color the cells of a formatted row, `paint` gives the style of each column.
+fn paint_row<F>(row: Vec<u8>, config: &OutputConfig, paint: F) -> Vec<u8>
+where
+    F: Fn(usize) -> Paint,
+{
+    if !config.color {
+        return row;
+    }
+
+    let text = String::from_utf8_lossy(&row);
+    let mut result = String::with_capacity(text.len() + 32);
+
+    for line in text.lines() {
+        let cells = line
+            .split('\t')
+            .enumerate()
+            .map(|(i, cell)| paint_cell(cell, paint(i), config))
+            .collect::<Vec<_>>();
+
+        result.push_str(&cells.join("\t"));
+        result.push('\n');
+    }
+
+    result.into_bytes()
+}
+
 fn write_tabular<W: Write>(
     values: &mut dyn Iterator<Item = Result<Vec<String>>>,
     config: &OutputConfig,
     headers: &[String],
     out: &mut W,
 ) -> Result<()> {
+    if config.layout.max_columns.is_none() && config.layout.width.is_none() {
+        return write_table(values, config, headers, out);
+    }
+
+    // measure the first batch to find out which columns fit
+    let batch = values.take(config.measured_rows()).collect::<Vec<_>>();
+    let mut widths = headers
+        .iter()
+        .map(|h| cmp::max(config.minwidth, UnicodeWidthStr::width(h.as_str())))
+        .collect::<Vec<_>>();
+
+    for row in batch.iter().flatten() {
+        for (i, cell) in row.iter().enumerate().take(widths.len()) {
+            let width = cell_width(UnicodeWidthStr::width(cell.as_str()), &config.layout);
+
+            widths[i] = cmp::max(widths[i], width);
+        }
+    }
+
+    let columns = visible_columns(&widths, &config.layout);
+    let headers = elide(headers.to_vec(), &columns);
+    let mut rows = batch
+        .into_iter()
+        .chain(values)
+        .map(|r| r.map(|vec| elide(vec, &columns)));
+
+    // highlights follow their fields to the visible columns
+    let highlights = columns
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| c.map(|c| (i, c)))
+        .flat_map(|(i, c)| {
+            config
+                .highlights
+                .iter()
+                .filter(move |h| h.0 == c)
+                .map(move |h| (i, h.1.clone()))
+        })
+        .collect();
+    let config = OutputConfig {
+        highlights,
+        ..config.clone()
+    };
+
+    write_table(&mut rows, &config, &headers, out)
+}
+
+fn write_table<W: Write>(
+    values: &mut dyn Iterator<Item = Result<Vec<String>>>,
+    config: &OutputConfig,
+    headers: &[String],
+    out: &mut W,
+) -> Result<()> {
+    let measured = config.measured_rows();
     let mut width = vec![config.minwidth; headers.len()];
     let mut writer = TabWriter::new(out).minwidth(config.minwidth);
 
-    writer.write_all(&format_row(0, config.batch_size, headers, &mut width))?;
+    // colored numbers are right aligned, so the first rows are measured before writing
+    let batch = if config.color {
+        values.take(measured).collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    let measure = |i: usize| !config.color && i < measured;
+
+    for cells in std::iter::once(headers).chain(batch.iter().flatten().map(Vec::as_slice))
+    {
+        for (w, cell) in width.iter_mut().zip(cells) {
+            *w = cmp::max(*w, UnicodeWidthStr::width(cell.as_str()));
+        }
+    }
+
+    let header = format_row(measure(0), headers, &mut width, &config.layout);
 
-    for (i, vec) in values.enumerate() {
-        writer.write_all(&format_row(i, config.batch_size, &vec?, &mut width))?;
+    writer.write_all(&paint_row(header, config, |_| Paint::Header))?;
 
-        if i > 0 && i % config.batch_size == 0 {
+    for (i, vec) in batch.into_iter().chain(values).enumerate() {
+        let row = format_row(measure(i), &vec?, &mut width, &config.layout);
+        let paint = |field| Paint::Value { field, align: true };
+
+        writer.write_all(&paint_row(row, config, paint))?;
+
+        if i > 0 && i % measured == 0 {
             writer.flush()?;
         }
     }
@@ -96,6 +441,7 @@ fn write_vertical<W: Write>(
     headers: &[String],
     out: &mut W,
 ) -> Result<()> {
+    let measured = config.measured_rows();
     let mut width = vec![config.minwidth; headers.len()];
     let mut writer = TabWriter::new(out).minwidth(config.minwidth);
 
@@ -105,87 +451,775 @@ fn write_vertical<W: Write>(
         for (h, cell) in row?.into_iter().enumerate() {
             let header = headers[h].to_string();
             let vec = vec![format!("{}:", header), cell];
+            let row = format_row(i < measured, &vec, &mut width, &config.layout);
+            let paint = |c| match c {
+                0 => Paint::Header,
+                _ => Paint::Value {
+                    field: h,
+                    align: false,
+                },
+            };
+
+            writer.write_all(&paint_row(row, config, paint))?;
+
+            if i > 0 && i % measured == 0 {
+                writer.flush()?;
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn write_csv<W: Write>(
+    values: &mut dyn Iterator<Item = Result<Vec<String>>>,
+    config: &OutputConfig,
+    headers: &[String],
+    out: &mut W,
+) -> Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .quote_style(csv::QuoteStyle::Never)
+        .delimiter(b',')
+        .from_writer(out);
+
+    writer.write_record(headers)?;
+
+    for (i, vec) in values.enumerate() {
+        writer.write_record(&vec?)?;
+
+        if i > 0 && i % config.batch_size == 0 {
+            writer.flush()?;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Escape a markdown table cell, pipes would end the cell and newlines the row.
+#[inline]
+fn escape_markdown(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+#[inline]
+fn markdown_row(cells: &[String]) -> String {
+    let cells = cells.iter().map(|c| escape_markdown(c)).collect::<Vec<_>>();
+
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn write_markdown<W: Write>(
+    values: &mut dyn Iterator<Item = Result<Vec<String>>>,
+    config: &OutputConfig,
+    headers: &[String],
+    out: &mut W,
+) -> Result<()> {
+    let delimiter = vec![String::from("---"); headers.len()];
+
+    out.write_all(markdown_row(headers).as_bytes())?;
+    out.write_all(format!("| {} |\n", delimiter.join(" | ")).as_bytes())?;
+
+    for (i, vec) in values.enumerate() {
+        out.write_all(markdown_row(&vec?).as_bytes())?;
+
+        if i > 0 && i % config.batch_size == 0 {
+            out.flush()?;
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Escape the html special characters of a table cell.
+#[inline]
+fn escape_html(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+#[inline]
+fn html_row(cells: &[String], tag: &str) -> String {
+    let cells = cells
+        .iter()
+        .map(|c| format!("<{0}>{1}</{0}>", tag, escape_html(c)))
+        .collect::<String>();
+
+    format!("<tr>{}</tr>\n", cells)
+}
+
+fn write_html<W: Write>(
+    values: &mut dyn Iterator<Item = Result<Vec<String>>>,
+    config: &OutputConfig,
+    headers: &[String],
+    out: &mut W,
+) -> Result<()> {
+    out.write_all(b"<table>\n<thead>\n")?;
+    out.write_all(html_row(headers, "th").as_bytes())?;
+    out.write_all(b"</thead>\n<tbody>\n")?;
+
+    for (i, vec) in values.enumerate() {
+        out.write_all(html_row(&vec?, "td").as_bytes())?;
+
+        if i > 0 && i % config.batch_size == 0 {
+            out.flush()?;
+        }
+    }
+
+    out.write_all(b"</tbody>\n</table>\n")?;
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Write the rows as an arrow IPC stream of string columns, one batch per batch size.
+fn write_arrow<W: Write>(
+    values: &mut dyn Iterator<Item = Result<Vec<String>>>,
+    config: &OutputConfig,
+    headers: &[String],
+    out: &mut W,
+) -> Result<()> {
+    let fields = headers
+        .iter()
+        .map(|h| Field::new(h, DataType::Utf8, true))
+        .collect::<Vec<_>>();
+    let schema = Arc::new(Schema::new(fields));
+    let mut writer = StreamWriter::try_new(out, &schema)?;
+
+    loop {
+        let rows = values.take(config.batch_size).collect::<Result<Vec<_>>>()?;
 
-            writer.write_all(&format_row(i, config.batch_size, &vec, &mut width))?;
+        if rows.is_empty() {
+            break;
+        }
+
+        let columns = (0..headers.len())
+            .map(|i| {
+                let array = rows
+                    .iter()
+                    .map(|r| r.get(i).map(String::as_str))
+                    .collect::<StringArray>();
+
+                Arc::new(array) as ArrayRef
+            })
+            .collect::<Vec<_>>();
+
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Output foramt.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    // Tabular format
+    Tabular,
+
+    // Vertical format
+    Vertical,
+
+    // CSV format
+    Csv,
+
+    // Markdown pipe table
+    Markdown,
+
+    // HTML table
+    Html,
+
+    // Arrow IPC stream
+    Arrow,
+}
+
+const OUTPUT_FORMAT_VALUES: &[&str] = &[
+    "t", "table", "tabular", "v", "vertical", "c", "csv", "md", "markdown", "html",
+    "arrow",
+];
+
+impl OutputFormat {
+    pub fn values() -> &'static [&'static str] {
+        OUTPUT_FORMAT_VALUES
+    }
+}
+
+impl TryFrom<String> for OutputFormat {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "csv" | "c" => Ok(OutputFormat::Csv),
+            "vertical" | "v" => Ok(OutputFormat::Vertical),
+            "tabular" | "table" | "t" => Ok(OutputFormat::Tabular),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "html" => Ok(OutputFormat::Html),
+            "arrow" => Ok(OutputFormat::Arrow),
+            _ => Err(Error::InvalidArgument(value)),
+        }
+    }
+}
+
+/// Rendering of nested (group, list and map) values.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NestedFormat {
+    // Parquet display format
+    Default,
+
+    // Compact JSON
+    Json,
+}
+
+const NESTED_FORMAT_VALUES: &[&str] = &["default", "json"];
+
+impl NestedFormat {
+    pub fn values() -> &'static [&'static str] {
+        NESTED_FORMAT_VALUES
+    }
+}
+
+impl TryFrom<&str> for NestedFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "default" => Ok(NestedFormat::Default),
+            "json" => Ok(NestedFormat::Json),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Rendering of binary (non UTF-8 byte array) values.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum BinaryFormat {
+    // Parquet display format, a list of the bytes
+    #[default]
+    Default,
+
+    // Lowercase hex
+    Hex,
+
+    // Standard base64 with padding
+    Base64,
+
+    // Number of bytes only, eg: `<12 bytes>`
+    Size,
+}
+
+const BINARY_FORMAT_VALUES: &[&str] = &["default", "hex", "base64", "size"];
+
+impl BinaryFormat {
+    pub fn values() -> &'static [&'static str] {
+        BINARY_FORMAT_VALUES
+    }
+}
+
+impl TryFrom<&str> for BinaryFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "default" => Ok(BinaryFormat::Default),
+            "hex" => Ok(BinaryFormat::Hex),
+            "base64" => Ok(BinaryFormat::Base64),
+            "size" => Ok(BinaryFormat::Size),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Limits of the tabular output, middle columns are elided when exceeded.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Layout {
+    // Max number of columns
+    pub max_columns: Option<usize>,
+
+    // Max width of a row, usually the terminal width
+    pub width: Option<usize>,
+
+    // Max width of a cell
+    pub max_col_width: Option<usize>,
+
+    // Handling of the cells wider than their column
+    pub overflow: Overflow,
+
+    // Rows measuring the column widths
+    pub measure: Measure,
+}
+
+/// Rows measuring the column widths of tables.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Measure {
+    // Widths of the first rows, later rows are written as they are read
+    #[default]
+    Fast,
+
+    // Widths of every row, rows are kept in memory until the table is written
+    Exact,
+}
+
+const MEASURE_VALUES: &[&str] = &["fast", "exact"];
+
+impl Measure {
+    pub fn values() -> &'static [&'static str] {
+        MEASURE_VALUES
+    }
+}
+
+impl TryFrom<&str> for Measure {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "fast" => Ok(Measure::Fast),
+            "exact" => Ok(Measure::Exact),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Handling of the cells wider than their column.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    // Cut the cell, ending with `...`
+    #[default]
+    Truncate,
+
+    // Write the whole cell, breaking the alignment of the row
+    Full,
+
+    // Continue the cell on the next lines
+    Wrap,
+}
+
+/// When tables are written with colors.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Color {
+    // Colors when writing to a terminal
+    #[default]
+    Auto,
+
+    // Colors even when piped
+    Always,
+
+    // No colors
+    Never,
+}
+
+const COLOR_VALUES: &[&str] = &["auto", "always", "never"];
+
+impl Color {
+    pub fn values() -> &'static [&'static str] {
+        COLOR_VALUES
+    }
+
+    /// Colors following the `NO_COLOR` and `CLICOLOR` conventions.
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        if var("NO_COLOR").is_some() {
+            return Color::Never;
+        }
+
+        match (var("CLICOLOR_FORCE"), var("CLICOLOR")) {
+            (Some(force), _) if force != "0" => Color::Always,
+            (_, Some(clicolor)) if clicolor == "0" => Color::Never,
+            _ => Color::Auto,
+        }
+    }
+
+    /// Whether an output written to a terminal, or not, is colored.
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            Color::Always => true,
+            Color::Auto => terminal,
+            Color::Never => false,
+        }
+    }
+}
+
+impl TryFrom<&str> for Color {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "auto" => Ok(Color::Auto),
+            "always" => Ok(Color::Always),
+            "never" => Ok(Color::Never),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Width of the terminal attached to stdout, if any.
+///
+/// The `COLUMNS` environment variable takes precedence over the terminal size.
+pub fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    let columns = std::env::var("COLUMNS").ok().and_then(|v| v.parse().ok());
+
+    columns
+        .or_else(|| terminal_size().map(|s| s.0))
+        .filter(|w| *w > 0)
+}
+
+/// Number of lines of the terminal attached to stdout, if any.
+///
+/// The `LINES` environment variable takes precedence over the terminal size.
+pub fn terminal_height() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    let lines = std::env::var("LINES").ok().and_then(|v| v.parse().ok());
+
+    lines
+        .or_else(|| terminal_size().map(|s| s.1))
+        .filter(|h| *h > 0)
+}
+
+/// Columns and lines of the terminal.
+#[cfg(unix)]
+#[inline]
+fn terminal_size() -> Option<(usize, usize)> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+
+    match result {
+        0 => Some((usize::from(size.ws_col), usize::from(size.ws_row))),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+#[inline]
+fn terminal_size() -> Option<(usize, usize)> {
+    None
+}
+
+/// Largest output copied to the clipboard.
+pub const MAX_CLIPBOARD_SIZE: usize = 1024 * 1024;
+
+/// Name of the output copying to the clipboard, eg: `--output clipboard`.
+pub const CLIPBOARD: &str = "clipboard";
+
+/// Clipboard commands tried in order, the first one installed is used.
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip.exe"],
+];
+
+/// Copy the output to the platform clipboard, piping it to the first command found.
+pub fn copy_to_clipboard(output: &[u8]) -> Result<()> {
+    copy_with(CLIPBOARD_COMMANDS, output)
+}
+
+fn copy_with(commands: &[&[&str]], output: &[u8]) -> Result<()> {
+    if output.len() > MAX_CLIPBOARD_SIZE {
+        return Err(Error::InvalidArgument(format!(
+            "Output of {} bytes is too large for the clipboard, max {} bytes",
+            output.len(),
+            MAX_CLIPBOARD_SIZE
+        )));
+    }
+
+    for command in commands {
+        let child = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(output)?;
+        }
+
+        let status = child.wait()?;
+
+        if !status.success() {
+            return Err(Error::IO(format!("{} failed, {}", command[0], status)));
+        }
+
+        return Ok(());
+    }
+
+    Err(Error::IO(String::from(
+        "No clipboard command found, install wl-copy, xclip or xsel",
+    )))
+}
+
+enum FileWriter {
+    Plain(BufWriter<NamedTempFile>),
+    Gzip(GzEncoder<BufWriter<NamedTempFile>>),
+}
+
+/// File written by `--output`, the output goes to a temporary file next to it
+/// renamed over the path once finished, paths ending with `.gz` are compressed.
+///
+/// The path is left as it was when the file is dropped before being finished.
+pub struct OutputFile {
+    path: PathBuf,
+    writer: FileWriter,
+}
+
+impl OutputFile {
+    pub fn create(path: &Path) -> Result<Self> {
+        Self::staged(path, None)
+    }
+
+    /// Write the temporary file to a staging directory instead, eg: a local disk
+    /// for outputs on network mounts.
+    pub fn staged(path: &Path, staging_dir: Option<&Path>) -> Result<Self> {
+        let dir = staging_dir.unwrap_or_else(|| parent_dir(path));
+        let temp = tempfile::Builder::new().prefix(".xpq-").tempfile_in(dir)?;
+        let writer = BufWriter::new(temp);
+        let writer = match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => {
+                FileWriter::Gzip(GzEncoder::new(writer, Compression::default()))
+            }
+            _ => FileWriter::Plain(writer),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer,
+        })
+    }
+
+    /// Flush the output and move it to the path, keeping the permissions
+    /// of the file it replaces.
+    pub fn finish(self) -> Result<()> {
+        let writer = match self.writer {
+            FileWriter::Plain(w) => w,
+            FileWriter::Gzip(w) => w.finish()?,
+        };
+        let temp = writer.into_inner().map_err(|e| e.into_error())?;
+        let permissions = output_permissions(&self.path);
+
+        if let Some(permissions) = &permissions {
+            temp.as_file().set_permissions(permissions.clone())?;
+        }
+
+        match temp.persist(&self.path) {
+            Ok(_) => Ok(()),
+            // staging directories on other devices are copied next to the path first,
+            // so the path is still replaced by a rename
+            Err(e) if e.error.kind() == std::io::ErrorKind::CrossesDevices => {
+                let mut staged = e.file.reopen()?;
+                let mut temp = tempfile::Builder::new()
+                    .prefix(".xpq-")
+                    .tempfile_in(parent_dir(&self.path))?;
+
+                std::io::copy(&mut staged, &mut temp)?;
+
+                if let Some(permissions) = permissions {
+                    temp.as_file().set_permissions(permissions)?;
+                }
+
+                temp.as_file().sync_all()?;
+                temp.persist(&self.path).map_err(|e| e.error)?;
+
+                Ok(())
+            }
+            Err(e) => Err(e.error.into()),
+        }
+    }
+}
+
+/// Permissions of the file at the path, or the default of new files
+/// (temporary files are only readable by their owner).
+#[cfg(unix)]
+fn output_permissions(path: &Path) -> Option<std::fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        return Some(metadata.permissions());
+    }
+
+    // the umask can only be read by replacing it
+    let umask = unsafe {
+        let umask = libc::umask(0o022);
+
+        libc::umask(umask);
+        umask
+    };
+
+    Some(std::fs::Permissions::from_mode(0o666 & !(umask as u32)))
+}
 
-            if i > 0 && i % config.batch_size == 0 {
-                writer.flush()?;
-            }
-        }
-    }
+#[cfg(not(unix))]
+fn output_permissions(path: &Path) -> Option<std::fs::Permissions> {
+    std::fs::metadata(path).ok().map(|m| m.permissions())
+}
 
-    writer.flush()?;
+#[inline]
+fn parent_dir(path: &Path) -> &Path {
+    path.parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+}
 
-    Ok(())
+/// Advisory lock of a path being written, held by a `<path>.lock` file removed
+/// once released, so that concurrent runs fail instead of racing to replace the path.
+///
+/// Only writers taking the lock are excluded, readers are never blocked.
+/// The lock is released when its holder exits, a lock file left behind by a
+/// killed run does not exclude anybody.
+pub struct WriteLock {
+    path: PathBuf,
+    _file: std::fs::File,
 }
 
-fn write_csv<W: Write>(
-    values: &mut dyn Iterator<Item = Result<Vec<String>>>,
-    config: &OutputConfig,
-    headers: &[String],
-    out: &mut W,
-) -> Result<()> {
-    let mut writer = csv::WriterBuilder::new()
-        .quote_style(csv::QuoteStyle::Never)
-        .delimiter(b',')
-        .from_writer(out);
+impl WriteLock {
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let mut name = path.as_os_str().to_os_string();
 
-    writer.write_record(headers)?;
+        name.push(".lock");
 
-    for (i, vec) in values.enumerate() {
-        writer.write_record(&vec?)?;
+        let lock_path = PathBuf::from(name);
 
-        if i > 0 && i % config.batch_size == 0 {
-            writer.flush()?;
+        loop {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&lock_path)?;
+
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    return Err(Error::IO(format!(
+                        "{} is locked by another writer, retry once it finishes",
+                        path.display()
+                    )));
+                }
+                Err(std::fs::TryLockError::Error(e)) => return Err(e.into()),
+            }
+
+            // a lock file removed by its previous holder before being locked here
+            // does not exclude anybody, creating it again
+            if same_file(&file, &lock_path) {
+                return Ok(Self {
+                    path: lock_path,
+                    _file: file,
+                });
+            }
         }
     }
+}
 
-    writer.flush()?;
-
-    Ok(())
+impl Drop for WriteLock {
+    fn drop(&mut self) {
+        // removed while still locked, the file is unlocked once closed
+        let _ = std::fs::remove_file(&self.path);
+    }
 }
 
-/// Output foramt.
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub enum OutputFormat {
-    // Tabular format
-    Tabular,
+#[cfg(unix)]
+#[inline]
+fn same_file(file: &std::fs::File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
 
-    // Vertical format
-    Vertical,
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
 
-    // CSV format
-    Csv,
+#[cfg(not(unix))]
+#[inline]
+fn same_file(_file: &std::fs::File, path: &Path) -> bool {
+    path.exists()
 }
 
-const OUTPUT_FORMAT_VALUES: &[&str] =
-    &["t", "table", "tabular", "v", "vertical", "c", "csv"];
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.writer {
+            FileWriter::Plain(w) => w.write(buf),
+            FileWriter::Gzip(w) => w.write(buf),
+        }
+    }
 
-impl OutputFormat {
-    pub fn values() -> &'static [&'static str] {
-        OUTPUT_FORMAT_VALUES
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.writer {
+            FileWriter::Plain(w) => w.flush(),
+            FileWriter::Gzip(w) => w.flush(),
+        }
     }
 }
 
-impl TryFrom<String> for OutputFormat {
-    type Error = Error;
-
-    fn try_from(value: String) -> Result<Self> {
-        match value.to_lowercase().as_ref() {
-            "csv" | "c" => Ok(OutputFormat::Csv),
-            "vertical" | "v" => Ok(OutputFormat::Vertical),
-            "tabular" | "table" | "t" => Ok(OutputFormat::Tabular),
-            _ => Err(Error::InvalidArgument(value)),
+/// Only plain files can be seeked, eg: by the sqlite writer of `export`.
+impl Seek for OutputFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match &mut self.writer {
+            FileWriter::Plain(w) => w.seek(pos),
+            FileWriter::Gzip(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "{} is compressed, it can not be seeked",
+                    self.path.display()
+                ),
+            )),
         }
     }
 }
 
 /// Output configuration.
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct OutputConfig {
     minwidth: usize,
     batch_size: usize,
     format: OutputFormat,
+    layout: Layout,
+    color: bool,
+    values: ValueFormat,
+    highlights: Vec<(usize, Regex)>,
+}
+
+impl OutputConfig {
+    /// Number of rows measuring the column widths of tables,
+    /// every row is measured by the exact layout.
+    #[inline]
+    fn measured_rows(&self) -> usize {
+        match self.layout.measure {
+            Measure::Fast => self.batch_size,
+            Measure::Exact => usize::MAX,
+        }
+    }
 }
 
 impl Default for OutputConfig {
@@ -194,20 +1228,45 @@ impl Default for OutputConfig {
             minwidth: 4,
             batch_size: 500,
             format: OutputFormat::Tabular,
+            layout: Layout::default(),
+            color: false,
+            values: ValueFormat::default(),
+            highlights: Vec::new(),
         }
     }
 }
 
+/// Cell of the rows written by `OutputWriter`.
+pub trait Cell {
+    /// Text of the cell, values are formatted as given to `OutputWriter::values`.
+    fn format(self, format: &ValueFormat) -> String;
+}
+
+impl Cell for String {
+    fn format(self, _: &ValueFormat) -> String {
+        self
+    }
+}
+
+impl Cell for Value {
+    fn format(self, format: &ValueFormat) -> String {
+        format.format(self)
+    }
+}
+
 /// OutputWriter values and writes to a io Write.
+///
+/// Cells are formatted when written, rows can hold any `Cell`, eg: `crate::value::Value`.
 pub struct OutputWriter<T> {
     values: T,
     headers: Vec<String>,
     config: OutputConfig,
 }
 
-impl<T> OutputWriter<T>
+impl<T, C> OutputWriter<T>
 where
-    T: Iterator<Item = Result<Vec<String>>>,
+    T: Iterator<Item = Result<Vec<C>>>,
+    C: Cell,
 {
     /// Create a new `OutputWriter`
     pub fn new(headers: Vec<String>, values: T) -> Self {
@@ -227,23 +1286,87 @@ where
             headers: self.headers,
             config: OutputConfig {
                 format,
-                minwidth: self.config.minwidth,
-                batch_size: self.config.batch_size,
+                ..self.config
+            },
+        }
+    }
+
+    /// Set the limits of the tables, unlimited by default.
+    pub fn layout(self, layout: Layout) -> OutputWriter<T> {
+        Self {
+            config: OutputConfig {
+                layout,
+                ..self.config
+            },
+            ..self
+        }
+    }
+
+    /// Set when tables are colored, `Color::Auto` colors them when stdout is a terminal.
+    ///
+    /// Nothing is colored by default.
+    pub fn color(self, color: Color) -> OutputWriter<T> {
+        Self {
+            config: OutputConfig {
+                color: color.enabled(std::io::stdout().is_terminal()),
+                ..self.config
+            },
+            ..self
+        }
+    }
+
+    /// Set how values are written, eg: the text of nulls.
+    pub fn values(self, values: ValueFormat) -> OutputWriter<T> {
+        Self {
+            config: OutputConfig {
+                values,
+                ..self.config
+            },
+            ..self
+        }
+    }
+
+    /// Highlight the parts of the values of a field matching the regex,
+    /// the field is the index of its header.
+    pub fn highlight(self, highlights: Vec<(usize, Regex)>) -> OutputWriter<T> {
+        Self {
+            config: OutputConfig {
+                highlights,
+                ..self.config
             },
+            ..self
         }
     }
 
     /// Write each row to the io Write.
     pub fn write<W: Write>(&mut self, out: &mut W) -> Result<()> {
+        let format = &self.config.values;
+        let mut values = (&mut self.values)
+            .inspect(|r| {
+                if r.is_ok() {
+                    summary::add_rows_emitted(1);
+                }
+            })
+            .map(|r| r.map(|vec| vec.into_iter().map(|c| c.format(format)).collect()));
+
         match self.config.format {
             OutputFormat::Tabular => {
-                write_tabular(&mut self.values, &self.config, &self.headers, out)?;
+                write_tabular(&mut values, &self.config, &self.headers, out)?;
             }
             OutputFormat::Vertical => {
-                write_vertical(&mut self.values, &self.config, &self.headers, out)?;
+                write_vertical(&mut values, &self.config, &self.headers, out)?;
             }
             OutputFormat::Csv => {
-                write_csv(&mut self.values, &self.config, &self.headers, out)?;
+                write_csv(&mut values, &self.config, &self.headers, out)?;
+            }
+            OutputFormat::Markdown => {
+                write_markdown(&mut values, &self.config, &self.headers, out)?;
+            }
+            OutputFormat::Html => {
+                write_html(&mut values, &self.config, &self.headers, out)?;
+            }
+            OutputFormat::Arrow => {
+                write_arrow(&mut values, &self.config, &self.headers, out)?;
             }
         }
 
@@ -278,16 +1401,17 @@ mod tests {
     #[test]
     fn test_table_format_row() {
         let batch_size = 1;
+        let layout = Layout::default();
         let mut width = vec![0; 2];
-        let values = vec![
+        let values = [
             vec!["12345".to_string(), "tÞykÂ¿".to_string()],
             vec!["123456789".to_string(), "123456789".to_string()],
             vec!["".to_string(), "".to_string()],
         ];
 
-        let result1 = format_row(0, batch_size, &values[0], &mut width);
-        let result2 = format_row(1, batch_size, &values[1], &mut width);
-        let result3 = format_row(2, batch_size, &values[2], &mut width);
+        let result1 = format_row(0 < batch_size, &values[0], &mut width, &layout);
+        let result2 = format_row(1 < batch_size, &values[1], &mut width, &layout);
+        let result3 = format_row(2 < batch_size, &values[2], &mut width, &layout);
 
         assert_eq!(vec![5, 6], width);
         assert_eq!(16, result1.len());
@@ -299,6 +1423,243 @@ mod tests {
         assert_eq!("     \t      \n", str::from_utf8(&result3).unwrap());
     }
 
+    #[test]
+    fn test_table_format_row_overflow() {
+        let cells = vec!["123456789".to_string(), "ab".to_string()];
+        let row = |index, overflow, max_col_width| {
+            let layout = Layout {
+                max_col_width,
+                overflow,
+                ..Layout::default()
+            };
+            let mut width = vec![5, 2];
+            let row = format_row(index < 1, &cells, &mut width, &layout);
+
+            String::from_utf8(row).unwrap()
+        };
+
+        assert_eq!(row(1, Overflow::Truncate, None), "12...\tab\n");
+        assert_eq!(row(1, Overflow::Full, None), "123456789\tab\n");
+        assert_eq!(row(1, Overflow::Wrap, None), "12345\tab\n6789 \t  \n");
+        assert_eq!(row(1, Overflow::Truncate, Some(4)), "1...\tab\n");
+        assert_eq!(row(0, Overflow::Truncate, None), "123456789\tab\n");
+        assert_eq!(row(0, Overflow::Truncate, Some(4)), "1...\tab\n");
+        assert_eq!(
+            row(0, Overflow::Wrap, Some(4)),
+            "1234\tab\n5678\t\n9   \t\n"
+        );
+    }
+
+    #[test]
+    fn test_table_write_table_measure() {
+        let headers = vec![String::from("c1"), String::from("c2")];
+        let write = |measure| {
+            let mut buff = Cursor::new(Vec::new());
+            let config = OutputConfig {
+                batch_size: 1,
+                layout: Layout {
+                    measure,
+                    ..Layout::default()
+                },
+                ..OutputConfig::default()
+            };
+            let mut values = vec![
+                Ok(vec![String::from("1"), String::from("a")]),
+                Ok(vec![String::from("2"), String::from("b")]),
+                Ok(vec![String::from("3"), String::from("123456789")]),
+            ]
+            .into_iter();
+
+            write_table(&mut values, &config, &headers, &mut buff).unwrap();
+
+            String::from_utf8(buff.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            write(Measure::Fast),
+            "c1    c2\n1     a\n2     b   \n3     1...\n"
+        );
+        assert_eq!(
+            write(Measure::Exact),
+            "c1    c2\n1     a\n2     b\n3     123456789\n"
+        );
+        assert_eq!(Measure::try_from("EXACT"), Ok(Measure::Exact));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_to_clipboard() {
+        let file = crate::api::tests::temp_file("clipboard", ".txt");
+        let copy = format!("cat > {}", file.path().display());
+        let commands: &[&[&str]] = &[&["xpq-missing-clipboard"], &["sh", "-c", &copy]];
+
+        copy_with(commands, b"| a |\n").unwrap();
+
+        assert_eq!(std::fs::read(file.path()).unwrap(), b"| a |\n");
+        assert_eq!(
+            copy_with(&[&["xpq-missing-clipboard"]], b"a"),
+            Err(Error::IO(String::from(
+                "No clipboard command found, install wl-copy, xclip or xsel"
+            )))
+        );
+        assert!(copy_with(&[&["false"]], b"a").is_err());
+        assert!(copy_with(commands, &vec![b'a'; MAX_CLIPBOARD_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_output_file() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let dir = crate::api::tests::temp_dir();
+        let plain = dir.path().join("out.txt");
+        let gzip = dir.path().join("out.txt.gz");
+
+        std::fs::write(&plain, b"old\n").unwrap();
+
+        let mut file = OutputFile::create(&plain).unwrap();
+
+        file.write_all(b"new\n").unwrap();
+        drop(file);
+
+        assert_eq!(std::fs::read(&plain).unwrap(), b"old\n");
+
+        let mut file = OutputFile::create(&plain).unwrap();
+
+        file.write_all(b"new\n").unwrap();
+        file.finish().unwrap();
+
+        let mut file = OutputFile::create(&gzip).unwrap();
+        let mut decoded = String::new();
+
+        file.write_all(b"compressed\n").unwrap();
+        file.finish().unwrap();
+
+        GzDecoder::new(std::fs::File::open(&gzip).unwrap())
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        assert_eq!(std::fs::read(&plain).unwrap(), b"new\n");
+        assert_eq!(decoded, "compressed\n");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = crate::api::tests::temp_dir();
+        let existing = dir.path().join("existing.txt");
+        let created = dir.path().join("created.txt");
+        let mode =
+            |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        std::fs::write(&existing, b"old\n").unwrap();
+        std::fs::set_permissions(&existing, std::fs::Permissions::from_mode(0o640))
+            .unwrap();
+
+        for path in [&existing, &created] {
+            let mut file = OutputFile::create(path).unwrap();
+
+            file.write_all(b"new\n").unwrap();
+            file.finish().unwrap();
+        }
+
+        assert_eq!(mode(&existing), 0o640);
+        assert_eq!(
+            Some(mode(&created)),
+            output_permissions(&dir.path().join("missing")).map(|p| p.mode() & 0o777)
+        );
+    }
+
+    #[test]
+    fn test_table_paint_cell() {
+        let config = OutputConfig {
+            color: true,
+            highlights: vec![(1, Regex::new("o+").unwrap())],
+            ..OutputConfig::default()
+        };
+        let value = |field, align| Paint::Value { field, align };
+
+        assert_eq!(
+            paint_cell("id  ", Paint::Header, &config),
+            "\x1b[1mid\x1b[0m  "
+        );
+        assert_eq!(
+            paint_cell("null  ", value(0, true), &config),
+            "\x1b[2mnull\x1b[0m  "
+        );
+        assert_eq!(
+            paint_cell("-1.5  ", value(0, true), &config),
+            "  \x1b[36m-1.5\x1b[0m"
+        );
+        assert_eq!(
+            paint_cell("12  ", value(0, false), &config),
+            "\x1b[36m12\x1b[0m  "
+        );
+        assert_eq!(
+            paint_cell("\"foo\"  ", value(1, true), &config),
+            "\"f\x1b[1;31moo\x1b[0m\"  "
+        );
+        assert_eq!(
+            paint_cell("\"foo\"  ", value(0, true), &config),
+            "\"foo\"  "
+        );
+        assert_eq!(paint_cell("    ", value(0, true), &config), "    ");
+    }
+
+    #[test]
+    fn test_table_highlight() {
+        let a = Regex::new("ab").unwrap();
+        let b = Regex::new("bc").unwrap();
+
+        assert_eq!(highlight("xaby", &[&a]), "x\x1b[1;31mab\x1b[0my");
+        assert_eq!(
+            highlight("\"abc\"", &[&a, &b]),
+            "\"\x1b[1;31mab\x1b[0m\x1b[1;31mc\x1b[0m\""
+        );
+        assert_eq!(highlight("xyz", &[&a]), "xyz");
+    }
+
+    #[test]
+    fn test_table_write_table_color() {
+        let headers = vec![String::from("id"), String::from("name")];
+        let config = OutputConfig {
+            color: true,
+            ..OutputConfig::default()
+        };
+        let mut buff = Cursor::new(Vec::new());
+        let mut values = vec![
+            Ok(vec![String::from("1"), String::from("\"a\"")]),
+            Ok(vec![String::from("22"), String::from("null")]),
+        ]
+        .into_iter();
+
+        write_table(&mut values, &config, &headers, &mut buff).unwrap();
+
+        let vec = buff.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+        let expected = [
+            "\x1b[1mid\x1b[0m    \x1b[1mname\x1b[0m",
+            "   \x1b[36m1\x1b[0m  \"a\" ",
+            "  \x1b[36m22\x1b[0m  \x1b[2mnull\x1b[0m",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(actual, expected);
+        assert_eq!(Color::try_from("Never"), Ok(Color::Never));
+        assert!(Color::try_from("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_table_wrap_cell() {
+        assert_eq!(wrap_cell("abcde", 2), vec!["ab", "cd", "e"]);
+        assert_eq!(wrap_cell("", 2), vec![""]);
+        assert_eq!(wrap_cell("日本語", 4), vec!["日本", "語"]);
+    }
+
     #[test]
     fn test_table_write_vertical() {
         let config = OutputConfig::default();
@@ -315,7 +1676,7 @@ mod tests {
 
         let vec = buff.into_inner();
         let actual = str::from_utf8(&vec).unwrap();
-        let expected = vec![
+        let expected = [
             "",
             "c1:   r1 - 1",
             "c2:   r1 - 2",
@@ -350,6 +1711,67 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_table_visible_columns() {
+        let widths = vec![4, 4, 4, 4, 4];
+        let max = |n| Layout {
+            max_columns: Some(n),
+            ..Layout::default()
+        };
+        let width = |n| Layout {
+            width: Some(n),
+            ..Layout::default()
+        };
+
+        assert_eq!(
+            visible_columns(&widths, &Layout::default()),
+            vec![Some(0), Some(1), Some(2), Some(3), Some(4)]
+        );
+        assert_eq!(
+            visible_columns(&widths, &max(3)),
+            vec![Some(0), Some(1), None, Some(4)]
+        );
+        assert_eq!(visible_columns(&widths, &max(0)), vec![Some(0), None]);
+        assert_eq!(
+            visible_columns(&widths, &width(28)),
+            vec![Some(0), Some(1), Some(2), Some(3), Some(4)]
+        );
+        assert_eq!(
+            visible_columns(&widths, &width(27)),
+            vec![Some(0), Some(1), None, Some(3), Some(4)]
+        );
+        assert_eq!(visible_columns(&widths, &width(1)), vec![Some(0), None]);
+    }
+
+    #[test]
+    fn test_table_write_tabular_elided() {
+        let config = OutputConfig {
+            layout: Layout {
+                max_columns: Some(2),
+                ..Layout::default()
+            },
+            ..OutputConfig::default()
+        };
+        let mut buff = Cursor::new(Vec::new());
+        let headers: Vec<String> =
+            vec![String::from("c1"), String::from("c2"), String::from("c3")];
+        let mut values = vec![Ok(vec![
+            String::from("r1 - 1"),
+            String::from("r1 - 2"),
+            String::from("r1 - 3"),
+        ])]
+        .into_iter();
+
+        write_tabular(&mut values, &config, &headers, &mut buff)
+            .expect("Fail to write tabular");
+
+        let vec = buff.into_inner();
+        let actual = str::from_utf8(&vec).unwrap();
+        let expected = "c1      ...   c3\nr1 - 1  ...   r1 - 3\n";
+
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn test_table_write_csv() {
         let config = OutputConfig::default();
@@ -407,6 +1829,21 @@ mod tests {
             OutputFormat::Vertical
         );
 
+        assert_eq!(
+            OutputFormat::try_from(String::from("md"))?,
+            OutputFormat::Markdown
+        );
+
+        assert_eq!(
+            OutputFormat::try_from(String::from("HTML"))?,
+            OutputFormat::Html
+        );
+
+        assert_eq!(
+            OutputFormat::try_from(String::from("Arrow"))?,
+            OutputFormat::Arrow
+        );
+
         assert_eq!(
             OutputFormat::try_from(String::from("foo")).err().unwrap(),
             Error::InvalidArgument(String::from("foo"))
@@ -419,7 +1856,31 @@ mod tests {
     fn test_table_output_format_values() {
         assert_eq!(
             OutputFormat::values(),
-            vec!["t", "table", "tabular", "v", "vertical", "c", "csv"]
+            vec![
+                "t", "table", "tabular", "v", "vertical", "c", "csv", "md", "markdown",
+                "html", "arrow"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binary_format_try_from() {
+        assert_eq!(BinaryFormat::try_from("HEX"), Ok(BinaryFormat::Hex));
+        assert_eq!(BinaryFormat::try_from("base64"), Ok(BinaryFormat::Base64));
+        assert_eq!(BinaryFormat::try_from("size"), Ok(BinaryFormat::Size));
+        assert_eq!(
+            BinaryFormat::try_from("foo"),
+            Err(Error::InvalidArgument(String::from("foo")))
+        );
+    }
+
+    #[test]
+    fn test_nested_format_try_from() {
+        assert_eq!(NestedFormat::try_from("JSON"), Ok(NestedFormat::Json));
+        assert_eq!(NestedFormat::try_from("default"), Ok(NestedFormat::Default));
+        assert_eq!(
+            NestedFormat::try_from("foo"),
+            Err(Error::InvalidArgument(String::from("foo")))
         );
     }
 
@@ -460,7 +1921,7 @@ mod tests {
 
         let vec = buff.into_inner();
         let actual = str::from_utf8(&vec).unwrap();
-        let expected = vec![
+        let expected = [
             "",
             "c1:   r1 - 1",
             "c2:   r1 - 2",
@@ -490,11 +1951,87 @@ mod tests {
 
         let vec = buff.into_inner();
         let actual = str::from_utf8(&vec).unwrap();
-        let expected = vec!["c1,c2", "1,11", "2,22", ""].join("\n");
+        let expected = ["c1,c2", "1,11", "2,22", ""].join("\n");
 
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_table_output_writer_markdown_and_html() {
+        let headers: Vec<String> = vec![String::from("c|1"), String::from("c2")];
+        let values = vec![
+            vec![String::from("a|b"), String::from("x\ny")],
+            vec![String::from("<b>&'"), String::from("\"q\"")],
+        ];
+        let write = |format| {
+            let mut buff = Cursor::new(Vec::new());
+            let iter = values.clone().into_iter().map(Ok);
+            let mut writer = OutputWriter::new(headers.clone(), iter).format(format);
+
+            writer.write(&mut buff).unwrap();
+
+            String::from_utf8(buff.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            write(OutputFormat::Markdown),
+            [
+                "| c\\|1 | c2 |",
+                "| --- | --- |",
+                "| a\\|b | x<br>y |",
+                "| <b>&' | \"q\" |",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            write(OutputFormat::Html),
+            [
+                "<table>",
+                "<thead>",
+                "<tr><th>c|1</th><th>c2</th></tr>",
+                "</thead>",
+                "<tbody>",
+                "<tr><td>a|b</td><td>x\ny</td></tr>",
+                "<tr><td>&lt;b&gt;&amp;&#39;</td><td>&quot;q&quot;</td></tr>",
+                "</tbody>",
+                "</table>",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_table_output_writer_arrow() {
+        use arrow::array::Array;
+        use arrow::ipc::reader::StreamReader;
+
+        let mut buff = Cursor::new(Vec::new());
+        let headers: Vec<String> = vec![String::from("c1"), String::from("c2")];
+        let values = (0..3).map(|i| Ok(vec![i.to_string(), format!("r{}", i)]));
+        let mut writer = OutputWriter::new(headers, values).format(OutputFormat::Arrow);
+
+        writer.config.batch_size = 2;
+        writer.write(&mut buff).unwrap();
+
+        let reader = StreamReader::try_new(Cursor::new(buff.into_inner()), None).unwrap();
+        let schema = reader.schema();
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>().unwrap();
+        let column = batches[1]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+
+        assert_eq!(schema.field(0).name(), "c1");
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(column.len(), 1);
+        assert_eq!(column.value(0), "r2");
+    }
+
     #[test]
     fn test_table_output_writer_write_minwidth() {
         let mut buff = Cursor::new(Vec::new());
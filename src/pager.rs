@@ -0,0 +1,218 @@
+use crate::api::{Error, Result};
+use std::convert::TryFrom;
+use std::env;
+use std::io::{self, Write};
+use std::process::{Child, Command, Stdio};
+
+/// Pager used when `PAGER` is not set, keeping long lines unwrapped and colors.
+const DEFAULT_PAGER: &[&str] = &["less", "-S", "-R"];
+
+/// When outputs written to a terminal are paged.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PagerMode {
+    // Page outputs taller than the terminal
+    #[default]
+    Auto,
+
+    // Write every output to the terminal
+    Never,
+}
+
+const PAGER_MODE_VALUES: &[&str] = &["auto", "never"];
+
+impl PagerMode {
+    pub fn values() -> &'static [&'static str] {
+        PAGER_MODE_VALUES
+    }
+}
+
+impl TryFrom<&str> for PagerMode {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            "auto" => Ok(PagerMode::Auto),
+            "never" => Ok(PagerMode::Never),
+            _ => Err(Error::InvalidArgument(value.to_string())),
+        }
+    }
+}
+
+/// Pager command from the `PAGER` environment variable, `less -S -R` when not set.
+pub fn pager_command() -> Vec<String> {
+    let command = env::var("PAGER")
+        .ok()
+        .map(|v| v.split_whitespace().map(String::from).collect::<Vec<_>>())
+        .filter(|c| !c.is_empty());
+
+    command.unwrap_or_else(|| DEFAULT_PAGER.iter().map(|a| a.to_string()).collect())
+}
+
+enum State {
+    // Output shorter than the terminal so far, with its number of lines
+    Buffering(Vec<u8>, usize),
+
+    // Output piped to the pager
+    Paging(Child),
+
+    // Output written as it is, the pager could not be started
+    Direct,
+}
+
+/// Writer holding the output until it is taller than the terminal,
+/// taller outputs are piped to the pager and shorter ones written once finished.
+pub struct Pager<W: Write> {
+    out: W,
+    height: usize,
+    command: Vec<String>,
+    state: State,
+}
+
+impl<W: Write> Pager<W> {
+    /// Create a new `Pager` of the terminal `height` using the pager command.
+    pub fn new(out: W, height: usize, command: Vec<String>) -> Self {
+        Self {
+            out,
+            height,
+            command,
+            state: State::Buffering(Vec::new(), 0),
+        }
+    }
+
+    /// Start the pager with the buffered output, writing to the terminal when it fails.
+    fn page(&mut self, buffer: Vec<u8>) -> io::Result<()> {
+        // the pager owns the terminal from now on
+        crate::progress::stop();
+
+        let child = self.command.split_first().and_then(|(program, args)| {
+            Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .spawn()
+                .ok()
+        });
+
+        match child {
+            Some(mut child) => {
+                let result = match child.stdin.as_mut() {
+                    Some(stdin) => stdin.write_all(&buffer),
+                    None => Ok(()),
+                };
+
+                self.state = State::Paging(child);
+
+                result
+            }
+            None => {
+                self.state = State::Direct;
+
+                self.out.write_all(&buffer)
+            }
+        }
+    }
+
+    /// Write what is still buffered, or wait for the pager to exit.
+    pub fn finish(mut self) -> io::Result<()> {
+        match &mut self.state {
+            State::Buffering(buffer, _) => {
+                self.out.write_all(buffer)?;
+                self.out.flush()
+            }
+            State::Paging(child) => {
+                drop(child.stdin.take());
+                child.wait().map(|_| ())
+            }
+            State::Direct => self.out.flush(),
+        }
+    }
+}
+
+impl<W: Write> Write for Pager<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let height = self.height;
+
+        match &mut self.state {
+            State::Buffering(buffer, lines) => {
+                buffer.extend_from_slice(buf);
+                *lines += buf.iter().filter(|b| **b == b'\n').count();
+
+                if *lines >= height {
+                    let buffer = std::mem::take(buffer);
+
+                    self.page(buffer)?;
+                }
+
+                Ok(buf.len())
+            }
+            State::Paging(child) => match child.stdin.as_mut() {
+                Some(stdin) => stdin.write(buf),
+                None => Err(io::ErrorKind::BrokenPipe.into()),
+            },
+            State::Direct => self.out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.state {
+            State::Buffering(_, _) => Ok(()),
+            State::Paging(child) => match child.stdin.as_mut() {
+                Some(stdin) => stdin.flush(),
+                None => Ok(()),
+            },
+            State::Direct => self.out.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use std::fs;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pager_taller_than_terminal() {
+        let file = api::tests::temp_file("pager", ".txt");
+        let command = vec![
+            String::from("sh"),
+            String::from("-c"),
+            format!("cat > {}", file.path().display()),
+        ];
+        let mut out = Vec::new();
+        let mut pager = Pager::new(&mut out, 2, command);
+
+        pager.write_all(b"a\n").unwrap();
+        pager.write_all(b"b\nc\n").unwrap();
+        pager.finish().unwrap();
+
+        assert!(out.is_empty());
+        assert_eq!(fs::read(file.path()).unwrap(), b"a\nb\nc\n");
+    }
+
+    #[test]
+    fn test_pager_shorter_than_terminal() {
+        let mut out = Vec::new();
+        let mut pager = Pager::new(&mut out, 3, vec![String::from("xpq-missing-pager")]);
+
+        pager.write_all(b"a\nb\n").unwrap();
+        pager.flush().unwrap();
+        pager.finish().unwrap();
+
+        assert_eq!(out, b"a\nb\n");
+    }
+
+    #[test]
+    fn test_pager_missing_command() {
+        let mut out = Vec::new();
+        let mut pager = Pager::new(&mut out, 1, vec![String::from("xpq-missing-pager")]);
+
+        pager.write_all(b"a\nb\n").unwrap();
+        pager.write_all(b"c\n").unwrap();
+        pager.finish().unwrap();
+
+        assert_eq!(out, b"a\nb\nc\n");
+        assert_eq!(PagerMode::try_from("NEVER"), Ok(PagerMode::Never));
+        assert!(PagerMode::try_from("always").is_err());
+    }
+}
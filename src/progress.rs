@@ -0,0 +1,319 @@
+use crate::command::estimate;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Time between two draws of the progress line.
+const DRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Files, row groups and bytes scanned so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct State {
+    files: usize,
+    total_files: usize,
+    row_groups: usize,
+    bytes: u64,
+    total_bytes: u64,
+    started: Option<Instant>,
+    drawn: Option<Instant>,
+
+    // Whether the line is on the screen
+    visible: bool,
+    stopped: bool,
+}
+
+impl State {
+    /// Remaining time, extrapolated from the bytes scanned so far.
+    fn eta(&self, now: Instant) -> Option<Duration> {
+        let elapsed = now.duration_since(self.started?).as_secs_f64();
+
+        if self.bytes == 0 || self.bytes > self.total_bytes {
+            return None;
+        }
+
+        let remaining =
+            elapsed * (self.total_bytes - self.bytes) as f64 / self.bytes as f64;
+
+        Some(Duration::from_secs_f64(remaining))
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "files {}/{}  row groups {}  {}/{}",
+            self.files,
+            self.total_files,
+            self.row_groups,
+            estimate::format_size(self.bytes),
+            estimate::format_size(self.total_bytes)
+        )?;
+
+        match self.eta(Instant::now()) {
+            Some(eta) => write!(f, "  ETA {}", format_duration(eta)),
+            None => Ok(()),
+        }
+    }
+}
+
+static PROGRESS: OnceLock<Mutex<State>> = OnceLock::new();
+
+/// Whether the output written to stdout ends with a new line, see `LineWriter`.
+static LINE_START: AtomicBool = AtomicBool::new(true);
+
+/// Report the progress of every scan on stderr, only the first call has any effect.
+pub fn enable() {
+    let _ = PROGRESS.set(Mutex::new(State::default()));
+}
+
+#[inline]
+pub fn is_enabled() -> bool {
+    PROGRESS.get().is_some()
+}
+
+#[inline]
+fn update<F: FnOnce(&mut State)>(f: F) {
+    if let Some(Ok(mut state)) = PROGRESS.get().map(Mutex::lock) {
+        f(&mut state);
+        draw(&mut state);
+    }
+}
+
+/// Draw the progress line, at most once per interval and not before the first one
+/// so that short scans are not reported.
+fn draw(state: &mut State) {
+    let now = Instant::now();
+    let due = state
+        .drawn
+        .or(state.started)
+        .is_some_and(|t| now.duration_since(t) >= DRAW_INTERVAL);
+
+    if state.stopped || !due || !LINE_START.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut stderr = io::stderr();
+    let _ = write!(stderr, "\r{}\x1b[K", state);
+    let _ = stderr.flush();
+
+    state.drawn = Some(now);
+    state.visible = true;
+}
+
+/// Erase the progress line, it is drawn again on the next update.
+pub fn clear() {
+    if let Some(Ok(mut state)) = PROGRESS.get().map(Mutex::lock) {
+        if state.visible {
+            let _ = write!(io::stderr(), "\r\x1b[K");
+
+            state.visible = false;
+        }
+    }
+}
+
+/// Erase the progress line and stop drawing it, eg: once the command is done.
+pub fn stop() {
+    clear();
+
+    if let Some(Ok(mut state)) = PROGRESS.get().map(Mutex::lock) {
+        state.stopped = true;
+    }
+}
+
+/// Add the files of a scan to the totals, using their size on disk.
+pub fn add_files<I: Iterator<Item = PathBuf>>(files: I) {
+    let sizes = files
+        .map(|p| std::fs::metadata(p).map_or(0, |m| m.len()))
+        .collect::<Vec<_>>();
+
+    update(|state| {
+        state.started = state.started.or_else(|| Some(Instant::now()));
+        state.total_files += sizes.len();
+        state.total_bytes += sizes.iter().sum::<u64>();
+    });
+}
+
+/// Rows of a file reporting each row group once its rows are read,
+/// the rest of the file counts as scanned once the rows end.
+pub struct Tracked<I> {
+    iter: I,
+    rows: usize,
+    row_groups: VecDeque<(usize, u64)>,
+    remaining: u64,
+    files: usize,
+    done: bool,
+}
+
+/// Track the rows of a file of `size` bytes, with the rows and compressed bytes
+/// of each of its row groups.
+pub fn track<I>(iter: I, size: u64, row_groups: Vec<(usize, u64)>) -> Tracked<I> {
+    Tracked {
+        iter,
+        rows: 0,
+        row_groups: row_groups.into(),
+        remaining: size,
+        files: 1,
+        done: !is_enabled(),
+    }
+}
+
+impl<I> Tracked<I> {
+    /// Whether the rows end the file, rows of a single row group only count the file
+    /// once its last row group is read.
+    pub fn ending_file(self, last: bool) -> Self {
+        Self {
+            files: last as usize,
+            ..self
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Tracked<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next();
+
+        if self.done {
+            return item;
+        }
+
+        if item.is_none() {
+            let remaining = self.remaining;
+            let files = self.files;
+
+            self.done = true;
+
+            update(|state| {
+                state.files += files;
+                state.bytes += remaining;
+            });
+
+            return item;
+        }
+
+        self.rows += 1;
+
+        while let Some((rows, bytes)) = self.row_groups.front().copied() {
+            if self.rows < rows {
+                break;
+            }
+
+            self.rows -= rows;
+            self.remaining = self.remaining.saturating_sub(bytes);
+            self.row_groups.pop_front();
+
+            update(|state| {
+                state.row_groups += 1;
+                state.bytes += bytes;
+            });
+        }
+
+        item
+    }
+}
+
+/// Writer of the command output erasing the progress line before each write,
+/// the line is only drawn again while the output ends with a new line.
+///
+/// Outputs that are not written to the terminal are left as they are.
+pub struct LineWriter<W> {
+    out: W,
+    terminal: bool,
+}
+
+impl<W: Write> LineWriter<W> {
+    pub fn new(out: W, terminal: bool) -> Self {
+        Self { out, terminal }
+    }
+}
+
+impl<W: Write> Write for LineWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.terminal {
+            return self.out.write(buf);
+        }
+
+        clear();
+
+        let written = self.out.write(buf)?;
+
+        if written > 0 {
+            LINE_START.store(buf[written - 1] == b'\n', Ordering::Relaxed);
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Short duration, eg: `45s`, `2m05s` or `1h20m`.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_line() {
+        let now = Instant::now();
+        let state = State {
+            files: 1,
+            total_files: 4,
+            row_groups: 3,
+            bytes: 1024,
+            total_bytes: 4096,
+            started: Some(now - Duration::from_secs(10)),
+            ..State::default()
+        };
+
+        assert_eq!(
+            state.to_string(),
+            "files 1/4  row groups 3  1.0 KiB/4.0 KiB  ETA 30s"
+        );
+        assert_eq!(state.eta(now), Some(Duration::from_secs(30)));
+        assert_eq!(State::default().eta(now), None);
+    }
+
+    #[test]
+    fn test_progress_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+        assert_eq!(format_duration(Duration::from_secs(4800)), "1h20m");
+    }
+
+    #[test]
+    fn test_progress_tracked_rows() {
+        let mut tracked = Tracked {
+            iter: 0..5,
+            rows: 0,
+            row_groups: vec![(2, 10), (0, 1), (3, 20)].into(),
+            remaining: 40,
+            files: 1,
+            done: false,
+        };
+
+        assert_eq!(tracked.by_ref().take(2).count(), 2);
+        assert_eq!(tracked.row_groups, vec![(3, 20)]);
+        assert_eq!(tracked.remaining, 29);
+        assert_eq!(tracked.by_ref().count(), 3);
+        assert!(tracked.row_groups.is_empty());
+        assert!(tracked.done);
+    }
+}
@@ -0,0 +1,922 @@
+use crate::api::{Error, Result};
+use crate::filter::{Expr, Operand, OperandSet, Operator, Predicate};
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Token of a query, keywords are words compared without case.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    // Bare word, a keyword or a column name, eg: `AND` or `user.age`
+    Word(String),
+
+    // Column name quoted with double quotes or backticks, eg: `"first name"`
+    Name(String),
+
+    // Single quoted string, eg: `'BR'`
+    Str(String),
+
+    // Number with an optional sign and fraction, eg: `-1.5`
+    Number(String),
+
+    // Operator or punctuation, eg: `>=` or `(`
+    Symbol(&'static str),
+}
+
+/// Symbols sorted so that two character symbols are tried first.
+const SYMBOLS: &[&str] = &[
+    "!=", "<>", "<=", ">=", "==", "=", "<", ">", "(", ")", ",", "*",
+];
+
+#[inline]
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+/// Split the query into tokens and their byte offsets.
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+
+    while let Some(c) = query[start..].chars().next() {
+        let rest = &query[start..];
+
+        if c.is_whitespace() {
+            start += c.len_utf8();
+            continue;
+        }
+
+        let (token, len) = if c == '\'' || c == '"' || c == '`' {
+            quoted(rest, c).ok_or_else(|| invalid(query, start, "Unterminated quote"))?
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|n: char| n.is_ascii_digit()))
+        {
+            let len = rest[1..]
+                .find(|n: char| !n.is_ascii_digit() && n != '.')
+                .map_or(rest.len(), |i| i + 1);
+
+            (Token::Number(rest[..len].to_string()), len)
+        } else if is_word_char(c) {
+            let len = rest.find(|n| !is_word_char(n)).unwrap_or(rest.len());
+
+            (Token::Word(rest[..len].to_string()), len)
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(*s))
+                .ok_or_else(|| invalid(query, start, "Unexpected character"))?;
+
+            (Token::Symbol(symbol), symbol.len())
+        };
+
+        tokens.push((start, token));
+        start += len;
+    }
+
+    Ok(tokens)
+}
+
+/// Token of the text quoted by `quote` and its length, quotes are escaped by
+/// doubling them, eg: `'it''s'`.
+#[inline]
+fn quoted(text: &str, quote: char) -> Option<(Token, usize)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1).peekable();
+
+    loop {
+        match chars.next()? {
+            (i, c) if c == quote => match chars.peek() {
+                Some((_, n)) if *n == quote => {
+                    value.push(quote);
+                    chars.next();
+                }
+                _ => {
+                    let token = match quote {
+                        '\'' => Token::Str(value),
+                        _ => Token::Name(value),
+                    };
+
+                    return Some((token, i + 1));
+                }
+            },
+            (_, c) => value.push(c),
+        }
+    }
+}
+
+#[inline]
+fn invalid(query: &str, position: usize, message: &str) -> Error {
+    Error::InvalidArgument(format!(
+        "{} at position {} of '{}'",
+        message, position, query
+    ))
+}
+
+/// Anchored regex of a `LIKE` pattern, `%` is any text and `_` any character.
+#[inline]
+fn like_regex(pattern: &str) -> Result<Regex> {
+    let mut regex = String::from("(?s)^");
+
+    for c in pattern.chars() {
+        match c {
+            '%' => regex.push_str(".*"),
+            '_' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+
+    Ok(Regex::new(&regex)?)
+}
+
+#[inline]
+fn not(expr: Expr, negated: bool) -> Expr {
+    if negated {
+        Expr::Not(Box::new(expr))
+    } else {
+        expr
+    }
+}
+
+/// Column or aggregate of a `SELECT` statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelectItem {
+    // Every column, `*`
+    All,
+
+    // Single column
+    Column(String),
+
+    // Number of rows of each group, `COUNT(*)`
+    Count,
+}
+
+/// Header of the `COUNT(*)` column, also used to order by it.
+pub const COUNT_COLUMN: &str = "count";
+
+/// Column of an `ORDER BY` clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderBy {
+    pub column: String,
+    pub descending: bool,
+}
+
+/// `JOIN` of a table on the equality of a column of each side,
+/// eg: `LEFT JOIN users u ON e.user_id = u.id`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Join {
+    // Table name or path of the joined side
+    pub table: String,
+
+    pub alias: Option<String>,
+
+    // Rows without a match are kept with nulls, `LEFT JOIN`
+    pub left: bool,
+
+    // Columns of the `ON` condition, as written
+    pub on: (String, String),
+}
+
+/// `SELECT` statement, eg: `SELECT a, b FROM 'data/' WHERE c > 5 ORDER BY a DESC LIMIT 100`.
+#[derive(Clone, Debug)]
+pub struct Select {
+    pub items: Vec<SelectItem>,
+
+    // Table name or path or url of the parquet file or directory
+    pub from: String,
+
+    pub alias: Option<String>,
+    pub joins: Vec<Join>,
+    pub filter: Option<Expr>,
+    pub group_by: Vec<String>,
+    pub order_by: Vec<OrderBy>,
+    pub limit: Option<usize>,
+}
+
+/// Values of the single column of a subquery, eg: `IN (SELECT id FROM users)`,
+/// as the text filters match, see `filter::field_text`.
+pub type Subquery<'a> = dyn FnMut(&Select) -> Result<HashSet<String>> + 'a;
+
+/// Recursive descent parser of `WHERE` clauses and `SELECT` statements.
+struct Parser<'a, 'b> {
+    query: &'a str,
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    subquery: &'a mut Subquery<'b>,
+}
+
+impl<'a, 'b> Parser<'a, 'b> {
+    fn new(query: &'a str, subquery: &'a mut Subquery<'b>) -> Result<Self> {
+        Ok(Self {
+            query,
+            tokens: tokenize(query)?,
+            position: 0,
+            subquery,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|t| &t.1)
+    }
+
+    /// Error at the current token.
+    fn error(&self, message: &str) -> Error {
+        let offset = self
+            .tokens
+            .get(self.position)
+            .map(|t| t.0)
+            .unwrap_or(self.query.len());
+
+        invalid(self.query, offset, message)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Consume the keyword when it is the next token.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+
+        if found {
+            self.position += 1;
+        }
+
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.keyword(keyword) {
+            return Ok(());
+        }
+
+        Err(self.error(&format!("Expected {}", keyword)))
+    }
+
+    fn symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+
+        if found {
+            self.position += 1;
+        }
+
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.symbol(symbol) {
+            return Ok(());
+        }
+
+        Err(self.error(&format!("Expected '{}'", symbol)))
+    }
+
+    /// Fail on trailing tokens.
+    fn end(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("Unexpected token")),
+        }
+    }
+
+    /// Whole `WHERE` clause.
+    fn parse(mut self) -> Result<Expr> {
+        let expr = self.or()?;
+
+        self.end()?;
+
+        Ok(expr)
+    }
+
+    /// Separated by commas list of at least one item.
+    fn list<T, F>(&mut self, mut item: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> Result<T>,
+    {
+        let mut items = vec![item(self)?];
+
+        while self.symbol(",") {
+            items.push(item(self)?);
+        }
+
+        Ok(items)
+    }
+
+    /// Whether the next tokens are `COUNT(*)`, consuming them.
+    fn count(&mut self) -> Result<bool> {
+        let is_count = self.is_keyword("COUNT")
+            && matches!(
+                self.tokens.get(self.position + 1),
+                Some((_, Token::Symbol("(")))
+            );
+
+        if is_count {
+            self.position += 2;
+            self.expect_symbol("*")?;
+            self.expect_symbol(")")?;
+        }
+
+        Ok(is_count)
+    }
+
+    fn select_item(&mut self) -> Result<SelectItem> {
+        if self.symbol("*") {
+            return Ok(SelectItem::All);
+        }
+
+        if self.count()? {
+            return Ok(SelectItem::Count);
+        }
+
+        Ok(SelectItem::Column(self.column()?))
+    }
+
+    fn order_by(&mut self) -> Result<OrderBy> {
+        let column = if self.count()? {
+            COUNT_COLUMN.to_string()
+        } else {
+            self.column()?
+        };
+        let descending = self.keyword("DESC");
+
+        if !descending {
+            self.keyword("ASC");
+        }
+
+        Ok(OrderBy { column, descending })
+    }
+
+    /// Table name or path followed by an optional alias, eg: `'data/' AS d`.
+    fn table(&mut self) -> Result<(String, Option<String>)> {
+        let path = match self.peek() {
+            Some(Token::Str(s)) | Some(Token::Name(s)) => s.clone(),
+            Some(Token::Word(w)) if !is_reserved(w) => w.clone(),
+            _ => return Err(self.error("Expected a path")),
+        };
+
+        self.position += 1;
+
+        let alias = match (self.keyword("AS"), self.peek()) {
+            (_, Some(Token::Word(w))) if !is_reserved(w) => Some(w.clone()),
+            (true, _) => return Err(self.error("Expected an alias")),
+            (false, _) => None,
+        };
+
+        if alias.is_some() {
+            self.position += 1;
+        }
+
+        Ok((path, alias))
+    }
+
+    /// `[INNER] JOIN` or `LEFT [OUTER] JOIN` clause, `None` when there is no join.
+    fn join(&mut self) -> Result<Option<Join>> {
+        let left = self.keyword("LEFT");
+
+        if left {
+            self.keyword("OUTER");
+        } else if !self.keyword("INNER") && !self.is_keyword("JOIN") {
+            return Ok(None);
+        }
+
+        self.expect_keyword("JOIN")?;
+
+        let (table, alias) = self.table()?;
+
+        self.expect_keyword("ON")?;
+
+        let column = self.column()?;
+
+        if !self.symbol("=") && !self.symbol("==") {
+            return Err(self.error("Expected '='"));
+        }
+
+        Ok(Some(Join {
+            table,
+            alias,
+            left,
+            on: (column, self.column()?),
+        }))
+    }
+
+    fn limit(&mut self) -> Result<usize> {
+        let limit = match self.peek() {
+            Some(Token::Number(n)) => n.parse().ok(),
+            _ => None,
+        };
+        let limit = limit.ok_or_else(|| self.error("Expected a number of rows"))?;
+
+        self.position += 1;
+
+        Ok(limit)
+    }
+
+    /// Whole `SELECT` statement.
+    fn select(mut self) -> Result<Select> {
+        let select = self.statement()?;
+
+        self.end()?;
+
+        Ok(select)
+    }
+
+    /// `SELECT` statement, also used by subqueries.
+    fn statement(&mut self) -> Result<Select> {
+        self.expect_keyword("SELECT")?;
+
+        let items = self.list(Self::select_item)?;
+
+        self.expect_keyword("FROM")?;
+
+        let (from, alias) = self.table()?;
+        let mut joins = vec![];
+
+        while let Some(join) = self.join()? {
+            joins.push(join);
+        }
+
+        let filter = if self.keyword("WHERE") {
+            Some(self.or()?)
+        } else {
+            None
+        };
+        let mut group_by = vec![];
+        let mut order_by = vec![];
+        let mut limit = None;
+
+        if self.keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            group_by = self.list(Self::column)?;
+        }
+
+        if self.keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            order_by = self.list(Self::order_by)?;
+        }
+
+        if self.keyword("LIMIT") {
+            limit = Some(self.limit()?);
+        }
+
+        Ok(Select {
+            items,
+            from,
+            alias,
+            joins,
+            filter,
+            group_by,
+            order_by,
+            limit,
+        })
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.and()?];
+
+        while self.keyword("OR") {
+            exprs.push(self.and()?);
+        }
+
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => Expr::Or(exprs),
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.unary()?];
+
+        while self.keyword("AND") {
+            exprs.push(self.unary()?);
+        }
+
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => Expr::And(exprs),
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        if self.symbol("(") {
+            let expr = self.or()?;
+
+            self.expect_symbol(")")?;
+
+            return Ok(expr);
+        }
+
+        self.condition()
+    }
+
+    fn column(&mut self) -> Result<String> {
+        let name = match self.peek() {
+            Some(Token::Word(w)) if !is_reserved(w) => w.clone(),
+            Some(Token::Name(n)) => n.clone(),
+            _ => return Err(self.error("Expected a column")),
+        };
+
+        self.position += 1;
+
+        Ok(name)
+    }
+
+    fn value(&mut self) -> Result<Operand> {
+        let text = match self.peek() {
+            Some(Token::Str(s)) | Some(Token::Number(s)) => s.clone(),
+            Some(Token::Word(w))
+                if w.eq_ignore_ascii_case("true") || w.eq_ignore_ascii_case("false") =>
+            {
+                w.to_lowercase()
+            }
+            _ => return Err(self.error("Expected a value")),
+        };
+
+        self.position += 1;
+
+        Ok(Operand::new(&text))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Str(s)) => {
+                let s = s.clone();
+
+                self.position += 1;
+
+                Ok(s)
+            }
+            _ => Err(self.error("Expected a string")),
+        }
+    }
+
+    /// Condition on a single column, eg: `age >= 30` or `name LIKE 'B%'`.
+    fn condition(&mut self) -> Result<Expr> {
+        let column = self.column()?;
+        let compare =
+            |op, operand| Expr::Column(column.clone(), Predicate::Compare(op, operand));
+
+        if let Some(Token::Symbol(symbol)) = self.peek() {
+            let op = match *symbol {
+                "=" | "==" => Operator::Eq,
+                "!=" | "<>" => Operator::Ne,
+                "<" => Operator::Lt,
+                "<=" => Operator::Le,
+                ">" => Operator::Gt,
+                ">=" => Operator::Ge,
+                _ => return Err(self.error("Expected an operator")),
+            };
+
+            self.position += 1;
+
+            return Ok(compare(op, self.value()?));
+        }
+
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+
+            self.expect_keyword("NULL")?;
+
+            return Ok(not(Expr::Column(column, Predicate::Null), negated));
+        }
+
+        let negated = self.keyword("NOT");
+
+        if self.keyword("LIKE") {
+            let regex = like_regex(&self.string()?)?;
+
+            return Ok(not(Expr::Column(column, Predicate::Regex(regex)), negated));
+        }
+
+        if self.keyword("IN") {
+            let mut exprs = Vec::new();
+
+            self.expect_symbol("(")?;
+
+            if self.is_keyword("SELECT") {
+                let select = self.statement()?;
+                let values = (self.subquery)(&select)?;
+
+                self.expect_symbol(")")?;
+
+                let expr = Expr::Column(
+                    column,
+                    Predicate::In(Arc::new(OperandSet::new(values))),
+                );
+
+                return Ok(not(expr, negated));
+            }
+
+            loop {
+                exprs.push(compare(Operator::Eq, self.value()?));
+
+                if !self.symbol(",") {
+                    break;
+                }
+            }
+
+            self.expect_symbol(")")?;
+
+            return Ok(not(Expr::Or(exprs), negated));
+        }
+
+        if self.keyword("BETWEEN") {
+            let low = self.value()?;
+
+            self.expect_keyword("AND")?;
+
+            let high = self.value()?;
+            let expr = Expr::And(vec![
+                compare(Operator::Ge, low),
+                compare(Operator::Le, high),
+            ]);
+
+            return Ok(not(expr, negated));
+        }
+
+        Err(self.error("Expected an operator"))
+    }
+}
+
+/// Keywords that can not be used as bare column names.
+const RESERVED: &[&str] = &[
+    "AND", "OR", "NOT", "IS", "NULL", "LIKE", "IN", "BETWEEN", "TRUE", "FALSE", "SELECT",
+    "FROM", "WHERE", "GROUP", "ORDER", "BY", "ASC", "DESC", "LIMIT", "JOIN", "INNER",
+    "LEFT", "OUTER", "ON", "AS",
+];
+
+#[inline]
+fn is_reserved(word: &str) -> bool {
+    RESERVED.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+/// Parse a SQL-like `WHERE` clause, eg: `age > 30 AND country = 'BR'`.
+///
+/// Supports `AND`, `OR`, `NOT`, parentheses, the `=`, `!=`, `<>`, `<`, `<=`, `>` and `>=`
+/// comparisons, `IS [NOT] NULL`, `[NOT] LIKE`, `[NOT] IN (..)` and `[NOT] BETWEEN .. AND ..`.
+/// Strings are single quoted and column names may be quoted with `"` or backticks.
+pub fn parse(query: &str) -> Result<Expr> {
+    Parser::new(query, &mut no_subquery)?.parse()
+}
+
+/// Subqueries are only run by `parse_select_with`.
+fn no_subquery(_: &Select) -> Result<HashSet<String>> {
+    Err(Error::InvalidArgument(String::from(
+        "Subqueries are not supported here",
+    )))
+}
+
+/// Parse a `SELECT` statement, eg: `SELECT a, b FROM 'data/' WHERE c > 5 ORDER BY a DESC LIMIT 100`.
+///
+/// Items are `*`, columns or `COUNT(*)`, followed by a `FROM` table name or path with an
+/// optional alias, `[INNER|LEFT] JOIN <table> ON <column> = <column>` clauses, an optional
+/// `WHERE` clause as in `parse`, `GROUP BY` columns, `ORDER BY` columns with `ASC` or `DESC`
+/// and a `LIMIT`.
+pub fn parse_select(query: &str) -> Result<Select> {
+    Parser::new(query, &mut no_subquery)?.select()
+}
+
+/// Parse a `SELECT` statement as `parse_select`, running the `[NOT] IN (SELECT ..)`
+/// subqueries of the `WHERE` clause with the given function while parsing.
+pub fn parse_select_with(query: &str, subquery: &mut Subquery) -> Result<Select> {
+    Parser::new(query, subquery)?.select()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::record::Field;
+
+    /// Whether a row of `(column, value)` pairs matches the query.
+    fn matches(query: &str, row: &[(&str, Field)]) -> bool {
+        let null = Field::Null;
+
+        parse(query).unwrap().is_match(&|column: &String| {
+            row.iter()
+                .find(|(name, _)| name == column)
+                .map(|(_, value)| value)
+                .unwrap_or(&null)
+        })
+    }
+
+    fn str(value: &str) -> Field {
+        Field::Str(value.to_string())
+    }
+
+    #[test]
+    fn test_query_tokenize() {
+        assert_eq!(
+            tokenize("a.b>=-1.5 AND \"c d\" <> 'it''s'").unwrap(),
+            vec![
+                (0, Token::Word(String::from("a.b"))),
+                (3, Token::Symbol(">=")),
+                (5, Token::Number(String::from("-1.5"))),
+                (10, Token::Word(String::from("AND"))),
+                (14, Token::Name(String::from("c d"))),
+                (20, Token::Symbol("<>")),
+                (23, Token::Str(String::from("it's"))),
+            ]
+        );
+        assert!(tokenize("a = 'b").is_err());
+        assert!(tokenize("a ~ 1").is_err());
+    }
+
+    #[test]
+    fn test_query_parse() {
+        let parsed = |q| parse(q).unwrap().to_string();
+
+        assert_eq!(
+            parsed("age > 30 AND country = 'BR'"),
+            "age>30 AND country=BR"
+        );
+        assert_eq!(parsed("a = 1 OR b = 2 AND c = 3"), "a=1 OR (b=2 AND c=3)");
+        assert_eq!(parsed("(a = 1 OR b = 2) AND c = 3"), "(a=1 OR b=2) AND c=3");
+        assert_eq!(
+            parsed("NOT a = 1 and b is not null"),
+            "NOT a=1 AND NOT b IS NULL"
+        );
+        assert_eq!(parsed("a IN (1, 2)"), "a=1 OR a=2");
+        assert_eq!(parsed("a NOT BETWEEN 1 AND 5"), "NOT (a>=1 AND a<=5)");
+        assert_eq!(parsed("name LIKE 'B_n%'"), "name:(?s)^B.n.*$");
+        assert_eq!(parsed("`first name` == TRUE"), "first name=true");
+
+        assert_eq!(
+            parse("age >").map(|_| ()),
+            Err(Error::InvalidArgument(String::from(
+                "Expected a value at position 5 of 'age >'"
+            )))
+        );
+        assert!(parse("").is_err());
+        assert!(parse("age").is_err());
+        assert!(parse("age = 1 country = 'BR'").is_err());
+        assert!(parse("(age = 1").is_err());
+        assert!(parse("and = 1").is_err());
+        assert!(parse("a = b").is_err());
+        assert!(parse("a LIKE 1").is_err());
+    }
+
+    #[test]
+    fn test_query_parse_select() {
+        let select = parse_select(
+            "select a, `b c`, COUNT(*) from 'data/' where a > 5 or c is null \
+             group by a, `b c` order by count(*) desc, a limit 10",
+        )
+        .unwrap();
+
+        assert_eq!(
+            select.items,
+            vec![
+                SelectItem::Column(String::from("a")),
+                SelectItem::Column(String::from("b c")),
+                SelectItem::Count,
+            ]
+        );
+        assert_eq!(select.from, "data/");
+        assert_eq!(
+            select.filter.map(|f| f.to_string()),
+            Some(String::from("a>5 OR c IS NULL"))
+        );
+        assert_eq!(select.group_by, vec!["a", "b c"]);
+        assert_eq!(
+            select.order_by,
+            vec![
+                OrderBy {
+                    column: String::from(COUNT_COLUMN),
+                    descending: true
+                },
+                OrderBy {
+                    column: String::from("a"),
+                    descending: false
+                },
+            ]
+        );
+        assert_eq!(select.limit, Some(10));
+
+        let select = parse_select("SELECT * FROM users.parquet").unwrap();
+
+        assert_eq!(select.items, vec![SelectItem::All]);
+        assert_eq!(select.from, "users.parquet");
+        assert!(select.filter.is_none() && select.limit.is_none());
+
+        assert!(parse_select("SELECT a FROM 'a' WHERE b IN (SELECT b FROM c)").is_err());
+        assert!(parse_select("SELECT FROM 'a'").is_err());
+        assert!(parse_select("SELECT a").is_err());
+        assert!(parse_select("SELECT a FROM 'a' LIMIT x").is_err());
+        assert!(parse_select("SELECT a FROM 'a' ORDER a").is_err());
+        assert!(parse_select("SELECT count(a) FROM 'a'").is_err());
+        assert!(parse_select("SELECT a FROM 'a' LIMIT 1 WHERE a = 1").is_err());
+    }
+
+    #[test]
+    fn test_query_parse_select_join() {
+        let mut subqueries = vec![];
+        let select = parse_select_with(
+            "SELECT e.id, u.name FROM events AS e JOIN users u ON e.user_id = u.id \
+             LEFT OUTER JOIN 'teams/' ON u.team = id \
+             WHERE e.kind NOT IN (SELECT kind FROM ignored WHERE a = 1) LIMIT 5",
+            &mut |select: &Select| {
+                subqueries.push(select.from.clone());
+
+                Ok(["click", "view"].iter().map(|v| v.to_string()).collect())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(select.from, "events");
+        assert_eq!(select.alias.as_deref(), Some("e"));
+        assert_eq!(
+            select.joins,
+            vec![
+                Join {
+                    table: String::from("users"),
+                    alias: Some(String::from("u")),
+                    left: false,
+                    on: (String::from("e.user_id"), String::from("u.id")),
+                },
+                Join {
+                    table: String::from("teams/"),
+                    alias: None,
+                    left: true,
+                    on: (String::from("u.team"), String::from("id")),
+                },
+            ]
+        );
+        assert_eq!(
+            select.filter.map(|f| f.to_string()),
+            Some(String::from("NOT e.kind IN (2 values)"))
+        );
+        assert_eq!(select.limit, Some(5));
+        assert_eq!(subqueries, vec!["ignored"]);
+
+        assert!(parse_select("SELECT a FROM b JOIN c").is_err());
+        assert!(parse_select("SELECT a FROM b JOIN c ON x > y").is_err());
+        assert!(parse_select("SELECT a FROM b AS WHERE a = 1").is_err());
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let row = [
+            ("age", Field::Int(34)),
+            ("country", str("BR")),
+            ("name", str("Ben")),
+        ];
+
+        assert!(matches("age > 30 AND country = 'BR'", &row));
+        assert!(!matches("age > 30 AND country = 'PT'", &row));
+        assert!(matches("age < 18 OR country IN ('PT', 'BR')", &row));
+        assert!(matches("age BETWEEN 30 AND 34", &row));
+        assert!(matches("name LIKE 'b%' OR name LIKE 'Be_'", &row));
+        assert!(matches("name NOT LIKE '%x%'", &row));
+        assert!(matches("email IS NULL AND name IS NOT NULL", &row));
+        assert!(!matches("email = 'a@b.c' OR email != 'a@b.c'", &row));
+        assert!(matches("NOT (age >= 40 OR country <> 'BR')", &row));
+
+        // comparing a null is unknown, and so is its negation
+        assert!(!matches("email NOT IN ('a@b.c', 'd@e.f')", &row));
+        assert!(!matches("email NOT LIKE '%x%'", &row));
+        assert!(!matches("score NOT BETWEEN 1 AND 5", &row));
+        assert!(!matches("NOT (score > 1)", &row));
+        assert!(!matches("NOT (score > 1 AND age > 30)", &row));
+        assert!(matches("NOT (score > 1 AND age > 40)", &row));
+        assert!(matches("NOT (score > 1) OR age > 30", &row));
+
+        let select = parse_select_with(
+            "SELECT a FROM t WHERE country IN (SELECT c FROM u)",
+            &mut |_| Ok(["BR", "PT"].iter().map(|v| v.to_string()).collect()),
+        )
+        .unwrap();
+        let null = Field::Null;
+        let filter = select.filter.unwrap();
+        let matches = |value: Field| {
+            filter.is_match(&|column: &String| match column.as_str() {
+                "country" => &value,
+                _ => &null,
+            })
+        };
+
+        assert!(matches(str("BR")));
+        assert!(!matches(str("US")));
+        assert!(!matches(Field::Null));
+
+        let select = parse_select_with(
+            "SELECT a FROM t WHERE country NOT IN (SELECT c FROM u)",
+            &mut |_| Ok(["BR", "PT"].iter().map(|v| v.to_string()).collect()),
+        )
+        .unwrap();
+        let filter = select.filter.unwrap();
+        let matches = |value: Field| {
+            filter.is_match(&|column: &String| match column.as_str() {
+                "country" => &value,
+                _ => &null,
+            })
+        };
+
+        assert!(!matches(str("BR")));
+        assert!(matches(str("US")));
+        assert!(!matches(Field::Null));
+    }
+}
@@ -1,393 +1,2452 @@
 use crate::api::Error;
 use crate::api::Result;
+use crate::cache::{FileCache, Fingerprint};
+use crate::filter::Expr;
+use crate::hash;
+use crate::http::{self, HttpFile};
+use crate::output::NestedFormat;
+use crate::progress;
+use crate::row_group_pruning::{self, PointFilter};
+use crate::snapshot::TRASH_DIR;
+use crate::summary;
+use crate::value::{Value, ValueFormat};
+use arrow::array::new_null_array;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use bytes::Bytes;
 use either::Either;
-use parquet::file::reader::FileReader;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{parquet_to_arrow_schema_by_columns, ProjectionMask};
+use parquet::basic::{ConvertedType, Repetition};
+use parquet::column::page::PageReader;
+use parquet::file::metadata::{
+    ColumnChunkMetaData, FileMetaData, ParquetMetaData, RowGroupMetaData,
+};
 use parquet::file::reader::SerializedFileReader;
+use parquet::file::reader::{ChunkReader, FileReader, Length, RowGroupReader};
+use parquet::file::serialized_reader::ReadOptionsBuilder;
 use parquet::record::reader::RowIter;
+use parquet::record::Field;
 use parquet::record::Row;
-use parquet::record::RowFormatter;
-use parquet::schema::types::Type;
+use parquet::schema::printer::print_schema;
+use parquet::schema::types::{SchemaDescriptor, Type, TypePtr};
 use regex::Regex;
-use std::collections::HashMap;
-use std::convert::TryFrom;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs::File;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use tempfile::{NamedTempFile, TempPath};
 use walkdir::{DirEntry, WalkDir};
 
-pub type ParquetFileReader = SerializedFileReader<File>;
+pub type ParquetFileReader = SerializedFileReader<ParquetSource>;
 
-#[inline]
-fn create_parquet_reader(path: &Path) -> Result<ParquetFileReader> {
-    SerializedFileReader::try_from(path)
-        .map_err(|e| Error::Parquet(path.to_path_buf(), e))
+/// Local or remote parquet file.
+pub enum ParquetSource {
+    File(File),
+    Http(HttpFile),
 }
 
-#[inline]
-fn file_metadata_num_rows(reader: &ParquetFileReader) -> usize {
-    let metadata = reader.metadata();
-    let file_meta = metadata.file_metadata();
+impl ParquetSource {
+    pub fn open(path: &Path) -> parquet::errors::Result<Self> {
+        summary::add_file(path);
 
-    file_meta.num_rows() as usize
+        match path.to_str().filter(|_| http::is_url(path)) {
+            Some(url) => HttpFile::open(url).map(ParquetSource::Http),
+            None => Ok(ParquetSource::File(File::open(path)?)),
+        }
+    }
 }
 
-#[inline]
-fn file_iterator_num_rows(reader: ParquetFileReader) -> usize {
-    let iter = reader.into_iter();
-
-    iter.count()
+impl Length for ParquetSource {
+    fn len(&self) -> u64 {
+        match self {
+            ParquetSource::File(f) => f.len(),
+            ParquetSource::Http(f) => f.len(),
+        }
+    }
 }
 
-#[inline]
-fn get_row_fields(
-    reader: &ParquetFileReader,
-    columns: &Option<Vec<String>>,
-) -> Vec<(usize, String)> {
-    let metadata = reader.metadata().file_metadata();
-    let schema = metadata.schema();
-    let mut result = Vec::new();
-    let fields = schema.get_fields();
-    let enumerate = fields.iter().enumerate();
-
-    match columns {
-        Some(names) => {
-            let map = enumerate
-                .map(|t| (t.1.name().to_lowercase(), t.0))
-                .collect::<HashMap<_, _>>();
+impl ChunkReader for ParquetSource {
+    type T = Box<dyn Read + Send>;
 
-            for name in names {
-                if let Some(index) = map.get(&name.to_lowercase()) {
-                    result.push((*index, String::from(fields[*index].name())));
-                }
-            }
-        }
-        None => {
-            for (index, field) in enumerate {
-                result.push((index, String::from(field.name())));
-            }
+    fn get_read(&self, start: u64, length: usize) -> parquet::errors::Result<Self::T> {
+        match self {
+            ParquetSource::File(f) => Ok(Box::new(f.get_read(start, length)?)),
+            ParquetSource::Http(f) => Ok(Box::new(f.get_read(start, length)?)),
         }
     }
 
-    result
-}
-#[inline]
-fn get_row_filters(
-    filelds: &[(usize, String)],
-    filters: &Option<HashMap<String, Regex>>,
-) -> Option<HashMap<usize, Regex>> {
-    match filters {
-        Some(filter_map) => {
-            let mut result = HashMap::new();
-            let field_map = filelds
-                .iter()
-                .enumerate()
-                .map(|t| ((t.1).1.to_lowercase(), t.0))
-                .collect::<HashMap<_, _>>();
-
-            for (field, regex) in filter_map.iter() {
-                if let Some(index) = field_map.get(&field.to_lowercase()) {
-                    result.insert(*index, regex.clone());
-                }
-            }
-
-            Some(result)
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        match self {
+            ParquetSource::File(f) => f.get_bytes(start, length),
+            ParquetSource::Http(f) => f.get_bytes(start, length),
         }
-        None => None,
     }
 }
 
-pub struct ParquetFile {
-    path: PathBuf,
-    fields: Option<Vec<String>>,
-    filters: Option<HashMap<String, Regex>>,
+/// Path argument reading parquet from stdin.
+pub const STDIN_PATH: &str = "-";
+
+/// Copy a parquet stream into a temporary file, parquet readers need to seek the footer.
+///
+/// The file is removed when the returned value is dropped.
+pub fn buffer_stdin<R: Read>(mut input: R) -> Result<NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix("xpq-stdin-")
+        .suffix(".parquet")
+        .tempfile()?;
+
+    io::copy(&mut input, &mut file)?;
+    file.flush()?;
+
+    Ok(file)
 }
 
-impl ParquetFile {
-    pub fn new(path: PathBuf) -> Self {
-        Self {
-            path,
-            fields: None,
-            filters: None,
-        }
-    }
+/// Copy of stdin, removed by `remove_stdin`.
+static STDIN_FILE: Mutex<Option<TempPath>> = Mutex::new(None);
 
-    pub fn with_fields(self, fields: Option<Vec<String>>) -> Self {
-        Self {
-            fields,
-            path: self.path,
-            filters: self.filters,
+/// Path of the copy of stdin read by `-` path arguments, stdin is copied on the first call.
+pub fn stdin_path() -> Result<&'static Path> {
+    static PATH: OnceLock<std::result::Result<PathBuf, String>> = OnceLock::new();
+
+    let path = PATH.get_or_init(|| {
+        let file = buffer_stdin(io::stdin().lock()).map_err(|e| e.to_string())?;
+        let path = file.into_temp_path();
+        let result = path.to_path_buf();
+
+        if let Ok(mut copy) = STDIN_FILE.lock() {
+            *copy = Some(path);
         }
+
+        Ok(result)
+    });
+
+    match path {
+        Ok(path) => Ok(path.as_path()),
+        Err(e) => Err(Error::IO(e.to_string())),
     }
+}
 
-    pub fn with_filters(self, filters: Option<HashMap<String, Regex>>) -> Self {
-        Self {
-            filters,
-            path: self.path,
-            fields: self.fields,
-        }
+/// Remove the copy of stdin, if any, `process::exit` skips destructors.
+pub fn remove_stdin() {
+    if let Ok(mut copy) = STDIN_FILE.lock() {
+        drop(copy.take());
     }
+}
 
-    pub fn num_rows(&self) -> usize {
-        self.files()
-            .map(|p| create_parquet_reader(p.as_path()))
-            .filter_map(Result::ok)
-            .map(|r| {
-                let meta_num_rouws = file_metadata_num_rows(&r);
+#[inline]
+fn create_parquet_reader(path: &Path) -> Result<ParquetFileReader> {
+    ParquetSource::open(path)
+        .and_then(SerializedFileReader::new)
+        .map_err(|e| Error::Parquet(path.to_path_buf(), e))
+}
 
-                if meta_num_rouws > 0 {
-                    return meta_num_rouws;
-                }
+/// Number of files whose footers are read ahead while scanning a directory.
+pub const PREFETCH_FILES: usize = 8;
 
-                file_iterator_num_rows(r)
-            })
-            .sum()
-    }
+/// Name of the field holding the row numbers, see `ParquetFile::with_row_numbers`.
+pub const ROW_NUMBER_FIELD: &str = "#";
 
-    pub fn field_names(&self) -> Result<Vec<String>> {
-        self.files()
-            .next()
-            .map(|p| create_parquet_reader(p.as_path()))
-            .map(|r| {
-                let fields = get_row_fields(&r?, &self.fields);
-                let names = fields.iter().map(|e| e.1.clone()).collect();
+/// Name of the field holding the path of the file of each row, see `ParquetFile::with_filename`.
+pub const FILENAME_FIELD: &str = "#file";
+
+/// Numbers of the rows read from a file, skipping the rows of the previous files
+/// and of the pruned row groups.
+struct RowNumbers {
+    // Rows read before each row group and the number of its first row
+    row_groups: Vec<(u64, u64)>,
+}
 
-                Ok(names)
+impl RowNumbers {
+    /// Numbers of the file starting at `first_row` with row groups of `rows` rows,
+    /// reading the `matching` row groups or every row group when `None`.
+    fn new(first_row: u64, rows: &[u64], matching: Option<&[usize]>) -> Self {
+        let mut number = first_row;
+        let starts = rows
+            .iter()
+            .map(|num| {
+                let start = number;
+
+                number += num;
+                start
             })
-            .unwrap_or_else(|| Err(Error::from(self.path.to_path_buf())))
-    }
+            .collect::<Vec<_>>();
+        let all = (0..rows.len()).collect::<Vec<_>>();
+        let mut read = 0;
+        let row_groups = matching
+            .unwrap_or(&all)
+            .iter()
+            .map(|i| {
+                let group = (read, starts[*i]);
 
-    pub fn schema(&self) -> Result<Type> {
-        self.files()
-            .next()
-            .map(|p| create_parquet_reader(p.as_path()))
-            .map(|r| Ok(r?.metadata().file_metadata().schema().clone()))
-            .unwrap_or_else(|| Err(Error::from(self.path.to_path_buf())))
-    }
+                read += rows[*i];
+                group
+            })
+            .collect();
 
-    pub fn iter(&self) -> impl Iterator<Item = Result<Vec<String>>> + '_ {
-        let iter = self.files();
-        let field_names = self.fields.clone();
-        let field_filter = self.filters.clone();
-
-        iter.map(move |p| {
-            let reader = create_parquet_reader(p.as_path())?;
-            let fields = get_row_fields(&reader, &field_names);
-            let filters = get_row_filters(&fields, &field_filter);
-            let row_iter: RowIter<'static> = reader.into_iter();
-            let iterator: Iter<_> = Iter::new(row_iter, fields, filters);
-
-            Ok(iterator)
-        })
-        .flat_map(|r| match r {
-            Ok(iter) => iter,
-            Err(e) => Iter::err(e),
-        })
+        Self { row_groups }
     }
 
-    fn files(&self) -> impl Iterator<Item = PathBuf> {
-        let is_file = self.path.is_file();
-        let is_parquet = |entry: &DirEntry| {
-            // accept partition directories
-            if entry.path().is_dir() {
-                return true;
-            }
-
-            entry
-                .file_name()
-                .to_str()
-                .map(|s| s.ends_with(".parquet"))
-                .unwrap_or(false)
-        };
+    /// Number of the row at a position of the rows read.
+    fn number(&self, position: u64) -> u64 {
+        let index = self.row_groups.partition_point(|g| g.0 <= position);
+        let (read, first) = self.row_groups[index.saturating_sub(1)];
 
-        WalkDir::new(&self.path)
-            .contents_first(true)
-            .into_iter()
-            .filter_entry(move |e| is_file || is_parquet(e))
-            .filter_map(std::result::Result::ok)
-            .map(DirEntry::into_path)
-            .filter(|p| p.is_file())
+        first + position - read
     }
 }
 
-impl From<&Path> for ParquetFile {
-    fn from(path: &Path) -> Self {
-        ParquetFile::new(path.to_path_buf())
-    }
+/// Thread reading the footers of the files sent to it, in the order they are sent.
+struct PrefetchWorker {
+    jobs: Sender<PathBuf>,
+    readers: Receiver<Result<ParquetFileReader>>,
+    handle: JoinHandle<()>,
 }
 
-impl From<(&Path, Option<Vec<String>>)> for ParquetFile {
-    fn from(tuple: (&Path, Option<Vec<String>>)) -> Self {
-        ParquetFile::from(tuple.0).with_fields(tuple.1)
+impl PrefetchWorker {
+    /// Start the thread, it stops once the jobs sender or the readers receiver is dropped.
+    fn start() -> Self {
+        let (jobs, queue) = mpsc::channel::<PathBuf>();
+        let (sender, readers) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            for path in queue {
+                if sender.send(create_parquet_reader(&path)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            jobs,
+            readers,
+            handle,
+        }
     }
 }
 
-struct Iter<T> {
-    fields: Vec<(usize, String)>,
-    values: Either<T, Vec<Error>>,
-    filters: Option<HashMap<usize, Regex>>,
+/// Readers of the files in order, the footers of the next `ahead` files are read
+/// concurrently by a pool of up to `ahead` threads while the current file is being consumed.
+///
+/// The file at index `i` of the scan is read by the worker `i % ahead`, workers are
+/// started with their first file and joined when the iterator is dropped.
+struct Prefetch<I> {
+    paths: I,
+    ahead: usize,
+    workers: Vec<PrefetchWorker>,
+    pending: VecDeque<PathBuf>,
+    next: usize,
 }
 
-impl<T> Iter<T>
+impl<I> Prefetch<I>
 where
-    T: Iterator<Item = Row>,
+    I: Iterator<Item = PathBuf>,
 {
-    fn new(
-        values: T,
-        fields: Vec<(usize, String)>,
-        filters: Option<HashMap<usize, Regex>>,
-    ) -> Self {
+    fn new(paths: I, ahead: usize) -> Self {
         Self {
-            values: Either::Left(values),
-            filters,
-            fields,
+            paths,
+            ahead,
+            workers: Vec::with_capacity(ahead),
+            pending: VecDeque::with_capacity(ahead),
+            next: 0,
         }
     }
+}
 
-    fn err(error: Error) -> Self {
-        Self {
-            values: Either::Right(vec![error]),
-            filters: None,
-            fields: vec![],
+impl<I> Iterator for Prefetch<I>
+where
+    I: Iterator<Item = PathBuf>,
+{
+    type Item = (PathBuf, Result<ParquetFileReader>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ahead == 0 {
+            return self.paths.next().map(|p| {
+                let reader = create_parquet_reader(&p);
+
+                (p, reader)
+            });
         }
-    }
 
-    fn filter_map_row(
-        row: Row,
-        fields: &[(usize, String)],
-        filters: &Option<HashMap<usize, Regex>>,
-    ) -> Option<Result<Vec<String>>> {
-        let result = fields
-            .iter()
-            .map(|e| format!("{}", row.fmt(e.0)))
-            .collect::<Vec<_>>();
+        while self.pending.len() < self.ahead {
+            let path = match self.paths.next() {
+                Some(path) => path,
+                None => break,
+            };
+            let worker = (self.next + self.pending.len()) % self.ahead;
 
-        if let Some(ref vec) = filters {
-            for (i, regex) in vec {
-                if !regex.is_match(&result[*i]) {
-                    return None;
-                }
+            if worker == self.workers.len() {
+                self.workers.push(PrefetchWorker::start());
             }
+
+            let _ = self.workers[worker].jobs.send(path.clone());
+
+            self.pending.push_back(path);
         }
 
-        Some(Ok(result))
+        let path = self.pending.pop_front()?;
+        let reader = self.workers[self.next % self.ahead]
+            .readers
+            .recv()
+            .unwrap_or_else(|_| {
+                Err(Error::IO(format!("Unable to read {}", path.display())))
+            });
+
+        self.next += 1;
+
+        Some((path, reader))
     }
+}
 
-    fn next_row(
-        iter: &mut dyn Iterator<Item = Row>,
-        fields: &[(usize, String)],
-        filters: &Option<HashMap<usize, Regex>>,
-    ) -> Option<Result<Vec<String>>> {
-        // while next try to find a matching row
-        for row in iter {
-            if let Some(next) = Iter::<T>::filter_map_row(row, fields, filters) {
-                return Some(next);
-            }
-        }
+impl<I> Drop for Prefetch<I> {
+    fn drop(&mut self) {
+        // dropping the channels stops the workers once their current file is read
+        let handles = self.workers.drain(..).map(|w| w.handle).collect::<Vec<_>>();
 
-        None
+        for handle in handles {
+            let _ = handle.join();
+        }
     }
+}
 
-    fn next_err(err: &mut Vec<Error>) -> Option<Result<Vec<String>>> {
-        err.pop().map(std::result::Result::Err)
+#[inline]
+fn create_parquet_row_groups_reader(
+    path: &Path,
+    row_groups: Vec<usize>,
+    page_index: bool,
+) -> Result<ParquetFileReader> {
+    let mut options = ReadOptionsBuilder::new()
+        .with_predicate(Box::new(move |_, i| row_groups.binary_search(&i).is_ok()));
+
+    if page_index {
+        options = options.with_page_index();
     }
+
+    ParquetSource::open(path)
+        .and_then(|f| SerializedFileReader::new_with_options(f, options.build()))
+        .map_err(|e| Error::Parquet(path.to_path_buf(), e))
 }
 
-impl<T> Iterator for Iter<T>
-where
-    T: Iterator<Item = Row>,
-{
-    type Item = Result<Vec<String>>;
+/// Row groups that may hold rows matching the point filters, eg: `--search id=12345`.
+///
+/// Row groups are pruned using the column chunk statistics, then using the column index
+/// of the remaining row groups when the file has one. The pages of the matching row groups
+/// are all read.
+/// Returns `None` when no row group can be skipped.
+fn matching_row_groups(
+    path: &Path,
+    reader: &ParquetFileReader,
+    filters: &[PointFilter],
+) -> Option<Vec<usize>> {
+    if filters.is_empty() {
+        return None;
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.values {
-            Either::Left(ref mut iter) => {
-                Iter::<T>::next_row(iter, &self.fields, &self.filters)
-            }
-            Either::Right(ref mut err) => Iter::<T>::next_err(err),
+    let row_groups = reader.metadata().row_groups();
+    let mut matching = (0..row_groups.len())
+        .filter(|i| row_group_pruning::row_group_may_match(&row_groups[*i], filters))
+        .collect::<Vec<_>>();
+    let indexed = matching.iter().any(|i| {
+        filters.iter().any(|f| {
+            row_groups[*i]
+                .column(f.column)
+                .column_index_offset()
+                .is_some()
+        })
+    });
+
+    // files with an unreadable column index are only pruned using statistics
+    if indexed {
+        let indexes = create_parquet_row_groups_reader(path, matching.clone(), true)
+            .ok()
+            .and_then(|r| r.metadata().page_indexes().cloned())
+            .filter(|indexes| indexes.len() == matching.len());
+
+        if let Some(indexes) = indexes {
+            matching = matching
+                .into_iter()
+                .zip(indexes.iter())
+                .filter(|(_, index)| {
+                    row_group_pruning::column_index_may_match(index, filters)
+                })
+                .map(|(i, _)| i)
+                .collect();
         }
     }
+
+    if matching.len() == row_groups.len() {
+        None
+    } else {
+        Some(matching)
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::api;
-    use chrono::NaiveDateTime;
-    use std::fs::File;
+#[inline]
+fn create_parquet_tail_reader(
+    path: &Path,
+    row_group: usize,
+) -> Result<ParquetFileReader> {
+    let options = ReadOptionsBuilder::new()
+        .with_predicate(Box::new(move |_, i| i >= row_group))
+        .build();
+
+    ParquetSource::open(path)
+        .and_then(|f| SerializedFileReader::new_with_options(f, options))
+        .map_err(|e| Error::Parquet(path.to_path_buf(), e))
+}
 
-    #[test]
-    fn test_path_to_reader() {
-        let dir = api::tests::temp_dir();
-        let path1 = dir.path().join("1.snappy.parquet");
-        let path2 = dir.path().join("2.snappy.parquet");
-        let path3 = dir.path().join("3.snappy.parquet");
-        let msg = api::tests::SimpleMessage {
-            field_int32: 1,
-            field_int64: 2,
-            field_float: 3.3,
-            field_double: 4.4,
-            field_boolean: true,
-            field_string: "5".to_string(),
-            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
-        };
+/// Whether the record api panics reading a column, it has no fields for `TIME` values.
+#[inline]
+fn is_time_column(column: &Type) -> bool {
+    matches!(
+        column.get_basic_info().converted_type(),
+        ConvertedType::TIME_MILLIS | ConvertedType::TIME_MICROS
+    )
+}
 
-        File::create(path1.clone()).unwrap();
-        File::create(path2.clone()).unwrap();
+#[inline]
+fn has_time_columns(field: &Type) -> bool {
+    if field.is_primitive() {
+        is_time_column(field)
+    } else {
+        field.get_fields().iter().any(|f| has_time_columns(f))
+    }
+}
 
-        api::tests::write_simple_messages_parquet(&path1, &[msg]);
+/// Copy of a schema without the types of `TIME` columns, their values are read as integers.
+fn plain_time_schema(field: &Type) -> parquet::errors::Result<Type> {
+    let info = field.get_basic_info();
 
-        let result1 = create_parquet_reader(&path1);
-        let result2 = create_parquet_reader(&path2);
-        let result3 = create_parquet_reader(&path3);
+    if field.is_primitive() && !is_time_column(field) {
+        return Ok(field.clone());
+    }
 
-        assert!(result1.is_ok());
-        assert!(result2.is_err());
-        assert!(result3.is_err());
+    if field.is_primitive() {
+        let mut builder =
+            Type::primitive_type_builder(field.name(), field.get_physical_type())
+                .with_repetition(info.repetition());
+
+        if info.has_id() {
+            builder = builder.with_id(info.id());
+        }
+
+        return builder.build();
     }
 
-    #[test]
-    fn test_parquet_files() {
-        let dir = api::tests::temp_dir();
-        let path1 = dir.path().join("part-1.snappy.parquet");
-        let path2 = dir.path().join("part-2.snappy.parquet");
-        let path3 = dir.path().join("_SUCCESS");
+    let mut fields = field
+        .get_fields()
+        .iter()
+        .map(|f| plain_time_schema(f).map(Arc::new))
+        .collect::<parquet::errors::Result<Vec<_>>>()?;
+    let mut builder = Type::group_type_builder(field.name())
+        .with_converted_type(info.converted_type())
+        .with_logical_type(info.logical_type())
+        .with_fields(&mut fields);
+
+    if info.has_repetition() {
+        builder = builder.with_repetition(info.repetition());
+    }
 
-        File::create(path1.clone()).unwrap();
-        File::create(path2.clone()).unwrap();
-        File::create(path3.clone()).unwrap();
+    if info.has_id() {
+        builder = builder.with_id(info.id());
+    }
 
-        let parquet_dir = ParquetFile::from(dir.path());
-        let parquet_file1 = ParquetFile::from(path1.as_path());
-        let parquet_file2 = ParquetFile::from(path2.as_path());
-        let parquet_file3 = ParquetFile::from(path3.as_path());
+    builder.build()
+}
 
-        let mut dir_vec = parquet_dir.files().collect::<Vec<_>>();
-        let file1_vec = parquet_file1.files().collect::<Vec<_>>();
-        let file2_vec = parquet_file2.files().collect::<Vec<_>>();
-        let file3_vec = parquet_file3.files().collect::<Vec<_>>();
+/// Reader of a file with `TIME` columns, using a schema without their types.
+///
+/// `Value::from_column_field` gives the integers read back their `TIME` type.
+struct PlainTimeReader {
+    reader: ParquetFileReader,
+    metadata: ParquetMetaData,
+}
 
-        dir_vec.sort();
+/// Row group of a `PlainTimeReader`.
+struct PlainTimeRowGroup<'a> {
+    reader: Box<dyn RowGroupReader + 'a>,
+    metadata: &'a RowGroupMetaData,
+}
 
-        assert_eq!(dir_vec.len(), 2);
-        assert_eq!(dir_vec, vec![path1.clone(), path2.clone()]);
+impl PlainTimeReader {
+    /// Reader of the records of a file, only files with `TIME` columns are wrapped.
+    fn records(reader: ParquetFileReader) -> Box<dyn FileReader> {
+        match Self::plain_metadata(reader.metadata()) {
+            Some(metadata) => Box::new(Self { reader, metadata }),
+            None => Box::new(reader),
+        }
+    }
 
-        assert_eq!(file1_vec.len(), 1);
-        assert_eq!(file1_vec, vec![path1]);
+    fn plain_metadata(metadata: &ParquetMetaData) -> Option<ParquetMetaData> {
+        let file = metadata.file_metadata();
 
-        assert_eq!(file2_vec.len(), 1);
-        assert_eq!(file2_vec, vec![path2]);
+        if !has_time_columns(file.schema()) {
+            return None;
+        }
 
-        assert_eq!(file3_vec.len(), 1);
-        assert_eq!(file3_vec, vec![path3]);
+        let schema = plain_time_schema(file.schema()).ok()?;
+        let descr = Arc::new(SchemaDescriptor::new(Arc::new(schema)));
+        let row_groups = metadata
+            .row_groups()
+            .iter()
+            .map(|row_group| {
+                let columns = row_group
+                    .columns()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        ColumnChunkMetaData::from_thrift(descr.column(i), c.to_thrift())
+                    })
+                    .collect::<parquet::errors::Result<Vec<_>>>()?;
+
+                RowGroupMetaData::builder(descr.clone())
+                    .set_num_rows(row_group.num_rows())
+                    .set_total_byte_size(row_group.total_byte_size())
+                    .set_column_metadata(columns)
+                    .build()
+            })
+            .collect::<parquet::errors::Result<Vec<_>>>()
+            .ok()?;
+        let file = FileMetaData::new(
+            file.version(),
+            file.num_rows(),
+            file.created_by().map(String::from),
+            file.key_value_metadata().cloned(),
+            descr,
+            file.column_orders().cloned(),
+        );
+
+        Some(ParquetMetaData::new(file, row_groups))
     }
+}
 
-    #[test]
-    fn test_get_row_fields() {
-        let dir = api::tests::temp_dir();
-        let path = dir.path().join("1.snappy.parquet");
+impl FileReader for PlainTimeReader {
+    fn metadata(&self) -> &ParquetMetaData {
+        &self.metadata
+    }
 
-        File::create(path.clone()).unwrap();
+    fn num_row_groups(&self) -> usize {
+        self.reader.num_row_groups()
+    }
 
-        let msg = api::tests::SimpleMessage {
+    fn get_row_group(
+        &self,
+        i: usize,
+    ) -> parquet::errors::Result<Box<dyn RowGroupReader + '_>> {
+        Ok(Box::new(PlainTimeRowGroup {
+            reader: self.reader.get_row_group(i)?,
+            metadata: self.metadata.row_group(i),
+        }))
+    }
+
+    fn get_row_iter(
+        &self,
+        projection: Option<Type>,
+    ) -> parquet::errors::Result<RowIter<'_>> {
+        RowIter::from_file(projection, self)
+    }
+}
+
+impl RowGroupReader for PlainTimeRowGroup<'_> {
+    fn metadata(&self) -> &RowGroupMetaData {
+        self.metadata
+    }
+
+    fn num_columns(&self) -> usize {
+        self.reader.num_columns()
+    }
+
+    fn get_column_page_reader(
+        &self,
+        i: usize,
+    ) -> parquet::errors::Result<Box<dyn PageReader>> {
+        self.reader.get_column_page_reader(i)
+    }
+
+    fn get_row_iter(
+        &self,
+        projection: Option<Type>,
+    ) -> parquet::errors::Result<RowIter<'_>> {
+        RowIter::from_row_group(projection, self)
+    }
+}
+
+/// Find the first row group and number of rows to skip
+/// in order to read the last `num` rows of a file.
+#[inline]
+fn tail_row_groups(reader: &ParquetFileReader, num: usize) -> (usize, usize, usize) {
+    let row_groups = reader.metadata().row_groups();
+    let mut first = row_groups.len();
+    let mut rows = 0;
+
+    while first > 0 && rows < num {
+        first -= 1;
+        rows += row_groups[first].num_rows() as usize;
+    }
+
+    let skip = rows.saturating_sub(num);
+
+    (first, skip, rows - skip)
+}
+
+#[inline]
+fn file_metadata_num_rows(reader: &ParquetFileReader) -> usize {
+    let metadata = reader.metadata();
+    let file_meta = metadata.file_metadata();
+
+    file_meta.num_rows() as usize
+}
+
+/// Whether no row group of the file holds rows, eg: header-only part files.
+#[inline]
+fn file_is_empty(reader: &ParquetFileReader) -> bool {
+    reader
+        .metadata()
+        .row_groups()
+        .iter()
+        .all(|g| g.num_rows() == 0)
+}
+
+/// Projection of the top level column with the smallest compressed size.
+#[inline]
+fn smallest_column_projection(reader: &ParquetFileReader) -> Option<Type> {
+    let metadata = reader.metadata();
+    let descr = metadata.file_metadata().schema_descr();
+    let root = descr.root_schema();
+    let fields = root.get_fields();
+    let mut sizes = vec![0; fields.len()];
+
+    for row_group in metadata.row_groups() {
+        for (i, column) in row_group.columns().iter().enumerate() {
+            sizes[descr.get_column_root_idx(i)] += column.compressed_size();
+        }
+    }
+
+    let index = (0..fields.len()).min_by_key(|i| sizes[*i])?;
+
+    Type::group_type_builder(root.name())
+        .with_fields(&mut vec![fields[index].clone()])
+        .build()
+        .ok()
+}
+
+/// Count rows decoding only the smallest column.
+#[inline]
+fn file_iterator_num_rows(reader: ParquetFileReader) -> usize {
+    let projection = smallest_column_projection(&reader);
+
+    let reader = PlainTimeReader::records(reader);
+    let count = reader.get_row_iter(projection).map(Iterator::count);
+
+    match count {
+        Ok(count) => count,
+        Err(_) => RowIter::from_file_into(reader).count(),
+    }
+}
+
+/// Number of rows from the metadata, counting the rows when missing.
+#[inline]
+fn file_num_rows(reader: ParquetFileReader) -> usize {
+    let meta_num_rows = file_metadata_num_rows(&reader);
+
+    if meta_num_rows > 0 {
+        return meta_num_rows;
+    }
+
+    file_iterator_num_rows(reader)
+}
+
+/// Hash of the printed schema, files with the same schema share the hash.
+#[inline]
+fn schema_hash(schema: &Type) -> String {
+    let mut printed = Vec::new();
+
+    print_schema(&mut printed, schema);
+
+    hash::to_hex(&hash::sha256(&printed))
+}
+
+#[inline]
+fn file_fingerprint(path: &Path) -> Result<Fingerprint> {
+    let reader = create_parquet_reader(path)?;
+    let schema_hash = schema_hash(reader.metadata().file_metadata().schema());
+
+    Ok(Fingerprint {
+        schema_hash,
+        num_rows: file_num_rows(reader),
+    })
+}
+
+/// Columns selected from the parquet schema.
+#[derive(Clone, Debug, Default)]
+struct Selection {
+    columns: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    flatten: bool,
+}
+
+impl Selection {
+    /// Whether a column outside of the file schema is selected, eg: a partition column.
+    fn selects(&self, name: &str) -> bool {
+        let matches = |n: &String| matches_column(n, name);
+        let selected = self.columns.as_ref().is_none_or(|c| c.iter().any(matches));
+        let excluded = self.exclude.as_ref().is_some_and(|e| e.iter().any(matches));
+
+        selected && !excluded
+    }
+}
+
+/// Value of hive partitions without a value.
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Decode the `%XX` escapes of a partition value, eg: `S%C3%A3o%20Paulo`.
+#[inline]
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+
+        match hex {
+            Some(byte) => {
+                result.push(byte);
+                i += 3;
+            }
+            None => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+/// Hive partition columns of the directories of a file below `root`,
+/// eg: `date=2024-05-01/country=BR/part-0.parquet`.
+#[inline]
+pub fn path_partitions(root: &Path, path: &Path) -> Vec<(String, Field)> {
+    path.parent()
+        .map_or_else(Vec::new, |dir| dir_partitions(root, dir))
+}
+
+/// Hive partition columns of a directory below `root`, eg: `date=2024-05-01/country=BR`.
+#[inline]
+fn dir_partitions(root: &Path, dir: &Path) -> Vec<(String, Field)> {
+    let relative = dir.strip_prefix(root).unwrap_or(dir);
+
+    relative
+        .components()
+        .filter_map(|c| {
+            let (key, value) = c.as_os_str().to_str()?.split_once('=')?;
+            let value = match value {
+                HIVE_DEFAULT_PARTITION => Field::Null,
+                _ => Field::Str(percent_decode(value)),
+            };
+
+            Some((key.to_string(), value)).filter(|_| !key.is_empty())
+        })
+        .collect()
+}
+
+/// Partitions of a file that are not columns of its schema, a file column wins over
+/// a partition with the same name.
+#[inline]
+fn file_partitions(root: &Path, path: &Path, schema: &Type) -> Vec<(String, Field)> {
+    let mut partitions = path_partitions(root, path);
+
+    partitions.retain(|(key, _)| {
+        !schema
+            .get_fields()
+            .iter()
+            .any(|f| f.name().eq_ignore_ascii_case(key))
+    });
+
+    partitions
+}
+
+/// Filter of the rows of a directory applied to its hive partition directories,
+/// partition keys that are columns of the reference file are left to the rows.
+#[derive(Clone, Debug)]
+struct PartitionFilter {
+    root: PathBuf,
+    expr: Expr,
+    columns: Vec<String>,
+}
+
+impl PartitionFilter {
+    /// Whether rows of the files below a directory could match the filter.
+    fn matches_dir(&self, dir: &Path) -> bool {
+        let partitions = dir_partitions(&self.root, dir);
+        let resolved = self.expr.resolve(&|name: &String| {
+            partitions
+                .iter()
+                .filter(|p| !self.columns.iter().any(|c| c.eq_ignore_ascii_case(&p.0)))
+                .find(|p| p.0.eq_ignore_ascii_case(name))
+                .map(|p| &p.1)
+        });
+
+        resolved.constant() != Some(false)
+    }
+}
+
+/// Which files of a directory are read, see `ParquetFile::with_discovery`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discovery {
+    /// File name suffixes, eg: `.parquet`
+    pub extensions: Vec<String>,
+
+    /// Levels of directories descended, `1` reads the files of the directory only
+    pub max_depth: Option<usize>,
+
+    pub follow_symlinks: bool,
+
+    /// Read files and directories starting with `.` or `_`, eg: `_temporary`
+    pub hidden: bool,
+
+    /// Globs of the files read, names or paths below the directory when having a `/`
+    pub include: Vec<String>,
+
+    /// Globs of the files and directories skipped
+    pub exclude: Vec<String>,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self {
+            extensions: vec![String::from(".parquet")],
+            max_depth: None,
+            follow_symlinks: false,
+            hidden: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl Discovery {
+    /// Whether an entry below the root is walked, directories only check
+    /// the hidden names and excluded globs.
+    ///
+    /// Snapshots of replaced files are never read, even with `hidden`.
+    fn accepts(&self, root: &Path, path: &Path, is_dir: bool) -> bool {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let matches = |pattern: &String| match path_glob(pattern) {
+            Some(regex) if pattern.contains('/') => regex.is_match(&relative),
+            Some(regex) => regex.is_match(name),
+            None => false,
+        };
+
+        if is_dir && name == TRASH_DIR {
+            return false;
+        }
+
+        if !self.hidden && name.starts_with(['.', '_']) {
+            return false;
+        }
+
+        if self.exclude.iter().any(matches) {
+            return false;
+        }
+
+        if is_dir {
+            return true;
+        }
+
+        let lowercase = name.to_lowercase();
+
+        self.extensions
+            .iter()
+            .any(|e| lowercase.ends_with(&e.to_lowercase()))
+            && (self.include.is_empty() || self.include.iter().any(matches))
+    }
+}
+
+/// Write a warning to stderr and the run summary, once per run.
+#[inline]
+fn warn_once(message: &str) {
+    static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+    let warned = WARNED.get_or_init(Default::default);
+    let first = warned
+        .lock()
+        .map_or(true, |mut w| w.insert(message.to_string()));
+
+    if first {
+        summary::warn(message);
+        eprintln!("{}", message);
+    }
+}
+
+/// Compile a file glob, `*` and `?` do not match `/` while `**` does.
+#[inline]
+fn path_glob(pattern: &str) -> Option<Regex> {
+    let glob = pattern
+        .split("**")
+        .map(|part| {
+            regex::escape(part)
+                .replace("\\*", "[^/]*")
+                .replace("\\?", "[^/]")
+        })
+        .collect::<Vec<_>>()
+        .join(".*")
+        // `**/` also matches no directory
+        .replace(".*/", "(?:.*/)?");
+
+    Regex::new(&format!("^{}$", glob)).ok()
+}
+
+/// Move the values to the positions of `order`, see `ParquetFile::column_order`.
+#[inline]
+fn reorder<T: Clone>(values: &mut [T], order: &[usize]) {
+    let copy = values.to_vec();
+
+    for (i, o) in order.iter().enumerate() {
+        values[i] = copy[*o].clone();
+    }
+}
+
+/// Value of each partition column of a file, partitions missing from the file are null.
+#[inline]
+fn partition_values(names: &[String], partitions: &[(String, Field)]) -> Vec<Field> {
+    names
+        .iter()
+        .map(|name| {
+            partitions
+                .iter()
+                .find(|p| p.0.eq_ignore_ascii_case(name))
+                .map_or(Field::Null, |p| p.1.clone())
+        })
+        .collect()
+}
+
+#[inline]
+fn get_row_fields(
+    reader: &ParquetFileReader,
+    selection: &Selection,
+) -> Vec<(usize, String)> {
+    get_schema_fields(reader.metadata().file_metadata().schema(), selection)
+}
+
+/// Compile a column pattern, either `/<regex>/` or a glob using `*` and `?`.
+///
+/// Returns `None` for plain column names.
+#[inline]
+fn column_pattern(name: &str) -> Option<Regex> {
+    let pattern = if name.len() > 1 && name.starts_with('/') && name.ends_with('/') {
+        name[1..name.len() - 1].to_string()
+    } else if name.contains(['*', '?']) {
+        let glob = regex::escape(name).replace("\\*", ".*").replace("\\?", ".");
+
+        format!("^{}$", glob)
+    } else {
+        return None;
+    };
+
+    Regex::new(&format!("(?i){}", pattern)).ok()
+}
+
+/// Whether a column name matches a name or pattern, see `column_pattern`.
+pub fn matches_column(pattern: &str, name: &str) -> bool {
+    match column_pattern(pattern) {
+        Some(regex) => regex.is_match(name),
+        None => pattern.eq_ignore_ascii_case(name),
+    }
+}
+
+/// Indexes of the fields matching a column name or pattern.
+#[inline]
+fn match_fields(fields: &[TypePtr], name: &str) -> Vec<usize> {
+    let enumerate = fields.iter().enumerate();
+
+    match column_pattern(name) {
+        Some(regex) => enumerate
+            .filter(|t| regex.is_match(t.1.name()))
+            .map(|t| t.0)
+            .collect(),
+        None => enumerate
+            .filter(|t| t.1.name().eq_ignore_ascii_case(name))
+            .map(|t| t.0)
+            .take(1)
+            .collect(),
+    }
+}
+
+/// Segment of a nested field path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PathSegment {
+    // Field of a group
+    Name(String),
+
+    // Element of a list
+    Index(usize),
+}
+
+/// Parse the nested part of a field path, eg: `.items[0].sku`.
+#[inline]
+fn parse_field_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut result = Vec::new();
+    let mut rest = path;
+
+    while !rest.is_empty() {
+        if let Some(tail) = rest.strip_prefix('.') {
+            let end = tail.find(['.', '[']).unwrap_or(tail.len());
+
+            if end == 0 {
+                return None;
+            }
+
+            result.push(PathSegment::Name(tail[..end].to_string()));
+            rest = &tail[end..];
+        } else if let Some(tail) = rest.strip_prefix('[') {
+            let end = tail.find(']')?;
+            let index = tail[..end].parse().ok()?;
+
+            result.push(PathSegment::Index(index));
+            rest = &tail[end + 1..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(result)
+}
+
+/// Find the top level field of a nested path, eg: `payload.items[0].sku`.
+#[inline]
+fn match_nested_field(fields: &[TypePtr], name: &str) -> Option<(usize, String)> {
+    name.match_indices(['.', '[']).find_map(|(end, _)| {
+        let index = fields
+            .iter()
+            .position(|f| f.name().eq_ignore_ascii_case(&name[..end]))?;
+
+        parse_field_path(&name[end..])?;
+
+        Some((index, format!("{}{}", fields[index].name(), &name[end..])))
+    })
+}
+
+/// Nested path of each selected field, empty for top level fields.
+#[inline]
+fn get_field_paths(
+    schema: &Type,
+    fields: &[(usize, String)],
+) -> Vec<(usize, Vec<PathSegment>)> {
+    let schema_fields = schema.get_fields();
+
+    fields
+        .iter()
+        .map(|(index, name)| {
+            let path = name
+                .strip_prefix(schema_fields[*index].name())
+                .and_then(parse_field_path)
+                .unwrap_or_default();
+
+            (*index, path)
+        })
+        .collect()
+}
+
+/// Projection of the top level fields used by the selected paths,
+/// returning the paths with their index remapped into the projection.
+#[inline]
+fn project_field_paths(
+    schema: &Type,
+    paths: Vec<(usize, Vec<PathSegment>)>,
+) -> (Option<Type>, Vec<(usize, Vec<PathSegment>)>) {
+    let fields = schema.get_fields();
+    let mut indexes = paths.iter().map(|p| p.0).collect::<Vec<_>>();
+
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    if indexes.is_empty() || indexes.len() == fields.len() {
+        return (None, paths);
+    }
+
+    let projection = Type::group_type_builder(schema.name())
+        .with_fields(&mut indexes.iter().map(|i| fields[*i].clone()).collect())
+        .build();
+
+    match projection {
+        Ok(projection) => {
+            let paths = paths
+                .into_iter()
+                .map(|(i, path)| (indexes.binary_search(&i).unwrap_or(i), path))
+                .collect();
+
+            (Some(projection), paths)
+        }
+        Err(_) => (None, paths),
+    }
+}
+
+/// Walk into groups and lists following the given path.
+#[inline]
+fn resolve_field<'a>(field: &'a Field, path: &[PathSegment]) -> Option<&'a Field> {
+    path.iter()
+        .try_fold(field, |field, segment| match (field, segment) {
+            (Field::Group(row), PathSegment::Name(name)) => row
+                .get_column_iter()
+                .find(|c| c.0.eq_ignore_ascii_case(name))
+                .map(|c| c.1),
+            (Field::ListInternal(list), PathSegment::Index(i)) => list.elements().get(*i),
+            _ => None,
+        })
+}
+
+/// Type of the elements of a list, eg: `LIST<repeated group list<element>>`
+/// or a repeated field.
+#[inline]
+fn list_element(field: &TypePtr) -> Option<TypePtr> {
+    let info = field.get_basic_info();
+
+    if info.has_repetition() && info.repetition() == Repetition::REPEATED {
+        return Some(field.clone());
+    }
+
+    if !field.is_group() || info.converted_type() != ConvertedType::LIST {
+        return None;
+    }
+
+    let repeated = field.get_fields().first()?;
+
+    match repeated.is_group() {
+        true if repeated.get_fields().len() == 1 => {
+            repeated.get_fields().first().cloned()
+        }
+        _ => Some(repeated.clone()),
+    }
+}
+
+/// Schema type of a top level field or a nested field path,
+/// eg: `payload.items[0].sku`.
+pub fn field_type(schema: &Type, name: &str) -> Option<TypePtr> {
+    let fields = schema.get_fields();
+
+    if let Some(field) = fields.iter().find(|f| f.name().eq_ignore_ascii_case(name)) {
+        return Some(field.clone());
+    }
+
+    let (index, name) = match_nested_field(fields, name)?;
+    let path = parse_field_path(&name[fields[index].name().len()..])?;
+
+    path.iter()
+        .try_fold(fields[index].clone(), |field, segment| match segment {
+            PathSegment::Name(name) if field.is_group() => field
+                .get_fields()
+                .iter()
+                .find(|f| f.name().eq_ignore_ascii_case(name))
+                .cloned(),
+            PathSegment::Name(_) => None,
+            PathSegment::Index(_) => list_element(&field),
+        })
+}
+
+/// Whether a schema type is a struct, not a list or map group.
+#[inline]
+fn is_struct(field: &Type) -> bool {
+    let info = field.get_basic_info();
+    let is_repeated = info.has_repetition() && info.repetition() == Repetition::REPEATED;
+    let is_collection = matches!(
+        info.converted_type(),
+        ConvertedType::LIST | ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE
+    );
+
+    field.is_group() && !is_repeated && !is_collection
+}
+
+/// Expand struct fields into the dotted paths of their children.
+#[inline]
+fn flatten_field(field: &Type, name: String, result: &mut Vec<String>) {
+    if !is_struct(field) {
+        result.push(name);
+
+        return;
+    }
+
+    for child in field.get_fields() {
+        flatten_field(child, format!("{}.{}", name, child.name()), result);
+    }
+}
+
+/// Whether an excluded name or pattern matches a selected field.
+#[inline]
+fn is_excluded(fields: &[TypePtr], field: &(usize, String), name: &str) -> bool {
+    match column_pattern(name) {
+        Some(regex) => regex.is_match(&field.1),
+        None => {
+            field.1.eq_ignore_ascii_case(name)
+                || fields[field.0].name().eq_ignore_ascii_case(name)
+        }
+    }
+}
+
+#[inline]
+fn get_schema_fields(schema: &Type, selection: &Selection) -> Vec<(usize, String)> {
+    let mut result = Vec::new();
+    let fields = schema.get_fields();
+
+    match &selection.columns {
+        Some(names) => {
+            for name in names {
+                let indexes = match_fields(fields, name);
+                let is_pattern = column_pattern(name).is_some();
+
+                if indexes.is_empty() && !is_pattern {
+                    result.extend(match_nested_field(fields, name));
+
+                    continue;
+                }
+
+                for index in indexes {
+                    if is_pattern && result.iter().any(|f: &(usize, String)| f.0 == index)
+                    {
+                        continue;
+                    }
+
+                    result.push((index, String::from(fields[index].name())));
+                }
+            }
+        }
+        None => {
+            for (index, field) in fields.iter().enumerate() {
+                result.push((index, String::from(field.name())));
+            }
+        }
+    }
+
+    if selection.flatten {
+        let mut flat = Vec::new();
+
+        for (index, name) in result {
+            let mut names = Vec::new();
+
+            if name == fields[index].name() {
+                flatten_field(&fields[index], name, &mut names);
+            } else {
+                names.push(name);
+            }
+
+            flat.extend(names.into_iter().map(|n| (index, n)));
+        }
+
+        result = flat;
+    }
+
+    if let Some(names) = &selection.exclude {
+        result.retain(|f| !names.iter().any(|n| is_excluded(fields, f, n)));
+    }
+
+    result
+}
+
+#[inline]
+fn field_values(
+    fields: &[Field],
+    types: &[Option<TypePtr>],
+    nested: NestedFormat,
+) -> Vec<Value> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            Value::from_column_field(f, types.get(i).and_then(Option::as_deref), nested)
+        })
+        .collect()
+}
+
+#[inline]
+fn format_values(values: Vec<Value>, format: &ValueFormat) -> Vec<String> {
+    values.into_iter().map(|v| format.format(v)).collect()
+}
+
+/// Index of the file field of each reference column, columns missing from the file are `None`.
+///
+/// Files of a directory may hold the same columns in a different order,
+/// values are emitted in the column order of the reference file.
+#[inline]
+fn get_output_columns(
+    fields: &[(usize, String)],
+    reference: Option<&[String]>,
+) -> Vec<Option<usize>> {
+    match reference {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                fields.iter().position(|f| f.1 == *name).or_else(|| {
+                    fields.iter().position(|f| f.1.eq_ignore_ascii_case(name))
+                })
+            })
+            .collect(),
+        None => (0..fields.len()).map(Some).collect(),
+    }
+}
+
+/// Columns of the batch in the order of the reference schema, missing columns are null.
+#[inline]
+fn align_batch(batch: RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    if batch.schema().fields() == schema.fields() {
+        return Ok(batch);
+    }
+
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|f| match batch.schema().index_of(f.name()) {
+            Ok(i) => batch.column(i).clone(),
+            Err(_) => new_null_array(f.data_type(), batch.num_rows()),
+        })
+        .collect();
+
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+/// Filter bound to the row fields, columns missing from the file are `None`.
+type RowFilter = Expr<Option<usize>>;
+
+/// Bind the filter columns to the row fields,
+/// appending the filter columns which are not selected.
+#[inline]
+fn get_row_filter(
+    schema: &Type,
+    fields: &mut Vec<(usize, String)>,
+    filter: &Option<Expr>,
+) -> Option<RowFilter> {
+    let position = |fields: &[(usize, String)], name: &str| {
+        fields.iter().position(|f| f.1.eq_ignore_ascii_case(name))
+    };
+
+    filter.as_ref().map(|expr| {
+        expr.map_columns(&mut |name: &String| {
+            position(fields, name).or_else(|| {
+                let selection = Selection {
+                    columns: Some(vec![name.to_string()]),
+                    ..Selection::default()
+                };
+
+                fields.extend(get_schema_fields(schema, &selection).into_iter().take(1));
+                position(fields, name)
+            })
+        })
+    })
+}
+
+#[derive(Clone)]
+pub struct ParquetFile {
+    path: PathBuf,
+    selection: Selection,
+    nested: NestedFormat,
+    filter: Option<Expr>,
+    prefetch: usize,
+    row_numbers: bool,
+    filename: bool,
+    discovery: Discovery,
+    strict: bool,
+    values: ValueFormat,
+
+    // Single file or row group read by `records`, see `file_parts`
+    part: Option<(PathBuf, Option<usize>)>,
+}
+
+impl ParquetFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            selection: Selection::default(),
+            nested: NestedFormat::Default,
+            filter: None,
+            prefetch: PREFETCH_FILES,
+            row_numbers: false,
+            filename: false,
+            discovery: Discovery::default(),
+            strict: false,
+            values: ValueFormat::default(),
+            part: None,
+        }
+    }
+
+    /// Number of footers read ahead while scanning the rows of a directory,
+    /// `0` reads each footer when its file is reached.
+    pub fn with_prefetch(self, prefetch: usize) -> Self {
+        Self { prefetch, ..self }
+    }
+
+    pub fn with_fields(self, columns: Option<Vec<String>>) -> Self {
+        let selection = Selection {
+            columns,
+            ..self.selection
+        };
+
+        Self { selection, ..self }
+    }
+
+    /// Remove the given columns from the selected fields.
+    pub fn with_exclude(self, exclude: Option<Vec<String>>) -> Self {
+        let selection = Selection {
+            exclude,
+            ..self.selection
+        };
+
+        Self { selection, ..self }
+    }
+
+    /// Expand struct fields into `parent.child` columns.
+    pub fn with_flatten(self, flatten: bool) -> Self {
+        let selection = Selection {
+            flatten,
+            ..self.selection
+        };
+
+        Self { selection, ..self }
+    }
+
+    /// Set how nested values are rendered by `iter` and `tail`.
+    pub fn with_nested(self, nested: NestedFormat) -> Self {
+        Self { nested, ..self }
+    }
+
+    /// Only read the rows matching the filter expression.
+    pub fn with_filter(self, filter: Option<Expr>) -> Self {
+        Self { filter, ..self }
+    }
+
+    /// Filter expression given to `with_filter`.
+    pub fn filter(&self) -> Option<&Expr> {
+        self.filter.as_ref()
+    }
+
+    /// Prepend a `#` field to the rows of `iter` and `values` holding the number
+    /// of the row in the files from 1, counted before filters and pruning.
+    pub fn with_row_numbers(self, row_numbers: bool) -> Self {
+        Self {
+            row_numbers,
+            ..self
+        }
+    }
+
+    /// Append a `#file` field to the rows of `iter` and `values` holding the path
+    /// of the file each row was read from.
+    pub fn with_filename(self, filename: bool) -> Self {
+        Self { filename, ..self }
+    }
+
+    /// Which files of directories are read, the `.parquet` files by default.
+    pub fn with_discovery(self, discovery: Discovery) -> Self {
+        Self { discovery, ..self }
+    }
+
+    /// Fail on unreadable files instead of skipping them with a warning.
+    pub fn with_strict(self, strict: bool) -> Self {
+        Self { strict, ..self }
+    }
+
+    /// Set how `iter` writes values, eg: the text of nulls.
+    pub fn with_value_format(self, values: ValueFormat) -> Self {
+        Self { values, ..self }
+    }
+
+    /// Result of reading the footer of a file, unreadable files of a directory
+    /// are skipped with a warning unless `with_strict` is set.
+    pub fn readable<T>(&self, result: Result<T>) -> Option<Result<T>> {
+        match result {
+            Err(e) if !self.strict && self.path.is_dir() => {
+                warn_once(&format!("warning: skipped unreadable file, {}", e));
+                None
+            }
+            result => Some(result),
+        }
+    }
+
+    /// Footer reader of each readable file, see `readable`.
+    fn readers(&self) -> impl Iterator<Item = Result<(PathBuf, ParquetFileReader)>> + '_ {
+        self.files().filter_map(move |p| {
+            let reader = create_parquet_reader(p.as_path()).map(|r| (p, r));
+
+            self.readable(reader)
+        })
+    }
+
+    /// Number of rows of the readable files, see `readable`.
+    pub fn num_rows(&self) -> Result<usize> {
+        self.file_num_rows().map(|r| r.map(|(_, rows)| rows)).sum()
+    }
+
+    /// Number of rows of each readable parquet file, see `readable`.
+    pub fn file_num_rows(&self) -> impl Iterator<Item = Result<(PathBuf, usize)>> + '_ {
+        self.fingerprints()
+            .into_iter()
+            .filter_map(move |r| self.readable(r))
+            .map(|r| r.map(|(p, f)| (p, f.num_rows)))
+    }
+
+    /// Schema hash and number of rows of each parquet file,
+    /// directories keep them in the user cache, see `FileCache`.
+    pub fn fingerprints(&self) -> Vec<Result<(PathBuf, Fingerprint)>> {
+        let mut cache = Some(self.path.as_path())
+            .filter(|p| p.is_dir())
+            .and_then(FileCache::open);
+        let fingerprints = self
+            .files()
+            .map(|p| {
+                let fingerprint = match cache.as_mut() {
+                    Some(cache) => cache.get(&p, || file_fingerprint(&p))?,
+                    None => file_fingerprint(&p)?,
+                };
+
+                Ok((p, fingerprint))
+            })
+            .collect();
+
+        // the cache only speeds up the next run, failing to write it is not an error
+        if let Some(cache) = cache {
+            let _ = cache.save();
+        }
+
+        fingerprints
+    }
+
+    /// Number of rows of each row group of each readable parquet file, see `readable`.
+    pub fn row_group_num_rows(
+        &self,
+    ) -> impl Iterator<Item = Result<(PathBuf, usize, usize)>> + '_ {
+        self.readers()
+            .map(|r| {
+                let (p, reader) = r?;
+                let row_groups = reader
+                    .metadata()
+                    .row_groups()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, g)| Ok((p.to_path_buf(), i, g.num_rows() as usize)))
+                    .collect::<Vec<_>>();
+
+                Ok(row_groups)
+            })
+            .flat_map(|r| match r {
+                Ok(vec) => vec,
+                Err(e) => vec![Err(e)],
+            })
+    }
+
+    /// Sum of the distinct count statistics of each leaf column, by column path.
+    ///
+    /// Columns missing the statistic in any row group are left out,
+    /// counts are upper bounds since values may repeat across row groups.
+    pub fn distinct_counts(&self) -> Result<HashMap<String, u64>> {
+        let mut counts = HashMap::new();
+        let mut missing = Vec::new();
+
+        for result in self.readers() {
+            let (_, reader) = result?;
+
+            for row_group in reader.metadata().row_groups() {
+                for column in row_group.columns() {
+                    let name = column.column_path().string();
+                    let distinct = column.statistics().and_then(|s| s.distinct_count());
+
+                    match distinct {
+                        Some(count) => *counts.entry(name).or_insert(0) += count,
+                        None => missing.push(name),
+                    }
+                }
+            }
+        }
+
+        for name in missing {
+            counts.remove(&name);
+        }
+
+        Ok(counts)
+    }
+
+    /// Sum of the null count statistics of each leaf column, by column path.
+    ///
+    /// Columns missing the statistics in any row group are left out.
+    pub fn null_counts(&self) -> Result<HashMap<String, u64>> {
+        let mut counts = HashMap::new();
+        let mut missing = Vec::new();
+
+        for result in self.readers() {
+            let (_, reader) = result?;
+
+            for row_group in reader.metadata().row_groups() {
+                for column in row_group.columns() {
+                    let name = column.column_path().string();
+
+                    match column.statistics() {
+                        Some(stats) => {
+                            *counts.entry(name).or_insert(0) += stats.null_count()
+                        }
+                        None => missing.push(name),
+                    }
+                }
+            }
+        }
+
+        for name in missing {
+            counts.remove(&name);
+        }
+
+        Ok(counts)
+    }
+
+    /// Path and reader of the file describing the schema, the first file with rows.
+    ///
+    /// Empty files often carry a placeholder schema,
+    /// they are only used when every file is empty, unreadable files never are.
+    fn reference_file(&self) -> Result<(PathBuf, ParquetFileReader)> {
+        let mut first = None;
+
+        for path in self.all_files() {
+            let reader = match self.readable(create_parquet_reader(path.as_path())) {
+                Some(reader) => reader?,
+                None => continue,
+            };
+
+            if !file_is_empty(&reader) {
+                return Ok((path, reader));
+            }
+
+            if first.is_none() {
+                first = Some((path, reader));
+            }
+        }
+
+        first.ok_or_else(|| Error::from(self.path.to_path_buf()))
+    }
+
+    /// Paths of the files without rows, unreadable files are left out.
+    pub fn empty_files(&self) -> Vec<PathBuf> {
+        self.files()
+            .filter(|p| {
+                create_parquet_reader(p.as_path())
+                    .map(|r| file_is_empty(&r))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Selected hive partition columns of a directory, named by the path of its reference file.
+    fn partition_names(&self) -> Vec<String> {
+        if !self.path.is_dir() {
+            return vec![];
+        }
+
+        let (path, reader) = match self.reference_file() {
+            Ok(reference) => reference,
+            Err(_) => return vec![],
+        };
+        let schema = reader.metadata().file_metadata().schema();
+
+        file_partitions(&self.path, &path, schema)
+            .into_iter()
+            .map(|p| p.0)
+            .filter(|name| self.selection.selects(name))
+            .collect()
+    }
+
+    /// Order of the selected file and partition columns following the selected columns,
+    /// eg: `-c date,country,id`, `None` when they are emitted as read.
+    ///
+    /// Partition columns are emitted after the file columns when no column is selected.
+    fn column_order(&self) -> Option<Vec<usize>> {
+        let names = self.selection.columns.as_ref()?;
+        let partitions = Some(self.partition_names()).filter(|p| !p.is_empty())?;
+        let (_, reader) = self.reference_file().ok()?;
+        let schema_fields = reader.metadata().file_metadata().schema().get_fields();
+        let fields = get_row_fields(&reader, &self.selection);
+        let mut last = 0;
+
+        // file columns are already in the selection order
+        let mut keys = fields
+            .iter()
+            .map(|f| {
+                let key = names.iter().position(|n| is_excluded(schema_fields, f, n));
+
+                last = last.max(key.unwrap_or(last));
+                last
+            })
+            .collect::<Vec<_>>();
+
+        keys.extend(partitions.iter().map(|p| {
+            names
+                .iter()
+                .position(|n| matches_column(n, p))
+                .unwrap_or(names.len())
+        }));
+
+        let mut order = (0..keys.len()).collect::<Vec<_>>();
+
+        order.sort_by_key(|i| keys[*i]);
+
+        Some(order)
+    }
+
+    /// Field names every file of a directory is aligned to, `None` for a single file.
+    fn reference_names(&self) -> Option<Vec<String>> {
+        if !self.path.is_dir() {
+            return None;
+        }
+
+        let (_, reader) = self.reference_file().ok()?;
+        let fields = get_row_fields(&reader, &self.selection);
+
+        Some(fields.into_iter().map(|e| e.1).collect())
+    }
+
+    pub fn field_names(&self) -> Result<Vec<String>> {
+        let (_, reader) = self.reference_file()?;
+        let fields = get_row_fields(&reader, &self.selection);
+        let number = Some(String::from(ROW_NUMBER_FIELD)).filter(|_| self.row_numbers);
+        let filename = Some(String::from(FILENAME_FIELD)).filter(|_| self.filename);
+        let mut columns = fields
+            .into_iter()
+            .map(|e| e.1)
+            .chain(self.partition_names())
+            .collect::<Vec<_>>();
+
+        if let Some(order) = self.column_order() {
+            reorder(&mut columns, &order);
+        }
+
+        Ok(number.into_iter().chain(columns).chain(filename).collect())
+    }
+
+    /// Schema type of each selected field, `None` for nested field paths.
+    fn field_types(&self) -> Vec<Option<TypePtr>> {
+        let (_, reader) = match self.reference_file() {
+            Ok(reference) => reference,
+            Err(_) => return vec![],
+        };
+        let schema = reader.metadata().file_metadata().schema();
+        let schema_fields = schema.get_fields();
+        let number = Some(None).filter(|_| self.row_numbers);
+        let filename = Some(None).filter(|_| self.filename);
+
+        let mut columns = get_row_fields(&reader, &self.selection)
+            .into_iter()
+            .map(|(index, name)| {
+                Some(schema_fields[index].clone()).filter(|f| f.name() == name)
+            })
+            .chain(self.partition_names().into_iter().map(|_| None))
+            .collect::<Vec<_>>();
+
+        if let Some(order) = self.column_order() {
+            reorder(&mut columns, &order);
+        }
+
+        number.into_iter().chain(columns).chain(filename).collect()
+    }
+
+    pub fn schema(&self) -> Result<Type> {
+        let (_, reader) = self.reference_file()?;
+
+        Ok(reader.metadata().file_metadata().schema().clone())
+    }
+
+    /// Key-value metadata of the footer, keys without a value are left out.
+    pub fn footer_metadata(&self) -> Result<HashMap<String, String>> {
+        let (_, reader) = self.reference_file()?;
+        let metadata = reader.metadata().file_metadata().key_value_metadata();
+
+        Ok(metadata
+            .into_iter()
+            .flatten()
+            .filter_map(|kv| kv.value.clone().map(|v| (kv.key.clone(), v)))
+            .collect())
+    }
+
+    /// Arrow schema of the batches, available even when no file holds rows.
+    pub fn arrow_schema(&self) -> Result<SchemaRef> {
+        let (path, reader) = self.reference_file()?;
+        let file_metadata = reader.metadata().file_metadata();
+        let descr = file_metadata.schema_descr();
+        let fields = get_schema_fields(descr.root_schema(), &self.selection);
+        let mask = ProjectionMask::roots(descr, fields.iter().map(|f| f.0));
+        let schema = parquet_to_arrow_schema_by_columns(
+            descr,
+            mask,
+            file_metadata.key_value_metadata(),
+        )
+        .map_err(|e| Error::Parquet(path, e))?;
+
+        Ok(Arc::new(schema))
+    }
+
+    /// Schema of each readable parquet file, see `readable`.
+    pub fn file_schemas(&self) -> impl Iterator<Item = Result<(PathBuf, Type)>> + '_ {
+        self.readers().map(|r| {
+            let (p, reader) = r?;
+            let schema = reader.metadata().file_metadata().schema().clone();
+
+            Ok((p, schema))
+        })
+    }
+
+    /// Iterate over the selected fields of each row, formatted as strings,
+    /// see `with_value_format`.
+    pub fn iter(&self) -> impl Iterator<Item = Result<Vec<String>>> + '_ {
+        self.values()
+            .map(move |r| r.map(|v| format_values(v, &self.values)))
+    }
+
+    /// Iterate over the typed values of the selected fields of each row.
+    pub fn values(&self) -> impl Iterator<Item = Result<Vec<Value>>> + '_ {
+        self.to_values(self.records())
+    }
+
+    /// Typed values of rows read by `records`, eg: once kept by a check of their fields.
+    pub fn to_values<'a, I>(
+        &self,
+        records: I,
+    ) -> impl Iterator<Item = Result<Vec<Value>>> + 'a
+    where
+        I: Iterator<Item = Result<Vec<Field>>> + 'a,
+    {
+        let nested = self.nested;
+        let types = self.field_types();
+
+        records.map(move |r| r.map(|vec| field_values(&vec, &types, nested)))
+    }
+
+    /// Iterate over the selected fields of each row, without formatting.
+    pub fn records(&self) -> impl Iterator<Item = Result<Vec<Field>>> + '_ {
+        let ahead = if self.path.is_dir() { self.prefetch } else { 0 };
+        let part = self.part.as_ref().and_then(|p| p.1);
+        let files = match &self.part {
+            Some((path, _)) => Either::Left(std::iter::once(path.clone())),
+            None => Either::Right(self.files()),
+        };
+
+        if progress::is_enabled() && self.part.is_none() {
+            progress::add_files(self.files());
+        }
+
+        let iter = Prefetch::new(files, ahead);
+        let selection = &self.selection;
+        let field_filter = &self.filter;
+        let reference = self.reference_names();
+        let partition_names = self.partition_names();
+        let order = self.column_order();
+        let root = &self.path;
+        let row_numbers = self.row_numbers;
+        let filename = self.filename;
+        let mut offset = 0;
+
+        iter.filter_map(move |(p, reader)| Some((p, self.readable(reader)?)))
+            .map(move |(p, reader)| {
+                let reader = reader?;
+                let first_row = offset;
+
+                offset += reader.metadata().file_metadata().num_rows() as u64;
+
+                let mut fields = get_row_fields(&reader, selection);
+                let columns = get_output_columns(&fields, reference.as_deref());
+                let schema = reader.metadata().file_metadata().schema();
+                let partitions = file_partitions(root, &p, schema);
+                let partition_filter = field_filter.as_ref().map(|expr| {
+                    expr.resolve(&|name: &String| {
+                        partitions
+                            .iter()
+                            .find(|p| p.0.eq_ignore_ascii_case(name))
+                            .map(|p| &p.1)
+                    })
+                });
+                let filter = get_row_filter(schema, &mut fields, &partition_filter);
+                let values = partition_values(&partition_names, &partitions);
+                let named_filters = filter
+                    .iter()
+                    .flat_map(RowFilter::conjunction)
+                    .filter_map(|(i, predicate)| {
+                        Some((fields[(*i)?].1.as_str(), predicate))
+                    })
+                    .collect::<Vec<_>>();
+                let schema_descr = reader.metadata().file_metadata().schema_descr();
+                let point_filters =
+                    row_group_pruning::point_filters(schema_descr, &named_filters);
+                let num_row_groups = reader.metadata().num_row_groups();
+                let matching = matching_row_groups(p.as_path(), &reader, &point_filters);
+                let (total, matching) = match part {
+                    Some(i) => {
+                        let matching = matching.map_or_else(
+                            || vec![i],
+                            |m| m.into_iter().filter(|g| *g == i).collect(),
+                        );
+
+                        (1, Some(matching))
+                    }
+                    None => (num_row_groups, matching),
+                };
+                // a part only accounts for the bytes of its row group
+                let size = match part {
+                    Some(i) => reader.metadata().row_group(i).compressed_size() as u64,
+                    None => std::fs::metadata(&p).map_or(0, |m| m.len()),
+                };
+                let last = part.is_none_or(|i| i + 1 == num_row_groups);
+                let rows = reader
+                    .metadata()
+                    .row_groups()
+                    .iter()
+                    .map(|g| g.num_rows() as u64)
+                    .collect::<Vec<_>>();
+                let numbers = RowNumbers::new(first_row, &rows, matching.as_deref());
+                let reader = match matching {
+                    Some(row_groups) => {
+                        create_parquet_row_groups_reader(p.as_path(), row_groups, false)?
+                    }
+                    None => reader,
+                };
+
+                summary::add_row_groups(
+                    total,
+                    total - reader.metadata().num_row_groups(),
+                );
+
+                let row_groups = reader
+                    .metadata()
+                    .row_groups()
+                    .iter()
+                    .map(|g| (g.num_rows() as usize, g.compressed_size() as u64))
+                    .collect::<Vec<_>>();
+                let schema = reader.metadata().file_metadata().schema();
+                let paths = get_field_paths(schema, &fields);
+                let (projection, paths) = project_field_paths(schema, paths);
+                let row_iter = RowIter::from_file_into(PlainTimeReader::records(reader))
+                    .project(projection)
+                    .map_err(|e| Error::Parquet(p.to_path_buf(), e))?;
+                // rows read so far, the last one read is the row returned by the filter
+                let position = Rc::new(Cell::new(0));
+                let read = Rc::clone(&position);
+                let row_iter = progress::track(row_iter, size, row_groups)
+                    .ending_file(last)
+                    .inspect(move |_| {
+                        read.set(read.get() + 1);
+                        summary::add_rows_scanned(1);
+                    });
+                let iterator: Iter<_> = Iter::new(row_iter, paths, columns, filter);
+                let name = p.display().to_string();
+                let order = order.clone();
+                let iterator = iterator.map(move |row| {
+                    let mut row = row?;
+
+                    if row_numbers {
+                        let number = numbers.number(position.get() - 1);
+
+                        row.insert(0, Field::ULong(number + 1));
+                    }
+
+                    row.extend(values.iter().cloned());
+
+                    if let Some(order) = &order {
+                        let start = row_numbers as usize;
+
+                        reorder(&mut row[start..start + order.len()], order);
+                    }
+
+                    if filename {
+                        row.push(Field::Str(name.to_string()));
+                    }
+
+                    Ok(row)
+                });
+
+                Ok(iterator)
+            })
+            .flat_map(|r| match r {
+                Ok(iter) => Either::Left(iter),
+                Err(e) => Either::Right(std::iter::once(Err(e))),
+            })
+    }
+
+    /// Iterate over the last `num` rows.
+    ///
+    /// Only the trailing row groups of the trailing files are decoded,
+    /// using the row group metadata to find where to start reading.
+    pub fn tail(&self, num: usize) -> impl Iterator<Item = Result<Vec<Value>>> + '_ {
+        let nested = self.nested;
+        let types = self.field_types();
+
+        self.tail_records(num)
+            .map(move |r| r.map(|vec| field_values(&vec, &types, nested)))
+    }
+
+    fn tail_records(&self, num: usize) -> impl Iterator<Item = Result<Vec<Field>>> + '_ {
+        let mut files = self.files().collect::<Vec<_>>();
+        let mut selected = Vec::new();
+        let mut remaining = num;
+
+        while remaining > 0 {
+            let path = match files.pop() {
+                Some(path) => path,
+                None => break,
+            };
+
+            match self.readable(create_parquet_reader(path.as_path())) {
+                Some(Ok(reader)) => {
+                    let (first, skip, rows) = tail_row_groups(&reader, remaining);
+
+                    remaining -= rows;
+
+                    selected.push(Ok((path, first, skip)));
+                }
+                Some(Err(e)) => selected.push(Err(e)),
+                None => continue,
+            }
+        }
+
+        let selection = &self.selection;
+        let reference = self.reference_names();
+        let partition_names = self.partition_names();
+        let order = self.column_order();
+        let root = &self.path;
+
+        selected.reverse();
+        selected
+            .into_iter()
+            .map(move |r| {
+                let (path, first, skip) = r?;
+                let reader = create_parquet_tail_reader(path.as_path(), first)?;
+                let fields = get_row_fields(&reader, selection);
+                let columns = get_output_columns(&fields, reference.as_deref());
+                let schema = reader.metadata().file_metadata().schema();
+                let paths = get_field_paths(schema, &fields);
+                let partitions = file_partitions(root, &path, schema);
+                let values = partition_values(&partition_names, &partitions);
+                let row_iter =
+                    RowIter::from_file_into(PlainTimeReader::records(reader)).skip(skip);
+                let iterator: Iter<_> = Iter::new(row_iter, paths, columns, None);
+                let order = order.clone();
+
+                Ok(iterator.map(move |row| {
+                    row.map(|mut row| {
+                        row.extend(values.iter().cloned());
+
+                        if let Some(order) = &order {
+                            reorder(&mut row, order);
+                        }
+
+                        row
+                    })
+                }))
+            })
+            .flat_map(|r| match r {
+                Ok(iter) => Either::Left(iter),
+                Err(e) => Either::Right(Iter::<std::iter::Empty<Row>>::err(e)),
+            })
+    }
+
+    /// Iterate over the selected fields as arrow record batches.
+    ///
+    /// Columns follow the order of the reference file, filters are not applied.
+    /// Nested field paths select their whole top level column.
+    pub fn batches(
+        &self,
+        batch_size: usize,
+    ) -> impl Iterator<Item = Result<RecordBatch>> + '_ {
+        let selection = &self.selection;
+        let reference = Some(&self.path)
+            .filter(|p| p.is_dir())
+            .and_then(|_| self.arrow_schema().ok());
+
+        self.files()
+            .map(move |p| {
+                let file = ParquetSource::open(p.as_path())
+                    .map_err(|e| Error::Parquet(p.to_path_buf(), e))?;
+                let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+                    .map_err(|e| Error::Parquet(p.to_path_buf(), e))?;
+                let schema = builder.parquet_schema();
+                let fields = get_schema_fields(schema.root_schema(), selection);
+                let mask = ProjectionMask::roots(schema, fields.iter().map(|f| f.0));
+
+                builder
+                    .with_projection(mask)
+                    .with_batch_size(batch_size)
+                    .build()
+                    .map_err(|e| Error::Parquet(p.to_path_buf(), e))
+            })
+            .flat_map(move |r| match r {
+                Ok(reader) => {
+                    let reference = reference.clone();
+
+                    let reader = reader.inspect(|b| {
+                        if let Ok(batch) = b {
+                            summary::add_rows_scanned(batch.num_rows());
+                        }
+                    });
+
+                    Either::Left(reader.map(move |b| match (b, &reference) {
+                        (Ok(batch), Some(schema)) => align_batch(batch, schema),
+                        (b, _) => b.map_err(Error::from),
+                    }))
+                }
+                Err(e) => Either::Right(std::iter::once(Err(e))),
+            })
+    }
+
+    /// Split the scan into its files, each path comes with a part reading only that file
+    /// with the same selection and filter, columns stay aligned to the other files.
+    ///
+    /// The files are added to the progress totals.
+    pub fn file_parts(&self) -> Vec<(PathBuf, ParquetFile)> {
+        if progress::is_enabled() {
+            progress::add_files(self.files());
+        }
+
+        self.files()
+            .map(|path| {
+                let part = Self {
+                    part: Some((path.clone(), None)),
+                    ..self.clone()
+                };
+
+                (path, part)
+            })
+            .collect()
+    }
+
+    /// Split the scan into its row groups, each part reads a single row group
+    /// with the same selection and filter, eg: to read the row groups in parallel.
+    ///
+    /// The files are added to the progress totals, row numbers are relative to each file.
+    pub fn row_group_parts(&self) -> Result<Vec<ParquetFile>> {
+        let mut parts = Vec::new();
+
+        if progress::is_enabled() {
+            progress::add_files(self.files());
+        }
+
+        for result in self.readers() {
+            let (path, reader) = result?;
+
+            for i in 0..reader.metadata().num_row_groups() {
+                parts.push(Self {
+                    part: Some((path.clone(), Some(i))),
+                    ..self.clone()
+                });
+            }
+        }
+
+        Ok(parts)
+    }
+
+    /// Filter of the partition directories of a directory, see `files`.
+    fn partition_filter(&self) -> Option<PartitionFilter> {
+        let expr = self.filter.clone()?;
+
+        if !self.path.is_dir() {
+            return None;
+        }
+
+        let columns = match self.reference_file() {
+            Ok((_, reader)) => reader
+                .metadata()
+                .file_metadata()
+                .schema()
+                .get_fields()
+                .iter()
+                .map(|f| f.name().to_string())
+                .collect(),
+            Err(_) => return None,
+        };
+
+        Some(PartitionFilter {
+            root: self.path.clone(),
+            expr,
+            columns,
+        })
+    }
+
+    /// Path of each parquet file.
+    ///
+    /// Hive partition directories the filter can not match are skipped without reading
+    /// their files, eg: `date=2024-04-30` when searching `date=2024-05-01`.
+    pub fn files(&self) -> impl Iterator<Item = PathBuf> {
+        let filter = self.partition_filter();
+
+        self.walk_files(filter)
+    }
+
+    /// Path of each parquet file, including the ones of pruned partitions.
+    fn all_files(&self) -> impl Iterator<Item = PathBuf> {
+        self.walk_files(None)
+    }
+
+    fn walk_files(
+        &self,
+        filter: Option<PartitionFilter>,
+    ) -> impl Iterator<Item = PathBuf> {
+        if http::is_url(&self.path) {
+            return Either::Left(std::iter::once(self.path.clone()));
+        }
+
+        let is_file = self.path.is_file();
+        let root = self.path.clone();
+        let discovery = self.discovery.clone();
+        let is_parquet = move |entry: &DirEntry| {
+            let is_dir = entry.file_type().is_dir();
+
+            if entry.depth() == 0 {
+                return true;
+            }
+
+            if !discovery.accepts(&root, entry.path(), is_dir) {
+                return false;
+            }
+
+            // accept partition directories the filter could match
+            !is_dir || filter.as_ref().is_none_or(|f| f.matches_dir(entry.path()))
+        };
+        let mut walk =
+            WalkDir::new(&self.path).follow_links(self.discovery.follow_symlinks);
+
+        if let Some(depth) = self.discovery.max_depth {
+            walk = walk.max_depth(depth);
+        }
+
+        // directories come before their contents so that pruned ones are not listed
+        let files = walk
+            .into_iter()
+            .filter_entry(move |e| is_file || is_parquet(e))
+            .filter_map(|r| {
+                r.map_err(|e| {
+                    warn_once(&format!("warning: skipped unreadable path, {}", e))
+                })
+                .ok()
+            })
+            .map(DirEntry::into_path)
+            .filter(|p| p.is_file());
+
+        Either::Right(files)
+    }
+}
+
+impl From<&Path> for ParquetFile {
+    fn from(path: &Path) -> Self {
+        ParquetFile::new(path.to_path_buf())
+    }
+}
+
+impl From<(&Path, Option<Vec<String>>)> for ParquetFile {
+    fn from(tuple: (&Path, Option<Vec<String>>)) -> Self {
+        ParquetFile::from(tuple.0).with_fields(tuple.1)
+    }
+}
+
+struct Iter<T> {
+    fields: Vec<(usize, Vec<PathSegment>)>,
+    values: Either<T, Vec<Error>>,
+    columns: Vec<Option<usize>>,
+    filter: Option<RowFilter>,
+}
+
+impl<T> Iter<T>
+where
+    T: Iterator<Item = Row>,
+{
+    /// Iterator over the `columns` fields of the matching rows,
+    /// the remaining fields are only read by the filter.
+    fn new(
+        values: T,
+        fields: Vec<(usize, Vec<PathSegment>)>,
+        columns: Vec<Option<usize>>,
+        filter: Option<RowFilter>,
+    ) -> Self {
+        Self {
+            values: Either::Left(values),
+            columns,
+            filter,
+            fields,
+        }
+    }
+
+    fn err(error: Error) -> Self {
+        Self {
+            values: Either::Right(vec![error]),
+            columns: vec![],
+            filter: None,
+            fields: vec![],
+        }
+    }
+
+    fn filter_map_row(
+        row: Row,
+        fields: &[(usize, Vec<PathSegment>)],
+        columns: &[Option<usize>],
+        filter: &Option<RowFilter>,
+    ) -> Option<Result<Vec<Field>>> {
+        let row_columns = row.get_column_iter().map(|c| c.1).collect::<Vec<_>>();
+        let resolve =
+            |e: &(usize, Vec<PathSegment>)| resolve_field(row_columns[e.0], &e.1);
+        let value = |i: &Option<usize>| {
+            i.and_then(|i| resolve(&fields[i])).unwrap_or(&Field::Null)
+        };
+
+        // only the filter columns are resolved until the row matches
+        if let Some(ref expr) = filter {
+            if !expr.is_match(&value) {
+                return None;
+            }
+        }
+
+        let result = columns.iter().map(|i| value(i).clone()).collect::<Vec<_>>();
+
+        Some(Ok(result))
+    }
+
+    fn next_row(
+        iter: &mut dyn Iterator<Item = Row>,
+        fields: &[(usize, Vec<PathSegment>)],
+        columns: &[Option<usize>],
+        filter: &Option<RowFilter>,
+    ) -> Option<Result<Vec<Field>>> {
+        // while next try to find a matching row
+        for row in iter {
+            if let Some(next) = Iter::<T>::filter_map_row(row, fields, columns, filter) {
+                return Some(next);
+            }
+        }
+
+        None
+    }
+
+    fn next_err(err: &mut Vec<Error>) -> Option<Result<Vec<Field>>> {
+        err.pop().map(std::result::Result::Err)
+    }
+}
+
+impl<T> Iterator for Iter<T>
+where
+    T: Iterator<Item = Row>,
+{
+    type Item = Result<Vec<Field>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.values {
+            Either::Left(ref mut iter) => {
+                Iter::<T>::next_row(iter, &self.fields, &self.columns, &self.filter)
+            }
+            Either::Right(ref mut err) => Iter::<T>::next_err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::filter::Predicate;
+    use chrono::NaiveDateTime;
+    use std::fs::{self, File};
+
+    fn row_fields(
+        reader: &ParquetFileReader,
+        columns: &Option<Vec<String>>,
+        exclude: &Option<Vec<String>>,
+    ) -> Vec<(usize, String)> {
+        let selection = Selection {
+            columns: columns.clone(),
+            exclude: exclude.clone(),
+            flatten: false,
+        };
+
+        get_row_fields(reader, &selection)
+    }
+
+    #[test]
+    fn test_path_to_reader() {
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.snappy.parquet");
+        let path2 = dir.path().join("2.snappy.parquet");
+        let path3 = dir.path().join("3.snappy.parquet");
+        let msg = api::tests::SimpleMessage {
+            field_int32: 1,
+            field_int64: 2,
+            field_float: 3.3,
+            field_double: 4.4,
+            field_boolean: true,
+            field_string: "5".to_string(),
+            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
+        };
+
+        File::create(path1.clone()).unwrap();
+        File::create(path2.clone()).unwrap();
+
+        api::tests::write_simple_messages_parquet(&path1, &[msg]);
+
+        let result1 = create_parquet_reader(&path1);
+        let result2 = create_parquet_reader(&path2);
+        let result3 = create_parquet_reader(&path3);
+
+        assert!(result1.is_ok());
+        assert!(result2.is_err());
+        assert!(result3.is_err());
+    }
+
+    #[test]
+    fn test_parquet_files() {
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("part-1.snappy.parquet");
+        let path2 = dir.path().join("part-2.snappy.parquet");
+        let path3 = dir.path().join("_SUCCESS");
+
+        File::create(path1.clone()).unwrap();
+        File::create(path2.clone()).unwrap();
+        File::create(path3.clone()).unwrap();
+
+        let parquet_dir = ParquetFile::from(dir.path());
+        let parquet_file1 = ParquetFile::from(path1.as_path());
+        let parquet_file2 = ParquetFile::from(path2.as_path());
+        let parquet_file3 = ParquetFile::from(path3.as_path());
+
+        let mut dir_vec = parquet_dir.files().collect::<Vec<_>>();
+        let file1_vec = parquet_file1.files().collect::<Vec<_>>();
+        let file2_vec = parquet_file2.files().collect::<Vec<_>>();
+        let file3_vec = parquet_file3.files().collect::<Vec<_>>();
+
+        dir_vec.sort();
+
+        assert_eq!(dir_vec.len(), 2);
+        assert_eq!(dir_vec, vec![path1.clone(), path2.clone()]);
+
+        assert_eq!(file1_vec.len(), 1);
+        assert_eq!(file1_vec, vec![path1]);
+
+        assert_eq!(file2_vec.len(), 1);
+        assert_eq!(file2_vec, vec![path2]);
+
+        assert_eq!(file3_vec.len(), 1);
+        assert_eq!(file3_vec, vec![path3]);
+    }
+
+    #[test]
+    fn test_get_row_fields() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("1.snappy.parquet");
+
+        File::create(path.clone()).unwrap();
+
+        let msg = api::tests::SimpleMessage {
             field_int32: 1,
             field_int64: 2,
             field_float: 3.3,
@@ -397,138 +2456,793 @@ mod tests {
             field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
         };
 
-        api::tests::write_simple_messages_parquet(&path, &[msg]);
+        api::tests::write_simple_messages_parquet(&path, &[msg]);
+
+        let reader = create_parquet_reader(&path).unwrap();
+        let result1 = row_fields(&reader, &None, &None);
+        let result2 = row_fields(
+            &reader,
+            &Some(vec![
+                String::from("field_timestamp"),
+                String::from("FIELD_INT64"),
+                String::from("field_int32"),
+            ]),
+            &None,
+        );
+
+        assert_eq!(result1.len(), 7);
+        assert_eq!(
+            result1,
+            vec![
+                (0, String::from("field_int32")),
+                (1, String::from("field_int64")),
+                (2, String::from("field_float")),
+                (3, String::from("field_double")),
+                (4, String::from("field_string")),
+                (5, String::from("field_boolean")),
+                (6, String::from("field_timestamp"))
+            ]
+        );
+
+        assert_eq!(result2.len(), 3);
+        assert_eq!(
+            result2,
+            vec![
+                (6, String::from("field_timestamp")),
+                (1, String::from("field_int64")),
+                (0, String::from("field_int32"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_row_fields_exclude() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("1.snappy.parquet");
+        let msgs = api::tests::create_simple_messages(1);
+
+        api::tests::write_simple_messages_parquet(&path, &msgs);
 
         let reader = create_parquet_reader(&path).unwrap();
-        let result1 = get_row_fields(&reader, &None);
-        let result2 = get_row_fields(
+        let result1 = row_fields(
             &reader,
+            &None,
             &Some(vec![
-                String::from("field_timestamp"),
-                String::from("FIELD_INT64"),
+                String::from("FIELD_FLOAT"),
+                String::from("field_double"),
+                String::from("field_unknown"),
+            ]),
+        );
+        let result2 = row_fields(
+            &reader,
+            &Some(vec![
+                String::from("field_int64"),
                 String::from("field_int32"),
             ]),
+            &Some(vec![String::from("field_int64")]),
         );
 
-        assert_eq!(result1.len(), 7);
         assert_eq!(
             result1,
             vec![
                 (0, String::from("field_int32")),
                 (1, String::from("field_int64")),
-                (2, String::from("field_float")),
-                (3, String::from("field_double")),
                 (4, String::from("field_string")),
                 (5, String::from("field_boolean")),
                 (6, String::from("field_timestamp"))
             ]
         );
 
-        assert_eq!(result2.len(), 3);
+        assert_eq!(result2, vec![(0, String::from("field_int32"))]);
+    }
+
+    #[test]
+    fn test_get_row_fields_patterns() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("1.snappy.parquet");
+        let msgs = api::tests::create_simple_messages(1);
+
+        api::tests::write_simple_messages_parquet(&path, &msgs);
+
+        let reader = create_parquet_reader(&path).unwrap();
+        let names =
+            |vec: Vec<(usize, String)>| vec.into_iter().map(|f| f.1).collect::<Vec<_>>();
+
+        let glob = row_fields(&reader, &Some(vec![String::from("FIELD_INT*")]), &None);
+        let regex = row_fields(
+            &reader,
+            &Some(vec![
+                String::from("field_string"),
+                String::from("/^field_(string|boolean)$/"),
+            ]),
+            &None,
+        );
+        let wildcard =
+            row_fields(&reader, &Some(vec![String::from("field_int??")]), &None);
+        let exclude = row_fields(
+            &reader,
+            &None,
+            &Some(vec![
+                String::from("field_int*"),
+                String::from("/^field_.*e$/"),
+            ]),
+        );
+
+        assert_eq!(names(glob), vec!["field_int32", "field_int64"]);
+        assert_eq!(names(regex), vec!["field_string", "field_boolean"]);
+        assert_eq!(names(wildcard), vec!["field_int32", "field_int64"]);
+        assert_eq!(
+            names(exclude),
+            vec![
+                "field_float",
+                "field_string",
+                "field_boolean",
+                "field_timestamp"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_type() {
+        let path = api::tests::temp_file("nested", ".parquet");
+
+        api::tests::write_json_parquet(
+            path.path(),
+            &[r#"{"id": 1, "payload": {"city": "x", "items": [{"sku": "a"}]}}"#],
+        );
+
+        let schema = ParquetFile::from(path.path()).schema().unwrap();
+        let name =
+            |column: &str| field_type(&schema, column).map(|f| f.name().to_string());
+
+        assert_eq!(name("id"), Some(String::from("id")));
+        assert_eq!(name("payload.city"), Some(String::from("city")));
+        assert_eq!(name("PAYLOAD.City"), Some(String::from("city")));
+        assert_eq!(name("payload.items[0].sku"), Some(String::from("sku")));
+        assert_eq!(name("payload.missing"), None);
+        assert_eq!(name("id[0]"), None);
+        assert_eq!(name("id.a"), None);
+    }
+
+    #[test]
+    fn test_parse_field_path() {
+        assert_eq!(parse_field_path(""), Some(vec![]));
+        assert_eq!(
+            parse_field_path(".items[0].sku"),
+            Some(vec![
+                PathSegment::Name(String::from("items")),
+                PathSegment::Index(0),
+                PathSegment::Name(String::from("sku")),
+            ])
+        );
+        assert_eq!(parse_field_path("..sku"), None);
+        assert_eq!(parse_field_path("[x]"), None);
+        assert_eq!(parse_field_path("[0"), None);
+    }
+
+    #[test]
+    fn test_reader_nested_fields() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("nested.parquet");
+
+        api::tests::write_json_parquet(
+            &path,
+            &[
+                r#"{"id": 1, "address": {"city": "Lisbon"}, "items": [{"sku": "a1"}, {"sku": "a2"}]}"#,
+                r#"{"id": 2, "address": {"city": "Porto"}, "items": []}"#,
+            ],
+        );
+
+        let fields = vec![
+            String::from("id"),
+            String::from("ADDRESS.city"),
+            String::from("items[1].sku"),
+            String::from("address.unknown"),
+            String::from("unknown.city"),
+        ];
+        let parquet = ParquetFile::from(path.as_path()).with_fields(Some(fields));
+        let rows = parquet.iter().map(|r| r.unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(
+            parquet.field_names(),
+            Ok(vec![
+                String::from("id"),
+                String::from("address.city"),
+                String::from("items[1].sku"),
+                String::from("address.unknown"),
+            ])
+        );
+        assert_eq!(
+            rows,
+            vec![
+                vec!["1", "\"Lisbon\"", "\"a2\"", "null"],
+                vec!["2", "\"Porto\"", "null", "null"],
+            ]
+        );
+
+        let filtered = ParquetFile::from(path.as_path())
+            .with_fields(Some(vec![String::from("address.city")]))
+            .with_filter(Some(Expr::Column(
+                String::from("address.city"),
+                Predicate::Regex(Regex::new("Porto").unwrap()),
+            )))
+            .iter()
+            .map(|r| r.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(filtered, vec![vec!["\"Porto\""]]);
+    }
+
+    #[test]
+    fn test_reader_flatten_nested_json() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("nested.parquet");
+
+        api::tests::write_json_parquet(
+            &path,
+            &[
+                r#"{"id": 1, "address": {"city": "Lisbon", "geo": {"lat": 1.5}}, "tags": ["a"]}"#,
+            ],
+        );
+
+        let flatten = ParquetFile::from(path.as_path()).with_flatten(true);
+        let exclude = ParquetFile::from(path.as_path())
+            .with_flatten(true)
+            .with_exclude(Some(vec![String::from("address.geo.lat")]));
+        let json = ParquetFile::from(path.as_path()).with_nested(NestedFormat::Json);
+
+        assert_eq!(
+            flatten.field_names(),
+            Ok(vec![
+                String::from("id"),
+                String::from("address.city"),
+                String::from("address.geo.lat"),
+                String::from("tags"),
+            ])
+        );
+        assert_eq!(
+            flatten.iter().map(|r| r.unwrap()).collect::<Vec<_>>(),
+            vec![vec!["1", "\"Lisbon\"", "1.5", "[\"a\"]"]]
+        );
+        assert_eq!(
+            exclude.field_names(),
+            Ok(vec![
+                String::from("id"),
+                String::from("address.city"),
+                String::from("tags"),
+            ])
+        );
+        assert_eq!(
+            json.iter().map(|r| r.unwrap()).collect::<Vec<_>>(),
+            vec![vec![
+                "1",
+                "{\"city\":\"Lisbon\",\"geo\":{\"lat\":1.5}}",
+                "[\"a\"]"
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_file_iterator_num_rows() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("1.parquet");
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(&path, &msgs);
+
+        let reader = create_parquet_reader(&path).unwrap();
+        let projection = smallest_column_projection(&reader).unwrap();
+
+        assert_eq!(projection.get_fields().len(), 1);
+        assert_eq!(projection.get_fields()[0].name(), "field_boolean");
+        assert_eq!(file_iterator_num_rows(reader), 5);
+    }
+
+    #[test]
+    fn test_parquet_file_num_files() {
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.snappy.parquet");
+        let path2 = dir.path().join("2.snappy.parquet");
+
+        File::create(path1.clone()).unwrap();
+        File::create(path2.clone()).unwrap();
+
+        let msg1 = api::tests::SimpleMessage {
+            field_int32: 1,
+            field_int64: 2,
+            field_float: 3.3,
+            field_double: 4.4,
+            field_boolean: true,
+            field_string: "5".to_string(),
+            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
+        };
+
+        let msg2 = api::tests::SimpleMessage {
+            field_int32: 11,
+            field_int64: 22,
+            field_float: 33.3,
+            field_double: 44.4,
+            field_boolean: false,
+            field_string: "55".to_string(),
+            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
+        };
+
+        api::tests::write_simple_messages_parquet(&path1, &[msg1]);
+        api::tests::write_simple_messages_parquet(&path2, &[msg2]);
+
+        let parquet_dir = ParquetFile::from(dir.path());
+        let parquet_path1 = ParquetFile::from(path1.as_path());
+        let parquet_path2 = ParquetFile::from(path2.as_path());
+
+        assert_eq!(2, parquet_dir.files().count());
+        assert_eq!(1, parquet_path1.files().count());
+        assert_eq!(1, parquet_path2.files().count());
+    }
+
+    #[test]
+    fn test_parquet_file_num_rows() {
+        let dir = api::tests::temp_dir();
+        let path1 = dir.path().join("1.snappy.parquet");
+        let path2 = dir.path().join("2.snappy.parquet");
+        let path3 = dir.path().join("NOT_A_PARQUET");
+
+        File::create(path1.clone()).unwrap();
+        File::create(path2.clone()).unwrap();
+        File::create(path3.clone()).unwrap();
+
+        let msg1 = api::tests::SimpleMessage {
+            field_int32: 1,
+            field_int64: 2,
+            field_float: 3.3,
+            field_double: 4.4,
+            field_boolean: true,
+            field_string: "5".to_string(),
+            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
+        };
+
+        let msg2 = api::tests::SimpleMessage {
+            field_int32: 11,
+            field_int64: 22,
+            field_float: 33.3,
+            field_double: 44.4,
+            field_boolean: false,
+            field_string: "55".to_string(),
+            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
+        };
+
+        let msg3 = api::tests::SimpleMessage {
+            field_int32: 111,
+            field_int64: 222,
+            field_float: 332.3,
+            field_double: 444.4,
+            field_boolean: false,
+            field_string: "555".to_string(),
+            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
+        };
+
+        api::tests::write_simple_messages_parquet(&path1, &[msg1, msg2]);
+        api::tests::write_simple_messages_parquet(&path2, &[msg3]);
+
+        let parquet_dir = ParquetFile::from(dir.path());
+        let parquet_path1 = ParquetFile::from(path1.as_path());
+        let parquet_path2 = ParquetFile::from(path2.as_path());
+        let parquet_path3 = ParquetFile::from(path3.as_path());
+
+        assert_eq!(Ok(3), parquet_dir.num_rows());
+        assert_eq!(Ok(2), parquet_path1.num_rows());
+        assert_eq!(Ok(1), parquet_path2.num_rows());
+
+        // an unreadable file is only skipped when read from a directory
+        assert!(parquet_path3.num_rows().is_err());
+    }
+
+    #[test]
+    fn test_buffer_stdin() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("msg.parquet");
+
+        api::tests::write_simple_messages_parquet(
+            &path,
+            &api::tests::create_simple_messages(3),
+        );
+
+        let file = buffer_stdin(File::open(&path).unwrap()).unwrap();
+        let parquet = ParquetFile::from(file.path());
+        let temp_path = file.path().to_path_buf();
+
+        assert_eq!(parquet.num_rows(), Ok(3));
+
+        drop(file);
+
+        assert!(!temp_path.exists());
+    }
+
+    #[test]
+    fn test_parquet_file_http_url() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("msg.parquet");
+
+        api::tests::write_simple_messages_parquet(
+            &path,
+            &api::tests::create_simple_messages(3),
+        );
+
+        let url = http::tests::serve_bytes(fs::read(&path).unwrap());
+        let columns = Some(vec![String::from("field_int32")]);
+        let parquet = ParquetFile::new(PathBuf::from(url)).with_fields(columns);
+        let values = parquet.values().map(|r| r.unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(parquet.num_rows(), Ok(3));
+        assert_eq!(parquet.files().count(), 1);
+        assert_eq!(
+            values,
+            vec![
+                vec![Value::Int(1)],
+                vec![Value::Int(2)],
+                vec![Value::Int(3)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parquet_file_column_order() {
+        let dir = api::tests::temp_dir();
+        let first = dir.path().join("1.parquet");
+        let second = dir.path().join("2.parquet");
+
+        api::tests::write_json_parquet(&first, &[r#"{"a": 1, "b": "x"}"#]);
+        api::tests::write_json_parquet(&second, &[r#"{"b": "y", "a": 2}"#]);
+
+        let names = |path: &Path| {
+            let reader = create_parquet_reader(path).unwrap();
+
+            get_row_fields(&reader, &Selection::default())
+                .into_iter()
+                .map(|f| f.1)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&first), vec!["a", "b"]);
+        assert_eq!(names(&second), vec!["b", "a"]);
+
+        // rows are keyed by header, files are not read in any specific order
+        let keyed = |headers: Vec<String>, rows: Vec<Vec<String>>| {
+            let mut rows = rows
+                .into_iter()
+                .map(|r| headers.iter().cloned().zip(r).collect::<Vec<_>>())
+                .map(|mut r| {
+                    r.sort();
+                    r
+                })
+                .collect::<Vec<_>>();
+
+            rows.sort();
+            rows
+        };
+        let pair = |a: &str, b: &str| {
+            vec![
+                (String::from("a"), String::from(a)),
+                (String::from("b"), String::from(b)),
+            ]
+        };
+
+        let parquet = ParquetFile::from(dir.path());
+        let headers = parquet.field_names().unwrap();
+        let rows = parquet.iter().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(
+            keyed(headers.clone(), rows),
+            vec![pair("1", "\"x\""), pair("2", "\"y\"")]
+        );
+
+        let tail = parquet
+            .tail(2)
+            .map(|r| r.map(|v| v.iter().map(Value::to_string).collect()))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(
+            keyed(headers, tail),
+            vec![pair("1", "\"x\""), pair("2", "\"y\"")]
+        );
+
+        let selected = ParquetFile::from(dir.path())
+            .with_fields(Some(vec![String::from("b"), String::from("a")]));
+        let mut rows = selected.iter().collect::<Result<Vec<_>>>().unwrap();
+
+        rows.sort();
+
+        assert_eq!(selected.field_names().unwrap(), vec!["b", "a"]);
+        assert_eq!(
+            rows,
+            vec![
+                vec![String::from("\"x\""), String::from("1")],
+                vec![String::from("\"y\""), String::from("2")],
+            ]
+        );
+
+        let batches = parquet.batches(10).collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].schema(), batches[1].schema());
+    }
+
+    #[test]
+    fn test_parquet_file_fingerprints() {
+        let dir = api::tests::temp_dir();
+        let first = dir.path().join("1.parquet");
+        let second = dir.path().join("2.parquet");
+        let third = dir.path().join("3.parquet");
+        let msgs = api::tests::create_simple_messages(3);
+
+        api::tests::write_simple_messages_parquet(&first, &msgs[0..1]);
+        api::tests::write_simple_messages_parquet(&second, &msgs[1..3]);
+        api::tests::write_json_parquet(&third, &[r#"{"a": 1}"#]);
+
+        let mut fingerprints = ParquetFile::from(dir.path())
+            .fingerprints()
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        fingerprints.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let num_rows = fingerprints
+            .iter()
+            .map(|f| f.1.num_rows)
+            .collect::<Vec<_>>();
+        let hashes = fingerprints
+            .iter()
+            .map(|f| &f.1.schema_hash)
+            .collect::<Vec<_>>();
+
+        assert_eq!(num_rows, vec![1, 2, 1]);
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert_eq!(ParquetFile::from(dir.path()).num_rows(), Ok(4));
         assert_eq!(
-            result2,
-            vec![
-                (6, String::from("field_timestamp")),
-                (1, String::from("field_int64")),
-                (0, String::from("field_int32"))
-            ]
+            ParquetFile::from(first.as_path()).fingerprints(),
+            vec![Ok((first.clone(), fingerprints[0].1.clone()))]
         );
     }
 
     #[test]
-    fn test_parquet_file_num_files() {
+    fn test_prefetch_keeps_file_order() {
         let dir = api::tests::temp_dir();
-        let path1 = dir.path().join("1.snappy.parquet");
-        let path2 = dir.path().join("2.snappy.parquet");
+        let msgs = api::tests::create_simple_messages(5);
+        let mut paths = (0..5)
+            .map(|i| dir.path().join(format!("{}.parquet", i)))
+            .collect::<Vec<_>>();
 
-        File::create(path1.clone()).unwrap();
-        File::create(path2.clone()).unwrap();
+        for (path, msg) in paths.iter().zip(msgs.chunks(1)) {
+            api::tests::write_simple_messages_parquet(path, msg);
+        }
 
-        let msg1 = api::tests::SimpleMessage {
-            field_int32: 1,
-            field_int64: 2,
-            field_float: 3.3,
-            field_double: 4.4,
-            field_boolean: true,
-            field_string: "5".to_string(),
-            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
-        };
+        paths.insert(2, dir.path().join("missing.parquet"));
 
-        let msg2 = api::tests::SimpleMessage {
-            field_int32: 11,
-            field_int64: 22,
-            field_float: 33.3,
-            field_double: 44.4,
-            field_boolean: false,
-            field_string: "55".to_string(),
-            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
-        };
+        for ahead in [0, 1, 3, 10] {
+            let prefetched = Prefetch::new(paths.clone().into_iter(), ahead)
+                .map(|(p, r)| (p, r.map(|r| file_metadata_num_rows(&r))))
+                .collect::<Vec<_>>();
 
-        api::tests::write_simple_messages_parquet(&path1, &[msg1]);
-        api::tests::write_simple_messages_parquet(&path2, &[msg2]);
+            assert_eq!(
+                prefetched.iter().map(|p| p.0.clone()).collect::<Vec<_>>(),
+                paths
+            );
+            assert!(prefetched[2].1.is_err());
+            assert!(prefetched
+                .iter()
+                .enumerate()
+                .all(|(i, p)| i == 2 || p.1 == Ok(1)));
+        }
 
-        let parquet_dir = ParquetFile::from(dir.path());
-        let parquet_path1 = ParquetFile::from(path1.as_path());
-        let parquet_path2 = ParquetFile::from(path2.as_path());
+        // workers are only started for the files to read
+        let mut prefetch = Prefetch::new(paths.clone().into_iter(), 10);
 
-        assert_eq!(2, parquet_dir.files().count());
-        assert_eq!(1, parquet_path1.files().count());
-        assert_eq!(1, parquet_path2.files().count());
+        assert!(prefetch.next().is_some());
+        assert_eq!(prefetch.workers.len(), paths.len());
+
+        drop(prefetch);
+
+        let parquet = ParquetFile::from(dir.path())
+            .with_fields(Some(vec![String::from("field_int32")]));
+        let rows = parquet.iter().collect::<Result<Vec<_>>>().unwrap();
+        let parquet = parquet.with_prefetch(0);
+
+        assert_eq!(rows.len(), 5);
+        assert_eq!(parquet.iter().collect::<Result<Vec<_>>>(), Ok(rows));
     }
 
     #[test]
-    fn test_parquet_file_num_rows() {
+    fn test_align_batch() {
+        use arrow::array::{Array, Int64Array, StringArray};
+        use arrow::datatypes::{DataType, Field as ArrowField, Schema};
+
+        let field_a = ArrowField::new("a", DataType::Int64, true);
+        let field_b = ArrowField::new("b", DataType::Utf8, true);
+        let schema = Arc::new(Schema::new(vec![field_a.clone(), field_b.clone()]));
+        let swapped = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![field_b, field_a.clone()])),
+            vec![
+                Arc::new(StringArray::from(vec!["x"])),
+                Arc::new(Int64Array::from(vec![1])),
+            ],
+        )
+        .unwrap();
+        let missing = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![field_a])),
+            vec![Arc::new(Int64Array::from(vec![2]))],
+        )
+        .unwrap();
+
+        let aligned = align_batch(swapped, &schema).unwrap();
+
+        assert_eq!(aligned.schema(), schema);
+        assert_eq!(aligned.column(0).data_type(), &DataType::Int64);
+        assert_eq!(aligned.column(1).data_type(), &DataType::Utf8);
+
+        let aligned = align_batch(missing, &schema).unwrap();
+
+        assert_eq!(aligned.schema(), schema);
+        assert_eq!(aligned.column(1).null_count(), 1);
+    }
+
+    #[test]
+    fn test_parquet_file_empty_files() {
         let dir = api::tests::temp_dir();
-        let path1 = dir.path().join("1.snappy.parquet");
-        let path2 = dir.path().join("2.snappy.parquet");
-        let path3 = dir.path().join("NOT_A_PARQUET");
+        let empty = dir.path().join("1.parquet");
+        let full = dir.path().join("2.parquet");
+
+        // header-only file with a different schema
+        api::tests::write_simple_messages_parquet(&empty, &[]);
+        api::tests::write_json_parquet(&full, &[r#"{"id": 1}"#, r#"{"id": 2}"#]);
+
+        let parquet = ParquetFile::from(dir.path());
+        let schema = parquet.arrow_schema().unwrap();
+
+        assert_eq!(parquet.empty_files(), vec![empty.clone()]);
+        assert_eq!(parquet.field_names().unwrap(), vec![String::from("id")]);
+        assert_eq!(parquet.schema().unwrap().get_fields().len(), 1);
+        assert_eq!(schema.fields().len(), 1);
+        assert_eq!(schema.field(0).name(), "id");
+        assert_eq!(parquet.num_rows(), Ok(2));
+        assert_eq!(parquet.iter().filter(|r| r.is_ok()).count(), 2);
+        assert_eq!(parquet.tail(5).filter(|r| r.is_ok()).count(), 2);
+
+        // every file is empty, the first one describes the schema
+        fs::remove_file(&full).unwrap();
+
+        let columns = parquet.field_names().unwrap();
+
+        assert_eq!(parquet.empty_files(), vec![empty]);
+        assert_eq!(columns.first().map(String::as_str), Some("field_int32"));
+        assert_eq!(parquet.iter().count(), 0);
+        assert_eq!(parquet.tail(5).count(), 0);
+    }
 
-        File::create(path1.clone()).unwrap();
-        File::create(path2.clone()).unwrap();
-        File::create(path3.clone()).unwrap();
+    #[test]
+    fn test_parquet_file_distinct_counts() {
+        use parquet::data_type::Int32Type;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
 
-        let msg1 = api::tests::SimpleMessage {
-            field_int32: 1,
-            field_int64: 2,
-            field_float: 3.3,
-            field_double: 4.4,
-            field_boolean: true,
-            field_string: "5".to_string(),
-            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
-        };
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("distinct.parquet");
+        let msgs = dir.path().join("msgs.parquet");
+        let message = "message schema { REQUIRED INT32 id; REQUIRED INT32 other; }";
+        let schema = Arc::new(parse_message_type(message).unwrap());
+        let props = Arc::new(WriterProperties::builder().build());
+        let file = File::create(&path).unwrap();
+        let mut writer = SerializedFileWriter::new(file, schema, props).unwrap();
+
+        for distinct in &[Some(2), None] {
+            let mut row_group = writer.next_row_group().unwrap();
+
+            for count in &[Some(2), *distinct] {
+                let mut column = row_group.next_column().unwrap().unwrap();
+
+                column
+                    .typed::<Int32Type>()
+                    .write_batch_with_statistics(&[1, 2], None, None, None, None, *count)
+                    .unwrap();
+                column.close().unwrap();
+            }
 
-        let msg2 = api::tests::SimpleMessage {
-            field_int32: 11,
-            field_int64: 22,
-            field_float: 33.3,
-            field_double: 44.4,
-            field_boolean: false,
-            field_string: "55".to_string(),
-            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
-        };
+            row_group.close().unwrap();
+        }
 
-        let msg3 = api::tests::SimpleMessage {
-            field_int32: 111,
-            field_int64: 222,
-            field_float: 332.3,
-            field_double: 444.4,
-            field_boolean: false,
-            field_string: "555".to_string(),
-            field_timestamp: NaiveDateTime::from_timestamp(1_000_000_000, 0),
+        writer.close().unwrap();
+
+        api::tests::write_simple_messages_parquet(
+            &msgs,
+            &api::tests::create_simple_messages(2),
+        );
+
+        let counts = ParquetFile::from(path.as_path()).distinct_counts().unwrap();
+        let expected = vec![(String::from("id"), 4)].into_iter().collect();
+
+        assert_eq!(counts, expected);
+        assert!(ParquetFile::from(msgs.as_path())
+            .distinct_counts()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parquet_file_logical_types() {
+        use parquet::data_type::{
+            FixedLenByteArray, FixedLenByteArrayType, Int32Type, Int64Type,
         };
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let parquet = api::tests::temp_file("logical", ".parquet");
+        let message = "message schema {
+            REQUIRED INT32 date (DATE);
+            REQUIRED INT32 millis (TIME(MILLIS,true));
+            REQUIRED INT64 micros (TIME(MICROS,false));
+            REQUIRED INT64 nanos (TIME(NANOS,true));
+            REQUIRED FIXED_LEN_BYTE_ARRAY (16) uuid (UUID);
+        }";
+        let schema = Arc::new(parse_message_type(message).unwrap());
+        let props = Arc::new(WriterProperties::builder().build());
+        let file = File::create(parquet.path()).unwrap();
+        let mut writer = SerializedFileWriter::new(file, schema, props).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let uuid = (0..16_u8).map(|b| b * 17).collect::<Vec<_>>();
+
+        for i in 0..5 {
+            let mut column = row_group.next_column().unwrap().unwrap();
+
+            match i {
+                0 => column
+                    .typed::<Int32Type>()
+                    .write_batch(&[19844], None, None),
+                1 => column
+                    .typed::<Int32Type>()
+                    .write_batch(&[49_500_123], None, None),
+                2 => {
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&[49_500_000_001], None, None)
+                }
+                3 => {
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&[1_000_000_001], None, None)
+                }
+                _ => column.typed::<FixedLenByteArrayType>().write_batch(
+                    &[FixedLenByteArray::from(uuid.clone())],
+                    None,
+                    None,
+                ),
+            }
+            .unwrap();
+            column.close().unwrap();
+        }
 
-        api::tests::write_simple_messages_parquet(&path1, &[msg1, msg2]);
-        api::tests::write_simple_messages_parquet(&path2, &[msg3]);
+        row_group.close().unwrap();
+        writer.close().unwrap();
 
-        let parquet_dir = ParquetFile::from(dir.path());
-        let parquet_path1 = ParquetFile::from(path1.as_path());
-        let parquet_path2 = ParquetFile::from(path2.as_path());
-        let parquet_path3 = ParquetFile::from(path3.as_path());
+        let parquet = ParquetFile::from(parquet.path());
+        let expected = vec![
+            "2024-05-01",
+            "13:45:00.123",
+            "13:45:00.000001",
+            "00:00:01.000000001",
+            "00112233-4455-6677-8899-aabbccddeeff",
+        ];
 
-        assert_eq!(3, parquet_dir.num_rows());
-        assert_eq!(2, parquet_path1.num_rows());
-        assert_eq!(1, parquet_path2.num_rows());
-        assert_eq!(0, parquet_path3.num_rows());
+        assert_eq!(parquet.iter().next().unwrap().unwrap(), expected);
+        assert_eq!(
+            parquet.tail(1).next().unwrap().unwrap()[1],
+            Value::Time(49_500_123_000_000)
+        );
+        assert_eq!(parquet.num_rows(), Ok(1));
     }
 
     #[test]
@@ -816,13 +3530,14 @@ mod tests {
 
         api::tests::write_simple_messages_parquet(&path, &[msg1, msg2, msg3]);
 
-        let mut filters = HashMap::new();
         let fields = vec![String::from("field_int32"), String::from("field_string")];
-
-        filters.insert(String::from("field_string"), Regex::new("odd").unwrap());
+        let filter = Expr::Column(
+            String::from("field_string"),
+            Predicate::Regex(Regex::new("odd").unwrap()),
+        );
 
         let result = ParquetFile::from(dir.path())
-            .with_filters(Some(filters))
+            .with_filter(Some(filter))
             .with_fields(Some(fields))
             .iter()
             .filter_map(Result::ok)
@@ -833,4 +3548,446 @@ mod tests {
         assert_eq!(result[0], vec!["1", "\"odd 1\""]);
         assert_eq!(result[1], vec!["111", "\"odd 2\""]);
     }
+
+    #[test]
+    fn test_reader_matching_row_groups() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("msg.parquet");
+        let msgs = api::tests::create_simple_messages(9);
+
+        api::tests::write_simple_messages_row_groups(
+            &path,
+            &[&msgs[0..3], &msgs[3..6], &msgs[6..9]],
+        );
+
+        let reader = create_parquet_reader(&path).unwrap();
+        let descr = reader.metadata().file_metadata().schema_descr_ptr();
+        let row_groups = |expressions: &[&str]| {
+            let filters = expressions
+                .iter()
+                .map(|e| crate::filter::parse(e).unwrap())
+                .collect::<Vec<_>>();
+            let named = filters
+                .iter()
+                .map(|(name, predicate)| (name.as_str(), predicate))
+                .collect::<Vec<_>>();
+            let point_filters = row_group_pruning::point_filters(&descr, &named);
+
+            matching_row_groups(&path, &reader, &point_filters)
+        };
+
+        assert_eq!(row_groups(&["field_int32=5"]), Some(vec![1]));
+        assert_eq!(row_groups(&["field_int32:^8$"]), Some(vec![2]));
+        assert_eq!(row_groups(&["field_string:^aaa$"]), Some(vec![]));
+        assert_eq!(row_groups(&["field_string=zzz"]), Some(vec![]));
+        assert_eq!(row_groups(&["field_string=odd 55555"]), Some(vec![1, 2]));
+        assert_eq!(row_groups(&["field_int32=100"]), Some(vec![]));
+        assert_eq!(row_groups(&["field_int32>=5"]), None);
+        assert_eq!(row_groups(&["field_int32:^[15]$"]), None);
+    }
+
+    #[test]
+    fn test_reader_row_numbers() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("msg.parquet");
+        let msgs = api::tests::create_simple_messages(9);
+        let (name, predicate) = crate::filter::parse("field_int32=5").unwrap();
+
+        api::tests::write_simple_messages_row_groups(
+            &path,
+            &[&msgs[0..3], &msgs[3..6], &msgs[6..9]],
+        );
+
+        // the first row group is pruned and the second one is empty
+        let numbers = RowNumbers::new(10, &[3, 0, 2, 4], Some(&[1, 2, 3]));
+        let parquet = ParquetFile::from(path.as_path())
+            .with_fields(Some(vec![String::from("field_int32")]))
+            .with_filter(Some(Expr::Column(name, predicate)))
+            .with_row_numbers(true);
+
+        assert_eq!(
+            (0..6).map(|p| numbers.number(p)).collect::<Vec<_>>(),
+            vec![13, 14, 15, 16, 17, 18]
+        );
+        assert_eq!(parquet.field_names().unwrap(), vec!["#", "field_int32"]);
+        assert_eq!(
+            parquet.iter().collect::<Result<Vec<_>>>().unwrap(),
+            vec![vec!["5", "5"]]
+        );
+    }
+
+    #[test]
+    fn test_reader_hive_partitions() {
+        let dir = api::tests::temp_dir();
+        let br = dir.path().join("date=2024-05-01").join("country=BR");
+        let sp = dir
+            .path()
+            .join("date=2024-05-02")
+            .join("country=S%C3%A3o%20Paulo");
+        let unknown = dir
+            .path()
+            .join("date=2024-05-02")
+            .join(format!("country={}", HIVE_DEFAULT_PARTITION));
+        let msgs = api::tests::create_simple_messages(3);
+
+        for (path, msgs) in [
+            (&br, &msgs[..1]),
+            (&sp, &msgs[1..2]),
+            (&unknown, &msgs[2..]),
+        ] {
+            fs::create_dir_all(path).unwrap();
+            api::tests::write_simple_messages_parquet(&path.join("part-0.parquet"), msgs);
+        }
+
+        let values = |columns: &[&str], exclude: Option<&str>, search: Option<&str>| {
+            let filter = search.map(|s| {
+                let (column, predicate) = crate::filter::parse(s).unwrap();
+
+                Expr::Column(column, predicate)
+            });
+            let parquet = ParquetFile::from(dir.path())
+                .with_fields(Some(columns.iter().map(|c| c.to_string()).collect()))
+                .with_exclude(exclude.map(|e| vec![e.to_string()]))
+                .with_filter(filter);
+            let rows = parquet.iter().collect::<Result<Vec<_>>>().unwrap();
+
+            (parquet.field_names().unwrap(), rows)
+        };
+
+        assert_eq!(
+            path_partitions(dir.path(), &sp.join("part-0.parquet")),
+            vec![
+                (String::from("date"), Field::Str(String::from("2024-05-02"))),
+                (
+                    String::from("country"),
+                    Field::Str(String::from("São Paulo"))
+                ),
+            ]
+        );
+        assert_eq!(
+            values(&["field_int32", "c*"], Some("date"), None),
+            (
+                vec![String::from("field_int32"), String::from("country")],
+                vec![
+                    vec![String::from("1"), String::from("\"BR\"")],
+                    vec![String::from("2"), String::from("\"São Paulo\"")],
+                    vec![String::from("3"), String::from("null")],
+                ]
+            )
+        );
+        assert_eq!(
+            values(
+                &["date", "country", "field_int32"],
+                None,
+                Some("date:05-02$")
+            ),
+            (
+                vec![
+                    String::from("date"),
+                    String::from("country"),
+                    String::from("field_int32")
+                ],
+                vec![
+                    vec![
+                        String::from("\"2024-05-02\""),
+                        String::from("\"São Paulo\""),
+                        String::from("2")
+                    ],
+                    vec![
+                        String::from("\"2024-05-02\""),
+                        String::from("null"),
+                        String::from("3")
+                    ],
+                ]
+            )
+        );
+        assert_eq!(
+            values(&["field_int32", "country", "field_string"], None, None).0,
+            vec!["field_int32", "country", "field_string"]
+        );
+        assert_eq!(
+            ParquetFile::from(dir.path())
+                .with_fields(Some(vec![
+                    String::from("country"),
+                    String::from("field_int32")
+                ]))
+                .tail(1)
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![vec![Value::Null, Value::Int(3)]]
+        );
+        assert_eq!(
+            values(&["field_int32"], None, Some("date:05-02$")).1,
+            vec![vec![String::from("2")], vec![String::from("3")]]
+        );
+        assert_eq!(
+            values(&["date"], None, Some("country:NULL")),
+            (
+                vec![String::from("date")],
+                vec![vec![String::from("\"2024-05-02\"")]]
+            )
+        );
+    }
+
+    #[test]
+    fn test_reader_partition_pruning() {
+        let dir = api::tests::temp_dir();
+        let msgs = api::tests::create_simple_messages(3);
+        let path = |date: &str, country: &str| {
+            dir.path()
+                .join(format!("date={}", date))
+                .join(format!("country={}", country))
+                .join("part-0.parquet")
+        };
+        let br = path("2024-05-01", "BR");
+        let pt = path("2024-05-01", "PT");
+        let other = path("2024-05-02", "BR");
+
+        for (path, msgs) in [(&br, &msgs[..1]), (&pt, &msgs[1..2]), (&other, &msgs[2..])]
+        {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            api::tests::write_simple_messages_parquet(path, msgs);
+        }
+
+        let filter = |search: &[&str]| {
+            let exprs = search
+                .iter()
+                .map(|s| {
+                    let (column, predicate) = crate::filter::parse(s).unwrap();
+
+                    Expr::Column(column, predicate)
+                })
+                .collect();
+
+            Some(Expr::And(exprs))
+        };
+        let files = |search: &[&str]| {
+            let parquet = ParquetFile::from(dir.path()).with_filter(filter(search));
+            let mut files = parquet.files().collect::<Vec<_>>();
+
+            files.sort();
+            files
+        };
+
+        assert_eq!(files(&["date=2024-05-01"]), vec![br.clone(), pt.clone()]);
+        assert_eq!(files(&["date=2024-05-01", "country=PT"]), vec![pt.clone()]);
+        assert_eq!(
+            files(&["field_int32=1", "country=BR"]),
+            vec![br.clone(), other]
+        );
+        assert_eq!(files(&["date>2024-05-02"]), Vec::<PathBuf>::new());
+        assert_eq!(
+            ParquetFile::from(dir.path())
+                .with_fields(Some(vec![String::from("field_int32")]))
+                .with_filter(filter(&["country=PT"]))
+                .iter()
+                .collect::<Result<Vec<_>>>(),
+            Ok(vec![vec![String::from("2")]])
+        );
+    }
+
+    #[test]
+    fn test_reader_num_rows_unreadable() {
+        let dir = api::tests::temp_dir();
+        let msgs = api::tests::create_simple_messages(2);
+        let corrupt = dir.path().join("2.parquet");
+
+        api::tests::write_simple_messages_parquet(&dir.path().join("1.parquet"), &msgs);
+        fs::write(&corrupt, b"not parquet").unwrap();
+
+        let parquet = ParquetFile::from(dir.path());
+
+        assert_eq!(parquet.num_rows(), Ok(2));
+        assert_eq!(parquet.file_num_rows().count(), 1);
+        assert_eq!(parquet.values().map(Result::unwrap).count(), 2);
+        assert_eq!(parquet.tail(5).map(Result::unwrap).count(), 2);
+        assert!(parquet.null_counts().is_ok());
+        assert!(parquet.row_group_parts().is_ok());
+        assert!(parquet
+            .clone()
+            .with_strict(true)
+            .values()
+            .any(|r| r.is_err()));
+        assert!(parquet.clone().with_strict(true).null_counts().is_err());
+        assert!(matches!(
+            parquet.with_strict(true).num_rows(),
+            Err(Error::Parquet(path, _)) if path == corrupt
+        ));
+    }
+
+    #[test]
+    fn test_reader_discovery() {
+        let dir = api::tests::temp_dir();
+        let msgs = api::tests::create_simple_messages(1);
+        let files = [
+            "a.parquet",
+            "b.PQ",
+            ".hidden.parquet",
+            "_temporary/0.parquet",
+            "scratch/1.parquet",
+            "2024/05/part-0.parquet",
+        ];
+
+        for file in files {
+            let path = dir.path().join(file);
+
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            api::tests::write_simple_messages_parquet(&path, &msgs);
+        }
+
+        let files = |discovery: Discovery| {
+            let parquet = ParquetFile::from(dir.path()).with_discovery(discovery);
+            let mut files = parquet
+                .files()
+                .map(|p| p.strip_prefix(dir.path()).unwrap().to_path_buf())
+                .map(|p| p.to_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+
+            files.sort();
+            files
+        };
+        let strings =
+            |values: &[&str]| values.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            files(Discovery::default()),
+            strings(&["2024/05/part-0.parquet", "a.parquet", "scratch/1.parquet"])
+        );
+        assert_eq!(
+            files(Discovery {
+                extensions: strings(&[".pq"]),
+                ..Discovery::default()
+            }),
+            strings(&["b.PQ"])
+        );
+        assert_eq!(
+            files(Discovery {
+                hidden: true,
+                max_depth: Some(2),
+                ..Discovery::default()
+            }),
+            strings(&[
+                ".hidden.parquet",
+                "_temporary/0.parquet",
+                "a.parquet",
+                "scratch/1.parquet"
+            ])
+        );
+        assert_eq!(
+            files(Discovery {
+                exclude: strings(&["scratch"]),
+                ..Discovery::default()
+            }),
+            strings(&["2024/05/part-0.parquet", "a.parquet"])
+        );
+        assert_eq!(
+            files(Discovery {
+                include: strings(&["2024/**/part-*.parquet"]),
+                ..Discovery::default()
+            }),
+            strings(&["2024/05/part-0.parquet"])
+        );
+        assert_eq!(
+            files(Discovery {
+                include: strings(&["**/1.parquet", "a.*"]),
+                ..Discovery::default()
+            }),
+            strings(&["a.parquet", "scratch/1.parquet"])
+        );
+    }
+
+    #[test]
+    fn test_reader_typed_filters() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("msg.parquet");
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_parquet(&path, &msgs);
+
+        let column = |e: &str| {
+            let (column, predicate) = crate::filter::parse(e).unwrap();
+
+            Expr::Column(column, predicate)
+        };
+        let values = |filter: Expr| {
+            // filters may use columns which are not selected
+            ParquetFile::from(dir.path())
+                .with_fields(Some(vec![String::from("field_int32")]))
+                .with_filter(Some(filter))
+                .iter()
+                .map(|r| r.unwrap().join(","))
+                .collect::<Vec<_>>()
+        };
+        let all = |e: &[&str]| Expr::And(e.iter().map(|e| column(e)).collect());
+        let any = |e: &[&str]| Expr::Or(e.iter().map(|e| column(e)).collect());
+
+        assert_eq!(values(all(&["field_string:^odd"])), vec!["1", "3", "5"]);
+        assert_eq!(values(all(&["field_string:^\"odd"])), Vec::<String>::new());
+        assert_eq!(
+            values(all(&["field_int64>22", "field_boolean=false"])),
+            vec!["3", "5"]
+        );
+        assert_eq!(values(all(&["field_double<=2222.4"])), vec!["1", "2"]);
+        assert_eq!(values(all(&["field_timestamp<2013-01-01"])), vec!["1", "2"]);
+        assert_eq!(values(all(&["field_int32!=3"])), vec!["1", "2", "4", "5"]);
+        assert_eq!(
+            values(all(&["field_int32>1", "field_int32<4"])),
+            vec!["2", "3"]
+        );
+        assert_eq!(
+            values(any(&["field_int32=1", "field_string:55555"])),
+            vec!["1", "5"]
+        );
+        assert_eq!(
+            values(Expr::Not(Box::new(column("field_string:^odd")))),
+            vec!["2", "4"]
+        );
+        assert_eq!(values(all(&["unknown:NULL"])).len(), 5);
+        assert_eq!(values(all(&["unknown:^null$"])).len(), 0);
+    }
+
+    #[test]
+    fn test_reader_tail() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("1.snappy.parquet");
+        let msgs = api::tests::create_simple_messages(7);
+        let groups: &[&[api::tests::SimpleMessage]] =
+            &[&msgs[0..2], &msgs[2..4], &msgs[4..7]];
+
+        api::tests::write_simple_messages_row_groups(&path, groups);
+
+        let fields = Some(vec![String::from("field_int32")]);
+        let parquet = ParquetFile::from(dir.path()).with_fields(fields);
+        let values = |num| {
+            parquet
+                .tail(num)
+                .map(|r| format_values(r.unwrap(), &ValueFormat::default()).join(","))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(0), Vec::<String>::new());
+        assert_eq!(values(1), vec!["7"]);
+        assert_eq!(values(2), vec!["6", "7"]);
+        assert_eq!(values(4), vec!["4", "5", "6", "7"]);
+        assert_eq!(values(6), vec!["2", "3", "4", "5", "6", "7"]);
+        assert_eq!(values(9), vec!["1", "2", "3", "4", "5", "6", "7"]);
+    }
+
+    #[test]
+    fn test_tail_row_groups() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("1.snappy.parquet");
+        let msgs = api::tests::create_simple_messages(5);
+
+        api::tests::write_simple_messages_row_groups(&path, &[&msgs[0..3], &msgs[3..5]]);
+
+        let reader = create_parquet_reader(&path).unwrap();
+
+        assert_eq!((2, 0, 0), tail_row_groups(&reader, 0));
+        assert_eq!((1, 1, 1), tail_row_groups(&reader, 1));
+        assert_eq!((1, 0, 2), tail_row_groups(&reader, 2));
+        assert_eq!((0, 2, 3), tail_row_groups(&reader, 3));
+        assert_eq!((0, 0, 5), tail_row_groups(&reader, 5));
+        assert_eq!((0, 0, 5), tail_row_groups(&reader, 8));
+    }
 }
@@ -0,0 +1,251 @@
+use crate::filter::{Operator, Predicate};
+use parquet::basic::{ConvertedType, Type as PhysicalType};
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::page_index::index::{Index, PageIndex};
+use parquet::schema::types::SchemaDescriptor;
+use regex::Regex;
+use std::convert::TryInto;
+
+/// Value a column must be equal to for a filter to match, eg: `--search id=12345`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Literal {
+    // Signed integer of an INT32 or INT64 column
+    Int(i64),
+
+    // Bytes of an UTF8 column
+    Bytes(Vec<u8>),
+}
+
+/// Filter matching a single value of a leaf column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PointFilter {
+    // Index of the leaf column in the schema
+    pub column: usize,
+
+    // Value the column must be equal to
+    pub literal: Literal,
+}
+
+const REGEX_META: &str = ".+*?()|[]{}^$";
+
+/// Literal text of an anchored regex without any meta character, eg: `^12345$`.
+#[inline]
+fn anchored_literal(regex: &Regex) -> Option<String> {
+    let inner = regex.as_str().strip_prefix('^')?.strip_suffix('$')?;
+    let mut chars = inner.chars();
+    let mut literal = String::with_capacity(inner.len());
+
+    while let Some(c) = chars.next() {
+        match c {
+            // escaped characters are only literal when they are punctuation, eg: `\.`
+            '\\' => match chars.next() {
+                Some(e) if e.is_ascii_punctuation() => literal.push(e),
+                _ => return None,
+            },
+            c if REGEX_META.contains(c) => return None,
+            c => literal.push(c),
+        }
+    }
+
+    Some(literal)
+}
+
+/// Value a predicate requires the column to be equal to, if any.
+#[inline]
+fn equal_text(predicate: &Predicate) -> Option<String> {
+    match predicate {
+        Predicate::Regex(regex) => anchored_literal(regex),
+        Predicate::Compare(Operator::Eq, operand) => Some(operand.text().to_string()),
+        Predicate::Compare(_, _)
+        | Predicate::Null
+        | Predicate::NotNull
+        | Predicate::In(_) => None,
+    }
+}
+
+/// Typed literal of the column equal to the given text.
+#[inline]
+fn column_literal(
+    physical: PhysicalType,
+    converted: ConvertedType,
+    text: &str,
+) -> Option<Literal> {
+    match (physical, converted) {
+        (PhysicalType::INT32, ConvertedType::NONE)
+        | (PhysicalType::INT32, ConvertedType::INT_8)
+        | (PhysicalType::INT32, ConvertedType::INT_16)
+        | (PhysicalType::INT32, ConvertedType::INT_32)
+        | (PhysicalType::INT64, ConvertedType::NONE)
+        | (PhysicalType::INT64, ConvertedType::INT_64) => {
+            text.parse().ok().map(Literal::Int)
+        }
+        (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8) => {
+            Some(Literal::Bytes(text.as_bytes().to_vec()))
+        }
+        _ => None,
+    }
+}
+
+/// Point filters of the given field predicates, only top level and dotted
+/// paths of primitive columns are supported.
+pub fn point_filters(
+    schema: &SchemaDescriptor,
+    filters: &[(&str, &Predicate)],
+) -> Vec<PointFilter> {
+    let mut result = Vec::new();
+
+    for (name, predicate) in filters {
+        let name = name.to_lowercase();
+        let column = (0..schema.num_columns())
+            .find(|i| schema.column(*i).path().string().to_lowercase() == name);
+
+        if let (Some(i), Some(text)) = (column, equal_text(predicate)) {
+            let descr = schema.column(i);
+            let literal =
+                column_literal(descr.physical_type(), descr.converted_type(), &text);
+
+            if let Some(literal) = literal {
+                result.push(PointFilter { column: i, literal });
+            }
+        }
+    }
+
+    result
+}
+
+#[inline]
+fn int_value(bytes: &[u8]) -> Option<i64> {
+    match bytes.len() {
+        4 => bytes.try_into().ok().map(i32::from_le_bytes).map(i64::from),
+        8 => bytes.try_into().ok().map(i64::from_le_bytes),
+        _ => None,
+    }
+}
+
+/// Whether a value between encoded min and max bounds may equal the literal.
+#[inline]
+fn in_bounds(literal: &Literal, min: &[u8], max: &[u8]) -> bool {
+    match literal {
+        Literal::Int(v) => match (int_value(min), int_value(max)) {
+            (Some(min), Some(max)) => min <= *v && *v <= max,
+            _ => true,
+        },
+        Literal::Bytes(v) => min <= v.as_slice() && v.as_slice() <= max,
+    }
+}
+
+/// Whether any row of the row group may match every filter, using the column chunk statistics.
+pub fn row_group_may_match(
+    row_group: &RowGroupMetaData,
+    filters: &[PointFilter],
+) -> bool {
+    filters.iter().all(|filter| {
+        let stats = match row_group.column(filter.column).statistics() {
+            Some(stats) if stats.has_min_max_set() => stats,
+            _ => return true,
+        };
+
+        // deprecated bounds of byte arrays use a signed sort order
+        if stats.is_min_max_deprecated() && matches!(filter.literal, Literal::Bytes(_)) {
+            return true;
+        }
+
+        in_bounds(&filter.literal, stats.min_bytes(), stats.max_bytes())
+    })
+}
+
+#[inline]
+fn pages_may_contain<T, F>(pages: &[PageIndex<T>], contains: F) -> bool
+where
+    F: Fn(&T, &T) -> bool,
+{
+    // pages without bounds only hold null values
+    pages.iter().any(|page| match (page.min(), page.max()) {
+        (Some(min), Some(max)) => contains(min, max),
+        _ => false,
+    })
+}
+
+/// Whether any page of the row group may match every filter, using the column index.
+///
+/// `index` holds the column index of each leaf column, columns without an index may match.
+/// Only decides whether the row group is read, its pages are not skipped.
+pub fn column_index_may_match(index: &[Index], filters: &[PointFilter]) -> bool {
+    filters
+        .iter()
+        .all(|filter| match (index.get(filter.column), &filter.literal) {
+            (Some(Index::INT32(i)), Literal::Int(v)) => {
+                pages_may_contain(&i.indexes, |min, max| {
+                    i64::from(*min) <= *v && *v <= i64::from(*max)
+                })
+            }
+            (Some(Index::INT64(i)), Literal::Int(v)) => {
+                pages_may_contain(&i.indexes, |min, max| min <= v && v <= max)
+            }
+            (Some(Index::BYTE_ARRAY(i)), Literal::Bytes(v)) => {
+                pages_may_contain(&i.indexes, |min, max| min <= v && v <= max)
+            }
+            _ => true,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchored_literal() {
+        let literal = |s| anchored_literal(&Regex::new(s).unwrap());
+
+        assert_eq!(literal("^12345$"), Some(String::from("12345")));
+        assert_eq!(literal("^\"odd 1\"$"), Some(String::from("\"odd 1\"")));
+        assert_eq!(literal("^a\\.b$"), Some(String::from("a.b")));
+        assert_eq!(literal("^a.b$"), None);
+        assert_eq!(literal("12345"), None);
+        assert_eq!(literal("^123"), None);
+        assert_eq!(literal("^1|2$"), None);
+        assert_eq!(literal("^\\d$"), None);
+    }
+
+    #[test]
+    fn test_column_literal() {
+        assert_eq!(
+            column_literal(PhysicalType::INT64, ConvertedType::NONE, "-12"),
+            Some(Literal::Int(-12))
+        );
+        assert_eq!(
+            column_literal(PhysicalType::BYTE_ARRAY, ConvertedType::UTF8, "a"),
+            Some(Literal::Bytes(b"a".to_vec()))
+        );
+        assert_eq!(
+            column_literal(PhysicalType::INT64, ConvertedType::TIMESTAMP_MILLIS, "1"),
+            None
+        );
+        assert_eq!(
+            column_literal(PhysicalType::INT32, ConvertedType::NONE, "null"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_equal_text() {
+        let text = |s| equal_text(&crate::filter::parse(s).unwrap().1);
+
+        assert_eq!(text("a:^odd 1$"), Some(String::from("odd 1")));
+        assert_eq!(text("a=12"), Some(String::from("12")));
+        assert_eq!(text("a:odd"), None);
+        assert_eq!(text("a>=12"), None);
+    }
+
+    #[test]
+    fn test_in_bounds() {
+        let min = 10i64.to_le_bytes();
+        let max = 20i32.to_le_bytes();
+
+        assert!(in_bounds(&Literal::Int(10), &min, &max));
+        assert!(in_bounds(&Literal::Int(20), &min, &max));
+        assert!(!in_bounds(&Literal::Int(21), &min, &max));
+        assert!(in_bounds(&Literal::Bytes(b"b".to_vec()), b"a", b"c"));
+        assert!(!in_bounds(&Literal::Bytes(b"d".to_vec()), b"a", b"c"));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::api::{Error, Result};
+use crate::output::WriteLock;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Directory of the snapshots of a dataset, skipped when reading it.
+pub const TRASH_DIR: &str = ".xpq-trash";
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Files replaced by a command, as they were before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    pub command: String,
+    pub created: String,
+
+    /// Paths relative to the dataset directory with the name of their copy
+    pub files: Vec<(String, String)>,
+}
+
+impl Manifest {
+    fn to_json_value(&self) -> serde_json::Value {
+        let files = self
+            .files
+            .iter()
+            .map(|(path, copy)| json!({ "path": path, "copy": copy }))
+            .collect::<Vec<_>>();
+
+        json!({
+            "command": self.command,
+            "created": self.created,
+            "files": files,
+        })
+    }
+
+    fn from_json_value(value: &serde_json::Value) -> Option<Self> {
+        let text = |v: &serde_json::Value, key| v.get(key)?.as_str().map(String::from);
+        let files = value
+            .get("files")?
+            .as_array()?
+            .iter()
+            .map(|f| Some((text(f, "path")?, text(f, "copy")?)))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            command: text(value, "command")?,
+            created: text(value, "created")?,
+            files,
+        })
+    }
+}
+
+/// Copies of the files a command replaces in a dataset directory, kept in
+/// `<dir>/.xpq-trash/<timestamp>-<random>` until restored by `undo`.
+///
+/// The copies are removed when the snapshot is dropped before being committed.
+pub struct Snapshot {
+    root: PathBuf,
+    dir: TempDir,
+    manifest: Manifest,
+}
+
+impl Snapshot {
+    pub fn create(root: &Path, command: &str) -> Result<Self> {
+        let now = chrono::Utc::now();
+        let trash = root.join(TRASH_DIR);
+
+        fs::create_dir_all(&trash)?;
+
+        let dir = tempfile::Builder::new()
+            .prefix(&format!("{}-", now.format("%Y%m%dT%H%M%S%9f")))
+            .rand_bytes(4)
+            .tempdir_in(&trash)?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            dir,
+            manifest: Manifest {
+                command: command.to_string(),
+                created: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                files: Vec::new(),
+            },
+        })
+    }
+
+    /// Keep a copy of a file before it is replaced, hard linked when possible.
+    pub fn keep(&mut self, path: &Path) -> Result<()> {
+        let relative = path
+            .strip_prefix(&self.root)
+            .map_err(|_| Error::InvalidArgument(path.display().to_string()))?;
+        let copy = format!("{}.parquet", self.manifest.files.len());
+        let target = self.dir.path().join(&copy);
+
+        if fs::hard_link(path, &target).is_err() {
+            fs::copy(path, &target)?;
+        }
+
+        self.manifest
+            .files
+            .push((relative.to_string_lossy().to_string(), copy));
+
+        Ok(())
+    }
+
+    /// Write the manifest once the files were replaced, returning the snapshot directory.
+    pub fn commit(self) -> Result<PathBuf> {
+        let dir = self.dir.into_path();
+        let json = serde_json::to_string_pretty(&self.manifest.to_json_value())?;
+
+        fs::write(dir.join(MANIFEST_FILE), json)?;
+
+        Ok(dir)
+    }
+}
+
+/// Committed snapshots of a dataset directory, from the oldest.
+pub fn snapshots(root: &Path) -> Result<Vec<(PathBuf, Manifest)>> {
+    let trash = root.join(TRASH_DIR);
+
+    if !trash.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut result = Vec::new();
+
+    for entry in fs::read_dir(trash)? {
+        let dir = entry?.path();
+
+        // snapshots of interrupted commands have no manifest
+        let json = match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+            Ok(json) => json,
+            Err(_) => continue,
+        };
+        let manifest = Manifest::from_json_value(&serde_json::from_str(&json)?)
+            .ok_or_else(|| Error::IO(format!("Invalid snapshot {}", dir.display())))?;
+
+        result.push((dir, manifest));
+    }
+
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(result)
+}
+
+/// Move the files of the latest snapshot back into place and remove it.
+///
+/// Returns `None` when the directory has no snapshot.
+pub fn restore(root: &Path) -> Result<Option<Manifest>> {
+    let (dir, manifest) = match snapshots(root)?.pop() {
+        Some(snapshot) => snapshot,
+        None => return Ok(None),
+    };
+
+    for (path, copy) in &manifest.files {
+        let path = root.join(path);
+        let _lock = WriteLock::acquire(&path)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::rename(dir.join(copy), &path)?;
+    }
+
+    fs::remove_dir_all(&dir)?;
+
+    Ok(Some(manifest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+
+    #[test]
+    fn test_snapshot_restore() {
+        let dir = api::tests::temp_dir();
+        let path = dir.path().join("out.parquet");
+
+        fs::write(&path, b"v1").unwrap();
+
+        let mut snapshot = Snapshot::create(dir.path(), "write").unwrap();
+
+        snapshot.keep(&path).unwrap();
+        drop(snapshot);
+
+        assert_eq!(snapshots(dir.path()).unwrap(), vec![]);
+        assert_eq!(fs::read_dir(dir.path().join(TRASH_DIR)).unwrap().count(), 0);
+
+        for version in [&b"v2"[..], b"v3"] {
+            let mut snapshot = Snapshot::create(dir.path(), "write").unwrap();
+
+            snapshot.keep(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            fs::write(&path, version).unwrap();
+            snapshot.commit().unwrap();
+        }
+
+        let manifest = restore(dir.path()).unwrap().unwrap();
+
+        assert_eq!(manifest.command, "write");
+        assert_eq!(
+            manifest.files,
+            vec![(String::from("out.parquet"), String::from("0.parquet"))]
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"v2");
+        assert_eq!(snapshots(dir.path()).unwrap().len(), 1);
+
+        restore(dir.path()).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"v1");
+        assert_eq!(restore(dir.path()), Ok(None));
+    }
+}
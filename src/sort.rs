@@ -0,0 +1,409 @@
+use crate::api::{Error, Result};
+use crate::value::Value;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+
+/// Limited sorts up to this number of rows keep a top-k heap in memory.
+pub const MAX_HEAP_ROWS: usize = 100_000;
+
+/// Number of rows sorted in memory before spilling them to a temporary file.
+pub const SPILL_ROWS: usize = 100_000;
+
+/// Column of a row to sort by.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub index: usize,
+    pub descending: bool,
+}
+
+/// Order of two rows by the sort keys, see `Value::total_cmp`.
+pub fn compare(a: &[Value], b: &[Value], keys: &[SortKey]) -> Ordering {
+    keys.iter().fold(Ordering::Equal, |ordering, key| {
+        ordering.then_with(|| {
+            let ordering = a[key.index].total_cmp(&b[key.index]);
+
+            if key.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+    })
+}
+
+/// Row ordered by the sort keys, ties are broken by `seq` so sorts are stable.
+struct Keyed {
+    row: Vec<Value>,
+    seq: usize,
+    keys: Rc<[SortKey]>,
+}
+
+impl Ord for Keyed {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(&self.row, &other.row, &self.keys).then(self.seq.cmp(&other.seq))
+    }
+}
+
+impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Keyed {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Keyed {}
+
+/// The first `limit` rows in order, keeping at most `limit` rows in memory.
+pub fn top_k<I>(iter: I, keys: &[SortKey], limit: usize) -> Result<Vec<Vec<Value>>>
+where
+    I: Iterator<Item = Result<Vec<Value>>>,
+{
+    let keys: Rc<[SortKey]> = Rc::from(keys);
+    let mut heap = BinaryHeap::with_capacity(limit.saturating_add(1).min(MAX_HEAP_ROWS));
+
+    if limit == 0 {
+        return Ok(vec![]);
+    }
+
+    for (seq, row) in iter.enumerate() {
+        heap.push(Keyed {
+            row: row?,
+            seq,
+            keys: keys.clone(),
+        });
+
+        if heap.len() > limit {
+            heap.pop();
+        }
+    }
+
+    Ok(heap.into_sorted_vec().into_iter().map(|k| k.row).collect())
+}
+
+/// Sort the rows using an external merge sort, runs of `spill_rows` rows are sorted in
+/// memory and written to temporary files which are merged while iterating.
+pub fn external_sort<I>(iter: I, keys: &[SortKey], spill_rows: usize) -> Result<Merge>
+where
+    I: Iterator<Item = Result<Vec<Value>>>,
+{
+    let mut runs = Vec::new();
+    let mut chunk = Vec::new();
+
+    for row in iter {
+        chunk.push(row?);
+
+        if chunk.len() >= spill_rows.max(1) {
+            runs.push(spill(&mut chunk, keys)?);
+        }
+    }
+
+    chunk.sort_by(|a, b| compare(a, b, keys));
+
+    // rows that fit in memory are never written
+    if runs.is_empty() {
+        return Ok(Merge::new(vec![], chunk, keys));
+    }
+
+    if !chunk.is_empty() {
+        runs.push(spill(&mut chunk, keys)?);
+    }
+
+    Ok(Merge::new(runs, chunk, keys))
+}
+
+/// Sort and write the rows to a temporary file, leaving the chunk empty.
+fn spill(chunk: &mut Vec<Vec<Value>>, keys: &[SortKey]) -> Result<BufReader<File>> {
+    let mut writer = BufWriter::new(tempfile::tempfile()?);
+
+    chunk.sort_by(|a, b| compare(a, b, keys));
+
+    for row in chunk.drain(..) {
+        write_row(&mut writer, &row)?;
+    }
+
+    let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(BufReader::new(file))
+}
+
+/// K-way merge of sorted runs, ties come from the earliest run.
+pub struct Merge {
+    runs: Vec<BufReader<File>>,
+    heap: BinaryHeap<std::cmp::Reverse<Keyed>>,
+    memory: std::vec::IntoIter<Vec<Value>>,
+    keys: Rc<[SortKey]>,
+    error: Option<Error>,
+}
+
+impl Merge {
+    fn new(
+        runs: Vec<BufReader<File>>,
+        memory: Vec<Vec<Value>>,
+        keys: &[SortKey],
+    ) -> Self {
+        let mut merge = Self {
+            heap: BinaryHeap::with_capacity(runs.len()),
+            runs,
+            memory: memory.into_iter(),
+            keys: Rc::from(keys),
+            error: None,
+        };
+
+        for run in 0..merge.runs.len() {
+            merge.advance(run);
+        }
+
+        merge
+    }
+
+    /// Push the next row of a run into the heap.
+    fn advance(&mut self, run: usize) {
+        match read_row(&mut self.runs[run]) {
+            Ok(Some(row)) => self.heap.push(std::cmp::Reverse(Keyed {
+                row,
+                seq: run,
+                keys: self.keys.clone(),
+            })),
+            Ok(None) => {}
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+impl Iterator for Merge {
+    type Item = Result<Vec<Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+
+        if self.runs.is_empty() {
+            return self.memory.next().map(Ok);
+        }
+
+        let std::cmp::Reverse(head) = self.heap.pop()?;
+
+        self.advance(head.seq);
+
+        Some(Ok(head.row))
+    }
+}
+
+#[inline]
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+/// Write a row as a number of values followed by each tagged value.
+fn write_row<W: Write>(writer: &mut W, row: &[Value]) -> io::Result<()> {
+    writer.write_all(&(row.len() as u64).to_le_bytes())?;
+
+    for value in row {
+        match value {
+            Value::Null => writer.write_all(&[0])?,
+            Value::Bool(v) => writer.write_all(&[1, u8::from(*v)])?,
+            Value::Int(v) => {
+                writer.write_all(&[2])?;
+                writer.write_all(&v.to_le_bytes())?
+            }
+            Value::UInt(v) => {
+                writer.write_all(&[3])?;
+                writer.write_all(&v.to_le_bytes())?
+            }
+            Value::Float(v) => {
+                writer.write_all(&[4])?;
+                writer.write_all(&v.to_le_bytes())?
+            }
+            Value::Double(v) => {
+                writer.write_all(&[5])?;
+                writer.write_all(&v.to_le_bytes())?
+            }
+            Value::Str(v) => {
+                writer.write_all(&[6])?;
+                write_bytes(writer, v.as_bytes())?
+            }
+            Value::Bytes(v) => {
+                writer.write_all(&[7])?;
+                write_bytes(writer, v)?
+            }
+            Value::Date(v) => {
+                writer.write_all(&[8])?;
+                writer.write_all(&v.to_le_bytes())?
+            }
+            Value::Timestamp(v) => {
+                writer.write_all(&[9])?;
+                writer.write_all(&v.to_le_bytes())?
+            }
+            Value::Decimal(v) => {
+                writer.write_all(&[10])?;
+                write_bytes(writer, v.as_bytes())?
+            }
+            Value::Nested(v) => {
+                writer.write_all(&[11])?;
+                write_bytes(writer, v.as_bytes())?
+            }
+            Value::Time(v) => {
+                writer.write_all(&[12])?;
+                writer.write_all(&v.to_le_bytes())?
+            }
+            Value::Uuid(v) => {
+                writer.write_all(&[13])?;
+                write_bytes(writer, v.as_bytes())?
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+
+    reader.read_exact(&mut buf)?;
+
+    Ok(buf)
+}
+
+#[inline]
+fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let len = u64::from_le_bytes(read_array(reader)?) as usize;
+    let mut buf = vec![0; len];
+
+    reader.read_exact(&mut buf)?;
+
+    Ok(buf)
+}
+
+#[inline]
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    String::from_utf8(read_bytes(reader)?).map_err(|e| Error::IO(e.to_string()))
+}
+
+/// Read the next row written by `write_row`, `None` at the end of the file.
+fn read_row<R: Read>(reader: &mut R) -> Result<Option<Vec<Value>>> {
+    let mut len = [0; 8];
+
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u64::from_le_bytes(len) as usize;
+    let mut row = Vec::with_capacity(len);
+
+    for _ in 0..len {
+        let [tag] = read_array(reader)?;
+        let value = match tag {
+            0 => Value::Null,
+            1 => Value::Bool(read_array::<_, 1>(reader)?[0] != 0),
+            2 => Value::Int(i64::from_le_bytes(read_array(reader)?)),
+            3 => Value::UInt(u64::from_le_bytes(read_array(reader)?)),
+            4 => Value::Float(f32::from_le_bytes(read_array(reader)?)),
+            5 => Value::Double(f64::from_le_bytes(read_array(reader)?)),
+            6 => Value::Str(read_string(reader)?),
+            7 => Value::Bytes(read_bytes(reader)?),
+            8 => Value::Date(u32::from_le_bytes(read_array(reader)?)),
+            9 => Value::Timestamp(u64::from_le_bytes(read_array(reader)?)),
+            10 => Value::Decimal(read_string(reader)?),
+            11 => Value::Nested(read_string(reader)?),
+            12 => Value::Time(i64::from_le_bytes(read_array(reader)?)),
+            13 => Value::Uuid(read_string(reader)?),
+            tag => return Err(Error::IO(format!("Invalid sort value tag {}", tag))),
+        };
+
+        row.push(value);
+    }
+
+    Ok(Some(row))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Vec<Value>> {
+        (0..10)
+            .map(|i| vec![Value::Int(i % 3), Value::UInt(i as u64)])
+            .collect()
+    }
+
+    fn ids(rows: &[Vec<Value>]) -> Vec<String> {
+        rows.iter().map(|r| r[1].to_string()).collect()
+    }
+
+    const KEYS: [SortKey; 1] = [SortKey {
+        index: 0,
+        descending: true,
+    }];
+
+    #[test]
+    fn test_sort_top_k() {
+        let sorted = top_k(rows().into_iter().map(Ok), &KEYS, 4).unwrap();
+
+        assert_eq!(ids(&sorted), vec!["2", "5", "8", "1"]);
+        assert!(top_k(rows().into_iter().map(Ok), &KEYS, 0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_sort_external() {
+        let mut expected = rows();
+
+        expected.sort_by(|a, b| compare(a, b, &KEYS));
+
+        for spill_rows in [1, 3, 100] {
+            let sorted = external_sort(rows().into_iter().map(Ok), &KEYS, spill_rows)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+
+            assert_eq!(sorted, expected);
+        }
+    }
+
+    #[test]
+    fn test_sort_row_encoding() {
+        let row = vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::Int(-1),
+            Value::UInt(2),
+            Value::Float(1.5),
+            Value::Double(-2.5),
+            Value::Str(String::from("ção")),
+            Value::Bytes(vec![0, 255]),
+            Value::Date(19000),
+            Value::Timestamp(1),
+            Value::Decimal(String::from("1.20")),
+            Value::Nested(String::from("{a: 1}")),
+            Value::Time(1),
+            Value::Uuid(String::from("f81d4fae-7dec-11d0-a765-00a0c91e6bf6")),
+        ];
+        let mut buf = Vec::new();
+
+        write_row(&mut buf, &row).unwrap();
+        write_row(&mut buf, &[]).unwrap();
+
+        let mut reader = buf.as_slice();
+
+        assert_eq!(read_row(&mut reader).unwrap(), Some(row));
+        assert_eq!(read_row(&mut reader).unwrap(), Some(vec![]));
+        assert_eq!(read_row(&mut reader).unwrap(), None);
+        assert!(read_row(&mut [1, 0, 0, 0, 0, 0, 0, 0, 99].as_slice()).is_err());
+    }
+}
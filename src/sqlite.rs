@@ -0,0 +1,478 @@
+use crate::api::Result;
+use std::io::{Seek, SeekFrom, Write};
+
+/// Size of every page of the database.
+pub const PAGE_SIZE: usize = 4096;
+
+/// Size of the database header at the start of the first page.
+const HEADER_SIZE: usize = 100;
+
+/// Largest payload of a table leaf cell kept in the page, larger payloads overflow.
+const MAX_LOCAL: usize = PAGE_SIZE - 35;
+
+/// Smallest part of an overflowing payload kept in the page.
+const MIN_LOCAL: usize = (PAGE_SIZE - 12) * 32 / 255 - 23;
+
+/// Payload bytes of each overflow page, after the next page number.
+const OVERFLOW_SIZE: usize = PAGE_SIZE - 4;
+
+/// Children of an interior page, sized for the largest keys.
+const INTERIOR_CHILDREN: usize = (PAGE_SIZE - 12) / (4 + 9 + 2);
+
+const LEAF_TABLE_PAGE: u8 = 0x0d;
+const INTERIOR_TABLE_PAGE: u8 = 0x05;
+
+/// Value stored in a database cell.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+/// Append a variable length integer, big endian with 7 bits per byte
+/// and all 8 bits of the ninth byte.
+fn put_varint(out: &mut Vec<u8>, value: u64) {
+    if value > 0x00ff_ffff_ffff_ffff {
+        let mut bytes = [0x80; 9];
+        let mut rest = value >> 8;
+
+        bytes[8] = value as u8;
+
+        for byte in bytes[..8].iter_mut().rev() {
+            *byte |= (rest & 0x7f) as u8;
+            rest >>= 7;
+        }
+
+        out.extend_from_slice(&bytes);
+
+        return;
+    }
+
+    let mut bytes = Vec::with_capacity(9);
+    let mut rest = value;
+
+    loop {
+        bytes.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+
+        if rest == 0 {
+            break;
+        }
+    }
+
+    bytes[0] &= 0x7f;
+    out.extend(bytes.iter().rev());
+}
+
+#[inline]
+fn varint_len(value: u64) -> usize {
+    let mut out = Vec::with_capacity(9);
+
+    put_varint(&mut out, value);
+
+    out.len()
+}
+
+/// Serial type and big endian bytes of an integer, using the smallest size holding it.
+fn integer(value: i64) -> (u64, Vec<u8>) {
+    let bytes = value.to_be_bytes();
+
+    match value {
+        0 => (8, vec![]),
+        1 => (9, vec![]),
+        -0x80..=0x7f => (1, bytes[7..].to_vec()),
+        -0x8000..=0x7fff => (2, bytes[6..].to_vec()),
+        -0x80_0000..=0x7f_ffff => (3, bytes[5..].to_vec()),
+        -0x8000_0000..=0x7fff_ffff => (4, bytes[4..].to_vec()),
+        -0x8000_0000_0000..=0x7fff_ffff_ffff => (5, bytes[2..].to_vec()),
+        _ => (6, bytes.to_vec()),
+    }
+}
+
+/// Encode the values as a record, a header of serial types followed by the values.
+pub fn record(values: &[Value]) -> Vec<u8> {
+    let mut types = Vec::new();
+    let mut body = Vec::new();
+
+    for value in values {
+        let serial_type = match value {
+            Value::Null => 0,
+            Value::Integer(v) => {
+                let (serial_type, bytes) = integer(*v);
+
+                body.extend(bytes);
+                serial_type
+            }
+            Value::Real(v) => {
+                body.extend_from_slice(&v.to_be_bytes());
+                7
+            }
+            Value::Text(v) => {
+                body.extend_from_slice(v.as_bytes());
+                v.len() as u64 * 2 + 13
+            }
+            Value::Blob(v) => {
+                body.extend_from_slice(v);
+                v.len() as u64 * 2 + 12
+            }
+        };
+
+        put_varint(&mut types, serial_type);
+    }
+
+    // the header size includes its own varint
+    let mut size = types.len() + 1;
+
+    while varint_len(size as u64) + types.len() > size {
+        size = varint_len(size as u64) + types.len();
+    }
+
+    let mut result = Vec::with_capacity(size + body.len());
+
+    put_varint(&mut result, size as u64);
+    result.extend(types);
+    result.extend(body);
+
+    result
+}
+
+/// Bytes of a payload kept in its leaf page.
+#[inline]
+fn local_size(payload: usize) -> usize {
+    if payload <= MAX_LOCAL {
+        return payload;
+    }
+
+    let size = MIN_LOCAL + (payload - MIN_LOCAL) % OVERFLOW_SIZE;
+
+    if size <= MAX_LOCAL {
+        size
+    } else {
+        MIN_LOCAL
+    }
+}
+
+/// Cells of a b-tree page, written from the end of the page.
+#[derive(Debug, Default)]
+struct Page {
+    cells: Vec<Vec<u8>>,
+    size: usize,
+}
+
+impl Page {
+    /// Whether a cell fits a page whose header starts at `offset`.
+    #[inline]
+    fn fits(&self, cell: usize, offset: usize, header: usize) -> bool {
+        offset + header + self.size + cell + 2 <= PAGE_SIZE
+    }
+
+    #[inline]
+    fn push(&mut self, cell: Vec<u8>) {
+        self.size += cell.len() + 2;
+        self.cells.push(cell);
+    }
+
+    /// Page bytes, the header starts at `offset` and is followed by the cell pointers.
+    fn encode(&self, kind: u8, offset: usize, right: Option<u32>) -> Vec<u8> {
+        let mut page = vec![0; PAGE_SIZE];
+        let header = if right.is_some() { 12 } else { 8 };
+        let mut content = PAGE_SIZE;
+
+        page[offset] = kind;
+        page[offset + 3..offset + 5]
+            .copy_from_slice(&(self.cells.len() as u16).to_be_bytes());
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            let pointer = offset + header + i * 2;
+
+            content -= cell.len();
+            page[content..content + cell.len()].copy_from_slice(cell);
+            page[pointer..pointer + 2].copy_from_slice(&(content as u16).to_be_bytes());
+        }
+
+        page[offset + 5..offset + 7].copy_from_slice(&(content as u16).to_be_bytes());
+
+        if let Some(right) = right {
+            page[offset + 8..offset + 12].copy_from_slice(&right.to_be_bytes());
+        }
+
+        page
+    }
+}
+
+/// Writes a database of a single table, the rows are written as they are inserted
+/// and the table b-tree is built from the leaf pages when finished.
+///
+/// Pages are numbered in the order they are written, the first page holding the
+/// database header and the schema is written last.
+pub struct TableWriter<F> {
+    file: F,
+    page: Page,
+    leaves: Vec<(u32, i64)>,
+    next_page: u32,
+    rowid: i64,
+}
+
+impl<F: Write + Seek> TableWriter<F> {
+    pub fn new(file: F) -> Self {
+        Self {
+            file,
+            page: Page::default(),
+            leaves: Vec::new(),
+            next_page: 2,
+            rowid: 0,
+        }
+    }
+
+    /// Number of rows inserted.
+    pub fn rows(&self) -> i64 {
+        self.rowid
+    }
+
+    #[inline]
+    fn allocate(&mut self) -> u32 {
+        self.next_page += 1;
+        self.next_page - 1
+    }
+
+    fn write_page(&mut self, number: u32, page: &[u8]) -> Result<()> {
+        let position = (u64::from(number) - 1) * PAGE_SIZE as u64;
+
+        self.file.seek(SeekFrom::Start(position))?;
+        self.file.write_all(page)?;
+
+        Ok(())
+    }
+
+    /// Leaf cell of the payload, the bytes not kept in the page go to overflow pages.
+    fn leaf_cell(&mut self, rowid: i64, payload: &[u8]) -> Result<Vec<u8>> {
+        let local = local_size(payload.len());
+        let mut cell = Vec::with_capacity(local + 22);
+
+        put_varint(&mut cell, payload.len() as u64);
+        put_varint(&mut cell, rowid as u64);
+        cell.extend_from_slice(&payload[..local]);
+
+        if local == payload.len() {
+            return Ok(cell);
+        }
+
+        let chunks = payload[local..].chunks(OVERFLOW_SIZE).collect::<Vec<_>>();
+        let first = self.next_page;
+
+        cell.extend_from_slice(&first.to_be_bytes());
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let number = self.allocate();
+            let next = if i + 1 < chunks.len() { number + 1 } else { 0 };
+            let mut page = vec![0; PAGE_SIZE];
+
+            page[..4].copy_from_slice(&next.to_be_bytes());
+            page[4..4 + chunk.len()].copy_from_slice(chunk);
+
+            self.write_page(number, &page)?;
+        }
+
+        Ok(cell)
+    }
+
+    fn flush_leaf(&mut self) -> Result<()> {
+        let number = self.allocate();
+        let page = std::mem::take(&mut self.page).encode(LEAF_TABLE_PAGE, 0, None);
+
+        self.write_page(number, &page)?;
+        self.leaves.push((number, self.rowid));
+
+        Ok(())
+    }
+
+    /// Append a row, rowids start at 1.
+    pub fn insert(&mut self, values: &[Value]) -> Result<()> {
+        let rowid = self.rowid + 1;
+        let cell = self.leaf_cell(rowid, &record(values))?;
+
+        if !self.page.fits(cell.len(), 0, 8) {
+            self.flush_leaf()?;
+        }
+
+        self.page.push(cell);
+        self.rowid = rowid;
+
+        Ok(())
+    }
+
+    /// Write the interior pages above the children, returning the root page.
+    ///
+    /// Children are spread evenly over the pages of each level,
+    /// so every interior page holds at least one cell besides its right-most child.
+    fn write_interior(&mut self, mut children: Vec<(u32, i64)>) -> Result<u32> {
+        while children.len() > 1 {
+            let pages = children.len().div_ceil(INTERIOR_CHILDREN);
+            let mut rest = children.into_iter();
+
+            children = Vec::with_capacity(pages);
+
+            for i in 0..pages {
+                let size = rest.len() / (pages - i);
+                let group = rest.by_ref().take(size).collect::<Vec<_>>();
+                let (right, key) = group[group.len() - 1];
+                let mut page = Page::default();
+
+                for (child, key) in &group[..group.len() - 1] {
+                    let mut cell = child.to_be_bytes().to_vec();
+
+                    put_varint(&mut cell, *key as u64);
+                    page.push(cell);
+                }
+
+                let number = self.allocate();
+
+                self.write_page(
+                    number,
+                    &page.encode(INTERIOR_TABLE_PAGE, 0, Some(right)),
+                )?;
+                children.push((number, key));
+            }
+        }
+
+        Ok(children[0].0)
+    }
+
+    /// Write the table b-tree and the first page with the schema of the table,
+    /// `sql` is the `CREATE TABLE` statement of the table.
+    pub fn finish(mut self, name: &str, sql: &str) -> Result<F> {
+        if !self.page.cells.is_empty() || self.leaves.is_empty() {
+            self.flush_leaf()?;
+        }
+
+        let leaves = std::mem::take(&mut self.leaves);
+        let root = self.write_interior(leaves)?;
+        let schema = |sql: &str| {
+            record(&[
+                Value::Text(String::from("table")),
+                Value::Text(name.to_string()),
+                Value::Text(name.to_string()),
+                Value::Integer(i64::from(root)),
+                Value::Text(sql.to_string()),
+            ])
+        };
+        let mut sql = sql.to_string();
+        let mut payload = schema(&sql);
+
+        // the first page holds less than the others after the database header,
+        // the statement is padded so that a smaller part of it is kept in the page
+        while !Page::default().fits(local_size(payload.len()) + 22, HEADER_SIZE, 8) {
+            let padding = if payload.len() <= MAX_LOCAL {
+                MAX_LOCAL + 1 - payload.len()
+            } else {
+                OVERFLOW_SIZE - (payload.len() - MIN_LOCAL) % OVERFLOW_SIZE
+            };
+
+            sql.push_str(&" ".repeat(padding));
+            payload = schema(&sql);
+        }
+
+        let mut page = Page::default();
+
+        page.push(self.leaf_cell(1, &payload)?);
+
+        let mut first = page.encode(LEAF_TABLE_PAGE, HEADER_SIZE, None);
+        let pages = self.next_page - 1;
+
+        first[..HEADER_SIZE].copy_from_slice(&header(pages));
+
+        self.write_page(1, &first)?;
+        self.file.flush()?;
+
+        Ok(self.file)
+    }
+}
+
+/// Database header of a file of `pages` pages, without free pages.
+fn header(pages: u32) -> [u8; HEADER_SIZE] {
+    let mut header = [0; HEADER_SIZE];
+    let mut put = |offset: usize, value: u32| {
+        header[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    };
+
+    // file change counter, database size and schema cookie
+    put(24, 1);
+    put(28, pages);
+    put(40, 1);
+    // schema format, text encoding (utf-8) and change counter the size is valid for
+    put(44, 4);
+    put(56, 1);
+    put(92, 1);
+    put(96, 3_040_001);
+
+    header[..16].copy_from_slice(b"SQLite format 3\0");
+    header[16..18].copy_from_slice(&(PAGE_SIZE as u16).to_be_bytes());
+    // file format versions, reserved bytes and payload fractions
+    header[18..24].copy_from_slice(&[1, 1, 0, 64, 32, 32]);
+
+    header
+}
+
+/// Quote an identifier with double quotes, eg: `"user name"`.
+pub fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_sqlite_record() {
+        let mut out = Vec::new();
+
+        put_varint(&mut out, 0x7f);
+        put_varint(&mut out, 0x80);
+        put_varint(&mut out, u64::MAX);
+
+        assert_eq!(out[..3], [0x7f, 0x81, 0x00]);
+        assert_eq!(out[3..], [0xff; 9]);
+
+        assert_eq!(
+            record(&[
+                Value::Null,
+                Value::Integer(1),
+                Value::Integer(-2),
+                Value::Integer(300),
+                Value::Text(String::from("ab")),
+                Value::Blob(vec![7]),
+            ]),
+            vec![7, 0, 9, 1, 2, 17, 14, 0xfe, 0x01, 0x2c, b'a', b'b', 7]
+        );
+    }
+
+    #[test]
+    fn test_sqlite_table_writer() {
+        let mut writer = TableWriter::new(Cursor::new(Vec::new()));
+        let text = "x".repeat(10_000);
+
+        for i in 0..2000 {
+            writer
+                .insert(&[Value::Integer(i), Value::Text(format!("row {}", i))])
+                .unwrap();
+        }
+
+        writer
+            .insert(&[Value::Integer(0), Value::Text(text)])
+            .unwrap();
+
+        let rows = writer.rows();
+        let file = writer.finish("t", "CREATE TABLE t (a, b)").unwrap();
+        let bytes = file.into_inner();
+        let pages = u32::from_be_bytes([bytes[28], bytes[29], bytes[30], bytes[31]]);
+
+        assert_eq!(rows, 2001);
+        assert_eq!(&bytes[..16], b"SQLite format 3\0");
+        assert_eq!(bytes.len(), pages as usize * PAGE_SIZE);
+        assert_eq!(bytes[HEADER_SIZE], LEAF_TABLE_PAGE);
+    }
+}
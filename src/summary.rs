@@ -0,0 +1,171 @@
+use crate::api::{Error, Result};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Counters of a run, written as json by `--stats-output`.
+#[derive(Debug, Default)]
+struct Summary {
+    files: Mutex<HashSet<PathBuf>>,
+    rows_scanned: AtomicU64,
+    rows_emitted: AtomicU64,
+    row_groups: AtomicU64,
+    row_groups_pruned: AtomicU64,
+    warnings: Mutex<Vec<String>>,
+}
+
+impl Summary {
+    fn add_file(&self, path: &Path) {
+        if let Ok(mut files) = self.files.lock() {
+            files.insert(path.to_path_buf());
+        }
+    }
+
+    fn add_row_groups(&self, total: usize, pruned: usize) {
+        self.row_groups.fetch_add(total as u64, Ordering::Relaxed);
+        self.row_groups_pruned
+            .fetch_add(pruned as u64, Ordering::Relaxed);
+    }
+
+    fn warn(&self, message: &str) {
+        if let Ok(mut warnings) = self.warnings.lock() {
+            warnings.push(message.to_string());
+        }
+    }
+
+    /// Json of the counters, with the command, its duration and its error if any.
+    fn to_json_value(
+        &self,
+        command: Option<&str>,
+        duration: Duration,
+        error: Option<&Error>,
+    ) -> serde_json::Value {
+        let files = self.files.lock().map_or(0, |f| f.len());
+        let warnings = self.warnings.lock().map_or(Vec::new(), |w| w.clone());
+
+        json!({
+            "command": command,
+            "duration_ms": duration.as_millis() as u64,
+            "files": files,
+            "rows_scanned": self.rows_scanned.load(Ordering::Relaxed),
+            "rows_emitted": self.rows_emitted.load(Ordering::Relaxed),
+            "row_groups": {
+                "total": self.row_groups.load(Ordering::Relaxed),
+                "pruned": self.row_groups_pruned.load(Ordering::Relaxed),
+            },
+            "warnings": warnings,
+            "error": error.map(|e| e.to_string()),
+        })
+    }
+}
+
+static SUMMARY: OnceLock<Summary> = OnceLock::new();
+
+/// Keep the counters of the run, only the first call has any effect.
+pub fn enable() {
+    let _ = SUMMARY.set(Summary::default());
+}
+
+/// Record a file opened by the run, files opened more than once count once.
+#[inline]
+pub fn add_file(path: &Path) {
+    if let Some(summary) = SUMMARY.get() {
+        summary.add_file(path);
+    }
+}
+
+/// Record rows read from the files, before filters.
+#[inline]
+pub fn add_rows_scanned(rows: usize) {
+    if let Some(summary) = SUMMARY.get() {
+        summary
+            .rows_scanned
+            .fetch_add(rows as u64, Ordering::Relaxed);
+    }
+}
+
+/// Record rows written to the output.
+#[inline]
+pub fn add_rows_emitted(rows: usize) {
+    if let Some(summary) = SUMMARY.get() {
+        summary
+            .rows_emitted
+            .fetch_add(rows as u64, Ordering::Relaxed);
+    }
+}
+
+/// Record the row groups of a scanned file and how many were skipped by the filters.
+#[inline]
+pub fn add_row_groups(total: usize, pruned: usize) {
+    if let Some(summary) = SUMMARY.get() {
+        summary.add_row_groups(total, pruned);
+    }
+}
+
+/// Record a warning reported on stderr, eg: `note: 2 of 10 files have no rows`.
+pub fn warn(message: &str) {
+    if let Some(summary) = SUMMARY.get() {
+        summary.warn(message);
+    }
+}
+
+/// Write the summary of the run as a json file.
+pub fn write(
+    path: &Path,
+    command: Option<&str>,
+    duration: Duration,
+    error: Option<&Error>,
+) -> Result<()> {
+    let summary = SUMMARY.get_or_init(Summary::default);
+    let json = summary.to_json_value(command, duration, error);
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    serde_json::to_writer_pretty(&mut writer, &json)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_json() {
+        let summary = Summary::default();
+
+        summary.add_file(Path::new("a.parquet"));
+        summary.add_file(Path::new("b.parquet"));
+        summary.add_file(Path::new("a.parquet"));
+        summary.add_row_groups(4, 3);
+        summary.rows_scanned.fetch_add(10, Ordering::Relaxed);
+        summary.rows_emitted.fetch_add(2, Ordering::Relaxed);
+        summary.warn("note: 1 of 2 files have no rows");
+
+        let error = Error::InvalidArgument(String::from("search"));
+
+        assert_eq!(
+            summary.to_json_value(
+                Some("read"),
+                Duration::from_millis(1500),
+                Some(&error)
+            ),
+            json!({
+                "command": "read",
+                "duration_ms": 1500,
+                "files": 2,
+                "rows_scanned": 10,
+                "rows_emitted": 2,
+                "row_groups": {"total": 4, "pruned": 3},
+                "warnings": ["note: 1 of 2 files have no rows"],
+                "error": "Invalid argument: search",
+            })
+        );
+    }
+}
@@ -0,0 +1,570 @@
+use crate::api::{Error, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone as _};
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Format of timestamps when no other format is given, the same as parquet fields.
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z";
+
+const MICROS_PER_SECOND: u64 = 1_000_000;
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+/// Zoneinfo directory, `$TZDIR` or the system zoneinfo database.
+fn zoneinfo_dir() -> PathBuf {
+    env::var_os("TZDIR")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/usr/share/zoneinfo"))
+}
+
+/// Daylight saving time transition of a POSIX TZ rule, eg: `M3.2.0/2`,
+/// the `day` of the `week` of the month at `time` seconds of local time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Transition {
+    month: u32,
+    week: u32,
+    day: u32,
+    time: i64,
+}
+
+impl Transition {
+    /// Local seconds since the epoch of the transition in the given year.
+    fn local_seconds(&self, year: i32) -> Option<i64> {
+        let first = NaiveDate::from_ymd_opt(year, self.month, 1)?;
+        let next = match self.month {
+            12 => NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+            month => NaiveDate::from_ymd_opt(year, month + 1, 1)?,
+        };
+        let days = next.signed_duration_since(first).num_days() as u32;
+        let weekday = first.weekday().num_days_from_sunday();
+        let mut day = 1 + (self.day + 7 - weekday) % 7 + (self.week - 1) * 7;
+
+        // week 5 is the last week of the month
+        while day > days {
+            day -= 7;
+        }
+
+        let date = first.with_day(day)?.and_hms_opt(0, 0, 0)?;
+
+        Some(date.timestamp() + self.time)
+    }
+}
+
+/// Offsets of a POSIX TZ rule, eg: `EST5EDT,M3.2.0,M11.1.0`,
+/// used after the last transition of a zoneinfo file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+    std: i32,
+    dst: Option<(i32, Transition, Transition)>,
+}
+
+impl Rule {
+    fn offset_at(&self, seconds: i64) -> i32 {
+        let (dst, start, end) = match &self.dst {
+            Some(dst) => dst,
+            None => return self.std,
+        };
+
+        let year = match FixedOffset::east_opt(self.std)
+            .and_then(|o| o.timestamp_opt(seconds, 0).single())
+        {
+            Some(local) => local.year(),
+            None => return self.std,
+        };
+
+        // the start is given in standard time and the end in daylight saving time
+        let start = start.local_seconds(year).map(|s| s - i64::from(self.std));
+        let end = end.local_seconds(year).map(|s| s - i64::from(*dst));
+        let in_dst = match (start, end) {
+            (Some(start), Some(end)) if start < end => start <= seconds && seconds < end,
+            (Some(start), Some(end)) => !(end <= seconds && seconds < start),
+            _ => false,
+        };
+
+        if in_dst {
+            *dst
+        } else {
+            self.std
+        }
+    }
+}
+
+/// Timezone names are letters, eg: `EST`, or anything between `<>`, eg: `<-03>`.
+fn skip_name(rule: &str) -> Option<&str> {
+    match rule.strip_prefix('<') {
+        Some(rest) => rest.find('>').map(|i| &rest[i + 1..]),
+        None => {
+            let end = rule
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rule.len());
+
+            if end < 3 {
+                None
+            } else {
+                Some(&rule[end..])
+            }
+        }
+    }
+}
+
+/// Parse a `[+-]hh[:mm[:ss]]` time in seconds, returning the rest of the text.
+fn parse_time(text: &str) -> Option<(i64, &str)> {
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == ':' || c == '+' || c == '-'))
+        .unwrap_or(text.len());
+    let (time, rest) = text.split_at(end);
+    let (sign, time) = match time.strip_prefix('-') {
+        Some(time) => (-1, time),
+        None => (1, time.strip_prefix('+').unwrap_or(time)),
+    };
+    let mut seconds = 0;
+    let mut parts = 0;
+
+    for part in time.split(':') {
+        seconds = seconds * 60 + part.parse::<i64>().ok()?;
+        parts += 1;
+    }
+
+    if parts > 3 {
+        return None;
+    }
+
+    Some((sign * seconds * 60_i64.pow(3 - parts), rest))
+}
+
+/// Parse a `Mm.w.d[/time]` transition, julian day rules are not supported.
+fn parse_transition(text: &str) -> Option<Transition> {
+    let (date, time) = match text.split_once('/') {
+        Some((date, time)) => (date, parse_time(time).filter(|t| t.1.is_empty())?.0),
+        None => (text, 2 * 3600),
+    };
+    let mut parts = date.strip_prefix('M')?.split('.').map(|p| p.parse().ok());
+    let transition = Transition {
+        month: parts.next()??,
+        week: parts.next()??,
+        day: parts.next()??,
+        time,
+    };
+
+    match transition {
+        Transition {
+            month: 1..=12,
+            week: 1..=5,
+            day: 0..=6,
+            ..
+        } => Some(transition),
+        _ => None,
+    }
+}
+
+/// Parse a POSIX TZ rule, note that POSIX offsets are positive west of Greenwich.
+fn parse_rule(rule: &str) -> Option<Rule> {
+    let (std, rest) = parse_time(skip_name(rule)?)?;
+    let std = -std as i32;
+
+    if rest.is_empty() {
+        return Some(Rule { std, dst: None });
+    }
+
+    let rest = skip_name(rest)?;
+    let (dst, rest) = match rest.strip_prefix(',') {
+        Some(_) => (std + 3600, rest),
+        None => parse_time(rest).map(|(dst, rest)| (-dst as i32, rest))?,
+    };
+    let mut transitions = rest.strip_prefix(',')?.split(',').map(parse_transition);
+    let start = transitions.next()??;
+    let end = transitions.next()??;
+
+    Some(Rule {
+        std,
+        dst: Some((dst, start, end)),
+    })
+}
+
+/// Offsets of a timezone from the transitions of a zoneinfo (TZif) file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Zone {
+    initial: i32,
+    transitions: Vec<(i64, i32)>,
+    rule: Option<Rule>,
+}
+
+/// Reads big endian integers of a TZif file.
+struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+
+        let (head, tail) = self.data.split_at(len);
+
+        self.data = tail;
+
+        Some(head)
+    }
+
+    fn int(&mut self, len: usize) -> Option<i64> {
+        let bytes = self.take(len)?;
+        let value = bytes.iter().fold(0_i64, |v, b| (v << 8) | i64::from(*b));
+        let bits = 64 - 8 * len as u32;
+
+        // sign extend 4 byte values
+        Some((value << bits) >> bits)
+    }
+}
+
+impl Zone {
+    /// Parse a TZif file, see RFC 8536.
+    fn parse(data: &[u8]) -> Option<Self> {
+        let mut cursor = Cursor { data };
+        let header = |cursor: &mut Cursor| -> Option<(u8, [usize; 6])> {
+            if cursor.take(4)? != b"TZif" {
+                return None;
+            }
+
+            let version = cursor.take(16)?[0];
+            let mut counts = [0; 6];
+
+            for count in counts.iter_mut() {
+                *count = cursor.int(4)? as usize;
+            }
+
+            Some((version, counts))
+        };
+
+        let (version, counts) = header(&mut cursor)?;
+        let (time_size, counts) = if version >= b'2' {
+            let [isut, isstd, leap, time, kind, chars] = counts;
+
+            // skip the 32-bit data, version 2 data follows with 64-bit times
+            cursor.take(time * 5 + kind * 6 + chars + leap * 8 + isstd + isut)?;
+
+            (8, header(&mut cursor)?.1)
+        } else {
+            (4, counts)
+        };
+
+        let [isut, isstd, leap, time, kind, chars] = counts;
+        let times = (0..time)
+            .map(|_| cursor.int(time_size))
+            .collect::<Option<Vec<_>>>()?;
+        let indexes = cursor.take(time)?.to_vec();
+        let offsets = (0..kind)
+            .map(|_| {
+                let offset = cursor.int(4)? as i32;
+
+                cursor.take(2)?;
+
+                Some(offset)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        cursor.take(chars + leap * (time_size + 4) + isstd + isut)?;
+
+        let transitions = times
+            .into_iter()
+            .zip(indexes)
+            .map(|(t, i)| offsets.get(i as usize).map(|o| (t, *o)))
+            .collect::<Option<Vec<_>>>()?;
+        let rule = if version >= b'2' {
+            std::str::from_utf8(cursor.data)
+                .ok()
+                .map(|footer| footer.trim_matches('\n'))
+                .and_then(parse_rule)
+        } else {
+            None
+        };
+
+        Some(Self {
+            initial: *offsets.first()?,
+            transitions,
+            rule,
+        })
+    }
+
+    fn offset_at(&self, seconds: i64) -> i32 {
+        let index = self.transitions.partition_point(|t| t.0 <= seconds);
+
+        match (index, &self.rule) {
+            (0, _) => self.initial,
+            (i, Some(rule)) if i == self.transitions.len() => rule.offset_at(seconds),
+            (i, _) => self.transitions[i - 1].1,
+        }
+    }
+}
+
+/// Timezone used to render timestamps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeZone {
+    // Constant offset from UTC in seconds
+    Fixed(i32),
+
+    // Timezone of the zoneinfo database, eg: America/Sao_Paulo
+    Zone(Zone),
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        TimeZone::Fixed(0)
+    }
+}
+
+impl TimeZone {
+    /// Parse `UTC`, an offset, eg: `-03:00`, `local` or a zoneinfo name,
+    /// zoneinfo files are read from `$TZDIR` or `/usr/share/zoneinfo`.
+    pub fn parse(name: &str) -> Result<Self> {
+        let error = || Error::InvalidArgument(format!("Unknown timezone '{}'", name));
+
+        if name.eq_ignore_ascii_case("utc") || name == "Z" {
+            return Ok(TimeZone::Fixed(0));
+        }
+
+        if name.starts_with('+') || name.starts_with('-') {
+            let text = match (name.len(), name.contains(':')) {
+                (5, false) => format!("{}:{}", &name[..3], &name[3..]),
+                _ => name.to_string(),
+            };
+
+            return match parse_time(&text) {
+                Some((offset, "")) if offset.abs() < 24 * 3600 => {
+                    Ok(TimeZone::Fixed((offset - offset % 60) as i32))
+                }
+                _ => Err(error()),
+            };
+        }
+
+        let path = match name {
+            "local" => PathBuf::from("/etc/localtime"),
+            _ if Path::new(name)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_))) =>
+            {
+                zoneinfo_dir().join(name)
+            }
+            _ => return Err(error()),
+        };
+
+        fs::read(path)
+            .ok()
+            .and_then(|data| Zone::parse(&data))
+            .map(TimeZone::Zone)
+            .ok_or_else(error)
+    }
+
+    /// Offset from UTC in seconds at the given seconds since the epoch.
+    pub fn offset_at(&self, seconds: i64) -> i32 {
+        match self {
+            TimeZone::Fixed(offset) => *offset,
+            TimeZone::Zone(zone) => zone.offset_at(seconds),
+        }
+    }
+}
+
+/// How timestamps are rendered by the outputs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeFormat {
+    pub timezone: TimeZone,
+    pub format: Option<String>,
+    pub unix: bool,
+}
+
+impl TimeFormat {
+    /// Validate the strftime format, eg: `%Y-%m-%dT%H:%M:%S`.
+    pub fn with_format(self, format: Option<String>) -> Result<Self> {
+        if let Some(format) = &format {
+            if StrftimeItems::new(format).any(|i| matches!(i, Item::Error)) {
+                return Err(Error::InvalidArgument(format!(
+                    "Invalid time format '{}'",
+                    format
+                )));
+            }
+        }
+
+        Ok(Self { format, ..self })
+    }
+
+    /// Render microseconds since the epoch, as seconds when `unix` is set.
+    pub fn format(&self, micros: u64) -> String {
+        let seconds = (micros / MICROS_PER_SECOND) as i64;
+        let fraction = micros % MICROS_PER_SECOND;
+
+        if self.unix {
+            let text = format!("{}.{:06}", seconds, fraction);
+
+            return text.trim_end_matches('0').trim_end_matches('.').to_string();
+        }
+
+        let format = self.format.as_deref().unwrap_or(DEFAULT_TIME_FORMAT);
+        let offset = FixedOffset::east_opt(self.timezone.offset_at(seconds))
+            .unwrap_or_else(|| FixedOffset::east(0));
+
+        match offset
+            .timestamp_opt(seconds, fraction as u32 * 1000)
+            .single()
+        {
+            Some(time) => time.format(format).to_string(),
+            None => micros.to_string(),
+        }
+    }
+}
+
+/// Text of a date given as days from the epoch, eg: `2024-05-01`.
+pub fn format_date(days: u32) -> String {
+    NaiveDate::from_ymd_opt(1970, 1, 1)
+        .and_then(|epoch| epoch.checked_add_signed(Duration::days(i64::from(days))))
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| days.to_string())
+}
+
+/// Text of a time of day given as nanoseconds from midnight, eg: `13:45:00.123`,
+/// the fraction has as many digits as needed and is left out when zero.
+pub fn format_time(nanos: i64) -> String {
+    let seconds = u32::try_from(nanos.div_euclid(NANOS_PER_SECOND)).ok();
+    let fraction = nanos.rem_euclid(NANOS_PER_SECOND) as u32;
+
+    match seconds
+        .filter(|_| nanos >= 0)
+        .and_then(|s| NaiveTime::from_num_seconds_from_midnight_opt(s, fraction))
+    {
+        Some(time) => time.format("%H:%M:%S%.f").to_string(),
+        None => nanos.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::record::Field;
+
+    // 2011-01-01 00:00:00 UTC
+    const MICROS: u64 = 1_293_840_000_000_000;
+
+    #[test]
+    fn test_time_format_default() {
+        assert_eq!(
+            TimeFormat::default().format(MICROS),
+            Field::TimestampMicros(MICROS).to_string()
+        );
+    }
+
+    #[test]
+    fn test_time_format_options() {
+        let format = |timezone: &str, format: Option<&str>, unix: bool| {
+            TimeFormat {
+                timezone: TimeZone::parse(timezone).unwrap(),
+                unix,
+                ..TimeFormat::default()
+            }
+            .with_format(format.map(String::from))
+            .unwrap()
+            .format(MICROS + 500_000)
+        };
+
+        assert_eq!(format("-03:00", None, false), "2010-12-31 21:00:00 -03:00");
+        assert_eq!(format("+0530", None, false), "2011-01-01 05:30:00 +05:30");
+        assert_eq!(
+            format("UTC", Some("%Y-%m-%dT%H:%M:%S%.3f"), false),
+            "2011-01-01T00:00:00.500"
+        );
+        assert_eq!(format("UTC", None, true), "1293840000.5");
+        assert!(!TimeFormat::default().with_format(None).unwrap().unix);
+        assert!(TimeFormat::default()
+            .with_format(Some(String::from("%Q")))
+            .is_err());
+    }
+
+    #[test]
+    fn test_time_zone_parse() {
+        assert_eq!(TimeZone::parse("utc"), Ok(TimeZone::Fixed(0)));
+        assert_eq!(TimeZone::parse("+01"), Ok(TimeZone::Fixed(3600)));
+        assert_eq!(TimeZone::parse("-02:30"), Ok(TimeZone::Fixed(-9000)));
+        assert_eq!(
+            TimeZone::parse("../etc/passwd"),
+            Err(Error::InvalidArgument(String::from(
+                "Unknown timezone '../etc/passwd'"
+            )))
+        );
+        assert!(TimeZone::parse("+25:00").is_err());
+        assert!(TimeZone::parse("Nowhere/City").is_err());
+    }
+
+    #[test]
+    fn test_time_zone_rule() {
+        let rule = parse_rule("EST5EDT,M3.2.0,M11.1.0").unwrap();
+        // 2021-03-14 06:59:59 and 07:00:00 UTC, around the start of daylight saving time
+        let start = 1_615_705_200;
+
+        assert_eq!(rule.offset_at(start - 1), -5 * 3600);
+        assert_eq!(rule.offset_at(start), -4 * 3600);
+        // 2021-11-07 05:59:59 and 06:00:00 UTC, around the end of daylight saving time
+        assert_eq!(rule.offset_at(1_636_264_799), -4 * 3600);
+        assert_eq!(rule.offset_at(1_636_264_800), -5 * 3600);
+
+        assert_eq!(
+            parse_rule("<-03>3"),
+            Some(Rule {
+                std: -3 * 3600,
+                dst: None
+            })
+        );
+        assert_eq!(
+            parse_rule("AEST-10AEDT,M10.1.0,M4.1.0/3")
+                .map(|r| (r.offset_at(1_609_459_200), r.offset_at(1_625_097_600))),
+            Some((11 * 3600, 10 * 3600))
+        );
+        assert_eq!(parse_rule("EST5EDT,J60,J300"), None);
+    }
+
+    #[test]
+    fn test_time_zone_info() {
+        // version 2 file with a single transition to UTC-03 and a footer rule
+        let mut data = Vec::new();
+        let header = |data: &mut Vec<u8>, time: u32| {
+            data.extend_from_slice(b"TZif2");
+            data.extend_from_slice(&[0; 15]);
+
+            for count in [0, 0, 0, time, 2, 8_u32] {
+                data.extend_from_slice(&count.to_be_bytes());
+            }
+        };
+
+        header(&mut data, 1);
+        data.extend_from_slice(&1000_i32.to_be_bytes());
+        data.push(1);
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0xff, 0xff, 0xd5, 0xd0, 0, 4]);
+        data.extend_from_slice(b"UTC\0-03\0");
+        header(&mut data, 1);
+        data.extend_from_slice(&1000_i64.to_be_bytes());
+        data.push(1);
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0xff, 0xff, 0xd5, 0xd0, 0, 4]);
+        data.extend_from_slice(b"UTC\0-03\0");
+        data.extend_from_slice(b"\n<-03>3\n");
+
+        let zone = Zone::parse(&data).unwrap();
+
+        assert_eq!(zone.offset_at(999), 0);
+        assert_eq!(zone.offset_at(1000), -3 * 3600);
+        assert_eq!(zone.offset_at(i64::from(i32::MAX)), -3 * 3600);
+        assert_eq!(zone.rule.as_ref().map(|r| r.std), Some(-3 * 3600));
+        assert_eq!(Zone::parse(b"TZif2"), None);
+    }
+
+    #[test]
+    fn test_time_format_date_and_time() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(19844), "2024-05-01");
+        assert_eq!(format_time(0), "00:00:00");
+        assert_eq!(format_time(49_500_123_000_000), "13:45:00.123");
+        assert_eq!(format_time(49_500_000_001_000), "13:45:00.000001");
+        assert_eq!(format_time(86_399_999_999_999), "23:59:59.999999999");
+        assert_eq!(format_time(-1), "-1");
+        assert_eq!(format_time(86_400_000_000_000), "86400000000000");
+    }
+}
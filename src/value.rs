@@ -0,0 +1,616 @@
+use crate::hash;
+use crate::output::{BinaryFormat, NestedFormat};
+use crate::time::{self, TimeFormat};
+use parquet::basic::{ConvertedType, LogicalType, TimeUnit};
+use parquet::data_type::{ByteArray, Decimal};
+use parquet::record::Field;
+use parquet::schema::types::{BasicTypeInfo, Type};
+use serde_json::Number;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Typed cell value of a row.
+///
+/// Values keep their parquet type until they are written,
+/// formatting only happens in the output layer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    // Null value
+    Null,
+
+    // Boolean value
+    Bool(bool),
+
+    // Signed integer value
+    Int(i64),
+
+    // Unsigned integer value
+    UInt(u64),
+
+    // 32-bit floating point value
+    Float(f32),
+
+    // 64-bit floating point value
+    Double(f64),
+
+    // UTF-8 string value
+    Str(String),
+
+    // Binary value
+    Bytes(Vec<u8>),
+
+    // Days from the unix epoch
+    Date(u32),
+
+    // Microseconds from the unix epoch
+    Timestamp(u64),
+
+    // Nanoseconds from midnight
+    Time(i64),
+
+    // Canonical UUID text
+    Uuid(String),
+
+    // Formatted decimal value
+    Decimal(String),
+
+    // Rendered group, list or map value
+    Nested(String),
+}
+
+impl Value {
+    /// Convert a parquet field, rendering nested values using the given format.
+    pub fn from_field(field: &Field, nested: NestedFormat) -> Self {
+        match field {
+            Field::Null => Value::Null,
+            Field::Bool(v) => Value::Bool(*v),
+            Field::Byte(v) => Value::Int(i64::from(*v)),
+            Field::Short(v) => Value::Int(i64::from(*v)),
+            Field::Int(v) => Value::Int(i64::from(*v)),
+            Field::Long(v) => Value::Int(*v),
+            Field::UByte(v) => Value::UInt(u64::from(*v)),
+            Field::UShort(v) => Value::UInt(u64::from(*v)),
+            Field::UInt(v) => Value::UInt(u64::from(*v)),
+            Field::ULong(v) => Value::UInt(*v),
+            Field::Float(v) => Value::Float(*v),
+            Field::Double(v) => Value::Double(*v),
+            Field::Str(v) => Value::Str(v.to_string()),
+            Field::Bytes(v) => Value::Bytes(v.data().to_vec()),
+            Field::Date(v) => Value::Date(*v),
+            Field::TimestampMillis(v) => Value::Timestamp(v.saturating_mul(1000)),
+            Field::TimestampMicros(v) => Value::Timestamp(*v),
+            Field::Decimal(v) => Value::Decimal(format_decimal(v)),
+            _ => match nested {
+                NestedFormat::Json => Value::Nested(field.to_json_value().to_string()),
+                NestedFormat::Default => Value::Nested(format!("{}", field)),
+            },
+        }
+    }
+
+    /// Convert a parquet field using the logical type of its column,
+    /// parquet reads `TIME` values as integers and `UUID` values as bytes.
+    pub fn from_column_field(
+        field: &Field,
+        column: Option<&Type>,
+        nested: NestedFormat,
+    ) -> Self {
+        let info = column
+            .filter(|c| c.is_primitive())
+            .map(Type::get_basic_info);
+        let unit = info.and_then(time_unit_nanos);
+        let uuid = matches!(
+            info.and_then(BasicTypeInfo::logical_type),
+            Some(LogicalType::Uuid)
+        );
+
+        match (field, unit) {
+            (Field::Int(v), Some(unit)) => {
+                Value::Time(i64::from(*v).saturating_mul(unit))
+            }
+            (Field::Long(v), Some(unit)) => Value::Time(v.saturating_mul(unit)),
+            (Field::Bytes(v), _) if uuid && v.len() == 16 => {
+                Value::Uuid(format_uuid(v.data()))
+            }
+            _ => Self::from_field(field, nested),
+        }
+    }
+
+    /// Numeric value, if any.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Int(v) => Some(v as f64),
+            Value::UInt(v) => Some(v as f64),
+            Value::Float(v) => Some(f64::from(v)),
+            Value::Double(v) => Some(v),
+            Value::Decimal(ref v) => v.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Whether the value is null or the zero value of its type,
+    /// eg: `0`, `false`, an empty string or an empty list.
+    pub fn is_default(&self) -> bool {
+        match self {
+            Value::Null => true,
+            Value::Bool(v) => !v,
+            Value::Int(v) => *v == 0,
+            Value::UInt(v) => *v == 0,
+            Value::Float(v) => *v == 0.0,
+            Value::Double(v) => *v == 0.0,
+            Value::Str(v) => v.is_empty(),
+            Value::Bytes(v) => v.is_empty(),
+            Value::Decimal(v) => v.parse::<f64>().is_ok_and(|d| d == 0.0),
+            Value::Nested(v) => matches!(v.as_str(), "[]" | "{}"),
+            Value::Date(_) | Value::Timestamp(_) | Value::Time(_) | Value::Uuid(_) => {
+                false
+            }
+        }
+    }
+
+    /// Order of two values of a column, numbers are compared across types,
+    /// nulls sort after every other value and other mixed types by their text.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => Ordering::Greater,
+            (_, Value::Null) => Ordering::Less,
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::UInt(a), Value::UInt(b)) => a.cmp(b),
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.cmp(b),
+            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
+            (Value::Time(a), Value::Time(b)) => a.cmp(b),
+            (Value::Uuid(a), Value::Uuid(b)) => a.cmp(b),
+            (Value::Decimal(a), Value::Decimal(b)) => {
+                match (a.parse::<f64>(), b.parse::<f64>()) {
+                    (Ok(a), Ok(b)) => a.total_cmp(&b),
+                    _ => a.cmp(b),
+                }
+            }
+            (a, b) => match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                _ => a.to_string().cmp(&b.to_string()),
+            },
+        }
+    }
+
+    /// Typed json representation of the value, binary values and timestamps
+    /// are rendered by the format.
+    pub fn to_json_value(&self, format: &ValueFormat) -> serde_json::Value {
+        match self {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(v) => serde_json::Value::Bool(*v),
+            Value::Int(v) => serde_json::Value::Number(Number::from(*v)),
+            Value::UInt(v) => serde_json::Value::Number(Number::from(*v)),
+            Value::Float(v) => Number::from_f64(f64::from(*v))
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Value::Double(v) => Number::from_f64(*v)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Value::Str(v) => serde_json::Value::String(v.to_string()),
+            Value::Bytes(v) => match format.binary(v) {
+                Some(text) => serde_json::Value::String(text),
+                None => Field::Bytes(ByteArray::from(v.to_vec())).to_json_value(),
+            },
+            Value::Timestamp(v) => {
+                let text = format.time.format(*v);
+
+                match text.parse::<Number>() {
+                    Ok(seconds) if format.time.unix => serde_json::Value::Number(seconds),
+                    _ => serde_json::Value::String(text),
+                }
+            }
+            Value::Date(_) | Value::Time(_) | Value::Decimal(_) => {
+                serde_json::Value::String(self.to_string())
+            }
+            Value::Uuid(v) => serde_json::Value::String(v.to_string()),
+            Value::Nested(v) => serde_json::Value::String(v.to_string()),
+        }
+    }
+}
+
+/// Text of a decimal using the scale of the schema, eg: `12345` with a scale of 2 is `123.45`.
+///
+/// Unlike parquet formatting, decimals whose precision is the same as the scale and
+/// decimals wider than 128 bits are supported, and a scale of 0 has no decimal point.
+pub fn format_decimal(decimal: &Decimal) -> String {
+    let data = decimal.data();
+    let negative = data.first().map(|b| b & 0x80 != 0).unwrap_or(false);
+    let mut magnitude = data.to_vec();
+
+    // two's complement of negative values
+    if negative {
+        let mut carry = true;
+
+        for byte in magnitude.iter_mut().rev() {
+            let (value, overflow) = (!*byte).overflowing_add(u8::from(carry));
+
+            *byte = value;
+            carry = carry && overflow;
+        }
+    }
+
+    let mut digits = Vec::new();
+
+    while magnitude.iter().any(|b| *b != 0) {
+        let mut remainder = 0_u32;
+
+        for byte in magnitude.iter_mut() {
+            let current = (remainder << 8) | u32::from(*byte);
+
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+
+        digits.push(b'0' + remainder as u8);
+    }
+
+    let scale = decimal.scale().max(0) as usize;
+
+    // at least one digit before the decimal point
+    while digits.len() <= scale {
+        digits.push(b'0');
+    }
+
+    digits.reverse();
+
+    let mut text = String::from_utf8(digits).unwrap_or_default();
+
+    if scale > 0 {
+        text.insert(text.len() - scale, '.');
+    }
+
+    if negative {
+        text.insert(0, '-');
+    }
+
+    text
+}
+
+/// Nanoseconds of a unit of the `TIME` values of a column.
+#[inline]
+fn time_unit_nanos(info: &BasicTypeInfo) -> Option<i64> {
+    match (info.logical_type(), info.converted_type()) {
+        (Some(LogicalType::Time { unit, .. }), _) => match unit {
+            TimeUnit::MILLIS(_) => Some(1_000_000),
+            TimeUnit::MICROS(_) => Some(1_000),
+            TimeUnit::NANOS(_) => Some(1),
+        },
+        (None, ConvertedType::TIME_MILLIS) => Some(1_000_000),
+        (None, ConvertedType::TIME_MICROS) => Some(1_000),
+        _ => None,
+    }
+}
+
+/// Canonical text of a UUID, eg: `f81d4fae-7dec-11d0-a765-00a0c91e6bf6`.
+pub fn format_uuid(bytes: &[u8]) -> String {
+    let hex = hash::to_hex(bytes);
+
+    [
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..],
+    ]
+    .join("-")
+}
+
+/// Text of a field as formatted by parquet, except for decimals and dates,
+/// see `format_decimal` and `time::format_date`.
+pub fn format_field(field: &Field) -> String {
+    match field {
+        Field::Decimal(v) => format_decimal(v),
+        Field::Date(v) => time::format_date(*v),
+        _ => field.to_string(),
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats values the same way parquet formats its fields.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "{}", Field::Null),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::UInt(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", Field::Float(*v)),
+            Value::Double(v) => write!(f, "{}", Field::Double(*v)),
+            Value::Str(v) => write!(f, "\"{}\"", v),
+            Value::Bytes(v) => write!(f, "{:?}", v),
+            Value::Date(v) => write!(f, "{}", time::format_date(*v)),
+            Value::Timestamp(v) => write!(f, "{}", Field::TimestampMicros(*v)),
+            Value::Time(v) => write!(f, "{}", time::format_time(*v)),
+            Value::Decimal(v) | Value::Uuid(v) | Value::Nested(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// How values are written by the outputs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValueFormat {
+    // Text written for nulls, `null` when not given
+    pub null_display: Option<String>,
+
+    // Rendering of binary values
+    pub binary: BinaryFormat,
+
+    // Rendering of timestamps
+    pub time: TimeFormat,
+}
+
+impl ValueFormat {
+    /// Text written for nulls.
+    pub fn null_display(&self) -> &str {
+        self.null_display.as_deref().unwrap_or("null")
+    }
+
+    /// Text of a binary value, `None` for the parquet display format.
+    fn binary(&self, bytes: &[u8]) -> Option<String> {
+        match self.binary {
+            BinaryFormat::Default => None,
+            BinaryFormat::Hex => Some(hash::to_hex(bytes)),
+            BinaryFormat::Base64 => Some(to_base64(bytes)),
+            BinaryFormat::Size => Some(format!("<{} bytes>", bytes.len())),
+        }
+    }
+
+    /// Text of the value as written by outputs.
+    pub fn format(&self, value: Value) -> String {
+        match (&value, &self.null_display) {
+            (Value::Null, Some(text)) => text.to_string(),
+            (Value::Timestamp(v), _) => self.time.format(*v),
+            (Value::Bytes(v), _) => self.binary(v).unwrap_or_else(|| value.to_string()),
+            _ => value.to_string(),
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 encoding of the bytes, with padding.
+fn to_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let block = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |block, (i, b)| block | u32::from(*b) << (16 - 8 * i));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (block >> (18 - 6 * i)) & 0x3f;
+
+                text.push(char::from(BASE64_ALPHABET[index as usize]));
+            } else {
+                text.push('=');
+            }
+        }
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::schema::parser::parse_message_type;
+
+    #[test]
+    fn test_value_from_field() {
+        let value = |f| Value::from_field(&f, NestedFormat::Default);
+
+        assert_eq!(value(Field::Null), Value::Null);
+        assert_eq!(value(Field::Short(-1)), Value::Int(-1));
+        assert_eq!(value(Field::UShort(1)), Value::UInt(1));
+        assert_eq!(
+            value(Field::Str(String::from("a"))),
+            Value::Str(String::from("a"))
+        );
+        assert_eq!(value(Field::TimestampMillis(2)), Value::Timestamp(2000));
+        assert_eq!(
+            value(Field::Bytes(ByteArray::from(vec![1, 2]))),
+            Value::Bytes(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn test_value_format_decimal() {
+        let bytes = |v: &[u8], precision, scale| {
+            format_decimal(&Decimal::from_bytes(
+                ByteArray::from(v.to_vec()),
+                precision,
+                scale,
+            ))
+        };
+
+        assert_eq!(format_decimal(&Decimal::from_i32(12345, 5, 2)), "123.45");
+        assert_eq!(format_decimal(&Decimal::from_i32(-5, 5, 2)), "-0.05");
+        assert_eq!(format_decimal(&Decimal::from_i32(0, 5, 2)), "0.00");
+        assert_eq!(format_decimal(&Decimal::from_i32(42, 2, 2)), "0.42");
+        assert_eq!(
+            format_decimal(&Decimal::from_i64(-1234567, 12, 0)),
+            "-1234567"
+        );
+        assert_eq!(bytes(&[0x00, 0x01, 0xe2, 0x40], 9, 4), "12.3456");
+        assert_eq!(bytes(&[0xff, 0xff], 4, 1), "-0.1");
+        assert_eq!(
+            bytes(&[0x80, 0, 0, 0, 0, 0, 0, 0], 19, 0),
+            i64::MIN.to_string()
+        );
+        assert_eq!(
+            bytes(&[0x01; 20], 48, 10),
+            "573137896992510948315170522633836478.2964441345"
+        );
+        assert_eq!(bytes(&[], 1, 0), "0");
+        assert_eq!(
+            Value::from_field(
+                &Field::Decimal(Decimal::from_i32(12345, 5, 2)),
+                NestedFormat::Default
+            )
+            .as_f64(),
+            Some(123.45)
+        );
+    }
+
+    #[test]
+    fn test_value_total_cmp() {
+        let mut values = vec![
+            Value::Null,
+            Value::Double(1.5),
+            Value::Int(2),
+            Value::UInt(1),
+            Value::Int(-3),
+        ];
+
+        values.sort_by(Value::total_cmp);
+
+        assert_eq!(
+            values,
+            vec![
+                Value::Int(-3),
+                Value::UInt(1),
+                Value::Double(1.5),
+                Value::Int(2),
+                Value::Null,
+            ]
+        );
+        assert_eq!(
+            Value::Str(String::from("b")).total_cmp(&Value::Str(String::from("a"))),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Value::Decimal(String::from("10.5"))
+                .total_cmp(&Value::Decimal(String::from("9"))),
+            Ordering::Greater
+        );
+        assert_eq!(Value::Date(2).total_cmp(&Value::Date(10)), Ordering::Less);
+    }
+
+    #[test]
+    fn test_value_is_default() {
+        let defaults = [
+            Value::Null,
+            Value::Bool(false),
+            Value::Int(0),
+            Value::Double(0.0),
+            Value::Str(String::new()),
+            Value::Decimal(String::from("0.00")),
+            Value::Nested(String::from("[]")),
+        ];
+
+        assert!(defaults.iter().all(Value::is_default));
+        assert!(!Value::Int(1).is_default());
+        assert!(!Value::Str(String::from(" ")).is_default());
+        assert!(!Value::Date(0).is_default());
+    }
+
+    #[test]
+    fn test_value_display() {
+        let fields = vec![
+            Field::Null,
+            Field::Bool(true),
+            Field::Long(-11),
+            Field::ULong(11),
+            Field::Float(111.3),
+            Field::Double(1111.0),
+            Field::Double(1e20),
+            Field::Str(String::from("odd")),
+            Field::Bytes(ByteArray::from(vec![1, 2])),
+            Field::TimestampMillis(1_293_840_000_000),
+            Field::TimestampMicros(1_293_840_000_000_000),
+        ];
+
+        for field in fields {
+            let value = Value::from_field(&field, NestedFormat::Default);
+
+            assert_eq!(value.to_string(), field.to_string());
+        }
+
+        assert_eq!(Value::Date(14975).to_string(), "2011-01-01");
+        assert_eq!(Value::Time(1_000_000).to_string(), "00:00:00.001");
+    }
+
+    #[test]
+    fn test_value_from_column_field() {
+        let column = |message: &str| {
+            let schema = parse_message_type(message).unwrap();
+
+            schema.get_fields()[0].clone()
+        };
+        let millis = column("message m { REQUIRED INT32 a (TIME(MILLIS,true)); }");
+        let uuid = column("message m { REQUIRED FIXED_LEN_BYTE_ARRAY (16) a (UUID); }");
+        let value = |field, column: &Type| {
+            Value::from_column_field(&field, Some(column), NestedFormat::Default)
+        };
+        let bytes = (0..16_u8).collect::<Vec<_>>();
+
+        assert_eq!(value(Field::Int(1), &millis), Value::Time(1_000_000));
+        assert_eq!(
+            value(Field::Bytes(ByteArray::from(bytes)), &uuid),
+            Value::Uuid(String::from("00010203-0405-0607-0809-0a0b0c0d0e0f"))
+        );
+        assert_eq!(
+            value(Field::Bytes(ByteArray::from(vec![1])), &uuid),
+            Value::Bytes(vec![1])
+        );
+        assert_eq!(value(Field::Int(1), &uuid), Value::Int(1));
+    }
+
+    #[test]
+    fn test_value_format() {
+        let format = ValueFormat {
+            null_display: Some(String::from("<null>")),
+            binary: BinaryFormat::Size,
+            time: TimeFormat {
+                unix: true,
+                ..TimeFormat::default()
+            },
+        };
+
+        assert_eq!(ValueFormat::default().format(Value::Null), "null");
+        assert_eq!(format.format(Value::Null), "<null>");
+        assert_eq!(format.format(Value::Int(1)), "1");
+        assert_eq!(format.format(Value::Bytes(vec![1, 2])), "<2 bytes>");
+        assert_eq!(format.format(Value::Timestamp(1_500_000)), "1.5");
+        assert_eq!(
+            Value::Timestamp(2_000_000).to_json_value(&format),
+            serde_json::json!(2)
+        );
+        assert_eq!(format.null_display(), "<null>");
+    }
+
+    #[test]
+    fn test_value_to_base64() {
+        assert_eq!(to_base64(b""), "");
+        assert_eq!(to_base64(b"f"), "Zg==");
+        assert_eq!(to_base64(b"fo"), "Zm8=");
+        assert_eq!(to_base64(b"foo"), "Zm9v");
+        assert_eq!(to_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(to_base64(&[0xff, 0xfe, 0x00, 0x80]), "//4AgA==");
+    }
+
+    #[test]
+    fn test_value_typed() {
+        assert_eq!(Value::Int(2).as_f64(), Some(2.0));
+        assert_eq!(Value::Float(1.5).as_f64(), Some(1.5));
+        assert_eq!(Value::Str(String::from("2")).as_f64(), None);
+        assert!(Value::Null.is_null());
+
+        let json = |value: Value| value.to_json_value(&ValueFormat::default());
+
+        assert_eq!(json(Value::Int(2)), serde_json::json!(2));
+        assert_eq!(json(Value::Bool(true)), serde_json::json!(true));
+        assert_eq!(json(Value::Null), serde_json::Value::Null);
+        assert_eq!(json(Value::Str(String::from("a"))), serde_json::json!("a"));
+    }
+}